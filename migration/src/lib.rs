@@ -11,6 +11,54 @@ mod m20240101_000008_create_playlist_tracks_table;
 mod m20240101_000009_add_album_source_column;
 mod m20240101_000010_add_playlist_is_synthetic;
 mod m20240101_000011_add_playlist_owned_count;
+mod m20240101_000012_add_user_settings_album_view;
+mod m20240101_000013_add_job_sync_cursor;
+mod m20240101_000014_add_album_barcode;
+mod m20240101_000015_create_profiles_tables;
+mod m20240101_000016_add_purge_disabled_playlist_tracks;
+mod m20240101_000017_add_slugs;
+mod m20240101_000018_add_album_estimated_value;
+mod m20240101_000019_add_user_settings_log_level;
+mod m20240101_000020_create_top_items_table;
+mod m20240101_000021_create_lidarr_webhook_events_table;
+mod m20240101_000022_add_playlist_unavailable_tracks;
+mod m20240101_000023_add_match_source_and_job_result;
+mod m20240101_000024_add_user_settings_sync_collaborative;
+mod m20240101_000025_create_ownership_events_table;
+mod m20240101_000026_add_user_settings_exclude_singles;
+mod m20240101_000027_add_user_settings_timezone;
+mod m20240101_000028_add_user_settings_profile_id;
+mod m20240101_000029_add_artist_normalized_name;
+mod m20240101_000030_add_album_listen_history;
+mod m20240101_000031_add_download_auto_retry;
+mod m20240101_000032_add_lidarr_extra_headers;
+mod m20240101_000033_add_stats_exclude_playlist_imports;
+mod m20240101_000034_add_playlist_is_external;
+mod m20240101_000035_add_artist_name_locked;
+mod m20240101_000036_add_playlist_sync_failures;
+mod m20240101_000037_add_path_mappings;
+mod m20240101_000038_add_tracks_title_index;
+mod m20240101_000039_add_archived_at;
+mod m20240101_000040_add_lidarr_artist_override;
+mod m20240101_000041_add_auto_fetch_cover_art;
+mod m20240101_000042_create_pending_side_effects_table;
+mod m20240101_000043_add_playlist_episode_count;
+mod m20240101_000044_create_goals_table;
+mod m20240101_000045_add_job_progress_detail;
+mod m20240101_000046_add_album_genre_source;
+mod m20240101_000047_add_user_settings_locale;
+mod m20240101_000048_add_track_duration_check;
+mod m20240101_000049_add_artist_images;
+mod m20240101_000050_add_album_wanted_since;
+mod m20240101_000051_add_user_settings_single_shared_row;
+mod m20240101_000052_add_wanted_demotion;
+mod m20240101_000053_create_activity_log_table;
+mod m20240101_000054_add_playlist_track_is_playable;
+mod m20240101_000055_add_album_notes;
+mod m20240101_000056_add_artists_normalized_name_trgm_index;
+mod m20240101_000057_add_archive_retention;
+mod m20240101_000058_add_profiles_top_items_fetched_at;
+mod m20240101_000059_add_artists_spotify_name;
 
 pub struct Migrator;
 
@@ -29,6 +77,54 @@ impl MigratorTrait for Migrator {
             Box::new(m20240101_000009_add_album_source_column::Migration),
             Box::new(m20240101_000010_add_playlist_is_synthetic::Migration),
             Box::new(m20240101_000011_add_playlist_owned_count::Migration),
+            Box::new(m20240101_000012_add_user_settings_album_view::Migration),
+            Box::new(m20240101_000013_add_job_sync_cursor::Migration),
+            Box::new(m20240101_000014_add_album_barcode::Migration),
+            Box::new(m20240101_000015_create_profiles_tables::Migration),
+            Box::new(m20240101_000016_add_purge_disabled_playlist_tracks::Migration),
+            Box::new(m20240101_000017_add_slugs::Migration),
+            Box::new(m20240101_000018_add_album_estimated_value::Migration),
+            Box::new(m20240101_000019_add_user_settings_log_level::Migration),
+            Box::new(m20240101_000020_create_top_items_table::Migration),
+            Box::new(m20240101_000021_create_lidarr_webhook_events_table::Migration),
+            Box::new(m20240101_000022_add_playlist_unavailable_tracks::Migration),
+            Box::new(m20240101_000023_add_match_source_and_job_result::Migration),
+            Box::new(m20240101_000024_add_user_settings_sync_collaborative::Migration),
+            Box::new(m20240101_000025_create_ownership_events_table::Migration),
+            Box::new(m20240101_000026_add_user_settings_exclude_singles::Migration),
+            Box::new(m20240101_000027_add_user_settings_timezone::Migration),
+            Box::new(m20240101_000028_add_user_settings_profile_id::Migration),
+            Box::new(m20240101_000029_add_artist_normalized_name::Migration),
+            Box::new(m20240101_000030_add_album_listen_history::Migration),
+            Box::new(m20240101_000031_add_download_auto_retry::Migration),
+            Box::new(m20240101_000032_add_lidarr_extra_headers::Migration),
+            Box::new(m20240101_000033_add_stats_exclude_playlist_imports::Migration),
+            Box::new(m20240101_000034_add_playlist_is_external::Migration),
+            Box::new(m20240101_000035_add_artist_name_locked::Migration),
+            Box::new(m20240101_000036_add_playlist_sync_failures::Migration),
+            Box::new(m20240101_000037_add_path_mappings::Migration),
+            Box::new(m20240101_000038_add_tracks_title_index::Migration),
+            Box::new(m20240101_000039_add_archived_at::Migration),
+            Box::new(m20240101_000040_add_lidarr_artist_override::Migration),
+            Box::new(m20240101_000041_add_auto_fetch_cover_art::Migration),
+            Box::new(m20240101_000042_create_pending_side_effects_table::Migration),
+            Box::new(m20240101_000043_add_playlist_episode_count::Migration),
+            Box::new(m20240101_000044_create_goals_table::Migration),
+            Box::new(m20240101_000045_add_job_progress_detail::Migration),
+            Box::new(m20240101_000046_add_album_genre_source::Migration),
+            Box::new(m20240101_000047_add_user_settings_locale::Migration),
+            Box::new(m20240101_000048_add_track_duration_check::Migration),
+            Box::new(m20240101_000049_add_artist_images::Migration),
+            Box::new(m20240101_000050_add_album_wanted_since::Migration),
+            Box::new(m20240101_000051_add_user_settings_single_shared_row::Migration),
+            Box::new(m20240101_000052_add_wanted_demotion::Migration),
+            Box::new(m20240101_000053_create_activity_log_table::Migration),
+            Box::new(m20240101_000054_add_playlist_track_is_playable::Migration),
+            Box::new(m20240101_000055_add_album_notes::Migration),
+            Box::new(m20240101_000056_add_artists_normalized_name_trgm_index::Migration),
+            Box::new(m20240101_000057_add_archive_retention::Migration),
+            Box::new(m20240101_000058_add_profiles_top_items_fetched_at::Migration),
+            Box::new(m20240101_000059_add_artists_spotify_name::Migration),
         ]
     }
 }
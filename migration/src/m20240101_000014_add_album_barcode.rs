@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(ColumnDef::new(AlbumsAdditions::Barcode).string_len(32).null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .add_column(
+                        ColumnDef::new(UserSettingsAdditions::MatchByBarcode)
+                            .boolean()
+                            .null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .drop_column(UserSettingsAdditions::MatchByBarcode)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(AlbumsAdditions::Barcode)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlbumsAdditions {
+    Barcode,
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    MatchByBarcode,
+}
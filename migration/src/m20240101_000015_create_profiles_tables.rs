@@ -0,0 +1,231 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+use super::m20240101_000007_create_playlists_table::Playlists;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Profiles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Profiles::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Profiles::Name)
+                            .string_len(100)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Profiles::SpotifyAccessToken).text())
+                    .col(ColumnDef::new(Profiles::SpotifyRefreshToken).text())
+                    .col(ColumnDef::new(Profiles::SpotifyTokenExpiresAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Profiles::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Profiles::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProfileAlbums::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProfileAlbums::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ProfileAlbums::ProfileId).integer().not_null())
+                    .col(ColumnDef::new(ProfileAlbums::AlbumId).integer().not_null())
+                    .col(
+                        ColumnDef::new(ProfileAlbums::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_profile_albums_profile_id")
+                            .from(ProfileAlbums::Table, ProfileAlbums::ProfileId)
+                            .to(Profiles::Table, Profiles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_profile_albums_album_id")
+                            .from(ProfileAlbums::Table, ProfileAlbums::AlbumId)
+                            .to(Albums::Table, Albums::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_profile_albums_unique")
+                    .table(ProfileAlbums::Table)
+                    .col(ProfileAlbums::ProfileId)
+                    .col(ProfileAlbums::AlbumId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProfilePlaylists::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProfilePlaylists::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ProfilePlaylists::ProfileId).integer().not_null())
+                    .col(ColumnDef::new(ProfilePlaylists::PlaylistId).integer().not_null())
+                    .col(
+                        ColumnDef::new(ProfilePlaylists::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_profile_playlists_profile_id")
+                            .from(ProfilePlaylists::Table, ProfilePlaylists::ProfileId)
+                            .to(Profiles::Table, Profiles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_profile_playlists_playlist_id")
+                            .from(ProfilePlaylists::Table, ProfilePlaylists::PlaylistId)
+                            .to(Playlists::Table, Playlists::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_profile_playlists_unique")
+                    .table(ProfilePlaylists::Table)
+                    .col(ProfilePlaylists::ProfileId)
+                    .col(ProfilePlaylists::PlaylistId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Everything synced so far belongs to a default profile, which also
+        // takes over the Spotify connection previously stored in user_settings
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "INSERT INTO profiles (name, created_at, updated_at) \
+             VALUES ('Default', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE profiles SET \
+             spotify_access_token = (SELECT spotify_access_token FROM user_settings ORDER BY id LIMIT 1), \
+             spotify_refresh_token = (SELECT spotify_refresh_token FROM user_settings ORDER BY id LIMIT 1), \
+             spotify_token_expires_at = (SELECT spotify_token_expires_at FROM user_settings ORDER BY id LIMIT 1) \
+             WHERE name = 'Default'",
+        )
+        .await?;
+        db.execute_unprepared(
+            "INSERT INTO profile_albums (profile_id, album_id, created_at) \
+             SELECT p.id, a.id, a.created_at FROM albums a, profiles p WHERE p.name = 'Default'",
+        )
+        .await?;
+        db.execute_unprepared(
+            "INSERT INTO profile_playlists (profile_id, playlist_id, created_at) \
+             SELECT p.id, pl.id, pl.created_at FROM playlists pl, profiles p WHERE p.name = 'Default'",
+        )
+        .await?;
+
+        // Liked Songs is per-account, so its synthetic Spotify ID becomes per-profile
+        db.execute_unprepared(
+            "UPDATE playlists SET spotify_id = '__LIKED_SONGS__:' || (SELECT id FROM profiles WHERE name = 'Default') \
+             WHERE spotify_id = '__LIKED_SONGS__'",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE playlists SET spotify_id = '__LIKED_SONGS__' \
+                 WHERE spotify_id = '__LIKED_SONGS__:' || (SELECT id FROM profiles WHERE name = 'Default')",
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ProfilePlaylists::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ProfileAlbums::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Profiles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Profiles {
+    Table,
+    Id,
+    Name,
+    SpotifyAccessToken,
+    SpotifyRefreshToken,
+    SpotifyTokenExpiresAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum ProfileAlbums {
+    Table,
+    Id,
+    ProfileId,
+    AlbumId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProfilePlaylists {
+    Table,
+    Id,
+    ProfileId,
+    PlaylistId,
+    CreatedAt,
+}
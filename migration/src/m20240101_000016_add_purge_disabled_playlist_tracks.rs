@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .add_column(
+                        ColumnDef::new(UserSettingsAdditions::PurgeDisabledPlaylistTracks)
+                            .boolean()
+                            .null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .drop_column(UserSettingsAdditions::PurgeDisabledPlaylistTracks)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    PurgeDisabledPlaylistTracks,
+}
@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000001_create_artists_table::Artists;
+use super::m20240101_000002_create_albums_table::Albums;
+
+/// Adds URL slugs to artists and albums
+///
+/// Existing rows are backfilled on startup (`db::slug::backfill_slugs`), since
+/// slug generation lives in the application crate.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .add_column(ColumnDef::new(Slug::Slug).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(ColumnDef::new(Slug::Slug).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_artists_slug")
+                    .table(Artists::Table)
+                    .col(Slug::Slug)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_albums_slug")
+                    .table(Albums::Table)
+                    .col(Slug::Slug)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_albums_slug").table(Albums::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_index(Index::drop().name("idx_artists_slug").table(Artists::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(Slug::Slug)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .drop_column(Slug::Slug)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Slug {
+    Slug,
+}
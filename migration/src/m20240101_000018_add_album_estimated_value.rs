@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(ColumnDef::new(AlbumsAdditions::EstimatedValue).double().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(AlbumsAdditions::EstimatedValue)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlbumsAdditions {
+    EstimatedValue,
+}
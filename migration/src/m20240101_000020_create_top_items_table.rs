@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000015_create_profiles_tables::Profiles;
+
+/// Cache of each profile's Spotify top artists and tracks
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TopItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TopItems::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TopItems::ProfileId).integer().not_null())
+                    .col(ColumnDef::new(TopItems::Kind).string_len(16).not_null())
+                    .col(ColumnDef::new(TopItems::Rank).integer().not_null())
+                    .col(ColumnDef::new(TopItems::SpotifyId).string().not_null())
+                    .col(ColumnDef::new(TopItems::Name).string().not_null())
+                    .col(ColumnDef::new(TopItems::ArtistName).string())
+                    .col(ColumnDef::new(TopItems::AlbumSpotifyId).string())
+                    .col(ColumnDef::new(TopItems::AlbumName).string())
+                    .col(ColumnDef::new(TopItems::ImageUrl).text())
+                    .col(
+                        ColumnDef::new(TopItems::FetchedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_top_items_profile_id")
+                            .from(TopItems::Table, TopItems::ProfileId)
+                            .to(Profiles::Table, Profiles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_top_items_profile_kind")
+                    .table(TopItems::Table)
+                    .col(TopItems::ProfileId)
+                    .col(TopItems::Kind)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TopItems::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TopItems {
+    Table,
+    Id,
+    ProfileId,
+    Kind,
+    Rank,
+    SpotifyId,
+    Name,
+    ArtistName,
+    AlbumSpotifyId,
+    AlbumName,
+    ImageUrl,
+    FetchedAt,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+/// Lidarr webhook payloads that have already been applied, so retried
+/// deliveries can be acknowledged without repeating their side effects
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LidarrWebhookEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LidarrWebhookEvents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(LidarrWebhookEvents::PayloadHash)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(LidarrWebhookEvents::EventType)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(LidarrWebhookEvents::ReceivedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LidarrWebhookEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LidarrWebhookEvents {
+    Table,
+    Id,
+    PayloadHash,
+    EventType,
+    ReceivedAt,
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+use super::m20240101_000005_create_jobs_table::Jobs;
+
+/// Who applied an album's match (`auto` or `manual`), and a JSON summary
+/// jobs can leave behind when they finish
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(ColumnDef::new(AlbumsAdditions::MatchSource).string_len(16).null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Jobs::Table)
+                    .add_column(ColumnDef::new(JobsAdditions::Result).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Jobs::Table)
+                    .drop_column(JobsAdditions::Result)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(AlbumsAdditions::MatchSource)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlbumsAdditions {
+    MatchSource,
+}
+
+#[derive(DeriveIden)]
+enum JobsAdditions {
+    Result,
+}
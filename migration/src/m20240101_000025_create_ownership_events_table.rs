@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+
+/// Audit trail of album ownership changes and the reason each was made
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OwnershipEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OwnershipEvents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OwnershipEvents::AlbumId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OwnershipEvents::FromStatus)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OwnershipEvents::ToStatus)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OwnershipEvents::Reason)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OwnershipEvents::Forced)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(OwnershipEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_ownership_events_album_id")
+                            .from(OwnershipEvents::Table, OwnershipEvents::AlbumId)
+                            .to(Albums::Table, Albums::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_ownership_events_album_id")
+                    .table(OwnershipEvents::Table)
+                    .col(OwnershipEvents::AlbumId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OwnershipEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OwnershipEvents {
+    Table,
+    Id,
+    AlbumId,
+    FromStatus,
+    ToStatus,
+    Reason,
+    Forced,
+    CreatedAt,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000001_create_artists_table::Artists;
+
+/// Adds a normalized artist name for bounded fuzzy lookups
+///
+/// Existing rows are backfilled on startup
+/// (`db::artist_names::backfill_normalized_names`), since normalization lives
+/// in the application crate.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .add_column(ColumnDef::new(ArtistsAdditions::NormalizedName).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_artists_normalized_name")
+                    .table(Artists::Table)
+                    .col(ArtistsAdditions::NormalizedName)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_artists_normalized_name")
+                    .table(Artists::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .drop_column(ArtistsAdditions::NormalizedName)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ArtistsAdditions {
+    NormalizedName,
+}
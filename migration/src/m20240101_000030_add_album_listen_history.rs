@@ -0,0 +1,129 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+
+/// Listening history per album, pulled from ListenBrainz
+///
+/// `user_settings` gains the ListenBrainz user to follow and the time of the
+/// newest listen already counted, so each sync only fetches newer listens.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite takes one column per ALTER TABLE
+        for mut column in [
+            ColumnDef::new(AlbumsAdditions::FirstListenedAt)
+                .timestamp_with_time_zone()
+                .null()
+                .to_owned(),
+            ColumnDef::new(AlbumsAdditions::LastListenedAt)
+                .timestamp_with_time_zone()
+                .null()
+                .to_owned(),
+            ColumnDef::new(AlbumsAdditions::ListenCount)
+                .integer()
+                .not_null()
+                .default(0)
+                .to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Albums::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_albums_last_listened_at")
+                    .table(Albums::Table)
+                    .col(AlbumsAdditions::LastListenedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        for mut column in [
+            ColumnDef::new(UserSettingsAdditions::ListenbrainzUsername)
+                .string_len(100)
+                .null()
+                .to_owned(),
+            ColumnDef::new(UserSettingsAdditions::ListensSyncedUntil)
+                .timestamp_with_time_zone()
+                .null()
+                .to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserSettings::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            UserSettingsAdditions::ListensSyncedUntil,
+            UserSettingsAdditions::ListenbrainzUsername,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserSettings::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_albums_last_listened_at")
+                    .table(Albums::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        for column in [
+            AlbumsAdditions::ListenCount,
+            AlbumsAdditions::LastListenedAt,
+            AlbumsAdditions::FirstListenedAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Albums::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlbumsAdditions {
+    FirstListenedAt,
+    LastListenedAt,
+    ListenCount,
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    ListenbrainzUsername,
+    ListensSyncedUntil,
+}
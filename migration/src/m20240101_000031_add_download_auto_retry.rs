@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+use super::m20240101_000006_create_lidarr_downloads_table::LidarrDownloads;
+
+/// Automatic retry of failed Lidarr downloads
+///
+/// Each download counts how often it was retried, so an album that keeps
+/// failing stops once it reaches `max_download_retries`.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LidarrDownloads::Table)
+                    .add_column(
+                        ColumnDef::new(LidarrDownloadsAdditions::RetryCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite takes one column per ALTER TABLE
+        for mut column in [
+            ColumnDef::new(UserSettingsAdditions::AutoRetryFailedDownloads)
+                .boolean()
+                .null()
+                .default(false)
+                .to_owned(),
+            ColumnDef::new(UserSettingsAdditions::MaxDownloadRetries)
+                .integer()
+                .null()
+                .default(3)
+                .to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserSettings::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            UserSettingsAdditions::MaxDownloadRetries,
+            UserSettingsAdditions::AutoRetryFailedDownloads,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserSettings::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LidarrDownloads::Table)
+                    .drop_column(LidarrDownloadsAdditions::RetryCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LidarrDownloadsAdditions {
+    RetryCount,
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    AutoRetryFailedDownloads,
+    MaxDownloadRetries,
+}
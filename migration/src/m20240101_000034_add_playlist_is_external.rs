@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000007_create_playlists_table::Playlists;
+
+/// Playlists imported by link without being followed on Spotify
+///
+/// Library syncs only walk followed playlists, so these are never refreshed
+/// or removed by a sync.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .add_column(
+                        ColumnDef::new(PlaylistsAdditions::IsExternal)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .drop_column(PlaylistsAdditions::IsExternal)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PlaylistsAdditions {
+    IsExternal,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000001_create_artists_table::Artists;
+
+/// Artist names corrected by the user, which syncs must not overwrite
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .add_column(
+                        ColumnDef::new(ArtistsAdditions::NameLocked)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .drop_column(ArtistsAdditions::NameLocked)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ArtistsAdditions {
+    NameLocked,
+}
@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+use super::m20240101_000007_create_playlists_table::Playlists;
+
+/// Consecutive sync failures per playlist, and why a playlist was disabled
+///
+/// Syncs disable a playlist once it fails `playlist_failure_threshold` times
+/// in a row (e.g. it went private) and record the reason for the UI.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite takes one column per ALTER TABLE
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .add_column(
+                        ColumnDef::new(PlaylistsAdditions::SyncFailureCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .add_column(ColumnDef::new(PlaylistsAdditions::DisabledReason).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .add_column(
+                        ColumnDef::new(UserSettingsAdditions::PlaylistFailureThreshold)
+                            .integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .drop_column(UserSettingsAdditions::PlaylistFailureThreshold)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .drop_column(PlaylistsAdditions::DisabledReason)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .drop_column(PlaylistsAdditions::SyncFailureCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PlaylistsAdditions {
+    SyncFailureCount,
+    DisabledReason,
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    PlaylistFailureThreshold,
+}
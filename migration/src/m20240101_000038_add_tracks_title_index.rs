@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend};
+
+/// Trigram index on `lower(tracks.title)` for the library-wide track search
+///
+/// The search matches `lower(title) LIKE '%…%'`, which a plain B-tree index
+/// can't serve. Postgres only: SQLite has no trigram indexes and scans.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+
+        let db = manager.get_connection();
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm").await?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_tracks_title_trgm \
+             ON tracks USING gin (lower(title) gin_trgm_ops)",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+
+        manager
+            .get_connection()
+            .execute_unprepared("DROP INDEX IF EXISTS idx_tracks_title_trgm")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+use super::m20240101_000003_create_tracks_table::Tracks;
+use super::m20240101_000007_create_playlists_table::Playlists;
+
+/// When an album, playlist or track disappeared from Spotify
+///
+/// Syncs set `archived_at` on albums that are no longer saved, playlists that
+/// are no longer followed and tracks removed from Liked Songs, instead of
+/// deleting them. Restoring one clears it.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(ColumnDef::new(ArchiveAdditions::ArchivedAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .add_column(ColumnDef::new(ArchiveAdditions::ArchivedAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tracks::Table)
+                    .add_column(ColumnDef::new(ArchiveAdditions::ArchivedAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tracks::Table)
+                    .drop_column(ArchiveAdditions::ArchivedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .drop_column(ArchiveAdditions::ArchivedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(ArchiveAdditions::ArchivedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ArchiveAdditions {
+    ArchivedAt,
+}
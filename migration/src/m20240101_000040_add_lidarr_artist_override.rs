@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+
+/// Per-album MusicBrainz artist ID to use for Lidarr instead of the album's own
+///
+/// For albums credited to a collaboration that Lidarr files under a
+/// different primary artist.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(
+                        ColumnDef::new(AlbumsAdditions::LidarrArtistMbidOverride)
+                            .string_len(36)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(AlbumsAdditions::LidarrArtistMbidOverride)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlbumsAdditions {
+    LidarrArtistMbidOverride,
+}
@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+/// External side effects that failed and are waiting to be retried
+///
+/// Rows that reach the attempt limit stay behind as dead letters until they
+/// are retried or discarded by hand.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingSideEffects::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PendingSideEffects::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingSideEffects::Kind)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PendingSideEffects::Payload).text().not_null())
+                    .col(
+                        ColumnDef::new(PendingSideEffects::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(PendingSideEffects::NextAttemptAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PendingSideEffects::LastError).text().null())
+                    .col(
+                        ColumnDef::new(PendingSideEffects::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingSideEffects::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pending_side_effects_next_attempt_at")
+                    .table(PendingSideEffects::Table)
+                    .col(PendingSideEffects::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PendingSideEffects::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PendingSideEffects {
+    Table,
+    Id,
+    Kind,
+    Payload,
+    Attempts,
+    NextAttemptAt,
+    LastError,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000007_create_playlists_table::Playlists;
+
+/// Number of podcast episodes the last sync skipped in a playlist
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .add_column(
+                        ColumnDef::new(PlaylistsAdditions::EpisodeCount)
+                            .integer()
+                            .null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Playlists::Table)
+                    .drop_column(PlaylistsAdditions::EpisodeCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PlaylistsAdditions {
+    EpisodeCount,
+}
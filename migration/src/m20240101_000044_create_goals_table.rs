@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000015_create_profiles_tables::Profiles;
+
+/// Collection goals: a target ownership percentage for a playlist or artist
+///
+/// Only the definition is stored; progress is computed from the library when
+/// goals are read.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Goals::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Goals::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Goals::ProfileId).integer().not_null())
+                    .col(ColumnDef::new(Goals::GoalType).string_len(16).not_null())
+                    .col(ColumnDef::new(Goals::TargetId).integer().not_null())
+                    .col(ColumnDef::new(Goals::TargetPercentage).double().not_null())
+                    .col(
+                        ColumnDef::new(Goals::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Goals::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_goals_profile_id")
+                            .from(Goals::Table, Goals::ProfileId)
+                            .to(Profiles::Table, Profiles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_goals_profile_target")
+                    .table(Goals::Table)
+                    .col(Goals::ProfileId)
+                    .col(Goals::GoalType)
+                    .col(Goals::TargetId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Goals::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Goals {
+    Table,
+    Id,
+    ProfileId,
+    GoalType,
+    TargetId,
+    TargetPercentage,
+    CreatedAt,
+    UpdatedAt,
+}
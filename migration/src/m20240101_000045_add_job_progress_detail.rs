@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000005_create_jobs_table::Jobs;
+
+/// JSON progress details a running job reports beyond its percentage,
+/// such as a sync's API usage and ETA
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Jobs::Table)
+                    .add_column(ColumnDef::new(JobsAdditions::ProgressDetail).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Jobs::Table)
+                    .drop_column(JobsAdditions::ProgressDetail)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JobsAdditions {
+    ProgressDetail,
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+
+/// Where an album's genres came from (`spotify`, `musicbrainz` or `manual`),
+/// so syncs don't overwrite enriched or hand-edited genres
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(ColumnDef::new(AlbumsAdditions::GenreSource).string_len(16).null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every genre list so far came from the Spotify sync
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE albums SET genre_source = 'spotify' \
+                 WHERE genres IS NOT NULL AND genres <> '[]'",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(AlbumsAdditions::GenreSource)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlbumsAdditions {
+    GenreSource,
+}
@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000003_create_tracks_table::Tracks;
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+
+/// Length of a track's local file as read by the filesystem scan, whether it
+/// differs from Spotify's length, and the allowed difference in seconds
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite adds one column per statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tracks::Table)
+                    .add_column(ColumnDef::new(TracksAdditions::LocalDurationMs).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tracks::Table)
+                    .add_column(
+                        ColumnDef::new(TracksAdditions::DurationMismatch)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .add_column(
+                        ColumnDef::new(UserSettingsAdditions::DurationToleranceSecs)
+                            .integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .drop_column(UserSettingsAdditions::DurationToleranceSecs)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tracks::Table)
+                    .drop_column(TracksAdditions::DurationMismatch)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tracks::Table)
+                    .drop_column(TracksAdditions::LocalDurationMs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TracksAdditions {
+    LocalDurationMs,
+    DurationMismatch,
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    DurationToleranceSecs,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000001_create_artists_table::Artists;
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+
+/// Artist image from Spotify, and whether the one-time backfill of images for
+/// existing artists was queued
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .add_column(ColumnDef::new(ArtistsAdditions::ImageUrl).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .add_column(
+                        ColumnDef::new(UserSettingsAdditions::ArtistImageBackfillQueued)
+                            .boolean()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserSettings::Table)
+                    .drop_column(UserSettingsAdditions::ArtistImageBackfillQueued)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .drop_column(ArtistsAdditions::ImageUrl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ArtistsAdditions {
+    ImageUrl,
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    ArtistImageBackfillQueued,
+}
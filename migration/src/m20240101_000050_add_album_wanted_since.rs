@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+
+/// When an album last became wanted (not owned), so stale wants can be told
+/// from recent ones
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .add_column(ColumnDef::new(AlbumsAdditions::WantedSince).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Albums wanted since they were added, unless the audit trail shows a later move back
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE albums SET wanted_since = COALESCE( \
+                     (SELECT MAX(e.created_at) FROM ownership_events e \
+                      WHERE e.album_id = albums.id AND e.to_status = 'not_owned'), \
+                     created_at) \
+                 WHERE ownership_status = 'not_owned'",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Albums::Table)
+                    .drop_column(AlbumsAdditions::WantedSince)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AlbumsAdditions {
+    WantedSince,
+}
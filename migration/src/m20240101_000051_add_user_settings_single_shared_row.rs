@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+/// One shared settings row, kept at a fixed id, and at most one row per profile
+///
+/// Two first-time saves racing each other could each insert a row, after
+/// which reads picked one of them and the other's settings seemed to vanish.
+/// Existing duplicates are collapsed into the newest row, taking each setting
+/// from the newest row that has it. The shared survivor moves to id 0, which
+/// saves upsert on. A profile's rows are already unique by
+/// `idx_user_settings_profile_id`, but are collapsed the same way in case a
+/// database was restored without that index, since any duplicate left would
+/// fail the new index. The unique index stops a second shared row or a
+/// second row for a profile from ever being inserted.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Id of the shared settings row; id sequences start at 1, so it never collides
+const SHARED_ID: i32 = 0;
+
+/// Nullable settings columns, merged newest value first
+const MERGED_COLUMNS: &[&str] = &[
+    "spotify_access_token",
+    "spotify_refresh_token",
+    "spotify_token_expires_at",
+    "lidarr_url",
+    "lidarr_api_key",
+    "music_folder_path",
+    "auto_sync_enabled",
+    "sync_interval_hours",
+    "match_by_barcode",
+    "purge_disabled_playlist_tracks",
+    "log_level",
+    "sync_collaborative_playlists",
+    "exclude_singles",
+    "single_track_threshold",
+    "timezone",
+    "listenbrainz_username",
+    "listens_synced_until",
+    "auto_retry_failed_downloads",
+    "max_download_retries",
+    "lidarr_extra_headers",
+    "stats_exclude_playlist_imports",
+    "playlist_failure_threshold",
+    "path_mappings",
+    "path_mismatch_prefixes",
+    "auto_fetch_cover_art",
+    "locale",
+    "duration_tolerance_secs",
+    "artist_image_backfill_queued",
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let duplicated = db
+            .query_all(Statement::from_string(
+                manager.get_database_backend(),
+                "SELECT profile_id FROM user_settings WHERE profile_id IS NOT NULL \
+                 GROUP BY profile_id HAVING COUNT(*) > 1",
+            ))
+            .await?;
+        for row in duplicated {
+            let profile_id: i32 = row.try_get("", "profile_id")?;
+            collapse(manager, Some(profile_id)).await?;
+        }
+
+        if let Some(keep) = collapse(manager, None).await? {
+            db.execute_unprepared(&format!("UPDATE user_settings SET id = {SHARED_ID} WHERE id = {keep}"))
+                .await?;
+        }
+
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX idx_user_settings_one_per_profile \
+             ON user_settings ((COALESCE(profile_id, 0)))",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DROP INDEX idx_user_settings_one_per_profile")
+            .await?;
+        Ok(())
+    }
+}
+
+/// Condition selecting the rows of `profile_id`, or the shared rows if `None`
+fn in_scope(alias: &str, profile_id: Option<i32>) -> String {
+    match profile_id {
+        Some(id) => format!("{alias}profile_id = {id}"),
+        None => format!("{alias}profile_id IS NULL"),
+    }
+}
+
+/// Merge the settings rows of `profile_id` (the shared rows if `None`) into
+/// the newest one and delete the rest; returns the id of the row kept
+async fn collapse(manager: &SchemaManager<'_>, profile_id: Option<i32>) -> Result<Option<i32>, DbErr> {
+    let db = manager.get_connection();
+    let newest = db
+        .query_one(Statement::from_string(
+            manager.get_database_backend(),
+            format!(
+                "SELECT id FROM user_settings WHERE {} ORDER BY updated_at DESC, id DESC LIMIT 1",
+                in_scope("", profile_id)
+            ),
+        ))
+        .await?;
+    let Some(row) = newest else {
+        return Ok(None);
+    };
+
+    let keep: i32 = row.try_get("", "id")?;
+    let scope = in_scope("s.", profile_id);
+    for column in MERGED_COLUMNS {
+        db.execute_unprepared(&format!(
+            "UPDATE user_settings SET {column} = ( \
+                 SELECT s.{column} FROM user_settings s \
+                 WHERE {scope} AND s.{column} IS NOT NULL \
+                 ORDER BY s.updated_at DESC, s.id DESC LIMIT 1) \
+             WHERE id = {keep}"
+        ))
+        .await?;
+    }
+    db.execute_unprepared(&format!(
+        "UPDATE user_settings SET created_at = ( \
+             SELECT MIN(s.created_at) FROM user_settings s WHERE {scope}) \
+         WHERE id = {keep}"
+    ))
+    .await?;
+    db.execute_unprepared(&format!(
+        "DELETE FROM user_settings WHERE {} AND id <> {keep}",
+        in_scope("", profile_id)
+    ))
+    .await?;
+    Ok(Some(keep))
+}
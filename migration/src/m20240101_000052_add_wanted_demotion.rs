@@ -0,0 +1,180 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+use super::m20240101_000004_create_user_settings_table::UserSettings;
+use super::m20240101_000015_create_profiles_tables::{ProfileAlbums, Profiles};
+
+/// Want priorities and the rule that lowers those of stale wants
+///
+/// Each profile's link to an album gets a priority from 1 to 5 (must have),
+/// 3 unless set, and the time the rule cleared the want, if it did. Both sit
+/// on `profile_albums` because the rule and its threshold are per profile,
+/// and the Spotify sync never writes them. Each demotion the rule makes is
+/// logged so it can be undone.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite takes one column per ALTER TABLE
+        for mut column in [
+            ColumnDef::new(ProfileAlbumsAdditions::WantPriority)
+                .integer()
+                .not_null()
+                .default(3)
+                .to_owned(),
+            ColumnDef::new(ProfileAlbumsAdditions::WantClearedAt)
+                .timestamp_with_time_zone()
+                .null()
+                .to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProfileAlbums::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        for mut column in [
+            ColumnDef::new(UserSettingsAdditions::WantedDemotionEnabled)
+                .boolean()
+                .null()
+                .default(false)
+                .to_owned(),
+            ColumnDef::new(UserSettingsAdditions::WantedDemotionDays)
+                .integer()
+                .null()
+                .default(365)
+                .to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserSettings::Table)
+                        .add_column(&mut column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WantDemotions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WantDemotions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(WantDemotions::AlbumId).integer().not_null())
+                    .col(ColumnDef::new(WantDemotions::ProfileId).integer().not_null())
+                    .col(ColumnDef::new(WantDemotions::FromPriority).integer().not_null())
+                    .col(ColumnDef::new(WantDemotions::ToPriority).integer().not_null())
+                    .col(
+                        ColumnDef::new(WantDemotions::Cleared)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(WantDemotions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WantDemotions::RestoredAt).timestamp_with_time_zone().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_want_demotions_album_id")
+                            .from(WantDemotions::Table, WantDemotions::AlbumId)
+                            .to(Albums::Table, Albums::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_want_demotions_profile_id")
+                            .from(WantDemotions::Table, WantDemotions::ProfileId)
+                            .to(Profiles::Table, Profiles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_want_demotions_album_id")
+                    .table(WantDemotions::Table)
+                    .col(WantDemotions::AlbumId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WantDemotions::Table).to_owned())
+            .await?;
+
+        for column in [
+            UserSettingsAdditions::WantedDemotionDays,
+            UserSettingsAdditions::WantedDemotionEnabled,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserSettings::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        for column in [
+            ProfileAlbumsAdditions::WantClearedAt,
+            ProfileAlbumsAdditions::WantPriority,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ProfileAlbums::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProfileAlbumsAdditions {
+    WantPriority,
+    WantClearedAt,
+}
+
+#[derive(DeriveIden)]
+enum UserSettingsAdditions {
+    WantedDemotionEnabled,
+    WantedDemotionDays,
+}
+
+#[derive(DeriveIden)]
+enum WantDemotions {
+    Table,
+    Id,
+    AlbumId,
+    ProfileId,
+    FromPriority,
+    ToPriority,
+    Cleared,
+    CreatedAt,
+    RestoredAt,
+}
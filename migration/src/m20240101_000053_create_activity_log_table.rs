@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000002_create_albums_table::Albums;
+use super::m20240101_000005_create_jobs_table::Jobs;
+
+/// Library-wide activity feed, written by the activity log event subscriber
+///
+/// Unlike `ownership_events` this is derived data: a row may be missing if
+/// the subscriber fell behind, in which case a `skipped` row records the gap.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ActivityLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ActivityLog::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ActivityLog::Kind).string_len(32).not_null())
+                    .col(ColumnDef::new(ActivityLog::AlbumId).integer().null())
+                    .col(ColumnDef::new(ActivityLog::JobId).integer().null())
+                    .col(ColumnDef::new(ActivityLog::Details).text().not_null())
+                    .col(
+                        ColumnDef::new(ActivityLog::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_activity_log_album_id")
+                            .from(ActivityLog::Table, ActivityLog::AlbumId)
+                            .to(Albums::Table, Albums::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_activity_log_job_id")
+                            .from(ActivityLog::Table, ActivityLog::JobId)
+                            .to(Jobs::Table, Jobs::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_activity_log_created_at")
+                    .table(ActivityLog::Table)
+                    .col(ActivityLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ActivityLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ActivityLog {
+    Table,
+    Id,
+    Kind,
+    AlbumId,
+    JobId,
+    Details,
+    CreatedAt,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000008_create_playlist_tracks_table::PlaylistTracks;
+
+/// Whether Spotify could play each playlist entry in the user's market at the
+/// last sync; NULL when Spotify didn't say
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PlaylistTracks::Table)
+                    .add_column(ColumnDef::new(PlaylistTracksAdditions::IsPlayable).boolean().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PlaylistTracks::Table)
+                    .drop_column(PlaylistTracksAdditions::IsPlayable)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PlaylistTracksAdditions {
+    IsPlayable,
+}
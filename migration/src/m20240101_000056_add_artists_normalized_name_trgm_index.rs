@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend};
+
+/// Trigram index on `artists.normalized_name` for fuzzy artist lookups
+///
+/// Lets a lookup find names misspelled in their first letters, which the
+/// shared-prefix candidates miss. Postgres only: SQLite has no trigram
+/// matching.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+
+        let db = manager.get_connection();
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm").await?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_artists_normalized_name_trgm \
+             ON artists USING gin (normalized_name gin_trgm_ops)",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+
+        manager
+            .get_connection()
+            .execute_unprepared("DROP INDEX IF EXISTS idx_artists_normalized_name_trgm")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000015_create_profiles_tables::Profiles;
+
+/// When a profile's Spotify top items were last fetched, so an empty result
+/// stays cached as long as a non-empty one
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profiles::Table)
+                    .add_column(
+                        ColumnDef::new(ProfilesAdditions::TopItemsFetchedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profiles::Table)
+                    .drop_column(ProfilesAdditions::TopItemsFetchedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProfilesAdditions {
+    TopItemsFetchedAt,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20240101_000001_create_artists_table::Artists;
+
+/// Artist name Spotify gave at the last sync, so a sync only follows a
+/// Spotify rename when the stored name hasn't been changed since
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .add_column(ColumnDef::new(ArtistsAdditions::SpotifyName).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Artists::Table)
+                    .drop_column(ArtistsAdditions::SpotifyName)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ArtistsAdditions {
+    SpotifyName,
+}
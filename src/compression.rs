@@ -0,0 +1,67 @@
+//! Response compression settings
+//!
+//! Compression is applied to every route, but it isn't free: small HTMX
+//! fragments gain little from gzip and pay for it in latency, and images,
+//! audio, fonts and archives are already compressed.
+
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+/// Responses smaller than this (in bytes) are sent uncompressed
+pub const MIN_COMPRESS_SIZE: u16 = 1024;
+
+/// Which responses get compressed
+pub fn predicate() -> impl Predicate {
+    SizeAbove::new(MIN_COMPRESS_SIZE)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::const_new("audio/"))
+        .and(NotForContentType::const_new("video/"))
+        .and(NotForContentType::const_new("font/woff"))
+        .and(NotForContentType::const_new("application/zip"))
+        .and(NotForContentType::const_new("application/gzip"))
+}
+
+/// Compression layer for the whole router
+pub fn layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(predicate())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Response};
+
+    fn response(content_type: &str, len: usize) -> Response<Body> {
+        Response::builder()
+            .header("content-type", content_type)
+            .body(Body::from("x".repeat(len)))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_compresses_large_text() {
+        assert!(predicate().should_compress(&response("application/json", 64 * 1024)));
+        assert!(predicate().should_compress(&response("text/html; charset=utf-8", 4096)));
+        assert!(predicate().should_compress(&response("image/svg+xml", 4096)));
+    }
+
+    #[test]
+    fn test_skips_small_fragments() {
+        assert!(!predicate().should_compress(&response("text/html; charset=utf-8", 300)));
+    }
+
+    #[test]
+    fn test_skips_compressed_assets() {
+        for content_type in ["image/jpeg", "image/webp", "audio/flac", "font/woff2", "application/zip"] {
+            assert!(
+                !predicate().should_compress(&response(content_type, 64 * 1024)),
+                "compressed {}",
+                content_type
+            );
+        }
+    }
+}
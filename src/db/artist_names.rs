@@ -0,0 +1,278 @@
+//! Normalized artist names for fuzzy lookups
+//!
+//! Incoming names (Lidarr webhooks, folder names) rarely match the stored
+//! Spotify spelling exactly. Rather than scoring every artist row, lookups
+//! fetch a bounded set of candidates sharing a prefix of the normalized name,
+//! with or without a leading article, and score only those in memory. On
+//! Postgres, trigram similarity adds names misspelled in their first letters.
+
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{extension::postgres::PgBinOper, Alias, Expr, Func, SimpleExpr},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseBackend,
+    DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+
+use crate::{db::entities::artists, error::Result};
+
+/// Most artists fetched for one fuzzy lookup
+pub const MAX_CANDIDATES: u64 = 50;
+
+/// Leading characters of the normalized name a candidate must share
+const PREFIX_LEN: usize = 3;
+
+/// Articles a name may or may not start with, as in "The Beatles"
+const ARTICLES: [&str; 3] = ["the", "a", "an"];
+
+/// Normalize an artist name for comparison
+///
+/// Unicode is transliterated and lowercased, `&` becomes "and", apostrophes
+/// are dropped and every other run of non-alphanumeric characters collapses
+/// into a single space. The result only holds ASCII letters, digits and
+/// spaces, so it can go straight into a `LIKE` pattern.
+pub fn normalize_name(name: &str) -> String {
+    let ascii = deunicode::deunicode(name);
+    let mut normalized = String::with_capacity(ascii.len());
+    let mut pending_space = false;
+
+    for c in ascii.chars() {
+        if c.is_ascii_alphanumeric() {
+            if pending_space && !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            pending_space = false;
+            normalized.push(c.to_ascii_lowercase());
+        } else if c == '&' {
+            if !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            normalized.push_str("and");
+            pending_space = true;
+        } else if c != '\'' && c != '`' {
+            pending_space = true;
+        }
+    }
+
+    normalized
+}
+
+/// A normalized name without its leading article, if it has one
+///
+/// "the beatles" and "beatles" both give "beatles". A name that is nothing
+/// but an article is kept whole.
+pub fn without_article(normalized: &str) -> &str {
+    ARTICLES
+        .iter()
+        .find_map(|article| normalized.strip_prefix(article)?.strip_prefix(' '))
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(normalized)
+}
+
+/// Artists whose normalized name shares a prefix with `name`
+///
+/// The prefix is compared with and without a leading article, so "Beatles"
+/// finds "The Beatles" and the other way round. On Postgres, names similar
+/// by trigrams are candidates too, which catches typos in the first letters.
+///
+/// Runs a single query returning at most [`MAX_CANDIDATES`] rows, however
+/// large the artist table grows. An exact match comes first, then matches
+/// but for the article, then the closest names, so the limit never cuts off
+/// an exact match.
+pub async fn candidates(db: &DatabaseConnection, name: &str) -> Result<Vec<artists::Model>> {
+    let normalized = normalize_name(name);
+    if normalized.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let core = without_article(&normalized);
+    let core_prefix: String = core.chars().take(PREFIX_LEN).collect();
+    let mut prefixes = vec![normalized.chars().take(PREFIX_LEN).collect::<String>(), core_prefix.clone()];
+    prefixes.extend(ARTICLES.iter().map(|article| format!("{} {}", article, core_prefix)));
+    prefixes.dedup();
+
+    let postgres = db.get_database_backend() == DatabaseBackend::Postgres;
+    let name_column = || Expr::col((artists::Entity, artists::Column::NormalizedName));
+    let mut matching = prefixes
+        .iter()
+        .fold(Condition::any(), |condition, prefix| {
+            condition.add(artists::Column::NormalizedName.starts_with(prefix))
+        });
+    if postgres {
+        matching = matching.add(name_column().binary(PgBinOper::Similarity, normalized.clone()));
+    }
+
+    let mut same_but_article = vec![core.to_string()];
+    same_but_article.extend(ARTICLES.iter().map(|article| format!("{} {}", article, core)));
+    let mut select = artists::Entity::find()
+        .filter(matching)
+        .order_by_asc(SimpleExpr::Case(Box::new(
+            Expr::case(artists::Column::NormalizedName.eq(normalized.clone()), 0).finally(1),
+        )))
+        .order_by_asc(SimpleExpr::Case(Box::new(
+            Expr::case(artists::Column::NormalizedName.is_in(same_but_article), 0).finally(1),
+        )));
+    if postgres {
+        select = select.order_by_desc(SimpleExpr::FunctionCall(
+            Func::cust(Alias::new("similarity"))
+                .arg(name_column())
+                .arg(normalized.clone()),
+        ));
+    }
+
+    Ok(select
+        .order_by_asc(SimpleExpr::FunctionCall(Func::abs(
+            Expr::expr(Func::char_length(name_column())).sub(normalized.len() as i64),
+        )))
+        .order_by_asc(artists::Column::Id)
+        .limit(MAX_CANDIDATES)
+        .all(db)
+        .await?)
+}
+
+/// Fill in the normalized name of artists that don't have one yet
+///
+/// Runs on startup so artists created before the column existed can be found
+/// by fuzzy lookups.
+pub async fn backfill_normalized_names(db: &DatabaseConnection) -> Result<u64> {
+    let artists_without_name = artists::Entity::find()
+        .filter(artists::Column::NormalizedName.is_null())
+        .all(db)
+        .await?;
+
+    let mut filled = 0;
+    for artist in artists_without_name {
+        let normalized = normalize_name(&artist.name);
+        let mut active: artists::ActiveModel = artist.into();
+        active.normalized_name = Set(Some(normalized));
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+        filled += 1;
+    }
+
+    if filled > 0 {
+        tracing::info!("Normalized names of {} artists", filled);
+    }
+
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Radiohead"), "radiohead");
+        assert_eq!(normalize_name("  Sigur Rós "), "sigur ros");
+        assert_eq!(normalize_name("Simon & Garfunkel"), "simon and garfunkel");
+        assert_eq!(normalize_name("Guns N' Roses"), "guns n roses");
+        assert_eq!(normalize_name("AC/DC"), "ac dc");
+        assert_eq!(normalize_name("!!!"), "");
+    }
+
+    #[test]
+    fn test_without_article() {
+        assert_eq!(without_article("the beatles"), "beatles");
+        assert_eq!(without_article("a tribe called quest"), "tribe called quest");
+        assert_eq!(without_article("beatles"), "beatles");
+        assert_eq!(without_article("theatre of tragedy"), "theatre of tragedy");
+        assert_eq!(without_article("the"), "the");
+    }
+
+    #[tokio::test]
+    async fn test_candidates_ignore_leading_article() {
+        let db = setup_test_db().await;
+        let the_beatles = create_test_artist(&db, "The Beatles", None).await;
+        let beach_boys = create_test_artist(&db, "Beach Boys", None).await;
+        create_test_artist(&db, "Radiohead", None).await;
+
+        let found = candidates(&db, "Beatles").await.unwrap();
+        assert_eq!(found.first().map(|a| a.id), Some(the_beatles.id));
+
+        let found = candidates(&db, "The Beach Boys").await.unwrap();
+        assert_eq!(found.first().map(|a| a.id), Some(beach_boys.id));
+        assert!(found.iter().all(|a| a.name != "Radiohead"));
+    }
+
+    #[tokio::test]
+    async fn test_candidates_find_typos_on_postgres() {
+        let Some(db) = setup_postgres_test_db().await else {
+            return;
+        };
+        let radiohead = create_test_artist(&db, "Radiohead", None).await;
+        create_test_artist(&db, "Portishead", None).await;
+
+        // No shared prefix, but most trigrams in common
+        let found = candidates(&db, "Tadiohead").await.unwrap();
+        assert_eq!(found.first().map(|a| a.id), Some(radiohead.id));
+        assert!(candidates(&db, "Massive Attack").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_normalized_names() {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Mötley Crüe", None).await;
+
+        let mut active: artists::ActiveModel = artist.clone().into();
+        active.normalized_name = Set(None);
+        active.update(&db).await.unwrap();
+
+        assert_eq!(backfill_normalized_names(&db).await.unwrap(), 1);
+        let artist = artists::Entity::find_by_id(artist.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(artist.normalized_name.as_deref(), Some("motley crue"));
+        assert_eq!(backfill_normalized_names(&db).await.unwrap(), 0);
+    }
+
+    async fn insert_artists(db: &DatabaseConnection, from: usize, to: usize) {
+        let now = Utc::now();
+        let rows = (from..to).map(|n| {
+            let name = format!("The Band {:04}", n);
+            artists::ActiveModel {
+                normalized_name: Set(Some(normalize_name(&name))),
+                name: Set(name),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+                ..Default::default()
+            }
+        });
+        artists::Entity::insert_many(rows).exec(db).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_candidates_stay_bounded_as_table_grows() {
+        let mut db = setup_test_db().await;
+        let queries = Arc::new(AtomicUsize::new(0));
+        let counter = queries.clone();
+        db.set_metric_callback(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let mut total = 0;
+        for size in [100, 500, 2000] {
+            // Insert in chunks to stay under SQLite's bound parameter limit
+            while total < size {
+                let next = (total + 100).min(size);
+                insert_artists(&db, total, next).await;
+                total = next;
+            }
+
+            queries.store(0, Ordering::SeqCst);
+            let found = candidates(&db, "The Band 1999").await.unwrap();
+
+            assert_eq!(queries.load(Ordering::SeqCst), 1, "{} artists", size);
+            assert_eq!(found.len() as u64, MAX_CANDIDATES, "{} artists", size);
+            // Once the exact name exists it is the first candidate
+            assert_eq!(found[0].name == "The Band 1999", size > 1999, "{} artists", size);
+        }
+
+        // Names without a shared prefix are never fetched
+        assert!(candidates(&db, "Radiohead").await.unwrap().is_empty());
+        assert!(candidates(&db, "???").await.unwrap().is_empty());
+    }
+}
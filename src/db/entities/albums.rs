@@ -3,7 +3,7 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "albums")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -29,6 +29,26 @@ pub struct Model {
     pub updated_at: DateTimeWithTimeZone,
     pub last_synced_at: Option<DateTimeWithTimeZone>,
     pub source: String,
+    pub barcode: Option<String>,
+    #[sea_orm(unique)]
+    pub slug: Option<String>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub estimated_value: Option<f64>,
+    pub match_source: Option<String>,
+    pub first_listened_at: Option<DateTimeWithTimeZone>,
+    pub last_listened_at: Option<DateTimeWithTimeZone>,
+    pub listen_count: i32,
+    /// When a sync found the album no longer saved on Spotify
+    pub archived_at: Option<DateTimeWithTimeZone>,
+    /// MusicBrainz artist ID Lidarr files this album under, when not the album's own artist
+    pub lidarr_artist_mbid_override: Option<String>,
+    /// Where `genres` came from: `spotify`, `musicbrainz` or `manual`
+    pub genre_source: Option<String>,
+    /// When the album last became wanted (not owned); unset while it's anything else
+    pub wanted_since: Option<DateTimeWithTimeZone>,
+    /// The user's own notes, e.g. tags like "vinyl preorder"
+    #[sea_orm(column_type = "Text", nullable)]
+    pub notes: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -43,8 +63,12 @@ pub enum Relation {
     Artists,
     #[sea_orm(has_many = "super::lidarr_downloads::Entity")]
     LidarrDownloads,
+    #[sea_orm(has_many = "super::ownership_events::Entity")]
+    OwnershipEvents,
     #[sea_orm(has_many = "super::tracks::Entity")]
     Tracks,
+    #[sea_orm(has_many = "super::want_demotions::Entity")]
+    WantDemotions,
 }
 
 impl Related<super::artists::Entity> for Entity {
@@ -59,10 +83,22 @@ impl Related<super::lidarr_downloads::Entity> for Entity {
     }
 }
 
+impl Related<super::ownership_events::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OwnershipEvents.def()
+    }
+}
+
 impl Related<super::tracks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Tracks.def()
     }
 }
 
+impl Related<super::want_demotions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WantDemotions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -14,6 +14,17 @@ pub struct Model {
     pub musicbrainz_id: Option<String>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(unique)]
+    pub slug: Option<String>,
+    pub normalized_name: Option<String>,
+    /// Name was corrected by the user and is kept across syncs
+    pub name_locked: bool,
+    /// Largest artist image Spotify has
+    #[sea_orm(column_type = "Text", nullable)]
+    pub image_url: Option<String>,
+    /// Name Spotify gave at the last sync; a sync only follows a Spotify
+    /// rename while `name` still matches it
+    pub spotify_name: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
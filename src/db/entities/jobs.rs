@@ -20,6 +20,13 @@ pub struct Model {
     pub completed_at: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub sync_cursor: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub result: Option<String>,
+    /// JSON details a running job reports alongside `progress`
+    #[sea_orm(column_type = "Text", nullable)]
+    pub progress_detail: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,11 +2,21 @@
 
 pub mod prelude;
 
+pub mod activity_log;
 pub mod albums;
 pub mod artists;
+pub mod goals;
 pub mod jobs;
 pub mod lidarr_downloads;
+pub mod lidarr_webhook_events;
+pub mod ownership_events;
+pub mod pending_side_effects;
 pub mod playlist_tracks;
 pub mod playlists;
+pub mod profile_albums;
+pub mod profile_playlists;
+pub mod profiles;
+pub mod top_items;
 pub mod tracks;
 pub mod user_settings;
+pub mod want_demotions;
@@ -12,6 +12,8 @@ pub struct Model {
     pub track_id: i32,
     pub position: i32,
     pub added_at: Option<DateTimeWithTimeZone>,
+    /// Spotify's `is_playable` for the user's market at the last sync
+    pub is_playable: Option<bool>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
@@ -25,6 +25,18 @@ pub struct Model {
     pub last_synced_at: Option<DateTimeWithTimeZone>,
     pub is_synthetic: bool,
     pub owned_count: Option<i32>,
+    pub unavailable_tracks: Option<i32>,
+    /// Imported by link without following it on Spotify
+    pub is_external: bool,
+    /// Syncs that failed in a row; reset by a successful sync
+    pub sync_failure_count: i32,
+    /// Why syncs disabled this playlist, until it is enabled again
+    #[sea_orm(column_type = "Text", nullable)]
+    pub disabled_reason: Option<String>,
+    /// When a sync found the playlist no longer followed on Spotify
+    pub archived_at: Option<DateTimeWithTimeZone>,
+    /// Podcast episodes the last sync skipped
+    pub episode_count: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,10 +1,20 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 
+pub use super::activity_log::Entity as ActivityLog;
 pub use super::albums::Entity as Albums;
 pub use super::artists::Entity as Artists;
+pub use super::goals::Entity as Goals;
 pub use super::jobs::Entity as Jobs;
 pub use super::lidarr_downloads::Entity as LidarrDownloads;
+pub use super::lidarr_webhook_events::Entity as LidarrWebhookEvents;
+pub use super::ownership_events::Entity as OwnershipEvents;
+pub use super::pending_side_effects::Entity as PendingSideEffects;
 pub use super::playlist_tracks::Entity as PlaylistTracks;
 pub use super::playlists::Entity as Playlists;
+pub use super::profile_albums::Entity as ProfileAlbums;
+pub use super::profile_playlists::Entity as ProfilePlaylists;
+pub use super::profiles::Entity as Profiles;
+pub use super::top_items::Entity as TopItems;
 pub use super::tracks::Entity as Tracks;
 pub use super::user_settings::Entity as UserSettings;
+pub use super::want_demotions::Entity as WantDemotions;
@@ -17,6 +17,12 @@ pub struct Model {
     pub musicbrainz_id: Option<String>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    /// When a sync found the track removed from Liked Songs
+    pub archived_at: Option<DateTimeWithTimeZone>,
+    /// Length of the local file, as read by the last filesystem scan
+    pub local_duration_ms: Option<i32>,
+    /// The local file's length differs from `duration_ms` by more than the tolerance
+    pub duration_mismatch: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -21,9 +21,60 @@ pub struct Model {
     pub sync_interval_hours: Option<i32>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub album_view: String,
+    pub match_by_barcode: Option<bool>,
+    pub purge_disabled_playlist_tracks: Option<bool>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub log_level: Option<String>,
+    pub sync_collaborative_playlists: Option<bool>,
+    pub exclude_singles: Option<bool>,
+    pub single_track_threshold: Option<i32>,
+    pub timezone: Option<String>,
+    #[sea_orm(unique)]
+    pub profile_id: Option<i32>,
+    pub listenbrainz_username: Option<String>,
+    pub listens_synced_until: Option<DateTimeWithTimeZone>,
+    pub auto_retry_failed_downloads: Option<bool>,
+    pub max_download_retries: Option<i32>,
+    /// JSON object of extra headers sent with every Lidarr request
+    #[sea_orm(column_type = "Text", nullable)]
+    pub lidarr_extra_headers: Option<String>,
+    pub stats_exclude_playlist_imports: Option<bool>,
+    pub playlist_failure_threshold: Option<i32>,
+    /// JSON object rewriting path prefixes reported by Lidarr
+    #[sea_orm(column_type = "Text", nullable)]
+    pub path_mappings: Option<String>,
+    /// JSON array of library folders seen outside the music folder
+    #[sea_orm(column_type = "Text", nullable)]
+    pub path_mismatch_prefixes: Option<String>,
+    pub auto_fetch_cover_art: Option<bool>,
+    pub locale: Option<String>,
+    pub duration_tolerance_secs: Option<i32>,
+    /// The artist image backfill for artists synced before images existed was queued
+    pub artist_image_backfill_queued: Option<bool>,
+    /// Lower the priority of albums wanted for longer than `wanted_demotion_days`
+    pub wanted_demotion_enabled: Option<bool>,
+    pub wanted_demotion_days: Option<i32>,
+    /// Days archived rows are kept before the purge removes them; unset keeps them
+    pub archive_retention_days: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::profiles::Entity",
+        from = "Column::ProfileId",
+        to = "super::profiles::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Profiles,
+}
+
+impl Related<super::profiles::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Profiles.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
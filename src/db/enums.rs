@@ -5,6 +5,8 @@ pub enum OwnershipStatus {
     NotOwned,
     Owned,
     Downloading,
+    /// Bought or pre-ordered and waiting to arrive
+    Ordered,
 }
 
 impl OwnershipStatus {
@@ -13,6 +15,7 @@ impl OwnershipStatus {
             Self::NotOwned => "not_owned",
             Self::Owned => "owned",
             Self::Downloading => "downloading",
+            Self::Ordered => "ordered",
         }
     }
 
@@ -21,9 +24,29 @@ impl OwnershipStatus {
             "not_owned" => Some(Self::NotOwned),
             "owned" => Some(Self::Owned),
             "downloading" => Some(Self::Downloading),
+            "ordered" => Some(Self::Ordered),
             _ => None,
         }
     }
+
+    /// States an album may move to from this one without an override
+    ///
+    /// Nothing leaves `Owned` unguarded: dropping an owned album means its
+    /// files are gone, which has to be asserted rather than inferred.
+    pub fn allowed_next(&self) -> &'static [OwnershipStatus] {
+        match self {
+            Self::NotOwned => &[Self::Ordered, Self::Downloading, Self::Owned],
+            Self::Ordered => &[Self::Downloading, Self::Owned, Self::NotOwned],
+            Self::Downloading => &[Self::Owned, Self::NotOwned],
+            Self::Owned => &[],
+        }
+    }
+
+    /// Whether an album may move to `next`; staying put is always allowed
+    /// and `override_guard` permits any move
+    pub fn can_transition_to(&self, next: OwnershipStatus, override_guard: bool) -> bool {
+        *self == next || override_guard || self.allowed_next().contains(&next)
+    }
 }
 
 impl From<OwnershipStatus> for String {
@@ -67,6 +90,74 @@ impl From<MatchStatus> for String {
     }
 }
 
+/// Who applied an album's MusicBrainz match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchSource {
+    /// Chosen by the matching job from its score
+    Auto,
+    /// Release group set by hand; never reclassified or reset by score
+    Manual,
+}
+
+impl MatchSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Auto => "auto",
+            Self::Manual => "manual",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(Self::Auto),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+}
+
+impl From<MatchSource> for String {
+    fn from(source: MatchSource) -> String {
+        source.as_str().to_string()
+    }
+}
+
+/// Where an album's genres came from, in rising precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GenreSource {
+    /// Spotify's album genres, refreshed by each sync
+    Spotify,
+    /// Top release group tags, filled in by the matching job
+    Musicbrainz,
+    /// Edited by hand; only another edit replaces them
+    Manual,
+}
+
+impl GenreSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Spotify => "spotify",
+            Self::Musicbrainz => "musicbrainz",
+            Self::Manual => "manual",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "spotify" => Some(Self::Spotify),
+            "musicbrainz" => Some(Self::Musicbrainz),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+}
+
+impl From<GenreSource> for String {
+    fn from(source: GenreSource) -> String {
+        source.as_str().to_string()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JobType {
     SpotifySync,
@@ -74,10 +165,20 @@ pub enum JobType {
     LidarrSearch,
     CoverArtFetch,
     FilesystemScan,
+    /// A filesystem scan that only proposes matches, for review before applying
+    FilesystemScanPreview,
     PlaylistStatsBackfill,
+    MatchReevaluation,
+    /// Fetch Spotify images for artists, resuming from a checkpoint
+    ArtistImageBackfill,
 }
 
 impl JobType {
+    /// Job types that record the album they work on as their `entity_id`
+    ///
+    /// Other types use `entity_id` for something else (a profile, for syncs) or not at all.
+    pub const ALBUM_SCOPED: [Self; 3] = [Self::MusicbrainzMatch, Self::LidarrSearch, Self::CoverArtFetch];
+
     pub fn as_str(&self) -> &str {
         match self {
             Self::SpotifySync => "spotify_sync",
@@ -85,7 +186,10 @@ impl JobType {
             Self::LidarrSearch => "lidarr_search",
             Self::CoverArtFetch => "cover_art_fetch",
             Self::FilesystemScan => "filesystem_scan",
+            Self::FilesystemScanPreview => "filesystem_scan_preview",
             Self::PlaylistStatsBackfill => "playlist_stats_backfill",
+            Self::MatchReevaluation => "match_reevaluation",
+            Self::ArtistImageBackfill => "artist_image_backfill",
         }
     }
 
@@ -96,7 +200,10 @@ impl JobType {
             "lidarr_search" => Some(Self::LidarrSearch),
             "cover_art_fetch" => Some(Self::CoverArtFetch),
             "filesystem_scan" => Some(Self::FilesystemScan),
+            "filesystem_scan_preview" => Some(Self::FilesystemScanPreview),
             "playlist_stats_backfill" => Some(Self::PlaylistStatsBackfill),
+            "match_reevaluation" => Some(Self::MatchReevaluation),
+            "artist_image_backfill" => Some(Self::ArtistImageBackfill),
             _ => None,
         }
     }
@@ -245,3 +352,134 @@ impl From<AlbumSource> for String {
         source.as_str().to_string()
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum AlbumView {
+    #[default]
+    Grid,
+    List,
+}
+
+impl AlbumView {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Grid => "grid",
+            Self::List => "list",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "grid" => Some(Self::Grid),
+            "list" => Some(Self::List),
+            _ => None,
+        }
+    }
+}
+
+impl From<AlbumView> for String {
+    fn from(view: AlbumView) -> String {
+        view.as_str().to_string()
+    }
+}
+
+/// Kind of entry in a profile's cached Spotify top items
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TopItemKind {
+    Artist,
+    Track,
+}
+
+impl TopItemKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Artist => "artist",
+            Self::Track => "track",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "artist" => Some(Self::Artist),
+            "track" => Some(Self::Track),
+            _ => None,
+        }
+    }
+}
+
+impl From<TopItemKind> for String {
+    fn from(kind: TopItemKind) -> String {
+        kind.as_str().to_string()
+    }
+}
+
+/// What a collection goal tracks the ownership of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GoalType {
+    Playlist,
+    Artist,
+}
+
+impl GoalType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Playlist => "playlist",
+            Self::Artist => "artist",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "playlist" => Some(Self::Playlist),
+            "artist" => Some(Self::Artist),
+            _ => None,
+        }
+    }
+}
+
+impl From<GoalType> for String {
+    fn from(goal_type: GoalType) -> String {
+        goal_type.as_str().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OwnershipStatus::{self, Downloading, NotOwned, Ordered, Owned};
+
+    #[test]
+    fn test_ownership_transition_matrix() {
+        // (from, to, allowed without override)
+        let matrix = [
+            (NotOwned, NotOwned, true),
+            (NotOwned, Ordered, true),
+            (NotOwned, Downloading, true),
+            (NotOwned, Owned, true),
+            (Ordered, NotOwned, true),
+            (Ordered, Ordered, true),
+            (Ordered, Downloading, true),
+            (Ordered, Owned, true),
+            (Downloading, NotOwned, true),
+            (Downloading, Ordered, false),
+            (Downloading, Downloading, true),
+            (Downloading, Owned, true),
+            (Owned, NotOwned, false),
+            (Owned, Ordered, false),
+            (Owned, Downloading, false),
+            (Owned, Owned, true),
+        ];
+
+        for (from, to, allowed) in matrix {
+            assert_eq!(from.can_transition_to(to, false), allowed, "{:?} -> {:?}", from, to);
+            assert!(from.can_transition_to(to, true), "{:?} -> {:?} with override", from, to);
+        }
+    }
+
+    #[test]
+    fn test_allowed_next_excludes_current_state() {
+        for status in [NotOwned, Ordered, Downloading, Owned] {
+            assert!(!status.allowed_next().contains(&status));
+        }
+        assert_eq!(OwnershipStatus::Owned.allowed_next(), &[] as &[OwnershipStatus]);
+    }
+}
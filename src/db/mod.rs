@@ -1,6 +1,11 @@
+pub mod artist_names;
 pub mod entities;
 pub mod enums;
+pub mod profile;
 pub mod repositories;
+pub mod schema;
+pub mod settings;
+pub mod slug;
 
 pub use entities::*;
 pub use enums::*;
@@ -0,0 +1,191 @@
+//! Profiles: separate Spotify accounts and libraries sharing one instance
+//!
+//! Albums and playlists are stored once and linked into each profile's library
+//! through the `profile_albums` / `profile_playlists` tables, so the same album
+//! saved by two accounts keeps a single metadata, match and ownership record.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{
+    sea_query::{Expr, Query, SimpleExpr},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+use crate::{
+    db::entities::{albums, artists, playlists, profile_albums, profile_playlists, profiles},
+    error::{AppError, Result},
+};
+
+/// Name of the profile created for libraries synced before profiles existed
+pub const DEFAULT_PROFILE_NAME: &str = "Default";
+
+/// Load a profile by ID, falling back to the default (oldest) profile
+///
+/// Unknown IDs (e.g. a stale cookie after a profile was removed) also fall back.
+pub async fn resolve_profile(db: &DatabaseConnection, profile_id: Option<i32>) -> Result<profiles::Model> {
+    if let Some(id) = profile_id {
+        if let Some(profile) = profiles::Entity::find_by_id(id).one(db).await? {
+            return Ok(profile);
+        }
+    }
+
+    default_profile(db).await
+}
+
+/// The default (oldest) profile, created if no profile exists yet
+pub async fn default_profile(db: &DatabaseConnection) -> Result<profiles::Model> {
+    if let Some(profile) = profiles::Entity::find()
+        .order_by_asc(profiles::Column::Id)
+        .one(db)
+        .await?
+    {
+        return Ok(profile);
+    }
+
+    create_profile(db, DEFAULT_PROFILE_NAME).await
+}
+
+/// Create a new, empty profile
+pub async fn create_profile(db: &DatabaseConnection, name: &str) -> Result<profiles::Model> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Profile name must not be empty".to_string()));
+    }
+
+    let existing = profiles::Entity::find()
+        .filter(profiles::Column::Name.eq(name))
+        .one(db)
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::Conflict(format!("Profile '{}' already exists", name)));
+    }
+
+    let profile = profiles::ActiveModel {
+        name: Set(name.to_string()),
+        created_at: Set(Utc::now().into()),
+        updated_at: Set(Utc::now().into()),
+        ..Default::default()
+    };
+
+    Ok(profile.insert(db).await?)
+}
+
+/// Add an album to a profile's library (no-op if already present)
+pub async fn link_album(db: &DatabaseConnection, profile_id: i32, album_id: i32) -> Result<()> {
+    let existing = profile_albums::Entity::find()
+        .filter(profile_albums::Column::ProfileId.eq(profile_id))
+        .filter(profile_albums::Column::AlbumId.eq(album_id))
+        .one(db)
+        .await?;
+
+    if existing.is_none() {
+        profile_albums::ActiveModel {
+            profile_id: Set(profile_id),
+            album_id: Set(album_id),
+            created_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Add a playlist to a profile's library (no-op if already present)
+pub async fn link_playlist(db: &DatabaseConnection, profile_id: i32, playlist_id: i32) -> Result<()> {
+    let existing = profile_playlists::Entity::find()
+        .filter(profile_playlists::Column::ProfileId.eq(profile_id))
+        .filter(profile_playlists::Column::PlaylistId.eq(playlist_id))
+        .one(db)
+        .await?;
+
+    if existing.is_none() {
+        profile_playlists::ActiveModel {
+            profile_id: Set(profile_id),
+            playlist_id: Set(playlist_id),
+            created_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Filter for `albums` queries: only albums in the profile's library
+pub fn albums_in_profile(profile_id: i32) -> SimpleExpr {
+    albums::Column::Id.in_subquery(
+        Query::select()
+            .column(profile_albums::Column::AlbumId)
+            .from(profile_albums::Entity)
+            .and_where(Expr::col(profile_albums::Column::ProfileId).eq(profile_id))
+            .to_owned(),
+    )
+}
+
+/// Filter for `albums` queries: albums the stale wants rule dropped from the
+/// profile's wants
+pub fn wants_cleared_in_profile(profile_id: i32) -> SimpleExpr {
+    albums::Column::Id.in_subquery(
+        Query::select()
+            .column(profile_albums::Column::AlbumId)
+            .from(profile_albums::Entity)
+            .and_where(Expr::col(profile_albums::Column::ProfileId).eq(profile_id))
+            .and_where(Expr::col(profile_albums::Column::WantClearedAt).is_not_null())
+            .to_owned(),
+    )
+}
+
+/// The profile's link to an album, if the album is in its library
+pub async fn album_link(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    album_id: i32,
+) -> Result<Option<profile_albums::Model>> {
+    Ok(profile_albums::Entity::find()
+        .filter(profile_albums::Column::ProfileId.eq(profile_id))
+        .filter(profile_albums::Column::AlbumId.eq(album_id))
+        .one(db)
+        .await?)
+}
+
+/// Want priorities of those of `album_ids` in the profile's library
+pub async fn want_priorities(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    album_ids: impl IntoIterator<Item = i32>,
+) -> Result<HashMap<i32, i32>> {
+    Ok(profile_albums::Entity::find()
+        .filter(profile_albums::Column::ProfileId.eq(profile_id))
+        .filter(profile_albums::Column::AlbumId.is_in(album_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|link| (link.album_id, link.want_priority))
+        .collect())
+}
+
+/// Filter for `playlists` queries: only playlists in the profile's library
+pub fn playlists_in_profile(profile_id: i32) -> SimpleExpr {
+    playlists::Column::Id.in_subquery(
+        Query::select()
+            .column(profile_playlists::Column::PlaylistId)
+            .from(profile_playlists::Entity)
+            .and_where(Expr::col(profile_playlists::Column::ProfileId).eq(profile_id))
+            .to_owned(),
+    )
+}
+
+/// Filter for `artists` queries: only artists with at least one album in the profile's library
+pub fn artists_in_profile(profile_id: i32) -> SimpleExpr {
+    artists::Column::Id.in_subquery(
+        Query::select()
+            .column(albums::Column::ArtistId)
+            .from(albums::Entity)
+            .and_where(albums_in_profile(profile_id))
+            .to_owned(),
+    )
+}
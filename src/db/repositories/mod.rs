@@ -54,6 +54,18 @@ impl ArtistRepository {
     pub async fn create(&self, artist: artists::ActiveModel) -> Result<artists::Model> {
         Ok(artist.insert(&self.db).await?)
     }
+
+    /// Give an artist a user-chosen name and lock it against sync updates
+    ///
+    /// The slug is left alone so existing links keep working.
+    pub async fn rename(&self, artist: artists::Model, name: &str) -> Result<artists::Model> {
+        let mut active: artists::ActiveModel = artist.into();
+        active.name = Set(name.to_string());
+        active.normalized_name = Set(Some(crate::db::artist_names::normalize_name(name)));
+        active.name_locked = Set(true);
+        active.updated_at = Set(chrono::Utc::now().into());
+        Ok(active.update(&self.db).await?)
+    }
 }
 
 pub struct UserSettingsRepository {
@@ -66,42 +78,12 @@ impl UserSettingsRepository {
     }
 
     pub async fn get_settings(&self) -> Result<Option<user_settings::Model>> {
-        Ok(user_settings::Entity::find().one(&self.db).await?)
+        Ok(crate::db::settings::shared(&self.db).await?)
     }
 
+    /// Save the settings set in `settings` to the shared row, creating it if needed
     pub async fn create_or_update(&self, settings: user_settings::ActiveModel) -> Result<user_settings::Model> {
-        // Check if settings exist
-        if let Some(existing) = user_settings::Entity::find().one(&self.db).await? {
-            let mut active: user_settings::ActiveModel = existing.into();
-            // Update fields from new settings
-            if let Set(val) = settings.spotify_access_token {
-                active.spotify_access_token = Set(val);
-            }
-            if let Set(val) = settings.spotify_refresh_token {
-                active.spotify_refresh_token = Set(val);
-            }
-            if let Set(val) = settings.spotify_token_expires_at {
-                active.spotify_token_expires_at = Set(val);
-            }
-            if let Set(val) = settings.lidarr_url {
-                active.lidarr_url = Set(val);
-            }
-            if let Set(val) = settings.lidarr_api_key {
-                active.lidarr_api_key = Set(val);
-            }
-            if let Set(val) = settings.music_folder_path {
-                active.music_folder_path = Set(val);
-            }
-            if let Set(val) = settings.auto_sync_enabled {
-                active.auto_sync_enabled = Set(val);
-            }
-            if let Set(val) = settings.sync_interval_hours {
-                active.sync_interval_hours = Set(val);
-            }
-            Ok(active.update(&self.db).await?)
-        } else {
-            Ok(settings.insert(&self.db).await?)
-        }
+        crate::db::settings::save_shared(&self.db, settings).await
     }
 }
 
@@ -0,0 +1,75 @@
+//! Schema compatibility checks between the database and this binary
+//!
+//! Rolling back to an older build after a newer one has migrated the database
+//! leaves the schema ahead of the code. Rather than failing later with opaque
+//! column errors, startup compares the applied migrations against the ones this
+//! binary ships with and refuses to run against unknown migrations.
+
+use std::collections::HashSet;
+
+use migration::MigratorTrait;
+use sea_orm::DatabaseConnection;
+
+use crate::error::{AppError, Result};
+
+/// Applied migrations compared against the migrations known to this binary
+#[derive(Debug, Clone)]
+pub struct SchemaStatus {
+    /// Every migration recorded in the database, oldest first
+    pub applied: Vec<String>,
+    /// Applied migrations that this binary does not know about
+    pub unknown: Vec<String>,
+}
+
+impl SchemaStatus {
+    pub fn is_ahead(&self) -> bool {
+        !self.unknown.is_empty()
+    }
+}
+
+/// Read the applied migrations and work out which ones this binary doesn't know
+pub async fn schema_status(db: &DatabaseConnection) -> Result<SchemaStatus> {
+    let known: HashSet<String> = migration::Migrator::migrations()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+
+    let applied: Vec<String> = migration::Migrator::get_migration_models(db)
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+
+    let unknown = applied
+        .iter()
+        .filter(|version| !known.contains(*version))
+        .cloned()
+        .collect();
+
+    Ok(SchemaStatus { applied, unknown })
+}
+
+/// Fail if the database has migrations this binary doesn't know about
+///
+/// Set `allow_ahead` (`ALLOW_SCHEMA_AHEAD=true`) to log a warning and continue instead.
+pub async fn check_schema_compatibility(db: &DatabaseConnection, allow_ahead: bool) -> Result<SchemaStatus> {
+    let status = schema_status(db).await?;
+
+    if status.is_ahead() {
+        let message = format!(
+            "Database schema is ahead of this binary (beat-collector {}). Unknown migrations: {}. \
+             Run a version of beat-collector that includes these migrations, \
+             or set ALLOW_SCHEMA_AHEAD=true to start anyway.",
+            env!("CARGO_PKG_VERSION"),
+            status.unknown.join(", ")
+        );
+
+        if !allow_ahead {
+            return Err(AppError::Configuration(message));
+        }
+
+        tracing::warn!("{} Continuing because ALLOW_SCHEMA_AHEAD is set.", message);
+    }
+
+    Ok(status)
+}
@@ -0,0 +1,429 @@
+//! User settings: one shared row plus optional per-profile rows
+//!
+//! The row without a profile holds the instance-wide settings. The default
+//! profile reads it, and so do scheduled jobs nobody queued, so a
+//! single-account install behaves exactly as before. Another profile gets its
+//! own row the first time it changes a setting, copied from the shared row.
+//! From then on that row answers the profile's settings requests, and the
+//! jobs that profile queues.
+//!
+//! The shared row is created and changed through `save_shared`, an upsert on
+//! `SHARED_ID`, so saves racing to create it end up in one row.
+
+use chrono::Utc;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, Iterable, QueryFilter, QueryOrder, Set,
+};
+
+use crate::{
+    db::{
+        entities::{profiles, user_settings},
+        enums::AlbumView,
+        profile,
+    },
+    error::{AppError, Result},
+};
+
+/// Id of the shared settings row; id sequences start at 1, so it's never taken
+pub const SHARED_ID: i32 = 0;
+
+/// The shared settings row, if settings were ever saved
+pub async fn shared(db: &DatabaseConnection) -> Result<Option<user_settings::Model>> {
+    Ok(user_settings::Entity::find()
+        .filter(user_settings::Column::ProfileId.is_null())
+        .order_by_asc(user_settings::Column::Id)
+        .one(db)
+        .await?)
+}
+
+/// Write the settings set in `changes` to the shared row, creating it if needed
+///
+/// Settings left unset keep their stored value, so concurrent saves of
+/// different settings are all kept.
+pub async fn save_shared(
+    db: &DatabaseConnection,
+    mut changes: user_settings::ActiveModel,
+) -> Result<user_settings::Model> {
+    let now = Utc::now();
+    changes.updated_at = Set(now.into());
+    let changed: Vec<user_settings::Column> = user_settings::Column::iter()
+        .filter(|column| {
+            !matches!(column, user_settings::Column::Id | user_settings::Column::CreatedAt)
+                && changes.get(*column).is_set()
+        })
+        .collect();
+
+    // A shared row saved before it had a fixed id keeps its own
+    let id = shared(db).await?.map_or(SHARED_ID, |row| row.id);
+    changes.id = Set(id);
+    changes.profile_id = Set(None);
+    if changes.created_at.is_not_set() {
+        changes.created_at = Set(now.into());
+    }
+    if changes.album_view.is_not_set() {
+        changes.album_view = Set(AlbumView::default().as_str().to_string());
+    }
+
+    user_settings::Entity::insert(changes)
+        .on_conflict(OnConflict::column(user_settings::Column::Id).update_columns(changed).to_owned())
+        .exec_without_returning(db)
+        .await?;
+
+    user_settings::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::Internal("Shared settings row missing after save".to_string()))
+}
+
+/// Profile that a settings row for `profile` belongs to; `None` for the
+/// default profile, which uses the shared row
+pub async fn scope(db: &DatabaseConnection, profile: &profiles::Model) -> Result<Option<i32>> {
+    let default = profile::default_profile(db).await?;
+    Ok((profile.id != default.id).then_some(profile.id))
+}
+
+/// Settings in effect for `profile`: its own row, or the shared one
+pub async fn for_profile(
+    db: &DatabaseConnection,
+    profile: &profiles::Model,
+) -> Result<Option<user_settings::Model>> {
+    match scope(db, profile).await? {
+        Some(profile_id) => match own_row(db, profile_id).await? {
+            Some(own) => Ok(Some(own)),
+            None => shared(db).await,
+        },
+        None => shared(db).await,
+    }
+}
+
+/// Settings in effect for the profile with `profile_id`, or for the default
+/// profile if `None` (e.g. a scheduled job)
+pub async fn for_profile_id(db: &DatabaseConnection, profile_id: Option<i32>) -> Result<Option<user_settings::Model>> {
+    let profile = profile::resolve_profile(db, profile_id).await?;
+    for_profile(db, &profile).await
+}
+
+/// Write the settings set in `changes` to `profile`'s row
+///
+/// The default profile saves through `save_shared`; another profile's row is
+/// created from the shared one first, as with `for_update`.
+pub async fn save_for_profile(
+    db: &DatabaseConnection,
+    profile: &profiles::Model,
+    mut changes: user_settings::ActiveModel,
+) -> Result<user_settings::Model> {
+    let Some(profile_id) = scope(db, profile).await? else {
+        return save_shared(db, changes).await;
+    };
+
+    let own = match for_update(db, profile).await? {
+        Some(own) => own,
+        None => {
+            let now = Utc::now();
+            let blank = user_settings::ActiveModel {
+                album_view: Set(AlbumView::default().as_str().to_string()),
+                profile_id: Set(Some(profile_id)),
+                created_at: Set(now.into()),
+                updated_at: Set(now.into()),
+                ..Default::default()
+            };
+            insert_own_row(db, profile_id, blank).await?
+        }
+    };
+
+    changes.id = Set(own.id);
+    changes.profile_id = Set(Some(profile_id));
+    changes.created_at = NotSet;
+    changes.updated_at = Set(Utc::now().into());
+    Ok(changes.update(db).await?)
+}
+
+/// The row `profile`'s settings changes are written to
+///
+/// A non-default profile's first change copies the shared row so it starts
+/// from the current settings. `None` means no settings exist yet.
+pub async fn for_update(
+    db: &DatabaseConnection,
+    profile: &profiles::Model,
+) -> Result<Option<user_settings::Model>> {
+    let Some(profile_id) = scope(db, profile).await? else {
+        return shared(db).await;
+    };
+    if let Some(own) = own_row(db, profile_id).await? {
+        return Ok(Some(own));
+    }
+    let Some(shared) = shared(db).await? else {
+        return Ok(None);
+    };
+
+    let mut copy = shared.into_active_model().reset_all();
+    copy.id = NotSet;
+    copy.profile_id = Set(Some(profile_id));
+    copy.created_at = Set(Utc::now().into());
+    copy.updated_at = Set(Utc::now().into());
+    Ok(Some(insert_own_row(db, profile_id, copy).await?))
+}
+
+/// Insert `row` as `profile_id`'s own settings and return the stored row
+///
+/// A concurrent first save may have inserted the row already; the unique
+/// index turns the second insert into a no-op and both read the same row.
+async fn insert_own_row(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    row: user_settings::ActiveModel,
+) -> Result<user_settings::Model> {
+    user_settings::Entity::insert(row)
+        .on_conflict(OnConflict::new().do_nothing().to_owned())
+        .exec_without_returning(db)
+        .await?;
+
+    own_row(db, profile_id)
+        .await?
+        .ok_or_else(|| AppError::Internal(format!("Settings row of profile {} missing after save", profile_id)))
+}
+
+async fn own_row(db: &DatabaseConnection, profile_id: i32) -> Result<Option<user_settings::Model>> {
+    Ok(user_settings::Entity::find()
+        .filter(user_settings::Column::ProfileId.eq(profile_id))
+        .one(db)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::UserSettingsRepository;
+    use sea_orm::QuerySelect;
+    use crate::test_utils::setup_test_db;
+
+    async fn insert_shared_row(db: &DatabaseConnection, lidarr_url: &str) {
+        user_settings::ActiveModel {
+            lidarr_url: Set(Some(lidarr_url.to_string())),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_first_saves_share_one_row() {
+        let db = setup_test_db().await;
+        let saves = [
+            user_settings::ActiveModel {
+                lidarr_url: Set(Some("http://lidarr:8686".to_string())),
+                ..Default::default()
+            },
+            user_settings::ActiveModel {
+                locale: Set(Some("de".to_string())),
+                ..Default::default()
+            },
+            user_settings::ActiveModel {
+                log_level: Set(Some("debug".to_string())),
+                ..Default::default()
+            },
+        ];
+
+        let handles: Vec<_> = saves
+            .into_iter()
+            .map(|changes| {
+                let repository = UserSettingsRepository::new(db.clone());
+                tokio::spawn(async move { repository.create_or_update(changes).await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        let rows = user_settings::Entity::find().all(&db).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, SHARED_ID);
+        assert_eq!(rows[0].lidarr_url.as_deref(), Some("http://lidarr:8686"));
+        assert_eq!(rows[0].locale.as_deref(), Some("de"));
+        assert_eq!(rows[0].log_level.as_deref(), Some("debug"));
+
+        // Another profile's first saves race to copy the shared row
+        profile::default_profile(&db).await.unwrap();
+        let partner = profile::create_profile(&db, "Partner").await.unwrap();
+        let saves = [
+            user_settings::ActiveModel {
+                locale: Set(Some("en".to_string())),
+                ..Default::default()
+            },
+            user_settings::ActiveModel {
+                timezone: Set(Some("Europe/Berlin".to_string())),
+                ..Default::default()
+            },
+            user_settings::ActiveModel {
+                log_level: Set(Some("warn".to_string())),
+                ..Default::default()
+            },
+        ];
+        let handles: Vec<_> = saves
+            .into_iter()
+            .map(|changes| {
+                let db = db.clone();
+                let partner = partner.clone();
+                tokio::spawn(async move { save_for_profile(&db, &partner, changes).await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        let own = user_settings::Entity::find()
+            .filter(user_settings::Column::ProfileId.eq(partner.id))
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(own.len(), 1);
+        assert_eq!(own[0].lidarr_url.as_deref(), Some("http://lidarr:8686"));
+        assert_eq!(own[0].locale.as_deref(), Some("en"));
+        assert_eq!(own[0].timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(own[0].log_level.as_deref(), Some("warn"));
+    }
+
+    #[tokio::test]
+    async fn test_migration_collapses_duplicate_rows() {
+        use migration::MigratorTrait;
+        use sea_orm::ConnectionTrait;
+
+        let db = sea_orm::Database::connect("sqlite::memory:").await.unwrap();
+        let before_fix = migration::Migrator::migrations()
+            .iter()
+            .position(|m| m.name() == "m20240101_000051_add_user_settings_single_shared_row")
+            .unwrap() as u32;
+        migration::Migrator::up(&db, Some(before_fix)).await.unwrap();
+
+        let saved_at = |hours_ago: i64| -> sea_orm::prelude::DateTimeWithTimeZone {
+            (Utc::now() - chrono::Duration::hours(hours_ago)).into()
+        };
+        for (hours_ago, lidarr_url, locale) in [
+            (3, Some("http://old:8686"), Some("de")),
+            (2, Some("http://new:8686"), None),
+            (1, None, None),
+        ] {
+            // Only columns that exist before the fix; the entity knows later ones too
+            let insert = sea_orm::sea_query::Query::insert()
+                .into_table(user_settings::Entity)
+                .columns([
+                    user_settings::Column::LidarrUrl,
+                    user_settings::Column::Locale,
+                    user_settings::Column::AlbumView,
+                    user_settings::Column::CreatedAt,
+                    user_settings::Column::UpdatedAt,
+                ])
+                .values_panic([
+                    lidarr_url.map(String::from).into(),
+                    locale.map(String::from).into(),
+                    format!("view-{}", hours_ago).into(),
+                    saved_at(hours_ago).into(),
+                    saved_at(hours_ago).into(),
+                ])
+                .to_owned();
+            db.execute(db.get_database_backend().build(&insert)).await.unwrap();
+        }
+
+        // Two rows for one profile, as in a database restored without its indexes
+        db.execute_unprepared("DROP INDEX idx_user_settings_profile_id").await.unwrap();
+        let insert = sea_orm::sea_query::Query::insert()
+            .into_table(profiles::Entity)
+            .columns([profiles::Column::Name, profiles::Column::CreatedAt, profiles::Column::UpdatedAt])
+            .values_panic(["Partner".into(), saved_at(5).into(), saved_at(5).into()])
+            .to_owned();
+        db.execute(db.get_database_backend().build(&insert)).await.unwrap();
+        let partner = profiles::Entity::find()
+            .filter(profiles::Column::Name.eq("Partner"))
+            .select_only()
+            .column(profiles::Column::Id)
+            .into_tuple::<i32>()
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        for (hours_ago, timezone) in [(2, Some("Europe/Berlin")), (1, None)] {
+            let insert = sea_orm::sea_query::Query::insert()
+                .into_table(user_settings::Entity)
+                .columns([
+                    user_settings::Column::ProfileId,
+                    user_settings::Column::Timezone,
+                    user_settings::Column::AlbumView,
+                    user_settings::Column::CreatedAt,
+                    user_settings::Column::UpdatedAt,
+                ])
+                .values_panic([
+                    partner.into(),
+                    timezone.map(String::from).into(),
+                    format!("partner-view-{}", hours_ago).into(),
+                    saved_at(hours_ago).into(),
+                    saved_at(hours_ago).into(),
+                ])
+                .to_owned();
+            db.execute(db.get_database_backend().build(&insert)).await.unwrap();
+        }
+        migration::Migrator::up(&db, None).await.unwrap();
+
+        let own = user_settings::Entity::find()
+            .filter(user_settings::Column::ProfileId.eq(partner))
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(own.len(), 1);
+        assert_eq!(own[0].album_view, "partner-view-1");
+        assert_eq!(own[0].timezone.as_deref(), Some("Europe/Berlin"));
+
+        // The newest row survives, with each setting from the newest row that had it
+        let rows = user_settings::Entity::find()
+            .filter(user_settings::Column::ProfileId.is_null())
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, SHARED_ID);
+        assert_eq!(rows[0].album_view, "view-1");
+        assert_eq!(rows[0].lidarr_url.as_deref(), Some("http://new:8686"));
+        assert_eq!(rows[0].locale.as_deref(), Some("de"));
+
+        // A second shared row can no longer be inserted
+        let duplicate = user_settings::ActiveModel {
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await;
+        assert!(duplicate.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_default_profile_uses_shared_row() {
+        let db = setup_test_db().await;
+        insert_shared_row(&db, "http://shared:8686").await;
+        let default = profile::default_profile(&db).await.unwrap();
+
+        let row = for_update(&db, &default).await.unwrap().unwrap();
+        assert_eq!(row.profile_id, None);
+        assert_eq!(user_settings::Entity::find().all(&db).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_other_profile_copies_shared_row_on_first_update() {
+        let db = setup_test_db().await;
+        insert_shared_row(&db, "http://shared:8686").await;
+        profile::default_profile(&db).await.unwrap();
+        let other = profile::create_profile(&db, "Partner").await.unwrap();
+
+        // Reads fall back to the shared row until the profile saves something
+        assert_eq!(for_profile(&db, &other).await.unwrap().unwrap().profile_id, None);
+
+        let own = for_update(&db, &other).await.unwrap().unwrap();
+        assert_eq!(own.profile_id, Some(other.id));
+        assert_eq!(own.lidarr_url.as_deref(), Some("http://shared:8686"));
+        assert_eq!(for_profile(&db, &other).await.unwrap().unwrap().id, own.id);
+        assert_eq!(shared(&db).await.unwrap().unwrap().profile_id, None);
+    }
+}
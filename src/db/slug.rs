@@ -0,0 +1,300 @@
+//! Human-readable URL slugs for artists and albums
+//!
+//! A slug is generated once, from the artist name or "artist title" for albums,
+//! and never regenerated, so links stay valid when a name is later corrected.
+//! Detail routes accept either the numeric ID or the slug; generated slugs are
+//! never purely numeric, so the two cannot be confused.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
+};
+
+use crate::{
+    db::entities::{albums, artists},
+    error::Result,
+};
+
+/// Longest slug generated from a name (before any collision suffix)
+const MAX_SLUG_LEN: usize = 80;
+
+/// Turn arbitrary text into a lowercase ASCII slug
+///
+/// Unicode is transliterated ("Björk" → "bjork", "Sigur Rós" → "sigur-ros"),
+/// `&` becomes "and", apostrophes are dropped and every other run of
+/// non-alphanumeric characters collapses into a single dash. May return an
+/// empty string for text with nothing to transliterate.
+pub fn slugify(text: &str) -> String {
+    let ascii = deunicode::deunicode(text);
+    let mut slug = String::with_capacity(ascii.len());
+    let mut pending_dash = false;
+
+    for c in ascii.chars() {
+        if c.is_ascii_alphanumeric() {
+            if pending_dash && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_dash = false;
+            slug.push(c.to_ascii_lowercase());
+        } else if c == '&' {
+            if !slug.is_empty() {
+                slug.push('-');
+            }
+            slug.push_str("and");
+            pending_dash = true;
+        } else if c != '\'' && c != '`' {
+            pending_dash = true;
+        }
+    }
+
+    if slug.len() > MAX_SLUG_LEN {
+        slug.truncate(MAX_SLUG_LEN);
+        let trimmed = slug.trim_end_matches('-').len();
+        slug.truncate(trimmed);
+    }
+
+    slug
+}
+
+/// Slug for a name, falling back to `kind` for empty results and prefixing it
+/// to purely numeric ones (which would read as IDs)
+fn base_slug(text: &str, kind: &str) -> String {
+    let slug = slugify(text);
+    if slug.is_empty() {
+        kind.to_string()
+    } else if slug.chars().all(|c| c.is_ascii_digit()) {
+        format!("{}-{}", kind, slug)
+    } else {
+        slug
+    }
+}
+
+/// First of `base`, `base-2`, `base-3`, ... not already taken
+fn first_free(base: String, taken: &HashSet<String>) -> String {
+    if !taken.contains(&base) {
+        return base;
+    }
+
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("unbounded suffix search always finds a free slug")
+}
+
+/// Unused slug for a new artist
+pub async fn new_artist_slug<C: ConnectionTrait>(db: &C, name: &str) -> Result<String> {
+    let base = base_slug(name, "artist");
+    let taken: HashSet<String> = artists::Entity::find()
+        .select_only()
+        .column(artists::Column::Slug)
+        .filter(artists::Column::Slug.starts_with(&base))
+        .into_tuple::<Option<String>>()
+        .all(db)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    Ok(first_free(base, &taken))
+}
+
+/// Unused slug for a new album, built from the artist name and album title
+pub async fn new_album_slug<C: ConnectionTrait>(db: &C, artist_name: &str, title: &str) -> Result<String> {
+    let base = base_slug(&format!("{} {}", artist_name, title), "album");
+    let taken: HashSet<String> = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::Slug)
+        .filter(albums::Column::Slug.starts_with(&base))
+        .into_tuple::<Option<String>>()
+        .all(db)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    Ok(first_free(base, &taken))
+}
+
+/// Assign slugs to artists and albums that don't have one yet
+///
+/// Runs on startup so rows created before slugs existed get one. Rows are
+/// processed oldest first, so the oldest of several same-named artists keeps
+/// the unsuffixed slug. Existing slugs are never touched.
+pub async fn backfill_slugs(db: &DatabaseConnection) -> Result<u64> {
+    let mut assigned = 0;
+
+    let artists_without_slug = artists::Entity::find()
+        .filter(artists::Column::Slug.is_null())
+        .order_by_asc(artists::Column::Id)
+        .all(db)
+        .await?;
+
+    for artist in artists_without_slug {
+        let slug = new_artist_slug(db, &artist.name).await?;
+        let mut active: artists::ActiveModel = artist.into();
+        active.slug = Set(Some(slug));
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+        assigned += 1;
+    }
+
+    let albums_without_slug = albums::Entity::find()
+        .filter(albums::Column::Slug.is_null())
+        .order_by_asc(albums::Column::Id)
+        .find_also_related(artists::Entity)
+        .all(db)
+        .await?;
+
+    for (album, artist) in albums_without_slug {
+        let artist_name = artist.map(|a| a.name).unwrap_or_default();
+        let slug = new_album_slug(db, &artist_name, &album.title).await?;
+        let mut active: albums::ActiveModel = album.into();
+        active.slug = Set(Some(slug));
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+        assigned += 1;
+    }
+
+    if assigned > 0 {
+        tracing::info!("Assigned slugs to {} artists and albums", assigned);
+    }
+
+    Ok(assigned)
+}
+
+/// Path segment identifying a row in links: its slug, or its ID if it has none yet
+pub fn link_key(slug: Option<&str>, id: i32) -> String {
+    slug.map(str::to_string).unwrap_or_else(|| id.to_string())
+}
+
+/// Find an artist by numeric ID or slug
+pub async fn find_artist(db: &DatabaseConnection, key: &str) -> Result<Option<artists::Model>> {
+    match key.parse::<i32>() {
+        Ok(id) => Ok(artists::Entity::find_by_id(id).one(db).await?),
+        Err(_) => Ok(artists::Entity::find()
+            .filter(artists::Column::Slug.eq(key))
+            .one(db)
+            .await?),
+    }
+}
+
+/// Find an album by numeric ID or slug
+pub async fn find_album(db: &DatabaseConnection, key: &str) -> Result<Option<albums::Model>> {
+    match key.parse::<i32>() {
+        Ok(id) => Ok(albums::Entity::find_by_id(id).one(db).await?),
+        Err(_) => Ok(albums::Entity::find()
+            .filter(albums::Column::Slug.eq(key))
+            .one(db)
+            .await?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_slugify_ascii() {
+        assert_eq!(slugify("OK Computer"), "ok-computer");
+        assert_eq!(slugify("  Kid A  "), "kid-a");
+        assert_eq!(slugify("Hail to the Thief (Special Edition)"), "hail-to-the-thief-special-edition");
+        assert_eq!(slugify("Simon & Garfunkel"), "simon-and-garfunkel");
+        assert_eq!(slugify("Don't Stop Me Now"), "dont-stop-me-now");
+        assert_eq!(slugify("AC/DC"), "ac-dc");
+    }
+
+    #[test]
+    fn test_slugify_unicode() {
+        assert_eq!(slugify("Björk"), "bjork");
+        assert_eq!(slugify("Sigur Rós"), "sigur-ros");
+        assert_eq!(slugify("Mötley Crüe"), "motley-crue");
+        assert_eq!(slugify("Beyoncé – Lemonade"), "beyonce-lemonade");
+        assert!(!slugify("坂本龍一").is_empty());
+        assert!(slugify("坂本龍一").chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+    }
+
+    #[test]
+    fn test_slugify_empty_and_long() {
+        assert_eq!(slugify("!!!"), "");
+        assert_eq!(slugify(""), "");
+
+        let long = slugify(&"word ".repeat(40));
+        assert!(long.len() <= MAX_SLUG_LEN);
+        assert!(!long.ends_with('-'));
+    }
+
+    #[test]
+    fn test_base_slug_fallbacks() {
+        assert_eq!(base_slug("!!!", "artist"), "artist");
+        assert_eq!(base_slug("1975", "artist"), "artist-1975");
+        assert_eq!(base_slug("The 1975", "artist"), "the-1975");
+    }
+
+    #[test]
+    fn test_first_free_suffixes() {
+        let taken: HashSet<String> = ["genesis", "genesis-2"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(first_free("genesis".to_string(), &taken), "genesis-3");
+        assert_eq!(first_free("yes".to_string(), &taken), "yes");
+    }
+
+    #[tokio::test]
+    async fn test_backfill_assigns_unique_slugs() {
+        let db = setup_test_db().await;
+        let first = create_test_artist(&db, "Genesis", None).await;
+        let second = create_test_artist(&db, "Genesis", None).await;
+        let album = create_test_album(&db, first.id, "Selling England by the Pound", None).await;
+
+        // Simulate rows created before slugs existed
+        for artist in [first.clone(), second.clone()] {
+            let mut active: artists::ActiveModel = artist.into();
+            active.slug = Set(None);
+            active.update(&db).await.unwrap();
+        }
+        let mut active: albums::ActiveModel = album.clone().into();
+        active.slug = Set(None);
+        active.update(&db).await.unwrap();
+
+        assert_eq!(backfill_slugs(&db).await.unwrap(), 3);
+
+        let first = find_artist(&db, "genesis").await.unwrap().unwrap();
+        let second_found = find_artist(&db, "genesis-2").await.unwrap().unwrap();
+        assert_eq!(second_found.id, second.id);
+        assert_eq!(first.slug.as_deref(), Some("genesis"));
+
+        let album = find_album(&db, "genesis-selling-england-by-the-pound").await.unwrap();
+        assert!(album.is_some());
+
+        // Nothing left to do on a second run
+        assert_eq!(backfill_slugs(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_slug_stable_after_rename() {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Prince", None).await;
+        let slug = artist.slug.clone().unwrap();
+
+        let mut active: artists::ActiveModel = artist.into();
+        active.name = Set("The Artist Formerly Known as Prince".to_string());
+        active.update(&db).await.unwrap();
+        backfill_slugs(&db).await.unwrap();
+
+        let renamed = find_artist(&db, &slug).await.unwrap().unwrap();
+        assert_eq!(renamed.slug, Some(slug));
+    }
+
+    #[tokio::test]
+    async fn test_find_by_id_or_slug() {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Radiohead", None).await;
+
+        let by_id = find_artist(&db, &artist.id.to_string()).await.unwrap().unwrap();
+        let by_slug = find_artist(&db, "radiohead").await.unwrap().unwrap();
+        assert_eq!(by_id.id, by_slug.id);
+        assert!(find_artist(&db, "unknown").await.unwrap().is_none());
+    }
+}
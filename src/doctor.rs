@@ -0,0 +1,196 @@
+//! Startup diagnostics (`beat-collector --check`)
+//!
+//! Validates the configuration and tries each external connection with a
+//! short timeout, so a misconfigured deployment can be checked without
+//! starting the server. Every check runs even after one fails, so a single
+//! run shows everything that needs fixing.
+
+use std::time::Duration;
+
+use reqwest::{redirect, Client, StatusCode};
+use sea_orm::{ConnectOptions, Database};
+
+use crate::config::Config;
+
+/// How long each connection attempt may take
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of one diagnostic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: &'static str,
+    /// What was found on success, or what went wrong
+    pub outcome: Result<String, String>,
+}
+
+impl CheckResult {
+    pub fn passed(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+/// Run every check against `config`
+pub async fn run_checks(config: &Config) -> Vec<CheckResult> {
+    vec![
+        CheckResult {
+            name: "Configuration",
+            outcome: config
+                .validate()
+                .map(|_| "valid".to_string())
+                .map_err(|e| e.0.join("; ")),
+        },
+        CheckResult {
+            name: "Database",
+            outcome: check_database(&config.database_url).await,
+        },
+        CheckResult {
+            name: "Redis",
+            outcome: check_redis(&config.redis_url).await,
+        },
+        CheckResult {
+            name: "Spotify client",
+            outcome: check_spotify_client(config).await,
+        },
+    ]
+}
+
+/// Results as a table, one row per check
+pub fn render_table(results: &[CheckResult]) -> String {
+    let width = results.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let mut table = String::new();
+    for result in results {
+        let (mark, detail) = match &result.outcome {
+            Ok(detail) => ("✅", detail),
+            Err(detail) => ("❌", detail),
+        };
+        table.push_str(&format!("{} {:<width$}  {}\n", mark, result.name, detail, width = width));
+    }
+    table
+}
+
+async fn check_database(url: &str) -> Result<String, String> {
+    let mut options = ConnectOptions::new(url);
+    options.connect_timeout(CHECK_TIMEOUT).sqlx_logging(false);
+
+    let db = tokio::time::timeout(CHECK_TIMEOUT, Database::connect(options))
+        .await
+        .map_err(|_| format!("no connection after {}s", CHECK_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("cannot connect: {}", e))?;
+    db.ping().await.map_err(|e| format!("connected but ping failed: {}", e))?;
+
+    Ok("connected".to_string())
+}
+
+async fn check_redis(url: &str) -> Result<String, String> {
+    let client = redis::Client::open(url).map_err(|e| format!("invalid URL: {}", e))?;
+
+    let mut conn = tokio::time::timeout(CHECK_TIMEOUT, client.get_multiplexed_tokio_connection())
+        .await
+        .map_err(|_| format!("no connection after {}s", CHECK_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("cannot connect: {}", e))?;
+    let _: String = redis::cmd("PING")
+        .query_async(&mut conn)
+        .await
+        .map_err(|e| format!("connected but PING failed: {}", e))?;
+
+    Ok("connected".to_string())
+}
+
+/// Start an authorization request without following it
+///
+/// Spotify redirects to its login page when the client ID and redirect URI
+/// are registered, and answers 400 when either is not.
+async fn check_spotify_client(config: &Config) -> Result<String, String> {
+    let client = Client::builder()
+        .redirect(redirect::Policy::none())
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let url = format!("{}/authorize", config.spotify_accounts_base.trim_end_matches('/'));
+    let response = client
+        .get(&url)
+        .query(&[
+            ("client_id", config.spotify_client_id.as_str()),
+            ("response_type", "code"),
+            ("redirect_uri", config.spotify_redirect_uri.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("cannot reach {}: {}", config.spotify_accounts_base, e))?;
+
+    let status = response.status();
+    if status.is_redirection() {
+        return Ok("client ID and redirect URI accepted".to_string());
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let reason = body.lines().find(|l| l.contains("INVALID")).unwrap_or(body.trim());
+    Err(match status {
+        StatusCode::BAD_REQUEST => format!(
+            "Spotify rejected the app ({}); check SPOTIFY_CLIENT_ID and that SPOTIFY_REDIRECT_URI is registered for it",
+            reason.trim()
+        ),
+        _ => format!("unexpected response {} from {}", status, url),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::test_utils::test_config;
+
+    fn spotify_config(server: &MockServer) -> Config {
+        Config {
+            spotify_accounts_base: server.uri(),
+            ..test_config()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spotify_client_check() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/authorize"))
+            .and(query_param("client_id", "test_client_id"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/login"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/authorize"))
+            .and(query_param("client_id", "unknown"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("INVALID_CLIENT: Invalid client"))
+            .mount(&server)
+            .await;
+
+        assert!(check_spotify_client(&spotify_config(&server)).await.is_ok());
+
+        let config = Config {
+            spotify_client_id: "unknown".to_string(),
+            ..spotify_config(&server)
+        };
+        let error = check_spotify_client(&config).await.unwrap_err();
+        assert!(error.contains("INVALID_CLIENT: Invalid client"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_connection_checks() {
+        assert!(check_database("sqlite::memory:").await.is_ok());
+        assert!(check_redis("redis://127.0.0.1:1").await.is_err());
+        assert!(check_redis("localhost:6379").await.unwrap_err().starts_with("invalid URL"));
+    }
+
+    #[test]
+    fn test_render_table() {
+        let table = render_table(&[
+            CheckResult { name: "Database", outcome: Ok("connected".to_string()) },
+            CheckResult { name: "Redis", outcome: Err("cannot connect".to_string()) },
+        ]);
+        assert_eq!(table, "✅ Database  connected\n❌ Redis     cannot connect\n");
+    }
+}
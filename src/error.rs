@@ -1,11 +1,23 @@
 use axum::{
-    http::StatusCode,
+    extract::{
+        rejection::{FormRejection, JsonRejection},
+        OriginalUri, Request,
+    },
+    http::{header, StatusCode},
+    middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 
+use crate::db::enums::OwnershipStatus;
+use crate::jobs::queue::{SubmitError, QUEUE_FULL_RETRY_AFTER_SECS};
+
+/// Most of a non-JSON error body kept as the message
+const REJECTION_BODY_LIMIT: usize = 64 * 1024;
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
@@ -26,59 +38,353 @@ pub enum AppError {
     #[error("Authentication error: {0}")]
     Authentication(String),
 
+    #[error("Spotify token is missing the {0} scope; reconnect Spotify")]
+    InsufficientScope(String),
+
     #[error("External API error: {0}")]
     ExternalApi(String),
 
     #[error("Configuration error: {0}")]
     Configuration(String),
 
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    #[error("Cannot change ownership from {} to {}", .from.as_str(), .to.as_str())]
+    OwnershipConflict {
+        from: OwnershipStatus,
+        to: OwnershipStatus,
+    },
+
+    /// The request clashes with existing data, such as a name already taken
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Job queue is full: {0}")]
+    QueueFull(String),
+
     #[error("Internal server error: {0}")]
     Internal(String),
 
+    /// A request turned away before a handler could answer, such as a
+    /// malformed JSON body, kept with the status axum gave it
+    #[error("Request rejected ({0}): {1}")]
+    Rejected(StatusCode, String),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            Self::Database(ref e) => {
+/// Machine-readable kind of an API error, stable across releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    Validation,
+    ExternalApi,
+    Authentication,
+    Conflict,
+    /// Too many jobs are waiting; retry after the `Retry-After` header
+    QueueFull,
+    Internal,
+}
+
+impl ErrorCode {
+    /// Code for an error response made outside a handler, from its status
+    fn for_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Authentication,
+            StatusCode::CONFLICT => Self::Conflict,
+            StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => Self::ExternalApi,
+            status if status.is_client_error() => Self::Validation,
+            _ => Self::Internal,
+        }
+    }
+}
+
+/// Body of every API error response: `{"error": {"code", "message", "details"?}}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    pub code: ErrorCode,
+    /// Human-readable explanation, safe to show to users
+    pub message: String,
+    /// Structured extras for some errors, such as the allowed next ownership statuses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::Validation(_) => ErrorCode::Validation,
+            Self::HttpRequest(_) | Self::ExternalApi(_) => ErrorCode::ExternalApi,
+            Self::Authentication(_) | Self::InsufficientScope(_) => ErrorCode::Authentication,
+            Self::OwnershipConflict { .. } | Self::Conflict(_) => ErrorCode::Conflict,
+            Self::QueueFull(_) => ErrorCode::QueueFull,
+            Self::Rejected(status, _) => ErrorCode::for_status(*status),
+            Self::Database(_)
+            | Self::Redis(_)
+            | Self::Serialization(_)
+            | Self::Configuration(_)
+            | Self::Internal(_)
+            | Self::Other(_) => ErrorCode::Internal,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::HttpRequest(_) | Self::ExternalApi(_) => StatusCode::BAD_GATEWAY,
+            Self::Authentication(_) => StatusCode::UNAUTHORIZED,
+            Self::InsufficientScope(_) => StatusCode::FORBIDDEN,
+            Self::OwnershipConflict { .. } | Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::QueueFull(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Rejected(status, _) => *status,
+            Self::Database(_)
+            | Self::Redis(_)
+            | Self::Serialization(_)
+            | Self::Configuration(_)
+            | Self::Internal(_)
+            | Self::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Message for the response; internal failures are logged and not shown
+    fn public_message(&self) -> String {
+        match self {
+            Self::Database(e) => {
                 tracing::error!("Database error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database error occurred")
+                "Database error occurred".to_string()
             }
-            Self::Redis(ref e) => {
+            Self::Redis(e) => {
                 tracing::error!("Redis error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Cache error occurred")
+                "Cache error occurred".to_string()
             }
-            Self::HttpRequest(ref e) => {
+            Self::HttpRequest(e) => {
                 tracing::error!("HTTP request error: {}", e);
-                (StatusCode::BAD_GATEWAY, "External service request failed")
+                "External service request failed".to_string()
             }
-            Self::Serialization(ref e) => {
+            Self::Serialization(e) => {
                 tracing::error!("Serialization error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Data processing error")
+                "Data processing error".to_string()
             }
-            Self::NotFound(ref msg) => (StatusCode::NOT_FOUND, msg.as_str()),
-            Self::Authentication(ref msg) => (StatusCode::UNAUTHORIZED, msg.as_str()),
-            Self::ExternalApi(ref msg) => (StatusCode::BAD_GATEWAY, msg.as_str()),
-            Self::Configuration(ref msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.as_str()),
-            Self::Internal(ref msg) => {
+            Self::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, msg.as_str())
+                msg.clone()
             }
-            Self::Other(ref e) => {
+            Self::Other(e) => {
                 tracing::error!("Unexpected error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "An unexpected error occurred")
+                "An unexpected error occurred".to_string()
             }
+            Self::InsufficientScope(_) => "Spotify needs to be reconnected to grant a new permission".to_string(),
+            Self::OwnershipConflict { .. } => "Ownership change needs force or a files_missing reason".to_string(),
+            Self::QueueFull(_) => "Too many jobs are waiting; try again later".to_string(),
+            Self::NotFound(msg)
+            | Self::Authentication(msg)
+            | Self::ExternalApi(msg)
+            | Self::Configuration(msg)
+            | Self::Validation(msg)
+            | Self::Conflict(msg)
+            | Self::Rejected(_, msg) => msg.clone(),
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::OwnershipConflict { from, to } => {
+                let allowed: Vec<&str> = from.allowed_next().iter().map(|s| s.as_str()).collect();
+                Some(json!({ "from": from.as_str(), "to": to.as_str(), "allowed": allowed }))
+            }
+            Self::InsufficientScope(scope) => Some(json!({ "scope": scope })),
+            Self::QueueFull(_) => Some(json!({ "retry_after_secs": QUEUE_FULL_RETRY_AFTER_SECS })),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let body = ErrorBody {
+            error: ErrorDetail {
+                code: self.code(),
+                message: self.public_message(),
+                details: self.details(),
+            },
         };
 
-        let body = Json(json!({
-            "error": error_message,
-            "details": self.to_string(),
-        }));
+        if let Self::QueueFull(_) = self {
+            let retry_after = [(header::RETRY_AFTER, QUEUE_FULL_RETRY_AFTER_SECS.to_string())];
+            return (status, retry_after, Json(body)).into_response();
+        }
+
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Middleware giving errors raised outside handlers, such as rejected request
+/// bodies and unsupported methods, the same JSON body as [`AppError`]
+///
+/// Error responses that are already JSON pass through untouched. Headers of the
+/// original response, such as `Allow` on a 405, are kept.
+pub async fn json_errors(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let text = axum::body::to_bytes(body, REJECTION_BODY_LIMIT)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .unwrap_or_default();
+    let message = if text.is_empty() {
+        status.canonical_reason().unwrap_or("Request failed").to_string()
+    } else {
+        text
+    };
+
+    let mut response = AppError::Rejected(status, message).into_response();
+    for (name, value) in &parts.headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            response.headers_mut().append(name, value.clone());
+        }
+    }
+    response
+}
+
+/// Fallback for API paths that match no route
+pub async fn api_not_found(OriginalUri(uri): OriginalUri) -> AppError {
+    AppError::NotFound(format!("No API endpoint at {}", uri.path()))
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::Rejected(rejection.status(), rejection.body_text())
+    }
+}
 
-        (status, body).into_response()
+impl From<FormRejection> for AppError {
+    fn from(rejection: FormRejection) -> Self {
+        Self::Rejected(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<SubmitError> for AppError {
+    fn from(error: SubmitError) -> Self {
+        match error {
+            SubmitError::Full(_) => Self::QueueFull(error.to_string()),
+            SubmitError::Closed => Self::Internal(error.to_string()),
+        }
     }
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_of(error: AppError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_not_found_and_validation_bodies() {
+        let (status, body) = body_of(AppError::NotFound("Album 7 not found".to_string())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, json!({ "error": { "code": "not_found", "message": "Album 7 not found" } }));
+
+        let (status, body) = body_of(AppError::Validation("Bad threshold".to_string())).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body, json!({ "error": { "code": "validation", "message": "Bad threshold" } }));
+    }
+
+    #[tokio::test]
+    async fn test_external_api_body() {
+        let (status, body) = body_of(AppError::ExternalApi("Spotify API error (500)".to_string())).await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(body["error"]["code"], "external_api");
+        assert_eq!(body["error"]["message"], "Spotify API error (500)");
+    }
+
+    #[tokio::test]
+    async fn test_authentication_bodies() {
+        let (status, body) = body_of(AppError::Authentication("Spotify not connected".to_string())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"]["code"], "authentication");
+
+        // A missing scope tells clients which one to reconnect for
+        let (status, body) = body_of(AppError::InsufficientScope("user-top-read".to_string())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "authentication");
+        assert_eq!(body["error"]["details"], json!({ "scope": "user-top-read" }));
+    }
+
+    #[tokio::test]
+    async fn test_conflict_body_lists_allowed_statuses() {
+        let error = AppError::OwnershipConflict {
+            from: OwnershipStatus::NotOwned,
+            to: OwnershipStatus::Owned,
+        };
+        let allowed: Vec<&str> = OwnershipStatus::NotOwned.allowed_next().iter().map(|s| s.as_str()).collect();
+        let (status, body) = body_of(error).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["code"], "conflict");
+        assert_eq!(
+            body["error"]["details"],
+            json!({ "from": "not_owned", "to": "owned", "allowed": allowed })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queue_full_body() {
+        let (status, body) = body_of(AppError::QueueFull("capacity 1".to_string())).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["error"]["code"], "queue_full");
+        assert_eq!(body["error"]["details"]["retry_after_secs"], QUEUE_FULL_RETRY_AFTER_SECS);
+    }
+
+    #[tokio::test]
+    async fn test_internal_bodies_hide_the_cause() {
+        let (status, body) = body_of(AppError::Other(anyhow::anyhow!("secret connection string"))).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body,
+            json!({ "error": { "code": "internal", "message": "An unexpected error occurred" } })
+        );
+
+        let (_, body) = body_of(AppError::Database(sea_orm::DbErr::Custom("no such table".to_string()))).await;
+        assert_eq!(body["error"]["code"], "internal");
+        assert_eq!(body["error"]["message"], "Database error occurred");
+    }
+
+    #[test]
+    fn test_rejected_codes_follow_status() {
+        let code = |status| AppError::Rejected(status, String::new()).code();
+        assert_eq!(code(StatusCode::BAD_REQUEST), ErrorCode::Validation);
+        assert_eq!(code(StatusCode::UNSUPPORTED_MEDIA_TYPE), ErrorCode::Validation);
+        assert_eq!(code(StatusCode::NOT_FOUND), ErrorCode::NotFound);
+        assert_eq!(code(StatusCode::UNAUTHORIZED), ErrorCode::Authentication);
+        assert_eq!(code(StatusCode::CONFLICT), ErrorCode::Conflict);
+        assert_eq!(code(StatusCode::BAD_GATEWAY), ErrorCode::ExternalApi);
+        assert_eq!(code(StatusCode::INTERNAL_SERVER_ERROR), ErrorCode::Internal);
+    }
+}
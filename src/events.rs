@@ -0,0 +1,229 @@
+//! In-process domain events
+//!
+//! The places that change state publish a `DomainEvent` on the bus in
+//! `AppState`, and features that react to those moments subscribe to it
+//! rather than being called from every code path that can cause them.
+//!
+//! Publishing never waits on a subscriber. Each subscriber reads its own
+//! queue in its own task; one that falls too far behind skips the oldest
+//! events and is told how many, so it can catch up another way, and one that
+//! panics loses only the event it panicked on.
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    task::JoinHandle,
+};
+
+use crate::{
+    db::enums::{AlbumSource, JobStatus, JobType, OwnershipStatus},
+    services::{activity_log, playlist_stats},
+    state::AppState,
+};
+
+/// Events a subscriber may fall behind by before it starts skipping them
+pub const EVENT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DomainEvent {
+    /// An album moved from one ownership status to another
+    OwnershipChanged {
+        album_id: i32,
+        from: OwnershipStatus,
+        to: OwnershipStatus,
+        reason: String,
+        forced: bool,
+    },
+    /// A job completed or failed
+    JobFinished {
+        job_id: i32,
+        job_type: JobType,
+        status: JobStatus,
+    },
+    /// A Spotify sync or import added an album to the library
+    AlbumImported { album_id: i32, source: AlbumSource },
+}
+
+/// Broadcast channel for domain events
+///
+/// Cloning is cheap; all clones publish to the same subscribers.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+    /// Subscribers started with `spawn_subscriber`
+    subscribers: Arc<AtomicI64>,
+    /// Deliveries to those subscribers not handled yet
+    pending: Arc<AtomicI64>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            subscribers: Arc::new(AtomicI64::new(0)),
+            pending: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Send an event to every subscriber; events nobody listens for are dropped
+    pub fn publish(&self, event: DomainEvent) {
+        tracing::debug!("Publishing {:?}", event);
+        if self.sender.send(event).is_ok() {
+            self.pending.fetch_add(self.subscribers.load(Ordering::SeqCst), Ordering::SeqCst);
+        }
+    }
+
+    /// A receiver of every event published from now on
+    pub fn receiver(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Run `handler` on every event published from now on
+    ///
+    /// Events are handled one at a time, in order, each in a task of its own
+    /// so that a panic is logged and the subscriber carries on. Skipped
+    /// events are only logged; see `spawn_resyncing_subscriber`.
+    pub fn spawn_subscriber<F, Fut>(&self, name: &'static str, handler: F) -> JoinHandle<()>
+    where
+        F: Fn(DomainEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_resyncing_subscriber(name, handler, |_| async {})
+    }
+
+    /// Like `spawn_subscriber`, and after falling behind run `resync` with
+    /// the number of skipped events, before handling the next one
+    ///
+    /// Subscribers whose derived data can be rebuilt from the database use
+    /// it to recompute what the skipped events would have updated.
+    pub fn spawn_resyncing_subscriber<F, Fut, R, RFut>(
+        &self,
+        name: &'static str,
+        handler: F,
+        resync: R,
+    ) -> JoinHandle<()>
+    where
+        F: Fn(DomainEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        R: Fn(u64) -> RFut + Send + 'static,
+        RFut: Future<Output = ()> + Send + 'static,
+    {
+        let mut receiver = self.receiver();
+        self.subscribers.fetch_add(1, Ordering::SeqCst);
+        let pending = self.pending.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if let Err(e) = tokio::spawn(handler(event)).await {
+                            tracing::error!("Event subscriber {} failed: {}", name, e);
+                        }
+                        pending.fetch_sub(1, Ordering::SeqCst);
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Event subscriber {} fell behind and skipped {} event(s)", name, skipped);
+                        if let Err(e) = tokio::spawn(resync(skipped)).await {
+                            tracing::error!("Event subscriber {} failed to resync: {}", name, e);
+                        }
+                        pending.fetch_sub(skipped as i64, Ordering::SeqCst);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Wait until the subscribers have handled every event published so far,
+    /// e.g. before a test looks at what they wrote
+    pub async fn settled(&self) {
+        while self.pending.load(Ordering::SeqCst) > 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+}
+
+/// Start the subscribers that keep derived data in step with events
+pub fn start_subscribers(state: &AppState) {
+    let (db, resync_db) = (state.db.clone(), state.db.clone());
+    state.events.spawn_resyncing_subscriber(
+        "activity_log",
+        move |event| activity_log::log_event(db.clone(), event),
+        move |skipped| activity_log::log_skipped(resync_db.clone(), skipped),
+    );
+
+    let (db, resync_db) = (state.db.clone(), state.db.clone());
+    state.events.spawn_resyncing_subscriber(
+        "playlist_stats",
+        move |event| playlist_stats::refresh_on_event(db.clone(), event),
+        move |_| playlist_stats::refresh_after_lag(resync_db.clone()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_finished(job_id: i32) -> DomainEvent {
+        DomainEvent::JobFinished {
+            job_id,
+            job_type: JobType::SpotifySync,
+            status: JobStatus::Completed,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_subscriber_keeps_receiving() {
+        let bus = EventBus::new(EVENT_CAPACITY);
+        let (seen_tx, mut seen) = tokio::sync::mpsc::unbounded_channel();
+        bus.spawn_subscriber("flaky", move |event| {
+            let seen_tx = seen_tx.clone();
+            async move {
+                if event == job_finished(1) {
+                    panic!("subscriber bug");
+                }
+                seen_tx.send(event).unwrap();
+            }
+        });
+
+        bus.publish(job_finished(1));
+        bus.publish(job_finished(2));
+        bus.settled().await;
+
+        assert_eq!(seen.recv().await, Some(job_finished(2)));
+        assert!(seen.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_does_not_block_publisher() {
+        let bus = EventBus::new(2);
+        let (release_tx, release) = tokio::sync::watch::channel(false);
+        bus.spawn_subscriber("stuck", move |_| {
+            let mut release = release.clone();
+            async move {
+                let _ = release.wait_for(|released| *released).await;
+            }
+        });
+        let mut fast = bus.receiver();
+
+        // Publishing returns right away even though "stuck" never keeps up
+        for job_id in 0..10 {
+            bus.publish(job_finished(job_id));
+        }
+        assert_eq!(fast.recv().await, Err(RecvError::Lagged(8)));
+        assert_eq!(fast.recv().await, Ok(job_finished(8)));
+
+        // Once it catches up, what it missed is accounted for
+        release_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), bus.settled()).await.unwrap();
+    }
+}
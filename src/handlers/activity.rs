@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, services::activity_log, state::AppState};
+
+/// Entries returned when no `limit` is given
+const DEFAULT_ACTIVITY_LIMIT: u64 = 50;
+
+#[derive(Deserialize)]
+pub struct ActivityQuery {
+    /// At most 200
+    pub limit: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ActivityEntry {
+    pub id: i32,
+    pub kind: String,
+    pub album_id: Option<i32>,
+    pub job_id: Option<i32>,
+    pub details: serde_json::Value,
+    pub at: String,
+}
+
+#[derive(Serialize)]
+pub struct ActivityResponse {
+    pub activity: Vec<ActivityEntry>,
+}
+
+/// The library-wide activity feed, newest first
+pub async fn list_activity(
+    State(state): State<AppState>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<ActivityResponse>> {
+    let limit = query.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).clamp(1, 200);
+    let activity = activity_log::recent(&state.db, limit)
+        .await?
+        .into_iter()
+        .map(|entry| ActivityEntry {
+            id: entry.id,
+            kind: entry.kind,
+            album_id: entry.album_id,
+            job_id: entry.job_id,
+            details: serde_json::from_str(&entry.details).unwrap_or_default(),
+            at: entry.created_at.to_rfc3339(),
+        })
+        .collect();
+    Ok(Json(ActivityResponse { activity }))
+}
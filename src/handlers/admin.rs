@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, Set};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        entities::{pending_side_effects, user_settings},
+        settings as db_settings,
+    },
+    error::Result,
+    logging,
+    services::side_effects,
+    state::AppState,
+    tasks,
+};
+
+#[derive(Serialize)]
+pub struct LogLevelResponse {
+    /// Directives currently in effect
+    pub current: String,
+    /// Directives used when no override is set
+    pub default: String,
+    /// Saved override, re-applied on startup
+    pub saved_override: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SetLogLevelRequest {
+    /// `EnvFilter` directives, e.g. `beat_collector=trace,tower_http=info`
+    pub directives: String,
+}
+
+pub async fn get_log_level(State(state): State<AppState>) -> Result<Json<LogLevelResponse>> {
+    let saved_override = db_settings::shared(&state.db)
+        .await?
+        .and_then(|settings| settings.log_level);
+
+    Ok(Json(log_level_response(&state, saved_override)))
+}
+
+/// Change the log filter at runtime and persist it across restarts
+///
+/// Malformed directives are rejected with 422 before anything is saved.
+pub async fn set_log_level(
+    State(state): State<AppState>,
+    Json(payload): Json<SetLogLevelRequest>,
+) -> Result<Json<LogLevelResponse>> {
+    let directives = payload.directives.trim().to_string();
+    logging::parse_directives(&directives)?;
+
+    save_log_level(&state, Some(directives.clone())).await?;
+    state.log_filter.apply(&directives)?;
+    tracing::info!("Log level changed to: {}", directives);
+
+    Ok(Json(log_level_response(&state, Some(directives))))
+}
+
+/// Drop the saved override and go back to the default directives
+pub async fn reset_log_level(State(state): State<AppState>) -> Result<Json<LogLevelResponse>> {
+    save_log_level(&state, None).await?;
+    state.log_filter.reset()?;
+    tracing::info!("Log level reset to: {}", state.log_filter.default_directives());
+
+    Ok(Json(log_level_response(&state, None)))
+}
+
+fn log_level_response(state: &AppState, saved_override: Option<String>) -> LogLevelResponse {
+    LogLevelResponse {
+        current: state.log_filter.current(),
+        default: state.log_filter.default_directives().to_string(),
+        saved_override,
+    }
+}
+
+async fn save_log_level(state: &AppState, log_level: Option<String>) -> Result<()> {
+    // Resetting before anything was saved has nothing to clear
+    if log_level.is_none() && db_settings::shared(&state.db).await?.is_none() {
+        return Ok(());
+    }
+
+    let changes = user_settings::ActiveModel {
+        log_level: Set(log_level),
+        ..Default::default()
+    };
+    db_settings::save_shared(&state.db, changes).await?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct SideEffectResponse {
+    pub id: i32,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub next_attempt_at: DateTimeWithTimeZone,
+    pub last_error: Option<String>,
+    /// Out of automatic retries; only a manual retry runs it again
+    pub dead: bool,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl From<pending_side_effects::Model> for SideEffectResponse {
+    fn from(entry: pending_side_effects::Model) -> Self {
+        Self {
+            dead: side_effects::is_dead(&entry),
+            payload: serde_json::from_str(&entry.payload)
+                .unwrap_or(serde_json::Value::String(entry.payload)),
+            id: entry.id,
+            kind: entry.kind,
+            attempts: entry.attempts,
+            next_attempt_at: entry.next_attempt_at,
+            last_error: entry.last_error,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SideEffectRetryResponse {
+    pub succeeded: bool,
+    /// The entry with the new failure recorded; absent when it succeeded
+    pub entry: Option<SideEffectResponse>,
+}
+
+/// Failed side effects waiting for a retry, dead letters included
+pub async fn list_side_effects(State(state): State<AppState>) -> Result<Json<Vec<SideEffectResponse>>> {
+    let entries = side_effects::list(&state.db).await?;
+    Ok(Json(entries.into_iter().map(SideEffectResponse::from).collect()))
+}
+
+/// Run a pending side effect now, even a dead letter
+pub async fn retry_side_effect(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<SideEffectRetryResponse>> {
+    let entry = side_effects::find(&state.db, id).await?;
+    let failed = tasks::side_effects::run_entry(&state, entry).await?;
+
+    Ok(Json(SideEffectRetryResponse {
+        succeeded: failed.is_none(),
+        entry: failed.map(SideEffectResponse::from),
+    }))
+}
+
+/// Drop a pending side effect without running it
+pub async fn discard_side_effect(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode> {
+    side_effects::discard(&state.db, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,20 +1,56 @@
+use std::collections::BTreeMap;
+
 use axum::{
-    extract::{Path, Query, State},
-    Json,
+    async_trait,
+    extract::{FromRequest, Path, Query, Request, State},
+    http::{header, HeaderMap},
+    response::{Html, IntoResponse, Response},
+    Form, Json,
 };
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
-    QuerySelect, Set,
+    sea_query::{Expr, Func, LikeExpr, NullOrdering, Query as SubQuery, SimpleExpr},
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, FromQueryResult, JoinType,
+    ModelTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+    Select, Set, TransactionTrait,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     db::{
-        entities::{albums, artists, user_settings},
-        enums::{AcquisitionSource, OwnershipStatus},
+        entities::{
+            albums, artists, jobs, lidarr_downloads, ownership_events, profile_albums, profiles,
+            tracks,
+        },
+        enums::{
+            AcquisitionSource, AlbumSource, GenreSource, JobStatus, JobType, MatchSource,
+            MatchStatus, OwnershipStatus,
+        },
+        profile, settings as db_settings, slug,
     },
     error::{AppError, Result},
+    handlers::{
+        export::{self, ExportFormat, ExportRows, EXPORT_BATCH_SIZE},
+        jobs::{enqueue_job, JobCreatedResponse},
+        profiles::ActiveProfile,
+        search::contains_pattern,
+        settings::DEFAULT_SINGLE_TRACK_THRESHOLD,
+    },
+    services::{
+        album_artist::{self, ArtistChoice},
+        album_grid_cache,
+        genres::{self, GenreList},
+        lidarr, ownership,
+        stats_cache,
+        suggestions::{self, AlbumSuggestion, RelatedArtist},
+        wanted,
+        SpotifyService,
+    },
     state::AppState,
+    tasks::musicbrainz_match::AUTO_MATCH_SCORE,
+    templates::{
+        album_card_oob, lidarr_album_missing_notification, lidarr_search_started_notification,
+        AlbumCardData, DEFAULT_ALBUM_PAGE_SIZE,
+    },
 };
 
 #[derive(Deserialize)]
@@ -23,6 +59,8 @@ pub struct ListAlbumsQuery {
     pub match_status: Option<String>,
     pub artist_id: Option<i32>,
     pub search: Option<String>,
+    /// Comma-separated fields `search` looks in: `title` (the default) and `artist`
+    pub search_in: Option<String>,
     #[serde(default = "default_page")]
     pub page: u64,
     #[serde(default = "default_page_size")]
@@ -31,6 +69,12 @@ pub struct ListAlbumsQuery {
     pub sort_by: String,
     #[serde(default = "default_sort_order")]
     pub sort_order: String,
+    /// HTML grid only: `grid` or `list`; persisted to user settings when given
+    pub view: Option<String>,
+    /// Hide singles; falls back to the `exclude_singles` setting when absent
+    pub exclude_singles: Option<bool>,
+    /// Only albums with a track whose local file's length doesn't match Spotify's
+    pub has_issues: Option<bool>,
 }
 
 fn default_page() -> u64 {
@@ -38,7 +82,7 @@ fn default_page() -> u64 {
 }
 
 fn default_page_size() -> u64 {
-    50
+    DEFAULT_ALBUM_PAGE_SIZE
 }
 
 fn default_sort_by() -> String {
@@ -49,6 +93,26 @@ fn default_sort_order() -> String {
     "desc".to_string()
 }
 
+#[derive(Deserialize)]
+pub struct MatchScoreQuery {
+    #[serde(default = "default_min_score")]
+    pub min: i32,
+    #[serde(default = "default_max_score")]
+    pub max: i32,
+    #[serde(default = "default_page")]
+    pub page: u64,
+    #[serde(default = "default_page_size")]
+    pub page_size: u64,
+}
+
+fn default_min_score() -> i32 {
+    0
+}
+
+fn default_max_score() -> i32 {
+    100
+}
+
 #[derive(Serialize)]
 pub struct AlbumResponse {
     pub id: i32,
@@ -59,6 +123,35 @@ pub struct AlbumResponse {
     pub ownership_status: String,
     pub match_score: Option<i32>,
     pub genres: Option<Vec<String>>,
+    /// Where the genres came from: `spotify`, `musicbrainz` or `manual`
+    pub genre_source: Option<String>,
+    pub estimated_value: Option<f64>,
+    /// Earliest listen credited from ListenBrainz, RFC 3339
+    pub first_listened_at: Option<String>,
+    /// Latest listen credited from ListenBrainz, RFC 3339
+    pub last_listened_at: Option<String>,
+    pub listen_count: i32,
+    /// When the album last became wanted, RFC 3339; unset unless not owned
+    pub wanted_since: Option<String>,
+    /// How much the album is wanted, from 1 to 5 (must have)
+    pub want_priority: i32,
+    /// The user's own notes
+    pub notes: Option<String>,
+    /// Fields the search was found in; only present when searching
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_on: Option<Vec<SearchField>>,
+}
+
+#[derive(Deserialize)]
+pub struct ResetMatchesQuery {
+    /// `low_score`, `no_match` or `all`
+    pub scope: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ResetMatchesResponse {
+    pub scope: String,
+    pub reset_count: u64,
 }
 
 #[derive(Serialize)]
@@ -81,15 +174,81 @@ pub struct PaginationInfo {
     pub total_pages: u64,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct StatsResponse {
     pub total_albums: u64,
     pub owned_albums: u64,
     pub not_owned_albums: u64,
     pub downloading_albums: u64,
+    pub ordered_albums: u64,
     pub matched_albums: u64,
     pub unmatched_albums: u64,
     pub total_artists: u64,
+    /// Counted albums by where they came from (`saved_album`, `playlist_import`)
+    pub by_source: BTreeMap<String, u64>,
+    /// Albums of the profile left out of the counts
+    pub ignored: IgnoredAlbums,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IgnoredAlbums {
+    /// No longer saved on Spotify
+    pub archived: u64,
+    /// Left out by the `stats_exclude_playlist_imports` setting
+    pub playlist_imports: u64,
+}
+
+/// Hint from album card quick actions to answer with the updated card
+#[derive(Deserialize)]
+pub struct CardQuery {
+    #[serde(default)]
+    pub card: bool,
+}
+
+impl CardQuery {
+    /// Whether an HTMX request asked for the card back
+    fn wants_card(&self, headers: &HeaderMap) -> bool {
+        self.card && headers.contains_key("HX-Request")
+    }
+}
+
+/// Request body sent as JSON by API clients or form-encoded by HTMX buttons
+pub struct JsonOrForm<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for JsonOrForm<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> std::result::Result<Self, Self::Rejection> {
+        let is_form = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+
+        if is_form {
+            let Form(value) = Form::<T>::from_request(req, state).await?;
+            Ok(Self(value))
+        } else {
+            let Json(value) = Json::<T>::from_request(req, state).await?;
+            Ok(Self(value))
+        }
+    }
+}
+
+/// Updated card for an album, swapped in out of band
+async fn album_card_fragment(state: &AppState, id: i32) -> Result<String> {
+    let (album, artist) = albums::Entity::find_by_id(id)
+        .find_also_related(artists::Entity)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+    let artist = artist.ok_or_else(|| AppError::NotFound("Artist not found".to_string()))?;
+    Ok(album_card_oob(&AlbumCardData::from_models(album, &artist)).into_string())
 }
 
 #[derive(Deserialize)]
@@ -97,16 +256,210 @@ pub struct UpdateAlbumRequest {
     pub ownership_status: Option<String>,
     pub acquisition_source: Option<String>,
     pub local_path: Option<String>,
+    /// User-supplied price estimate, in the user's own currency
+    pub estimated_value: Option<f64>,
+    /// Manually assign a MusicBrainz release group
+    pub musicbrainz_release_group_id: Option<String>,
+    /// Why the ownership status is changing; `files_missing` lets an owned
+    /// album move without `force`
+    pub reason: Option<String>,
+    /// Skip the ownership transition guard
+    #[serde(default)]
+    pub force: bool,
+    /// Move the album to this existing artist
+    pub artist_id: Option<i32>,
+    /// Move the album to a newly created artist with this name
+    pub new_artist_name: Option<String>,
+    /// MusicBrainz artist ID to use for Lidarr instead of the album's own; blank clears it
+    pub lidarr_artist_mbid_override: Option<String>,
+    /// Replace the genres by hand; syncs and matching leave them alone afterwards
+    pub genres: Option<GenreList>,
+    /// How much the album is wanted, from 1 to 5 (must have)
+    pub want_priority: Option<i32>,
+    /// Replace the notes; blank clears them
+    pub notes: Option<String>,
+}
+
+impl UpdateAlbumRequest {
+    /// Artist the album should move to, if the request asks for one
+    fn artist_choice(&self) -> Result<Option<ArtistChoice<'_>>> {
+        match (self.artist_id, self.new_artist_name.as_deref()) {
+            (Some(_), Some(_)) => Err(AppError::Validation(
+                "Give either artist_id or new_artist_name, not both".to_string(),
+            )),
+            (Some(id), None) => Ok(Some(ArtistChoice::Existing(id))),
+            (None, Some(name)) => Ok(Some(ArtistChoice::New(name))),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Order an album query by a `sort_by` key, `asc` or `desc`
+///
+/// Keys: `title`, `artist`, `release_date`, `last_listened`, `listen_count`,
+/// and `created_at` (date added) for anything else. Sorting `last_listened`
+/// ascending puts albums never listened to first, then the least recently
+/// played.
+pub(crate) fn sort_albums(
+    select: Select<albums::Entity>,
+    sort_by: &str,
+    sort_order: &str,
+) -> Select<albums::Entity> {
+    let order = if sort_order == "asc" { Order::Asc } else { Order::Desc };
+
+    match sort_by {
+        "title" => select.order_by(albums::Column::Title, order),
+        "artist" => select
+            .join(JoinType::LeftJoin, albums::Relation::Artists.def())
+            .order_by(artists::Column::Name, order),
+        "release_date" => select.order_by(albums::Column::ReleaseDate, order),
+        "last_listened" => {
+            let nulls = if order == Order::Asc { NullOrdering::First } else { NullOrdering::Last };
+            select
+                .order_by_with_nulls(albums::Column::LastListenedAt, order, nulls)
+                .order_by_asc(albums::Column::Id)
+        }
+        "listen_count" => select
+            .order_by(albums::Column::ListenCount, order)
+            .order_by_asc(albums::Column::Id),
+        _ => select.order_by(albums::Column::CreatedAt, order),
+    }
+}
+
+/// Album field a library search can look in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Title,
+    Artist,
+    Notes,
+}
+
+impl SearchField {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "title" => Some(Self::Title),
+            "artist" => Some(Self::Artist),
+            "notes" => Some(Self::Notes),
+            _ => None,
+        }
+    }
+
+    /// Fields named in a `search_in` list; just the title when there's none,
+    /// as before fields could be picked
+    pub fn parse_list(search_in: Option<&str>) -> Result<Vec<Self>> {
+        let Some(list) = search_in.filter(|l| !l.trim().is_empty()) else {
+            return Ok(vec![Self::Title]);
+        };
+        let mut fields = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let field = Self::parse(name).ok_or_else(|| {
+                AppError::Validation(format!("Unknown search field: {} (use title, artist or notes)", name))
+            })?;
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+        Ok(fields)
+    }
+}
+
+/// Condition for albums with `search` in any of `fields`, ignoring case
+///
+/// Compares lowercased on both sides like the track search, since Postgres
+/// `LIKE` is case-sensitive where SQLite's isn't.
+pub(crate) fn search_filter(search: &str, fields: &[SearchField]) -> Condition {
+    let pattern = contains_pattern(search);
+    let matches = |column: SimpleExpr| {
+        Expr::expr(Func::lower(column)).like(LikeExpr::new(pattern.clone()).escape('\\'))
+    };
+    fields.iter().fold(Condition::any(), |condition, field| match field {
+        SearchField::Title => condition.add(matches(Expr::col((albums::Entity, albums::Column::Title)).into())),
+        SearchField::Artist => condition.add(
+            albums::Column::ArtistId.in_subquery(
+                SubQuery::select()
+                    .column(artists::Column::Id)
+                    .from(artists::Entity)
+                    .and_where(matches(Expr::col((artists::Entity, artists::Column::Name)).into()))
+                    .to_owned(),
+            ),
+        ),
+        SearchField::Notes => condition.add(matches(Expr::col((albums::Entity, albums::Column::Notes)).into())),
+    })
+}
+
+/// Which of `fields` a search result has `search` in, for hinting why it showed up
+pub(crate) fn matched_on(
+    search: &str,
+    fields: &[SearchField],
+    album: &albums::Model,
+    artist_name: &str,
+) -> Vec<SearchField> {
+    let needle = search.to_lowercase();
+    fields
+        .iter()
+        .copied()
+        .filter(|field| match field {
+            SearchField::Title => album.title.to_lowercase().contains(&needle),
+            SearchField::Artist => artist_name.to_lowercase().contains(&needle),
+            SearchField::Notes => album
+                .notes
+                .as_deref()
+                .is_some_and(|notes| notes.to_lowercase().contains(&needle)),
+        })
+        .collect()
+}
+
+/// Condition hiding singles from the library, if they should be hidden
+///
+/// `requested` overrides the saved setting. Albums without a known track
+/// count are never treated as singles.
+pub(crate) async fn singles_filter(
+    state: &AppState,
+    active_profile: &profiles::Model,
+    requested: Option<bool>,
+) -> Result<Option<Condition>> {
+    let settings = db_settings::for_profile(&state.db, active_profile).await?;
+    let exclude = requested
+        .or_else(|| settings.as_ref().and_then(|s| s.exclude_singles))
+        .unwrap_or(false);
+    if !exclude {
+        return Ok(None);
+    }
+
+    let threshold = settings
+        .and_then(|s| s.single_track_threshold)
+        .unwrap_or(DEFAULT_SINGLE_TRACK_THRESHOLD);
+    Ok(Some(
+        Condition::any()
+            .add(albums::Column::TotalTracks.is_null())
+            .add(albums::Column::TotalTracks.gt(threshold)),
+    ))
+}
+
+/// Filter for `albums` queries: albums with a track flagged by the
+/// filesystem scan's length check
+pub(crate) fn albums_with_issues() -> SimpleExpr {
+    albums::Column::Id.in_subquery(
+        SubQuery::select()
+            .column(tracks::Column::AlbumId)
+            .from(tracks::Entity)
+            .and_where(Expr::col(tracks::Column::DurationMismatch).eq(true))
+            .to_owned(),
+    )
 }
 
 pub async fn list_albums(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Query(query): Query<ListAlbumsQuery>,
 ) -> Result<Json<PaginatedAlbumsResponse>> {
     let page = query.page.max(1);
     let page_size = query.page_size.min(200).max(1);
 
-    let mut select = albums::Entity::find();
+    let mut select = albums::Entity::find()
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(albums::Column::ArchivedAt.is_null());
 
     // Apply filters
     if let Some(status) = &query.ownership_status {
@@ -121,12 +474,17 @@ pub async fn list_albums(
         select = select.filter(albums::Column::ArtistId.eq(artist_id));
     }
 
+    let search_fields = SearchField::parse_list(query.search_in.as_deref())?;
     if let Some(search) = &query.search {
-        select = select.filter(
-            albums::Column::Title
-                .contains(search)
-                .or(albums::Column::Title.like(&format!("%{}%", search))),
-        );
+        select = select.filter(search_filter(search, &search_fields));
+    }
+
+    if let Some(condition) = singles_filter(&state, &active_profile, query.exclude_singles).await? {
+        select = select.filter(condition);
+    }
+
+    if query.has_issues == Some(true) {
+        select = select.filter(albums_with_issues());
     }
 
     // Get total count
@@ -134,14 +492,110 @@ pub async fn list_albums(
     let total_pages = (total_items + page_size - 1) / page_size;
 
     // Get paginated results
+    let albums = sort_albums(select, &query.sort_by, &query.sort_order)
+        .offset((page - 1) * page_size)
+        .limit(page_size)
+        .find_also_related(artists::Entity)
+        .all(&state.db)
+        .await?;
+
+    let priorities = profile::want_priorities(
+        &state.db,
+        active_profile.id,
+        albums.iter().map(|(album, _)| album.id),
+    )
+    .await?;
+
+    let album_responses: Vec<AlbumResponse> = albums
+        .into_iter()
+        .filter_map(|(album, artist)| {
+            artist.map(|a| AlbumResponse {
+                matched_on: query
+                    .search
+                    .as_deref()
+                    .map(|search| matched_on(search, &search_fields, &album, &a.name)),
+                id: album.id,
+                title: album.title,
+                artist: ArtistResponse {
+                    id: a.id,
+                    name: a.name,
+                },
+                cover_art_url: album.cover_art_url,
+                release_date: album.release_date.map(|d| d.to_string()),
+                ownership_status: format!("{:?}", album.ownership_status),
+                match_score: album.match_score,
+                genres: album.genres.and_then(|g| serde_json::from_str(&g).ok()),
+                genre_source: album.genre_source,
+                estimated_value: album.estimated_value,
+                first_listened_at: album.first_listened_at.map(|t| t.to_rfc3339()),
+                last_listened_at: album.last_listened_at.map(|t| t.to_rfc3339()),
+                listen_count: album.listen_count,
+                wanted_since: album.wanted_since.map(|t| t.to_rfc3339()),
+                want_priority: priorities
+                    .get(&album.id)
+                    .copied()
+                    .unwrap_or(wanted::DEFAULT_WANT_PRIORITY),
+                notes: album.notes,
+            })
+        })
+        .collect();
+
+    Ok(Json(PaginatedAlbumsResponse {
+        albums: album_responses,
+        pagination: PaginationInfo {
+            page,
+            page_size,
+            total_items,
+            total_pages,
+        },
+    }))
+}
+
+/// List albums whose MusicBrainz match score falls within `min..=max`
+///
+/// Only matched albums with a score are listed. Results are ordered by score
+/// ascending so the shakiest matches are reviewed first.
+pub async fn list_albums_by_match_score(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<MatchScoreQuery>,
+) -> Result<Json<PaginatedAlbumsResponse>> {
+    if query.min > query.max {
+        return Err(AppError::Validation(format!(
+            "Invalid score range: min ({}) is greater than max ({})",
+            query.min, query.max
+        )));
+    }
+
+    let page = query.page.max(1);
+    let page_size = query.page_size.clamp(1, 200);
+
+    let select = albums::Entity::find()
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .filter(albums::Column::MatchStatus.eq(MatchStatus::Matched.as_str()))
+        .filter(albums::Column::MatchScore.is_not_null())
+        .filter(albums::Column::MatchScore.between(query.min, query.max));
+
+    let total_items = select.clone().count(&state.db).await?;
+    let total_pages = total_items.div_ceil(page_size);
+
     let albums = select
-        .order_by_desc(albums::Column::CreatedAt)
+        .order_by_asc(albums::Column::MatchScore)
+        .order_by_asc(albums::Column::Id)
         .offset((page - 1) * page_size)
         .limit(page_size)
         .find_also_related(artists::Entity)
         .all(&state.db)
         .await?;
 
+    let priorities = profile::want_priorities(
+        &state.db,
+        active_profile.id,
+        albums.iter().map(|(album, _)| album.id),
+    )
+    .await?;
+
     let album_responses: Vec<AlbumResponse> = albums
         .into_iter()
         .filter_map(|(album, artist)| {
@@ -157,6 +611,18 @@ pub async fn list_albums(
                 ownership_status: format!("{:?}", album.ownership_status),
                 match_score: album.match_score,
                 genres: album.genres.and_then(|g| serde_json::from_str(&g).ok()),
+                genre_source: album.genre_source,
+                estimated_value: album.estimated_value,
+                first_listened_at: album.first_listened_at.map(|t| t.to_rfc3339()),
+                last_listened_at: album.last_listened_at.map(|t| t.to_rfc3339()),
+                listen_count: album.listen_count,
+                wanted_since: album.wanted_since.map(|t| t.to_rfc3339()),
+                want_priority: priorities
+                    .get(&album.id)
+                    .copied()
+                    .unwrap_or(wanted::DEFAULT_WANT_PRIORITY),
+                notes: album.notes,
+                matched_on: None,
             })
         })
         .collect();
@@ -172,59 +638,213 @@ pub async fn list_albums(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct ExportWantedQuery {
+    /// `mbid` (default) or `json`
+    pub format: Option<String>,
+    pub artist_id: Option<i32>,
+    pub search: Option<String>,
+    /// Comma-separated fields `search` looks in: `title` (the default) and `artist`
+    pub search_in: Option<String>,
+    /// Hide singles; falls back to the `exclude_singles` setting when absent
+    pub exclude_singles: Option<bool>,
+    /// Only albums wanted for at least this many days
+    pub min_wanted_days: Option<u32>,
+}
+
+/// Rows of a wanted-albums export
+///
+/// `mbid` is one release group MBID per line; `json` is
+/// `{"albums": [{artist, title, mbid}], "skipped_without_mbid": n}`.
+struct WantedRows {
+    format: ExportFormat,
+    written: u64,
+    skipped: u64,
+}
+
+impl ExportRows for WantedRows {
+    type Row = (albums::Model, Option<artists::Model>);
+
+    fn header(&mut self) -> String {
+        match self.format {
+            ExportFormat::Json => "{\"albums\":[".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn row(&mut self, (album, artist): Self::Row, out: &mut String) {
+        // Matched albums should always carry an MBID, but some don't
+        let Some(mbid) = album.musicbrainz_release_group_id.filter(|id| !id.is_empty()) else {
+            self.skipped += 1;
+            return;
+        };
+        if self.format == ExportFormat::Json {
+            if self.written > 0 {
+                out.push(',');
+            }
+            let mut entry = serde_json::json!({
+                "artist": artist.map(|a| a.name),
+                "title": album.title,
+                "mbid": mbid,
+            });
+            if let Some(artist_mbid) = album.lidarr_artist_mbid_override {
+                entry["lidarr_artist_mbid"] = artist_mbid.into();
+            }
+            if let Some(wanted_since) = album.wanted_since {
+                entry["wanted_since"] = wanted_since.to_rfc3339().into();
+            }
+            out.push_str(&entry.to_string());
+        } else {
+            out.push_str(&mbid);
+            out.push('\n');
+        }
+        self.written += 1;
+    }
+
+    fn footer(&mut self) -> String {
+        match self.format {
+            ExportFormat::Json => format!("],\"skipped_without_mbid\":{}}}", self.skipped),
+            _ if self.skipped > 0 => {
+                format!("# Skipped {} matched album(s) without a MusicBrainz ID\n", self.skipped)
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+/// Download the wanted, matched albums as a file of MusicBrainz IDs
+///
+/// Honors the same `artist_id`, `search`, `search_in` and `exclude_singles`
+/// filters as the album list, and `min_wanted_days` leaves out recent wants.
+pub async fn export_wanted(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<ExportWantedQuery>,
+) -> Result<Response> {
+    let format_name = query.format.as_deref().unwrap_or("mbid");
+    let format = ExportFormat::parse(format_name, &[ExportFormat::Mbid, ExportFormat::Json])?;
+
+    let mut select = albums::Entity::find()
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .filter(albums::Column::OwnershipStatus.eq(OwnershipStatus::NotOwned.as_str()))
+        .filter(albums::Column::MatchStatus.eq(MatchStatus::Matched.as_str()))
+        .filter(wanted::not_cleared(active_profile.id));
+
+    if let Some(artist_id) = query.artist_id {
+        select = select.filter(albums::Column::ArtistId.eq(artist_id));
+    }
+
+    let search_fields = SearchField::parse_list(query.search_in.as_deref())?;
+    if let Some(search) = &query.search {
+        select = select.filter(search_filter(search, &search_fields));
+    }
+
+    if let Some(condition) = singles_filter(&state, &active_profile, query.exclude_singles).await? {
+        select = select.filter(condition);
+    }
+
+    if let Some(days) = query.min_wanted_days {
+        select = select.filter(wanted::wanted_for_at_least(days, chrono::Utc::now()));
+    }
+
+    let select = select
+        .find_also_related(artists::Entity)
+        .order_by_asc(artists::Column::Name)
+        .order_by_asc(albums::Column::Title)
+        .order_by_asc(albums::Column::Id);
+    let rows = WantedRows { format, written: 0, skipped: 0 };
+    let db = state.db.clone();
+    Ok(export::download(format, "wanted-albums", rows, move |page| {
+        let (db, select) = (db.clone(), select.clone());
+        async move { Ok(select.paginate(&db, EXPORT_BATCH_SIZE).fetch_page(page).await?) }
+    }))
+}
+
 pub async fn get_album(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
 ) -> Result<Json<AlbumResponse>> {
-    let album_with_artist = albums::Entity::find_by_id(id)
-        .find_also_related(artists::Entity)
-        .one(&state.db)
-        .await?;
+    let album_with_artist = match slug::find_album(&state.db, &key).await? {
+        Some(album) => {
+            let artist = album.find_related(artists::Entity).one(&state.db).await?;
+            Some((album, artist))
+        }
+        None => None,
+    };
 
     match album_with_artist {
-        Some((album, Some(artist))) => Ok(Json(AlbumResponse {
-            id: album.id,
-            title: album.title,
-            artist: ArtistResponse {
-                id: artist.id,
-                name: artist.name,
-            },
-            cover_art_url: album.cover_art_url,
-            release_date: album.release_date.map(|d| d.to_string()),
-            ownership_status: format!("{:?}", album.ownership_status),
-            match_score: album.match_score,
-            genres: album.genres.and_then(|g| serde_json::from_str(&g).ok()),
-        })),
+        Some((album, Some(artist))) => {
+            let want_priority = profile::album_link(&state.db, active_profile.id, album.id)
+                .await?
+                .map_or(wanted::DEFAULT_WANT_PRIORITY, |link| link.want_priority);
+            Ok(Json(AlbumResponse {
+                id: album.id,
+                title: album.title,
+                artist: ArtistResponse {
+                    id: artist.id,
+                    name: artist.name,
+                },
+                cover_art_url: album.cover_art_url,
+                release_date: album.release_date.map(|d| d.to_string()),
+                ownership_status: format!("{:?}", album.ownership_status),
+                match_score: album.match_score,
+                genres: album.genres.and_then(|g| serde_json::from_str(&g).ok()),
+                genre_source: album.genre_source,
+                estimated_value: album.estimated_value,
+                first_listened_at: album.first_listened_at.map(|t| t.to_rfc3339()),
+                last_listened_at: album.last_listened_at.map(|t| t.to_rfc3339()),
+                listen_count: album.listen_count,
+                wanted_since: album.wanted_since.map(|t| t.to_rfc3339()),
+                want_priority,
+                notes: album.notes,
+                matched_on: None,
+            }))
+        }
         _ => Err(AppError::NotFound("Album not found".to_string())),
     }
 }
 
+/// Update an album's ownership, metadata, match or artist
+///
+/// Takes JSON or, from HTMX buttons, a form. With `?card=true` an HTMX
+/// request gets the updated album card back, swapped in out of band.
 pub async fn update_album(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Path(id): Path<i32>,
-    Json(payload): Json<UpdateAlbumRequest>,
-) -> Result<Json<AlbumResponse>> {
+    Query(card_query): Query<CardQuery>,
+    headers: HeaderMap,
+    JsonOrForm(payload): JsonOrForm<UpdateAlbumRequest>,
+) -> Result<Response> {
     let album = albums::Entity::find_by_id(id)
         .one(&state.db)
         .await?
         .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
 
-    let mut active: albums::ActiveModel = album.into();
-    let ownership_changed = payload.ownership_status.is_some();
+    let reason = payload.reason.as_deref().unwrap_or(ownership::REASON_MANUAL);
+    if reason.is_empty() || reason.len() > 64 {
+        return Err(AppError::Validation("Reason must be 1-64 characters".to_string()));
+    }
+    let artist_choice = payload.artist_choice()?;
+    let genres = payload.genres.as_ref().map(genres::parse_edit).transpose()?;
+
+    let from_status = ownership::current_status(&album);
+    let mut active: albums::ActiveModel = album.clone().into();
+    let mut ownership_change = None;
 
-    if let Some(status) = payload.ownership_status {
+    if let Some(status) = payload.ownership_status.as_deref() {
         // Parse the ownership status
-        let ownership_status = match status.as_str() {
-            "not_owned" => OwnershipStatus::NotOwned,
-            "owned" => OwnershipStatus::Owned,
-            "downloading" => OwnershipStatus::Downloading,
-            _ => return Err(AppError::Internal("Invalid ownership status".to_string())),
-        };
-        active.ownership_status = Set(ownership_status.as_str().to_string());
+        let ownership_status = OwnershipStatus::from_str(status)
+            .ok_or_else(|| AppError::Validation(format!("Invalid ownership status: {}", status)))?;
+        ownership::check_transition(from_status, ownership_status, reason, payload.force)?;
+        ownership::set_status(&mut active, from_status, ownership_status);
+        ownership_change = Some(ownership_status);
     }
 
-    if let Some(source) = payload.acquisition_source {
-        let acquisition_source = match source.as_str() {
+    if let Some(source) = payload.acquisition_source.as_deref() {
+        let acquisition_source = match source {
             "bandcamp" => Some(AcquisitionSource::Bandcamp),
             "physical" => Some(AcquisitionSource::Physical),
             "lidarr" => Some(AcquisitionSource::Lidarr),
@@ -234,55 +854,396 @@ pub async fn update_album(
         active.acquisition_source = Set(acquisition_source.map(|s| s.as_str().to_string()));
     }
 
-    if let Some(path) = payload.local_path {
-        active.local_path = Set(Some(path));
+    if let Some(path) = &payload.local_path {
+        active.local_path = Set(Some(path.clone()));
     }
 
-    active.updated_at = Set(chrono::Utc::now().into());
-    let updated = active.update(&state.db).await?;
+    if let Some(value) = payload.estimated_value {
+        if !value.is_finite() || value < 0.0 {
+            return Err(AppError::Validation(format!("Invalid estimated value: {}", value)));
+        }
+        active.estimated_value = Set(Some(value));
+    }
 
-    // Update playlist owned_count if ownership changed
-    if ownership_changed {
-        if let Err(e) = crate::services::playlist_stats::update_playlists_for_album(&state.db, id).await {
-            tracing::warn!("Failed to update playlist stats after album ownership change: {}", e);
+    // Priorities belong to the profile's link; setting one also undoes a cleared want
+    let mut want_link = None;
+    if let Some(priority) = payload.want_priority {
+        if !(wanted::LOWEST_WANT_PRIORITY..=wanted::MUST_HAVE_PRIORITY).contains(&priority) {
+            return Err(AppError::Validation(format!(
+                "Invalid want priority {}: expected 1 to 5",
+                priority
+            )));
         }
+        let link = profile::album_link(&state.db, active_profile.id, id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound("Album is not in this profile's library".to_string())
+            })?;
+        let mut link: profile_albums::ActiveModel = link.into();
+        link.want_priority = Set(priority);
+        link.want_cleared_at = Set(None);
+        want_link = Some(link);
+    }
+
+    if let Some(mbid) = &payload.musicbrainz_release_group_id {
+        let mbid = uuid::Uuid::parse_str(mbid.trim()).map_err(|_| {
+            AppError::Validation(format!("Invalid MusicBrainz release group ID: {}", mbid))
+        })?;
+        active.musicbrainz_release_group_id = Set(Some(mbid.to_string()));
+        active.match_status = Set(Some(MatchStatus::Matched.as_str().to_string()));
+        active.match_score = Set(None);
+        active.match_source = Set(Some(MatchSource::Manual.as_str().to_string()));
+    }
+
+    if let Some(mbid) = &payload.lidarr_artist_mbid_override {
+        active.lidarr_artist_mbid_override = Set(lidarr::parse_artist_override(mbid)?);
+    }
+
+    if let Some(notes) = &payload.notes {
+        let notes = notes.trim();
+        active.notes = Set((!notes.is_empty()).then(|| notes.to_string()));
+    }
+
+    if let Some(genres) = &genres {
+        active.genres = Set(genres::to_column(genres));
+        active.genre_source = Set(Some(GenreSource::Manual.into()));
+    }
+
+    // Only the artist changes here; the update below leaves artist_id alone
+    if let Some(choice) = artist_choice {
+        album_artist::reassign_album(&state.db, album, choice).await?;
+    }
+
+    active.updated_at = Set(chrono::Utc::now().into());
+    let txn = state.db.begin().await?;
+    active.update(&txn).await?;
+    if let Some(link) = want_link {
+        link.update(&txn).await?;
+    }
+    if let Some(to_status) = ownership_change {
+        ownership::record_transition(&txn, id, from_status, to_status, reason, payload.force).await?;
+    }
+    txn.commit().await?;
+    album_grid_cache::invalidate(&state.redis).await;
+
+    if let Some(to_status) = ownership_change {
+        ownership::publish_transition(&state.events, id, from_status, to_status, reason, payload.force);
+    }
+
+    if card_query.wants_card(&headers) {
+        return Ok(Html(album_card_fragment(&state, id).await?).into_response());
     }
 
     // Fetch with artist for response
-    get_album(State(state), Path(id)).await
+    Ok(get_album(State(state), ActiveProfile(active_profile), Path(id.to_string()))
+        .await?
+        .into_response())
 }
 
-pub async fn trigger_match(
+/// Put matched albums back to pending so the next match-all job reconsiders them
+///
+/// Manually assigned matches are left alone unless the scope is `all`.
+pub async fn reset_matches(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<serde_json::Value>> {
-    // This would trigger a background job to match this specific album
-    // For now, return a placeholder
-    Ok(Json(serde_json::json!({
-        "message": "Match job queued",
-        "album_id": id
-    })))
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<ResetMatchesQuery>,
+) -> Result<Json<ResetMatchesResponse>> {
+    let scope = query
+        .scope
+        .ok_or_else(|| AppError::Validation("Missing scope".to_string()))?;
+
+    let pending = MatchStatus::Pending.as_str();
+    let not_manual = Condition::any()
+        .add(albums::Column::MatchSource.is_null())
+        .add(albums::Column::MatchSource.ne(MatchSource::Manual.as_str()));
+    let condition = match scope.as_str() {
+        "low_score" => Condition::all()
+            .add(albums::Column::MatchScore.is_not_null())
+            .add(albums::Column::MatchScore.lt(AUTO_MATCH_SCORE))
+            .add(not_manual),
+        "no_match" => Condition::all()
+            .add(albums::Column::MatchStatus.eq(MatchStatus::NoMatch.as_str()))
+            .add(not_manual),
+        "all" => Condition::any()
+            .add(albums::Column::MatchStatus.ne(pending))
+            .add(albums::Column::MatchStatus.is_null()),
+        _ => return Err(AppError::Validation(format!("Invalid scope: {}", scope))),
+    };
+
+    let reset_count = albums::Entity::update_many()
+        .col_expr(albums::Column::MatchStatus, Expr::value(pending))
+        .col_expr(albums::Column::MatchScore, Expr::value(Option::<i32>::None))
+        .col_expr(
+            albums::Column::MusicbrainzReleaseGroupId,
+            Expr::value(Option::<String>::None),
+        )
+        .col_expr(albums::Column::MatchSource, Expr::value(Option::<String>::None))
+        .col_expr(albums::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(condition)
+        .exec(&state.db)
+        .await?
+        .rows_affected;
+    album_grid_cache::invalidate(&state.redis).await;
+
+    tracing::info!("Reset {} album match(es) to pending (scope: {})", reset_count, scope);
+
+    Ok(Json(ResetMatchesResponse { scope, reset_count }))
 }
 
-pub async fn search_lidarr(
+/// Queue a MusicBrainz match for one album, whatever its match status
+pub async fn trigger_match(
     State(state): State<AppState>,
     Path(id): Path<i32>,
-) -> Result<Json<serde_json::Value>> {
-    use crate::services::LidarrService;
+) -> Result<Json<JobCreatedResponse>> {
+    let album = albums::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+
+    let now = chrono::Utc::now().into();
+    let job = jobs::ActiveModel {
+        job_type: Set(JobType::MusicbrainzMatch.as_str().to_string()),
+        status: Set(JobStatus::Pending.as_str().to_string()),
+        entity_id: Set(Some(album.id)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
 
-    // Get user settings for Lidarr configuration
-    let settings = user_settings::Entity::find()
+    let job_id = job.id;
+    enqueue_job(&state, job, JobType::MusicbrainzMatch).await?;
+
+    Ok(Json(JobCreatedResponse {
+        job_id,
+        status: JobStatus::Pending.as_str().to_string(),
+    }))
+}
+
+/// One entry in an album's activity history
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlbumActivity {
+    /// A background job that worked on this album
+    Job {
+        id: i32,
+        job_type: String,
+        status: String,
+        error_message: Option<String>,
+        at: String,
+    },
+    /// A Lidarr download of the album
+    LidarrDownload {
+        id: i32,
+        status: String,
+        download_id: Option<String>,
+        error_message: Option<String>,
+        retry_count: i32,
+        at: String,
+    },
+    /// A change of ownership status, including those made by Lidarr searches
+    OwnershipChange {
+        from_status: String,
+        to_status: String,
+        reason: String,
+        at: String,
+    },
+    /// The demotion rule lowered the album's priority, or cleared the want
+    WantDemotion {
+        id: i32,
+        from_priority: i32,
+        to_priority: i32,
+        cleared: bool,
+        /// Undoes the demotion; absent once restored
+        restore_url: Option<String>,
+        at: String,
+    },
+}
+
+#[derive(Serialize)]
+pub struct AlbumActivityResponse {
+    pub album_id: i32,
+    /// Newest first
+    pub activity: Vec<AlbumActivity>,
+}
+
+/// Unowned library albums by artists Spotify relates to an owned album's artist
+///
+/// Empty for albums that aren't owned or whose artist isn't on Spotify.
+pub(crate) async fn album_suggestions(
+    state: &AppState,
+    active_profile: profiles::Model,
+    key: &str,
+) -> Result<Vec<AlbumSuggestion>> {
+    let album = slug::find_album(&state.db, key)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Album {} not found", key)))?;
+    if album.ownership_status != OwnershipStatus::Owned.as_str() {
+        return Ok(Vec::new());
+    }
+    let artist_spotify_id = artists::Entity::find_by_id(album.artist_id)
         .one(&state.db)
         .await?
-        .ok_or_else(|| AppError::Internal("User settings not found".to_string()))?;
+        .and_then(|artist| artist.spotify_id);
+    let Some(artist_spotify_id) = artist_spotify_id else {
+        return Ok(Vec::new());
+    };
+
+    let profile_id = active_profile.id;
+    let related = match suggestions::cached_related_artists(&state.redis, &artist_spotify_id).await {
+        Some(related) => related,
+        None => {
+            let spotify_service = SpotifyService::from_config(&state.config);
+            let access_token = spotify_service.profile_access_token(&state.db, active_profile).await?;
+            let related: Vec<RelatedArtist> = spotify_service
+                .fetch_related_artists(&access_token, &artist_spotify_id)
+                .await?
+                .into_iter()
+                .map(RelatedArtist::from)
+                .collect();
+            suggestions::cache_related_artists(&state.redis, &artist_spotify_id, &related).await;
+            related
+        }
+    };
+
+    suggestions::library_suggestions(&state.db, profile_id, &related).await
+}
+
+/// Up to five albums to get next, for an owned album: unowned albums already
+/// in the library by related artists
+pub async fn get_album_suggestions(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
+) -> Result<Json<Vec<AlbumSuggestion>>> {
+    Ok(Json(album_suggestions(&state, active_profile, &key).await?))
+}
+
+/// Jobs, Lidarr downloads, ownership changes and want demotions for one
+/// album, newest first
+pub async fn get_album_activity(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
+) -> Result<Json<AlbumActivityResponse>> {
+    let album = slug::find_album(&state.db, &key)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+
+    let album_jobs = jobs::Entity::find()
+        .filter(jobs::Column::EntityId.eq(album.id))
+        .filter(jobs::Column::JobType.is_in(JobType::ALBUM_SCOPED.iter().map(|t| t.as_str())))
+        .all(&state.db)
+        .await?;
+    let downloads = lidarr_downloads::Entity::find()
+        .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
+        .all(&state.db)
+        .await?;
+    let events = ownership_events::Entity::find()
+        .filter(ownership_events::Column::AlbumId.eq(album.id))
+        .all(&state.db)
+        .await?;
+    let demotions = wanted::album_demotions(&state.db, active_profile.id, album.id).await?;
+
+    let mut timeline: Vec<(chrono::DateTime<chrono::FixedOffset>, AlbumActivity)> = Vec::new();
+    timeline.extend(album_jobs.into_iter().map(|j| {
+        (
+            j.created_at,
+            AlbumActivity::Job {
+                id: j.id,
+                job_type: j.job_type,
+                status: j.status,
+                error_message: j.error_message,
+                at: j.created_at.to_rfc3339(),
+            },
+        )
+    }));
+    timeline.extend(downloads.into_iter().map(|d| {
+        (
+            d.created_at,
+            AlbumActivity::LidarrDownload {
+                id: d.id,
+                status: d.status,
+                download_id: d.download_id,
+                error_message: d.error_message,
+                retry_count: d.retry_count,
+                at: d.created_at.to_rfc3339(),
+            },
+        )
+    }));
+    timeline.extend(events.into_iter().map(|e| {
+        (
+            e.created_at,
+            AlbumActivity::OwnershipChange {
+                from_status: e.from_status,
+                to_status: e.to_status,
+                reason: e.reason,
+                at: e.created_at.to_rfc3339(),
+            },
+        )
+    }));
+    timeline.extend(demotions.into_iter().map(|d| {
+        (
+            d.created_at,
+            AlbumActivity::WantDemotion {
+                id: d.id,
+                from_priority: d.from_priority,
+                to_priority: d.to_priority,
+                cleared: d.cleared,
+                restore_url: d
+                    .restored_at
+                    .is_none()
+                    .then(|| format!("/api/wanted/demotions/{}/restore", d.id)),
+                at: d.created_at.to_rfc3339(),
+            },
+        )
+    }));
+    // Stable, so entries with the same timestamp keep their per-kind order
+    timeline.sort_by(|a, b| b.0.cmp(&a.0));
 
-    let lidarr_url = settings
-        .lidarr_url
-        .ok_or_else(|| AppError::Internal("Lidarr URL not configured".to_string()))?;
+    Ok(Json(AlbumActivityResponse {
+        album_id: album.id,
+        activity: timeline.into_iter().map(|(_, entry)| entry).collect(),
+    }))
+}
 
-    let lidarr_api_key = settings
-        .lidarr_api_key
-        .ok_or_else(|| AppError::Internal("Lidarr API key not configured".to_string()))?;
+#[derive(Serialize)]
+pub struct RestoreDemotionResponse {
+    pub id: i32,
+    pub album_id: i32,
+    pub want_priority: i32,
+    pub restored_at: Option<String>,
+}
+
+/// Undo a demotion made by the stale wants rule
+pub async fn restore_demotion(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+) -> Result<Json<RestoreDemotionResponse>> {
+    let demotion = wanted::restore_demotion(&state.db, active_profile.id, id).await?;
+    album_grid_cache::invalidate(&state.redis).await;
+    Ok(Json(RestoreDemotionResponse {
+        id: demotion.id,
+        album_id: demotion.album_id,
+        want_priority: demotion.from_priority,
+        restored_at: demotion.restored_at.map(|t| t.to_rfc3339()),
+    }))
+}
+
+/// Ask Lidarr to search for an album
+///
+/// HTMX callers get a notification fragment; everyone else gets JSON.
+pub async fn search_lidarr(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+    Query(card_query): Query<CardQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let (lidarr_url, lidarr_api_key) = super::settings::lidarr_connection(&state, Some(active_profile.id)).await?;
 
     // Get the album from database
     let album = albums::Entity::find_by_id(id)
@@ -290,21 +1251,37 @@ pub async fn search_lidarr(
         .await?
         .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
 
+    let is_htmx = headers.contains_key("HX-Request");
+    let album_slug = slug::link_key(album.slug.as_deref(), album.id);
+
+    let from_status = ownership::current_status(&album);
+    ownership::check_transition(
+        from_status,
+        OwnershipStatus::Downloading,
+        ownership::REASON_LIDARR_SEARCH,
+        false,
+    )?;
+
     // Get MusicBrainz ID
     let mb_id = album
         .musicbrainz_release_group_id
         .clone()
         .ok_or_else(|| {
-            AppError::Internal(
+            AppError::Validation(
                 "Album not matched to MusicBrainz. Please match it first.".to_string(),
             )
         })?;
 
-    let lidarr_service = LidarrService::new();
+    let lidarr_service = super::settings::lidarr_service(&state, Some(active_profile.id)).await?;
 
     // Lookup album in Lidarr by MusicBrainz ID
     let lidarr_album = lidarr_service
-        .lookup_album(&lidarr_url, &lidarr_api_key, &mb_id.to_string())
+        .lookup_album(
+            &lidarr_url,
+            &lidarr_api_key,
+            &mb_id.to_string(),
+            album.lidarr_artist_mbid_override.as_deref(),
+        )
         .await?;
 
     match lidarr_album {
@@ -315,67 +1292,147 @@ pub async fn search_lidarr(
                 .await?;
 
             // Update album status to Downloading
+            let txn = state.db.begin().await?;
             let mut active: albums::ActiveModel = album.into();
-            active.ownership_status = Set(OwnershipStatus::Downloading.as_str().to_string());
+            ownership::set_status(&mut active, from_status, OwnershipStatus::Downloading);
             active.updated_at = Set(chrono::Utc::now().into());
-            active.update(&state.db).await?;
+            active.update(&txn).await?;
+            ownership::record_transition(
+                &txn,
+                id,
+                from_status,
+                OwnershipStatus::Downloading,
+                ownership::REASON_LIDARR_SEARCH,
+                false,
+            )
+            .await?;
+            txn.commit().await?;
+            ownership::publish_transition(
+                &state.events,
+                id,
+                from_status,
+                OwnershipStatus::Downloading,
+                ownership::REASON_LIDARR_SEARCH,
+                false,
+            );
+            album_grid_cache::invalidate(&state.redis).await;
+
+            if is_htmx {
+                let mut html =
+                    lidarr_search_started_notification(&album_slug, search_result.id).into_string();
+                if card_query.wants_card(&headers) {
+                    html.push_str(&album_card_fragment(&state, id).await?);
+                }
+                return Ok(Html(html).into_response());
+            }
 
             Ok(Json(serde_json::json!({
                 "success": true,
                 "message": "Lidarr search triggered",
                 "command_id": search_result.id,
                 "album_id": id
-            })))
+            }))
+            .into_response())
         }
         None => {
             // Album doesn't exist in Lidarr yet
             // TODO: Implement adding album to Lidarr first
+            if is_htmx {
+                return Ok(Html(lidarr_album_missing_notification().into_string()).into_response());
+            }
+
             Ok(Json(serde_json::json!({
                 "success": false,
                 "message": "Album not found in Lidarr. Please add it to Lidarr first.",
                 "album_id": id
-            })))
+            }))
+            .into_response())
         }
     }
 }
 
-pub async fn get_stats(State(state): State<AppState>) -> Result<Json<StatsResponse>> {
-    let total_albums = albums::Entity::find().count(&state.db).await?;
+pub async fn get_stats(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Json<StatsResponse>> {
+    let exclude_imports = db_settings::for_profile(&state.db, &active_profile)
+        .await?
+        .and_then(|s| s.stats_exclude_playlist_imports)
+        .unwrap_or(false);
 
-    let owned_albums = albums::Entity::find()
-        .filter(albums::Column::OwnershipStatus.eq("owned"))
-        .count(&state.db)
-        .await?;
+    if let Some(stats) = stats_cache::get(&state.redis, active_profile.id, exclude_imports).await {
+        return Ok(Json(stats));
+    }
 
-    let not_owned_albums = albums::Entity::find()
-        .filter(albums::Column::OwnershipStatus.eq("not_owned"))
-        .count(&state.db)
-        .await?;
+    let stats = count_stats(&state.db, active_profile.id, exclude_imports).await?;
+    stats_cache::put(&state.redis, active_profile.id, exclude_imports, &stats).await;
+    Ok(Json(stats))
+}
 
-    let downloading_albums = albums::Entity::find()
-        .filter(albums::Column::OwnershipStatus.eq("downloading"))
-        .count(&state.db)
-        .await?;
+/// Count a profile's albums and artists in two queries
+///
+/// Archived albums never count; with playlist imports excluded, only albums
+/// saved in Spotify do. Both kinds are reported under `ignored` instead.
+async fn count_stats(db: &DatabaseConnection, profile_id: i32, exclude_imports: bool) -> Result<StatsResponse> {
+    #[derive(FromQueryResult)]
+    struct AlbumCount {
+        ownership_status: String,
+        match_status: Option<String>,
+        source: String,
+        archived: bool,
+        count: i64,
+    }
 
-    let matched_albums = albums::Entity::find()
-        .filter(albums::Column::MatchStatus.eq("matched"))
-        .count(&state.db)
+    let archived = Expr::col(albums::Column::ArchivedAt).is_not_null();
+    let counts = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::OwnershipStatus)
+        .column(albums::Column::MatchStatus)
+        .column(albums::Column::Source)
+        .column_as(archived.clone(), "archived")
+        .column_as(Expr::col(albums::Column::Id).count(), "count")
+        .filter(profile::albums_in_profile(profile_id))
+        .group_by(albums::Column::OwnershipStatus)
+        .group_by(albums::Column::MatchStatus)
+        .group_by(albums::Column::Source)
+        .group_by(archived)
+        .into_model::<AlbumCount>()
+        .all(db)
         .await?;
 
-    let unmatched_albums = albums::Entity::find()
-        .filter(albums::Column::MatchStatus.eq("pending"))
-        .count(&state.db)
-        .await?;
+    let mut stats = StatsResponse {
+        total_artists: artists::Entity::find()
+            .filter(profile::artists_in_profile(profile_id))
+            .count(db)
+            .await?,
+        ..Default::default()
+    };
+    for row in counts {
+        let count = row.count as u64;
+        if row.archived {
+            stats.ignored.archived += count;
+            continue;
+        }
+        if exclude_imports && row.source != AlbumSource::SavedAlbum.as_str() {
+            stats.ignored.playlist_imports += count;
+            continue;
+        }
 
-    let total_artists = artists::Entity::find().count(&state.db).await?;
+        stats.total_albums += count;
+        *stats.by_source.entry(row.source).or_default() += count;
+        match OwnershipStatus::from_str(&row.ownership_status) {
+            Some(OwnershipStatus::Owned) => stats.owned_albums += count,
+            Some(OwnershipStatus::NotOwned) => stats.not_owned_albums += count,
+            Some(OwnershipStatus::Downloading) => stats.downloading_albums += count,
+            Some(OwnershipStatus::Ordered) => stats.ordered_albums += count,
+            None => {}
+        }
+        match row.match_status.as_deref().and_then(MatchStatus::from_str) {
+            Some(MatchStatus::Matched) => stats.matched_albums += count,
+            Some(MatchStatus::Pending) => stats.unmatched_albums += count,
+            _ => {}
+        }
+    }
 
-    Ok(Json(StatsResponse {
-        total_albums,
-        owned_albums,
-        not_owned_albums,
-        downloading_albums,
-        matched_albums,
-        unmatched_albums,
-        total_artists,
-    }))
+    Ok(stats)
 }
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{AppError, Result},
+    handlers::profiles::ActiveProfile,
+    services::{
+        album_grid_cache,
+        archive::{self, Archive, ArchiveKind},
+    },
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct ArchiveQuery {
+    /// `albums`, `playlists` or `tracks`; all three when absent
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RestoreResponse {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub id: i32,
+}
+
+fn parse_kind(kind: &str) -> Result<ArchiveKind> {
+    ArchiveKind::parse(kind).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Invalid archive type '{}': expected albums, playlists or tracks",
+            kind
+        ))
+    })
+}
+
+/// Albums, playlists and Liked Songs tracks that disappeared from Spotify
+pub async fn list_archive(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<ArchiveQuery>,
+) -> Result<Json<Archive>> {
+    let kind = query.kind.as_deref().map(parse_kind).transpose()?;
+    Ok(Json(archive::list_archive(&state.db, active_profile.id, kind).await?))
+}
+
+/// Take an album, playlist or track out of the archive
+pub async fn restore(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path((kind, id)): Path<(String, i32)>,
+) -> Result<Json<RestoreResponse>> {
+    let archive_kind = parse_kind(&kind)?;
+    archive::restore(&state.db, active_profile.id, archive_kind, id).await?;
+    if archive_kind == ArchiveKind::Album {
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+    Ok(Json(RestoreResponse { kind, id }))
+}
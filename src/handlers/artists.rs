@@ -3,14 +3,22 @@ use axum::{
     Json,
 };
 use sea_orm::{
-    ColumnTrait, EntityTrait, FromQueryResult, JoinType, PaginatorTrait, QueryFilter, QueryOrder,
-    QuerySelect, RelationTrait,
+    sea_query::Expr, ColumnTrait, EntityTrait, FromQueryResult, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    db::entities::{albums, artists},
+    db::{
+        entities::{albums, artists},
+        enums::{AcquisitionSource, OwnershipStatus},
+        profile,
+        repositories::ArtistRepository,
+        slug,
+    },
     error::{AppError, Result},
+    handlers::profiles::ActiveProfile,
+    services::{album_grid_cache, ownership},
     state::AppState,
 };
 
@@ -47,6 +55,7 @@ fn default_page_size() -> u64 {
 pub struct ArtistResponse {
     pub id: i32,
     pub name: String,
+    pub image_url: Option<String>,
     pub album_count: i64,
     pub owned_count: i64,
     pub not_owned_count: i64,
@@ -83,25 +92,80 @@ pub struct ArtistAlbumResponse {
     pub match_score: Option<i32>,
 }
 
+#[derive(Deserialize, Default)]
+pub struct MarkArtistOwnedRequest {
+    /// Recorded on every album that becomes owned
+    pub acquisition_source: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MarkArtistOwnedResponse {
+    pub artist_id: i32,
+    /// Albums that weren't owned before
+    pub updated_count: u64,
+}
+
 /// Internal struct for querying artist with album stats
 #[derive(FromQueryResult)]
-struct ArtistWithStats {
+pub(crate) struct ArtistWithStats {
     id: i32,
     name: String,
+    image_url: Option<String>,
     album_count: i64,
     owned_count: i64,
 }
 
+impl From<ArtistWithStats> for ArtistResponse {
+    fn from(a: ArtistWithStats) -> Self {
+        let ownership_percentage = if a.album_count > 0 {
+            (a.owned_count as f64 / a.album_count as f64) * 100.0
+        } else {
+            0.0
+        };
+        Self {
+            id: a.id,
+            name: a.name,
+            image_url: a.image_url,
+            album_count: a.album_count,
+            owned_count: a.owned_count,
+            not_owned_count: a.album_count - a.owned_count,
+            ownership_percentage,
+        }
+    }
+}
+
+/// Owned albums among those counted by `artist_stats`
+pub(crate) const OWNED_COUNT_SQL: &str =
+    "SUM(CASE WHEN albums.ownership_status = 'owned' THEN 1 ELSE 0 END)";
+
+/// Artists with their album and owned counts in a profile's library, as `ArtistWithStats` rows
+pub(crate) fn artist_stats(profile_id: i32) -> Select<artists::Entity> {
+    // Use raw SQL for the conditional count since SeaORM's CASE doesn't directly support .sum()
+    artists::Entity::find()
+        .select_only()
+        .column(artists::Column::Id)
+        .column(artists::Column::Name)
+        .column(artists::Column::ImageUrl)
+        .column_as(albums::Column::Id.count(), "album_count")
+        .column_as(Expr::cust(OWNED_COUNT_SQL), "owned_count")
+        .join(JoinType::LeftJoin, artists::Relation::Albums.def())
+        .filter(profile::albums_in_profile(profile_id))
+        .group_by(artists::Column::Id)
+        .group_by(artists::Column::Name)
+        .group_by(artists::Column::ImageUrl)
+}
+
 /// List artists with album statistics
 pub async fn list_artists(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Query(query): Query<ListArtistsQuery>,
 ) -> Result<Json<PaginatedArtistsResponse>> {
     let page = query.page.max(1);
     let page_size = query.page_size.min(200).max(1);
 
-    // Build base query for filtering
-    let mut base_filter = artists::Entity::find();
+    // Build base query for filtering (artists with albums in the active profile)
+    let mut base_filter = artists::Entity::find().filter(profile::artists_in_profile(active_profile.id));
 
     if let Some(search) = &query.search {
         if !search.is_empty() {
@@ -141,43 +205,15 @@ pub async fn list_artists(
     }
 
     // Query artists with aggregate stats
-    // Use raw SQL for the conditional count since SeaORM's CASE doesn't directly support .sum()
-    let artists_with_stats: Vec<ArtistWithStats> = artists::Entity::find()
+    let artists_with_stats: Vec<ArtistWithStats> = artist_stats(active_profile.id)
         .filter(artists::Column::Id.is_in(artist_ids.clone()))
-        .select_only()
-        .column(artists::Column::Id)
-        .column(artists::Column::Name)
-        .column_as(albums::Column::Id.count(), "album_count")
-        .column_as(
-            sea_orm::prelude::Expr::cust("SUM(CASE WHEN albums.ownership_status = 'owned' THEN 1 ELSE 0 END)"),
-            "owned_count",
-        )
-        .join(JoinType::LeftJoin, artists::Relation::Albums.def())
-        .group_by(artists::Column::Id)
-        .group_by(artists::Column::Name)
         .into_model::<ArtistWithStats>()
         .all(&state.db)
         .await?;
 
     // Convert to response and apply sorting
-    let mut artist_responses: Vec<ArtistResponse> = artists_with_stats
-        .into_iter()
-        .map(|a| {
-            let ownership_percentage = if a.album_count > 0 {
-                (a.owned_count as f64 / a.album_count as f64) * 100.0
-            } else {
-                0.0
-            };
-            ArtistResponse {
-                id: a.id,
-                name: a.name,
-                album_count: a.album_count,
-                owned_count: a.owned_count,
-                not_owned_count: a.album_count - a.owned_count,
-                ownership_percentage,
-            }
-        })
-        .collect();
+    let mut artist_responses: Vec<ArtistResponse> =
+        artists_with_stats.into_iter().map(ArtistResponse::from).collect();
 
     // Sort based on query params
     match query.sort_by.as_str() {
@@ -227,17 +263,18 @@ pub async fn list_artists(
 /// Get a single artist with their albums
 pub async fn get_artist(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
 ) -> Result<Json<ArtistDetailResponse>> {
-    // Get the artist
-    let artist = artists::Entity::find_by_id(id)
-        .one(&state.db)
+    // Get the artist by ID or slug
+    let artist = slug::find_artist(&state.db, &key)
         .await?
         .ok_or_else(|| AppError::NotFound("Artist not found".to_string()))?;
 
-    // Get all albums for this artist
+    // Get all albums for this artist in the active profile's library
     let artist_albums = albums::Entity::find()
-        .filter(albums::Column::ArtistId.eq(id))
+        .filter(albums::Column::ArtistId.eq(artist.id))
+        .filter(profile::albums_in_profile(active_profile.id))
         .order_by_desc(albums::Column::ReleaseDate)
         .all(&state.db)
         .await?;
@@ -256,6 +293,7 @@ pub async fn get_artist(
     let artist_response = ArtistResponse {
         id: artist.id,
         name: artist.name,
+        image_url: artist.image_url,
         album_count,
         owned_count,
         not_owned_count: album_count - owned_count,
@@ -279,3 +317,131 @@ pub async fn get_artist(
         albums: album_responses,
     }))
 }
+
+#[derive(Deserialize)]
+pub struct RenameArtistRequest {
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct RenameArtistResponse {
+    pub id: i32,
+    pub name: String,
+    /// Unchanged by a rename, so existing links keep working
+    pub slug: Option<String>,
+    /// Syncs keep this name instead of Spotify's
+    pub name_locked: bool,
+}
+
+/// Correct an artist's name
+///
+/// The new name is locked, so later Spotify syncs don't change it back.
+pub async fn rename_artist(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Json(payload): Json<RenameArtistRequest>,
+) -> Result<Json<RenameArtistResponse>> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Artist name must not be empty".to_string()));
+    }
+
+    let artist = slug::find_artist(&state.db, &key)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Artist not found".to_string()))?;
+
+    let renamed = ArtistRepository::new(state.db.clone()).rename(artist, name).await?;
+
+    // Album cards show the artist name
+    album_grid_cache::invalidate(&state.redis).await;
+
+    Ok(Json(RenameArtistResponse {
+        id: renamed.id,
+        name: renamed.name,
+        slug: renamed.slug,
+        name_locked: renamed.name_locked,
+    }))
+}
+
+/// Mark all of an artist's albums in the active profile's library as owned
+///
+/// Albums that are already owned are left alone, including their acquisition
+/// source. Playlist owned counts are recalculated for the albums that changed.
+pub async fn mark_artist_owned(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
+    payload: Option<Json<MarkArtistOwnedRequest>>,
+) -> Result<Json<MarkArtistOwnedResponse>> {
+    let Json(payload) = payload.unwrap_or_default();
+
+    let acquisition_source = match payload.acquisition_source.as_deref() {
+        Some(source) => Some(AcquisitionSource::from_str(source).ok_or_else(|| {
+            AppError::Validation(format!("Invalid acquisition source: {}", source))
+        })?),
+        None => None,
+    };
+
+    let artist = slug::find_artist(&state.db, &key)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Artist not found".to_string()))?;
+
+    let txn = state.db.begin().await?;
+
+    let rows: Vec<(i32, String)> = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::Id)
+        .column(albums::Column::OwnershipStatus)
+        .filter(albums::Column::ArtistId.eq(artist.id))
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(albums::Column::OwnershipStatus.ne(OwnershipStatus::Owned.as_str()))
+        .into_tuple()
+        .all(&txn)
+        .await?;
+
+    let mut changes = Vec::with_capacity(rows.len());
+    for (album_id, status) in rows {
+        let from = OwnershipStatus::from_str(&status).unwrap_or(OwnershipStatus::NotOwned);
+        ownership::check_transition(
+            from,
+            OwnershipStatus::Owned,
+            ownership::REASON_ARTIST_MARKED_OWNED,
+            false,
+        )?;
+        changes.push((album_id, from));
+    }
+    let album_ids: Vec<i32> = changes.iter().map(|(album_id, _)| *album_id).collect();
+
+    let mut update = albums::Entity::update_many()
+        .col_expr(albums::Column::OwnershipStatus, Expr::value(OwnershipStatus::Owned.as_str()))
+        .col_expr(albums::Column::WantedSince, Expr::value(Option::<chrono::DateTime<chrono::Utc>>::None))
+        .col_expr(albums::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+        .filter(albums::Column::Id.is_in(album_ids));
+    if let Some(source) = acquisition_source {
+        update = update.col_expr(albums::Column::AcquisitionSource, Expr::value(source.as_str()));
+    }
+    let updated_count = update.exec(&txn).await?.rows_affected;
+    ownership::record_transitions(
+        &txn,
+        &changes,
+        OwnershipStatus::Owned,
+        ownership::REASON_ARTIST_MARKED_OWNED,
+    )
+    .await?;
+
+    txn.commit().await?;
+    ownership::publish_transitions(
+        &state.events,
+        &changes,
+        OwnershipStatus::Owned,
+        ownership::REASON_ARTIST_MARKED_OWNED,
+    );
+    album_grid_cache::invalidate(&state.redis).await;
+
+    tracing::info!("Marked {} album(s) by {} as owned", updated_count, artist.name);
+
+    Ok(Json(MarkArtistOwnedResponse {
+        artist_id: artist.id,
+        updated_count,
+    }))
+}
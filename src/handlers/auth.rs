@@ -7,12 +7,13 @@ use axum::{
 use chrono::{Duration, Utc};
 use maud::html;
 use redis::AsyncCommands;
-use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, Set};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    db::entities::user_settings,
+    db::entities::profiles,
     error::Result,
+    handlers::profiles::ActiveProfile,
     services::SpotifyService,
     state::AppState,
 };
@@ -32,10 +33,7 @@ pub struct CallbackQuery {
 pub async fn authorize(
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse> {
-    let spotify_service = SpotifyService::new(
-        state.config.spotify_client_id.clone(),
-        state.config.spotify_redirect_uri.clone(),
-    );
+    let spotify_service = SpotifyService::from_config(&state.config);
 
     let auth_url = spotify_service.generate_authorization_url()?;
 
@@ -53,6 +51,7 @@ pub async fn authorize(
 
 pub async fn callback(
     State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
     Query(params): Query<CallbackQuery>,
 ) -> Result<impl IntoResponse> {
     // Retrieve code_verifier from Redis using state
@@ -66,10 +65,7 @@ pub async fn callback(
     // Delete the used state
     let _: () = redis_conn.del(&cache_key).await?;
 
-    let spotify_service = SpotifyService::new(
-        state.config.spotify_client_id.clone(),
-        state.config.spotify_redirect_uri.clone(),
-    );
+    let spotify_service = SpotifyService::from_config(&state.config);
 
     // Exchange code for tokens
     let token_response = spotify_service
@@ -78,36 +74,13 @@ pub async fn callback(
 
     let expires_at = Utc::now() + Duration::seconds(token_response.expires_in);
 
-    // Save tokens to database
-    let settings = user_settings::ActiveModel {
-        spotify_access_token: Set(Some(token_response.access_token)),
-        spotify_refresh_token: Set(token_response.refresh_token),
-        spotify_token_expires_at: Set(Some(expires_at.into())),
-        ..Default::default()
-    };
-
-    // Get first user settings or create new
-    let existing = user_settings::Entity::find()
-        .one(&state.db)
-        .await?;
-
-    if let Some(existing_settings) = existing {
-        let mut active: user_settings::ActiveModel = existing_settings.into();
-        active.spotify_access_token = settings.spotify_access_token;
-        active.spotify_refresh_token = settings.spotify_refresh_token;
-        active.spotify_token_expires_at = settings.spotify_token_expires_at;
-        active.update(&state.db).await?;
-    } else {
-        let new_settings = user_settings::ActiveModel {
-            spotify_access_token: settings.spotify_access_token,
-            spotify_refresh_token: settings.spotify_refresh_token,
-            spotify_token_expires_at: settings.spotify_token_expires_at,
-            created_at: Set(Utc::now().into()),
-            updated_at: Set(Utc::now().into()),
-            ..Default::default()
-        };
-        new_settings.insert(&state.db).await?;
-    }
+    // Save tokens on the active profile
+    let mut active: profiles::ActiveModel = profile.into();
+    active.spotify_access_token = Set(Some(token_response.access_token));
+    active.spotify_refresh_token = Set(token_response.refresh_token);
+    active.spotify_token_expires_at = Set(Some(expires_at.into()));
+    active.updated_at = Set(Utc::now().into());
+    active.update(&state.db).await?;
 
     // Redirect to settings page with success
     Ok(Redirect::to("/settings"))
@@ -116,20 +89,10 @@ pub async fn callback(
 /// Check Spotify connection status and attempt token refresh if needed
 pub async fn spotify_status(
     State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
 ) -> Result<Json<SpotifyStatus>> {
-    let settings = user_settings::Entity::find()
-        .one(&state.db)
-        .await?;
-
-    let Some(settings) = settings else {
-        return Ok(Json(SpotifyStatus {
-            connected: false,
-            needs_reauth: true,
-        }));
-    };
-
     // No token at all
-    let Some(access_token) = &settings.spotify_access_token else {
+    let Some(access_token) = &profile.spotify_access_token else {
         return Ok(Json(SpotifyStatus {
             connected: false,
             needs_reauth: true,
@@ -137,7 +100,7 @@ pub async fn spotify_status(
     };
 
     // Check if token is expired
-    let is_expired = settings
+    let is_expired = profile
         .spotify_token_expires_at
         .map(|exp| Utc::now() + Duration::minutes(5) >= exp.to_utc())
         .unwrap_or(true);
@@ -150,23 +113,20 @@ pub async fn spotify_status(
     }
 
     // Try to refresh the token
-    let Some(refresh_token) = &settings.spotify_refresh_token else {
+    let Some(refresh_token) = &profile.spotify_refresh_token else {
         return Ok(Json(SpotifyStatus {
             connected: false,
             needs_reauth: true,
         }));
     };
 
-    let spotify_service = SpotifyService::new(
-        state.config.spotify_client_id.clone(),
-        state.config.spotify_redirect_uri.clone(),
-    );
+    let spotify_service = SpotifyService::from_config(&state.config);
 
     match spotify_service.refresh_token(refresh_token).await {
         Ok(token_response) => {
             // Update tokens in database
             let expires_at = Utc::now() + Duration::seconds(token_response.expires_in);
-            let mut active: user_settings::ActiveModel = settings.into();
+            let mut active: profiles::ActiveModel = profile.into();
             active.spotify_access_token = Set(Some(token_response.access_token));
             if let Some(new_refresh) = token_response.refresh_token {
                 active.spotify_refresh_token = Set(Some(new_refresh));
@@ -193,42 +153,34 @@ pub async fn spotify_status(
 /// HTML partial for Spotify button - checks status and renders appropriate button
 pub async fn spotify_button(
     State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
 ) -> Result<Html<String>> {
-    let settings = user_settings::Entity::find()
-        .one(&state.db)
-        .await?;
-
     let mut needs_auth = true;
 
-    if let Some(settings) = settings {
-        if settings.spotify_access_token.is_some() {
-            // Check if expired
-            let is_expired = settings
-                .spotify_token_expires_at
-                .map(|exp| Utc::now() + Duration::minutes(5) >= exp.to_utc())
-                .unwrap_or(true);
-
-            if !is_expired {
-                needs_auth = false;
-            } else if let Some(refresh_token) = &settings.spotify_refresh_token {
-                // Try refresh
-                let spotify_service = SpotifyService::new(
-                    state.config.spotify_client_id.clone(),
-                    state.config.spotify_redirect_uri.clone(),
-                );
-
-                if let Ok(token_response) = spotify_service.refresh_token(refresh_token).await {
-                    let expires_at = Utc::now() + Duration::seconds(token_response.expires_in);
-                    let mut active: user_settings::ActiveModel = settings.into();
-                    active.spotify_access_token = Set(Some(token_response.access_token));
-                    if let Some(new_refresh) = token_response.refresh_token {
-                        active.spotify_refresh_token = Set(Some(new_refresh));
-                    }
-                    active.spotify_token_expires_at = Set(Some(expires_at.into()));
-                    active.updated_at = Set(Utc::now().into());
-                    let _ = active.update(&state.db).await;
-                    needs_auth = false;
+    if profile.spotify_access_token.is_some() {
+        // Check if expired
+        let is_expired = profile
+            .spotify_token_expires_at
+            .map(|exp| Utc::now() + Duration::minutes(5) >= exp.to_utc())
+            .unwrap_or(true);
+
+        if !is_expired {
+            needs_auth = false;
+        } else if let Some(refresh_token) = &profile.spotify_refresh_token {
+            // Try refresh
+            let spotify_service = SpotifyService::from_config(&state.config);
+
+            if let Ok(token_response) = spotify_service.refresh_token(refresh_token).await {
+                let expires_at = Utc::now() + Duration::seconds(token_response.expires_in);
+                let mut active: profiles::ActiveModel = profile.into();
+                active.spotify_access_token = Set(Some(token_response.access_token));
+                if let Some(new_refresh) = token_response.refresh_token {
+                    active.spotify_refresh_token = Set(Some(new_refresh));
                 }
+                active.spotify_token_expires_at = Set(Some(expires_at.into()));
+                active.updated_at = Set(Utc::now().into());
+                let _ = active.update(&state.db).await;
+                needs_auth = false;
             }
         }
     }
@@ -0,0 +1,187 @@
+//! File downloads streamed out in batches
+//!
+//! Exports read their rows a page at a time and send each page as soon as it
+//! is rendered, so large libraries don't have to fit in memory at once.
+
+use std::future::Future;
+
+use axum::{
+    body::Body,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures_util::stream;
+
+use crate::error::{AppError, Result};
+
+/// Rows read from the database per chunk of an export
+pub const EXPORT_BATCH_SIZE: u64 = 200;
+
+/// File layout of a download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    /// One MusicBrainz ID per line
+    Mbid,
+}
+
+impl ExportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Mbid => "mbid",
+        }
+    }
+
+    /// Parse `s` as one of the formats an endpoint offers
+    pub fn parse(s: &str, offered: &[Self]) -> Result<Self> {
+        offered.iter().copied().find(|format| format.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = offered.iter().map(|format| format.as_str()).collect();
+            AppError::Validation(format!("Unknown export format: {} (use {})", s, names.join(" or ")))
+        })
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Mbid => "text/plain; charset=utf-8",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Mbid => "txt",
+        }
+    }
+}
+
+/// How an export renders its rows
+pub trait ExportRows: Send + 'static {
+    type Row: Send + 'static;
+
+    /// Text before the first row
+    fn header(&mut self) -> String {
+        String::new()
+    }
+
+    /// Append one row to `out`
+    fn row(&mut self, row: Self::Row, out: &mut String);
+
+    /// Text after the last row
+    fn footer(&mut self) -> String {
+        String::new()
+    }
+}
+
+/// Stream a download named `name` (without extension), reading pages of
+/// `EXPORT_BATCH_SIZE` rows through `fetch_page` until one comes back short
+///
+/// A failed page ends the body with an error, so the client sees a broken
+/// download rather than a truncated file.
+pub fn download<R, F, Fut>(format: ExportFormat, name: &str, rows: R, fetch_page: F) -> Response
+where
+    R: ExportRows,
+    F: FnMut(u64) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<R::Row>>> + Send,
+{
+    let state = PagedExport { rows, fetch_page, page: 0, done: false };
+    let label = name.to_string();
+    let chunks = stream::unfold(state, move |mut export| {
+        let label = label.clone();
+        async move {
+            match export.next_chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), export)),
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::error!("Export {} failed: {}", label, e);
+                    export.done = true;
+                    Some((Err(std::io::Error::other(e.to_string())), export))
+                }
+            }
+        }
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.{}\"", name, format.extension()),
+            ),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response()
+}
+
+/// Paging state of an export being streamed out
+struct PagedExport<R, F> {
+    rows: R,
+    fetch_page: F,
+    page: u64,
+    done: bool,
+}
+
+impl<R, F, Fut> PagedExport<R, F>
+where
+    R: ExportRows,
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<Vec<R::Row>>>,
+{
+    /// Render the next page, or `None` once the footer is out
+    async fn next_chunk(&mut self) -> Result<Option<String>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let batch = (self.fetch_page)(self.page).await?;
+        let mut chunk = if self.page == 0 { self.rows.header() } else { String::new() };
+        self.page += 1;
+
+        let last_batch = (batch.len() as u64) < EXPORT_BATCH_SIZE;
+        for row in batch {
+            self.rows.row(row, &mut chunk);
+        }
+
+        if last_batch {
+            self.done = true;
+            chunk.push_str(&self.rows.footer());
+        }
+        Ok(Some(chunk))
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("Daft Punk"), "Daft Punk");
+        assert_eq!(csv_field("Crosby, Stills & Nash"), "\"Crosby, Stills & Nash\"");
+        assert_eq!(csv_field("The \"Band\""), "\"The \"\"Band\"\"\"");
+        assert_eq!(csv_field("Line\nBreak"), "\"Line\nBreak\"");
+    }
+
+    #[test]
+    fn test_parse_lists_offered_formats() {
+        let offered = [ExportFormat::Mbid, ExportFormat::Json];
+        assert_eq!(ExportFormat::parse("json", &offered).unwrap(), ExportFormat::Json);
+        let err = ExportFormat::parse("csv", &offered).unwrap_err();
+        assert_eq!(err.to_string(), "Validation error: Unknown export format: csv (use mbid or json)");
+    }
+}
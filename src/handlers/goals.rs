@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        entities::{artists, goals, playlists},
+        enums::GoalType,
+        profile,
+    },
+    error::{AppError, Result},
+    handlers::{
+        artists::{artist_stats, ArtistResponse, ArtistWithStats},
+        profiles::ActiveProfile,
+    },
+    services::playlist_stats,
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct CreateGoalRequest {
+    /// `playlist` or `artist`
+    pub goal_type: String,
+    pub target_id: i32,
+    pub target_percentage: f64,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateGoalRequest {
+    pub target_percentage: f64,
+}
+
+#[derive(Serialize)]
+pub struct GoalResponse {
+    pub id: i32,
+    pub goal_type: String,
+    pub target_id: i32,
+    /// Playlist or artist name; absent once the target left the library
+    pub target_name: Option<String>,
+    pub target_percentage: f64,
+    /// Owned tracks of a playlist, or owned albums of an artist
+    pub owned_count: i64,
+    pub total_count: i64,
+    pub current_percentage: f64,
+    pub achieved: bool,
+    /// Items still to acquire to reach the target
+    pub remaining: i64,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl GoalResponse {
+    fn new(goal: goals::Model, target_name: Option<String>, owned_count: i64, total_count: i64) -> Self {
+        let current_percentage = if total_count > 0 {
+            (owned_count as f64 / total_count as f64) * 100.0
+        } else {
+            0.0
+        };
+        // Smallest owned count whose percentage meets the target
+        let needed = (goal.target_percentage / 100.0 * total_count as f64).ceil() as i64;
+        let remaining = (needed - owned_count).max(0);
+
+        Self {
+            id: goal.id,
+            goal_type: goal.goal_type,
+            target_id: goal.target_id,
+            target_name,
+            target_percentage: goal.target_percentage,
+            owned_count,
+            total_count,
+            current_percentage,
+            achieved: total_count > 0 && remaining == 0,
+            remaining,
+            created_at: goal.created_at,
+        }
+    }
+}
+
+fn parse_goal_type(goal_type: &str) -> Result<GoalType> {
+    GoalType::parse(goal_type).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Invalid goal type '{}': expected playlist or artist",
+            goal_type
+        ))
+    })
+}
+
+fn validate_percentage(target_percentage: f64) -> Result<()> {
+    if !(target_percentage > 0.0 && target_percentage <= 100.0) {
+        return Err(AppError::Validation(
+            "target_percentage must be greater than 0 and at most 100".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Attach current progress to goals, using the same ownership counts as the
+/// playlist and artist views
+async fn with_progress(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    goals: Vec<goals::Model>,
+) -> Result<Vec<GoalResponse>> {
+    let ids_of = |goal_type: GoalType| -> Vec<i32> {
+        goals
+            .iter()
+            .filter(|g| g.goal_type == goal_type.as_str())
+            .map(|g| g.target_id)
+            .collect()
+    };
+    let playlist_ids = ids_of(GoalType::Playlist);
+    let artist_ids = ids_of(GoalType::Artist);
+
+    let goal_playlists = if playlist_ids.is_empty() {
+        Vec::new()
+    } else {
+        playlists::Entity::find()
+            .filter(playlists::Column::Id.is_in(playlist_ids))
+            .filter(profile::playlists_in_profile(profile_id))
+            .all(db)
+            .await?
+    };
+    let playlist_counts = playlist_stats::get_batch_playlist_counts(db, &goal_playlists).await?;
+    let playlist_names: HashMap<i32, String> =
+        goal_playlists.into_iter().map(|p| (p.id, p.name)).collect();
+
+    let artist_stats: HashMap<i32, ArtistResponse> = if artist_ids.is_empty() {
+        HashMap::new()
+    } else {
+        artist_stats(profile_id)
+            .filter(artists::Column::Id.is_in(artist_ids))
+            .into_model::<ArtistWithStats>()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|a| {
+                let artist = ArtistResponse::from(a);
+                (artist.id, artist)
+            })
+            .collect()
+    };
+
+    Ok(goals
+        .into_iter()
+        .map(|goal| {
+            let (name, owned, total) = match GoalType::parse(&goal.goal_type) {
+                Some(GoalType::Playlist) => {
+                    let counts = playlist_counts.get(&goal.target_id).copied().unwrap_or_default();
+                    (playlist_names.get(&goal.target_id).cloned(), counts.owned, counts.synced)
+                }
+                Some(GoalType::Artist) => match artist_stats.get(&goal.target_id) {
+                    Some(artist) => (Some(artist.name.clone()), artist.owned_count, artist.album_count),
+                    None => (None, 0, 0),
+                },
+                None => (None, 0, 0),
+            };
+            GoalResponse::new(goal, name, owned, total)
+        })
+        .collect())
+}
+
+async fn find_goal(db: &DatabaseConnection, profile_id: i32, id: i32) -> Result<goals::Model> {
+    goals::Entity::find_by_id(id)
+        .filter(goals::Column::ProfileId.eq(profile_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Goal {} not found", id)))
+}
+
+async fn goal_response(db: &DatabaseConnection, profile_id: i32, goal: goals::Model) -> Result<GoalResponse> {
+    with_progress(db, profile_id, vec![goal])
+        .await?
+        .pop()
+        .ok_or_else(|| AppError::Internal("Goal progress missing".to_string()))
+}
+
+/// The active profile's goals with their current progress
+pub async fn list_goals(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Json<Vec<GoalResponse>>> {
+    let goals = goals::Entity::find()
+        .filter(goals::Column::ProfileId.eq(active_profile.id))
+        .order_by_asc(goals::Column::CreatedAt)
+        .order_by_asc(goals::Column::Id)
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(with_progress(&state.db, active_profile.id, goals).await?))
+}
+
+pub async fn get_goal(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+) -> Result<Json<GoalResponse>> {
+    let goal = find_goal(&state.db, active_profile.id, id).await?;
+    Ok(Json(goal_response(&state.db, active_profile.id, goal).await?))
+}
+
+/// Set a target percentage for a playlist or artist in the active profile's library
+pub async fn create_goal(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Json(payload): Json<CreateGoalRequest>,
+) -> Result<(StatusCode, Json<GoalResponse>)> {
+    let goal_type = parse_goal_type(&payload.goal_type)?;
+    validate_percentage(payload.target_percentage)?;
+
+    let target_exists = match goal_type {
+        GoalType::Playlist => playlists::Entity::find_by_id(payload.target_id)
+            .filter(profile::playlists_in_profile(active_profile.id))
+            .one(&state.db)
+            .await?
+            .is_some(),
+        GoalType::Artist => artists::Entity::find_by_id(payload.target_id)
+            .filter(profile::artists_in_profile(active_profile.id))
+            .one(&state.db)
+            .await?
+            .is_some(),
+    };
+    if !target_exists {
+        return Err(AppError::NotFound(format!(
+            "{} {} not found",
+            goal_type.as_str(),
+            payload.target_id
+        )));
+    }
+
+    let duplicate = goals::Entity::find()
+        .filter(goals::Column::ProfileId.eq(active_profile.id))
+        .filter(goals::Column::GoalType.eq(goal_type.as_str()))
+        .filter(goals::Column::TargetId.eq(payload.target_id))
+        .one(&state.db)
+        .await?;
+    if let Some(duplicate) = duplicate {
+        return Err(AppError::Validation(format!(
+            "Goal {} already tracks this {}",
+            duplicate.id,
+            goal_type.as_str()
+        )));
+    }
+
+    let now = Utc::now();
+    let goal = goals::ActiveModel {
+        profile_id: Set(active_profile.id),
+        goal_type: Set(goal_type.into()),
+        target_id: Set(payload.target_id),
+        target_percentage: Set(payload.target_percentage),
+        created_at: Set(now.into()),
+        updated_at: Set(now.into()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(goal_response(&state.db, active_profile.id, goal).await?),
+    ))
+}
+
+/// Change a goal's target percentage
+pub async fn update_goal(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdateGoalRequest>,
+) -> Result<Json<GoalResponse>> {
+    validate_percentage(payload.target_percentage)?;
+    let goal = find_goal(&state.db, active_profile.id, id).await?;
+
+    let mut active: goals::ActiveModel = goal.into();
+    active.target_percentage = Set(payload.target_percentage);
+    active.updated_at = Set(Utc::now().into());
+    let goal = active.update(&state.db).await?;
+
+    Ok(Json(goal_response(&state.db, active_profile.id, goal).await?))
+}
+
+pub async fn delete_goal(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+) -> Result<StatusCode> {
+    let goal = find_goal(&state.db, active_profile.id, id).await?;
+    goals::Entity::delete_by_id(goal.id).exec(&state.db).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,12 +1,46 @@
-use axum::{http::StatusCode, Json};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
 use serde_json::{json, Value};
 
-pub async fn health_check() -> (StatusCode, Json<Value>) {
-    (
-        StatusCode::OK,
-        Json(json!({
-            "status": "healthy",
-            "service": "beat-collector"
-        })),
-    )
+use crate::{db::schema, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct HealthQuery {
+    /// Include applied database migrations and job queue depth in the response
+    #[serde(default)]
+    pub verbose: bool,
+}
+
+pub async fn health_check(
+    State(state): State<AppState>,
+    Query(query): Query<HealthQuery>,
+) -> (StatusCode, Json<Value>) {
+    let mut body = json!({
+        "status": "healthy",
+        "service": "beat-collector"
+    });
+
+    if query.verbose {
+        body["version"] = json!(env!("CARGO_PKG_VERSION"));
+        body["migrations"] = match schema::schema_status(&state.db).await {
+            Ok(status) => json!({
+                "applied": status.applied,
+                "unknown": status.unknown,
+            }),
+            Err(e) => {
+                tracing::error!("Failed to read applied migrations: {}", e);
+                json!({ "error": "Failed to read applied migrations" })
+            }
+        };
+        body["job_queue"] = json!({
+            "depth": state.job_queue.depth(),
+            "capacity": state.job_queue.capacity(),
+        });
+    }
+
+    (StatusCode::OK, Json(body))
 }
@@ -1,44 +1,114 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Path, Query, State},
-    response::Html,
+    response::{Html, IntoResponse},
+    Form,
 };
-use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect};
+use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect};
+use chrono_tz::Tz;
+use maud::Markup;
 use serde::Deserialize;
 
 use crate::{
     db::{
-        entities::{albums, artists, playlists, user_settings},
-        enums::OwnershipStatus,
+        entities::{
+            albums, artists, jobs, lidarr_downloads, playlist_tracks, playlists, profiles, top_items, tracks,
+            user_settings,
+        },
+        enums::{AlbumView, GenreSource, JobStatus, JobType, OwnershipStatus, TopItemKind},
+        profile, settings as db_settings, slug,
+    },
+    error::{AppError, Result},
+    handlers::profiles::ActiveProfile,
+    i18n::{self, Locale},
+    services::{
+        album_artist::{self, ArtistChoice},
+        album_grid_cache, archive as archive_service, artist_heatmap::{self, DEFAULT_HEATMAP_ARTISTS}, genres, lidarr, music_paths, ownership, playlist_cleanup, playlist_stats,
+        wanted::{self, WantedAge},
     },
-    error::Result,
-    services::playlist_stats,
     state::AppState,
+    t,
+    tasks::{
+        filesystem_scan::{ScanPreview, PREVIEW_CONFIDENCE_THRESHOLD},
+        spotify_sync::SyncProgress,
+        top_items::{cached_top_items, is_stale, refresh_top_items},
+    },
     templates::{
-        album_detail_modal, album_grid_partial, artist_detail_page, artist_grid_partial,
-        artists_page, home_page, jobs_page, playlists_page, playlist_detail_partial,
-        playlist_grid_partial, playlist_tracks_rows, playlist_card_oob, settings_page,
-        stats_page, AlbumCardData, ArtistCardData, PlaylistCardData, PlaylistTrackData,
+        album_detail_modal, album_grid_partial, archive_page, artist_detail_page, artist_grid_partial, artist_options,
+        artists_page, board_card_oob, lidarr_artist_preview, board_column_count, board_page, home_page, job_detail_page, jobs_list_partial, jobs_page, playlists_page,
+        playlist_detail_partial, playlist_grid_partial, playlist_tracks_rows, playlist_card_oob, playlist_stats_bar_oob, playlist_track_status,
+        album_suggestions_strip, artist_heatmap_panel, settings_page, notification, heavy_rotation_message, heavy_rotation_panel, heavy_rotation_reauth,
+        ownership_percentage, stats_page, resolve_timezone, timestamp, AlbumCardData, AlbumDownloadData, AlbumListensData, AlbumTracksData,
+        ArchivedRowData, ArtistCardData, LidarrArtistPreview, ArtistOptionData, BoardCardData, BoardColumnData, JobRowData, BOARD_COLUMNS, PageInfo, PlaylistCardData, PlaylistTrackData, ScanProposalData, TopAlbumData,
+        TopArtistData, TrackMismatchData,
     },
 };
 
-use super::albums::ListAlbumsQuery;
+use super::albums::{albums_with_issues, search_filter, singles_filter, sort_albums, ListAlbumsQuery, SearchField};
 use super::artists::ListArtistsQuery;
 use super::playlists::ListPlaylistsQuery;
 
 /// Home page with album grid
-pub async fn index() -> Html<String> {
-    Html(home_page().into_string())
+pub async fn index(State(state): State<AppState>, ActiveProfile(active_profile): ActiveProfile) -> Html<String> {
+    let view = match db_settings::for_profile(&state.db, &active_profile).await {
+        Ok(Some(settings)) => AlbumView::from_str(&settings.album_view).unwrap_or_default(),
+        _ => AlbumView::default(),
+    };
+
+    Html(home_page(view).into_string())
+}
+
+/// Resolve the album grid view, persisting an explicitly requested view to user settings
+async fn resolve_album_view(
+    state: &AppState,
+    active_profile: &profiles::Model,
+    requested: Option<&str>,
+) -> Result<AlbumView> {
+    use sea_orm::Set;
+
+    let existing = db_settings::for_profile(&state.db, active_profile).await?;
+
+    let Some(view) = requested.and_then(AlbumView::from_str) else {
+        return Ok(existing
+            .and_then(|s| AlbumView::from_str(&s.album_view))
+            .unwrap_or_default());
+    };
+
+    if existing.map(|s| s.album_view) != Some(view.as_str().to_string()) {
+        let changes = user_settings::ActiveModel {
+            album_view: Set(view.as_str().to_string()),
+            ..Default::default()
+        };
+        db_settings::save_for_profile(&state.db, active_profile, changes).await?;
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+
+    Ok(view)
 }
 
 /// Album grid partial (for HTMX updates)
 pub async fn albums_grid(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Query(query): Query<ListAlbumsQuery>,
 ) -> Result<Html<String>> {
     let page = query.page.max(1);
     let page_size = query.page_size.min(200).max(1);
 
-    let mut select = albums::Entity::find();
+    // The default first page is by far the most requested
+    let cacheable = album_grid_cache::is_cacheable(&query);
+    if cacheable {
+        if let Some(html) = album_grid_cache::get(&state.redis, active_profile.id, page_size).await {
+            return Ok(Html(html));
+        }
+    }
+
+    let view = resolve_album_view(&state, &active_profile, query.view.as_deref()).await?;
+
+    let mut select = albums::Entity::find()
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(albums::Column::ArchivedAt.is_null());
 
     // Apply filters
     if let Some(status) = &query.ownership_status {
@@ -54,52 +124,23 @@ pub async fn albums_grid(
     }
 
     if let Some(search) = &query.search {
-        select = select.filter(
-            albums::Column::Title
-                .contains(search)
-                .or(albums::Column::Title.like(&format!("%{}%", search))),
-        );
+        let fields = SearchField::parse_list(query.search_in.as_deref())?;
+        select = select.filter(search_filter(search, &fields));
+    }
+
+    if let Some(condition) = singles_filter(&state, &active_profile, query.exclude_singles).await? {
+        select = select.filter(condition);
+    }
+
+    if query.has_issues == Some(true) {
+        select = select.filter(albums_with_issues());
     }
 
     // Get total count
     let total_items = select.clone().count(&state.db).await?;
-    let total_pages = (total_items + page_size - 1) / page_size;
+    let page_info = PageInfo { page, page_size, total_items };
 
-    // Apply sorting
-    let select = match query.sort_by.as_str() {
-        "title" => {
-            if query.sort_order == "asc" {
-                select.order_by_asc(albums::Column::Title)
-            } else {
-                select.order_by_desc(albums::Column::Title)
-            }
-        }
-        "artist" => {
-            // For artist sorting, we need to join and order by artist name
-            use sea_orm::{JoinType, RelationTrait};
-            let select = select.join(JoinType::LeftJoin, albums::Relation::Artists.def());
-            if query.sort_order == "asc" {
-                select.order_by_asc(artists::Column::Name)
-            } else {
-                select.order_by_desc(artists::Column::Name)
-            }
-        }
-        "release_date" => {
-            if query.sort_order == "asc" {
-                select.order_by_asc(albums::Column::ReleaseDate)
-            } else {
-                select.order_by_desc(albums::Column::ReleaseDate)
-            }
-        }
-        _ => {
-            // Default: created_at (date added)
-            if query.sort_order == "asc" {
-                select.order_by_asc(albums::Column::CreatedAt)
-            } else {
-                select.order_by_desc(albums::Column::CreatedAt)
-            }
-        }
-    };
+    let select = sort_albums(select, &query.sort_by, &query.sort_order);
 
     // Get paginated results
     let albums = select
@@ -112,43 +153,80 @@ pub async fn albums_grid(
     let album_data: Vec<AlbumCardData> = albums
         .into_iter()
         .filter_map(|(album, artist)| {
-            artist.map(|a| AlbumCardData {
-                id: album.id,
-                title: album.title,
-                artist_id: a.id,
-                artist_name: a.name,
-                cover_art_url: album.cover_art_url,
-                release_date: album.release_date.map(|d| d.to_string()),
-                ownership_status: OwnershipStatus::from_str(&album.ownership_status).unwrap_or(OwnershipStatus::NotOwned),
-                match_score: album.match_score,
-            })
+            artist.map(|a| AlbumCardData::from_models(album, &a))
         })
         .collect();
 
-    let markup = album_grid_partial(album_data, page, total_pages);
-    Ok(Html(markup.into_string()))
+    let html = album_grid_partial(album_data, page_info, view).into_string();
+    if cacheable {
+        album_grid_cache::put(&state.redis, active_profile.id, page_size, &html).await;
+    }
+    Ok(Html(html))
 }
 
 /// Album detail modal (for HTMX)
 pub async fn album_detail(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
 ) -> Result<Html<String>> {
-    let album_with_artist = albums::Entity::find_by_id(id)
-        .find_also_related(artists::Entity)
-        .one(&state.db)
-        .await?;
+    let album_with_artist = match slug::find_album(&state.db, &key).await? {
+        Some(album) => {
+            let artist = album.find_related(artists::Entity).one(&state.db).await?;
+            Some((album, artist))
+        }
+        None => None,
+    };
 
     if let Some((album, Some(artist))) = album_with_artist {
-        let album_data = AlbumCardData {
-            id: album.id,
-            title: album.title.clone(),
-            artist_id: artist.id,
-            artist_name: artist.name.clone(),
-            cover_art_url: album.cover_art_url.clone(),
-            release_date: album.release_date.map(|d| d.to_string()),
-            ownership_status: OwnershipStatus::from_str(&album.ownership_status).unwrap_or(OwnershipStatus::NotOwned),
-            match_score: album.match_score,
+        let album_data = AlbumCardData::from_models(album.clone(), &artist);
+
+        let tz = display_timezone(&state, &active_profile).await?;
+        let downloads: Vec<AlbumDownloadData> = lidarr_downloads::Entity::find()
+            .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
+            .order_by_desc(lidarr_downloads::Column::CreatedAt)
+            .all(&state.db)
+            .await?
+            .into_iter()
+            .map(|d| AlbumDownloadData {
+                status: d.status,
+                download_id: d.download_id,
+                created_at: timestamp(&d.created_at, tz),
+                error_message: d.error_message,
+                retry_count: d.retry_count,
+            })
+            .collect();
+
+        let listens = match (&album.first_listened_at, &album.last_listened_at) {
+            (Some(first), Some(last)) => Some(AlbumListensData {
+                count: album.listen_count,
+                first_listened: timestamp(first, tz),
+                last_listened: timestamp(last, tz),
+            }),
+            _ => None,
+        };
+
+        let mismatches = tracks::Entity::find()
+            .filter(tracks::Column::AlbumId.eq(album.id))
+            .filter(tracks::Column::DurationMismatch.eq(true))
+            .order_by_asc(tracks::Column::DiscNumber)
+            .order_by_asc(tracks::Column::TrackNumber)
+            .all(&state.db)
+            .await?
+            .into_iter()
+            .filter_map(|t| {
+                Some(TrackMismatchData {
+                    disc_number: t.disc_number.unwrap_or(1),
+                    track_number: t.track_number?,
+                    title: t.title,
+                    spotify_duration_ms: t.duration_ms?,
+                    local_duration_ms: t.local_duration_ms?,
+                })
+            })
+            .collect();
+        let track_data = AlbumTracksData {
+            total: album.total_tracks,
+            mismatches,
         };
 
         let genres: Option<Vec<String>> = album.genres.and_then(|g| serde_json::from_str(&g).ok());
@@ -156,7 +234,10 @@ pub async fn album_detail(
             &album_data,
             &artist.name,
             &genres,
-            album.total_tracks,
+            &track_data,
+            &downloads,
+            listens.as_ref(),
+            album.lidarr_artist_mbid_override.as_deref(),
         );
         Ok(Html(markup.into_string()))
     } else {
@@ -164,19 +245,248 @@ pub async fn album_detail(
     }
 }
 
+#[derive(Deserialize)]
+pub struct ArtistOptionsQuery {
+    #[serde(default)]
+    pub search: String,
+}
+
+/// Artists an album could be moved to, as `<option>`s for the modal (for HTMX)
+///
+/// Backed by the `/api/artists` search; the album's current artist is left out.
+pub async fn album_artist_options(
+    State(state): State<AppState>,
+    active_profile: ActiveProfile,
+    Path(id): Path<i32>,
+    Query(query): Query<ArtistOptionsQuery>,
+) -> Result<Html<String>> {
+    let album = albums::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+
+    let search = query.search.trim();
+    let options: Vec<ArtistOptionData> = if search.is_empty() {
+        Vec::new()
+    } else {
+        let list_query = ListArtistsQuery {
+            search: Some(search.to_string()),
+            sort_by: "name".to_string(),
+            sort_order: "asc".to_string(),
+            page: 1,
+            page_size: 20,
+        };
+        super::artists::list_artists(State(state.clone()), active_profile, Query(list_query))
+            .await?
+            .0
+            .artists
+            .into_iter()
+            .filter(|a| a.id != album.artist_id)
+            .map(|a| ArtistOptionData { id: a.id, name: a.name, album_count: a.album_count })
+            .collect()
+    };
+
+    Ok(Html(artist_options(&options, search).into_string()))
+}
+
+#[derive(Deserialize)]
+pub struct AlbumArtistForm {
+    #[serde(default)]
+    pub search: String,
+    /// An artist ID, or `new` to create an artist named after the search
+    pub artist: String,
+}
+
+/// Move an album to another artist and re-render its modal (for HTMX)
+pub async fn album_change_artist(
+    State(state): State<AppState>,
+    active_profile: ActiveProfile,
+    Path(id): Path<i32>,
+    Form(form): Form<AlbumArtistForm>,
+) -> Result<Html<String>> {
+    let choice = match form.artist.as_str() {
+        "new" => ArtistChoice::New(&form.search),
+        other => ArtistChoice::Existing(
+            other
+                .parse()
+                .map_err(|_| AppError::Validation(format!("Invalid artist: {}", other)))?,
+        ),
+    };
+
+    let album = albums::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+
+    album_artist::reassign_album(&state.db, album, choice).await?;
+    album_grid_cache::invalidate(&state.redis).await;
+
+    album_detail(State(state), active_profile, Path(id.to_string())).await
+}
+
+#[derive(Deserialize)]
+pub struct LidarrArtistForm {
+    /// MusicBrainz artist ID; blank clears the override
+    #[serde(default)]
+    pub mbid: String,
+}
+
+/// Set or clear the artist an album uses in Lidarr and re-render its modal (for HTMX)
+pub async fn album_set_lidarr_artist(
+    State(state): State<AppState>,
+    active_profile: ActiveProfile,
+    Path(id): Path<i32>,
+    Form(form): Form<LidarrArtistForm>,
+) -> Result<Html<String>> {
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let mbid = lidarr::parse_artist_override(&form.mbid)?;
+    let album = albums::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+
+    let mut active: albums::ActiveModel = album.into();
+    active.lidarr_artist_mbid_override = Set(mbid);
+    active.updated_at = Set(chrono::Utc::now().into());
+    active.update(&state.db).await?;
+
+    album_detail(State(state), active_profile, Path(id.to_string())).await
+}
+
+#[derive(Deserialize)]
+pub struct GenresForm {
+    /// Comma-separated genres; blank clears them
+    #[serde(default)]
+    pub genres: String,
+}
+
+/// "You might also want" strip of an album's modal (for HTMX)
+///
+/// Loaded after the modal, since it may call Spotify; failures leave it out.
+pub async fn album_suggestions(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
+) -> Html<String> {
+    let suggestions = match super::albums::album_suggestions(&state, active_profile, &key).await {
+        Ok(suggestions) => suggestions,
+        Err(e) => {
+            tracing::warn!("Failed to load suggestions for album {}: {}", key, e);
+            Vec::new()
+        }
+    };
+    Html(album_suggestions_strip(&suggestions).into_string())
+}
+
+/// Replace an album's genres by hand and re-render its modal (for HTMX)
+pub async fn album_set_genres(
+    State(state): State<AppState>,
+    active_profile: ActiveProfile,
+    Path(id): Path<i32>,
+    Form(form): Form<GenresForm>,
+) -> Result<Html<String>> {
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let genres = genres::parse_edit(&genres::GenreList::Text(form.genres))?;
+    let album = albums::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+
+    let mut active: albums::ActiveModel = album.into();
+    active.genres = Set(genres::to_column(&genres));
+    active.genre_source = Set(Some(GenreSource::Manual.into()));
+    active.updated_at = Set(chrono::Utc::now().into());
+    active.update(&state.db).await?;
+
+    album_detail(State(state), active_profile, Path(id.to_string())).await
+}
+
+/// Resolve a Lidarr artist override to the artist's name before saving it (for HTMX)
+pub async fn album_lidarr_artist_preview(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(form): Query<LidarrArtistForm>,
+) -> Result<Html<String>> {
+    let preview = match lidarr::parse_artist_override(&form.mbid) {
+        Ok(None) => return Ok(Html(String::new())),
+        Ok(Some(mbid)) => resolve_lidarr_artist(&state, &active_profile, &mbid).await,
+        Err(e) => LidarrArtistPreview::Error(e.to_string()),
+    };
+    Ok(Html(lidarr_artist_preview(&preview).into_string()))
+}
+
+async fn resolve_lidarr_artist(state: &AppState, active_profile: &profiles::Model, mbid: &str) -> LidarrArtistPreview {
+    let lookup = async {
+        let (lidarr_url, lidarr_api_key) = super::settings::lidarr_connection(state, Some(active_profile.id)).await?;
+        super::settings::lidarr_service(state, Some(active_profile.id))
+            .await?
+            .lookup_artist(&lidarr_url, &lidarr_api_key, mbid)
+            .await
+    };
+    match lookup.await {
+        Ok(Some(artist)) => LidarrArtistPreview::Found(artist.artist_name),
+        Ok(None) => LidarrArtistPreview::NotFound,
+        Err(e) => LidarrArtistPreview::Error(e.to_string()),
+    }
+}
+
+/// Time zone for rendering timestamps, from the profile's settings
+async fn display_timezone(state: &AppState, active_profile: &profiles::Model) -> Result<Tz> {
+    let settings = db_settings::for_profile(&state.db, active_profile).await?;
+    Ok(resolve_timezone(settings.as_ref().and_then(|s| s.timezone.as_deref())))
+}
+
 /// Settings page
-pub async fn settings(State(state): State<AppState>) -> Html<String> {
-    let settings_result = user_settings::Entity::find().one(&state.db).await;
+pub async fn settings(State(state): State<AppState>, ActiveProfile(active_profile): ActiveProfile) -> Html<String> {
+    let settings_result = db_settings::for_profile(&state.db, &active_profile).await;
 
-    let (lidarr_url, music_folder) = match settings_result {
+    let (lidarr_url, music_folder, path_mismatches, locale) = match settings_result {
         Ok(Some(settings)) => (
-            settings.lidarr_url,
+            settings.lidarr_url.as_deref().map(lidarr::mask_url_password),
             settings.music_folder_path,
+            music_paths::parse_mismatches(settings.path_mismatch_prefixes.as_deref()),
+            Locale::resolve(settings.locale.as_deref()),
         ),
-        _ => (None, None),
+        _ => (None, None, Vec::new(), Locale::default()),
     };
 
-    Html(settings_page(lidarr_url, music_folder).into_string())
+    Html(settings_page(lidarr_url, music_folder, &path_mismatches, locale).into_string())
+}
+
+#[derive(Deserialize)]
+pub struct LocaleForm {
+    pub locale: String,
+}
+
+/// Switch the UI language; HTMX reloads the page so it renders in the new one
+pub async fn settings_locale(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Form(form): Form<LocaleForm>,
+) -> Result<impl IntoResponse> {
+    use sea_orm::Set;
+
+    let locale = Locale::parse(&form.locale)
+        .ok_or_else(|| AppError::Validation(format!("Unknown locale: {}", form.locale)))?;
+
+    let changes = user_settings::ActiveModel {
+        locale: Set(Some(locale.into())),
+        ..Default::default()
+    };
+    db_settings::save_for_profile(&state.db, &active_profile, changes).await?;
+
+    // Cached album grids hold dates formatted for the old locale
+    album_grid_cache::invalidate(&state.redis).await;
+
+    let message = i18n::with_locale(locale, || {
+        t!("settings.language_saved", language = locale.native_name())
+    });
+    Ok((
+        [("HX-Refresh", "true")],
+        Html(notification(&message, "success").into_string()),
+    ))
 }
 
 /// Jobs page
@@ -184,11 +494,423 @@ pub async fn jobs() -> Html<String> {
     Html(jobs_page().into_string())
 }
 
+/// Table row for a job, with a Spotify sync's progress summarized
+fn job_row(job: jobs::Model, tz: Tz) -> JobRowData {
+    let running = job.status == JobStatus::Running.as_str();
+    let detail = job
+        .progress_detail
+        .as_deref()
+        .and_then(|detail| serde_json::from_str::<SyncProgress>(detail).ok())
+        .map(|progress| progress.summary(running));
+    JobRowData {
+        id: job.id,
+        job_type: job.job_type,
+        status: job.status,
+        progress: job.progress,
+        error_message: job.error_message,
+        detail,
+        created: timestamp(&job.created_at, tz),
+        completed: job.completed_at.map(|t| timestamp(&t, tz)),
+    }
+}
+
+/// Recent jobs table for the jobs page (polled by HTMX)
+pub async fn jobs_list(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Html<String>> {
+    let tz = display_timezone(&state, &active_profile).await?;
+    let rows: Vec<JobRowData> = jobs::Entity::find()
+        .order_by_desc(jobs::Column::CreatedAt)
+        .limit(50)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|job| job_row(job, tz))
+        .collect();
+
+    Ok(Html(jobs_list_partial(&rows).into_string()))
+}
+
+/// Job detail page; a finished scan preview lists its matches for review
+pub async fn job_detail(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+) -> Result<Html<String>> {
+    let tz = display_timezone(&state, &active_profile).await?;
+    let job = jobs::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Job {} not found", id)))?;
+
+    let proposals: Option<Vec<ScanProposalData>> = (job.job_type == JobType::FilesystemScanPreview.as_str())
+        .then(|| job.result.as_deref().and_then(|r| serde_json::from_str::<ScanPreview>(r).ok()))
+        .flatten()
+        .map(|preview| {
+            preview
+                .proposals
+                .into_iter()
+                .map(|p| ScanProposalData {
+                    album_id: p.album_id,
+                    directory: p.directory,
+                    artist_name: p.artist_name,
+                    album_title: p.album_title,
+                    confidence: (p.confidence * 100.0).round() as u32,
+                    accepted: p.confidence >= PREVIEW_CONFIDENCE_THRESHOLD,
+                })
+                .collect()
+        });
+
+    let threshold = (PREVIEW_CONFIDENCE_THRESHOLD * 100.0).round() as u32;
+    Ok(Html(
+        job_detail_page(&job_row(job, tz), proposals.as_deref(), threshold).into_string(),
+    ))
+}
+
+/// Apply the matches checked in a scan preview review (`accept_<album id>` fields)
+pub async fn job_apply_scan(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Html<String>> {
+    let album_ids: Vec<i32> = form
+        .keys()
+        .filter_map(|key| key.strip_prefix("accept_")?.parse().ok())
+        .collect();
+    if album_ids.is_empty() {
+        return Ok(Html(notification("No matches were accepted", "info").into_string()));
+    }
+
+    let report = super::jobs::apply_scan_matches(&state, id, &album_ids).await?;
+    let mut message = format!("Marked {} albums owned", report.applied);
+    if report.skipped > 0 {
+        message.push_str(&format!(", {} skipped because their status no longer allows it", report.skipped));
+    }
+    Ok(Html(notification(&message, "success").into_string()))
+}
+
 /// Stats page
 pub async fn stats() -> Html<String> {
     Html(stats_page().into_string())
 }
 
+/// Albums shown per column of the want-list board
+const BOARD_COLUMN_LIMIT: u64 = 50;
+
+/// Album counts per ownership state in the active profile, leaving out its
+/// cleared wants
+async fn board_counts(state: &AppState, profile_id: i32) -> Result<Vec<(OwnershipStatus, u64)>> {
+    use sea_orm::FromQueryResult;
+
+    #[derive(FromQueryResult)]
+    struct StatusCount {
+        ownership_status: String,
+        albums: i64,
+    }
+
+    let counts = albums::Entity::find()
+        .filter(profile::albums_in_profile(profile_id))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .filter(wanted::not_cleared(profile_id))
+        .select_only()
+        .column(albums::Column::OwnershipStatus)
+        .column_as(albums::Column::Id.count(), "albums")
+        .group_by(albums::Column::OwnershipStatus)
+        .into_model::<StatusCount>()
+        .all(&state.db)
+        .await?;
+
+    Ok(BOARD_COLUMNS
+        .iter()
+        .map(|status| {
+            let count = counts
+                .iter()
+                .find(|c| c.ownership_status == status.as_str())
+                .map_or(0, |c| c.albums as u64);
+            (*status, count)
+        })
+        .collect())
+}
+
+fn board_card_data(album: albums::Model, artist: Option<artists::Model>) -> BoardCardData {
+    let ownership_status = ownership::current_status(&album);
+    BoardCardData {
+        id: album.id,
+        slug: slug::link_key(album.slug.as_deref(), album.id),
+        wanted_age: album
+            .wanted_since
+            .filter(|_| ownership_status == OwnershipStatus::NotOwned)
+            .map(|since| WantedAge::since(since, chrono::Utc::now())),
+        ownership_status,
+        title: album.title,
+        artist_name: artist.map(|a| a.name).unwrap_or_default(),
+        cover_art_url: album.cover_art_url,
+    }
+}
+
+/// Want-list board: Wanted → Ordered → Downloading → Owned
+///
+/// Each column lists the most recently updated albums in that state.
+pub async fn board(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Html<String>> {
+    let mut columns = Vec::new();
+
+    for (status, count) in board_counts(&state, active_profile.id).await? {
+        let cards = albums::Entity::find()
+            .filter(profile::albums_in_profile(active_profile.id))
+            .filter(albums::Column::ArchivedAt.is_null())
+            .filter(wanted::not_cleared(active_profile.id))
+            .filter(albums::Column::OwnershipStatus.eq(status.as_str()))
+            .order_by_desc(albums::Column::UpdatedAt)
+            .order_by_desc(albums::Column::Id)
+            .limit(BOARD_COLUMN_LIMIT)
+            .find_also_related(artists::Entity)
+            .all(&state.db)
+            .await?
+            .into_iter()
+            .map(|(album, artist)| board_card_data(album, artist))
+            .collect();
+
+        columns.push(BoardColumnData { status, count, cards });
+    }
+
+    Ok(Html(board_page(&columns).into_string()))
+}
+
+#[derive(Deserialize)]
+pub struct BoardMoveForm {
+    pub to: String,
+}
+
+/// Move an album to another board column (for HTMX)
+///
+/// The moved card replaces itself with nothing and is inserted out of band at
+/// the top of its new column, along with fresh counts for every column.
+pub async fn board_move(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+    Form(form): Form<BoardMoveForm>,
+) -> Result<Html<String>> {
+    use sea_orm::{ActiveModelTrait, Set, TransactionTrait};
+
+    let to = OwnershipStatus::from_str(&form.to)
+        .ok_or_else(|| AppError::Validation(format!("Invalid ownership status: {}", form.to)))?;
+
+    let album = albums::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+
+    let from = ownership::current_status(&album);
+    ownership::check_transition(from, to, ownership::REASON_MANUAL, false)?;
+
+    let txn = state.db.begin().await?;
+    let mut active: albums::ActiveModel = album.into();
+    ownership::set_status(&mut active, from, to);
+    active.updated_at = Set(chrono::Utc::now().into());
+    let album = active.update(&txn).await?;
+    ownership::record_transition(&txn, album.id, from, to, ownership::REASON_MANUAL, false).await?;
+    txn.commit().await?;
+    ownership::publish_transition(&state.events, album.id, from, to, ownership::REASON_MANUAL, false);
+    album_grid_cache::invalidate(&state.redis).await;
+
+    let artist = album.find_related(artists::Entity).one(&state.db).await?;
+    let mut html = board_card_oob(&board_card_data(album, artist)).into_string();
+    for (status, count) in board_counts(&state, active_profile.id).await? {
+        html.push_str(&board_column_count(status, count, true).into_string());
+    }
+
+    Ok(Html(html))
+}
+
+/// Archive of albums, playlists and Liked Songs tracks removed from Spotify
+pub async fn archive(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Html<String>> {
+    let tz = display_timezone(&state, &active_profile).await?;
+    let archive = archive_service::list_archive(&state.db, active_profile.id, None).await?;
+
+    let albums: Vec<ArchivedRowData> = archive
+        .albums
+        .into_iter()
+        .map(|a| ArchivedRowData {
+            id: a.id,
+            title: a.title,
+            detail: Some(a.artist_name),
+            archived_at: a.archived_at,
+        })
+        .collect();
+    let playlists: Vec<ArchivedRowData> = archive
+        .playlists
+        .into_iter()
+        .map(|p| ArchivedRowData {
+            id: p.id,
+            title: p.name,
+            detail: p.owner_name,
+            archived_at: p.archived_at,
+        })
+        .collect();
+    let tracks: Vec<ArchivedRowData> = archive
+        .tracks
+        .into_iter()
+        .map(|t| ArchivedRowData {
+            id: t.id,
+            title: t.title,
+            detail: Some(format!("{} · {}", t.artist_name, t.album_title)),
+            archived_at: t.archived_at,
+        })
+        .collect();
+
+    Ok(Html(archive_page(&albums, &playlists, &tracks, tz).into_string()))
+}
+
+/// Items shown per column of the heavy rotation panel
+const HEAVY_ROTATION_LIMIT: usize = 10;
+
+/// "Heavy rotation" panel: Spotify top artists and albums with ownership status (for HTMX)
+///
+/// Uses the cached top items, fetching them first when the active profile has
+/// never fetched them or the last fetch is stale.
+pub async fn heavy_rotation(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Html<String>> {
+    if active_profile.spotify_access_token.is_none() {
+        return Ok(Html(
+            heavy_rotation_message("Connect Spotify to see what you listen to most.").into_string(),
+        ));
+    }
+
+    let profile_id = active_profile.id;
+    let mut items = cached_top_items(&state.db, profile_id).await?;
+
+    if is_stale(&active_profile) {
+        match refresh_top_items(&state, active_profile).await {
+            Ok(_) => items = cached_top_items(&state.db, profile_id).await?,
+            Err(AppError::InsufficientScope(_)) => {
+                return Ok(Html(heavy_rotation_reauth().into_string()));
+            }
+            Err(e) if items.is_empty() => {
+                tracing::warn!("Failed to load top items: {}", e);
+                return Ok(Html(
+                    heavy_rotation_message("Couldn't load your top artists from Spotify.").into_string(),
+                ));
+            }
+            // Stale items are still better than nothing
+            Err(e) => tracing::warn!("Failed to refresh top items, showing cached: {}", e),
+        }
+    }
+
+    let (top_artists, top_tracks): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .partition(|item| item.kind == TopItemKind::Artist.as_str());
+
+    let top_artists = annotate_top_artists(&state, profile_id, top_artists).await?;
+    let top_albums = annotate_top_albums(&state, profile_id, top_tracks).await?;
+
+    Ok(Html(heavy_rotation_panel(&top_artists, &top_albums).into_string()))
+}
+
+/// Ownership heatmap of the artists with the most albums, for the stats page
+pub async fn artist_heatmap(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Html<String>> {
+    let artists = artist_heatmap::artist_heatmap(&state.db, active_profile.id, DEFAULT_HEATMAP_ARTISTS).await?;
+    Ok(Html(artist_heatmap_panel(&artists).into_string()))
+}
+
+/// Top artists with how many of their albums in the profile's library are owned
+async fn annotate_top_artists(
+    state: &AppState,
+    profile_id: i32,
+    top_artists: Vec<top_items::Model>,
+) -> Result<Vec<TopArtistData>> {
+    let top_artists: Vec<top_items::Model> = top_artists.into_iter().take(HEAVY_ROTATION_LIMIT).collect();
+    let spotify_ids: Vec<String> = top_artists.iter().map(|a| a.spotify_id.clone()).collect();
+
+    let known_artists = artists::Entity::find()
+        .filter(artists::Column::SpotifyId.is_in(spotify_ids))
+        .all(&state.db)
+        .await?;
+    let library_albums = albums::Entity::find()
+        .filter(albums::Column::ArtistId.is_in(known_artists.iter().map(|a| a.id)))
+        .filter(profile::albums_in_profile(profile_id))
+        .all(&state.db)
+        .await?;
+
+    Ok(top_artists
+        .into_iter()
+        .map(|item| {
+            let artist = known_artists
+                .iter()
+                .find(|a| a.spotify_id.as_deref() == Some(item.spotify_id.as_str()));
+            let artist_albums: Vec<&albums::Model> = artist
+                .map(|a| library_albums.iter().filter(|al| al.artist_id == a.id).collect())
+                .unwrap_or_default();
+
+            TopArtistData {
+                name: item.name,
+                slug: artist
+                    .filter(|_| !artist_albums.is_empty())
+                    .map(|a| slug::link_key(a.slug.as_deref(), a.id)),
+                image_url: item.image_url,
+                album_count: artist_albums.len() as i64,
+                owned_count: artist_albums
+                    .iter()
+                    .filter(|al| al.ownership_status == OwnershipStatus::Owned.as_str())
+                    .count() as i64,
+            }
+        })
+        .collect())
+}
+
+/// Albums of the top tracks (first appearance wins) with their library status
+async fn annotate_top_albums(
+    state: &AppState,
+    profile_id: i32,
+    top_tracks: Vec<top_items::Model>,
+) -> Result<Vec<TopAlbumData>> {
+    let mut seen = std::collections::HashSet::new();
+    let top_tracks: Vec<top_items::Model> = top_tracks
+        .into_iter()
+        .filter(|t| t.album_spotify_id.as_ref().is_some_and(|id| seen.insert(id.clone())))
+        .take(HEAVY_ROTATION_LIMIT)
+        .collect();
+    let spotify_ids: Vec<String> = top_tracks
+        .iter()
+        .filter_map(|t| t.album_spotify_id.clone())
+        .collect();
+
+    let library_albums = albums::Entity::find()
+        .filter(albums::Column::SpotifyId.is_in(spotify_ids))
+        .filter(profile::albums_in_profile(profile_id))
+        .all(&state.db)
+        .await?;
+
+    Ok(top_tracks
+        .into_iter()
+        .map(|track| {
+            let album = library_albums
+                .iter()
+                .find(|a| a.spotify_id.is_some() && a.spotify_id == track.album_spotify_id);
+
+            TopAlbumData {
+                title: track.album_name.unwrap_or(track.name),
+                artist_name: track.artist_name.unwrap_or_default(),
+                image_url: track.image_url,
+                slug: album.map(|a| slug::link_key(a.slug.as_deref(), a.id)),
+                ownership_status: album.and_then(|a| OwnershipStatus::from_str(&a.ownership_status)),
+            }
+        })
+        .collect())
+}
+
 /// Artists page
 pub async fn artists() -> Html<String> {
     Html(artists_page().into_string())
@@ -197,6 +919,7 @@ pub async fn artists() -> Html<String> {
 /// Artists grid partial (for HTMX)
 pub async fn artists_grid(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Query(query): Query<ListArtistsQuery>,
 ) -> Result<Html<String>> {
     use sea_orm::{FromQueryResult, JoinType, RelationTrait};
@@ -204,8 +927,8 @@ pub async fn artists_grid(
     let page = query.page.max(1);
     let page_size = query.page_size.min(200).max(1);
 
-    // Build base query for filtering
-    let mut base_filter = artists::Entity::find();
+    // Build base query for filtering (artists with albums in the active profile)
+    let mut base_filter = artists::Entity::find().filter(profile::artists_in_profile(active_profile.id));
 
     if let Some(search) = &query.search {
         if !search.is_empty() {
@@ -219,7 +942,7 @@ pub async fn artists_grid(
 
     // Get total count
     let total_items = base_filter.clone().count(&state.db).await?;
-    let total_pages = (total_items + page_size - 1) / page_size;
+    let page_info = PageInfo { page, page_size, total_items };
 
     // Get paginated artist IDs
     let artist_ids: Vec<i32> = base_filter
@@ -233,7 +956,7 @@ pub async fn artists_grid(
         .await?;
 
     if artist_ids.is_empty() {
-        let markup = artist_grid_partial(vec![], page, total_pages);
+        let markup = artist_grid_partial(vec![], page_info);
         return Ok(Html(markup.into_string()));
     }
 
@@ -242,6 +965,8 @@ pub async fn artists_grid(
     struct ArtistWithStats {
         id: i32,
         name: String,
+        slug: Option<String>,
+        image_url: Option<String>,
         album_count: i64,
         owned_count: i64,
     }
@@ -252,14 +977,19 @@ pub async fn artists_grid(
         .select_only()
         .column(artists::Column::Id)
         .column(artists::Column::Name)
+        .column(artists::Column::Slug)
+        .column(artists::Column::ImageUrl)
         .column_as(albums::Column::Id.count(), "album_count")
         .column_as(
             sea_orm::prelude::Expr::cust("SUM(CASE WHEN albums.ownership_status = 'owned' THEN 1 ELSE 0 END)"),
             "owned_count",
         )
         .join(JoinType::LeftJoin, artists::Relation::Albums.def())
+        .filter(profile::albums_in_profile(active_profile.id))
         .group_by(artists::Column::Id)
         .group_by(artists::Column::Name)
+        .group_by(artists::Column::Slug)
+        .group_by(artists::Column::ImageUrl)
         .into_model::<ArtistWithStats>()
         .all(&state.db)
         .await?;
@@ -267,19 +997,14 @@ pub async fn artists_grid(
     // Convert to card data and apply sorting
     let mut artist_data: Vec<ArtistCardData> = artists_with_stats
         .into_iter()
-        .map(|a| {
-            let ownership_percentage = if a.album_count > 0 {
-                (a.owned_count as f64 / a.album_count as f64) * 100.0
-            } else {
-                0.0
-            };
-            ArtistCardData {
-                id: a.id,
-                name: a.name,
-                album_count: a.album_count,
-                owned_count: a.owned_count,
-                ownership_percentage,
-            }
+        .map(|a| ArtistCardData {
+            id: a.id,
+            slug: slug::link_key(a.slug.as_deref(), a.id),
+            name: a.name,
+            image_url: a.image_url,
+            album_count: a.album_count,
+            owned_count: a.owned_count,
+            ownership_percentage: ownership_percentage(a.owned_count, a.album_count),
         })
         .collect();
 
@@ -317,24 +1042,26 @@ pub async fn artists_grid(
         }
     }
 
-    let markup = artist_grid_partial(artist_data, page, total_pages);
+    let markup = artist_grid_partial(artist_data, page_info);
     Ok(Html(markup.into_string()))
 }
 
 /// Artist detail page (full page)
 pub async fn artist_detail(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(key): Path<String>,
 ) -> Result<Html<String>> {
-    // Get the artist
-    let artist = artists::Entity::find_by_id(id)
-        .one(&state.db)
-        .await?;
+    // Get the artist by ID or slug
+    let artist = slug::find_artist(&state.db, &key).await?;
 
     if let Some(artist) = artist {
-        // Get all albums for this artist
+        let artist_slug = slug::link_key(artist.slug.as_deref(), artist.id);
+
+        // Get all albums for this artist in the active profile's library
         let artist_albums = albums::Entity::find()
-            .filter(albums::Column::ArtistId.eq(id))
+            .filter(albums::Column::ArtistId.eq(artist.id))
+            .filter(profile::albums_in_profile(active_profile.id))
             .order_by_desc(albums::Column::ReleaseDate)
             .all(&state.db)
             .await?;
@@ -344,33 +1071,20 @@ pub async fn artist_detail(
             .filter(|a| a.ownership_status == "owned")
             .count() as i64;
         let album_count = artist_albums.len() as i64;
-        let ownership_percentage = if album_count > 0 {
-            (owned_count as f64 / album_count as f64) * 100.0
-        } else {
-            0.0
-        };
 
         let artist_card_data = ArtistCardData {
             id: artist.id,
+            slug: artist_slug.clone(),
             name: artist.name.clone(),
+            image_url: artist.image_url.clone(),
             album_count,
             owned_count,
-            ownership_percentage,
+            ownership_percentage: ownership_percentage(owned_count, album_count),
         };
 
         let album_data: Vec<AlbumCardData> = artist_albums
             .into_iter()
-            .map(|album| AlbumCardData {
-                id: album.id,
-                title: album.title,
-                artist_id: artist.id,
-                artist_name: artist.name.clone(),
-                cover_art_url: album.cover_art_url,
-                release_date: album.release_date.map(|d| d.to_string()),
-                ownership_status: OwnershipStatus::from_str(&album.ownership_status)
-                    .unwrap_or(OwnershipStatus::NotOwned),
-                match_score: album.match_score,
-            })
+            .map(|album| AlbumCardData::from_models(album, &artist))
             .collect();
 
         let markup = artist_detail_page(&artist_card_data, album_data);
@@ -388,19 +1102,22 @@ pub async fn playlists() -> Html<String> {
 /// Playlists grid partial (for HTMX)
 pub async fn playlists_grid(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Query(query): Query<ListPlaylistsQuery>,
 ) -> Result<Html<String>> {
     let page = query.page.max(1);
     let page_size = query.page_size.min(200).max(1);
 
-    let mut select = playlists::Entity::find();
+    let mut select = playlists::Entity::find()
+        .filter(profile::playlists_in_profile(active_profile.id))
+        .filter(playlists::Column::ArchivedAt.is_null());
 
     if let Some(enabled) = query.is_enabled {
         select = select.filter(playlists::Column::IsEnabled.eq(enabled));
     }
 
     let total_items = select.clone().count(&state.db).await?;
-    let total_pages = (total_items + page_size - 1) / page_size;
+    let page_info = PageInfo { page, page_size, total_items };
 
     let playlist_models = select
         .order_by_desc(playlists::Column::IsEnabled)  // Enabled playlists first
@@ -410,43 +1127,21 @@ pub async fn playlists_grid(
         .all(&state.db)
         .await?;
 
-    // Batch fetch ownership stats for all playlists (single query!)
-    let playlist_ids: Vec<i32> = playlist_models.iter().map(|p| p.id).collect();
-    let stats_map = playlist_stats::get_batch_playlist_ownership_stats(&state.db, playlist_ids)
+    // Batch fetch track counts for all playlists (single query!)
+    let counts_map = playlist_stats::get_batch_playlist_counts(&state.db, &playlist_models)
         .await
         .unwrap_or_default();
 
+    let tz = display_timezone(&state, &active_profile).await?;
     let playlist_data: Vec<PlaylistCardData> = playlist_models
         .into_iter()
         .map(|playlist| {
-            // Use precomputed owned_count if available, otherwise use batch stats
-            let (owned_count, total_count) = if let Some(precomputed) = playlist.owned_count {
-                (precomputed as i64, playlist.total_tracks.unwrap_or(0) as i64)
-            } else {
-                stats_map.get(&playlist.id).copied().unwrap_or((0, 0))
-            };
-
-            let ownership_percentage = if total_count > 0 {
-                (owned_count as f64 / total_count as f64) * 100.0
-            } else {
-                0.0
-            };
-
-            PlaylistCardData {
-                id: playlist.id,
-                name: playlist.name,
-                owner_name: playlist.owner_name,
-                track_count: playlist.total_tracks.unwrap_or(0),
-                owned_count: owned_count as i32,
-                cover_image_url: playlist.cover_image_url,
-                is_enabled: playlist.is_enabled,
-                ownership_percentage,
-                is_synthetic: playlist.is_synthetic,
-            }
+            let counts = counts_map.get(&playlist.id).copied().unwrap_or_default();
+            PlaylistCardData::from_model(playlist, counts.owned, counts.synced, tz)
         })
         .collect();
 
-    let markup = playlist_grid_partial(playlist_data, page, total_pages);
+    let markup = playlist_grid_partial(playlist_data, page_info);
     Ok(Html(markup.into_string()))
 }
 
@@ -463,9 +1158,63 @@ fn default_page() -> u64 {
 
 const TRACKS_PER_PAGE: u64 = 50;
 
+fn playlist_track_data(t: playlist_stats::PlaylistTrackDetails) -> PlaylistTrackData {
+    PlaylistTrackData {
+        position: t.position,
+        track_name: t.track_name,
+        artist_slug: slug::link_key(t.artist_slug.as_deref(), t.artist_id),
+        artist_name: t.artist_name,
+        album_id: t.album_id,
+        album_slug: slug::link_key(t.album_slug.as_deref(), t.album_id),
+        album_name: t.album_name,
+        duration_ms: t.duration_ms,
+        ownership_status: OwnershipStatus::from_str(&t.ownership_status)
+            .unwrap_or(OwnershipStatus::NotOwned),
+    }
+}
+
+/// Render the playlist detail modal for `page`, returning the card data alongside it
+/// so callers can also refresh the playlist's grid card
+async fn render_playlist_detail(
+    state: &AppState,
+    active_profile: &profiles::Model,
+    playlist: playlists::Model,
+    page: u64,
+) -> Result<(PlaylistCardData, Markup)> {
+    let counts = playlist_stats::playlist_counts(&state.db, &playlist)
+        .await
+        .unwrap_or_default();
+
+    let tz = display_timezone(state, active_profile).await?;
+    let playlist_data = PlaylistCardData::from_model(playlist, counts.owned, counts.synced, tz);
+
+    // Calculate pagination over the synced tracks, the only ones listed
+    let page = page.max(1);
+    let offset = (page - 1) * TRACKS_PER_PAGE;
+    let total_pages = ((counts.synced as u64) + TRACKS_PER_PAGE - 1) / TRACKS_PER_PAGE;
+
+    let (track_details, _total) = playlist_stats::get_playlist_tracks_paginated(
+        &state.db,
+        playlist_data.id,
+        offset,
+        TRACKS_PER_PAGE,
+    )
+    .await
+    .unwrap_or_default();
+
+    let track_data: Vec<PlaylistTrackData> = track_details
+        .into_iter()
+        .map(playlist_track_data)
+        .collect();
+
+    let markup = playlist_detail_partial(&playlist_data, track_data, page, total_pages.max(1));
+    Ok((playlist_data, markup))
+}
+
 /// Playlist detail partial (for HTMX)
 pub async fn playlist_detail(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Path(id): Path<i32>,
     Query(query): Query<PlaylistDetailQuery>,
 ) -> Result<Html<String>> {
@@ -474,63 +1223,7 @@ pub async fn playlist_detail(
         .await?;
 
     if let Some(playlist) = playlist {
-        // Use precomputed owned_count if available
-        let total_count = playlist.total_tracks.unwrap_or(0) as i64;
-        let owned_count = if let Some(precomputed) = playlist.owned_count {
-            precomputed as i64
-        } else {
-            playlist_stats::recalculate_playlist_owned_count(&state.db, playlist.id)
-                .await
-                .unwrap_or(0) as i64
-        };
-
-        let ownership_percentage = if total_count > 0 {
-            (owned_count as f64 / total_count as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let playlist_data = PlaylistCardData {
-            id: playlist.id,
-            name: playlist.name.clone(),
-            owner_name: playlist.owner_name.clone(),
-            track_count: playlist.total_tracks.unwrap_or(0),
-            owned_count: owned_count as i32,
-            cover_image_url: playlist.cover_image_url.clone(),
-            is_enabled: playlist.is_enabled,
-            ownership_percentage,
-            is_synthetic: playlist.is_synthetic,
-        };
-
-        // Calculate pagination
-        let page = query.page.max(1);
-        let offset = (page - 1) * TRACKS_PER_PAGE;
-        let total_pages = ((total_count as u64) + TRACKS_PER_PAGE - 1) / TRACKS_PER_PAGE;
-
-        let (track_details, _total) = playlist_stats::get_playlist_tracks_paginated(
-            &state.db,
-            id,
-            offset,
-            TRACKS_PER_PAGE,
-        )
-        .await
-        .unwrap_or_default();
-
-        let track_data: Vec<PlaylistTrackData> = track_details
-            .into_iter()
-            .map(|t| PlaylistTrackData {
-                position: t.position,
-                track_name: t.track_name,
-                artist_name: t.artist_name,
-                album_id: t.album_id,
-                album_name: t.album_name,
-                duration_ms: t.duration_ms,
-                ownership_status: OwnershipStatus::from_str(&t.ownership_status)
-                    .unwrap_or(OwnershipStatus::NotOwned),
-            })
-            .collect();
-
-        let markup = playlist_detail_partial(&playlist_data, track_data, page, total_pages.max(1));
+        let (_, markup) = render_playlist_detail(&state, &active_profile, playlist, query.page).await?;
         Ok(Html(markup.into_string()))
     } else {
         Ok(Html("<div class='p-4 text-red-600'>Playlist not found</div>".to_string()))
@@ -540,6 +1233,7 @@ pub async fn playlist_detail(
 /// Toggle playlist enabled and return updated modal (for HTMX)
 pub async fn playlist_toggle(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Path(id): Path<i32>,
     Query(query): Query<PlaylistDetailQuery>,
 ) -> Result<Html<String>> {
@@ -555,68 +1249,21 @@ pub async fn playlist_toggle(
 
         let mut active: playlists::ActiveModel = playlist.into();
         active.is_enabled = Set(new_enabled);
+        if new_enabled {
+            // A playlist syncs disabled for failing gets a fresh start
+            active.sync_failure_count = Set(0);
+            active.disabled_reason = Set(None);
+        }
         active.updated_at = Set(chrono::Utc::now().into());
         let playlist = active.update(&state.db).await?;
 
-        // Now render the modal with updated data
-        let total_count = playlist.total_tracks.unwrap_or(0) as i64;
-        let owned_count = if let Some(precomputed) = playlist.owned_count {
-            precomputed as i64
-        } else {
-            playlist_stats::recalculate_playlist_owned_count(&state.db, playlist.id)
-                .await
-                .unwrap_or(0) as i64
-        };
-
-        let ownership_percentage = if total_count > 0 {
-            (owned_count as f64 / total_count as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let playlist_data = PlaylistCardData {
-            id: playlist.id,
-            name: playlist.name.clone(),
-            owner_name: playlist.owner_name.clone(),
-            track_count: playlist.total_tracks.unwrap_or(0),
-            owned_count: owned_count as i32,
-            cover_image_url: playlist.cover_image_url.clone(),
-            is_enabled: playlist.is_enabled,
-            ownership_percentage,
-            is_synthetic: playlist.is_synthetic,
-        };
-
-        let page = query.page.max(1);
-        let offset = (page - 1) * TRACKS_PER_PAGE;
-        let total_pages = ((total_count as u64) + TRACKS_PER_PAGE - 1) / TRACKS_PER_PAGE;
+        // Optionally drop the track memberships of a playlist that was just disabled
+        playlist_cleanup::purge_if_disabled(&state.db, Some(active_profile.id), &playlist).await?;
 
-        let (track_details, _total) = playlist_stats::get_playlist_tracks_paginated(
-            &state.db,
-            id,
-            offset,
-            TRACKS_PER_PAGE,
-        )
-        .await
-        .unwrap_or_default();
-
-        let track_data: Vec<PlaylistTrackData> = track_details
-            .into_iter()
-            .map(|t| PlaylistTrackData {
-                position: t.position,
-                track_name: t.track_name,
-                artist_name: t.artist_name,
-                album_id: t.album_id,
-                album_name: t.album_name,
-                duration_ms: t.duration_ms,
-                ownership_status: OwnershipStatus::from_str(&t.ownership_status)
-                    .unwrap_or(OwnershipStatus::NotOwned),
-            })
-            .collect();
-
-        let modal_markup = playlist_detail_partial(&playlist_data, track_data, page, total_pages.max(1));
+        // Re-render the modal with updated data, plus an OOB update for the grid card
+        let (playlist_data, modal_markup) = render_playlist_detail(&state, &active_profile, playlist, query.page).await?;
         let card_oob_markup = playlist_card_oob(&playlist_data);
 
-        // Combine modal content with OOB card update
         let combined = format!("{}{}", modal_markup.into_string(), card_oob_markup.into_string());
         Ok(Html(combined))
     } else {
@@ -624,6 +1271,64 @@ pub async fn playlist_toggle(
     }
 }
 
+/// Mark a track's album owned from the playlist modal
+///
+/// Everything comes back out of band: the ownership cell of every row of
+/// that album, the modal's stats bar and the grid card. Albums with no track
+/// in the playlist are not found.
+pub async fn playlist_mark_album_owned(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path((id, album_id)): Path<(i32, i32)>,
+) -> Result<Html<String>> {
+    use sea_orm::{ActiveModelTrait, JoinType, RelationTrait, Set, TransactionTrait};
+
+    let playlist = playlists::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Playlist not found".to_string()))?;
+    let album = albums::Entity::find_by_id(album_id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Album not found".to_string()))?;
+    let in_playlist = playlist_tracks::Entity::find()
+        .join(JoinType::InnerJoin, playlist_tracks::Relation::Tracks.def())
+        .filter(playlist_tracks::Column::PlaylistId.eq(id))
+        .filter(tracks::Column::AlbumId.eq(album_id))
+        .count(&state.db)
+        .await?;
+    if in_playlist == 0 {
+        return Err(AppError::NotFound(format!(
+            "Album {} has no track in playlist {}",
+            album_id, id
+        )));
+    }
+
+    let from = ownership::current_status(&album);
+    let to = OwnershipStatus::Owned;
+    ownership::check_transition(from, to, ownership::REASON_MANUAL, false)?;
+
+    let txn = state.db.begin().await?;
+    let mut active: albums::ActiveModel = album.into();
+    ownership::set_status(&mut active, from, to);
+    active.updated_at = Set(chrono::Utc::now().into());
+    active.update(&txn).await?;
+    ownership::record_transition(&txn, album_id, from, to, ownership::REASON_MANUAL, false).await?;
+    txn.commit().await?;
+    ownership::publish_transition(&state.events, album_id, from, to, ownership::REASON_MANUAL, false);
+    album_grid_cache::invalidate(&state.redis).await;
+
+    // Counted the same way as the full modal, so a reload shows the same numbers
+    let counts = playlist_stats::playlist_counts(&state.db, &playlist).await?;
+    let tz = display_timezone(&state, &active_profile).await?;
+    let playlist_data = PlaylistCardData::from_model(playlist, counts.owned, counts.synced, tz);
+
+    let mut html = playlist_track_status(to, id, album_id, true).into_string();
+    html.push_str(&playlist_stats_bar_oob(&playlist_data).into_string());
+    html.push_str(&playlist_card_oob(&playlist_data).into_string());
+    Ok(Html(html))
+}
+
 use super::playlists::PlaylistTracksQuery;
 
 /// Playlist tracks partial (for HTMX infinite scroll)
@@ -632,11 +1337,12 @@ pub async fn playlist_tracks_partial(
     Path(id): Path<i32>,
     Query(query): Query<PlaylistTracksQuery>,
 ) -> Result<Html<String>> {
+    let limit = query.page_limit();
     let (track_details, total) = playlist_stats::get_playlist_tracks_paginated(
         &state.db,
         id,
         query.offset,
-        query.limit,
+        limit,
     )
     .await?;
 
@@ -644,18 +1350,9 @@ pub async fn playlist_tracks_partial(
 
     let track_data: Vec<PlaylistTrackData> = track_details
         .into_iter()
-        .map(|t| PlaylistTrackData {
-            position: t.position,
-            track_name: t.track_name,
-            artist_name: t.artist_name,
-            album_id: t.album_id,
-            album_name: t.album_name,
-            duration_ms: t.duration_ms,
-            ownership_status: OwnershipStatus::from_str(&t.ownership_status)
-                .unwrap_or(OwnershipStatus::NotOwned),
-        })
+        .map(playlist_track_data)
         .collect();
 
-    let markup = playlist_tracks_rows(track_data, has_more, id, query.offset + query.limit);
+    let markup = playlist_tracks_rows(track_data, has_more, id, query.offset + limit);
     Ok(Html(markup.into_string()))
 }
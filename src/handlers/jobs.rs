@@ -1,10 +1,11 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
     Json,
 };
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, EntityTrait, QueryOrder, QuerySelect, Set};
-use serde::Serialize;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     db::{
@@ -12,7 +13,14 @@ use crate::{
         enums::{JobStatus, JobType},
     },
     error::{AppError, Result},
+    handlers::profiles::ActiveProfile,
+    jobs::queue::JobMessage,
+    services::album_grid_cache,
     state::AppState,
+    tasks::{
+        artist_images,
+        filesystem_scan::{self, ScanApplyReport},
+    },
 };
 
 #[derive(Serialize)]
@@ -20,6 +28,8 @@ pub struct JobResponse {
     pub id: i32,
     pub job_type: String,
     pub status: String,
+    /// What the job works on: an album for album-scoped jobs, a profile for syncs
+    pub entity_id: Option<i32>,
     pub progress: Option<i32>,
     pub processed_items: Option<i32>,
     pub total_items: Option<i32>,
@@ -27,6 +37,10 @@ pub struct JobResponse {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub created_at: String,
+    /// Summary a job left behind when it finished, if it records one
+    pub result: Option<serde_json::Value>,
+    /// Live detail of a running job, such as a Spotify sync's API usage and time left
+    pub progress_detail: Option<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -35,8 +49,30 @@ pub struct JobCreatedResponse {
     pub status: String,
 }
 
-pub async fn list_jobs(State(state): State<AppState>) -> Result<Json<Vec<JobResponse>>> {
-    let jobs = jobs::Entity::find()
+/// Header on `GET /api/jobs` carrying how many jobs are waiting in the in-memory queue
+pub const JOB_QUEUE_DEPTH_HEADER: &str = "x-job-queue-depth";
+
+#[derive(Debug, Deserialize)]
+pub struct ListJobsQuery {
+    /// Only jobs in this status; `queued` is an alias for `pending`
+    pub status: Option<String>,
+}
+
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<(HeaderMap, Json<Vec<JobResponse>>)> {
+    let mut select = jobs::Entity::find();
+    if let Some(status) = query.status.as_deref() {
+        let status = match status {
+            "queued" => JobStatus::Pending,
+            other => JobStatus::from_str(other)
+                .ok_or_else(|| AppError::Validation(format!("Unknown job status: {}", other)))?,
+        };
+        select = select.filter(jobs::Column::Status.eq(status.as_str()));
+    }
+
+    let jobs = select
         .order_by_desc(jobs::Column::CreatedAt)
         .limit(50)
         .all(&state.db)
@@ -48,6 +84,7 @@ pub async fn list_jobs(State(state): State<AppState>) -> Result<Json<Vec<JobResp
             id: j.id,
             job_type: format!("{:?}", j.job_type),
             status: format!("{:?}", j.status),
+            entity_id: j.entity_id,
             progress: j.progress,
             processed_items: j.processed_items,
             total_items: j.total_items,
@@ -55,10 +92,33 @@ pub async fn list_jobs(State(state): State<AppState>) -> Result<Json<Vec<JobResp
             started_at: j.started_at.map(|dt| dt.to_string()),
             completed_at: j.completed_at.map(|dt| dt.to_string()),
             created_at: j.created_at.to_string(),
+            result: j.result.and_then(|r| serde_json::from_str(&r).ok()),
+            progress_detail: j.progress_detail.and_then(|d| serde_json::from_str(&d).ok()),
         })
         .collect();
 
-    Ok(Json(responses))
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static(JOB_QUEUE_DEPTH_HEADER),
+        HeaderValue::from(state.job_queue.depth()),
+    );
+
+    Ok((headers, Json(responses)))
+}
+
+/// Hand a freshly inserted job to the executor, failing the row if the queue turns it away
+pub(crate) async fn enqueue_job(state: &AppState, job: jobs::Model, job_type: JobType) -> Result<()> {
+    let message = JobMessage { job_id: job.id, job_type, entity_id: job.entity_id };
+    if let Err(e) = state.job_queue.submit(message) {
+        // Leave no pending row behind that nothing will ever pick up
+        let mut failed: jobs::ActiveModel = job.into();
+        failed.status = Set(JobStatus::Failed.as_str().to_string());
+        failed.error_message = Set(Some(e.to_string()));
+        failed.updated_at = Set(Utc::now().into());
+        failed.update(&state.db).await?;
+        return Err(e.into());
+    }
+    Ok(())
 }
 
 pub async fn get_job_status(
@@ -74,6 +134,7 @@ pub async fn get_job_status(
         id: job_record.id,
         job_type: format!("{:?}", job_record.job_type),
         status: format!("{:?}", job_record.status),
+        entity_id: job_record.entity_id,
         progress: job_record.progress,
         processed_items: job_record.processed_items,
         total_items: job_record.total_items,
@@ -81,17 +142,21 @@ pub async fn get_job_status(
         started_at: job_record.started_at.map(|dt| dt.to_string()),
         completed_at: job_record.completed_at.map(|dt| dt.to_string()),
         created_at: job_record.created_at.to_string(),
+        result: job_record.result.and_then(|r| serde_json::from_str(&r).ok()),
+        progress_detail: job_record.progress_detail.and_then(|d| serde_json::from_str(&d).ok()),
     }))
 }
 
 pub async fn trigger_spotify_sync(
     State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
 ) -> Result<Json<JobCreatedResponse>> {
-    // Create a new job record
+    // Create a new job record (the entity is the profile being synced)
     let now = Utc::now().into();
     let new_job = jobs::ActiveModel {
         job_type: Set(JobType::SpotifySync.as_str().to_string()),
         status: Set(JobStatus::Pending.as_str().to_string()),
+        entity_id: Set(Some(profile.id)),
         created_at: Set(now),
         updated_at: Set(now),
         ..Default::default()
@@ -100,14 +165,11 @@ pub async fn trigger_spotify_sync(
     let inserted_job = new_job.insert(&state.db).await?;
 
     // Submit job to the queue
-    state.job_queue.submit(crate::jobs::queue::JobMessage {
-        job_id: inserted_job.id,
-        job_type: JobType::SpotifySync,
-        entity_id: None,
-    })?;
+    let job_id = inserted_job.id;
+    enqueue_job(&state, inserted_job, JobType::SpotifySync).await?;
 
     Ok(Json(JobCreatedResponse {
-        job_id: inserted_job.id,
+        job_id,
         status: "pending".to_string(),
     }))
 }
@@ -128,29 +190,149 @@ pub async fn trigger_musicbrainz_match(
     let inserted_job = new_job.insert(&state.db).await?;
 
     // Submit job to the queue
-    state.job_queue.submit(crate::jobs::queue::JobMessage {
-        job_id: inserted_job.id,
-        job_type: JobType::MusicbrainzMatch,
-        entity_id: None,
-    })?;
+    let job_id = inserted_job.id;
+    enqueue_job(&state, inserted_job, JobType::MusicbrainzMatch).await?;
+
+    Ok(Json(JobCreatedResponse {
+        job_id,
+        status: "pending".to_string(),
+    }))
+}
+
+/// Re-classify scored matches against the current thresholds (no MusicBrainz calls)
+pub async fn trigger_match_reevaluation(
+    State(state): State<AppState>,
+) -> Result<Json<JobCreatedResponse>> {
+    let now = Utc::now().into();
+    let new_job = jobs::ActiveModel {
+        job_type: Set(JobType::MatchReevaluation.as_str().to_string()),
+        status: Set(JobStatus::Pending.as_str().to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+
+    let inserted_job = new_job.insert(&state.db).await?;
+
+    let job_id = inserted_job.id;
+    enqueue_job(&state, inserted_job, JobType::MatchReevaluation).await?;
+
+    Ok(Json(JobCreatedResponse {
+        job_id,
+        status: "pending".to_string(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ArtistImagesQuery {
+    /// Fetch images again for artists that already have one
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Fill in Spotify images for artists without one, or for every artist with `refresh`
+pub async fn trigger_artist_image_backfill(
+    State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
+    Query(query): Query<ArtistImagesQuery>,
+) -> Result<Json<JobCreatedResponse>> {
+    let job = artist_images::queue_backfill(&state, Some(profile.id), query.refresh).await?;
+
+    Ok(Json(JobCreatedResponse {
+        job_id: job.id,
+        status: "pending".to_string(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ScanQuery {
+    /// Only propose matches for review instead of marking albums owned
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Scan the music folder for albums; with `dry_run` the matches are only proposed
+pub async fn trigger_filesystem_scan(
+    State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
+    Query(query): Query<ScanQuery>,
+) -> Result<Json<JobCreatedResponse>> {
+    let job_type = if query.dry_run {
+        JobType::FilesystemScanPreview
+    } else {
+        JobType::FilesystemScan
+    };
+
+    // The entity is the profile whose music folder and settings the scan uses
+    let now = Utc::now().into();
+    let new_job = jobs::ActiveModel {
+        job_type: Set(job_type.as_str().to_string()),
+        status: Set(JobStatus::Pending.as_str().to_string()),
+        entity_id: Set(Some(profile.id)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+
+    let inserted_job = new_job.insert(&state.db).await?;
+
+    let job_id = inserted_job.id;
+    enqueue_job(&state, inserted_job, job_type).await?;
 
     Ok(Json(JobCreatedResponse {
-        job_id: inserted_job.id,
+        job_id,
         status: "pending".to_string(),
     }))
 }
 
+#[derive(Deserialize)]
+pub struct ApplyScanRequest {
+    /// Proposed albums to mark owned; the rest are rejected
+    pub album_ids: Vec<i32>,
+}
+
+/// Apply the accepted matches of a finished scan preview
+pub async fn apply_scan(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ApplyScanRequest>,
+) -> Result<Json<ScanApplyReport>> {
+    let report = apply_scan_matches(&state, id, &payload.album_ids).await?;
+    Ok(Json(report))
+}
+
+pub(crate) async fn apply_scan_matches(
+    state: &AppState,
+    job_id: i32,
+    album_ids: &[i32],
+) -> Result<ScanApplyReport> {
+    let job = jobs::Entity::find_by_id(job_id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+
+    let report = filesystem_scan::apply_scan_preview(state, &job, album_ids).await?;
+    if report.applied > 0 {
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test_utils::*;
     use sea_orm::EntityTrait;
 
+    async fn default_active_profile(state: &AppState) -> ActiveProfile {
+        ActiveProfile(crate::db::profile::default_profile(&state.db).await.unwrap())
+    }
+
     #[tokio::test]
     async fn test_trigger_spotify_sync_creates_job() {
         let (state, _receiver) = setup_test_app_state_with_queue().await;
 
-        let response = trigger_spotify_sync(State(state.clone()))
+        let response = trigger_spotify_sync(State(state.clone()), default_active_profile(&state).await)
             .await
             .expect("Should successfully create job");
 
@@ -173,7 +355,7 @@ mod tests {
     async fn test_trigger_spotify_sync_sets_timestamps() {
         let (state, _receiver) = setup_test_app_state_with_queue().await;
 
-        let response = trigger_spotify_sync(State(state.clone()))
+        let response = trigger_spotify_sync(State(state.clone()), default_active_profile(&state).await)
             .await
             .expect("Should successfully create job");
 
@@ -216,6 +398,72 @@ mod tests {
         assert!(job.updated_at.timestamp() > 0, "updated_at must be set");
     }
 
+    #[tokio::test]
+    async fn test_trigger_match_reevaluation_creates_job() {
+        let (state, mut receiver) = setup_test_app_state_with_queue().await;
+
+        let response = trigger_match_reevaluation(State(state.clone()))
+            .await
+            .expect("Should successfully create job");
+
+        let job = jobs::Entity::find_by_id(response.0.job_id)
+            .one(&state.db)
+            .await
+            .expect("Query should succeed")
+            .expect("Job should exist");
+
+        assert_eq!(job.job_type, JobType::MatchReevaluation.as_str());
+        assert_eq!(job.status, JobStatus::Pending.as_str());
+
+        let message = receiver.try_recv().expect("Job should be queued");
+        assert_eq!(message.job_type, JobType::MatchReevaluation);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_filesystem_scan_dry_run_queues_preview() {
+        let (state, mut receiver) = setup_test_app_state_with_queue().await;
+
+        let response = trigger_filesystem_scan(
+            State(state.clone()),
+            default_active_profile(&state).await,
+            Query(ScanQuery { dry_run: true }),
+        )
+            .await
+            .expect("Should successfully create job");
+
+        let job = jobs::Entity::find_by_id(response.0.job_id)
+            .one(&state.db)
+            .await
+            .expect("Query should succeed")
+            .expect("Job should exist");
+        assert_eq!(job.job_type, JobType::FilesystemScanPreview.as_str());
+
+        let message = receiver.try_recv().expect("Job should be queued");
+        assert_eq!(message.job_type, JobType::FilesystemScanPreview);
+
+        trigger_filesystem_scan(
+            State(state.clone()),
+            default_active_profile(&state).await,
+            Query(ScanQuery { dry_run: false }),
+        )
+            .await
+            .expect("Should successfully create job");
+        let message = receiver.try_recv().expect("Job should be queued");
+        assert_eq!(message.job_type, JobType::FilesystemScan);
+    }
+
+    #[tokio::test]
+    async fn test_apply_scan_rejects_other_jobs() {
+        let state = setup_test_app_state().await;
+        let job = create_test_job(&state.db, JobType::SpotifySync, JobStatus::Completed).await;
+
+        let result = apply_scan_matches(&state, job.id, &[1]).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let result = apply_scan_matches(&state, job.id + 100, &[1]).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
     #[tokio::test]
     async fn test_list_jobs_returns_recent_jobs() {
         let state = setup_test_app_state().await;
@@ -225,11 +473,11 @@ mod tests {
         create_test_job(&state.db, JobType::MusicbrainzMatch, JobStatus::Running).await;
         create_test_job(&state.db, JobType::SpotifySync, JobStatus::Completed).await;
 
-        let response = list_jobs(State(state.clone()))
+        let response = list_jobs(State(state.clone()), Query(ListJobsQuery { status: None }))
             .await
             .expect("Should successfully list jobs");
 
-        let jobs = response.0;
+        let (_, Json(jobs)) = response;
         assert_eq!(jobs.len(), 3);
 
         // Jobs should be ordered by created_at DESC (most recent first)
@@ -240,6 +488,61 @@ mod tests {
         assert_eq!(jobs[2].status, "\"pending\"");
     }
 
+    #[tokio::test]
+    async fn test_full_queue_returns_503_until_drained() {
+        use axum::{http::StatusCode, response::IntoResponse};
+
+        let (state, mut receiver) = setup_test_app_state_with_queue_capacity(1).await;
+
+        let first = trigger_musicbrainz_match(State(state.clone()))
+            .await
+            .expect("First job fits in the queue");
+
+        let Err(error) = trigger_musicbrainz_match(State(state.clone())).await else {
+            panic!("Second job should be turned away");
+        };
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers()["retry-after"],
+            crate::jobs::queue::QUEUE_FULL_RETRY_AFTER_SECS.to_string()
+        );
+
+        // The rejected job is failed rather than left pending forever
+        let (headers, Json(queued)) = list_jobs(
+            State(state.clone()),
+            Query(ListJobsQuery { status: Some("queued".to_string()) }),
+        )
+        .await
+        .expect("Should list queued jobs");
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].id, first.0.job_id);
+        assert_eq!(headers[JOB_QUEUE_DEPTH_HEADER], "1");
+
+        let (_, Json(failed)) = list_jobs(
+            State(state.clone()),
+            Query(ListJobsQuery { status: Some("failed".to_string()) }),
+        )
+        .await
+        .expect("Should list failed jobs");
+        assert_eq!(failed.len(), 1);
+
+        // Once the executor picks up the waiting job there's room again
+        assert_eq!(receiver.recv().await.unwrap().job_id, first.0.job_id);
+        trigger_musicbrainz_match(State(state.clone()))
+            .await
+            .expect("Queue should accept jobs after draining");
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_rejects_unknown_status() {
+        let state = setup_test_app_state().await;
+
+        let result = list_jobs(State(state), Query(ListJobsQuery { status: Some("stuck".to_string()) })).await;
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
     #[tokio::test]
     async fn test_get_job_status_returns_job() {
         let state = setup_test_app_state().await;
@@ -252,6 +555,7 @@ mod tests {
 
         let job_response = response.0;
         assert_eq!(job_response.id, job.id);
+        assert_eq!(job_response.entity_id, None);
         // Note: job_type and status are formatted with Debug which wraps the string in quotes
         assert_eq!(job_response.job_type, "\"spotify_sync\"");
         assert_eq!(job_response.status, "\"running\"");
@@ -1,35 +1,107 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     Json,
 };
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ColumnTrait, Condition, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::{
     db::{
-        entities::{albums, artists, lidarr_downloads},
-        enums::{AcquisitionSource, OwnershipStatus},
+        artist_names,
+        entities::{albums, artists, lidarr_downloads, lidarr_webhook_events},
+        enums::{AcquisitionSource, DownloadStatus, MatchStatus, OwnershipStatus},
+        profile, settings as db_settings,
+    },
+    error::{AppError, Result},
+    handlers::{
+        albums::{ArtistResponse, PaginationInfo},
+        profiles::ActiveProfile,
     },
-    error::Result,
-    services::LidarrWebhook,
+    services::{album_grid_cache, music_paths, ownership, LidarrWebhook},
     state::AppState,
 };
 
 /// Handle Lidarr webhook notifications
+///
+/// Lidarr retries deliveries it thinks timed out, so each payload is recorded
+/// by hash before it is applied. A payload seen before is acknowledged with
+/// 200 without touching albums, downloads or playlist stats again.
 pub async fn webhook(
     State(state): State<AppState>,
-    Json(payload): Json<LidarrWebhook>,
+    Json(raw): Json<serde_json::Value>,
 ) -> Result<StatusCode> {
+    let payload: LidarrWebhook = serde_json::from_value(raw.clone())
+        .map_err(|e| AppError::Validation(format!("Invalid Lidarr webhook payload: {}", e)))?;
     tracing::info!("Received Lidarr webhook: {:?}", payload);
 
+    let hash = payload_hash(&raw);
+    let event_type = raw["eventType"].as_str().unwrap_or_default();
+    if !claim_event(&state, &hash, event_type).await? {
+        tracing::info!("Ignoring replayed Lidarr {} webhook ({})", event_type, hash);
+        return Ok(StatusCode::OK);
+    }
+
+    if let Err(e) = apply_webhook(&state, payload).await {
+        // Let Lidarr's retry apply the event instead of swallowing it
+        release_event(&state, &hash).await;
+        return Err(e);
+    }
+    album_grid_cache::invalidate(&state.redis).await;
+
+    Ok(StatusCode::OK)
+}
+
+/// Hash identifying a webhook payload, independent of JSON key order and whitespace
+fn payload_hash(raw: &serde_json::Value) -> String {
+    format!("{:x}", Sha256::digest(raw.to_string().as_bytes()))
+}
+
+/// Record a payload as processed; `false` if it already was
+async fn claim_event(state: &AppState, hash: &str, event_type: &str) -> Result<bool> {
+    let event = lidarr_webhook_events::ActiveModel {
+        payload_hash: Set(hash.to_string()),
+        event_type: Set(event_type.to_string()),
+        received_at: Set(Utc::now().into()),
+        ..Default::default()
+    };
+
+    let inserted = lidarr_webhook_events::Entity::insert(event)
+        .on_conflict(
+            OnConflict::column(lidarr_webhook_events::Column::PayloadHash)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&state.db)
+        .await?;
+
+    Ok(inserted > 0)
+}
+
+/// Forget a claimed payload whose processing failed
+async fn release_event(state: &AppState, hash: &str) {
+    if let Err(e) = lidarr_webhook_events::Entity::delete_many()
+        .filter(lidarr_webhook_events::Column::PayloadHash.eq(hash))
+        .exec(&state.db)
+        .await
+    {
+        tracing::warn!("Failed to release Lidarr webhook event {}: {}", hash, e);
+    }
+}
+
+async fn apply_webhook(state: &AppState, payload: LidarrWebhook) -> Result<()> {
     match payload {
         LidarrWebhook::Grab {
             artist,
             albums,
             download_id,
         } => {
-            handle_grab(&state, artist, albums, download_id).await?;
+            handle_grab(state, artist, albums, download_id).await?;
         }
         LidarrWebhook::Download {
             artist,
@@ -37,21 +109,21 @@ pub async fn webhook(
             track_files,
             is_upgrade,
         } => {
-            handle_download(&state, artist, albums, track_files, is_upgrade).await?;
+            handle_download(state, artist, albums, track_files, is_upgrade).await?;
         }
         LidarrWebhook::AlbumDownload { artist, album } => {
-            handle_album_download(&state, artist, album).await?;
+            handle_album_download(state, artist, album).await?;
         }
         LidarrWebhook::DownloadFailure {
             artist,
             albums,
             message,
         } => {
-            handle_download_failure(&state, artist, albums, message).await?;
+            handle_download_failure(state, artist, albums, message).await?;
         }
     }
 
-    Ok(StatusCode::OK)
+    Ok(())
 }
 
 /// Handle "Grab" event - album download started
@@ -70,22 +142,53 @@ async fn handle_grab(
         )
         .await?
         {
-            // Update album status to Downloading
-            let mut active: albums::ActiveModel = album.clone().into();
-            active.ownership_status = Set(OwnershipStatus::Downloading.as_str().to_string());
-            active.updated_at = Set(Utc::now().into());
-            active.update(&state.db).await?;
-
-            // Create lidarr_download record
-            let download_record = lidarr_downloads::ActiveModel {
-                album_id: Set(album.id),
-                lidarr_album_id: Set(Some(lidarr_album.id)),
-                download_id: Set(Some(download_id.clone())),
-                status: Set("grabbing".to_string()),
-                created_at: Set(Utc::now().into()),
-                ..Default::default()
+            // Update album status to Downloading; an owned album being
+            // upgraded stays owned
+            set_ownership(state, &album, OwnershipStatus::Downloading, ownership::REASON_LIDARR_GRAB)
+                .await?;
+
+            // Create or refresh the lidarr_download record for this grab. A
+            // grab answering an automatic retry takes over the retried record,
+            // so its retry count carries over.
+            let existing = match lidarr_downloads::Entity::find()
+                .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
+                .filter(lidarr_downloads::Column::DownloadId.eq(download_id.as_str()))
+                .one(&state.db)
+                .await?
+            {
+                Some(download) => Some(download),
+                None => {
+                    lidarr_downloads::Entity::find()
+                        .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
+                        .filter(lidarr_downloads::Column::LidarrAlbumId.eq(lidarr_album.id))
+                        .filter(lidarr_downloads::Column::Status.eq(DownloadStatus::Searching.as_str()))
+                        .order_by_desc(lidarr_downloads::Column::Id)
+                        .one(&state.db)
+                        .await?
+                }
             };
-            download_record.insert(&state.db).await?;
+            match existing {
+                Some(download) => {
+                    let mut active_download: lidarr_downloads::ActiveModel = download.into();
+                    active_download.lidarr_album_id = Set(Some(lidarr_album.id));
+                    active_download.download_id = Set(Some(download_id.clone()));
+                    active_download.status = Set("grabbing".to_string());
+                    active_download.updated_at = Set(Utc::now().into());
+                    active_download.update(&state.db).await?;
+                }
+                None => {
+                    let download_record = lidarr_downloads::ActiveModel {
+                        album_id: Set(album.id),
+                        lidarr_album_id: Set(Some(lidarr_album.id)),
+                        download_id: Set(Some(download_id.clone())),
+                        status: Set("grabbing".to_string()),
+                        created_at: Set(Utc::now().into()),
+                        updated_at: Set(Utc::now().into()),
+                        ..Default::default()
+                    };
+                    download_record.insert(&state.db).await?;
+                }
+            }
 
             tracing::info!(
                 "Album '{}' download started (download_id: {})",
@@ -106,6 +209,14 @@ async fn handle_download(
     track_files: Vec<crate::services::TrackFile>,
     _is_upgrade: bool,
 ) -> Result<()> {
+    let mappings = music_paths::parse_mappings(
+        db_settings::shared(&state.db)
+            .await?
+            .and_then(|s| s.path_mappings)
+            .as_deref(),
+    );
+    let mut stored_paths = Vec::new();
+
     for lidarr_album in albums {
         if let Some(album) = find_album_by_title_and_artist(
             state,
@@ -114,38 +225,58 @@ async fn handle_download(
         )
         .await?
         {
-            // Extract local path from first track file
+            // Extract local path from first track file, as seen from here
             let local_path = track_files
                 .first()
                 .and_then(|tf| {
                     std::path::Path::new(&tf.path)
                         .parent()
                         .map(|p| p.to_string_lossy().to_string())
-                });
+                })
+                .map(|path| music_paths::apply_mappings(&path, &mappings));
+            stored_paths.extend(local_path.clone());
 
             // Update album to Owned status
+            let from = ownership::current_status(&album);
+            ownership::check_transition(from, OwnershipStatus::Owned, ownership::REASON_LIDARR_IMPORT, false)?;
             let mut active: albums::ActiveModel = album.clone().into();
-            active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+            ownership::set_status(&mut active, from, OwnershipStatus::Owned);
             active.acquisition_source = Set(Some(AcquisitionSource::Lidarr.as_str().to_string()));
             active.local_path = Set(local_path);
             active.updated_at = Set(Utc::now().into());
-            active.update(&state.db).await?;
-
-            // Update playlist owned_count
-            if let Err(e) = crate::services::playlist_stats::update_playlists_for_album(&state.db, album.id).await {
-                tracing::warn!("Failed to update playlist stats after download: {}", e);
-            }
+            let txn = state.db.begin().await?;
+            active.update(&txn).await?;
+            ownership::record_transition(
+                &txn,
+                album.id,
+                from,
+                OwnershipStatus::Owned,
+                ownership::REASON_LIDARR_IMPORT,
+                false,
+            )
+            .await?;
+            txn.commit().await?;
+            ownership::publish_transition(
+                &state.events,
+                album.id,
+                from,
+                OwnershipStatus::Owned,
+                ownership::REASON_LIDARR_IMPORT,
+                false,
+            );
 
             // Update lidarr_download record
             if let Some(download) = lidarr_downloads::Entity::find()
                 .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
                 .filter(lidarr_downloads::Column::LidarrAlbumId.eq(lidarr_album.id))
+                .order_by_desc(lidarr_downloads::Column::Id)
                 .one(&state.db)
                 .await?
             {
                 let mut active_download: lidarr_downloads::ActiveModel = download.into();
                 active_download.status = Set("completed".to_string());
                 active_download.completed_at = Set(Some(Utc::now().into()));
+                active_download.updated_at = Set(Utc::now().into());
                 active_download.update(&state.db).await?;
             }
 
@@ -157,6 +288,11 @@ async fn handle_download(
         }
     }
 
+    // Warn when Lidarr's library doesn't line up with the music folder
+    if let Err(e) = music_paths::record_path_check(&state.db, None, &stored_paths).await {
+        tracing::warn!("Failed to check Lidarr paths against the music folder: {}", e);
+    }
+
     Ok(())
 }
 
@@ -174,16 +310,32 @@ async fn handle_album_download(
     )
     .await?
     {
+        let from = ownership::current_status(&db_album);
+        ownership::check_transition(from, OwnershipStatus::Owned, ownership::REASON_LIDARR_IMPORT, false)?;
         let mut active: albums::ActiveModel = db_album.clone().into();
-        active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+        ownership::set_status(&mut active, from, OwnershipStatus::Owned);
         active.acquisition_source = Set(Some(AcquisitionSource::Lidarr.as_str().to_string()));
         active.updated_at = Set(Utc::now().into());
-        active.update(&state.db).await?;
-
-        // Update playlist owned_count
-        if let Err(e) = crate::services::playlist_stats::update_playlists_for_album(&state.db, db_album.id).await {
-            tracing::warn!("Failed to update playlist stats after album download: {}", e);
-        }
+        let txn = state.db.begin().await?;
+        active.update(&txn).await?;
+        ownership::record_transition(
+            &txn,
+            db_album.id,
+            from,
+            OwnershipStatus::Owned,
+            ownership::REASON_LIDARR_IMPORT,
+            false,
+        )
+        .await?;
+        txn.commit().await?;
+        ownership::publish_transition(
+            &state.events,
+            db_album.id,
+            from,
+            OwnershipStatus::Owned,
+            ownership::REASON_LIDARR_IMPORT,
+            false,
+        );
 
         tracing::info!(
             "Album '{}' by '{}' download completed",
@@ -210,27 +362,23 @@ async fn handle_download_failure(
         )
         .await?
         {
-            // Update album back to NotOwned
-            let mut active: albums::ActiveModel = album.clone().into();
-            active.ownership_status = Set(OwnershipStatus::NotOwned.as_str().to_string());
-            active.updated_at = Set(Utc::now().into());
-            active.update(&state.db).await?;
-
-            // Update playlist owned_count
-            if let Err(e) = crate::services::playlist_stats::update_playlists_for_album(&state.db, album.id).await {
-                tracing::warn!("Failed to update playlist stats after download failure: {}", e);
-            }
+            // Update album back to NotOwned; a failed upgrade of an owned
+            // album leaves the existing files in place
+            set_ownership(state, &album, OwnershipStatus::NotOwned, ownership::REASON_LIDARR_FAILURE)
+                .await?;
 
             // Update lidarr_download record
             if let Some(download) = lidarr_downloads::Entity::find()
                 .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
                 .filter(lidarr_downloads::Column::LidarrAlbumId.eq(lidarr_album.id))
+                .order_by_desc(lidarr_downloads::Column::Id)
                 .one(&state.db)
                 .await?
             {
                 let mut active_download: lidarr_downloads::ActiveModel = download.into();
                 active_download.status = Set("failed".to_string());
                 active_download.error_message = Set(Some(error_message.clone()));
+                active_download.updated_at = Set(Utc::now().into());
                 active_download.update(&state.db).await?;
             }
 
@@ -245,22 +393,267 @@ async fn handle_download_failure(
     Ok(())
 }
 
+/// Move an album to `to` if the transition rules allow it
+///
+/// Webhooks can't be retried with `force`, so a disallowed move is logged
+/// and skipped rather than failing the whole delivery.
+async fn set_ownership(
+    state: &AppState,
+    album: &albums::Model,
+    to: OwnershipStatus,
+    reason: &str,
+) -> Result<()> {
+    let from = ownership::current_status(album);
+    if let Err(e) = ownership::check_transition(from, to, reason, false) {
+        tracing::warn!("Keeping album {} as {}: {}", album.id, from.as_str(), e);
+        return Ok(());
+    }
+
+    let txn = state.db.begin().await?;
+    let mut active: albums::ActiveModel = album.clone().into();
+    ownership::set_status(&mut active, from, to);
+    active.updated_at = Set(Utc::now().into());
+    active.update(&txn).await?;
+    ownership::record_transition(&txn, album.id, from, to, reason, false).await?;
+    txn.commit().await?;
+    ownership::publish_transition(&state.events, album.id, from, to, reason, false);
+    Ok(())
+}
+
+/// Which albums a search preview lists
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewList {
+    /// Albums a batch Lidarr search would request
+    Qualifying,
+    /// Albums it would pass over, each with a skip reason
+    Skipped,
+}
+
+#[derive(Deserialize)]
+pub struct SearchPreviewQuery {
+    #[serde(default = "default_preview_list")]
+    pub list: PreviewList,
+    #[serde(default = "default_page")]
+    pub page: u64,
+    #[serde(default = "default_page_size")]
+    pub page_size: u64,
+}
+
+fn default_preview_list() -> PreviewList {
+    PreviewList::Qualifying
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_page_size() -> u64 {
+    50
+}
+
+#[derive(Serialize)]
+pub struct SearchPreviewAlbum {
+    pub id: i32,
+    pub title: String,
+    pub artist: ArtistResponse,
+    pub ownership_status: String,
+    pub musicbrainz_release_group_id: Option<String>,
+    /// Why a batch search would pass over this album; `None` if it qualifies
+    pub skip_reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SearchPreviewTotals {
+    pub qualifying: u64,
+    pub already_owned: u64,
+    pub already_downloading: u64,
+    pub already_ordered: u64,
+    pub not_matched: u64,
+    pub no_mbid: u64,
+}
+
+#[derive(Serialize)]
+pub struct SearchPreviewResponse {
+    pub albums: Vec<SearchPreviewAlbum>,
+    pub totals: SearchPreviewTotals,
+    pub pagination: PaginationInfo,
+}
+
+/// Reason a batch Lidarr search would skip an album
+///
+/// Checked in declaration order, so an owned album without a MusicBrainz
+/// match counts as owned, and an unconfirmed match as not matched even when
+/// it has a release group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SkipReason {
+    AlreadyOwned,
+    AlreadyDownloading,
+    AlreadyOrdered,
+    NotMatched,
+    NoMbid,
+}
+
+impl SkipReason {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::AlreadyOwned => "already_owned",
+            Self::AlreadyDownloading => "already_downloading",
+            Self::AlreadyOrdered => "already_ordered",
+            Self::NotMatched => "not_matched",
+            Self::NoMbid => "no_mbid",
+        }
+    }
+
+    fn of(album: &albums::Model) -> Option<Self> {
+        match OwnershipStatus::from_str(&album.ownership_status) {
+            Some(OwnershipStatus::Owned) => Some(Self::AlreadyOwned),
+            Some(OwnershipStatus::Downloading) => Some(Self::AlreadyDownloading),
+            Some(OwnershipStatus::Ordered) => Some(Self::AlreadyOrdered),
+            _ if album.match_status.as_deref() != Some(MatchStatus::Matched.as_str()) => {
+                Some(Self::NotMatched)
+            }
+            _ if album.musicbrainz_release_group_id.is_none() => Some(Self::NoMbid),
+            _ => None,
+        }
+    }
+
+    fn condition(&self) -> Condition {
+        match self {
+            Self::AlreadyOwned => Condition::all()
+                .add(albums::Column::OwnershipStatus.eq(OwnershipStatus::Owned.as_str())),
+            Self::AlreadyDownloading => Condition::all()
+                .add(albums::Column::OwnershipStatus.eq(OwnershipStatus::Downloading.as_str())),
+            Self::AlreadyOrdered => Condition::all()
+                .add(albums::Column::OwnershipStatus.eq(OwnershipStatus::Ordered.as_str())),
+            Self::NotMatched => Condition::all()
+                .add(albums::Column::OwnershipStatus.eq(OwnershipStatus::NotOwned.as_str()))
+                .add(
+                    Condition::any()
+                        .add(albums::Column::MatchStatus.is_null())
+                        .add(albums::Column::MatchStatus.ne(MatchStatus::Matched.as_str())),
+                ),
+            Self::NoMbid => Condition::all()
+                .add(albums::Column::OwnershipStatus.eq(OwnershipStatus::NotOwned.as_str()))
+                .add(albums::Column::MatchStatus.eq(MatchStatus::Matched.as_str()))
+                .add(albums::Column::MusicbrainzReleaseGroupId.is_null()),
+        }
+    }
+}
+
+/// Albums a batch Lidarr search would request: not owned, with a confirmed
+/// MusicBrainz match
+fn qualifying_condition() -> Condition {
+    Condition::all()
+        .add(albums::Column::OwnershipStatus.eq(OwnershipStatus::NotOwned.as_str()))
+        .add(albums::Column::MatchStatus.eq(MatchStatus::Matched.as_str()))
+        .add(albums::Column::MusicbrainzReleaseGroupId.is_not_null())
+}
+
+/// Preview which albums a batch Lidarr search would request, without
+/// contacting Lidarr
+///
+/// Fails like a real search would when Lidarr isn't configured. Totals cover
+/// every album in the active profile; `list` picks whether the page holds the
+/// qualifying or the skipped albums.
+pub async fn search_preview(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<SearchPreviewQuery>,
+) -> Result<Json<SearchPreviewResponse>> {
+    super::settings::lidarr_connection(&state, Some(active_profile.id)).await?;
+
+    let page = query.page.max(1);
+    let page_size = query.page_size.clamp(1, 200);
+    let in_profile = albums::Entity::find().filter(profile::albums_in_profile(active_profile.id));
+
+    let count = |condition: Condition| in_profile.clone().filter(condition).count(&state.db);
+    let totals = SearchPreviewTotals {
+        qualifying: count(qualifying_condition()).await?,
+        already_owned: count(SkipReason::AlreadyOwned.condition()).await?,
+        already_downloading: count(SkipReason::AlreadyDownloading.condition()).await?,
+        already_ordered: count(SkipReason::AlreadyOrdered.condition()).await?,
+        not_matched: count(SkipReason::NotMatched.condition()).await?,
+        no_mbid: count(SkipReason::NoMbid.condition()).await?,
+    };
+
+    let (listed, total_items) = match query.list {
+        PreviewList::Qualifying => (qualifying_condition(), totals.qualifying),
+        PreviewList::Skipped => (
+            Condition::any()
+                .add(SkipReason::AlreadyOwned.condition())
+                .add(SkipReason::AlreadyDownloading.condition())
+                .add(SkipReason::AlreadyOrdered.condition())
+                .add(SkipReason::NotMatched.condition())
+                .add(SkipReason::NoMbid.condition()),
+            totals.already_owned
+                + totals.already_downloading
+                + totals.already_ordered
+                + totals.not_matched
+                + totals.no_mbid,
+        ),
+    };
+
+    let albums = in_profile
+        .filter(listed)
+        .order_by_asc(albums::Column::Id)
+        .offset((page - 1) * page_size)
+        .limit(page_size)
+        .find_also_related(artists::Entity)
+        .all(&state.db)
+        .await?;
+
+    let albums = albums
+        .into_iter()
+        .filter_map(|(album, artist)| {
+            artist.map(|a| SearchPreviewAlbum {
+                skip_reason: SkipReason::of(&album).map(|r| r.as_str().to_string()),
+                id: album.id,
+                title: album.title,
+                artist: ArtistResponse {
+                    id: a.id,
+                    name: a.name,
+                },
+                ownership_status: album.ownership_status,
+                musicbrainz_release_group_id: album.musicbrainz_release_group_id,
+            })
+        })
+        .collect();
+
+    Ok(Json(SearchPreviewResponse {
+        albums,
+        totals,
+        pagination: PaginationInfo {
+            page,
+            page_size,
+            total_items,
+            total_pages: total_items.div_ceil(page_size),
+        },
+    }))
+}
+
 /// Find album in database by title and artist name (fuzzy match)
 async fn find_album_by_title_and_artist(
     state: &AppState,
     title: &str,
     artist_name: &str,
 ) -> Result<Option<albums::Model>> {
-    // Find all artists and albums, then fuzzy match
-    // This is not the most efficient but works for moderate sizes
-    let artists = artists::Entity::find()
-        .all(&state.db)
-        .await?;
-
-    let matching_artist = artists.iter().find(|a| {
-        a.name.to_lowercase() == artist_name.to_lowercase()
-            || similarity_score(&a.name.to_lowercase(), &artist_name.to_lowercase()) > 0.85
-    });
+    // Score only a bounded set of candidates, so bursts of webhooks during
+    // mass imports don't each load the whole artist table
+    // "The Beatles" and "Beatles" are the same artist
+    let wanted = artist_names::normalize_name(artist_name);
+    let wanted = artist_names::without_article(&wanted);
+    let matching_artist = artist_names::candidates(&state.db, artist_name)
+        .await?
+        .into_iter()
+        .map(|a| {
+            let name = artist_names::without_article(a.normalized_name.as_deref().unwrap_or_default());
+            let score = similarity_score(name, wanted);
+            (a, score)
+        })
+        .filter(|(_, score)| *score > 0.85)
+        .max_by(|(a, a_score), (b, b_score)| a_score.total_cmp(b_score).then(b.id.cmp(&a.id)))
+        .map(|(a, _)| a);
 
     if let Some(artist) = matching_artist {
         let albums = albums::Entity::find()
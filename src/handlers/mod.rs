@@ -1,19 +1,28 @@
 pub mod health;
+pub mod activity;
+pub mod admin;
 pub mod albums;
+pub mod archive;
 pub mod artists;
 pub mod auth;
+pub mod export;
+pub mod goals;
 pub mod jobs;
 pub mod playlists;
+pub mod profiles;
+pub mod reports;
+pub mod search;
 pub mod settings;
 pub mod html;
 pub mod lidarr;
 
 use axum::{
-    routing::{get, post, patch, put},
+    middleware,
+    routing::{delete, get, post, patch, put},
     Router,
 };
 
-use crate::state::AppState;
+use crate::{error, state::AppState};
 
 /// HTML page routes (MASH stack)
 pub fn html_routes() -> Router<AppState> {
@@ -23,9 +32,17 @@ pub fn html_routes() -> Router<AppState> {
         .route("/artists", get(html::artists))
         .route("/artists/:id", get(html::artist_detail))
         .route("/settings", get(html::settings))
+        .route("/settings/locale", post(html::settings_locale))
         .route("/jobs", get(html::jobs))
+        .route("/jobs-list", get(html::jobs_list))
+        .route("/jobs/:id", get(html::job_detail))
+        .route("/jobs/:id/apply-scan", post(html::job_apply_scan))
         .route("/stats", get(html::stats))
+        .route("/stats/heavy-rotation", get(html::heavy_rotation))
+        .route("/stats/artist-heatmap", get(html::artist_heatmap))
         .route("/playlists", get(html::playlists))
+        .route("/board", get(html::board))
+        .route("/archive", get(html::archive))
 
         // OAuth callback (GET with query params from Spotify)
         .route("/auth/callback", get(auth::callback))
@@ -33,11 +50,19 @@ pub fn html_routes() -> Router<AppState> {
         // HTMX partials
         .route("/albums", get(html::albums_grid))
         .route("/albums/:id", get(html::album_detail))
+        .route("/albums/:id/artist", post(html::album_change_artist))
+        .route("/albums/:id/artist-options", get(html::album_artist_options))
+        .route("/albums/:id/lidarr-artist", post(html::album_set_lidarr_artist))
+        .route("/albums/:id/genres", post(html::album_set_genres))
+        .route("/albums/:id/suggestions", get(html::album_suggestions))
+        .route("/albums/:id/lidarr-artist-preview", get(html::album_lidarr_artist_preview))
         .route("/artists-grid", get(html::artists_grid))
         .route("/playlists-grid", get(html::playlists_grid))
         .route("/playlists/:id", get(html::playlist_detail))
         .route("/playlists/:id/toggle", post(html::playlist_toggle))
         .route("/playlists/:id/tracks", get(html::playlist_tracks_partial))
+        .route("/playlists/:id/albums/:album_id/mark-owned", post(html::playlist_mark_album_owned))
+        .route("/board/albums/:id/move", post(html::board_move))
 }
 
 /// JSON API routes (for programmatic access)
@@ -50,35 +75,98 @@ pub fn api_routes() -> Router<AppState> {
 
         // Album endpoints
         .route("/albums", get(albums::list_albums))
+        .route("/albums/by-match-score", get(albums::list_albums_by_match_score))
+        .route("/albums/reset-matches", post(albums::reset_matches))
+        .route("/albums/export-wanted", get(albums::export_wanted))
         .route("/albums/:id", get(albums::get_album))
         .route("/albums/:id", patch(albums::update_album))
         .route("/albums/:id/match", post(albums::trigger_match))
+        .route("/albums/:id/activity", get(albums::get_album_activity))
+        .route("/albums/:id/suggestions", get(albums::get_album_suggestions))
         .route("/albums/:id/search-lidarr", post(albums::search_lidarr))
 
         // Playlist endpoints
         .route("/playlists", get(playlists::list_playlists))
+        .route("/playlists/settings-export", get(playlists::export_playlist_settings))
+        .route("/playlists/settings-import", post(playlists::import_playlist_settings))
         .route("/playlists/:id", get(playlists::get_playlist))
         .route("/playlists/:id/tracks", get(playlists::get_playlist_tracks))
         .route("/playlists/:id/toggle", post(playlists::toggle_playlist_enabled))
+        .route("/playlists/:id/sync-preview", get(playlists::sync_preview))
+        .route("/playlists/by-spotify-id/:spotify_id", get(playlists::get_playlist_by_spotify_id))
+        .route(
+            "/playlists/by-spotify-id/:spotify_id/toggle",
+            post(playlists::toggle_playlist_by_spotify_id),
+        )
+        .route("/import/spotify-playlist", post(playlists::import_spotify_playlist))
+
+        // Profile endpoints
+        .route("/profiles", get(profiles::list_profiles))
+        .route("/profiles", post(profiles::create_profile))
+        .route("/profiles/select", post(profiles::select_profile))
+        .route("/profiles/selector", get(profiles::selector))
 
         // Job endpoints
         .route("/jobs", get(jobs::list_jobs))
         .route("/jobs/:id/status", get(jobs::get_job_status))
         .route("/jobs/spotify-sync", post(jobs::trigger_spotify_sync))
         .route("/jobs/musicbrainz-match-all", post(jobs::trigger_musicbrainz_match))
+        .route("/jobs/reevaluate-matches", post(jobs::trigger_match_reevaluation))
+        .route("/jobs/artist-images", post(jobs::trigger_artist_image_backfill))
+        .route("/jobs/filesystem-scan", post(jobs::trigger_filesystem_scan))
+        .route("/jobs/:id/apply-scan", post(jobs::apply_scan))
 
         // Settings endpoints
         .route("/settings", get(settings::get_settings))
         .route("/settings", put(settings::update_settings))
         .route("/settings/test-lidarr", post(settings::test_lidarr_connection))
 
-        // Lidarr webhook
+        // Admin endpoints
+        .route("/admin/log-level", get(admin::get_log_level))
+        .route("/admin/log-level", put(admin::set_log_level))
+        .route("/admin/log-level", delete(admin::reset_log_level))
+        .route("/admin/side-effects", get(admin::list_side_effects))
+        .route("/admin/side-effects/:id/retry", post(admin::retry_side_effect))
+        .route("/admin/side-effects/:id", delete(admin::discard_side_effect))
+
+        // Lidarr endpoints
+        .route("/lidarr/search-preview", get(lidarr::search_preview))
         .route("/webhooks/lidarr", post(lidarr::webhook))
 
         // Artist endpoints
         .route("/artists", get(artists::list_artists))
-        .route("/artists/:id", get(artists::get_artist))
+        .route("/artists/:id", get(artists::get_artist).patch(artists::rename_artist))
+        .route("/artists/:id/mark-owned", post(artists::mark_artist_owned))
 
         // Statistics
         .route("/stats", get(albums::get_stats))
+        .route("/stats/track-ownership", get(reports::track_ownership))
+        .route("/stats/genres", get(reports::genre_distribution))
+        .route("/stats/artist-heatmap", get(reports::artist_heatmap))
+        .route("/reports/value", get(reports::collection_value))
+        .route("/reports/sync-history", get(reports::sync_history))
+        .route("/reports/artist-completion", get(reports::artist_completion))
+
+        // Collection goals
+        .route("/goals", get(goals::list_goals).post(goals::create_goal))
+        .route(
+            "/goals/:id",
+            get(goals::get_goal).patch(goals::update_goal).delete(goals::delete_goal),
+        )
+
+        // Search
+        .route("/search/tracks", get(search::search_tracks))
+        .route("/tracks/search", get(search::search_tracks))
+
+        // Library-wide activity feed
+        .route("/activity", get(activity::list_activity))
+
+        // Archive of rows removed from Spotify
+        .route("/archive", get(archive::list_archive))
+        .route("/archive/:type/:id/restore", post(archive::restore))
+        .route("/wanted/demotions/:id/restore", post(albums::restore_demotion))
+
+        // Every error gets the JSON error body, including ones raised before a handler runs
+        .fallback(error::api_not_found)
+        .layer(middleware::from_fn(error::json_errors))
 }
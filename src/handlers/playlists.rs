@@ -1,17 +1,35 @@
+use std::collections::{BTreeMap, HashMap};
+
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
+    http::header,
+    response::{Html, IntoResponse, Response},
     Json,
 };
+use futures_util::{stream, StreamExt};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    db::entities::{playlists},
+    db::{
+        entities::{playlists, profiles},
+        profile,
+    },
     error::{AppError, Result},
-    services::playlist_stats,
+    handlers::{albums::JsonOrForm, profiles::ActiveProfile},
+    services::{
+        album_grid_cache, parse_playlist_id, playlist_cleanup,
+        playlist_diff::{self, PlaylistDiff},
+        playlist_stats::{self, PlaylistTrackDetails},
+        SpotifyService,
+    },
     state::AppState,
+    tasks::spotify_sync::{self, PlaylistImportSummary},
+    templates::components::notification,
 };
 
 #[derive(Deserialize)]
@@ -38,12 +56,23 @@ pub struct PlaylistResponse {
     pub description: Option<String>,
     pub owner_name: Option<String>,
     pub is_collaborative: bool,
+    /// Track count Spotify reports, including entries a sync skipped
     pub total_tracks: i32,
+    /// Tracks actually synced; ownership is measured against these
+    pub synced_tracks: i32,
     pub cover_image_url: Option<String>,
     pub is_enabled: bool,
     pub is_synthetic: bool,
+    /// Imported by link rather than followed on Spotify
+    pub is_external: bool,
+    /// Set when syncs disabled the playlist after repeated failures
+    pub disabled_reason: Option<String>,
     pub owned_count: i64,
     pub ownership_percentage: f64,
+    /// Entries Spotify can't play in the user's market, as of the last sync
+    pub unavailable_tracks: i32,
+    /// Podcast episodes the last sync skipped; they aren't counted as tracks
+    pub episode_count: i32,
     pub last_synced_at: Option<String>,
 }
 
@@ -74,21 +103,34 @@ pub struct PlaylistTrackResponse {
     pub added_at: Option<String>,
 }
 
-#[derive(Serialize)]
-pub struct PlaylistDetailResponse {
-    pub playlist: PlaylistResponse,
-    pub tracks: Vec<PlaylistTrackResponse>,
+impl From<PlaylistTrackDetails> for PlaylistTrackResponse {
+    fn from(t: PlaylistTrackDetails) -> Self {
+        Self {
+            id: t.id,
+            position: t.position,
+            track_name: t.track_name,
+            artist_name: t.artist_name,
+            album_id: t.album_id,
+            album_name: t.album_name,
+            duration_ms: t.duration_ms,
+            ownership_status: t.ownership_status,
+            added_at: None,
+        }
+    }
 }
 
 /// List all playlists with ownership statistics
 pub async fn list_playlists(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Query(query): Query<ListPlaylistsQuery>,
 ) -> Result<Json<PaginatedPlaylistsResponse>> {
     let page = query.page.max(1);
     let page_size = query.page_size.min(200).max(1);
 
-    let mut select = playlists::Entity::find();
+    let mut select = playlists::Entity::find()
+        .filter(profile::playlists_in_profile(active_profile.id))
+        .filter(playlists::Column::ArchivedAt.is_null());
 
     if let Some(enabled) = query.is_enabled {
         select = select.filter(playlists::Column::IsEnabled.eq(enabled));
@@ -105,27 +147,15 @@ pub async fn list_playlists(
         .all(&state.db)
         .await?;
 
-    // Batch fetch ownership stats for all playlists on this page (single query!)
-    let playlist_ids: Vec<i32> = playlist_models.iter().map(|p| p.id).collect();
-    let stats_map = playlist_stats::get_batch_playlist_ownership_stats(&state.db, playlist_ids)
+    // Batch fetch track counts for all playlists on this page (single query!)
+    let counts_map = playlist_stats::get_batch_playlist_counts(&state.db, &playlist_models)
         .await
         .unwrap_or_default();
 
     let playlist_responses: Vec<PlaylistResponse> = playlist_models
         .into_iter()
         .map(|playlist| {
-            // Use precomputed owned_count if available, otherwise use batch stats
-            let (owned_count, total_count) = if let Some(precomputed) = playlist.owned_count {
-                (precomputed as i64, playlist.total_tracks.unwrap_or(0) as i64)
-            } else {
-                stats_map.get(&playlist.id).copied().unwrap_or((0, 0))
-            };
-
-            let ownership_percentage = if total_count > 0 {
-                (owned_count as f64 / total_count as f64) * 100.0
-            } else {
-                0.0
-            };
+            let counts = counts_map.get(&playlist.id).copied().unwrap_or_default();
 
             PlaylistResponse {
                 id: playlist.id,
@@ -134,11 +164,16 @@ pub async fn list_playlists(
                 owner_name: playlist.owner_name,
                 is_collaborative: playlist.is_collaborative,
                 total_tracks: playlist.total_tracks.unwrap_or(0),
+                synced_tracks: counts.synced as i32,
                 cover_image_url: playlist.cover_image_url,
                 is_enabled: playlist.is_enabled,
                 is_synthetic: playlist.is_synthetic,
-                owned_count,
-                ownership_percentage,
+                is_external: playlist.is_external,
+                disabled_reason: playlist.disabled_reason,
+                owned_count: counts.owned,
+                ownership_percentage: counts.ownership_percentage(),
+                unavailable_tracks: playlist.unavailable_tracks.unwrap_or(0),
+                episode_count: playlist.episode_count.unwrap_or(0),
                 last_synced_at: playlist.last_synced_at.map(|dt| dt.to_rfc3339()),
             }
         })
@@ -155,31 +190,44 @@ pub async fn list_playlists(
     }))
 }
 
-/// Get a single playlist with all its tracks (use paginated version for large playlists)
+/// Get a single playlist with its first tracks (use paginated version for large playlists)
+///
+/// Responds with `{"playlist": ..., "tracks": [...]}`; the tracks are streamed.
 pub async fn get_playlist(
     State(state): State<AppState>,
     Path(id): Path<i32>,
-) -> Result<Json<PlaylistDetailResponse>> {
+) -> Result<Response> {
     let playlist = playlists::Entity::find_by_id(id)
         .one(&state.db)
         .await?
         .ok_or_else(|| AppError::NotFound("Playlist not found".to_string()))?;
 
-    // Use precomputed owned_count if available, otherwise calculate
-    let total_count = playlist.total_tracks.unwrap_or(0) as i64;
-    let owned_count = if let Some(precomputed) = playlist.owned_count {
-        precomputed as i64
-    } else {
-        playlist_stats::recalculate_playlist_owned_count(&state.db, playlist.id)
-            .await
-            .unwrap_or(0) as i64
-    };
+    playlist_detail(&state, playlist).await
+}
 
-    let ownership_percentage = if total_count > 0 {
-        (owned_count as f64 / total_count as f64) * 100.0
-    } else {
-        0.0
-    };
+/// Get a playlist by its Spotify ID, for scripts that only know Spotify's identifiers
+pub async fn get_playlist_by_spotify_id(
+    State(state): State<AppState>,
+    Path(spotify_id): Path<String>,
+) -> Result<Response> {
+    let playlist = find_by_spotify_id(&state, &spotify_id).await?;
+    playlist_detail(&state, playlist).await
+}
+
+/// Local playlist synced from the Spotify playlist `spotify_id`
+async fn find_by_spotify_id(state: &AppState, spotify_id: &str) -> Result<playlists::Model> {
+    playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq(spotify_id))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No playlist with Spotify ID {}", spotify_id)))
+}
+
+async fn playlist_detail(state: &AppState, playlist: playlists::Model) -> Result<Response> {
+    let id = playlist.id;
+    let counts = playlist_stats::playlist_counts(&state.db, &playlist)
+        .await
+        .unwrap_or_default();
 
     let playlist_response = PlaylistResponse {
         id: playlist.id,
@@ -188,38 +236,101 @@ pub async fn get_playlist(
         owner_name: playlist.owner_name.clone(),
         is_collaborative: playlist.is_collaborative,
         total_tracks: playlist.total_tracks.unwrap_or(0),
+        synced_tracks: counts.synced as i32,
         cover_image_url: playlist.cover_image_url.clone(),
         is_enabled: playlist.is_enabled,
         is_synthetic: playlist.is_synthetic,
-        owned_count,
-        ownership_percentage,
+        is_external: playlist.is_external,
+        disabled_reason: playlist.disabled_reason.clone(),
+        owned_count: counts.owned,
+        ownership_percentage: counts.ownership_percentage(),
+        unavailable_tracks: playlist.unavailable_tracks.unwrap_or(0),
+        episode_count: playlist.episode_count.unwrap_or(0),
         last_synced_at: playlist.last_synced_at.map(|dt| dt.to_rfc3339()),
     };
 
-    // Use optimized paginated query - get first batch of tracks
-    let (track_details, _total) = playlist_stats::get_playlist_tracks_paginated(&state.db, id, 0, 100)
-        .await
-        .unwrap_or_default();
+    let head = format!(r#"{{"playlist":{},"tracks":["#, serde_json::to_string(&playlist_response)?);
+    let tracks = TrackBatches::new(state, id, 0, DETAIL_TRACK_LIMIT);
+    streamed_tracks_json(head, tracks, "]}".to_string()).await
+}
 
-    let tracks: Vec<PlaylistTrackResponse> = track_details
-        .into_iter()
-        .map(|t| PlaylistTrackResponse {
-            id: t.id,
-            position: t.position,
-            track_name: t.track_name,
-            artist_name: t.artist_name,
-            album_id: t.album_id,
-            album_name: t.album_name,
-            duration_ms: t.duration_ms,
-            ownership_status: t.ownership_status,
-            added_at: None,
-        })
-        .collect();
+/// Tracks included with a playlist's detail
+const DETAIL_TRACK_LIMIT: u64 = 100;
+
+/// Tracks read from the database per chunk of a streamed track list
+const TRACK_BATCH_SIZE: u64 = 50;
+
+/// Paging state of a playlist's tracks being streamed out as a JSON array
+struct TrackBatches {
+    db: DatabaseConnection,
+    playlist_id: i32,
+    offset: u64,
+    /// Offset the stream stops at
+    end: u64,
+    first: bool,
+    done: bool,
+}
 
-    Ok(Json(PlaylistDetailResponse {
-        playlist: playlist_response,
-        tracks,
-    }))
+impl TrackBatches {
+    fn new(state: &AppState, playlist_id: i32, offset: u64, limit: u64) -> Self {
+        Self {
+            db: state.db.clone(),
+            playlist_id,
+            offset,
+            end: offset + limit,
+            first: true,
+            done: limit == 0,
+        }
+    }
+
+    /// Render the next batch of array elements, or `None` once the last one is out
+    async fn next_chunk(&mut self) -> Result<Option<String>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let limit = TRACK_BATCH_SIZE.min(self.end - self.offset);
+        let batch =
+            playlist_stats::get_playlist_tracks_page(&self.db, self.playlist_id, self.offset, limit).await?;
+        self.offset += limit;
+        self.done = (batch.len() as u64) < limit || self.offset >= self.end;
+
+        let mut chunk = String::new();
+        for track in batch {
+            if !self.first {
+                chunk.push(',');
+            }
+            self.first = false;
+            chunk.push_str(&serde_json::to_string(&PlaylistTrackResponse::from(track))?);
+        }
+        Ok(Some(chunk))
+    }
+}
+
+/// JSON response whose track array is read and sent in batches, between
+/// already serialized text before and after it
+///
+/// The first batch is read before answering, so a database failure up front
+/// is still a 500. A later failure ends the body with an error instead of the
+/// closing text, so the client sees a broken response rather than valid JSON
+/// with tracks missing.
+async fn streamed_tracks_json(head: String, mut tracks: TrackBatches, tail: String) -> Result<Response> {
+    let first = tracks.next_chunk().await?.unwrap_or_default();
+
+    let rest = stream::unfold((tracks, Some(tail)), |(mut tracks, mut tail)| async move {
+        match tracks.next_chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), (tracks, tail))),
+            Ok(None) => tail.take().map(|tail| (Ok(tail), (tracks, None))),
+            Err(e) => {
+                tracing::error!("Streaming playlist tracks failed: {}", e);
+                tracks.done = true;
+                Some((Err(std::io::Error::other(e.to_string())), (tracks, None)))
+            }
+        }
+    });
+    let chunks = stream::iter([Ok(head + &first)]).chain(rest);
+
+    Ok(([(header::CONTENT_TYPE, "application/json")], Body::from_stream(chunks)).into_response())
 }
 
 #[derive(Deserialize)]
@@ -234,62 +345,50 @@ fn default_track_limit() -> u64 {
     50
 }
 
-#[derive(Serialize)]
-pub struct PaginatedTracksResponse {
-    pub tracks: Vec<PlaylistTrackResponse>,
-    pub has_more: bool,
-    pub total: u64,
-    pub next_offset: u64,
+/// Upper bound on tracks per page, so one request can't build a response
+/// for a whole 10k-track playlist in memory
+pub const MAX_TRACK_LIMIT: u64 = 200;
+
+impl PlaylistTracksQuery {
+    /// Requested page size, clamped to `1..=MAX_TRACK_LIMIT`
+    pub fn page_limit(&self) -> u64 {
+        self.limit.clamp(1, MAX_TRACK_LIMIT)
+    }
 }
 
 /// Get paginated tracks for a playlist (for infinite scroll)
+///
+/// Responds with `{"tracks": [...], "has_more", "total", "next_offset"}`; the
+/// tracks are streamed.
 pub async fn get_playlist_tracks(
     State(state): State<AppState>,
     Path(id): Path<i32>,
     Query(query): Query<PlaylistTracksQuery>,
-) -> Result<Json<PaginatedTracksResponse>> {
+) -> Result<Response> {
     // Verify playlist exists
     let _playlist = playlists::Entity::find_by_id(id)
         .one(&state.db)
         .await?
         .ok_or_else(|| AppError::NotFound("Playlist not found".to_string()))?;
 
-    let (track_details, total) = playlist_stats::get_playlist_tracks_paginated(
-        &state.db,
-        id,
-        query.offset,
-        query.limit,
-    )
-    .await?;
-
-    let has_more = (query.offset + track_details.len() as u64) < total;
+    let limit = query.page_limit();
+    let total = playlist_stats::count_playlist_tracks(&state.db, id).await?;
+    let has_more = query.offset + limit < total;
 
-    let tracks: Vec<PlaylistTrackResponse> = track_details
-        .into_iter()
-        .map(|t| PlaylistTrackResponse {
-            id: t.id,
-            position: t.position,
-            track_name: t.track_name,
-            artist_name: t.artist_name,
-            album_id: t.album_id,
-            album_name: t.album_name,
-            duration_ms: t.duration_ms,
-            ownership_status: t.ownership_status,
-            added_at: None,
-        })
-        .collect();
-
-    Ok(Json(PaginatedTracksResponse {
-        tracks,
+    let tail = format!(
+        r#"],"has_more":{},"total":{},"next_offset":{}}}"#,
         has_more,
         total,
-        next_offset: query.offset + query.limit,
-    }))
+        query.offset + limit
+    );
+    let tracks = TrackBatches::new(&state, id, query.offset, limit);
+    streamed_tracks_json(r#"{"tracks":["#.to_string(), tracks, tail).await
 }
 
 /// Toggle playlist enabled status
 pub async fn toggle_playlist_enabled(
     State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
     Path(id): Path<i32>,
 ) -> Result<Json<PlaylistResponse>> {
     let playlist = playlists::Entity::find_by_id(id)
@@ -297,28 +396,53 @@ pub async fn toggle_playlist_enabled(
         .await?
         .ok_or_else(|| AppError::NotFound("Playlist not found".to_string()))?;
 
-    let new_enabled = !playlist.is_enabled;
+    toggle_playlist(&state, &active_profile, playlist).await
+}
 
+/// Toggle playlist enabled status, addressing the playlist by its Spotify ID
+pub async fn toggle_playlist_by_spotify_id(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(spotify_id): Path<String>,
+) -> Result<Json<PlaylistResponse>> {
+    let playlist = find_by_spotify_id(&state, &spotify_id).await?;
+    toggle_playlist(&state, &active_profile, playlist).await
+}
+
+/// Enable or disable a playlist
+async fn set_enabled(
+    state: &AppState,
+    active_profile: &profiles::Model,
+    playlist: playlists::Model,
+    enabled: bool,
+) -> Result<playlists::Model> {
     let mut active: playlists::ActiveModel = playlist.into();
-    active.is_enabled = Set(new_enabled);
+    active.is_enabled = Set(enabled);
+    if enabled {
+        // A playlist syncs disabled for failing gets a fresh start
+        active.sync_failure_count = Set(0);
+        active.disabled_reason = Set(None);
+    }
     active.updated_at = Set(chrono::Utc::now().into());
     let updated = active.update(&state.db).await?;
 
-    // Use precomputed owned_count if available
-    let total_count = updated.total_tracks.unwrap_or(0) as i64;
-    let owned_count = if let Some(precomputed) = updated.owned_count {
-        precomputed as i64
-    } else {
-        playlist_stats::recalculate_playlist_owned_count(&state.db, updated.id)
-            .await
-            .unwrap_or(0) as i64
-    };
+    // Optionally drop the track memberships of a playlist that was just disabled
+    playlist_cleanup::purge_if_disabled(&state.db, Some(active_profile.id), &updated).await?;
 
-    let ownership_percentage = if total_count > 0 {
-        (owned_count as f64 / total_count as f64) * 100.0
-    } else {
-        0.0
-    };
+    Ok(updated)
+}
+
+async fn toggle_playlist(
+    state: &AppState,
+    active_profile: &profiles::Model,
+    playlist: playlists::Model,
+) -> Result<Json<PlaylistResponse>> {
+    let enabled = !playlist.is_enabled;
+    let updated = set_enabled(state, active_profile, playlist, enabled).await?;
+
+    let counts = playlist_stats::playlist_counts(&state.db, &updated)
+        .await
+        .unwrap_or_default();
 
     Ok(Json(PlaylistResponse {
         id: updated.id,
@@ -327,11 +451,226 @@ pub async fn toggle_playlist_enabled(
         owner_name: updated.owner_name,
         is_collaborative: updated.is_collaborative,
         total_tracks: updated.total_tracks.unwrap_or(0),
+        synced_tracks: counts.synced as i32,
         cover_image_url: updated.cover_image_url,
         is_enabled: updated.is_enabled,
         is_synthetic: updated.is_synthetic,
-        owned_count,
-        ownership_percentage,
+        is_external: updated.is_external,
+        disabled_reason: updated.disabled_reason,
+        owned_count: counts.owned,
+        ownership_percentage: counts.ownership_percentage(),
+        unavailable_tracks: updated.unavailable_tracks.unwrap_or(0),
+        episode_count: updated.episode_count.unwrap_or(0),
         last_synced_at: updated.last_synced_at.map(|dt| dt.to_rfc3339()),
     }))
 }
+
+/// Settings of one playlist that should survive a database wipe and resync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistSettings {
+    pub is_enabled: bool,
+}
+
+/// Playlist settings keyed by Spotify ID, as exported and imported
+pub type PlaylistSettingsExport = BTreeMap<String, PlaylistSettings>;
+
+/// Body of a settings import
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum PlaylistSettingsImport {
+    /// A settings export pasted into the settings page form
+    Pasted { settings: String },
+    Export(PlaylistSettingsExport),
+}
+
+#[derive(Serialize)]
+pub struct PlaylistSettingsImportResponse {
+    /// Playlists whose settings changed
+    pub updated: usize,
+    /// Playlists that already had the imported settings
+    pub unchanged: usize,
+    /// Spotify IDs with no matching playlist in the library, e.g. not synced yet
+    pub unknown: Vec<String>,
+}
+
+/// Settings of every playlist in the active profile's library, as a download
+pub async fn export_playlist_settings(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Response> {
+    let export: PlaylistSettingsExport = playlists::Entity::find()
+        .filter(profile::playlists_in_profile(active_profile.id))
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|p| (p.spotify_id, PlaylistSettings { is_enabled: p.is_enabled }))
+        .collect();
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"playlist-settings.json\"",
+        )],
+        Json(export),
+    )
+        .into_response())
+}
+
+/// Apply a settings export to the active profile's playlists by Spotify ID
+///
+/// Meant to run after a sync has brought the playlists back; IDs that match
+/// no playlist are reported rather than rejected. Accepts the export as a JSON
+/// body, or pasted into the settings page form, which gets a notification
+/// back instead of JSON.
+pub async fn import_playlist_settings(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    JsonOrForm(body): JsonOrForm<PlaylistSettingsImport>,
+) -> Result<Response> {
+    let (import, pasted) = match body {
+        PlaylistSettingsImport::Export(import) => (import, false),
+        PlaylistSettingsImport::Pasted { settings } => match serde_json::from_str(&settings) {
+            Ok(import) => (import, true),
+            Err(e) => {
+                let message = format!("Not a playlist settings export: {}", e);
+                return Ok(Html(notification(&message, "error").into_string()).into_response());
+            }
+        },
+    };
+
+    let mut by_spotify_id: HashMap<String, playlists::Model> = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.is_in(import.keys().cloned()))
+        .filter(profile::playlists_in_profile(active_profile.id))
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|p| (p.spotify_id.clone(), p))
+        .collect();
+
+    let mut response = PlaylistSettingsImportResponse {
+        updated: 0,
+        unchanged: 0,
+        unknown: Vec::new(),
+    };
+    for (spotify_id, settings) in import {
+        let Some(playlist) = by_spotify_id.remove(&spotify_id) else {
+            response.unknown.push(spotify_id);
+            continue;
+        };
+        if playlist.is_enabled == settings.is_enabled {
+            response.unchanged += 1;
+        } else {
+            set_enabled(&state, &active_profile, playlist, settings.is_enabled).await?;
+            response.updated += 1;
+        }
+    }
+
+    if !pasted {
+        return Ok(Json(response).into_response());
+    }
+
+    let mut message = format!(
+        "Imported playlist settings: {} updated, {} unchanged",
+        response.updated, response.unchanged
+    );
+    if !response.unknown.is_empty() {
+        message.push_str(&format!(
+            ", {} not in the library yet (run a Spotify sync, then import again)",
+            response.unknown.len()
+        ));
+    }
+    Ok(Html(notification(&message, "success").into_string()).into_response())
+}
+
+#[derive(Serialize)]
+pub struct SyncPreviewResponse {
+    pub playlist_id: i32,
+    /// Whether a sync would change anything
+    pub has_changes: bool,
+    #[serde(flatten)]
+    pub diff: PlaylistDiff,
+}
+
+/// Preview what a track sync would change, without writing anything
+///
+/// Fetches the live tracks from Spotify (saved tracks for Liked Songs) and
+/// compares them against the stored memberships.
+pub async fn sync_preview(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Path(id): Path<i32>,
+) -> Result<Json<SyncPreviewResponse>> {
+    let playlist = playlists::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Playlist not found".to_string()))?;
+
+    let access_token = active_profile
+        .spotify_access_token
+        .ok_or_else(|| AppError::Authentication("Spotify not connected".to_string()))?;
+
+    let spotify_service = SpotifyService::from_config(&state.config);
+    let live_tracks = if playlist.is_synthetic {
+        spotify_service.fetch_saved_tracks(&access_token).await?
+    } else {
+        spotify_service
+            .fetch_playlist_tracks(&access_token, &playlist.spotify_id)
+            .await?
+    };
+
+    let stored_tracks = playlist_diff::stored_playlist_tracks(&state.db, playlist.id).await?;
+    let diff = playlist_diff::diff_playlist_tracks(&stored_tracks, &live_tracks);
+
+    Ok(Json(SyncPreviewResponse {
+        playlist_id: playlist.id,
+        has_changes: !diff.is_empty(),
+        diff,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ImportSpotifyPlaylistRequest {
+    /// open.spotify.com link, `spotify:playlist:` URI or bare playlist ID
+    pub playlist: String,
+    /// Also keep a local playlist record, flagged external
+    #[serde(default)]
+    pub keep_playlist: bool,
+}
+
+/// Import a Spotify playlist's albums into the library without following it
+///
+/// Any public playlist can be read with the user's token. Its albums are added
+/// as not-owned playlist imports, exactly as a sync of a followed playlist would.
+pub async fn import_spotify_playlist(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Json(payload): Json<ImportSpotifyPlaylistRequest>,
+) -> Result<Json<PlaylistImportSummary>> {
+    let spotify_id = parse_playlist_id(&payload.playlist).ok_or_else(|| {
+        AppError::Validation(format!("Not a Spotify playlist link or ID: {}", payload.playlist))
+    })?;
+
+    let access_token = active_profile
+        .spotify_access_token
+        .ok_or_else(|| AppError::Authentication("Spotify not connected".to_string()))?;
+
+    let spotify_service = SpotifyService::from_config(&state.config);
+    let spotify_playlist = spotify_service.fetch_playlist(&access_token, spotify_id).await?;
+    let spotify_tracks = spotify_service
+        .fetch_playlist_tracks(&access_token, spotify_id)
+        .await?;
+
+    let summary = spotify_sync::import_playlist(
+        &state.db,
+        &state.events,
+        &spotify_playlist,
+        &spotify_tracks,
+        active_profile.id,
+        payload.keep_playlist,
+    )
+    .await?;
+
+    album_grid_cache::invalidate(&state.redis).await;
+
+    Ok(Json(summary))
+}
@@ -0,0 +1,168 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap, HeaderValue},
+    response::{Html, IntoResponse},
+    Form, Json,
+};
+use sea_orm::{EntityTrait, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{entities::profiles, profile},
+    error::{AppError, Result},
+    state::AppState,
+    templates::profile_selector,
+};
+
+/// Cookie holding the ID of the selected profile
+pub const PROFILE_COOKIE: &str = "beat_profile";
+
+/// The profile selected by the profile cookie, or the default profile
+pub struct ActiveProfile(pub profiles::Model);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ActiveProfile {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self> {
+        let requested = profile_id_from_cookies(&parts.headers);
+        Ok(Self(profile::resolve_profile(&state.db, requested).await?))
+    }
+}
+
+/// Read the profile ID from the request's Cookie headers
+fn profile_id_from_cookies(headers: &HeaderMap) -> Option<i32> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == PROFILE_COOKIE)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+#[derive(Serialize)]
+pub struct ProfileResponse {
+    pub id: i32,
+    pub name: String,
+    pub spotify_connected: bool,
+    pub active: bool,
+}
+
+impl ProfileResponse {
+    fn new(profile: profiles::Model, active_id: i32) -> Self {
+        Self {
+            id: profile.id,
+            active: profile.id == active_id,
+            spotify_connected: profile.spotify_access_token.is_some(),
+            name: profile.name,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateProfileRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct SelectProfileRequest {
+    pub profile_id: i32,
+}
+
+pub async fn list_profiles(
+    State(state): State<AppState>,
+    ActiveProfile(active): ActiveProfile,
+) -> Result<Json<Vec<ProfileResponse>>> {
+    let profiles = profiles::Entity::find()
+        .order_by_asc(profiles::Column::Id)
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(
+        profiles
+            .into_iter()
+            .map(|p| ProfileResponse::new(p, active.id))
+            .collect(),
+    ))
+}
+
+pub async fn create_profile(
+    State(state): State<AppState>,
+    ActiveProfile(active): ActiveProfile,
+    Json(payload): Json<CreateProfileRequest>,
+) -> Result<Json<ProfileResponse>> {
+    let created = profile::create_profile(&state.db, &payload.name).await?;
+    Ok(Json(ProfileResponse::new(created, active.id)))
+}
+
+/// Switch the active profile by setting the profile cookie
+///
+/// Accepts a form body so the header selector can post it directly; tells HTMX
+/// to reload the page so every view picks up the new library.
+pub async fn select_profile(
+    State(state): State<AppState>,
+    Form(payload): Form<SelectProfileRequest>,
+) -> Result<impl IntoResponse> {
+    let selected = profiles::Entity::find_by_id(payload.profile_id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Profile not found".to_string()))?;
+
+    let cookie = format!(
+        "{}={}; Path=/; Max-Age=31536000; SameSite=Lax",
+        PROFILE_COOKIE, selected.id
+    );
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&cookie).map_err(|e| AppError::Internal(e.to_string()))?,
+    );
+    headers.insert("HX-Refresh", HeaderValue::from_static("true"));
+
+    let active_id = selected.id;
+    Ok((headers, Json(ProfileResponse::new(selected, active_id))))
+}
+
+/// HTML partial for the profile selector in the navigation bar
+pub async fn selector(
+    State(state): State<AppState>,
+    ActiveProfile(active): ActiveProfile,
+) -> Result<Html<String>> {
+    let profiles: Vec<(i32, String)> = profiles::Entity::find()
+        .order_by_asc(profiles::Column::Id)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+
+    Ok(Html(profile_selector(&profiles, active.id).into_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_id_from_cookies() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; beat_profile=2; other=x"),
+        );
+        assert_eq!(profile_id_from_cookies(&headers), Some(2));
+    }
+
+    #[test]
+    fn test_profile_id_from_cookies_missing_or_invalid() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(profile_id_from_cookies(&headers), None);
+
+        headers.insert(header::COOKIE, HeaderValue::from_static("beat_profile=abc"));
+        assert_eq!(profile_id_from_cookies(&headers), None);
+    }
+}
@@ -0,0 +1,549 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, EntityTrait, FromQueryResult, JoinType, Order,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        entities::{albums, artists, jobs, playlist_tracks, playlists, tracks},
+        enums::{AcquisitionSource, AlbumSource, JobStatus, JobType, OwnershipStatus},
+        profile, settings as db_settings,
+    },
+    error::{AppError, Result},
+    handlers::{
+        albums::PaginationInfo,
+        artists::{artist_stats, ArtistResponse, ArtistWithStats, OWNED_COUNT_SQL},
+        export::{self, csv_field, ExportFormat, ExportRows, EXPORT_BATCH_SIZE},
+        profiles::ActiveProfile,
+    },
+    services::artist_heatmap::{self, HeatmapArtist, DEFAULT_HEATMAP_ARTISTS},
+    state::AppState,
+};
+
+#[derive(Serialize)]
+pub struct CollectionValueResponse {
+    /// Sum of the estimated values of all owned albums
+    pub total_value: f64,
+    pub owned_albums: i64,
+    /// Owned albums with an estimated value set
+    pub valued_albums: i64,
+    pub by_source: Vec<SourceValue>,
+}
+
+#[derive(Serialize)]
+pub struct SourceValue {
+    pub acquisition_source: String,
+    pub total_value: f64,
+    pub owned_albums: i64,
+    pub valued_albums: i64,
+}
+
+/// Estimated value of the owned albums in the active profile's library
+///
+/// Values are whatever the user entered per album; albums without one count
+/// towards `owned_albums` but add nothing to the totals. Albums without an
+/// acquisition source are reported under `unknown`.
+pub async fn collection_value(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Json<CollectionValueResponse>> {
+    #[derive(FromQueryResult)]
+    struct SourceTotals {
+        acquisition_source: Option<String>,
+        total_value: Option<f64>,
+        owned_albums: i64,
+        valued_albums: i64,
+    }
+
+    let rows = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::AcquisitionSource)
+        .column_as(albums::Column::EstimatedValue.sum(), "total_value")
+        .column_as(albums::Column::Id.count(), "owned_albums")
+        .column_as(albums::Column::EstimatedValue.count(), "valued_albums")
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .filter(albums::Column::OwnershipStatus.eq(OwnershipStatus::Owned.as_str()))
+        .group_by(albums::Column::AcquisitionSource)
+        .into_model::<SourceTotals>()
+        .all(&state.db)
+        .await?;
+
+    // Missing sources and explicit "unknown" end up in the same bucket
+    let mut by_source: BTreeMap<String, SourceValue> = BTreeMap::new();
+    for row in rows {
+        let source = row
+            .acquisition_source
+            .unwrap_or_else(|| AcquisitionSource::Unknown.as_str().to_string());
+        let entry = by_source.entry(source.clone()).or_insert_with(|| SourceValue {
+            acquisition_source: source,
+            total_value: 0.0,
+            owned_albums: 0,
+            valued_albums: 0,
+        });
+        entry.total_value += row.total_value.unwrap_or(0.0);
+        entry.owned_albums += row.owned_albums;
+        entry.valued_albums += row.valued_albums;
+    }
+
+    let by_source: Vec<SourceValue> = by_source.into_values().collect();
+
+    Ok(Json(CollectionValueResponse {
+        total_value: by_source.iter().map(|s| s.total_value).sum(),
+        owned_albums: by_source.iter().map(|s| s.owned_albums).sum(),
+        valued_albums: by_source.iter().map(|s| s.valued_albums).sum(),
+        by_source,
+    }))
+}
+
+#[derive(Serialize)]
+pub struct TrackOwnershipResponse {
+    /// Enabled playlists in the active profile
+    pub enabled_playlists: u64,
+    /// Every track counted once, however many playlists it is in
+    pub distinct: TrackOwnershipTotals,
+    /// Per-playlist counts added up; a track in three playlists counts three times
+    pub naive_sum: TrackOwnershipTotals,
+}
+
+#[derive(Serialize)]
+pub struct TrackOwnershipTotals {
+    pub tracks: i64,
+    pub owned_tracks: i64,
+    pub ownership_percentage: f64,
+}
+
+impl TrackOwnershipTotals {
+    fn new(tracks: i64, owned_tracks: i64) -> Self {
+        let ownership_percentage = if tracks > 0 {
+            (owned_tracks as f64 / tracks as f64) * 100.0
+        } else {
+            0.0
+        };
+        Self { tracks, owned_tracks, ownership_percentage }
+    }
+}
+
+/// Track ownership across the active profile's enabled playlists
+///
+/// Adding up per-playlist stats overstates coverage when albums are shared
+/// between playlists, so this reports the deduplicated figures next to the
+/// naive sum. Per-playlist owned counts are unaffected.
+pub async fn track_ownership(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+) -> Result<Json<TrackOwnershipResponse>> {
+    #[derive(FromQueryResult)]
+    struct OwnershipCounts {
+        entries: i64,
+        owned_entries: Option<i64>,
+        distinct_tracks: i64,
+        distinct_owned_tracks: i64,
+    }
+
+    let owned = OwnershipStatus::Owned.as_str();
+    let enabled_in_profile = || {
+        profile::playlists_in_profile(active_profile.id)
+            .and(playlists::Column::IsEnabled.eq(true))
+            .and(playlists::Column::ArchivedAt.is_null())
+    };
+
+    let enabled_playlists = playlists::Entity::find()
+        .filter(enabled_in_profile())
+        .count(&state.db)
+        .await?;
+
+    let counts = playlist_tracks::Entity::find()
+        .select_only()
+        .column_as(Expr::cust("COUNT(*)"), "entries")
+        .column_as(
+            Expr::cust_with_values(
+                r#"SUM(CASE WHEN "albums"."ownership_status" = ? THEN 1 ELSE 0 END)"#,
+                [owned],
+            ),
+            "owned_entries",
+        )
+        .column_as(
+            Expr::cust(r#"COUNT(DISTINCT "playlist_tracks"."track_id")"#),
+            "distinct_tracks",
+        )
+        .column_as(
+            Expr::cust_with_values(
+                r#"COUNT(DISTINCT CASE WHEN "albums"."ownership_status" = ? THEN "playlist_tracks"."track_id" END)"#,
+                [owned],
+            ),
+            "distinct_owned_tracks",
+        )
+        .join(JoinType::InnerJoin, playlist_tracks::Relation::Playlists.def())
+        .join(JoinType::InnerJoin, playlist_tracks::Relation::Tracks.def())
+        .join(JoinType::InnerJoin, tracks::Relation::Albums.def())
+        .filter(enabled_in_profile())
+        .filter(tracks::Column::ArchivedAt.is_null())
+        .filter(albums::Column::ArchivedAt.is_null())
+        .into_model::<OwnershipCounts>()
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::Internal("Track ownership query returned no row".to_string()))?;
+
+    Ok(Json(TrackOwnershipResponse {
+        enabled_playlists,
+        distinct: TrackOwnershipTotals::new(counts.distinct_tracks, counts.distinct_owned_tracks),
+        naive_sum: TrackOwnershipTotals::new(counts.entries, counts.owned_entries.unwrap_or(0)),
+    }))
+}
+
+/// Genres listed by default before the rest are folded into `other`
+const DEFAULT_TOP_GENRES: usize = 10;
+
+/// Most genres a single request may list individually
+const MAX_TOP_GENRES: usize = 50;
+
+/// Bucket for albums without any genre
+const UNKNOWN_GENRE: &str = "Unknown";
+
+#[derive(Deserialize)]
+pub struct GenreDistributionQuery {
+    /// How many genres to list before folding the rest into `other`
+    pub top: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct GenreDistributionResponse {
+    pub total_albums: usize,
+    /// Largest genres first
+    pub genres: Vec<GenreCount>,
+    /// Everything past the top genres; `None` when nothing was left over
+    pub other: Option<OtherGenres>,
+}
+
+#[derive(Serialize)]
+pub struct GenreCount {
+    pub genre: String,
+    pub album_count: usize,
+    pub owned_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct OtherGenres {
+    /// Genres folded into this bucket
+    pub genre_count: usize,
+    /// Albums with at least one of those genres, each counted once
+    pub album_count: usize,
+    pub owned_count: usize,
+}
+
+/// Album and owned counts per genre in the active profile's library
+///
+/// Genres are stored as a JSON array per album, so they're tallied here
+/// rather than in SQL. An album counts once towards each of its genres, so
+/// the counts can add up to more than `total_albums`. Albums without genres
+/// are counted under "Unknown". Like `/api/stats`, only saved albums count
+/// when `stats_exclude_playlist_imports` is on.
+pub async fn genre_distribution(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<GenreDistributionQuery>,
+) -> Result<Json<GenreDistributionResponse>> {
+    let top = query.top.unwrap_or(DEFAULT_TOP_GENRES).clamp(1, MAX_TOP_GENRES);
+
+    let exclude_imports = db_settings::for_profile(&state.db, &active_profile)
+        .await?
+        .and_then(|s| s.stats_exclude_playlist_imports)
+        .unwrap_or(false);
+    let mut condition = Condition::all()
+        .add(profile::albums_in_profile(active_profile.id))
+        .add(albums::Column::ArchivedAt.is_null());
+    if exclude_imports {
+        condition = condition.add(albums::Column::Source.eq(AlbumSource::SavedAlbum.as_str()));
+    }
+
+    let rows: Vec<(Option<String>, String)> = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::Genres)
+        .column(albums::Column::OwnershipStatus)
+        .filter(condition)
+        .into_tuple()
+        .all(&state.db)
+        .await?;
+
+    let albums: Vec<(BTreeSet<String>, bool)> = rows
+        .into_iter()
+        .map(|(genres, ownership_status)| {
+            (album_genres(genres.as_deref()), ownership_status == OwnershipStatus::Owned.as_str())
+        })
+        .collect();
+
+    let mut counts: HashMap<&str, GenreCount> = HashMap::new();
+    for (genres, owned) in &albums {
+        for genre in genres {
+            let entry = counts.entry(genre).or_insert_with(|| GenreCount {
+                genre: genre.clone(),
+                album_count: 0,
+                owned_count: 0,
+            });
+            entry.album_count += 1;
+            entry.owned_count += usize::from(*owned);
+        }
+    }
+
+    let mut genres: Vec<GenreCount> = counts.into_values().collect();
+    genres.sort_by(|a, b| b.album_count.cmp(&a.album_count).then_with(|| a.genre.cmp(&b.genre)));
+
+    let rest = genres.split_off(top.min(genres.len()));
+    let other = (!rest.is_empty()).then(|| {
+        let folded: BTreeSet<&str> = rest.iter().map(|g| g.genre.as_str()).collect();
+        let in_other: Vec<bool> = albums
+            .iter()
+            .filter(|(album_genres, _)| album_genres.iter().any(|g| folded.contains(g.as_str())))
+            .map(|(_, owned)| *owned)
+            .collect();
+        OtherGenres {
+            genre_count: rest.len(),
+            album_count: in_other.len(),
+            owned_count: in_other.iter().filter(|owned| **owned).count(),
+        }
+    });
+
+    Ok(Json(GenreDistributionResponse { total_albums: albums.len(), genres, other }))
+}
+
+/// Distinct genres of an album, or just "Unknown" when it has none
+fn album_genres(raw: Option<&str>) -> BTreeSet<String> {
+    let genres: BTreeSet<String> = raw
+        .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|genre| genre.trim().to_string())
+        .filter(|genre| !genre.is_empty())
+        .collect();
+
+    if genres.is_empty() {
+        BTreeSet::from([UNKNOWN_GENRE.to_string()])
+    } else {
+        genres
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ArtistHeatmapQuery {
+    /// How many artists to show, most albums first
+    pub limit: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ArtistHeatmapResponse {
+    pub artists: Vec<HeatmapArtist>,
+}
+
+/// Ownership status of each album of the artists with the most albums
+///
+/// Albums are in release order. At most `MAX_HEATMAP_CELLS` albums are
+/// listed in all; artists past that are left out, and an artist cut short
+/// reports how many albums were left out in `hidden_albums`.
+pub async fn artist_heatmap(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<ArtistHeatmapQuery>,
+) -> Result<Json<ArtistHeatmapResponse>> {
+    let limit = query.limit.unwrap_or(DEFAULT_HEATMAP_ARTISTS);
+    let artists = artist_heatmap::artist_heatmap(&state.db, active_profile.id, limit).await?;
+    Ok(Json(ArtistHeatmapResponse { artists }))
+}
+
+#[derive(Deserialize)]
+pub struct ArtistCompletionQuery {
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+    /// Leave out artists with fewer albums than this
+    #[serde(default)]
+    pub min_albums: i64,
+}
+
+#[derive(Serialize)]
+pub struct ArtistCompletionResponse {
+    /// Least complete first
+    pub artists: Vec<ArtistResponse>,
+}
+
+const ARTIST_COMPLETION_CSV_HEADER: &str = "artist_id,artist,album_count,owned_count,ownership_percentage\n";
+
+/// Rows of the artist completion CSV
+struct CompletionCsv;
+
+impl ExportRows for CompletionCsv {
+    type Row = ArtistWithStats;
+
+    fn header(&mut self) -> String {
+        ARTIST_COMPLETION_CSV_HEADER.to_string()
+    }
+
+    fn row(&mut self, row: ArtistWithStats, out: &mut String) {
+        let artist = ArtistResponse::from(row);
+        out.push_str(&format!(
+            "{},{},{},{},{:.1}\n",
+            artist.id,
+            csv_field(&artist.name),
+            artist.album_count,
+            artist.owned_count,
+            artist.ownership_percentage
+        ));
+    }
+}
+
+/// Album and owned counts per artist in the active profile's library, least complete first
+///
+/// A shareable list of what to work on next. Ties in completion are broken
+/// by name. Archived albums don't count.
+pub async fn artist_completion(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<ArtistCompletionQuery>,
+) -> Result<Response> {
+    let format_name = query.format.as_deref().unwrap_or("json");
+    let format = ExportFormat::parse(format_name, &[ExportFormat::Json, ExportFormat::Csv])?;
+
+    let select = artist_stats(active_profile.id)
+        .filter(albums::Column::ArchivedAt.is_null())
+        .having(Expr::expr(albums::Column::Id.count()).gte(query.min_albums.max(1)))
+        .order_by(
+            Expr::cust(format!("{} * 1.0 / COUNT(albums.id)", OWNED_COUNT_SQL)),
+            Order::Asc,
+        )
+        .order_by_asc(artists::Column::Name)
+        .order_by_asc(artists::Column::Id);
+
+    if format == ExportFormat::Csv {
+        let db = state.db.clone();
+        return Ok(export::download(format, "artist-completion", CompletionCsv, move |page| {
+            let (db, select) = (db.clone(), select.clone());
+            async move {
+                Ok(select
+                    .into_model::<ArtistWithStats>()
+                    .paginate(&db, EXPORT_BATCH_SIZE)
+                    .fetch_page(page)
+                    .await?)
+            }
+        }));
+    }
+
+    let artists = select.into_model::<ArtistWithStats>().all(&state.db).await?;
+    Ok(Json(ArtistCompletionResponse {
+        artists: artists.into_iter().map(ArtistResponse::from).collect(),
+    })
+    .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct SyncHistoryQuery {
+    /// Only jobs of this type (e.g. `spotify_sync`); all types when omitted
+    pub job_type: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: u64,
+    #[serde(default = "default_page_size")]
+    pub page_size: u64,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_page_size() -> u64 {
+    20
+}
+
+#[derive(Serialize)]
+pub struct SyncHistoryResponse {
+    pub jobs: Vec<SyncHistoryEntry>,
+    pub pagination: PaginationInfo,
+}
+
+#[derive(Serialize)]
+pub struct SyncHistoryEntry {
+    pub id: i32,
+    pub job_type: String,
+    pub status: String,
+    /// `None` while the job hasn't finished
+    pub succeeded: Option<bool>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    /// Wall-clock time from start to finish, for finished jobs
+    pub duration_ms: Option<i64>,
+    pub items_processed: Option<i32>,
+    /// Summary the job recorded when it finished
+    pub result: Option<serde_json::Value>,
+    pub error_message: Option<String>,
+}
+
+impl From<jobs::Model> for SyncHistoryEntry {
+    fn from(job: jobs::Model) -> Self {
+        let succeeded = match JobStatus::from_str(&job.status) {
+            Some(JobStatus::Completed) => Some(true),
+            Some(JobStatus::Failed) => Some(false),
+            _ => None,
+        };
+        let duration_ms = job
+            .started_at
+            .zip(job.completed_at)
+            .map(|(start, end)| (end - start).num_milliseconds());
+
+        Self {
+            id: job.id,
+            job_type: job.job_type,
+            status: job.status,
+            succeeded,
+            started_at: job.started_at.map(|dt| dt.to_rfc3339()),
+            completed_at: job.completed_at.map(|dt| dt.to_rfc3339()),
+            duration_ms,
+            items_processed: job.processed_items,
+            result: job.result.and_then(|r| serde_json::from_str(&r).ok()),
+            error_message: job.error_message,
+        }
+    }
+}
+
+/// Recent background jobs with their duration and outcome, newest first
+///
+/// Meant for spotting trends such as syncs getting slower over time. Jobs are
+/// global, not per profile.
+pub async fn sync_history(
+    State(state): State<AppState>,
+    Query(query): Query<SyncHistoryQuery>,
+) -> Result<Json<SyncHistoryResponse>> {
+    let page = query.page.max(1);
+    let page_size = query.page_size.clamp(1, 100);
+
+    let mut select = jobs::Entity::find();
+    if let Some(job_type) = &query.job_type {
+        let job_type = JobType::from_str(job_type)
+            .ok_or_else(|| AppError::Validation(format!("Invalid job type: {}", job_type)))?;
+        select = select.filter(jobs::Column::JobType.eq(job_type.as_str()));
+    }
+
+    let total_items = select.clone().count(&state.db).await?;
+
+    let jobs = select
+        .order_by_desc(jobs::Column::CreatedAt)
+        .order_by_desc(jobs::Column::Id)
+        .offset((page - 1) * page_size)
+        .limit(page_size)
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(SyncHistoryResponse {
+        jobs: jobs.into_iter().map(SyncHistoryEntry::from).collect(),
+        pagination: PaginationInfo {
+            page,
+            page_size,
+            total_items,
+            total_pages: total_items.div_ceil(page_size),
+        },
+    }))
+}
@@ -0,0 +1,152 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use sea_orm::{
+    sea_query::{Expr, Func, LikeExpr},
+    ColumnTrait, EntityTrait, FromQueryResult, JoinType, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, RelationTrait,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        entities::{albums, artists, tracks},
+        profile,
+    },
+    error::{AppError, Result},
+    handlers::{albums::PaginationInfo, profiles::ActiveProfile},
+    state::AppState,
+};
+
+pub const DEFAULT_TRACK_SEARCH_PAGE_SIZE: u64 = 25;
+pub const MAX_TRACK_SEARCH_PAGE_SIZE: u64 = 100;
+
+#[derive(Deserialize)]
+pub struct TrackSearchQuery {
+    /// Matched anywhere in the track title, ignoring case
+    #[serde(default)]
+    pub q: String,
+    #[serde(default = "default_page")]
+    pub page: u64,
+    #[serde(default = "default_page_size")]
+    pub page_size: u64,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_page_size() -> u64 {
+    DEFAULT_TRACK_SEARCH_PAGE_SIZE
+}
+
+#[derive(Serialize)]
+pub struct TrackSearchResponse {
+    pub tracks: Vec<TrackHit>,
+    pub pagination: PaginationInfo,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct TrackHit {
+    pub track_id: i32,
+    pub title: String,
+    pub track_number: Option<i32>,
+    pub disc_number: Option<i32>,
+    pub duration_ms: Option<i32>,
+    pub album_id: i32,
+    pub album_title: String,
+    pub artist_id: i32,
+    pub artist_name: String,
+    /// Ownership status of the containing album
+    pub ownership_status: String,
+}
+
+/// `LIKE` pattern matching `search` anywhere, with `\` as the escape character
+pub(crate) fn contains_pattern(search: &str) -> String {
+    let mut pattern = String::with_capacity(search.len() + 2);
+    pattern.push('%');
+    for c in search.to_lowercase().chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Search track titles across the active profile's library
+///
+/// Each hit carries its album and artist so the caller can open the album
+/// without a second lookup. Wildcards in `q` are matched literally.
+pub async fn search_tracks(
+    State(state): State<AppState>,
+    ActiveProfile(active_profile): ActiveProfile,
+    Query(query): Query<TrackSearchQuery>,
+) -> Result<Json<TrackSearchResponse>> {
+    let search = query.q.trim();
+    if search.is_empty() {
+        return Err(AppError::Validation("q must not be empty".to_string()));
+    }
+
+    let page = query.page.max(1);
+    let page_size = query.page_size.clamp(1, MAX_TRACK_SEARCH_PAGE_SIZE);
+
+    let select = tracks::Entity::find()
+        .select_only()
+        .column_as(tracks::Column::Id, "track_id")
+        .column(tracks::Column::Title)
+        .column(tracks::Column::TrackNumber)
+        .column(tracks::Column::DiscNumber)
+        .column(tracks::Column::DurationMs)
+        .column(tracks::Column::AlbumId)
+        .column_as(albums::Column::Title, "album_title")
+        .column_as(artists::Column::Id, "artist_id")
+        .column_as(artists::Column::Name, "artist_name")
+        .column(albums::Column::OwnershipStatus)
+        .join(JoinType::InnerJoin, tracks::Relation::Albums.def())
+        .join(JoinType::InnerJoin, albums::Relation::Artists.def())
+        .filter(profile::albums_in_profile(active_profile.id))
+        .filter(tracks::Column::ArchivedAt.is_null())
+        .filter(albums::Column::ArchivedAt.is_null())
+        .filter(
+            Expr::expr(Func::lower(Expr::col((tracks::Entity, tracks::Column::Title))))
+                .like(LikeExpr::new(contains_pattern(search)).escape('\\')),
+        );
+
+    let total_items = select.clone().count(&state.db).await?;
+    let total_pages = total_items.div_ceil(page_size);
+
+    let hits = select
+        .order_by_asc(tracks::Column::Title)
+        .order_by_asc(albums::Column::Title)
+        .order_by_asc(tracks::Column::Id)
+        .offset((page - 1) * page_size)
+        .limit(page_size)
+        .into_model::<TrackHit>()
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(TrackSearchResponse {
+        tracks: hits,
+        pagination: PaginationInfo {
+            page,
+            page_size,
+            total_items,
+            total_pages,
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_pattern_escapes_wildcards() {
+        assert_eq!(contains_pattern("100% _Pure_"), "%100\\% \\_pure\\_%");
+        assert_eq!(contains_pattern("a\\b"), "%a\\\\b%");
+        assert_eq!(contains_pattern("Café"), "%café%");
+    }
+}
@@ -1,32 +1,128 @@
 use axum::{extract::State, Json};
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, Set};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::{
-    db::entities::user_settings,
+    db::{entities::user_settings, enums::AlbumView, settings as db_settings},
     error::{AppError, Result},
-    services::LidarrService,
+    handlers::profiles::ActiveProfile,
+    services::{
+        album_grid_cache,
+        audio_files::DEFAULT_DURATION_TOLERANCE_SECS,
+        archive, lidarr,
+        music_paths::{self, PathMappings},
+        playlist_cleanup::{self, DEFAULT_PLAYLIST_FAILURE_THRESHOLD},
+        wanted, LidarrService,
+    },
+    i18n::Locale,
     state::AppState,
+    templates::resolve_timezone,
 };
 
+/// Albums with this many tracks or fewer are singles unless configured otherwise
+pub const DEFAULT_SINGLE_TRACK_THRESHOLD: i32 = 1;
+
+/// Automatic retries of a failed download unless configured otherwise
+pub const DEFAULT_MAX_DOWNLOAD_RETRIES: i32 = 3;
+
+/// Upper bound for `max_download_retries`
+const MAX_DOWNLOAD_RETRIES_LIMIT: i32 = 10;
+
 #[derive(Serialize)]
 pub struct SettingsResponse {
     pub id: i32,
+    /// With any embedded password masked as `user:****@`
     pub lidarr_url: Option<String>,
+    /// Names of the extra headers sent to Lidarr; values may hold credentials
+    pub lidarr_extra_headers: Vec<String>,
     pub music_folder_path: Option<String>,
     pub auto_sync_enabled: Option<bool>,
     pub sync_interval_hours: Option<i32>,
+    pub album_view: String,
+    pub match_by_barcode: bool,
+    pub purge_disabled_playlist_tracks: bool,
+    pub sync_collaborative_playlists: bool,
+    pub exclude_singles: bool,
+    pub single_track_threshold: i32,
+    pub timezone: String,
+    /// UI language, e.g. `en` or `de`
+    pub locale: String,
+    /// ListenBrainz user whose listens feed album listening history
+    pub listenbrainz_username: Option<String>,
+    pub auto_retry_failed_downloads: bool,
+    pub max_download_retries: i32,
+    /// Library statistics only count saved albums
+    pub stats_exclude_playlist_imports: bool,
+    pub playlist_failure_threshold: i32,
+    /// Prefix rewrites applied to album paths from Lidarr webhooks
+    pub path_mappings: PathMappings,
+    /// Library folders Lidarr reported outside the music folder at the last check
+    pub path_mismatch_prefixes: Vec<String>,
+    /// Queue a cover art fetch after each Spotify sync
+    pub auto_fetch_cover_art: bool,
+    /// Seconds a local file may differ from Spotify's track length
+    pub duration_tolerance_secs: i32,
+    /// Lower the priority of albums wanted for longer than `wanted_demotion_days`
+    pub wanted_demotion_enabled: bool,
+    pub wanted_demotion_days: i32,
+    /// Days archived rows are kept before they are purged; `None` keeps them
+    pub archive_retention_days: Option<i32>,
+    /// Profile owning these settings; `None` for the shared settings
+    pub profile_id: Option<i32>,
     pub spotify_connected: bool,
 }
 
 #[derive(Deserialize)]
 pub struct UpdateSettingsRequest {
+    /// A masked password, as settings show it, keeps the stored one
     pub lidarr_url: Option<String>,
     pub lidarr_api_key: Option<String>,
+    /// Extra headers for every Lidarr request; an empty object removes them
+    pub lidarr_extra_headers: Option<BTreeMap<String, String>>,
     pub music_folder_path: Option<String>,
     pub auto_sync_enabled: Option<bool>,
     pub sync_interval_hours: Option<i32>,
+    pub album_view: Option<String>,
+    pub match_by_barcode: Option<bool>,
+    pub purge_disabled_playlist_tracks: Option<bool>,
+    pub sync_collaborative_playlists: Option<bool>,
+    /// With `sync_collaborative_playlists: false`, also delete collaborative
+    /// playlists that were already synced
+    #[serde(default)]
+    pub remove_collaborative_playlists: bool,
+    pub exclude_singles: Option<bool>,
+    /// Albums with at most this many tracks count as singles
+    pub single_track_threshold: Option<i32>,
+    /// IANA zone name used to display timestamps, e.g. `Europe/Berlin`
+    pub timezone: Option<String>,
+    /// UI language: `en` or `de`
+    pub locale: Option<String>,
+    /// ListenBrainz user to follow; an empty string turns listening history off
+    pub listenbrainz_username: Option<String>,
+    /// Search again for recently failed Lidarr downloads
+    pub auto_retry_failed_downloads: Option<bool>,
+    /// Automatic retries per download, from 1 to 10
+    pub max_download_retries: Option<i32>,
+    /// Leave albums imported from playlists out of the library statistics
+    pub stats_exclude_playlist_imports: Option<bool>,
+    /// Failed syncs in a row before a playlist is disabled, at least 1
+    pub playlist_failure_threshold: Option<i32>,
+    /// Rewrite Lidarr's path prefixes, e.g. `{"/data/music": "/music"}`; an
+    /// empty object removes them
+    pub path_mappings: Option<PathMappings>,
+    /// Download missing covers after every Spotify sync
+    pub auto_fetch_cover_art: Option<bool>,
+    /// Seconds a local file may differ from Spotify's track length before the
+    /// filesystem scan flags it, at least 0
+    pub duration_tolerance_secs: Option<i32>,
+    /// Lower stale wants one priority at a time, archiving those at the lowest
+    pub wanted_demotion_enabled: Option<bool>,
+    /// Days an album is wanted before it is demoted, at least 1
+    pub wanted_demotion_days: Option<i32>,
+    /// Days to keep archived rows, up to 3650; 0 keeps them for good
+    pub archive_retention_days: Option<i32>,
 }
 
 #[derive(Serialize)]
@@ -35,33 +131,168 @@ pub struct TestConnectionResponse {
     pub message: String,
 }
 
-pub async fn get_settings(State(state): State<AppState>) -> Result<Json<SettingsResponse>> {
-    let settings = user_settings::Entity::find()
-        .one(&state.db)
+pub async fn get_settings(
+    State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
+) -> Result<Json<SettingsResponse>> {
+    let settings = db_settings::for_profile(&state.db, &profile)
         .await?
         .ok_or_else(|| AppError::NotFound("Settings not found".to_string()))?;
 
     Ok(Json(SettingsResponse {
         id: settings.id,
-        lidarr_url: settings.lidarr_url,
+        lidarr_extra_headers: extra_header_names(settings.lidarr_extra_headers.as_deref()),
+        lidarr_url: settings.lidarr_url.as_deref().map(lidarr::mask_url_password),
         music_folder_path: settings.music_folder_path,
         auto_sync_enabled: settings.auto_sync_enabled,
         sync_interval_hours: settings.sync_interval_hours,
-        spotify_connected: settings.spotify_access_token.is_some(),
+        album_view: settings.album_view,
+        match_by_barcode: settings.match_by_barcode.unwrap_or(true),
+        purge_disabled_playlist_tracks: settings.purge_disabled_playlist_tracks.unwrap_or(false),
+        sync_collaborative_playlists: settings.sync_collaborative_playlists.unwrap_or(true),
+        exclude_singles: settings.exclude_singles.unwrap_or(false),
+        single_track_threshold: settings.single_track_threshold.unwrap_or(DEFAULT_SINGLE_TRACK_THRESHOLD),
+        timezone: resolve_timezone(settings.timezone.as_deref()).name().to_string(),
+        locale: Locale::resolve(settings.locale.as_deref()).as_str().to_string(),
+        listenbrainz_username: settings.listenbrainz_username,
+        auto_retry_failed_downloads: settings.auto_retry_failed_downloads.unwrap_or(false),
+        max_download_retries: settings.max_download_retries.unwrap_or(DEFAULT_MAX_DOWNLOAD_RETRIES),
+        stats_exclude_playlist_imports: settings.stats_exclude_playlist_imports.unwrap_or(false),
+        playlist_failure_threshold: settings
+            .playlist_failure_threshold
+            .unwrap_or(DEFAULT_PLAYLIST_FAILURE_THRESHOLD),
+        path_mappings: music_paths::parse_mappings(settings.path_mappings.as_deref()),
+        path_mismatch_prefixes: music_paths::parse_mismatches(settings.path_mismatch_prefixes.as_deref()),
+        auto_fetch_cover_art: settings.auto_fetch_cover_art.unwrap_or(false),
+        duration_tolerance_secs: settings
+            .duration_tolerance_secs
+            .unwrap_or(DEFAULT_DURATION_TOLERANCE_SECS),
+        wanted_demotion_enabled: settings.wanted_demotion_enabled.unwrap_or(false),
+        wanted_demotion_days: settings.wanted_demotion_days.unwrap_or(wanted::DEFAULT_DEMOTION_DAYS),
+        archive_retention_days: settings.archive_retention_days,
+        profile_id: settings.profile_id,
+        spotify_connected: profile.spotify_access_token.is_some(),
     }))
 }
 
 pub async fn update_settings(
     State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
     Json(payload): Json<UpdateSettingsRequest>,
 ) -> Result<Json<SettingsResponse>> {
+    let album_view = payload
+        .album_view
+        .as_deref()
+        .map(|v| {
+            AlbumView::from_str(v)
+                .ok_or_else(|| AppError::Validation(format!("Invalid album view: {}", v)))
+        })
+        .transpose()?;
+
+    if let Some(threshold) = payload.single_track_threshold {
+        if threshold < 1 {
+            return Err(AppError::Validation(format!(
+                "Single track threshold must be at least 1, got {}",
+                threshold
+            )));
+        }
+    }
+
+    if let Some(threshold) = payload.playlist_failure_threshold {
+        if threshold < 1 {
+            return Err(AppError::Validation(format!(
+                "Playlist failure threshold must be at least 1, got {}",
+                threshold
+            )));
+        }
+    }
+
+    if let Some(tolerance) = payload.duration_tolerance_secs {
+        if tolerance < 0 {
+            return Err(AppError::Validation(format!(
+                "Duration tolerance must be at least 0 seconds, got {}",
+                tolerance
+            )));
+        }
+    }
+
+    if let Some(days) = payload.wanted_demotion_days {
+        if days < 1 {
+            return Err(AppError::Validation(format!(
+                "Wanted demotion threshold must be at least 1 day, got {}",
+                days
+            )));
+        }
+    }
+
+    if let Some(days) = payload.archive_retention_days {
+        if !(0..=archive::MAX_ARCHIVE_RETENTION_DAYS).contains(&days) {
+            return Err(AppError::Validation(format!(
+                "Archive retention must be between 0 and {} days, got {}",
+                archive::MAX_ARCHIVE_RETENTION_DAYS,
+                days
+            )));
+        }
+    }
+    let archive_retention_days = payload.archive_retention_days.map(|days| Some(days).filter(|d| *d > 0));
+
+    if let Some(name) = payload.timezone.as_deref() {
+        if name.parse::<chrono_tz::Tz>().is_err() {
+            return Err(AppError::Validation(format!("Unknown time zone: {}", name)));
+        }
+    }
+
+    let locale = payload
+        .locale
+        .as_deref()
+        .map(|l| Locale::parse(l).ok_or_else(|| AppError::Validation(format!("Unknown locale: {}", l))))
+        .transpose()?;
+
+    if let Some(retries) = payload.max_download_retries {
+        if !(1..=MAX_DOWNLOAD_RETRIES_LIMIT).contains(&retries) {
+            return Err(AppError::Validation(format!(
+                "Max download retries must be between 1 and {}, got {}",
+                MAX_DOWNLOAD_RETRIES_LIMIT, retries
+            )));
+        }
+    }
+
+    let lidarr_extra_headers = payload
+        .lidarr_extra_headers
+        .map(|headers| -> Result<Option<String>> {
+            lidarr::extra_header_map(&headers)?;
+            Ok(Some(serde_json::to_string(&headers)?).filter(|_| !headers.is_empty()))
+        })
+        .transpose()?;
+
+    let path_mappings = payload
+        .path_mappings
+        .map(|mappings| -> Result<Option<String>> {
+            music_paths::validate_mappings(&mappings)?;
+            Ok(Some(serde_json::to_string(&mappings)?).filter(|_| !mappings.is_empty()))
+        })
+        .transpose()?;
+
+    let listenbrainz_username = payload
+        .listenbrainz_username
+        .map(|u| Some(u.trim().to_string()).filter(|u| !u.is_empty()));
+
+    let lidarr_url = match payload.lidarr_url {
+        Some(url) => {
+            let current = db_settings::for_profile(&state.db, &profile).await?;
+            let stored = current.as_ref().and_then(|s| s.lidarr_url.as_deref());
+            Some(lidarr::unmask_url_password(&url, stored))
+        }
+        None => None,
+    };
+
     // Get existing settings or create new
-    let existing = user_settings::Entity::find().one(&state.db).await?;
+    let existing = db_settings::for_update(&state.db, &profile).await?;
 
     let settings = if let Some(existing_settings) = existing {
         let mut active: user_settings::ActiveModel = existing_settings.into();
 
-        if let Some(url) = payload.lidarr_url {
+        if let Some(url) = lidarr_url {
             active.lidarr_url = Set(Some(url));
         }
 
@@ -69,6 +300,10 @@ pub async fn update_settings(
             active.lidarr_api_key = Set(Some(key));
         }
 
+        if let Some(headers) = lidarr_extra_headers {
+            active.lidarr_extra_headers = Set(headers);
+        }
+
         if let Some(path) = payload.music_folder_path {
             active.music_folder_path = Set(Some(path));
         }
@@ -81,49 +316,237 @@ pub async fn update_settings(
             active.sync_interval_hours = Set(Some(interval));
         }
 
+        if let Some(view) = album_view {
+            active.album_view = Set(view.as_str().to_string());
+        }
+
+        if let Some(enabled) = payload.match_by_barcode {
+            active.match_by_barcode = Set(Some(enabled));
+        }
+
+        if let Some(enabled) = payload.purge_disabled_playlist_tracks {
+            active.purge_disabled_playlist_tracks = Set(Some(enabled));
+        }
+
+        if let Some(enabled) = payload.sync_collaborative_playlists {
+            active.sync_collaborative_playlists = Set(Some(enabled));
+        }
+
+        if let Some(enabled) = payload.exclude_singles {
+            active.exclude_singles = Set(Some(enabled));
+        }
+
+        if let Some(threshold) = payload.single_track_threshold {
+            active.single_track_threshold = Set(Some(threshold));
+        }
+
+        if let Some(timezone) = payload.timezone {
+            active.timezone = Set(Some(timezone));
+        }
+
+        if let Some(locale) = locale {
+            active.locale = Set(Some(locale.into()));
+        }
+
+        // Another user's history starts from scratch
+        if let Some(username) = listenbrainz_username {
+            if username != *active.listenbrainz_username.as_ref() {
+                active.listens_synced_until = Set(None);
+            }
+            active.listenbrainz_username = Set(username);
+        }
+
+        if let Some(enabled) = payload.auto_retry_failed_downloads {
+            active.auto_retry_failed_downloads = Set(Some(enabled));
+        }
+
+        if let Some(retries) = payload.max_download_retries {
+            active.max_download_retries = Set(Some(retries));
+        }
+
+        if let Some(enabled) = payload.stats_exclude_playlist_imports {
+            active.stats_exclude_playlist_imports = Set(Some(enabled));
+        }
+
+        if let Some(threshold) = payload.playlist_failure_threshold {
+            active.playlist_failure_threshold = Set(Some(threshold));
+        }
+
+        if let Some(mappings) = path_mappings {
+            active.path_mappings = Set(mappings);
+        }
+
+        if let Some(enabled) = payload.auto_fetch_cover_art {
+            active.auto_fetch_cover_art = Set(Some(enabled));
+        }
+
+        if let Some(tolerance) = payload.duration_tolerance_secs {
+            active.duration_tolerance_secs = Set(Some(tolerance));
+        }
+
+        if let Some(enabled) = payload.wanted_demotion_enabled {
+            active.wanted_demotion_enabled = Set(Some(enabled));
+        }
+
+        if let Some(days) = payload.wanted_demotion_days {
+            active.wanted_demotion_days = Set(Some(days));
+        }
+
+        if let Some(days) = archive_retention_days {
+            active.archive_retention_days = Set(days);
+        }
+
         active.updated_at = Set(Utc::now().into());
         active.update(&state.db).await?
     } else {
         let new_settings = user_settings::ActiveModel {
-            lidarr_url: Set(payload.lidarr_url),
+            lidarr_url: Set(lidarr_url),
             lidarr_api_key: Set(payload.lidarr_api_key),
+            lidarr_extra_headers: Set(lidarr_extra_headers.flatten()),
             music_folder_path: Set(payload.music_folder_path),
             auto_sync_enabled: Set(payload.auto_sync_enabled),
             sync_interval_hours: Set(payload.sync_interval_hours),
+            album_view: Set(album_view.unwrap_or_default().as_str().to_string()),
+            match_by_barcode: Set(Some(payload.match_by_barcode.unwrap_or(true))),
+            purge_disabled_playlist_tracks: Set(Some(
+                payload.purge_disabled_playlist_tracks.unwrap_or(false),
+            )),
+            sync_collaborative_playlists: Set(Some(
+                payload.sync_collaborative_playlists.unwrap_or(true),
+            )),
+            exclude_singles: Set(Some(payload.exclude_singles.unwrap_or(false))),
+            single_track_threshold: Set(Some(
+                payload.single_track_threshold.unwrap_or(DEFAULT_SINGLE_TRACK_THRESHOLD),
+            )),
+            timezone: Set(payload.timezone),
+            locale: Set(locale.map(String::from)),
+            listenbrainz_username: Set(listenbrainz_username.flatten()),
+            auto_retry_failed_downloads: Set(Some(payload.auto_retry_failed_downloads.unwrap_or(false))),
+            max_download_retries: Set(Some(
+                payload.max_download_retries.unwrap_or(DEFAULT_MAX_DOWNLOAD_RETRIES),
+            )),
+            stats_exclude_playlist_imports: Set(Some(
+                payload.stats_exclude_playlist_imports.unwrap_or(false),
+            )),
+            playlist_failure_threshold: Set(Some(
+                payload.playlist_failure_threshold.unwrap_or(DEFAULT_PLAYLIST_FAILURE_THRESHOLD),
+            )),
+            path_mappings: Set(path_mappings.flatten()),
+            auto_fetch_cover_art: Set(Some(payload.auto_fetch_cover_art.unwrap_or(false))),
+            duration_tolerance_secs: Set(Some(
+                payload.duration_tolerance_secs.unwrap_or(DEFAULT_DURATION_TOLERANCE_SECS),
+            )),
+            wanted_demotion_enabled: Set(Some(payload.wanted_demotion_enabled.unwrap_or(false))),
+            wanted_demotion_days: Set(Some(
+                payload.wanted_demotion_days.unwrap_or(wanted::DEFAULT_DEMOTION_DAYS),
+            )),
+            archive_retention_days: Set(archive_retention_days.flatten()),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
             ..Default::default()
         };
-        new_settings.insert(&state.db).await?
+        // Another first-time save may be creating the same row right now
+        db_settings::save_for_profile(&state.db, &profile, new_settings).await?
     };
 
+    // Turning purging on cleans up playlists that were disabled earlier
+    if payload.purge_disabled_playlist_tracks == Some(true) {
+        playlist_cleanup::purge_all_disabled(&state.db, profile.id).await?;
+    }
+
+    if payload.sync_collaborative_playlists == Some(false) && payload.remove_collaborative_playlists {
+        playlist_cleanup::remove_collaborative_playlists(&state.db, profile.id).await?;
+    }
+
+    // The album grid depends on the view and singles settings
+    album_grid_cache::invalidate(&state.redis).await;
+
     Ok(Json(SettingsResponse {
         id: settings.id,
-        lidarr_url: settings.lidarr_url,
+        lidarr_extra_headers: extra_header_names(settings.lidarr_extra_headers.as_deref()),
+        lidarr_url: settings.lidarr_url.as_deref().map(lidarr::mask_url_password),
         music_folder_path: settings.music_folder_path,
         auto_sync_enabled: settings.auto_sync_enabled,
         sync_interval_hours: settings.sync_interval_hours,
-        spotify_connected: settings.spotify_access_token.is_some(),
+        album_view: settings.album_view,
+        match_by_barcode: settings.match_by_barcode.unwrap_or(true),
+        purge_disabled_playlist_tracks: settings.purge_disabled_playlist_tracks.unwrap_or(false),
+        sync_collaborative_playlists: settings.sync_collaborative_playlists.unwrap_or(true),
+        exclude_singles: settings.exclude_singles.unwrap_or(false),
+        single_track_threshold: settings.single_track_threshold.unwrap_or(DEFAULT_SINGLE_TRACK_THRESHOLD),
+        timezone: resolve_timezone(settings.timezone.as_deref()).name().to_string(),
+        locale: Locale::resolve(settings.locale.as_deref()).as_str().to_string(),
+        listenbrainz_username: settings.listenbrainz_username,
+        auto_retry_failed_downloads: settings.auto_retry_failed_downloads.unwrap_or(false),
+        max_download_retries: settings.max_download_retries.unwrap_or(DEFAULT_MAX_DOWNLOAD_RETRIES),
+        stats_exclude_playlist_imports: settings.stats_exclude_playlist_imports.unwrap_or(false),
+        playlist_failure_threshold: settings
+            .playlist_failure_threshold
+            .unwrap_or(DEFAULT_PLAYLIST_FAILURE_THRESHOLD),
+        path_mappings: music_paths::parse_mappings(settings.path_mappings.as_deref()),
+        path_mismatch_prefixes: music_paths::parse_mismatches(settings.path_mismatch_prefixes.as_deref()),
+        auto_fetch_cover_art: settings.auto_fetch_cover_art.unwrap_or(false),
+        duration_tolerance_secs: settings
+            .duration_tolerance_secs
+            .unwrap_or(DEFAULT_DURATION_TOLERANCE_SECS),
+        wanted_demotion_enabled: settings.wanted_demotion_enabled.unwrap_or(false),
+        wanted_demotion_days: settings.wanted_demotion_days.unwrap_or(wanted::DEFAULT_DEMOTION_DAYS),
+        archive_retention_days: settings.archive_retention_days,
+        profile_id: settings.profile_id,
+        spotify_connected: profile.spotify_access_token.is_some(),
     }))
 }
 
-pub async fn test_lidarr_connection(
-    State(state): State<AppState>,
-) -> Result<Json<TestConnectionResponse>> {
-    let settings = user_settings::Entity::find()
-        .one(&state.db)
-        .await?
-        .ok_or_else(|| AppError::Configuration("Settings not configured".to_string()))?;
+/// Resolve the Lidarr URL and API key
+///
+/// Values saved in the settings of `profile_id` (the default profile if
+/// `None`) win; `LIDARR_URL` / `LIDARR_API_KEY` from the app config are used
+/// as a fallback.
+pub async fn lidarr_connection(state: &AppState, profile_id: Option<i32>) -> Result<(String, String)> {
+    let settings = db_settings::for_profile_id(&state.db, profile_id).await?;
+    let (settings_url, settings_api_key) = settings
+        .map(|s| (s.lidarr_url, s.lidarr_api_key))
+        .unwrap_or_default();
 
-    let lidarr_url = settings
-        .lidarr_url
+    let lidarr_url = settings_url
+        .or_else(|| state.config.lidarr_url.clone())
         .ok_or_else(|| AppError::Configuration("Lidarr URL not configured".to_string()))?;
 
-    let lidarr_api_key = settings
-        .lidarr_api_key
+    let lidarr_api_key = settings_api_key
+        .or_else(|| state.config.lidarr_api_key.clone())
         .ok_or_else(|| AppError::Configuration("Lidarr API key not configured".to_string()))?;
 
-    let lidarr_service = LidarrService::new();
+    Ok((lidarr_url, lidarr_api_key))
+}
+
+/// Lidarr client sending the extra headers saved in `profile_id`'s settings
+pub async fn lidarr_service(state: &AppState, profile_id: Option<i32>) -> Result<LidarrService> {
+    let headers = db_settings::for_profile_id(&state.db, profile_id)
+        .await?
+        .and_then(|s| s.lidarr_extra_headers)
+        .map(|json| lidarr::parse_extra_headers(&json))
+        .transpose()?;
+
+    Ok(match headers {
+        Some(headers) => LidarrService::new().with_extra_headers(headers),
+        None => LidarrService::new(),
+    })
+}
+
+/// Header names from the stored extra headers JSON
+fn extra_header_names(json: Option<&str>) -> Vec<String> {
+    json.and_then(|json| serde_json::from_str::<BTreeMap<String, String>>(json).ok())
+        .map(|headers| headers.into_keys().collect())
+        .unwrap_or_default()
+}
+
+pub async fn test_lidarr_connection(
+    State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
+) -> Result<Json<TestConnectionResponse>> {
+    let (lidarr_url, lidarr_api_key) = lidarr_connection(&state, Some(profile.id)).await?;
+
+    let lidarr_service = lidarr_service(&state, Some(profile.id)).await?;
 
     match lidarr_service
         .test_connection(&lidarr_url, &lidarr_api_key)
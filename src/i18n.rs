@@ -0,0 +1,256 @@
+//! UI translations
+//!
+//! Message catalogs are flat JSON objects in `locales/`, compiled into the
+//! binary. English is the complete baseline; other locales may be partial and
+//! fall back to English key by key. Messages take named `{placeholders}`.
+//!
+//! The locale is chosen per request from the `locale` setting and held in a
+//! task-local, so templates translate with [`t!`](crate::t) without threading
+//! it through every function. Outside a request (jobs, tests) it's English.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{db::settings as db_settings, handlers::profiles::ActiveProfile, state::AppState};
+
+/// Language the UI is rendered in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "en" => Some(Self::En),
+            "de" => Some(Self::De),
+            _ => None,
+        }
+    }
+
+    /// The saved locale, or English when unset or unknown
+    pub fn resolve(name: Option<&str>) -> Self {
+        name.and_then(Self::parse).unwrap_or_default()
+    }
+
+    /// Name of the language in itself, for the language picker
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Self::En => "English",
+            Self::De => "Deutsch",
+        }
+    }
+
+    /// chrono format of a date and time with its zone
+    pub fn datetime_format(&self) -> &'static str {
+        match self {
+            Self::En => "%Y-%m-%d %H:%M %Z",
+            Self::De => "%d.%m.%Y %H:%M %Z",
+        }
+    }
+
+    /// Separator between groups of three digits
+    fn thousands_separator(&self) -> char {
+        match self {
+            Self::En => ',',
+            Self::De => '.',
+        }
+    }
+
+    fn catalog_source(&self) -> &'static str {
+        match self {
+            Self::En => include_str!("../locales/en.json"),
+            Self::De => include_str!("../locales/de.json"),
+        }
+    }
+}
+
+impl From<Locale> for String {
+    fn from(locale: Locale) -> Self {
+        locale.as_str().to_string()
+    }
+}
+
+static CATALOGS: LazyLock<HashMap<Locale, HashMap<String, String>>> = LazyLock::new(|| {
+    Locale::ALL
+        .into_iter()
+        .map(|locale| {
+            let catalog = serde_json::from_str(locale.catalog_source())
+                .unwrap_or_else(|e| panic!("Invalid message catalog for '{}': {}", locale.as_str(), e));
+            (locale, catalog)
+        })
+        .collect()
+});
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+/// Parse the message catalogs, so a broken one fails at startup
+pub fn load() {
+    for locale in Locale::ALL {
+        tracing::debug!("Loaded {} messages for locale '{}'", catalog(locale).len(), locale.as_str());
+    }
+}
+
+fn catalog(locale: Locale) -> &'static HashMap<String, String> {
+    &CATALOGS[&locale]
+}
+
+/// Locale of the request being handled; English outside one
+pub fn current() -> Locale {
+    LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// Run `f` with `locale` as the current locale
+pub fn with_locale<R>(locale: Locale, f: impl FnOnce() -> R) -> R {
+    LOCALE.sync_scope(locale, f)
+}
+
+/// Middleware rendering the rest of the request in the active profile's locale
+pub async fn scope_locale(
+    State(state): State<AppState>,
+    ActiveProfile(profile): ActiveProfile,
+    request: Request,
+    next: Next,
+) -> Response {
+    let locale = match db_settings::for_profile(&state.db, &profile).await {
+        Ok(settings) => Locale::resolve(settings.and_then(|s| s.locale).as_deref()),
+        Err(e) => {
+            tracing::warn!("Failed to read the locale setting: {}", e);
+            Locale::default()
+        }
+    };
+    LOCALE.scope(locale, next.run(request)).await
+}
+
+/// Message `key` in the current locale with its placeholders filled in
+///
+/// A key missing from the English baseline is a bug: debug builds panic, so
+/// the template snapshot tests catch it, and release builds show the key.
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    let message = catalog(current())
+        .get(key)
+        .or_else(|| catalog(Locale::En).get(key));
+    let Some(message) = message else {
+        if cfg!(debug_assertions) {
+            panic!("Missing message '{}' in the English catalog", key);
+        }
+        tracing::warn!("Missing message '{}' in the English catalog", key);
+        return key.to_string();
+    };
+
+    args.iter().fold(message.clone(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Integer with digit groups separated the current locale's way, e.g. "1,234"
+pub fn format_number(n: i64) -> String {
+    let separator = current().thousands_separator();
+    let digits = n.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    if n < 0 {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
+/// Translate a message: `t!("album.tracks")`, `t!("album.genre_remove", genre = name)`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_agree_with_english() {
+        let english = catalog(Locale::En);
+        for locale in Locale::ALL {
+            for (key, message) in catalog(locale) {
+                let baseline = english
+                    .get(key)
+                    .unwrap_or_else(|| panic!("'{}' has '{}', which English lacks", locale.as_str(), key));
+                assert_eq!(
+                    placeholders(message),
+                    placeholders(baseline),
+                    "'{}' in '{}' has different placeholders",
+                    key,
+                    locale.as_str()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_uses_current_locale() {
+        assert_eq!(crate::t!("nav.settings"), "Settings");
+        assert_eq!(with_locale(Locale::De, || crate::t!("nav.settings")), "Einstellungen");
+        assert_eq!(
+            with_locale(Locale::De, || crate::t!("album.genre_remove", genre = "House")),
+            "House entfernen"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing message")]
+    fn test_missing_key_panics_in_debug_builds() {
+        crate::t!("no.such.message");
+    }
+
+    #[test]
+    fn test_format_number_groups_digits() {
+        assert_eq!(format_number(0), "0");
+        assert_eq!(format_number(999), "999");
+        assert_eq!(format_number(1234567), "1,234,567");
+        assert_eq!(format_number(-4321), "-4,321");
+        assert_eq!(with_locale(Locale::De, || format_number(1234567)), "1.234.567");
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_english() {
+        assert_eq!(Locale::resolve(Some("de")), Locale::De);
+        assert_eq!(Locale::resolve(Some("tlh")), Locale::En);
+        assert_eq!(Locale::resolve(None), Locale::En);
+    }
+}
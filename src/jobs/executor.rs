@@ -8,20 +8,21 @@ use crate::{
         entities::jobs,
         enums::{JobStatus, JobType},
     },
+    events::DomainEvent,
     jobs::queue::JobMessage,
-    services::playlist_stats,
+    services::{album_grid_cache, playlist_stats},
     state::AppState,
-    tasks::{filesystem_scan, musicbrainz_match, spotify_sync},
+    tasks::{artist_images, cover_art, filesystem_scan, match_reevaluation, musicbrainz_match, spotify_sync},
 };
 
 /// Background job executor that processes jobs from the queue
 pub struct JobExecutor {
     state: AppState,
-    receiver: mpsc::UnboundedReceiver<JobMessage>,
+    receiver: mpsc::Receiver<JobMessage>,
 }
 
 impl JobExecutor {
-    pub fn new(state: AppState, receiver: mpsc::UnboundedReceiver<JobMessage>) -> Self {
+    pub fn new(state: AppState, receiver: mpsc::Receiver<JobMessage>) -> Self {
         Self { state, receiver }
     }
 
@@ -48,6 +49,18 @@ impl JobExecutor {
         tracing::warn!("Job executor stopped - queue closed");
     }
 
+    /// The music folder a filesystem scan walks, from the settings of the
+    /// profile that queued it
+    async fn music_folder(state: &AppState, profile_id: Option<i32>) -> Result<std::path::PathBuf> {
+        let settings = crate::db::settings::for_profile_id(&state.db, profile_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User settings not found"))?;
+        settings
+            .music_folder_path
+            .map(std::path::PathBuf::from)
+            .ok_or_else(|| anyhow::anyhow!("Music folder path not configured"))
+    }
+
     /// Execute a single job
     async fn execute_job(state: AppState, message: JobMessage) -> Result<()> {
         let job_id = message.job_id;
@@ -67,40 +80,32 @@ impl JobExecutor {
 
         // Execute the job based on type
         let result = match message.job_type {
-            JobType::SpotifySync => spotify_sync::run_spotify_sync(state.clone()).await,
+            JobType::SpotifySync => spotify_sync::run_spotify_sync(state.clone(), job_id, message.entity_id).await,
 
             JobType::MusicbrainzMatch => {
-                musicbrainz_match::run_musicbrainz_match(state.clone()).await
+                musicbrainz_match::run_musicbrainz_match(state.clone(), message.entity_id).await
             }
 
-            JobType::FilesystemScan => {
-                if let Some(settings) = crate::db::entities::user_settings::Entity::find()
-                    .one(&state.db)
-                    .await?
-                {
-                    if let Some(music_path) = settings.music_folder_path {
-                        filesystem_scan::run_filesystem_scan(
-                            state.clone(),
-                            std::path::Path::new(&music_path),
-                        )
-                        .await
-                    } else {
-                        Err(anyhow::anyhow!("Music folder path not configured"))
-                    }
-                } else {
-                    Err(anyhow::anyhow!("User settings not found"))
+            JobType::FilesystemScan => match Self::music_folder(&state, message.entity_id).await {
+                Ok(music_path) => {
+                    filesystem_scan::run_filesystem_scan_job(state.clone(), job_id, message.entity_id, &music_path).await
                 }
-            }
+                Err(e) => Err(e),
+            },
+
+            JobType::FilesystemScanPreview => match Self::music_folder(&state, message.entity_id).await {
+                Ok(music_path) => {
+                    filesystem_scan::run_filesystem_scan_preview(state.clone(), job_id, &music_path).await
+                }
+                Err(e) => Err(e),
+            },
 
             JobType::LidarrSearch => {
                 // TODO: Implement Lidarr search job
                 Err(anyhow::anyhow!("Lidarr search not yet implemented"))
             }
 
-            JobType::CoverArtFetch => {
-                // TODO: Implement cover art fetch job
-                Err(anyhow::anyhow!("Cover art fetch not yet implemented"))
-            }
+            JobType::CoverArtFetch => cover_art::download_all_missing_covers(state.clone()).await,
 
             JobType::PlaylistStatsBackfill => {
                 playlist_stats::recalculate_all_playlist_stats(&state.db)
@@ -109,10 +114,23 @@ impl JobExecutor {
                         tracing::info!("Recalculated stats for {} playlists", count);
                     })
             }
+
+            JobType::MatchReevaluation => {
+                match_reevaluation::run_match_reevaluation(&state.db, job_id)
+                    .await
+                    .map(|_| ())
+            }
+
+            JobType::ArtistImageBackfill => {
+                artist_images::run_artist_image_backfill(state.clone(), job_id, message.entity_id).await
+            }
         };
 
+        // Jobs write albums as they go, so even a failed one may have changed the grid
+        album_grid_cache::invalidate(&state.redis).await;
+
         // Update job status based on result
-        match result {
+        let status = match result {
             Ok(_) => {
                 tracing::info!("Job {} completed successfully", job_id);
                 Self::update_job_status(
@@ -123,6 +141,7 @@ impl JobExecutor {
                     None,
                 )
                 .await?;
+                JobStatus::Completed
             }
             Err(e) => {
                 tracing::error!("Job {} failed: {}", job_id, e);
@@ -134,8 +153,15 @@ impl JobExecutor {
                     None,
                 )
                 .await?;
+                JobStatus::Failed
             }
-        }
+        };
+
+        state.events.publish(DomainEvent::JobFinished {
+            job_id,
+            job_type: message.job_type,
+            status,
+        });
 
         Ok(())
     }
@@ -164,6 +190,11 @@ impl JobExecutor {
             active.started_at = Set(Some(start.with_timezone(&chrono::Utc).into()));
         }
 
+        // A completed job has nothing left to resume
+        if status == JobStatus::Completed {
+            active.sync_cursor = Set(None);
+        }
+
         if status == JobStatus::Completed || status == JobStatus::Failed {
             active.completed_at = Set(Some(Utc::now().into()));
         }
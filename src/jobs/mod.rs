@@ -1,5 +1,7 @@
 pub mod queue;
 pub mod executor;
+pub mod result;
 
 pub use queue::JobQueue;
 pub use executor::JobExecutor;
+pub use result::record_result;
@@ -1,8 +1,11 @@
-use anyhow::Result;
-use tokio::sync::mpsc;
+use thiserror::Error;
+use tokio::sync::mpsc::{self, error::TrySendError};
 
 use crate::db::JobType;
 
+/// Seconds a client is asked to wait before retrying when the queue is full
+pub const QUEUE_FULL_RETRY_AFTER_SECS: u64 = 30;
+
 /// Message sent to the job queue
 #[derive(Debug, Clone)]
 pub struct JobMessage {
@@ -11,31 +14,81 @@ pub struct JobMessage {
     pub entity_id: Option<i32>,
 }
 
+/// Why a job couldn't be queued
+#[derive(Debug, Error)]
+pub enum SubmitError {
+    #[error("Job queue is full ({0} jobs waiting)")]
+    Full(usize),
+    #[error("Job queue is closed")]
+    Closed,
+}
+
 /// Job queue for async background task processing
+///
+/// Bounded, so a client looping on a trigger endpoint gets turned away
+/// instead of piling up work in memory.
 #[derive(Clone)]
 pub struct JobQueue {
-    sender: mpsc::UnboundedSender<JobMessage>,
+    sender: mpsc::Sender<JobMessage>,
 }
 
 impl JobQueue {
-    /// Create a new job queue and return (queue, receiver)
-    pub fn new() -> (Self, mpsc::UnboundedReceiver<JobMessage>) {
-        let (sender, receiver) = mpsc::unbounded_channel();
+    /// Create a queue holding at most `capacity` waiting jobs and return (queue, receiver)
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<JobMessage>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
         (Self { sender }, receiver)
     }
 
-    /// Submit a job to the queue
-    pub fn submit(&self, message: JobMessage) -> Result<()> {
-        tracing::info!(
-            "Job {} ({:?}) submitted to queue",
-            message.job_id,
-            message.job_type
-        );
+    /// Submit a job to the queue, failing right away if it is full
+    pub fn submit(&self, message: JobMessage) -> Result<(), SubmitError> {
+        let (job_id, job_type) = (message.job_id, message.job_type);
+
+        match self.sender.try_send(message) {
+            Ok(()) => {
+                tracing::info!("Job {} ({:?}) submitted to queue", job_id, job_type);
+                Ok(())
+            }
+            Err(TrySendError::Full(_)) => {
+                tracing::warn!("Job queue full, rejected job {} ({:?})", job_id, job_type);
+                Err(SubmitError::Full(self.depth()))
+            }
+            Err(TrySendError::Closed(_)) => Err(SubmitError::Closed),
+        }
+    }
+
+    /// Jobs waiting for the executor to pick them up
+    pub fn depth(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+
+    /// Most jobs that can wait at once
+    pub fn capacity(&self) -> usize {
+        self.sender.max_capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(job_id: i32) -> JobMessage {
+        JobMessage { job_id, job_type: JobType::MusicbrainzMatch, entity_id: None }
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_rejects_until_drained() {
+        let (queue, mut receiver) = JobQueue::new(2);
+
+        queue.submit(message(1)).unwrap();
+        queue.submit(message(2)).unwrap();
+        assert_eq!(queue.depth(), 2);
+        assert!(matches!(queue.submit(message(3)), Err(SubmitError::Full(2))));
 
-        self.sender
-            .send(message)
-            .map_err(|e| anyhow::anyhow!("Failed to submit job: {}", e))?;
+        assert_eq!(receiver.recv().await.unwrap().job_id, 1);
+        assert_eq!(queue.depth(), 1);
+        queue.submit(message(3)).unwrap();
 
-        Ok(())
+        drop(receiver);
+        assert!(matches!(queue.submit(message(4)), Err(SubmitError::Closed)));
     }
 }
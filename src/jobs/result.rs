@@ -0,0 +1,28 @@
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde::Serialize;
+
+use crate::db::entities::jobs;
+
+/// Store a finished job's summary, and how many items it processed if known
+///
+/// The summary is kept as JSON in `jobs.result` and shows up on the job
+/// status endpoints and the sync history report.
+pub async fn record_result<T: Serialize>(
+    db: &DatabaseConnection,
+    job_id: i32,
+    processed_items: Option<usize>,
+    summary: &T,
+) -> Result<()> {
+    if let Some(job) = jobs::Entity::find_by_id(job_id).one(db).await? {
+        let mut active: jobs::ActiveModel = job.into();
+        active.result = Set(Some(serde_json::to_string(summary)?));
+        if let Some(processed) = processed_items {
+            active.processed_items = Set(Some(processed as i32));
+        }
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+    }
+    Ok(())
+}
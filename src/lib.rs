@@ -2,11 +2,16 @@
 //!
 //! This library exposes modules for integration testing
 
+pub mod compression;
 pub mod config;
 pub mod db;
+pub mod doctor;
 pub mod error;
+pub mod events;
 pub mod handlers;
+pub mod i18n;
 pub mod jobs;
+pub mod logging;
 pub mod services;
 pub mod state;
 pub mod tasks;
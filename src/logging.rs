@@ -0,0 +1,220 @@
+//! Tracing subscriber setup with a log filter that can be changed at runtime
+//!
+//! The `EnvFilter` sits behind a `tracing_subscriber::reload` layer, so the
+//! admin API can raise or lower log levels without a restart. An override set
+//! that way is persisted in user settings and re-applied on the next startup.
+
+use std::sync::Arc;
+
+use sea_orm::DatabaseConnection;
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+use crate::{
+    config::LogFormat,
+    db::settings as db_settings,
+    error::{AppError, Result},
+};
+
+/// Filter used when `RUST_LOG` is unset or invalid
+pub const DEFAULT_LOG_DIRECTIVES: &str = "beat_collector=debug,tower_http=debug,axum=trace";
+
+/// Handle to the active log filter
+///
+/// Cloning is cheap; all clones control the same filter.
+#[derive(Clone)]
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    default_directives: Arc<str>,
+    /// Keeps the filter layer alive when it isn't part of an installed subscriber
+    _detached: Option<Arc<reload::Layer<EnvFilter, Registry>>>,
+}
+
+impl LogFilter {
+    /// Create a reloadable filter layer starting at `default_directives`
+    ///
+    /// The returned layer must be added to a subscriber built on `Registry`.
+    pub fn new(default_directives: &str) -> (reload::Layer<EnvFilter, Registry>, Self) {
+        let (directives, filter) = match parse_directives(default_directives) {
+            Ok(filter) => (default_directives, filter),
+            Err(_) => (DEFAULT_LOG_DIRECTIVES, EnvFilter::new(DEFAULT_LOG_DIRECTIVES)),
+        };
+        let (layer, handle) = reload::Layer::new(filter);
+
+        (
+            layer,
+            Self {
+                handle,
+                default_directives: directives.into(),
+                _detached: None,
+            },
+        )
+    }
+
+    /// A filter that isn't attached to any subscriber, for tests
+    pub fn detached(default_directives: &str) -> Self {
+        let (layer, mut filter) = Self::new(default_directives);
+        filter._detached = Some(Arc::new(layer));
+        filter
+    }
+
+    /// Directives the filter started with (`RUST_LOG` or the built-in default)
+    pub fn default_directives(&self) -> &str {
+        &self.default_directives
+    }
+
+    /// Directives currently in effect
+    pub fn current(&self) -> String {
+        self.handle
+            .with_current(|filter| filter.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Replace the filter with `directives`, e.g. `beat_collector=trace,tower_http=info`
+    ///
+    /// Malformed directives are rejected with a validation error and leave the
+    /// current filter untouched.
+    pub fn apply(&self, directives: &str) -> Result<()> {
+        let filter = parse_directives(directives)?;
+        self.handle
+            .reload(filter)
+            .map_err(|e| AppError::Internal(format!("Failed to reload log filter: {}", e)))
+    }
+
+    /// Go back to the default directives
+    pub fn reset(&self) -> Result<()> {
+        self.apply(&self.default_directives)
+    }
+
+    /// Apply the override saved in user settings, if any
+    ///
+    /// An invalid saved override is logged and ignored rather than preventing startup.
+    pub async fn restore_saved(&self, db: &DatabaseConnection) -> Result<()> {
+        let saved = db_settings::shared(db)
+            .await?
+            .and_then(|settings| settings.log_level);
+
+        if let Some(directives) = saved {
+            match self.apply(&directives) {
+                Ok(()) => tracing::info!("Restored log level override: {}", directives),
+                Err(e) => tracing::warn!("Ignoring saved log level override: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse filter directives, rejecting blank or malformed input
+pub fn parse_directives(directives: &str) -> Result<EnvFilter> {
+    if directives.trim().is_empty() {
+        return Err(AppError::Validation("Log directives must not be empty".to_string()));
+    }
+
+    EnvFilter::try_new(directives)
+        .map_err(|e| AppError::Validation(format!("Invalid log directives '{}': {}", directives, e)))
+}
+
+/// Install the global subscriber, writing lines in `format` to stdout
+///
+/// The starting filter comes from `RUST_LOG`, falling back to
+/// [`DEFAULT_LOG_DIRECTIVES`] when it is unset or invalid.
+pub fn init(format: LogFormat) -> LogFilter {
+    let directives = std::env::var("RUST_LOG").unwrap_or_else(|_| DEFAULT_LOG_DIRECTIVES.to_string());
+    let (filter_layer, log_filter) = LogFilter::new(&directives);
+
+    let (json, pretty) = match format {
+        LogFormat::Json => (Some(fmt::layer().json()), None),
+        LogFormat::Pretty => (None, Some(fmt::layer())),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(json)
+        .with(pretty)
+        .init();
+
+    log_filter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Write, sync::Mutex};
+
+    /// Writer collecting formatted log lines in memory
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Captured {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> fmt::MakeWriter<'a> for Captured {
+        type Writer = Captured;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_reload_changes_captured_output() {
+        let (layer, log_filter) = LogFilter::new("beat_collector=info");
+        let captured = Captured::default();
+        let subscriber = Registry::default()
+            .with(layer)
+            .with(fmt::layer().with_writer(captured.clone()).with_ansi(false));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "beat_collector::tasks", "before override");
+
+            log_filter.apply("beat_collector=debug").unwrap();
+            assert_eq!(log_filter.current(), "beat_collector=debug");
+            tracing::debug!(target: "beat_collector::tasks", "during override");
+            tracing::debug!(target: "other_crate", "other crate");
+
+            log_filter.reset().unwrap();
+            tracing::debug!(target: "beat_collector::tasks", "debug after reset");
+            tracing::info!(target: "beat_collector::tasks", "info after reset");
+        });
+
+        let output = captured.contents();
+        assert!(!output.contains("before override"));
+        assert!(output.contains("during override"));
+        assert!(!output.contains("other crate"));
+        assert!(!output.contains("debug after reset"));
+        assert!(output.contains("info after reset"));
+    }
+
+    #[test]
+    fn test_invalid_directives_leave_filter_untouched() {
+        let log_filter = LogFilter::detached("beat_collector=info");
+
+        for invalid in ["", "   ", "beat_collector=loud", "beat_collector[{=debug"] {
+            let err = log_filter.apply(invalid).unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)), "accepted {:?}", invalid);
+        }
+        assert_eq!(log_filter.current(), "beat_collector=info");
+    }
+
+    #[test]
+    fn test_invalid_default_falls_back() {
+        let log_filter = LogFilter::detached("beat_collector=loud");
+        assert_eq!(log_filter.default_directives(), DEFAULT_LOG_DIRECTIVES);
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use axum::{
+    middleware,
     routing::get,
     Router,
 };
@@ -10,13 +11,11 @@ use std::net::SocketAddr;
 use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
-    compression::CompressionLayer,
     services::ServeDir,
 };
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 // Re-export library for binary
-use beat_collector::{config, db, error, handlers, jobs, services, state, tasks, templates};
+use beat_collector::{compression, config, db, doctor, error, events, handlers, i18n, jobs, logging, services, state, tasks, templates};
 
 use config::Config;
 use state::AppState;
@@ -26,28 +25,49 @@ async fn main() -> Result<()> {
     // Load environment variables
     dotenv().ok();
 
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "beat_collector=debug,tower_http=debug,axum=trace".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Load configuration (before tracing, which needs the log format)
+    let config = Config::from_env()?;
 
-    tracing::info!("Starting Beat Collector...");
+    // `--check`: report on the configuration and connections, then exit
+    if std::env::args().skip(1).any(|arg| arg == "--check") {
+        let results = doctor::run_checks(&config).await;
+        print!("{}", doctor::render_table(&results));
+        std::process::exit(if results.iter().all(doctor::CheckResult::passed) { 0 } else { 1 });
+    }
 
-    // Load configuration
-    let config = Config::from_env()?;
+    // Fail fast with every problem listed rather than on the first connection
+    config.validate()?;
+
+    // Initialize tracing with a filter that can be changed at runtime
+    let log_filter = logging::init(config.log_format);
+
+    tracing::info!("Starting Beat Collector...");
     tracing::info!("Configuration loaded");
 
     // Connect to database
     let db = Database::connect(&config.database_url).await?;
     tracing::info!("Connected to database");
 
-    // Run migrations
-    migration::Migrator::up(&db, None).await?;
-    tracing::info!("Database migrations completed");
+    // Refuse to run against a schema migrated by a newer build
+    let schema = db::schema::check_schema_compatibility(&db, config.allow_schema_ahead).await?;
+
+    // Run migrations (the migrator can't run against unknown migrations, so skip when ahead)
+    if schema.is_ahead() {
+        tracing::warn!("Skipping migrations: database schema is ahead of this binary");
+    } else {
+        migration::Migrator::up(&db, None).await?;
+        tracing::info!("Database migrations completed");
+
+        // Give rows created before slugs existed a stable URL slug
+        db::slug::backfill_slugs(&db).await?;
+        db::artist_names::backfill_normalized_names(&db).await?;
+    }
+
+    // Re-apply a log level set through the admin API before the last restart
+    log_filter.restore_saved(&db).await?;
+
+    // Fail fast on a broken message catalog
+    i18n::load();
 
     // Connect to Redis
     let redis_client = redis::Client::open(config.redis_url.as_str())?;
@@ -55,11 +75,12 @@ async fn main() -> Result<()> {
     tracing::info!("Connected to Redis");
 
     // Initialize job queue and executor
-    let (job_queue, job_receiver) = jobs::JobQueue::new();
+    let (job_queue, job_receiver) = jobs::JobQueue::new(config.job_queue_capacity);
     tracing::info!("Job queue initialized");
 
     // Initialize application state
-    let state = AppState::new(db, redis_conn, config.clone(), job_queue);
+    let state = AppState::new(db, redis_conn, config.clone(), job_queue, log_filter);
+    events::start_subscribers(&state);
 
     // Start job executor
     let executor = jobs::JobExecutor::new(state.clone(), job_receiver);
@@ -68,6 +89,11 @@ async fn main() -> Result<()> {
     });
     tracing::info!("Job executor started");
 
+    // Fill in artist images once for libraries synced before they existed
+    if let Err(e) = tasks::artist_images::queue_initial_backfill(&state).await {
+        tracing::warn!("Failed to queue the artist image backfill: {}", e);
+    }
+
     // Start background tasks
     let task_scheduler = tasks::start_scheduler(state.clone()).await?;
     tracing::info!("Background task scheduler started");
@@ -91,11 +117,19 @@ fn create_router(state: AppState) -> Router {
         // Health check
         .route("/health", get(handlers::health::health_check))
 
-        // API routes (JSON)
-        .nest("/api", handlers::api_routes())
+        // API routes (JSON), in the active profile's language for the HTML
+        // fragments some of them return to HTMX
+        .nest(
+            "/api",
+            handlers::api_routes()
+                .layer(middleware::from_fn_with_state(state.clone(), i18n::scope_locale)),
+        )
 
-        // HTML routes (MASH stack - Maud + HTMX)
-        .merge(handlers::html_routes())
+        // HTML routes (MASH stack - Maud + HTMX), rendered in the active profile's language
+        .merge(
+            handlers::html_routes()
+                .layer(middleware::from_fn_with_state(state.clone(), i18n::scope_locale)),
+        )
 
         // Static file serving for cover art and assets
         .nest_service("/static", ServeDir::new("static"))
@@ -107,7 +141,7 @@ fn create_router(state: AppState) -> Router {
                 .allow_methods(Any)
                 .allow_headers(Any),
         )
-        .layer(CompressionLayer::new())
+        .layer(compression::layer())
         .layer(TraceLayer::new_for_http())
         .with_state(state)
 }
@@ -0,0 +1,84 @@
+//! Library-wide activity feed
+//!
+//! The activity log subscriber turns every domain event into a row of
+//! `activity_log`, newest first on `GET /api/activity`. It is derived data:
+//! the `ownership_events` audit trail is written in the saving transaction
+//! and is the record to trust. When the subscriber falls behind and skips
+//! events, a `skipped` row marks the gap instead.
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, QueryOrder, QuerySelect, Set};
+use serde_json::json;
+
+use crate::{
+    db::entities::activity_log,
+    error::Result,
+    events::DomainEvent,
+};
+
+/// Kind of the row that records skipped events
+pub const KIND_SKIPPED: &str = "skipped";
+
+/// The row an event adds to the feed
+fn entry_for(event: DomainEvent) -> activity_log::ActiveModel {
+    let (kind, album_id, job_id, details) = match event {
+        DomainEvent::OwnershipChanged { album_id, from, to, reason, forced } => (
+            "ownership_changed",
+            Some(album_id),
+            None,
+            json!({ "from": from.as_str(), "to": to.as_str(), "reason": reason, "forced": forced }),
+        ),
+        DomainEvent::JobFinished { job_id, job_type, status } => (
+            "job_finished",
+            None,
+            Some(job_id),
+            json!({ "job_type": job_type.as_str(), "status": status.as_str() }),
+        ),
+        DomainEvent::AlbumImported { album_id, source } => (
+            "album_imported",
+            Some(album_id),
+            None,
+            json!({ "source": source.as_str() }),
+        ),
+    };
+
+    activity_log::ActiveModel {
+        kind: Set(kind.to_string()),
+        album_id: Set(album_id),
+        job_id: Set(job_id),
+        details: Set(details.to_string()),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    }
+}
+
+/// Activity log subscriber: add the event to the feed
+pub async fn log_event(db: DatabaseConnection, event: DomainEvent) {
+    if let Err(e) = entry_for(event).insert(&db).await {
+        tracing::warn!("Failed to add an event to the activity log: {}", e);
+    }
+}
+
+/// Activity log subscriber, after falling behind: record how many events
+/// the feed is missing
+pub async fn log_skipped(db: DatabaseConnection, skipped: u64) {
+    let entry = activity_log::ActiveModel {
+        kind: Set(KIND_SKIPPED.to_string()),
+        details: Set(json!({ "skipped": skipped }).to_string()),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    };
+    if let Err(e) = entry.insert(&db).await {
+        tracing::warn!("Failed to record {} skipped event(s) in the activity log: {}", skipped, e);
+    }
+}
+
+/// The newest `limit` entries of the feed, newest first
+pub async fn recent(db: &DatabaseConnection, limit: u64) -> Result<Vec<activity_log::Model>> {
+    Ok(activity_log::Entity::find()
+        .order_by_desc(activity_log::Column::CreatedAt)
+        .order_by_desc(activity_log::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await?)
+}
@@ -0,0 +1,139 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, EntityTrait, ModelTrait, PaginatorTrait, Set,
+    TransactionTrait,
+};
+
+use crate::{
+    db::{
+        artist_names,
+        entities::{albums, artists},
+        slug,
+    },
+    error::{AppError, Result},
+};
+
+/// Where an album is being moved to
+#[derive(Debug, Clone, Copy)]
+pub enum ArtistChoice<'a> {
+    Existing(i32),
+    /// Create an artist with this name first
+    New(&'a str),
+}
+
+/// Result of moving an album to another artist
+#[derive(Debug)]
+pub struct Reassignment {
+    pub album: albums::Model,
+    pub artist: artists::Model,
+    /// Previous artist, if it was left without albums and removed
+    pub removed_artist_id: Option<i32>,
+}
+
+/// Move an album to another artist, creating that artist if asked to
+///
+/// Spotify sync never changes the artist of an album it already knows, so
+/// the move sticks. The album keeps its slug so existing links keep working.
+pub async fn reassign_album(
+    db: &DatabaseConnection,
+    album: albums::Model,
+    choice: ArtistChoice<'_>,
+) -> Result<Reassignment> {
+    // A new artist is created in the same transaction, so a failed move
+    // doesn't leave it behind
+    let txn = db.begin().await?;
+
+    let artist = match choice {
+        ArtistChoice::Existing(id) => artists::Entity::find_by_id(id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| AppError::Validation(format!("Artist {} does not exist", id)))?,
+        ArtistChoice::New(name) => create_artist(&txn, name).await?,
+    };
+
+    let previous_artist_id = album.artist_id;
+    if previous_artist_id == artist.id {
+        return Ok(Reassignment { album, artist, removed_artist_id: None });
+    }
+
+    let mut active: albums::ActiveModel = album.into();
+    active.artist_id = Set(artist.id);
+    active.updated_at = Set(Utc::now().into());
+    let album = active.update(&txn).await?;
+
+    let previous = artists::Entity::find_by_id(previous_artist_id).one(&txn).await?;
+    let mut removed_artist_id = None;
+    if let Some(previous) = previous {
+        let remaining = previous.find_related(albums::Entity).count(&txn).await?;
+        if is_orphaned(&previous, remaining) {
+            removed_artist_id = Some(previous.id);
+            previous.delete(&txn).await?;
+        }
+    }
+
+    txn.commit().await?;
+
+    if let Some(id) = removed_artist_id {
+        tracing::info!("Removed artist {} after its last album moved to {}", id, artist.name);
+    }
+
+    Ok(Reassignment { album, artist, removed_artist_id })
+}
+
+/// Whether an artist left with `remaining` albums can be removed
+///
+/// Artists whose name the user corrected are kept even when empty, so the
+/// correction isn't lost if Spotify brings one of their albums back.
+fn is_orphaned(artist: &artists::Model, remaining: u64) -> bool {
+    remaining == 0 && !artist.name_locked
+}
+
+/// Create an artist the user named; the name is locked like a rename
+async fn create_artist<C: ConnectionTrait>(db: &C, name: &str) -> Result<artists::Model> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Artist name must not be empty".to_string()));
+    }
+
+    let now = Utc::now().into();
+    let artist = artists::ActiveModel {
+        name: Set(name.to_string()),
+        slug: Set(Some(slug::new_artist_slug(db, name).await?)),
+        normalized_name: Set(Some(artist_names::normalize_name(name))),
+        name_locked: Set(true),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    tracing::info!("Created artist {} ({})", artist.name, artist.id);
+    Ok(artist)
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{ColumnTrait, QueryFilter};
+
+    use super::*;
+    use crate::test_utils::*;
+
+    #[tokio::test]
+    async fn test_failed_move_does_not_leave_the_new_artist_behind() {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Daft Punk", None).await;
+        let album = create_test_album(&db, artist.id, "Discovery", None).await;
+
+        // Deleted while the move was being requested, so the album update fails
+        albums::Entity::delete_by_id(album.id).exec(&db).await.unwrap();
+        assert!(reassign_album(&db, album, ArtistChoice::New("Kraftwerk")).await.is_err());
+
+        let created = artists::Entity::find()
+            .filter(artists::Column::Name.eq("Kraftwerk"))
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(created, 0);
+    }
+}
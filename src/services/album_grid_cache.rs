@@ -0,0 +1,86 @@
+//! Redis cache of the default album grid page
+//!
+//! Page 1 of the library with no filters, newest first, is rendered on every
+//! visit to the home page. Its HTML is cached per profile. Rather than working
+//! out which cached pages a write affects, anything that changes albums starts
+//! a new generation, which is part of every cache key; entries of older
+//! generations are never read again and expire on their own. The cached
+//! library stats (`stats_cache`) are keyed by the same generation.
+//!
+//! Redis problems never fail a request: a failed read is a cache miss and a
+//! failed write is only logged.
+
+use redis::aio::ConnectionManager;
+
+use crate::{handlers::albums::ListAlbumsQuery, services::CacheService};
+
+const GENERATION_KEY: &str = "albums:grid:generation";
+
+/// Lifetime of a cached page, as a backstop for a missed invalidation
+const TTL_SECONDS: usize = 600;
+
+/// Whether `query` asks for the default first page that gets cached
+///
+/// A requested view is persisted to settings by the grid handler, so only
+/// requests without one are served from cache.
+pub fn is_cacheable(query: &ListAlbumsQuery) -> bool {
+    query.page <= 1
+        && query.ownership_status.is_none()
+        && query.match_status.is_none()
+        && query.artist_id.is_none()
+        && query.search.is_none()
+        && query.sort_by == "created_at"
+        && query.sort_order == "desc"
+        && query.view.is_none()
+        && query.exclude_singles.is_none()
+        && query.has_issues.is_none()
+}
+
+/// Cached grid HTML for a profile, if any
+pub async fn get(redis: &ConnectionManager, profile_id: i32, page_size: u64) -> Option<String> {
+    let cache = CacheService::new(redis.clone());
+    let key = page_key(&cache, profile_id, page_size).await?;
+    match cache.get(&key).await {
+        Ok(html) => html,
+        Err(e) => {
+            tracing::warn!("Failed to read cached album grid: {}", e);
+            None
+        }
+    }
+}
+
+/// Cache the rendered grid for a profile
+pub async fn put(redis: &ConnectionManager, profile_id: i32, page_size: u64, html: &str) {
+    let cache = CacheService::new(redis.clone());
+    let Some(key) = page_key(&cache, profile_id, page_size).await else {
+        return;
+    };
+    if let Err(e) = cache.set(&key, &html, Some(TTL_SECONDS)).await {
+        tracing::warn!("Failed to cache album grid: {}", e);
+    }
+}
+
+/// Drop every cached grid page; call after changing albums or the settings
+/// that shape the grid
+pub async fn invalidate(redis: &ConnectionManager) {
+    let generation = uuid::Uuid::new_v4().simple().to_string();
+    if let Err(e) = CacheService::new(redis.clone()).set_permanent(GENERATION_KEY, &generation).await {
+        tracing::warn!("Failed to invalidate cached album grid: {}", e);
+    }
+}
+
+async fn page_key(cache: &CacheService, profile_id: i32, page_size: u64) -> Option<String> {
+    let generation = generation(cache).await?;
+    Some(format!("albums:grid:{}:{}:{}", generation, profile_id, page_size))
+}
+
+/// Current generation of album data; `None` when Redis can't be read
+pub async fn generation(cache: &CacheService) -> Option<String> {
+    match cache.get::<String>(GENERATION_KEY).await {
+        Ok(generation) => Some(generation.unwrap_or_else(|| "0".to_string())),
+        Err(e) => {
+            tracing::warn!("Failed to read album grid cache generation: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection,
+    EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+    TransactionTrait,
+};
+use serde::Serialize;
+
+use crate::{
+    db::{
+        entities::{albums, artists, playlist_tracks, playlists, profile_albums, profile_playlists, profiles, tracks},
+        enums::AlbumSource,
+        profile,
+        settings as db_settings,
+    },
+    error::{AppError, Result},
+};
+
+/// Longest `archive_retention_days` a profile can set
+pub const MAX_ARCHIVE_RETENTION_DAYS: i32 = 3650;
+
+/// What an archived row is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// Saved album no longer saved on Spotify
+    Album,
+    /// Playlist no longer followed
+    Playlist,
+    /// Track removed from Liked Songs
+    Track,
+}
+
+impl ArchiveKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Album => "albums",
+            Self::Playlist => "playlists",
+            Self::Track => "tracks",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "albums" => Some(Self::Album),
+            "playlists" => Some(Self::Playlist),
+            "tracks" => Some(Self::Track),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct ArchivedAlbum {
+    pub id: i32,
+    pub title: String,
+    pub artist_name: String,
+    pub ownership_status: String,
+    pub archived_at: DateTimeWithTimeZone,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct ArchivedPlaylist {
+    pub id: i32,
+    pub name: String,
+    pub owner_name: Option<String>,
+    pub archived_at: DateTimeWithTimeZone,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct ArchivedTrack {
+    pub id: i32,
+    pub title: String,
+    pub album_id: i32,
+    pub album_title: String,
+    pub artist_name: String,
+    pub archived_at: DateTimeWithTimeZone,
+}
+
+/// Rows the archive purge removed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PurgedArchive {
+    pub albums: u64,
+    pub playlists: u64,
+    pub tracks: u64,
+}
+
+impl PurgedArchive {
+    pub fn total(&self) -> u64 {
+        self.albums + self.playlists + self.tracks
+    }
+}
+
+/// Everything in a profile's library that disappeared from Spotify, newest first
+#[derive(Debug, Default, Serialize)]
+pub struct Archive {
+    pub albums: Vec<ArchivedAlbum>,
+    pub playlists: Vec<ArchivedPlaylist>,
+    pub tracks: Vec<ArchivedTrack>,
+}
+
+/// Record that a sync saw these albums among the saved albums
+///
+/// Re-saving an archived album takes it out of the archive.
+pub async fn mark_albums_saved(db: &DatabaseConnection, album_ids: Vec<i32>) -> Result<()> {
+    if album_ids.is_empty() {
+        return Ok(());
+    }
+
+    albums::Entity::update_many()
+        .col_expr(albums::Column::LastSyncedAt, Expr::value(DateTimeWithTimeZone::from(Utc::now())))
+        .col_expr(albums::Column::ArchivedAt, Expr::value(Option::<DateTimeWithTimeZone>::None))
+        .filter(albums::Column::Id.is_in(album_ids))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Archive the profile's saved albums that a complete sync started at `sync_started_at` didn't see
+///
+/// Albums restored from the archive have no `last_synced_at`, so they are
+/// only archived again after being saved and un-saved once more.
+pub async fn archive_unsaved_albums(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    sync_started_at: DateTimeWithTimeZone,
+) -> Result<u64> {
+    let archived = albums::Entity::update_many()
+        .col_expr(albums::Column::ArchivedAt, Expr::value(DateTimeWithTimeZone::from(Utc::now())))
+        .filter(profile::albums_in_profile(profile_id))
+        .filter(albums::Column::Source.eq(AlbumSource::SavedAlbum.as_str()))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .filter(albums::Column::LastSyncedAt.lt(sync_started_at))
+        .exec(db)
+        .await?
+        .rows_affected;
+
+    if archived > 0 {
+        tracing::info!("Archived {} albums no longer saved on Spotify", archived);
+    }
+    Ok(archived)
+}
+
+/// Archive the profile's followed playlists missing from `followed_spotify_ids`
+///
+/// Liked Songs and playlists imported by link aren't followed, so they are
+/// never archived this way.
+pub async fn archive_unfollowed_playlists(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    followed_spotify_ids: &[String],
+) -> Result<u64> {
+    let archived = playlists::Entity::update_many()
+        .col_expr(playlists::Column::ArchivedAt, Expr::value(DateTimeWithTimeZone::from(Utc::now())))
+        .filter(profile::playlists_in_profile(profile_id))
+        .filter(playlists::Column::IsSynthetic.eq(false))
+        .filter(playlists::Column::IsExternal.eq(false))
+        .filter(playlists::Column::ArchivedAt.is_null())
+        .filter(playlists::Column::SpotifyId.is_not_in(followed_spotify_ids.to_vec()))
+        .exec(db)
+        .await?
+        .rows_affected;
+
+    if archived > 0 {
+        tracing::info!("Archived {} playlists no longer followed on Spotify", archived);
+    }
+    Ok(archived)
+}
+
+/// Track IDs currently in a playlist
+pub async fn playlist_track_ids(db: &DatabaseConnection, playlist_id: i32) -> Result<HashSet<i32>> {
+    let ids: Vec<i32> = playlist_tracks::Entity::find()
+        .select_only()
+        .column(playlist_tracks::Column::TrackId)
+        .filter(playlist_tracks::Column::PlaylistId.eq(playlist_id))
+        .into_tuple()
+        .all(db)
+        .await?;
+    Ok(ids.into_iter().collect())
+}
+
+/// Archive tracks that left Liked Songs and un-archive those liked again
+///
+/// `liked_before` is the playlist's membership before the sync replaced it.
+pub async fn archive_unliked_tracks(
+    db: &DatabaseConnection,
+    liked_playlist_id: i32,
+    liked_before: &HashSet<i32>,
+) -> Result<u64> {
+    let liked_now = playlist_track_ids(db, liked_playlist_id).await?;
+    let unliked: Vec<i32> = liked_before.difference(&liked_now).copied().collect();
+
+    let archived = if unliked.is_empty() {
+        0
+    } else {
+        tracks::Entity::update_many()
+            .col_expr(tracks::Column::ArchivedAt, Expr::value(DateTimeWithTimeZone::from(Utc::now())))
+            .filter(tracks::Column::Id.is_in(unliked))
+            .filter(tracks::Column::ArchivedAt.is_null())
+            .exec(db)
+            .await?
+            .rows_affected
+    };
+
+    tracks::Entity::update_many()
+        .col_expr(tracks::Column::ArchivedAt, Expr::value(Option::<DateTimeWithTimeZone>::None))
+        .filter(tracks::Column::ArchivedAt.is_not_null())
+        .filter(
+            tracks::Column::Id.in_subquery(
+                sea_orm::sea_query::Query::select()
+                    .column(playlist_tracks::Column::TrackId)
+                    .from(playlist_tracks::Entity)
+                    .and_where(Expr::col(playlist_tracks::Column::PlaylistId).eq(liked_playlist_id))
+                    .to_owned(),
+            ),
+        )
+        .exec(db)
+        .await?;
+
+    if archived > 0 {
+        tracing::info!("Archived {} tracks removed from Liked Songs", archived);
+    }
+    Ok(archived)
+}
+
+/// Archived rows in a profile's library, optionally of one kind only
+pub async fn list_archive(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    kind: Option<ArchiveKind>,
+) -> Result<Archive> {
+    let wants = |k: ArchiveKind| kind.is_none_or(|wanted| wanted == k);
+    let mut archive = Archive::default();
+
+    if wants(ArchiveKind::Album) {
+        archive.albums = albums::Entity::find()
+            .select_only()
+            .column(albums::Column::Id)
+            .column(albums::Column::Title)
+            .column_as(artists::Column::Name, "artist_name")
+            .column(albums::Column::OwnershipStatus)
+            .column(albums::Column::ArchivedAt)
+            .join(JoinType::InnerJoin, albums::Relation::Artists.def())
+            .filter(profile::albums_in_profile(profile_id))
+            .filter(albums::Column::ArchivedAt.is_not_null())
+            .order_by_desc(albums::Column::ArchivedAt)
+            .order_by_asc(albums::Column::Id)
+            .into_model::<ArchivedAlbum>()
+            .all(db)
+            .await?;
+    }
+
+    if wants(ArchiveKind::Playlist) {
+        archive.playlists = playlists::Entity::find()
+            .select_only()
+            .column(playlists::Column::Id)
+            .column(playlists::Column::Name)
+            .column(playlists::Column::OwnerName)
+            .column(playlists::Column::ArchivedAt)
+            .filter(profile::playlists_in_profile(profile_id))
+            .filter(playlists::Column::ArchivedAt.is_not_null())
+            .order_by_desc(playlists::Column::ArchivedAt)
+            .order_by_asc(playlists::Column::Id)
+            .into_model::<ArchivedPlaylist>()
+            .all(db)
+            .await?;
+    }
+
+    if wants(ArchiveKind::Track) {
+        archive.tracks = tracks::Entity::find()
+            .select_only()
+            .column(tracks::Column::Id)
+            .column(tracks::Column::Title)
+            .column(tracks::Column::AlbumId)
+            .column_as(albums::Column::Title, "album_title")
+            .column_as(artists::Column::Name, "artist_name")
+            .column(tracks::Column::ArchivedAt)
+            .join(JoinType::InnerJoin, tracks::Relation::Albums.def())
+            .join(JoinType::InnerJoin, albums::Relation::Artists.def())
+            .filter(profile::albums_in_profile(profile_id))
+            .filter(tracks::Column::ArchivedAt.is_not_null())
+            .order_by_desc(tracks::Column::ArchivedAt)
+            .order_by_asc(tracks::Column::Id)
+            .into_model::<ArchivedTrack>()
+            .all(db)
+            .await?;
+    }
+
+    Ok(archive)
+}
+
+/// Days `profile` keeps archived rows, or `None` to keep them for good
+pub async fn retention_days(db: &DatabaseConnection, profile: &profiles::Model) -> Result<Option<u32>> {
+    Ok(db_settings::for_profile(db, profile)
+        .await?
+        .and_then(|settings| settings.archive_retention_days)
+        .map(|days| days.max(1) as u32))
+}
+
+/// Remove the rows of `profile_id`'s archive that were archived before `cutoff`
+///
+/// Albums and playlists another profile also has are only taken out of this
+/// profile's library; the rest are deleted along with their tracks and
+/// memberships. Archived tracks are deleted unless their album is in another
+/// profile's library too.
+pub async fn purge_archived(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    cutoff: DateTime<Utc>,
+) -> Result<PurgedArchive> {
+    let cutoff = DateTimeWithTimeZone::from(cutoff);
+    let album_ids: Vec<i32> = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::Id)
+        .filter(profile::albums_in_profile(profile_id))
+        .filter(albums::Column::ArchivedAt.lt(cutoff))
+        .into_tuple()
+        .all(db)
+        .await?;
+    let playlist_ids: Vec<i32> = playlists::Entity::find()
+        .select_only()
+        .column(playlists::Column::Id)
+        .filter(profile::playlists_in_profile(profile_id))
+        .filter(playlists::Column::ArchivedAt.lt(cutoff))
+        .into_tuple()
+        .all(db)
+        .await?;
+    let shared_albums = sea_orm::sea_query::Query::select()
+        .column(profile_albums::Column::AlbumId)
+        .from(profile_albums::Entity)
+        .and_where(Expr::col(profile_albums::Column::ProfileId).ne(profile_id))
+        .to_owned();
+    let shared_playlists: Vec<i32> = profile_playlists::Entity::find()
+        .select_only()
+        .column(profile_playlists::Column::PlaylistId)
+        .filter(profile_playlists::Column::PlaylistId.is_in(playlist_ids.clone()))
+        .filter(profile_playlists::Column::ProfileId.ne(profile_id))
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut purged = PurgedArchive::default();
+    let txn = db.begin().await?;
+
+    purged.tracks = tracks::Entity::delete_many()
+        .filter(tracks::Column::ArchivedAt.lt(cutoff))
+        .filter(tracks::Column::AlbumId.in_subquery(
+            sea_orm::sea_query::Query::select()
+                .column(profile_albums::Column::AlbumId)
+                .from(profile_albums::Entity)
+                .and_where(Expr::col(profile_albums::Column::ProfileId).eq(profile_id))
+                .to_owned(),
+        ))
+        .filter(tracks::Column::AlbumId.not_in_subquery(shared_albums.clone()))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+
+    purged.albums = profile_albums::Entity::delete_many()
+        .filter(profile_albums::Column::ProfileId.eq(profile_id))
+        .filter(profile_albums::Column::AlbumId.is_in(album_ids.clone()))
+        .filter(profile_albums::Column::AlbumId.in_subquery(shared_albums.clone()))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+    purged.albums += albums::Entity::delete_many()
+        .filter(albums::Column::Id.is_in(album_ids))
+        .filter(albums::Column::Id.not_in_subquery(shared_albums))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+
+    purged.playlists = profile_playlists::Entity::delete_many()
+        .filter(profile_playlists::Column::ProfileId.eq(profile_id))
+        .filter(profile_playlists::Column::PlaylistId.is_in(shared_playlists.clone()))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+    purged.playlists += playlists::Entity::delete_many()
+        .filter(playlists::Column::Id.is_in(playlist_ids))
+        .filter(playlists::Column::Id.is_not_in(shared_playlists))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+
+    txn.commit().await?;
+
+    if purged.total() > 0 {
+        tracing::info!(
+            "Purged {} albums, {} playlists and {} tracks archived before {} from profile {}",
+            purged.albums,
+            purged.playlists,
+            purged.tracks,
+            cutoff,
+            profile_id
+        );
+    }
+    Ok(purged)
+}
+
+/// Take a row out of the archive and keep it locally
+///
+/// A restored album is detached from the saved albums sync and a restored
+/// playlist is kept like one imported by link, so the next sync doesn't
+/// archive them again. Restoring a row that isn't archived changes nothing,
+/// and rows outside the profile's library are not found.
+pub async fn restore(db: &DatabaseConnection, profile_id: i32, kind: ArchiveKind, id: i32) -> Result<()> {
+    let now = Utc::now();
+    match kind {
+        ArchiveKind::Album => {
+            let album = albums::Entity::find_by_id(id)
+                .filter(profile::albums_in_profile(profile_id))
+                .one(db)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Album {} not found", id)))?;
+            if album.archived_at.is_none() {
+                return Ok(());
+            }
+            let mut active: albums::ActiveModel = album.into();
+            active.archived_at = Set(None);
+            active.last_synced_at = Set(None);
+            active.updated_at = Set(now.into());
+            active.update(db).await?;
+        }
+        ArchiveKind::Playlist => {
+            let playlist = playlists::Entity::find_by_id(id)
+                .filter(profile::playlists_in_profile(profile_id))
+                .one(db)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Playlist {} not found", id)))?;
+            if playlist.archived_at.is_none() {
+                return Ok(());
+            }
+            let mut active: playlists::ActiveModel = playlist.into();
+            active.archived_at = Set(None);
+            active.is_external = Set(true);
+            active.updated_at = Set(now.into());
+            active.update(db).await?;
+        }
+        ArchiveKind::Track => {
+            let track = tracks::Entity::find_by_id(id)
+                .join(JoinType::InnerJoin, tracks::Relation::Albums.def())
+                .filter(profile::albums_in_profile(profile_id))
+                .one(db)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Track {} not found", id)))?;
+            if track.archived_at.is_none() {
+                return Ok(());
+            }
+            let mut active: tracks::ActiveModel = track.into();
+            active.archived_at = Set(None);
+            active.updated_at = Set(now.into());
+            active.update(db).await?;
+        }
+    }
+    Ok(())
+}
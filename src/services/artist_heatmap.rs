@@ -0,0 +1,203 @@
+//! Ownership heatmap of the artists with the most albums
+//!
+//! Each artist gets a row of cells, one per album in release order, colored
+//! by ownership status. Two queries build it: the top artists by album count,
+//! then the albums of just those artists. Cells are capped across the whole
+//! heatmap, so an artist with a huge discography can't make the page heavy.
+
+use std::collections::HashMap;
+
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, JoinType, Order, QueryFilter, QueryOrder,
+    QuerySelect, RelationTrait,
+};
+use serde::Serialize;
+
+use crate::{
+    db::{
+        entities::{albums, artists},
+        enums::OwnershipStatus,
+        profile, slug,
+    },
+    error::Result,
+};
+
+/// Artists shown when the request doesn't say
+pub const DEFAULT_HEATMAP_ARTISTS: u64 = 30;
+
+/// Most artists a single request may ask for
+pub const MAX_HEATMAP_ARTISTS: u64 = 100;
+
+/// Most album cells in one heatmap
+pub const MAX_HEATMAP_CELLS: usize = 600;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeatmapArtist {
+    pub id: i32,
+    pub name: String,
+    /// Path segment for the artist's page
+    pub slug: String,
+    pub album_count: u64,
+    /// Oldest release first; albums without a release date come last
+    pub albums: Vec<HeatmapAlbum>,
+    /// Albums left out to stay under `MAX_HEATMAP_CELLS`
+    pub hidden_albums: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeatmapAlbum {
+    pub id: i32,
+    /// Path segment for the album's detail link
+    pub slug: String,
+    pub title: String,
+    pub release_date: Option<String>,
+    pub ownership_status: String,
+}
+
+impl HeatmapAlbum {
+    pub fn status(&self) -> OwnershipStatus {
+        OwnershipStatus::from_str(&self.ownership_status).unwrap_or(OwnershipStatus::NotOwned)
+    }
+}
+
+/// The `limit` artists with the most albums in a profile's library, with
+/// each of their albums' ownership status
+///
+/// Ties in album count go to the artist first by name. Archived albums are
+/// left out, as elsewhere on the stats page.
+pub async fn artist_heatmap(db: &DatabaseConnection, profile_id: i32, limit: u64) -> Result<Vec<HeatmapArtist>> {
+    #[derive(FromQueryResult)]
+    struct ArtistCount {
+        id: i32,
+        name: String,
+        slug: Option<String>,
+        album_count: i64,
+    }
+
+    let top: Vec<ArtistCount> = artists::Entity::find()
+        .select_only()
+        .column(artists::Column::Id)
+        .column(artists::Column::Name)
+        .column(artists::Column::Slug)
+        .column_as(albums::Column::Id.count(), "album_count")
+        .join(JoinType::InnerJoin, artists::Relation::Albums.def())
+        .filter(profile::albums_in_profile(profile_id))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .group_by(artists::Column::Id)
+        .group_by(artists::Column::Name)
+        .group_by(artists::Column::Slug)
+        .order_by(albums::Column::Id.count(), Order::Desc)
+        .order_by_asc(artists::Column::Name)
+        .order_by_asc(artists::Column::Id)
+        .limit(limit.clamp(1, MAX_HEATMAP_ARTISTS))
+        .into_model()
+        .all(db)
+        .await?;
+
+    if top.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut by_artist: HashMap<i32, Vec<albums::Model>> = HashMap::new();
+    let album_rows = albums::Entity::find()
+        .filter(albums::Column::ArtistId.is_in(top.iter().map(|a| a.id)))
+        .filter(profile::albums_in_profile(profile_id))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .order_by_asc(albums::Column::ReleaseDate.is_null())
+        .order_by_asc(albums::Column::ReleaseDate)
+        .order_by_asc(albums::Column::Title)
+        .order_by_asc(albums::Column::Id)
+        .all(db)
+        .await?;
+    for album in album_rows {
+        by_artist.entry(album.artist_id).or_default().push(album);
+    }
+
+    let mut heatmap: Vec<HeatmapArtist> = top
+        .into_iter()
+        .map(|artist| HeatmapArtist {
+            slug: slug::link_key(artist.slug.as_deref(), artist.id),
+            albums: by_artist
+                .remove(&artist.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|album| HeatmapAlbum {
+                    slug: slug::link_key(album.slug.as_deref(), album.id),
+                    id: album.id,
+                    title: album.title,
+                    release_date: album.release_date.map(|d| d.to_string()),
+                    ownership_status: album.ownership_status,
+                })
+                .collect(),
+            id: artist.id,
+            name: artist.name,
+            album_count: artist.album_count as u64,
+            hidden_albums: 0,
+        })
+        .collect();
+    cap_cells(&mut heatmap, MAX_HEATMAP_CELLS);
+
+    Ok(heatmap)
+}
+
+/// Keep at most `max_cells` albums, cutting the last artists short first
+fn cap_cells(heatmap: &mut Vec<HeatmapArtist>, max_cells: usize) {
+    let mut cells_left = max_cells;
+    heatmap.retain_mut(|artist| {
+        if cells_left == 0 {
+            return false;
+        }
+        let shown = artist.albums.len().min(cells_left);
+        artist.hidden_albums = (artist.albums.len() - shown) as u64;
+        artist.albums.truncate(shown);
+        cells_left -= shown;
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artist(id: i32, albums: usize) -> HeatmapArtist {
+        HeatmapArtist {
+            id,
+            name: format!("Artist {}", id),
+            slug: format!("artist-{}", id),
+            album_count: albums as u64,
+            albums: (0..albums as i32)
+                .map(|n| HeatmapAlbum {
+                    id: id * 100 + n,
+                    slug: format!("album-{}", n),
+                    title: format!("Album {}", n),
+                    release_date: None,
+                    ownership_status: OwnershipStatus::NotOwned.as_str().to_string(),
+                })
+                .collect(),
+            hidden_albums: 0,
+        }
+    }
+
+    #[test]
+    fn test_cap_cells_cuts_the_last_artists_short() {
+        let mut heatmap = vec![artist(1, 4), artist(2, 4), artist(3, 4), artist(4, 1)];
+        cap_cells(&mut heatmap, 6);
+
+        let shown: Vec<(i32, usize, u64)> = heatmap
+            .iter()
+            .map(|a| (a.id, a.albums.len(), a.hidden_albums))
+            .collect();
+        assert_eq!(shown, vec![(1, 4, 0), (2, 2, 2)]);
+        // The album count still covers every album
+        assert_eq!(heatmap[1].album_count, 4);
+        assert_eq!(heatmap[1].albums.last().unwrap().id, 201);
+    }
+
+    #[test]
+    fn test_cap_cells_under_the_cap_keeps_everything() {
+        let mut heatmap = vec![artist(1, 3), artist(2, 2)];
+        cap_cells(&mut heatmap, 5);
+        assert_eq!(heatmap.len(), 2);
+        assert!(heatmap.iter().all(|a| a.hidden_albums == 0));
+    }
+}
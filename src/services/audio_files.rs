@@ -0,0 +1,239 @@
+//! Track positions and lengths read from local audio files
+//!
+//! The filesystem scan compares these with Spotify's track lengths to find
+//! broken or wrong rips. Lengths come from the FLAC stream info and the ID3
+//! `TLEN` frame of MP3s; other formats only give a position, taken from a
+//! leading number in the file name.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Seconds a local file may differ from Spotify's track length unless configured otherwise
+pub const DEFAULT_DURATION_TOLERANCE_SECS: i32 = 3;
+
+/// Extensions the scan treats as audio files
+pub const AUDIO_EXTENSIONS: [&str; 7] = ["mp3", "flac", "m4a", "ogg", "opus", "wav", "aac"];
+
+const FLAC_STREAMINFO: u8 = 0;
+const FLAC_VORBIS_COMMENT: u8 = 4;
+
+/// What a local file says about the track it holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalTrack {
+    pub path: String,
+    /// Disc number, 1 when the file doesn't say
+    pub disc_number: i32,
+    pub track_number: Option<i32>,
+    pub duration_ms: Option<i32>,
+}
+
+/// Position and length as read from a file's tags
+#[derive(Debug, Default, PartialEq)]
+struct TagInfo {
+    disc_number: Option<i32>,
+    track_number: Option<i32>,
+    duration_ms: Option<i32>,
+}
+
+pub fn is_audio_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// The audio files directly inside an album folder, sorted by file name
+pub fn read_album_folder(path: &Path) -> io::Result<Vec<LocalTrack>> {
+    let mut files: Vec<_> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| is_audio_file(path))
+        .collect();
+    files.sort();
+    Ok(files.iter().map(|path| read_file(path)).collect())
+}
+
+/// Read one file; unreadable tags leave the position to the file name
+fn read_file(path: &Path) -> LocalTrack {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    let tags = match extension.as_deref() {
+        Some("flac") => File::open(path).and_then(|f| read_flac(&mut BufReader::new(f))),
+        Some("mp3") => read_id3(path),
+        _ => Ok(TagInfo::default()),
+    }
+    .unwrap_or_else(|e| {
+        tracing::debug!("Failed to read tags of {:?}: {}", path, e);
+        TagInfo::default()
+    });
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    LocalTrack {
+        path: path.to_string_lossy().to_string(),
+        disc_number: tags.disc_number.unwrap_or(1),
+        track_number: tags.track_number.or_else(|| leading_number(file_name)),
+        duration_ms: tags.duration_ms,
+    }
+}
+
+fn read_id3(path: &Path) -> io::Result<TagInfo> {
+    use id3::TagLike;
+
+    let tag = id3::Tag::read_from_path(path).map_err(io::Error::other)?;
+    Ok(TagInfo {
+        disc_number: tag.disc().map(|n| n as i32),
+        track_number: tag.track().map(|n| n as i32),
+        duration_ms: tag.duration().map(|ms| ms as i32),
+    })
+}
+
+/// Walk the FLAC metadata blocks for the stream info and Vorbis comments
+fn read_flac<R: Read + Seek>(reader: &mut R) -> io::Result<TagInfo> {
+    let mut marker = [0u8; 4];
+    reader.read_exact(&mut marker)?;
+    if &marker != b"fLaC" {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a FLAC file"));
+    }
+
+    let mut info = TagInfo::default();
+    loop {
+        let mut header = [0u8; 4];
+        reader.read_exact(&mut header)?;
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7f;
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+
+        match block_type {
+            FLAC_STREAMINFO | FLAC_VORBIS_COMMENT => {
+                let mut block = vec![0u8; length];
+                reader.read_exact(&mut block)?;
+                if block_type == FLAC_STREAMINFO {
+                    info.duration_ms = streaminfo_duration_ms(&block);
+                } else {
+                    for (name, value) in vorbis_comments(&block) {
+                        match name.to_uppercase().as_str() {
+                            "TRACKNUMBER" => info.track_number = parse_position(&value),
+                            "DISCNUMBER" => info.disc_number = parse_position(&value),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {
+                reader.seek(SeekFrom::Current(length as i64))?;
+            }
+        }
+
+        if is_last {
+            return Ok(info);
+        }
+    }
+}
+
+/// Length from the sample rate and total samples of a STREAMINFO block
+fn streaminfo_duration_ms(block: &[u8]) -> Option<i32> {
+    if block.len() < 18 {
+        return None;
+    }
+    let sample_rate =
+        (u64::from(block[10]) << 12) | (u64::from(block[11]) << 4) | (u64::from(block[12]) >> 4);
+    let total_samples = (u64::from(block[13] & 0x0f) << 32)
+        | u64::from(u32::from_be_bytes([block[14], block[15], block[16], block[17]]));
+    // A total of 0 means the encoder didn't know it
+    if sample_rate == 0 || total_samples == 0 {
+        return None;
+    }
+    i32::try_from(total_samples * 1000 / sample_rate).ok()
+}
+
+/// `NAME=value` pairs of a VORBIS_COMMENT block
+fn vorbis_comments(block: &[u8]) -> Vec<(String, String)> {
+    fn read_u32(block: &[u8], at: usize) -> Option<usize> {
+        let bytes = block.get(at..at + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+    }
+
+    let mut comments = Vec::new();
+    let Some(vendor_length) = read_u32(block, 0) else {
+        return comments;
+    };
+    let mut at = 4 + vendor_length;
+    let Some(count) = read_u32(block, at) else {
+        return comments;
+    };
+    at += 4;
+    for _ in 0..count {
+        let Some(length) = read_u32(block, at) else {
+            break;
+        };
+        at += 4;
+        let Some(comment) = block.get(at..at + length) else {
+            break;
+        };
+        at += length;
+        if let Some((name, value)) = String::from_utf8_lossy(comment).split_once('=') {
+            comments.push((name.to_string(), value.to_string()));
+        }
+    }
+    comments
+}
+
+/// A track or disc number, also in the `3/12` form
+fn parse_position(value: &str) -> Option<i32> {
+    value.split('/').next()?.trim().parse().ok().filter(|n| *n > 0)
+}
+
+/// Number a file name starts with, e.g. 7 for `07 - Aerodynamic.flac`
+fn leading_number(file_name: &str) -> Option<i32> {
+    let digits: String = file_name.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok().filter(|n| *n > 0)
+}
+
+/// Whether a local length differs from Spotify's by more than `tolerance_secs`
+pub fn exceeds_tolerance(spotify_ms: i32, local_ms: i32, tolerance_secs: i32) -> bool {
+    (i64::from(spotify_ms) - i64::from(local_ms)).abs() > i64::from(tolerance_secs) * 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_flac;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_flac_stream_info_and_comments() {
+        let bytes = test_flac(212_000, Some(7), Some(2));
+        let info = read_flac(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            info,
+            TagInfo {
+                disc_number: Some(2),
+                track_number: Some(7),
+                duration_ms: Some(212_000),
+            }
+        );
+
+        assert!(read_flac(&mut Cursor::new(b"ID3\x04junk".to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_positions_from_tags_and_file_names() {
+        assert_eq!(parse_position("3/12"), Some(3));
+        assert_eq!(parse_position(" 4 "), Some(4));
+        assert_eq!(parse_position("A1"), None);
+        assert_eq!(leading_number("07 - Aerodynamic.flac"), Some(7));
+        assert_eq!(leading_number("Aerodynamic.flac"), None);
+        assert_eq!(leading_number("00 - Intro.mp3"), None);
+    }
+
+    #[test]
+    fn test_exceeds_tolerance() {
+        assert!(!exceeds_tolerance(212_000, 214_000, 3));
+        assert!(!exceeds_tolerance(212_000, 209_000, 3));
+        assert!(exceeds_tolerance(212_000, 208_999, 3));
+        assert!(exceeds_tolerance(212_000, 212_001, 0));
+    }
+}
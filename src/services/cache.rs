@@ -76,6 +76,10 @@ impl CacheService {
         format!("spotify:album:{}", spotify_id)
     }
 
+    pub fn spotify_related_artists_key(spotify_id: &str) -> String {
+        format!("spotify:related:{}", spotify_id)
+    }
+
     pub fn cover_art_key(musicbrainz_id: &str) -> String {
         format!("cover:mb:{}", musicbrainz_id)
     }
@@ -0,0 +1,154 @@
+use serde::Deserialize;
+
+use crate::{
+    db::{entities::albums, enums::GenreSource},
+    error::{AppError, Result},
+};
+
+/// How many release group tags the matching job keeps as genres
+pub const MUSICBRAINZ_GENRE_LIMIT: usize = 5;
+
+/// Most genres an album may be given by hand
+pub const MAX_GENRES: usize = 20;
+
+/// Longest genre name accepted from an edit
+pub const MAX_GENRE_LENGTH: usize = 64;
+
+/// Genres from an edit: a JSON array, or comma separated from a form
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum GenreList {
+    List(Vec<String>),
+    Text(String),
+}
+
+impl GenreList {
+    fn to_vec(&self) -> Vec<String> {
+        match self {
+            Self::List(genres) => genres.clone(),
+            Self::Text(text) => text.split(',').map(str::to_string).collect(),
+        }
+    }
+}
+
+/// An album's genres, empty when it has none or they can't be read
+pub fn album_genres(album: &albums::Model) -> Vec<String> {
+    album
+        .genres
+        .as_deref()
+        .and_then(|g| serde_json::from_str(g).ok())
+        .unwrap_or_default()
+}
+
+/// Stored form of a genre list
+pub fn to_column(genres: &[String]) -> Option<String> {
+    serde_json::to_string(genres).ok()
+}
+
+/// Trim genres and drop blanks and case-insensitive repeats, keeping the first spelling
+pub fn normalize(genres: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for genre in genres {
+        let genre = genre.trim();
+        if !genre.is_empty() && !normalized.iter().any(|g| g.eq_ignore_ascii_case(genre)) {
+            normalized.push(genre.to_string());
+        }
+    }
+    normalized
+}
+
+/// Normalize a hand-edited genre list, rejecting oversized ones
+pub fn parse_edit(list: &GenreList) -> Result<Vec<String>> {
+    let genres = normalize(list.to_vec());
+    if genres.len() > MAX_GENRES {
+        return Err(AppError::Validation(format!(
+            "An album can have at most {} genres",
+            MAX_GENRES
+        )));
+    }
+    if let Some(genre) = genres.iter().find(|g| g.chars().count() > MAX_GENRE_LENGTH) {
+        return Err(AppError::Validation(format!(
+            "Genre '{}' is longer than {} characters",
+            genre, MAX_GENRE_LENGTH
+        )));
+    }
+    Ok(genres)
+}
+
+/// Whether genres from `incoming` may replace an album's current ones
+///
+/// A hand edit always wins and is only replaced by another edit. MusicBrainz
+/// tags only fill an empty list. Spotify refreshes only genres it set itself;
+/// albums without a recorded source count as Spotify's.
+pub fn may_replace(album: &albums::Model, incoming: GenreSource) -> bool {
+    let current = album.genre_source.as_deref().and_then(GenreSource::parse);
+    match incoming {
+        GenreSource::Manual => true,
+        GenreSource::Musicbrainz => {
+            current != Some(GenreSource::Manual) && album_genres(album).is_empty()
+        }
+        GenreSource::Spotify => matches!(current, None | Some(GenreSource::Spotify)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    async fn album_with(genres: &[&str], source: Option<GenreSource>) -> albums::Model {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Daft Punk", None).await;
+        let mut album = create_test_album(&db, artist.id, "Discovery", None).await;
+        let genres: Vec<String> = genres.iter().map(|g| g.to_string()).collect();
+        album.genres = to_column(&genres);
+        album.genre_source = source.map(String::from);
+        album
+    }
+
+    #[tokio::test]
+    async fn test_manual_genres_are_only_replaced_by_edits() {
+        let album = album_with(&["french house"], Some(GenreSource::Manual)).await;
+        assert!(may_replace(&album, GenreSource::Manual));
+        assert!(!may_replace(&album, GenreSource::Musicbrainz));
+        assert!(!may_replace(&album, GenreSource::Spotify));
+
+        // Clearing them by hand keeps them cleared
+        let album = album_with(&[], Some(GenreSource::Manual)).await;
+        assert!(!may_replace(&album, GenreSource::Musicbrainz));
+    }
+
+    #[tokio::test]
+    async fn test_musicbrainz_fills_only_empty_genres() {
+        let album = album_with(&[], Some(GenreSource::Spotify)).await;
+        assert!(may_replace(&album, GenreSource::Musicbrainz));
+
+        let album = album_with(&["filter house"], Some(GenreSource::Spotify)).await;
+        assert!(!may_replace(&album, GenreSource::Musicbrainz));
+
+        let album = album_with(&["electronic"], Some(GenreSource::Musicbrainz)).await;
+        assert!(!may_replace(&album, GenreSource::Spotify));
+    }
+
+    #[tokio::test]
+    async fn test_spotify_refreshes_its_own_genres() {
+        assert!(may_replace(&album_with(&["house"], None).await, GenreSource::Spotify));
+        assert!(may_replace(&album_with(&["house"], Some(GenreSource::Spotify)).await, GenreSource::Spotify));
+    }
+
+    #[test]
+    fn test_parse_edit_normalizes_lists_and_text() {
+        let genres = parse_edit(&GenreList::Text(" House, electronic,,house ".to_string())).unwrap();
+        assert_eq!(genres, vec!["House", "electronic"]);
+
+        let genres = parse_edit(&GenreList::List(vec!["disco".to_string(), " ".to_string()])).unwrap();
+        assert_eq!(genres, vec!["disco"]);
+    }
+
+    #[test]
+    fn test_parse_edit_rejects_oversized_lists() {
+        let many: Vec<String> = (0..=MAX_GENRES).map(|i| format!("genre {}", i)).collect();
+        assert!(parse_edit(&GenreList::List(many)).is_err());
+        assert!(parse_edit(&GenreList::List(vec!["x".repeat(MAX_GENRE_LENGTH + 1)])).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::{AppError, Result};
+
+pub const LISTENBRAINZ_API_BASE: &str = "https://api.listenbrainz.org";
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most listens ListenBrainz returns per request
+const PAGE_SIZE: usize = 1000;
+
+/// Pages fetched per call; a long backlog is picked up over several syncs
+const MAX_PAGES: usize = 20;
+
+/// Reads a user's public listening history; no token is needed
+#[derive(Clone)]
+pub struct ListenBrainzService {
+    client: Client,
+    base_url: String,
+}
+
+/// One scrobbled track
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listen {
+    pub listened_at: DateTime<Utc>,
+    pub artist_name: String,
+    /// Album the track was played from, if the scrobbler reported one
+    pub release_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListensResponse {
+    payload: ListensPayload,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListensPayload {
+    listens: Vec<RawListen>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawListen {
+    listened_at: i64,
+    track_metadata: TrackMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackMetadata {
+    artist_name: String,
+    release_name: Option<String>,
+}
+
+impl ListenBrainzService {
+    pub fn new() -> Self {
+        Self::with_base_url(LISTENBRAINZ_API_BASE)
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_base_url(&config.listenbrainz_api_base)
+    }
+
+    /// Point the service at a different API host (e.g. a mock server in tests)
+    pub fn with_base_url(base_url: &str) -> Self {
+        let client = Client::builder()
+            .user_agent("BeatCollector/0.1.0")
+            .timeout(API_TIMEOUT)
+            .build()
+            .expect("Failed to build HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Listens newer than `since`, oldest first
+    ///
+    /// Stops after [`MAX_PAGES`] pages; the caller resumes from the newest
+    /// listen returned.
+    pub async fn fetch_listens_since(
+        &self,
+        username: &str,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Listen>> {
+        let mut min_ts = since.map(|t| t.timestamp()).unwrap_or(0);
+        let mut listens = Vec::new();
+
+        for _ in 0..MAX_PAGES {
+            let page = self.fetch_page(username, min_ts).await?;
+            let full_page = page.len() >= PAGE_SIZE;
+
+            match page.last() {
+                Some(newest) => min_ts = newest.listened_at.timestamp(),
+                None => break,
+            }
+            listens.extend(page);
+
+            if !full_page {
+                break;
+            }
+        }
+
+        Ok(listens)
+    }
+
+    /// One page of listens after `min_ts`, sorted oldest first
+    async fn fetch_page(&self, username: &str, min_ts: i64) -> Result<Vec<Listen>> {
+        let url = format!(
+            "{}/1/user/{}/listens",
+            self.base_url,
+            urlencoding::encode(username)
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("min_ts", min_ts.to_string()), ("count", PAGE_SIZE.to_string())])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(AppError::ExternalApi(format!(
+                "ListenBrainz API error ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let body: ListensResponse = response.json().await?;
+        let mut listens: Vec<Listen> = body
+            .payload
+            .listens
+            .into_iter()
+            .filter_map(|raw| {
+                Some(Listen {
+                    listened_at: Utc.timestamp_opt(raw.listened_at, 0).single()?,
+                    artist_name: raw.track_metadata.artist_name,
+                    release_name: raw.track_metadata.release_name.filter(|r| !r.trim().is_empty()),
+                })
+            })
+            .collect();
+
+        // The API returns newest first
+        listens.sort_by_key(|l| l.listened_at);
+        Ok(listens)
+    }
+}
+
+impl Default for ListenBrainzService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,14 +1,31 @@
 pub mod spotify;
 pub mod musicbrainz;
 pub mod lidarr;
+pub mod listenbrainz;
 pub mod cache;
+pub mod album_grid_cache;
+pub mod stats_cache;
 pub mod playlist_stats;
+pub mod playlist_cleanup;
+pub mod playlist_diff;
+pub mod ownership;
+pub mod album_artist;
+pub mod music_paths;
+pub mod archive;
+pub mod side_effects;
+pub mod genres;
+pub mod audio_files;
+pub mod suggestions;
+pub mod wanted;
+pub mod artist_heatmap;
+pub mod activity_log;
 
 pub use spotify::{
-    SpotifyService, SpotifyAlbum, SpotifyArtist, SpotifyImage,
+    SpotifyService, SpotifyAlbum, SavedAlbumsPage, SpotifyArtist, SpotifyExternalIds, SpotifyImage,
     SpotifyPlaylist, SpotifyPlaylistOwner, SpotifyPlaylistTracksRef,
-    SpotifyPlaylistTrack, SpotifyTrack,
+    SpotifyPlaylistTrack, SpotifyTrack, SpotifyLinkedTrack, parse_playlist_id,
 };
 pub use musicbrainz::MusicBrainzService;
-pub use lidarr::{LidarrService, LidarrWebhook, LidarrArtist, LidarrAlbum, TrackFile};
+pub use lidarr::{LidarrService, LidarrWebhook, LidarrArtist, LidarrArtistLookup, LidarrAlbum, TrackFile};
+pub use listenbrainz::{ListenBrainzService, Listen};
 pub use cache::CacheService;
@@ -0,0 +1,191 @@
+//! Consistency between the paths Lidarr reports and the music folder
+//!
+//! Lidarr often runs in a container that mounts the library somewhere else,
+//! e.g. `/data/music` while `music_folder_path` is `/music`. Paths from its
+//! webhooks are rewritten with the `path_mappings` setting. Album folders that
+//! still fall outside the music folder are recorded as a warning, shown on
+//! the settings page until a later check finds the paths in order.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+
+use crate::{
+    db::{entities::user_settings, settings as db_settings},
+    error::{AppError, Result},
+};
+
+/// From-prefix to to-prefix rewrites, as stored in `path_mappings`
+pub type PathMappings = BTreeMap<String, String>;
+
+/// Mappings from the stored setting; unreadable JSON counts as none
+pub fn parse_mappings(raw: Option<&str>) -> PathMappings {
+    raw.and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default()
+}
+
+/// Check mappings given in a settings update: both sides absolute paths
+pub fn validate_mappings(mappings: &PathMappings) -> Result<()> {
+    for (from, to) in mappings {
+        if !from.starts_with('/') || !to.starts_with('/') {
+            return Err(AppError::Validation(format!(
+                "Path mappings must map absolute paths, got {} -> {}",
+                from, to
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite `path` with the mapping whose from-prefix covers the most of it
+///
+/// Prefixes match whole path components, so `/data/music` doesn't rewrite
+/// `/data/musicvideos`.
+pub fn apply_mappings(path: &str, mappings: &PathMappings) -> String {
+    mappings
+        .iter()
+        .filter_map(|(from, to)| {
+            let from = Path::new(from);
+            let rest = Path::new(path).strip_prefix(from).ok()?;
+            let mapped = if rest.as_os_str().is_empty() {
+                Path::new(to).to_path_buf()
+            } else {
+                Path::new(to).join(rest)
+            };
+            Some((from.components().count(), mapped))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, mapped)| mapped.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Library folder of an album folder, going by the `<Artist>/<Album>` layout
+fn library_root(album_dir: &str) -> String {
+    Path::new(album_dir)
+        .parent()
+        .and_then(Path::parent)
+        .filter(|root| !root.as_os_str().is_empty())
+        .map(|root| root.to_string_lossy().to_string())
+        .unwrap_or_else(|| album_dir.to_string())
+}
+
+/// Library folders of the album folders outside `music_folder`, sorted and deduplicated
+pub fn mismatched_prefixes<'a>(
+    album_dirs: impl IntoIterator<Item = &'a str>,
+    music_folder: &str,
+) -> Vec<String> {
+    let mut prefixes: Vec<String> = album_dirs
+        .into_iter()
+        .filter(|dir| !Path::new(dir).starts_with(music_folder))
+        .map(library_root)
+        .collect();
+    prefixes.sort();
+    prefixes.dedup();
+    prefixes
+}
+
+/// Prefixes recorded by the last path check, from the stored setting
+pub fn parse_mismatches(raw: Option<&str>) -> Vec<String> {
+    raw.and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default()
+}
+
+/// Record, or clear, the mismatch warning for the album folders just seen
+///
+/// The music folder and the warning are those of `profile_id`'s settings
+/// (the default profile if `None`). Nothing is checked until a music folder
+/// is configured. Returns the mismatched library folders.
+pub async fn record_path_check(
+    db: &DatabaseConnection,
+    profile_id: Option<i32>,
+    album_dirs: &[String],
+) -> Result<Vec<String>> {
+    if album_dirs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(settings) = db_settings::for_profile_id(db, profile_id).await? else {
+        return Ok(Vec::new());
+    };
+    let Some(music_folder) = settings.music_folder_path.clone().filter(|f| !f.is_empty()) else {
+        return Ok(Vec::new());
+    };
+
+    let mismatched = mismatched_prefixes(album_dirs.iter().map(String::as_str), &music_folder);
+    let stored = if mismatched.is_empty() { None } else { Some(serde_json::to_string(&mismatched)?) };
+    if stored == settings.path_mismatch_prefixes {
+        return Ok(mismatched);
+    }
+
+    if mismatched.is_empty() {
+        tracing::info!("Lidarr paths are inside the music folder again");
+    } else {
+        tracing::warn!(
+            "Lidarr reported albums under {} but the music folder is {}; check path_mappings",
+            mismatched.join(", "),
+            music_folder
+        );
+    }
+
+    let mut active: user_settings::ActiveModel = settings.into();
+    active.path_mismatch_prefixes = Set(stored);
+    active.updated_at = Set(Utc::now().into());
+    active.update(db).await?;
+
+    Ok(mismatched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mappings(pairs: &[(&str, &str)]) -> PathMappings {
+        pairs.iter().map(|(from, to)| (from.to_string(), to.to_string())).collect()
+    }
+
+    #[test]
+    fn test_apply_mappings_rewrites_prefix() {
+        let mappings = mappings(&[("/data/music", "/music")]);
+
+        assert_eq!(apply_mappings("/data/music/Daft Punk/Discovery", &mappings), "/music/Daft Punk/Discovery");
+        assert_eq!(apply_mappings("/data/music/", &mappings), "/music");
+        assert_eq!(apply_mappings("/downloads/Discovery", &mappings), "/downloads/Discovery");
+    }
+
+    #[test]
+    fn test_apply_mappings_matches_whole_components() {
+        let mappings = mappings(&[("/data/music", "/music")]);
+
+        assert_eq!(apply_mappings("/data/musicvideos/Live", &mappings), "/data/musicvideos/Live");
+    }
+
+    #[test]
+    fn test_apply_mappings_prefers_longest_prefix() {
+        let mappings = mappings(&[("/data", "/mnt/data"), ("/data/music/", "/music")]);
+
+        assert_eq!(apply_mappings("/data/music/Air/Moon Safari", &mappings), "/music/Air/Moon Safari");
+        assert_eq!(apply_mappings("/data/books/Dune", &mappings), "/mnt/data/books/Dune");
+    }
+
+    #[test]
+    fn test_validate_mappings_requires_absolute_paths() {
+        assert!(validate_mappings(&mappings(&[("/data/music", "/music")])).is_ok());
+        assert!(validate_mappings(&mappings(&[("data/music", "/music")])).is_err());
+        assert!(validate_mappings(&mappings(&[("/data/music", "music")])).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_prefixes_reports_library_roots() {
+        let dirs = [
+            "/music/Air/Moon Safari",
+            "/data/music/Daft Punk/Discovery",
+            "/data/music/Daft Punk/Homework",
+            "/downloads/Radiohead/OK Computer",
+        ];
+
+        assert_eq!(
+            mismatched_prefixes(dirs, "/music"),
+            vec!["/data/music".to_string(), "/downloads".to_string()]
+        );
+        assert!(mismatched_prefixes(["/music/Air/Moon Safari"], "/music/").is_empty());
+    }
+}
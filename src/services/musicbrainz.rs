@@ -12,11 +12,15 @@ use crate::error::{AppError, Result};
 const MUSICBRAINZ_API_BASE: &str = "https://musicbrainz.org/ws/2";
 const COVER_ART_ARCHIVE_BASE: &str = "https://coverartarchive.org";
 const RATE_LIMIT_DELAY: Duration = Duration::from_secs(1); // 1 request per second
+/// Minimum gap between Cover Art Archive requests, across concurrent downloads
+const COVER_ART_DELAY: Duration = Duration::from_millis(100);
 
 #[derive(Clone)]
 pub struct MusicBrainzService {
     client: Client,
+    base_url: String,
     last_request: Arc<Mutex<Option<Instant>>>,
+    last_cover_art_request: Arc<Mutex<Option<Instant>>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,8 +61,48 @@ struct ReleaseGroup {
     first_release_date: Option<String>,
 }
 
+/// A folksonomy tag and how many users applied it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    #[serde(default)]
+    pub count: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagLookupResponse {
+    #[serde(default)]
+    tags: Vec<Tag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseSearchResponse {
+    releases: Vec<Release>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    score: i32,
+    #[serde(rename = "artist-credit", default)]
+    artist_credit: Vec<ArtistCredit>,
+    date: Option<String>,
+    #[serde(rename = "release-group")]
+    release_group: Option<ReleaseGroupRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseGroupRef {
+    id: Uuid,
+    title: String,
+}
+
 impl MusicBrainzService {
     pub fn new(user_agent: String) -> Self {
+        Self::with_base_url(user_agent, MUSICBRAINZ_API_BASE.to_string())
+    }
+
+    /// Create a service pointing at a different MusicBrainz API (e.g. a mirror)
+    pub fn with_base_url(user_agent: String, base_url: String) -> Self {
         let client = Client::builder()
             .user_agent(user_agent)
             .timeout(Duration::from_secs(30))
@@ -67,7 +111,9 @@ impl MusicBrainzService {
 
         Self {
             client,
+            base_url: base_url.trim_end_matches('/').to_string(),
             last_request: Arc::new(Mutex::new(None)),
+            last_cover_art_request: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -115,6 +161,53 @@ impl MusicBrainzService {
         Ok(filtered)
     }
 
+    /// Look up the release group for an exact UPC/EAN barcode
+    ///
+    /// Barcodes identify a specific release, so only exact (score 100) hits are returned.
+    pub async fn search_by_barcode(&self, barcode: &str) -> Result<Option<MusicBrainzMatch>> {
+        self.wait_for_rate_limit().await;
+
+        let url = format!(
+            "{}/release?query={}&fmt=json&limit=5",
+            self.base_url,
+            urlencoding::encode(&format!("barcode:{}", barcode))
+        );
+
+        tracing::debug!("MusicBrainz barcode lookup: {}", barcode);
+
+        let data: ReleaseSearchResponse = self.get_json(&url).await?;
+        Ok(Self::barcode_match(data))
+    }
+
+    /// Pick the release group of the first exact barcode hit
+    fn barcode_match(response: ReleaseSearchResponse) -> Option<MusicBrainzMatch> {
+        response
+            .releases
+            .into_iter()
+            .filter(|release| release.score >= 100)
+            .find_map(|release| {
+                release.release_group.map(|rg| MusicBrainzMatch {
+                    id: rg.id,
+                    title: rg.title,
+                    artist_credit: release.artist_credit,
+                    score: release.score,
+                    first_release_date: release.date,
+                })
+            })
+    }
+
+    /// Tags of a release group, most applied first
+    pub async fn fetch_release_group_tags(&self, mbid: Uuid) -> Result<Vec<Tag>> {
+        self.wait_for_rate_limit().await;
+
+        let url = format!("{}/release-group/{}?inc=tags&fmt=json", self.base_url, mbid);
+        let data: TagLookupResponse = self.get_json(&url).await?;
+
+        let mut tags = data.tags;
+        tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        Ok(tags)
+    }
+
     /// Fetch cover art for a release group
     pub async fn fetch_cover_art(&self, mbid: Uuid, size: CoverArtSize) -> Result<Vec<u8>> {
         let url = match size {
@@ -123,8 +216,9 @@ impl MusicBrainzService {
             CoverArtSize::Large => format!("{}/release-group/{}/front-1200", COVER_ART_ARCHIVE_BASE, mbid),
         };
 
-        // Note: Cover Art Archive has no rate limit, but we'll be respectful
-        sleep(Duration::from_millis(100)).await;
+        // Note: Cover Art Archive has no rate limit, but we'll be respectful.
+        // Clones share the last request time, so parallel downloads are spaced too.
+        Self::wait_since(&self.last_cover_art_request, COVER_ART_DELAY).await;
 
         let response = self.client.get(&url).send().await?;
 
@@ -144,11 +238,28 @@ impl MusicBrainzService {
     async fn execute_search(&self, query: &str) -> Result<Vec<MusicBrainzMatch>> {
         let url = format!(
             "{}/release-group?query={}&fmt=json&limit=10",
-            MUSICBRAINZ_API_BASE,
+            self.base_url,
             urlencoding::encode(query)
         );
 
-        let response = self.client.get(&url).send().await?;
+        let data: SearchResponse = self.get_json(&url).await?;
+
+        Ok(data
+            .release_groups
+            .into_iter()
+            .map(|rg| MusicBrainzMatch {
+                id: rg.id,
+                title: rg.title,
+                artist_credit: rg.artist_credit,
+                score: rg.score,
+                first_release_date: rg.first_release_date,
+            })
+            .collect())
+    }
+
+    /// GET a MusicBrainz API URL and decode the JSON body
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let response = self.client.get(url).send().await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -169,29 +280,22 @@ impl MusicBrainzService {
             )));
         }
 
-        let data: SearchResponse = response.json().await?;
-
-        Ok(data
-            .release_groups
-            .into_iter()
-            .map(|rg| MusicBrainzMatch {
-                id: rg.id,
-                title: rg.title,
-                artist_credit: rg.artist_credit,
-                score: rg.score,
-                first_release_date: rg.first_release_date,
-            })
-            .collect())
+        Ok(response.json().await?)
     }
 
     /// Enforce 1 request per second rate limit
     async fn wait_for_rate_limit(&self) {
-        let mut last_request = self.last_request.lock().await;
+        Self::wait_since(&self.last_request, RATE_LIMIT_DELAY).await;
+    }
+
+    /// Wait until `delay` has passed since the last request, then record this one
+    async fn wait_since(last_request: &Mutex<Option<Instant>>, delay: Duration) {
+        let mut last_request = last_request.lock().await;
 
         if let Some(last) = *last_request {
             let elapsed = last.elapsed();
-            if elapsed < RATE_LIMIT_DELAY {
-                let wait_time = RATE_LIMIT_DELAY - elapsed;
+            if elapsed < delay {
+                let wait_time = delay - elapsed;
                 tracing::debug!("Rate limiting: waiting {:?}", wait_time);
                 sleep(wait_time).await;
             }
@@ -243,4 +347,81 @@ mod tests {
             "Artist"
         );
     }
+
+    #[tokio::test]
+    async fn test_cover_art_requests_are_spaced_across_clones() {
+        let service = MusicBrainzService::new("Test/1.0".to_string());
+        let started = Instant::now();
+
+        let waits: Vec<_> = (0..3)
+            .map(|_| {
+                let service = service.clone();
+                tokio::spawn(async move {
+                    MusicBrainzService::wait_since(&service.last_cover_art_request, COVER_ART_DELAY).await
+                })
+            })
+            .collect();
+        for wait in waits {
+            wait.await.unwrap();
+        }
+
+        // The first request goes right away, the other two wait their turn
+        assert!(started.elapsed() >= COVER_ART_DELAY * 2);
+    }
+
+    #[test]
+    fn test_barcode_match_uses_release_group() {
+        let response: ReleaseSearchResponse = serde_json::from_str(
+            r#"{
+                "releases": [
+                    {
+                        "id": "b84ee12a-09ef-421b-82de-0441a926375b",
+                        "score": 100,
+                        "title": "Discovery",
+                        "date": "2001-03-12",
+                        "artist-credit": [
+                            {
+                                "name": "Daft Punk",
+                                "artist": {
+                                    "id": "056e4f3e-d505-4dad-8ec1-d04f521cbb56",
+                                    "name": "Daft Punk"
+                                }
+                            }
+                        ],
+                        "release-group": {
+                            "id": "48117b82-8b61-3b57-a9e1-0d9bd29e8ba6",
+                            "title": "Discovery",
+                            "primary-type": "Album"
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let matched = MusicBrainzService::barcode_match(response).expect("Should match");
+        assert_eq!(matched.id.to_string(), "48117b82-8b61-3b57-a9e1-0d9bd29e8ba6");
+        assert_eq!(matched.score, 100);
+        assert_eq!(matched.artist_credit[0].name, "Daft Punk");
+    }
+
+    #[test]
+    fn test_barcode_match_ignores_inexact_hits() {
+        let response: ReleaseSearchResponse = serde_json::from_str(
+            r#"{
+                "releases": [
+                    {
+                        "score": 62,
+                        "release-group": {
+                            "id": "48117b82-8b61-3b57-a9e1-0d9bd29e8ba6",
+                            "title": "Discovery"
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(MusicBrainzService::barcode_match(response).is_none());
+    }
 }
@@ -0,0 +1,169 @@
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ConnectionTrait, EntityTrait, Set};
+
+use crate::{
+    db::{
+        entities::{albums, ownership_events},
+        enums::OwnershipStatus,
+    },
+    error::{AppError, Result},
+    events::{DomainEvent, EventBus},
+};
+
+/// Reason given when an owned album's files are known to be gone. It lets
+/// an album leave `Owned` without `force`.
+pub const FILES_MISSING: &str = "files_missing";
+
+pub const REASON_MANUAL: &str = "manual";
+pub const REASON_ARTIST_MARKED_OWNED: &str = "artist_marked_owned";
+pub const REASON_LIDARR_SEARCH: &str = "lidarr_search";
+pub const REASON_LIDARR_GRAB: &str = "lidarr_grab";
+pub const REASON_LIDARR_IMPORT: &str = "lidarr_import";
+pub const REASON_LIDARR_FAILURE: &str = "lidarr_download_failed";
+pub const REASON_LIDARR_RETRY: &str = "lidarr_retry";
+pub const REASON_FILESYSTEM_SCAN: &str = "filesystem_scan";
+
+/// Stored status of an album; unrecognised values count as not owned
+pub fn current_status(album: &albums::Model) -> OwnershipStatus {
+    OwnershipStatus::from_str(&album.ownership_status).unwrap_or(OwnershipStatus::NotOwned)
+}
+
+/// Put `to` on an album about to be saved, restarting or clearing
+/// `wanted_since` in the same write
+///
+/// Moving to not owned starts the wanted clock; moving on stops it. A move
+/// that leaves the status unchanged keeps the clock as it is.
+pub fn set_status(active: &mut albums::ActiveModel, from: OwnershipStatus, to: OwnershipStatus) {
+    active.ownership_status = Set(to.as_str().to_string());
+    if from != to {
+        active.wanted_since = Set((to == OwnershipStatus::NotOwned).then(|| Utc::now().into()));
+    }
+}
+
+/// Check a move against the transition rules
+///
+/// A `files_missing` reason overrides the guard the same way `force` does.
+pub fn check_transition(
+    from: OwnershipStatus,
+    to: OwnershipStatus,
+    reason: &str,
+    force: bool,
+) -> Result<()> {
+    if from.can_transition_to(to, force || reason == FILES_MISSING) {
+        Ok(())
+    } else {
+        Err(AppError::OwnershipConflict { from, to })
+    }
+}
+
+/// Add a change to the ownership audit trail. Moves that leave the status
+/// unchanged are not recorded.
+///
+/// Pass the transaction that saves the album, so the trail holds every
+/// committed change and nothing that was rolled back.
+pub async fn record_transition<C: ConnectionTrait>(
+    db: &C,
+    album_id: i32,
+    from: OwnershipStatus,
+    to: OwnershipStatus,
+    reason: &str,
+    forced: bool,
+) -> Result<()> {
+    if from == to {
+        return Ok(());
+    }
+
+    ownership_events::ActiveModel {
+        album_id: Set(album_id),
+        from_status: Set(from.as_str().to_string()),
+        to_status: Set(to.as_str().to_string()),
+        reason: Set(reason.to_string()),
+        forced: Set(forced),
+        created_at: Set(Utc::now().into()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Record the same move for many albums at once, e.g. after a bulk update
+pub async fn record_transitions<C: ConnectionTrait>(
+    db: &C,
+    changes: &[(i32, OwnershipStatus)],
+    to: OwnershipStatus,
+    reason: &str,
+) -> Result<()> {
+    let now = Utc::now();
+    let events: Vec<ownership_events::ActiveModel> = changes
+        .iter()
+        .filter(|(_, from)| *from != to)
+        .map(|(album_id, from)| ownership_events::ActiveModel {
+            album_id: Set(*album_id),
+            from_status: Set(from.as_str().to_string()),
+            to_status: Set(to.as_str().to_string()),
+            reason: Set(reason.to_string()),
+            forced: Set(false),
+            created_at: Set(now.into()),
+            ..Default::default()
+        })
+        .collect();
+
+    if !events.is_empty() {
+        ownership_events::Entity::insert_many(events).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+/// Announce a recorded change to the event subscribers, such as the playlist
+/// stats. Moves that leave the status unchanged are not announced.
+///
+/// Publish only once the change is committed: subscribers read the album
+/// from the database as soon as the event arrives.
+pub fn publish_transition(
+    events: &EventBus,
+    album_id: i32,
+    from: OwnershipStatus,
+    to: OwnershipStatus,
+    reason: &str,
+    forced: bool,
+) {
+    if from == to {
+        return;
+    }
+
+    events.publish(DomainEvent::OwnershipChanged {
+        album_id,
+        from,
+        to,
+        reason: reason.to_string(),
+        forced,
+    });
+}
+
+/// Announce the same move for many albums at once
+pub fn publish_transitions(events: &EventBus, changes: &[(i32, OwnershipStatus)], to: OwnershipStatus, reason: &str) {
+    for (album_id, from) in changes {
+        publish_transition(events, *album_id, *from, to, reason, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_missing_reason_overrides_guard() {
+        let from = OwnershipStatus::Owned;
+        let to = OwnershipStatus::NotOwned;
+
+        assert!(matches!(
+            check_transition(from, to, REASON_MANUAL, false),
+            Err(AppError::OwnershipConflict { .. })
+        ));
+        assert!(check_transition(from, to, FILES_MISSING, false).is_ok());
+        assert!(check_transition(from, to, REASON_MANUAL, true).is_ok());
+    }
+}
@@ -0,0 +1,375 @@
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, TransactionTrait,
+};
+use tracing::{info, warn};
+
+use crate::{
+    db::{
+        entities::{playlist_tracks, playlists, profile_playlists},
+        profile, settings as db_settings,
+    },
+    error::AppError,
+};
+
+/// Failed syncs in a row before a playlist is disabled unless configured otherwise
+pub const DEFAULT_PLAYLIST_FAILURE_THRESHOLD: i32 = 3;
+
+/// Whether track memberships of disabled playlists should be purged (off by default)
+///
+/// Settings are those of `profile_id`, or of the default profile if `None`.
+pub async fn purge_enabled(db: &DatabaseConnection, profile_id: Option<i32>) -> Result<bool> {
+    Ok(db_settings::for_profile_id(db, profile_id)
+        .await?
+        .and_then(|s| s.purge_disabled_playlist_tracks)
+        .unwrap_or(false))
+}
+
+/// Whether collaborative playlists are synced (on by default)
+pub async fn sync_collaborative_enabled(db: &DatabaseConnection, profile_id: Option<i32>) -> Result<bool> {
+    Ok(db_settings::for_profile_id(db, profile_id)
+        .await?
+        .and_then(|s| s.sync_collaborative_playlists)
+        .unwrap_or(true))
+}
+
+/// Remove the collaborative playlists from a profile's library
+///
+/// Used when the profile switches collaborative syncing off. A playlist another
+/// profile also follows is only unlinked from this one; the rest are deleted
+/// along with their track memberships. Albums imported from these playlists
+/// stay in the library.
+pub async fn remove_collaborative_playlists(db: &DatabaseConnection, profile_id: i32) -> Result<u64> {
+    let ids: Vec<i32> = playlists::Entity::find()
+        .filter(playlists::Column::IsCollaborative.eq(true))
+        .filter(playlists::Column::IsSynthetic.eq(false))
+        .filter(profile::playlists_in_profile(profile_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let followed_elsewhere: Vec<i32> = profile_playlists::Entity::find()
+        .filter(profile_playlists::Column::PlaylistId.is_in(ids.clone()))
+        .filter(profile_playlists::Column::ProfileId.ne(profile_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|link| link.playlist_id)
+        .collect();
+
+    let txn = db.begin().await?;
+    let unlinked = profile_playlists::Entity::delete_many()
+        .filter(profile_playlists::Column::ProfileId.eq(profile_id))
+        .filter(profile_playlists::Column::PlaylistId.is_in(followed_elsewhere.clone()))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+    let deleted = playlists::Entity::delete_many()
+        .filter(playlists::Column::Id.is_in(ids))
+        .filter(playlists::Column::Id.is_not_in(followed_elsewhere))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+    txn.commit().await?;
+
+    info!(
+        "Removed {} collaborative playlists from profile {} ({} still followed by another profile)",
+        deleted + unlinked,
+        profile_id,
+        unlinked
+    );
+    Ok(deleted + unlinked)
+}
+
+/// Delete all track memberships of a playlist, keeping the playlist record
+///
+/// Snapshot and sync time are cleared so re-enabling the playlist triggers a
+/// full track sync instead of being skipped as unchanged.
+pub async fn purge_playlist_tracks(db: &DatabaseConnection, playlist: playlists::Model) -> Result<u64> {
+    let deleted = playlist_tracks::Entity::delete_many()
+        .filter(playlist_tracks::Column::PlaylistId.eq(playlist.id))
+        .exec(db)
+        .await?
+        .rows_affected;
+
+    let name = playlist.name.clone();
+    let mut active: playlists::ActiveModel = playlist.into();
+    active.snapshot_id = Set(None);
+    active.last_synced_at = Set(None);
+    active.owned_count = Set(Some(0));
+    active.updated_at = Set(Utc::now().into());
+    active.update(db).await?;
+
+    info!("Purged {} tracks from disabled playlist '{}'", deleted, name);
+    Ok(deleted)
+}
+
+/// Purge a playlist's tracks if it is disabled and `profile_id` has purging turned on
+pub async fn purge_if_disabled(
+    db: &DatabaseConnection,
+    profile_id: Option<i32>,
+    playlist: &playlists::Model,
+) -> Result<u64> {
+    if playlist.is_enabled || !purge_enabled(db, profile_id).await? {
+        return Ok(0);
+    }
+    purge_playlist_tracks(db, playlist.clone()).await
+}
+
+/// Purge the tracks of every disabled playlist in a profile's library (e.g.
+/// when the profile switches the setting on)
+pub async fn purge_all_disabled(db: &DatabaseConnection, profile_id: i32) -> Result<u64> {
+    let disabled = playlists::Entity::find()
+        .filter(playlists::Column::IsEnabled.eq(false))
+        .filter(profile::playlists_in_profile(profile_id))
+        .all(db)
+        .await?;
+
+    let mut total = 0;
+    for playlist in disabled {
+        total += purge_playlist_tracks(db, playlist).await?;
+    }
+    Ok(total)
+}
+
+/// Failed syncs in a row after which a playlist is disabled
+pub async fn sync_failure_threshold(db: &DatabaseConnection, profile_id: Option<i32>) -> Result<i32> {
+    Ok(db_settings::for_profile_id(db, profile_id)
+        .await?
+        .and_then(|s| s.playlist_failure_threshold)
+        .unwrap_or(DEFAULT_PLAYLIST_FAILURE_THRESHOLD))
+}
+
+/// Whether a failed track fetch counts towards disabling the playlist
+///
+/// Only a playlist that is gone or no longer readable (Spotify's 404 and
+/// 403) counts. Outages and rate limits say nothing about the playlist.
+pub fn counts_as_sync_failure(error: &AppError) -> bool {
+    matches!(error, AppError::NotFound(_) | AppError::Authentication(_))
+}
+
+/// Count a failed sync of `playlist`, disabling it once `threshold` failures
+/// happened in a row
+///
+/// The reason is kept on the playlist so the UI can say why it stopped syncing.
+pub async fn record_sync_failure(
+    db: &DatabaseConnection,
+    profile_id: Option<i32>,
+    playlist: playlists::Model,
+    error: &str,
+    threshold: i32,
+) -> Result<playlists::Model> {
+    let failures = playlist.sync_failure_count + 1;
+    let name = playlist.name.clone();
+
+    let mut active: playlists::ActiveModel = playlist.into();
+    active.sync_failure_count = Set(failures);
+    if failures >= threshold {
+        active.is_enabled = Set(false);
+        active.disabled_reason = Set(Some(format!(
+            "Disabled after {} failed syncs in a row: {}",
+            failures, error
+        )));
+        warn!("Disabled playlist '{}' after {} failed syncs", name, failures);
+    }
+    active.updated_at = Set(Utc::now().into());
+    let playlist = active.update(db).await?;
+
+    purge_if_disabled(db, profile_id, &playlist).await?;
+    Ok(playlist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::entities::user_settings;
+    use crate::test_utils::*;
+    use sea_orm::PaginatorTrait;
+
+    async fn create_playlist(db: &DatabaseConnection, spotify_id: &str, enabled: bool) -> playlists::Model {
+        create_playlist_with(db, spotify_id, enabled, false).await
+    }
+
+    async fn create_playlist_with(
+        db: &DatabaseConnection,
+        spotify_id: &str,
+        enabled: bool,
+        collaborative: bool,
+    ) -> playlists::Model {
+        let now = Utc::now().into();
+        playlists::ActiveModel {
+            name: Set(format!("Playlist {}", spotify_id)),
+            spotify_id: Set(spotify_id.to_string()),
+            is_collaborative: Set(collaborative),
+            total_tracks: Set(Some(1)),
+            snapshot_id: Set(Some("snapshot".to_string())),
+            is_enabled: Set(enabled),
+            is_synthetic: Set(false),
+            last_synced_at: Set(Some(now)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    /// A playlist in the default profile's library
+    async fn create_library_playlist(
+        db: &DatabaseConnection,
+        spotify_id: &str,
+        enabled: bool,
+        collaborative: bool,
+    ) -> playlists::Model {
+        let playlist = create_playlist_with(db, spotify_id, enabled, collaborative).await;
+        let default_profile = profile::default_profile(db).await.unwrap();
+        profile::link_playlist(db, default_profile.id, playlist.id).await.unwrap();
+        playlist
+    }
+
+    async fn add_track(db: &DatabaseConnection, playlist_id: i32, track_id: i32) {
+        let now = Utc::now().into();
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist_id),
+            track_id: Set(track_id),
+            position: Set(0),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    async fn create_track(db: &DatabaseConnection) -> i32 {
+        use crate::db::entities::tracks;
+
+        let artist = create_test_artist(db, "Artist", None).await;
+        let album = create_test_album(db, artist.id, "Album", None).await;
+        let now = Utc::now().into();
+        tracks::ActiveModel {
+            album_id: Set(album.id),
+            title: Set("Track".to_string()),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn set_purge(db: &DatabaseConnection, enabled: bool) {
+        let now = Utc::now().into();
+        user_settings::ActiveModel {
+            album_view: Set("grid".to_string()),
+            purge_disabled_playlist_tracks: Set(Some(enabled)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    async fn track_count(db: &DatabaseConnection, playlist_id: i32) -> u64 {
+        playlist_tracks::Entity::find()
+            .filter(playlist_tracks::Column::PlaylistId.eq(playlist_id))
+            .count(db)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_purge_disabled_by_default() {
+        let db = setup_test_db().await;
+        let track_id = create_track(&db).await;
+        let playlist = create_playlist(&db, "a", false).await;
+        add_track(&db, playlist.id, track_id).await;
+
+        assert_eq!(purge_if_disabled(&db, None, &playlist).await.unwrap(), 0);
+        assert_eq!(track_count(&db, playlist.id).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_if_disabled_keeps_playlist() {
+        let db = setup_test_db().await;
+        set_purge(&db, true).await;
+        let track_id = create_track(&db).await;
+        let playlist = create_playlist(&db, "a", false).await;
+        add_track(&db, playlist.id, track_id).await;
+
+        assert_eq!(purge_if_disabled(&db, None, &playlist).await.unwrap(), 1);
+        assert_eq!(track_count(&db, playlist.id).await, 0);
+
+        let kept = playlists::Entity::find_by_id(playlist.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(kept.snapshot_id, None);
+        assert_eq!(kept.last_synced_at, None);
+        assert_eq!(kept.owned_count, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_purge_skips_enabled_playlists() {
+        let db = setup_test_db().await;
+        set_purge(&db, true).await;
+        let track_id = create_track(&db).await;
+        let enabled = create_library_playlist(&db, "a", true, false).await;
+        let disabled = create_library_playlist(&db, "b", false, false).await;
+        add_track(&db, enabled.id, track_id).await;
+        add_track(&db, disabled.id, track_id).await;
+
+        let default_profile = profile::default_profile(&db).await.unwrap();
+        assert_eq!(purge_if_disabled(&db, None, &enabled).await.unwrap(), 0);
+        assert_eq!(purge_all_disabled(&db, default_profile.id).await.unwrap(), 1);
+        assert_eq!(track_count(&db, enabled.id).await, 1);
+        assert_eq!(track_count(&db, disabled.id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_remove_collaborative_playlists() {
+        let db = setup_test_db().await;
+        let track_id = create_track(&db).await;
+        let solo = create_library_playlist(&db, "a", true, false).await;
+        let shared = create_library_playlist(&db, "b", true, true).await;
+        add_track(&db, solo.id, track_id).await;
+        add_track(&db, shared.id, track_id).await;
+
+        let default_profile = profile::default_profile(&db).await.unwrap();
+        assert!(sync_collaborative_enabled(&db, None).await.unwrap());
+        assert_eq!(remove_collaborative_playlists(&db, default_profile.id).await.unwrap(), 1);
+
+        assert!(playlists::Entity::find_by_id(shared.id).one(&db).await.unwrap().is_none());
+        assert_eq!(track_count(&db, shared.id).await, 0);
+        assert_eq!(track_count(&db, solo.id).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_record_sync_failure_disables_at_threshold() {
+        let db = setup_test_db().await;
+        let playlist = create_playlist(&db, "a", true).await;
+        assert_eq!(sync_failure_threshold(&db, None).await.unwrap(), DEFAULT_PLAYLIST_FAILURE_THRESHOLD);
+
+        let playlist = record_sync_failure(&db, None, playlist, "404 Not Found", 2).await.unwrap();
+        assert_eq!(playlist.sync_failure_count, 1);
+        assert!(playlist.is_enabled);
+        assert_eq!(playlist.disabled_reason, None);
+
+        let playlist = record_sync_failure(&db, None, playlist, "404 Not Found", 2).await.unwrap();
+        assert_eq!(playlist.sync_failure_count, 2);
+        assert!(!playlist.is_enabled);
+        assert_eq!(
+            playlist.disabled_reason.as_deref(),
+            Some("Disabled after 2 failed syncs in a row: 404 Not Found")
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder,
+    QuerySelect, RelationTrait,
+};
+use serde::Serialize;
+
+use crate::{
+    db::entities::{albums, artists, playlist_tracks, tracks},
+    services::SpotifyPlaylistTrack,
+};
+
+/// What a track sync would change in a playlist's stored memberships
+#[derive(Debug, Default, Serialize)]
+pub struct PlaylistDiff {
+    pub added: Vec<DiffTrack>,
+    pub removed: Vec<DiffTrack>,
+    pub reordered: Vec<ReorderedTrack>,
+    /// Tracks present on both sides at the same position
+    pub unchanged: usize,
+}
+
+impl PlaylistDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.reordered.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffTrack {
+    pub spotify_id: String,
+    pub track_name: String,
+    pub artist_name: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReorderedTrack {
+    pub spotify_id: String,
+    pub track_name: String,
+    pub artist_name: String,
+    pub from_position: i32,
+    pub to_position: i32,
+}
+
+/// Stored tracks of a playlist, in position order
+///
+/// Tracks without a Spotify ID can't be compared against the live playlist
+/// and are left out.
+pub async fn stored_playlist_tracks(db: &DatabaseConnection, playlist_id: i32) -> Result<Vec<DiffTrack>> {
+    #[derive(FromQueryResult)]
+    struct StoredRow {
+        spotify_id: Option<String>,
+        track_name: String,
+        artist_name: String,
+        position: i32,
+    }
+
+    let rows: Vec<StoredRow> = playlist_tracks::Entity::find()
+        .filter(playlist_tracks::Column::PlaylistId.eq(playlist_id))
+        .select_only()
+        .column_as(tracks::Column::SpotifyId, "spotify_id")
+        .column_as(tracks::Column::Title, "track_name")
+        .column_as(artists::Column::Name, "artist_name")
+        .column(playlist_tracks::Column::Position)
+        .join(JoinType::InnerJoin, playlist_tracks::Relation::Tracks.def())
+        .join(JoinType::InnerJoin, tracks::Relation::Albums.def())
+        .join(JoinType::InnerJoin, albums::Relation::Artists.def())
+        .order_by_asc(playlist_tracks::Column::Position)
+        .into_model::<StoredRow>()
+        .all(db)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(DiffTrack {
+                spotify_id: row.spotify_id?,
+                track_name: row.track_name,
+                artist_name: row.artist_name,
+                position: row.position,
+            })
+        })
+        .collect())
+}
+
+/// Compare stored tracks against the live Spotify playlist without writing anything
+///
+/// Positions are assigned the way the sync assigns them: the index in the
+/// live list, counting entries that are skipped (local files, removed tracks, podcast
+/// episodes).
+/// A track listed twice keeps its last position, as the sync stores one row
+/// per track.
+pub fn diff_playlist_tracks(stored: &[DiffTrack], live: &[SpotifyPlaylistTrack]) -> PlaylistDiff {
+    let mut live_tracks: Vec<DiffTrack> = Vec::new();
+    let mut live_index: HashMap<String, usize> = HashMap::new();
+
+    for (position, item) in live.iter().enumerate() {
+        let Some(track) = &item.track else { continue };
+        let Some(spotify_id) = track.spotify_id() else { continue };
+        if !track.is_music() {
+            continue;
+        }
+
+        let diff_track = DiffTrack {
+            spotify_id: spotify_id.to_string(),
+            track_name: track.name.clone(),
+            artist_name: track.artists.first().map(|a| a.name.clone()).unwrap_or_default(),
+            position: position as i32,
+        };
+        match live_index.get(spotify_id) {
+            Some(&i) => live_tracks[i] = diff_track,
+            None => {
+                live_index.insert(spotify_id.to_string(), live_tracks.len());
+                live_tracks.push(diff_track);
+            }
+        }
+    }
+
+    let stored_ids: HashMap<&str, &DiffTrack> =
+        stored.iter().map(|t| (t.spotify_id.as_str(), t)).collect();
+
+    let mut diff = PlaylistDiff::default();
+
+    for track in stored {
+        if !live_index.contains_key(&track.spotify_id) {
+            diff.removed.push(track.clone());
+        }
+    }
+
+    for track in live_tracks {
+        match stored_ids.get(track.spotify_id.as_str()) {
+            None => diff.added.push(track),
+            Some(existing) if existing.position != track.position => {
+                diff.reordered.push(ReorderedTrack {
+                    spotify_id: track.spotify_id,
+                    track_name: track.track_name,
+                    artist_name: track.artist_name,
+                    from_position: existing.position,
+                    to_position: track.position,
+                });
+            }
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{SpotifyAlbum, SpotifyArtist, SpotifyTrack};
+
+    fn stored(spotify_id: &str, position: i32) -> DiffTrack {
+        DiffTrack {
+            spotify_id: spotify_id.to_string(),
+            track_name: format!("Track {}", spotify_id),
+            artist_name: "Artist".to_string(),
+            position,
+        }
+    }
+
+    fn live(spotify_id: Option<&str>) -> SpotifyPlaylistTrack {
+        let album: SpotifyAlbum = serde_json::from_value(serde_json::json!({
+            "id": "album",
+            "name": "Album",
+            "artists": [],
+            "release_date": "2000",
+            "total_tracks": 1,
+            "images": []
+        }))
+        .unwrap();
+
+        SpotifyPlaylistTrack {
+            track: Some(SpotifyTrack {
+                id: spotify_id.map(String::from),
+                name: format!("Track {}", spotify_id.unwrap_or("local")),
+                track_number: 1,
+                disc_number: 1,
+                duration_ms: 1000,
+                album,
+                artists: vec![SpotifyArtist {
+                    id: "artist".to_string(),
+                    name: "Artist".to_string(),
+                }],
+                is_playable: None,
+                linked_from: None,
+                item_type: None,
+            }),
+            added_at: None,
+            is_local: spotify_id.is_none(),
+            is_episode: false,
+        }
+    }
+
+    #[test]
+    fn test_diff_detects_added_removed_and_reordered() {
+        let stored = vec![stored("a", 0), stored("b", 1), stored("c", 2)];
+        let live = vec![live(Some("a")), live(Some("c")), live(Some("d"))];
+
+        let diff = diff_playlist_tracks(&stored, &live);
+
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].spotify_id, "b");
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].spotify_id, "d");
+        assert_eq!(diff.added[0].position, 2);
+        assert_eq!(diff.reordered.len(), 1);
+        assert_eq!(diff.reordered[0].spotify_id, "c");
+        assert_eq!((diff.reordered[0].from_position, diff.reordered[0].to_position), (2, 1));
+    }
+
+    #[test]
+    fn test_diff_positions_count_skipped_entries() {
+        // The sync stores "b" at position 2 because the local file takes slot 1
+        let stored = vec![stored("a", 0), stored("b", 2)];
+        let live = vec![live(Some("a")), live(None), live(Some("b"))];
+
+        let diff = diff_playlist_tracks(&stored, &live);
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+
+    #[test]
+    fn test_diff_duplicate_keeps_last_position() {
+        let stored = vec![stored("a", 2), stored("b", 1)];
+        let live = vec![live(Some("a")), live(Some("b")), live(Some("a"))];
+
+        let diff = diff_playlist_tracks(&stored, &live);
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+}
@@ -1,19 +1,31 @@
 use anyhow::Result;
 use chrono::Utc;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, JoinType,
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    FromQueryResult, JoinType, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+    RelationTrait, Set, TransactionTrait,
 };
 use std::collections::HashSet;
 use tracing::info;
 
 use crate::db::entities::{albums, playlist_tracks, playlists, tracks};
+use crate::db::enums::OwnershipStatus;
+use crate::events::DomainEvent;
 
 /// Recalculate and update owned_count for playlists containing tracks from a specific album
 pub async fn update_playlists_for_album(db: &DatabaseConnection, album_id: i32) -> Result<()> {
-    // Find all tracks belonging to this album
+    update_playlists_for_albums(db, &[album_id]).await
+}
+
+/// Recalculate and update owned_count for playlists containing tracks from any of the albums
+pub async fn update_playlists_for_albums(db: &DatabaseConnection, album_ids: &[i32]) -> Result<()> {
+    if album_ids.is_empty() {
+        return Ok(());
+    }
+
+    // Find all tracks belonging to these albums
     let track_ids: Vec<i32> = tracks::Entity::find()
-        .filter(tracks::Column::AlbumId.eq(album_id))
+        .filter(tracks::Column::AlbumId.is_in(album_ids.iter().copied()))
         .select_only()
         .column(tracks::Column::Id)
         .into_tuple()
@@ -35,29 +47,73 @@ pub async fn update_playlists_for_album(db: &DatabaseConnection, album_id: i32)
         .await?;
 
     info!(
-        "Updating owned_count for {} playlists affected by album {}",
+        "Updating owned_count for {} playlists affected by {} album(s)",
         playlist_ids.len(),
-        album_id
+        album_ids.len()
     );
 
     // Recalculate owned_count for each affected playlist
     for playlist_id in playlist_ids {
-        let owned_count = recalculate_playlist_owned_count(db, playlist_id).await?;
+        refresh_playlist_owned_count(db, playlist_id).await?;
+    }
 
-        if let Some(playlist) = playlists::Entity::find_by_id(playlist_id).one(db).await? {
-            let mut active: playlists::ActiveModel = playlist.into();
-            active.owned_count = Set(Some(owned_count));
-            active.updated_at = Set(Utc::now().into());
-            active.update(db).await?;
-        }
+    Ok(())
+}
+
+/// Playlist stats subscriber: recount the playlists of an album that
+/// became owned or stopped being owned
+pub async fn refresh_on_event(db: DatabaseConnection, event: DomainEvent) {
+    let DomainEvent::OwnershipChanged { album_id, from, to, .. } = event else {
+        return;
+    };
+    if from != OwnershipStatus::Owned && to != OwnershipStatus::Owned {
+        return;
+    }
+
+    if let Err(e) = update_playlists_for_album(&db, album_id).await {
+        tracing::warn!("Failed to update playlist stats after ownership change of album {}: {}", album_id, e);
     }
+}
+
+/// Playlist stats subscriber, after falling behind: the skipped events may
+/// have changed any playlist, so recount them all
+pub async fn refresh_after_lag(db: DatabaseConnection) {
+    if let Err(e) = recalculate_all_playlist_stats(&db).await {
+        tracing::warn!("Failed to recount playlist stats after skipped events: {}", e);
+    }
+}
 
+/// Recount a playlist's owned tracks and store the result
+///
+/// Two ownership changes touching the same playlist can finish in either
+/// order, so the count is always recomputed from scratch rather than
+/// adjusted. The playlist row is locked first so a slower recount that
+/// started before the other change committed can't overwrite the newer one.
+async fn refresh_playlist_owned_count(db: &DatabaseConnection, playlist_id: i32) -> Result<()> {
+    let txn = db.begin().await?;
+
+    let Some(playlist) = playlists::Entity::find_by_id(playlist_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let owned_count = recalculate_playlist_owned_count(&txn, playlist_id).await?;
+
+    let mut active: playlists::ActiveModel = playlist.into();
+    active.owned_count = Set(Some(owned_count));
+    active.updated_at = Set(Utc::now().into());
+    active.update(&txn).await?;
+
+    txn.commit().await?;
     Ok(())
 }
 
 /// Calculate owned track count for a single playlist
-pub async fn recalculate_playlist_owned_count(
-    db: &DatabaseConnection,
+pub async fn recalculate_playlist_owned_count<C: ConnectionTrait>(
+    db: &C,
     playlist_id: i32,
 ) -> Result<i32> {
     // Get all playlist tracks with their album ownership status
@@ -92,12 +148,7 @@ pub async fn recalculate_all_playlist_stats(db: &DatabaseConnection) -> Result<u
     info!("Recalculating owned_count for {} playlists", count);
 
     for (i, playlist) in all_playlists.into_iter().enumerate() {
-        let owned_count = recalculate_playlist_owned_count(db, playlist.id).await?;
-
-        let mut active: playlists::ActiveModel = playlist.into();
-        active.owned_count = Set(Some(owned_count));
-        active.updated_at = Set(Utc::now().into());
-        active.update(db).await?;
+        refresh_playlist_owned_count(db, playlist.id).await?;
 
         if (i + 1) % 100 == 0 {
             info!("Processed {}/{} playlists", i + 1, count);
@@ -108,15 +159,45 @@ pub async fn recalculate_all_playlist_stats(db: &DatabaseConnection) -> Result<u
     Ok(count)
 }
 
-/// Batch fetch ownership stats for multiple playlists (for list views)
-/// Returns a map of playlist_id -> (owned_count, total_count)
-pub async fn get_batch_playlist_ownership_stats(
+/// Track counts behind a playlist's ownership percentage
+///
+/// Spotify's track total also counts entries a sync skipped, such as local
+/// files, so ownership is measured against the tracks actually synced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlaylistCounts {
+    /// Tracks synced into the playlist
+    pub synced: i64,
+    /// Synced tracks whose album is owned
+    pub owned: i64,
+    /// Track count Spotify reports for the playlist
+    pub spotify_total: i64,
+}
+
+impl PlaylistCounts {
+    pub fn ownership_percentage(&self) -> f64 {
+        if self.synced > 0 {
+            (self.owned as f64 / self.synced as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Synced, owned and Spotify track counts for a playlist
+pub async fn playlist_counts(db: &DatabaseConnection, playlist: &playlists::Model) -> Result<PlaylistCounts> {
+    let mut counts = get_batch_playlist_counts(db, std::slice::from_ref(playlist)).await?;
+    Ok(counts.remove(&playlist.id).unwrap_or_default())
+}
+
+/// Batch fetch track counts for multiple playlists (for list views)
+/// Returns a map of playlist_id -> counts, with an entry for every playlist
+pub async fn get_batch_playlist_counts(
     db: &DatabaseConnection,
-    playlist_ids: Vec<i32>,
-) -> Result<std::collections::HashMap<i32, (i64, i64)>> {
+    playlists: &[playlists::Model],
+) -> Result<std::collections::HashMap<i32, PlaylistCounts>> {
     use std::collections::HashMap;
 
-    if playlist_ids.is_empty() {
+    if playlists.is_empty() {
         return Ok(HashMap::new());
     }
 
@@ -127,8 +208,9 @@ pub async fn get_batch_playlist_ownership_stats(
         ownership_status: String,
     }
 
+    let playlist_ids: Vec<i32> = playlists.iter().map(|p| p.id).collect();
     let results: Vec<PlaylistTrackOwnership> = playlist_tracks::Entity::find()
-        .filter(playlist_tracks::Column::PlaylistId.is_in(playlist_ids.clone()))
+        .filter(playlist_tracks::Column::PlaylistId.is_in(playlist_ids))
         .select_only()
         .column(playlist_tracks::Column::PlaylistId)
         .column(albums::Column::OwnershipStatus)
@@ -138,24 +220,28 @@ pub async fn get_batch_playlist_ownership_stats(
         .all(db)
         .await?;
 
-    // Aggregate results by playlist_id
-    let mut stats_map: HashMap<i32, (i64, i64)> = HashMap::new();
-
-    // Initialize all requested playlist_ids with (0, 0)
-    for id in &playlist_ids {
-        stats_map.insert(*id, (0, 0));
-    }
+    // Initialize every requested playlist, so empty ones still report their Spotify total
+    let mut counts_map: HashMap<i32, PlaylistCounts> = playlists
+        .iter()
+        .map(|p| {
+            let counts = PlaylistCounts {
+                spotify_total: p.total_tracks.unwrap_or(0) as i64,
+                ..Default::default()
+            };
+            (p.id, counts)
+        })
+        .collect();
 
-    // Count owned and total for each playlist
+    // Count synced and owned tracks for each playlist
     for row in results {
-        let entry = stats_map.entry(row.playlist_id).or_insert((0, 0));
-        entry.1 += 1; // total count
+        let counts = counts_map.entry(row.playlist_id).or_default();
+        counts.synced += 1;
         if row.ownership_status == "owned" {
-            entry.0 += 1; // owned count
+            counts.owned += 1;
         }
     }
 
-    Ok(stats_map)
+    Ok(counts_map)
 }
 
 /// Get paginated tracks for a playlist with all details (optimized single query)
@@ -166,8 +252,11 @@ pub struct PlaylistTrackDetails {
     pub track_name: String,
     pub duration_ms: Option<i32>,
     pub album_id: i32,
+    pub album_slug: Option<String>,
     pub album_name: String,
     pub ownership_status: String,
+    pub artist_id: i32,
+    pub artist_slug: Option<String>,
     pub artist_name: String,
 }
 
@@ -177,13 +266,27 @@ pub async fn get_playlist_tracks_paginated(
     offset: u64,
     limit: u64,
 ) -> Result<(Vec<PlaylistTrackDetails>, u64)> {
-    use crate::db::entities::artists;
+    let total = count_playlist_tracks(db, playlist_id).await?;
+    let details = get_playlist_tracks_page(db, playlist_id, offset, limit).await?;
+    Ok((details, total))
+}
 
-    // Get total count
-    let total = playlist_tracks::Entity::find()
+/// Number of track entries in a playlist
+pub async fn count_playlist_tracks(db: &DatabaseConnection, playlist_id: i32) -> Result<u64> {
+    Ok(playlist_tracks::Entity::find()
         .filter(playlist_tracks::Column::PlaylistId.eq(playlist_id))
         .count(db)
-        .await?;
+        .await?)
+}
+
+/// One page of a playlist's tracks in position order, without counting them
+pub async fn get_playlist_tracks_page(
+    db: &DatabaseConnection,
+    playlist_id: i32,
+    offset: u64,
+    limit: u64,
+) -> Result<Vec<PlaylistTrackDetails>> {
+    use crate::db::entities::artists;
 
     // Single JOIN query for paginated tracks
     #[derive(FromQueryResult)]
@@ -193,8 +296,11 @@ pub async fn get_playlist_tracks_paginated(
         track_name: String,
         duration_ms: Option<i32>,
         album_id: i32,
+        album_slug: Option<String>,
         album_name: String,
         ownership_status: String,
+        artist_id: i32,
+        artist_slug: Option<String>,
         artist_name: String,
     }
 
@@ -206,8 +312,11 @@ pub async fn get_playlist_tracks_paginated(
         .column_as(tracks::Column::Title, "track_name")
         .column_as(tracks::Column::DurationMs, "duration_ms")
         .column_as(albums::Column::Id, "album_id")
+        .column_as(albums::Column::Slug, "album_slug")
         .column_as(albums::Column::Title, "album_name")
         .column_as(albums::Column::OwnershipStatus, "ownership_status")
+        .column_as(artists::Column::Id, "artist_id")
+        .column_as(artists::Column::Slug, "artist_slug")
         .column_as(artists::Column::Name, "artist_name")
         .join(JoinType::InnerJoin, playlist_tracks::Relation::Tracks.def())
         .join(JoinType::InnerJoin, tracks::Relation::Albums.def())
@@ -227,11 +336,133 @@ pub async fn get_playlist_tracks_paginated(
             track_name: t.track_name,
             duration_ms: t.duration_ms,
             album_id: t.album_id,
+            album_slug: t.album_slug,
             album_name: t.album_name,
             ownership_status: t.ownership_status,
+            artist_id: t.artist_id,
+            artist_slug: t.artist_slug,
             artist_name: t.artist_name,
         })
         .collect();
 
-    Ok((details, total))
+    Ok(details)
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DatabaseBackend, DatabaseTransaction, Statement};
+
+    use super::*;
+    use crate::db::enums::OwnershipStatus;
+    use crate::test_utils::*;
+
+    async fn add_album_track(db: &DatabaseConnection, playlist_id: i32, album_id: i32, position: i32) {
+        let now = Utc::now().into();
+        let track = tracks::ActiveModel {
+            album_id: Set(album_id),
+            title: Set(format!("Track {}", position)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist_id),
+            track_id: Set(track.id),
+            position: Set(position),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    /// Whether another session is waiting on a lock `txn` holds
+    async fn blocks_another_session(txn: &DatabaseTransaction) -> bool {
+        let row = txn
+            .query_one(Statement::from_string(
+                DatabaseBackend::Postgres,
+                "SELECT COUNT(*) AS blocked FROM pg_stat_activity \
+                 WHERE pg_backend_pid() = ANY(pg_blocking_pids(pid))",
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        row.try_get::<i64>("", "blocked").unwrap() > 0
+    }
+
+    #[tokio::test]
+    async fn test_recount_waits_for_a_concurrent_ownership_change() {
+        // SQLite serializes writers, so only Postgres can interleave recounts
+        let Some(db) = setup_postgres_test_db().await else {
+            return;
+        };
+        let now = Utc::now().into();
+        let playlist = playlists::ActiveModel {
+            name: Set("Shared".to_string()),
+            spotify_id: Set("shared".to_string()),
+            is_collaborative: Set(false),
+            is_enabled: Set(true),
+            is_synthetic: Set(false),
+            owned_count: Set(Some(0)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let artist = create_test_artist(&db, "Artist", None).await;
+        let album = create_test_album(&db, artist.id, "Album", None).await;
+        add_album_track(&db, playlist.id, album.id, 0).await;
+
+        // Another ownership change is midway: the album is owned and its
+        // recount holds the playlist row, but nothing is committed yet
+        let other = db.begin().await.unwrap();
+        playlists::Entity::find_by_id(playlist.id)
+            .lock_exclusive()
+            .one(&other)
+            .await
+            .unwrap();
+        albums::ActiveModel {
+            id: Set(album.id),
+            ownership_status: Set(OwnershipStatus::Owned.as_str().to_string()),
+            ..Default::default()
+        }
+        .update(&other)
+        .await
+        .unwrap();
+        playlists::ActiveModel {
+            id: Set(playlist.id),
+            owned_count: Set(Some(1)),
+            ..Default::default()
+        }
+        .update(&other)
+        .await
+        .unwrap();
+
+        // A recount that started before it committed runs into it
+        let recount = tokio::spawn({
+            let db = db.clone();
+            async move { refresh_playlist_owned_count(&db, playlist.id).await.unwrap() }
+        });
+        let mut waits = 0;
+        while !blocks_another_session(&other).await {
+            waits += 1;
+            assert!(waits < 500, "the recount never reached the playlist row");
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        other.commit().await.unwrap();
+        recount.await.unwrap();
+
+        // Without the lock it would have counted before the change committed
+        // and then overwritten the newer count with 0
+        let playlist = playlists::Entity::find_by_id(playlist.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(playlist.owned_count, Some(1));
+    }
 }
@@ -0,0 +1,278 @@
+//! Retry queue for external side effects that failed
+//!
+//! Best-effort work such as downloading a cover shouldn't be lost to a
+//! transient error. A failed side effect is stored in `pending_side_effects`
+//! and retried by a scheduled task with exponential backoff. After
+//! `MAX_SIDE_EFFECT_ATTEMPTS` failures it stays in the table as a dead letter
+//! until it is retried or discarded from the admin API.
+
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::entities::pending_side_effects,
+    error::{AppError, Result},
+};
+
+/// Failed attempts after which an entry is no longer retried automatically
+pub const MAX_SIDE_EFFECT_ATTEMPTS: i32 = 8;
+
+/// Delay before the first retry; each further failure doubles it
+const BASE_BACKOFF_SECS: i64 = 60;
+
+/// Longest delay between two retries
+const MAX_BACKOFF_SECS: i64 = 6 * 60 * 60;
+
+/// What a pending side effect does when it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SideEffectKind {
+    /// Download an album's cover from the Cover Art Archive
+    CoverArtDownload,
+}
+
+impl SideEffectKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::CoverArtDownload => "cover_art_download",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cover_art_download" => Some(Self::CoverArtDownload),
+            _ => None,
+        }
+    }
+}
+
+/// Payload of a `cover_art_download` side effect
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverArtDownload {
+    pub album_id: i32,
+    pub release_group_id: String,
+}
+
+/// Delay before the next attempt of an entry that has failed `attempts` times
+pub fn backoff(attempts: i32) -> Duration {
+    let doublings = attempts.saturating_sub(1).clamp(0, 30) as u32;
+    let secs = BASE_BACKOFF_SECS.saturating_mul(1_i64 << doublings);
+    Duration::seconds(secs.min(MAX_BACKOFF_SECS))
+}
+
+/// Whether an entry has used up its automatic retries
+pub fn is_dead(entry: &pending_side_effects::Model) -> bool {
+    entry.attempts >= MAX_SIDE_EFFECT_ATTEMPTS
+}
+
+/// Queue a side effect whose first attempt failed with `error`
+///
+/// The same side effect queued twice is stored once; the existing entry keeps
+/// its schedule and only its last error is updated.
+pub async fn enqueue<P: Serialize>(
+    db: &DatabaseConnection,
+    kind: SideEffectKind,
+    payload: &P,
+    error: &str,
+) -> Result<pending_side_effects::Model> {
+    let payload = serde_json::to_string(payload)?;
+    let now = Utc::now();
+
+    let existing = pending_side_effects::Entity::find()
+        .filter(pending_side_effects::Column::Kind.eq(kind.as_str()))
+        .filter(pending_side_effects::Column::Payload.eq(payload.as_str()))
+        .one(db)
+        .await?;
+    if let Some(existing) = existing {
+        let mut active: pending_side_effects::ActiveModel = existing.into();
+        active.last_error = Set(Some(error.to_string()));
+        active.updated_at = Set(now.into());
+        return Ok(active.update(db).await?);
+    }
+
+    let entry = pending_side_effects::ActiveModel {
+        kind: Set(kind.as_str().to_string()),
+        payload: Set(payload),
+        attempts: Set(1),
+        next_attempt_at: Set((now + backoff(1)).into()),
+        last_error: Set(Some(error.to_string())),
+        created_at: Set(now.into()),
+        updated_at: Set(now.into()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    tracing::info!(
+        "Queued {} side effect {} for retry: {}",
+        kind.as_str(),
+        entry.id,
+        error
+    );
+    Ok(entry)
+}
+
+/// Entries due for an automatic retry, oldest schedule first
+pub async fn due(db: &DatabaseConnection) -> Result<Vec<pending_side_effects::Model>> {
+    Ok(pending_side_effects::Entity::find()
+        .filter(pending_side_effects::Column::NextAttemptAt.lte(Utc::now()))
+        .filter(pending_side_effects::Column::Attempts.lt(MAX_SIDE_EFFECT_ATTEMPTS))
+        .order_by_asc(pending_side_effects::Column::NextAttemptAt)
+        .order_by_asc(pending_side_effects::Column::Id)
+        .all(db)
+        .await?)
+}
+
+/// Count another failed attempt and schedule the next one
+pub async fn record_failure(
+    db: &DatabaseConnection,
+    entry: pending_side_effects::Model,
+    error: &str,
+) -> Result<pending_side_effects::Model> {
+    let now = Utc::now();
+    let attempts = entry.attempts.saturating_add(1);
+
+    let mut active: pending_side_effects::ActiveModel = entry.into();
+    active.attempts = Set(attempts);
+    active.next_attempt_at = Set((now + backoff(attempts)).into());
+    active.last_error = Set(Some(error.to_string()));
+    active.updated_at = Set(now.into());
+    let entry = active.update(db).await?;
+
+    if is_dead(&entry) {
+        tracing::warn!(
+            "Giving up on {} side effect {} after {} attempts: {}",
+            entry.kind,
+            entry.id,
+            entry.attempts,
+            error
+        );
+    }
+    Ok(entry)
+}
+
+/// Remove an entry once its side effect has run
+pub async fn complete(db: &DatabaseConnection, id: i32) -> Result<()> {
+    pending_side_effects::Entity::delete_by_id(id).exec(db).await?;
+    Ok(())
+}
+
+/// Every pending entry, dead letters included, oldest first
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<pending_side_effects::Model>> {
+    Ok(pending_side_effects::Entity::find()
+        .order_by_asc(pending_side_effects::Column::CreatedAt)
+        .order_by_asc(pending_side_effects::Column::Id)
+        .all(db)
+        .await?)
+}
+
+pub async fn find(db: &DatabaseConnection, id: i32) -> Result<pending_side_effects::Model> {
+    pending_side_effects::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Pending side effect {} not found", id)))
+}
+
+/// Drop an entry without running it
+pub async fn discard(db: &DatabaseConnection, id: i32) -> Result<()> {
+    let deleted = pending_side_effects::Entity::delete_by_id(id)
+        .exec(db)
+        .await?
+        .rows_affected;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Pending side effect {} not found", id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn cover(album_id: i32) -> CoverArtDownload {
+        CoverArtDownload {
+            album_id,
+            release_group_id: "f5093c06-23e3-404f-aeaa-40f72885ee3a".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        assert_eq!(backoff(1), Duration::seconds(60));
+        assert_eq!(backoff(2), Duration::seconds(120));
+        assert_eq!(backoff(5), Duration::seconds(960));
+        assert_eq!(backoff(20), Duration::seconds(MAX_BACKOFF_SECS));
+        assert_eq!(backoff(i32::MAX), Duration::seconds(MAX_BACKOFF_SECS));
+        // Counts below one are treated as the first failure
+        assert_eq!(backoff(0), Duration::seconds(60));
+    }
+
+    #[test]
+    fn test_kind_round_trips() {
+        let kind = SideEffectKind::CoverArtDownload;
+        assert_eq!(SideEffectKind::parse(kind.as_str()), Some(kind));
+        assert_eq!(SideEffectKind::parse("webhook"), None);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_schedules_first_retry_and_dedupes() {
+        let db = setup_test_db().await;
+
+        let before = Utc::now();
+        let entry = enqueue(&db, SideEffectKind::CoverArtDownload, &cover(1), "timed out")
+            .await
+            .unwrap();
+        assert_eq!(entry.attempts, 1);
+        assert!(entry.next_attempt_at >= before + backoff(1));
+        assert!(!is_dead(&entry));
+
+        let again = enqueue(&db, SideEffectKind::CoverArtDownload, &cover(1), "503")
+            .await
+            .unwrap();
+        assert_eq!(again.id, entry.id);
+        assert_eq!(again.attempts, 1);
+        assert_eq!(again.last_error.as_deref(), Some("503"));
+
+        enqueue(&db, SideEffectKind::CoverArtDownload, &cover(2), "timed out")
+            .await
+            .unwrap();
+        assert_eq!(list(&db).await.unwrap().len(), 2);
+
+        // Not due until the backoff has passed
+        assert!(due(&db).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entry_becomes_dead_letter_after_max_attempts() {
+        let db = setup_test_db().await;
+
+        let mut entry = enqueue(&db, SideEffectKind::CoverArtDownload, &cover(1), "timed out")
+            .await
+            .unwrap();
+        while entry.attempts < MAX_SIDE_EFFECT_ATTEMPTS {
+            let mut active: pending_side_effects::ActiveModel = entry.into();
+            active.next_attempt_at = Set((Utc::now() - Duration::minutes(1)).into());
+            let overdue = active.update(&db).await.unwrap();
+            assert_eq!(due(&db).await.unwrap().len(), 1);
+
+            let before = Utc::now();
+            entry = record_failure(&db, overdue, "timed out").await.unwrap();
+            assert!(entry.next_attempt_at >= before + backoff(entry.attempts));
+        }
+        assert!(is_dead(&entry));
+
+        // Dead letters stay listed but are never due again
+        let mut active: pending_side_effects::ActiveModel = entry.clone().into();
+        active.next_attempt_at = Set((Utc::now() - Duration::minutes(1)).into());
+        active.update(&db).await.unwrap();
+        assert!(due(&db).await.unwrap().is_empty());
+        assert_eq!(list(&db).await.unwrap().len(), 1);
+
+        discard(&db, entry.id).await.unwrap();
+        assert!(list(&db).await.unwrap().is_empty());
+        assert!(matches!(discard(&db, entry.id).await, Err(AppError::NotFound(_))));
+    }
+}
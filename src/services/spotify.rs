@@ -5,22 +5,97 @@ use governor::{Quota, RateLimiter, clock::DefaultClock, state::InMemoryState, st
 use nonzero_ext::nonzero;
 use rand::Rng;
 use reqwest::Client;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::sync::Arc;
-
-use crate::error::{AppError, Result};
-
-const SPOTIFY_AUTH_URL: &str = "https://accounts.spotify.com/authorize";
-const SPOTIFY_TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
-const SPOTIFY_API_BASE: &str = "https://api.spotify.com/v1";
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::Instant;
+
+use crate::{
+    config::Config,
+    db::entities::profiles,
+    error::{AppError, Result},
+};
+
+/// Default base URL of the Spotify accounts service (authorization and tokens)
+pub const SPOTIFY_ACCOUNTS_BASE: &str = "https://accounts.spotify.com";
+/// Default base URL of the Spotify Web API
+pub const SPOTIFY_API_BASE: &str = "https://api.spotify.com/v1";
+/// Scope needed for the personalization (top artists/tracks) endpoints
+pub const TOP_READ_SCOPE: &str = "user-top-read";
+
+/// Most artists `GET /artists` returns in one request
+pub const ARTISTS_BATCH_SIZE: usize = 50;
 
 #[derive(Clone)]
 pub struct SpotifyService {
     client: Client,
     client_id: String,
     redirect_uri: String,
+    accounts_base: String,
+    api_base: String,
     rate_limiter: Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>,
+    stats: Arc<SpotifyStats>,
+}
+
+/// Request counters shared by a `SpotifyService` and its clones
+///
+/// A sync creates its own service, so these describe that sync's API usage.
+#[derive(Debug)]
+pub struct SpotifyStats {
+    started_at: Instant,
+    requests: AtomicU64,
+    rate_limit_hits: AtomicU64,
+}
+
+impl Default for SpotifyStats {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            requests: AtomicU64::new(0),
+            rate_limit_hits: AtomicU64::new(0),
+        }
+    }
+}
+
+impl SpotifyStats {
+    /// Requests sent so far
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Responses that were 429 Too Many Requests
+    pub fn rate_limit_hits(&self) -> u64 {
+        self.rate_limit_hits.load(Ordering::Relaxed)
+    }
+
+    /// Requests per second since the service was created, throttling included
+    pub fn request_rate(&self) -> f64 {
+        self.rate_over(self.started_at.elapsed())
+    }
+
+    fn rate_over(&self, elapsed: std::time::Duration) -> f64 {
+        let secs = elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.requests() as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Rough time `remaining` more requests take at the current rate
+    ///
+    /// `None` until a request has been made, since there is no rate yet.
+    pub fn eta(&self, remaining: u64) -> Option<std::time::Duration> {
+        eta_at_rate(self.request_rate(), remaining)
+    }
+}
+
+fn eta_at_rate(rate: f64, remaining: u64) -> Option<std::time::Duration> {
+    (rate > 0.0).then(|| std::time::Duration::from_secs_f64(remaining as f64 / rate))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,11 +118,43 @@ pub struct TokenResponse {
 pub struct SpotifyAlbum {
     pub id: String,
     pub name: String,
+    #[serde(default, deserialize_with = "deserialize_null_as_empty_vec")]
     pub artists: Vec<SpotifyArtist>,
     pub release_date: String,
     pub total_tracks: i32,
     pub images: Vec<SpotifyImage>,
     pub genres: Option<Vec<String>>,
+    /// Only present on full album objects (e.g. saved albums), not on track album stubs
+    #[serde(default)]
+    pub external_ids: Option<SpotifyExternalIds>,
+    /// `album` for music; podcast episodes carry a `show` stub here instead
+    #[serde(default, rename = "type")]
+    pub item_type: Option<String>,
+}
+
+impl SpotifyAlbum {
+    /// Whether this is a music album that can be added to the library
+    ///
+    /// Saved content can include podcast shows, which have no artists.
+    pub fn is_music(&self) -> bool {
+        !self.artists.is_empty() && self.item_type.as_deref().unwrap_or("album") == "album"
+    }
+
+    /// UPC or EAN barcode, if Spotify provided one
+    pub fn barcode(&self) -> Option<&str> {
+        let ids = self.external_ids.as_ref()?;
+        ids.upc
+            .as_deref()
+            .or(ids.ean.as_deref())
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotifyExternalIds {
+    pub upc: Option<String>,
+    pub ean: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +163,18 @@ pub struct SpotifyArtist {
     pub name: String,
 }
 
+/// Full artist object, as returned by the artists, related artists and top artists endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotifyTopArtist {
+    pub id: String,
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_null_as_empty_vec")]
+    pub images: Vec<SpotifyImage>,
+    /// 0-100, higher for artists played more
+    #[serde(default)]
+    pub popularity: Option<i32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpotifyImage {
     pub url: String,
@@ -75,6 +194,15 @@ struct SavedAlbumItem {
     album: SpotifyAlbum,
 }
 
+/// One page of saved albums plus the cursor for the following page
+#[derive(Debug, Clone)]
+pub struct SavedAlbumsPage {
+    pub albums: Vec<SpotifyAlbum>,
+    pub next: Option<String>,
+    /// Saved albums in the whole library, not just this page
+    pub total: i32,
+}
+
 // Playlist-related types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpotifyPlaylist {
@@ -99,6 +227,32 @@ where
     Ok(opt.unwrap_or_default())
 }
 
+/// Playlist ID from an open.spotify.com link, a `spotify:playlist:` URI or a bare ID
+pub fn parse_playlist_id(input: &str) -> Option<&str> {
+    let input = input.trim();
+    let url = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+        .unwrap_or(input);
+
+    let id = if let Some(uri) = input.strip_prefix("spotify:") {
+        // spotify:playlist:ID, or the older spotify:user:NAME:playlist:ID
+        let mut parts = uri.split(':');
+        parts.find(|part| *part == "playlist")?;
+        parts.next()?
+    } else if let Some(path) = url.strip_prefix("open.spotify.com/") {
+        // Locale (intl-de/) and embed/ prefixes come before the playlist segment
+        let path = path.split(['?', '#']).next()?;
+        let mut segments = path.split('/');
+        segments.find(|segment| *segment == "playlist")?;
+        segments.next()?
+    } else {
+        input
+    };
+
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())).then_some(id)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpotifyPlaylistOwner {
     pub id: String,
@@ -111,20 +265,121 @@ pub struct SpotifyPlaylistTracksRef {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawPlaylistTrack")]
 pub struct SpotifyPlaylistTrack {
     pub track: Option<SpotifyTrack>,
     pub added_at: Option<String>,
+    pub is_local: bool,
+    /// A podcast episode; its `track` is dropped, since episode objects
+    /// don't have a track's shape
+    pub is_episode: bool,
+}
+
+/// Playlist entry as Spotify sends it, before episodes are told apart from tracks
+#[derive(Deserialize)]
+struct RawPlaylistTrack {
+    track: Option<serde_json::Value>,
+    added_at: Option<String>,
+    #[serde(default)]
+    is_local: bool,
+    /// Only present when reading back an entry this crate serialized
+    #[serde(default)]
+    is_episode: bool,
+}
+
+impl TryFrom<RawPlaylistTrack> for SpotifyPlaylistTrack {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawPlaylistTrack) -> std::result::Result<Self, Self::Error> {
+        let is_episode = raw.is_episode
+            || raw
+                .track
+                .as_ref()
+                .and_then(|track| track.get("type"))
+                .and_then(|item_type| item_type.as_str())
+                == Some("episode");
+        let track = match raw.track {
+            Some(track) if !is_episode => Some(serde_json::from_value(track)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            track,
+            added_at: raw.added_at,
+            is_local: raw.is_local,
+            is_episode,
+        })
+    }
+}
+
+impl SpotifyPlaylistTrack {
+    /// Whether Spotify can't play this entry in the user's market
+    ///
+    /// Restricted tracks come back either flagged with `is_playable: false` or
+    /// without a track object or ID. Local files and podcast episodes also
+    /// lack one but aren't a market restriction, so they don't count.
+    pub fn is_unavailable(&self) -> bool {
+        if self.is_local || self.is_episode {
+            return false;
+        }
+        match &self.track {
+            Some(track) => track.spotify_id().is_none() || track.is_playable == Some(false),
+            None => true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpotifyTrack {
     pub id: Option<String>,
     pub name: String,
+    #[serde(default)]
     pub track_number: i32,
+    #[serde(default)]
     pub disc_number: i32,
     pub duration_ms: i32,
     pub album: SpotifyAlbum,
+    #[serde(default, deserialize_with = "deserialize_null_as_empty_vec")]
     pub artists: Vec<SpotifyArtist>,
+    /// Only sent when a market is requested (`market=from_token`)
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    /// The track the playlist actually holds, when Spotify swapped in
+    /// another copy that plays in the user's market (track relinking)
+    #[serde(default)]
+    pub linked_from: Option<SpotifyLinkedTrack>,
+    /// `track` for music, `episode` for podcast episodes in playlists
+    #[serde(default, rename = "type")]
+    pub item_type: Option<String>,
+}
+
+/// The original of a relinked track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotifyLinkedTrack {
+    pub id: Option<String>,
+}
+
+impl SpotifyTrack {
+    /// ID of the track as saved in the playlist, before any relinking
+    ///
+    /// Storing this one keeps a track's record the same whichever market
+    /// copy Spotify hands back.
+    pub fn spotify_id(&self) -> Option<&str> {
+        self.linked_from
+            .as_ref()
+            .and_then(|linked| linked.id.as_deref())
+            .or(self.id.as_deref())
+    }
+
+    /// Whether this is a music track that can be added to the library
+    ///
+    /// Podcast episodes come back in track form, either typed `episode` or
+    /// with no artists at all.
+    pub fn is_music(&self) -> bool {
+        !self.artists.is_empty()
+            && self.item_type.as_deref().unwrap_or("track") == "track"
+            && self.album.item_type.as_deref() != Some("show")
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -134,6 +389,17 @@ struct PlaylistsResponse {
     total: i32,
 }
 
+#[derive(Debug, Deserialize)]
+struct ArtistsResponse {
+    /// `null` for IDs Spotify doesn't know
+    artists: Vec<Option<SpotifyTopArtist>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopItemsResponse<T> {
+    items: Vec<T>,
+}
+
 #[derive(Debug, Deserialize)]
 struct PlaylistTracksResponse {
     items: Vec<SpotifyPlaylistTrack>,
@@ -151,8 +417,41 @@ impl SpotifyService {
             client: Client::new(),
             client_id,
             redirect_uri,
+            accounts_base: SPOTIFY_ACCOUNTS_BASE.to_string(),
+            api_base: SPOTIFY_API_BASE.to_string(),
             rate_limiter,
+            stats: Arc::new(SpotifyStats::default()),
+        }
+    }
+
+    /// Create a service using the client settings and base URLs from the app config
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.spotify_client_id.clone(), config.spotify_redirect_uri.clone())
+            .with_base_urls(&config.spotify_accounts_base, &config.spotify_api_base)
+    }
+
+    /// Point the service at different accounts and Web API hosts (e.g. a mock server in tests)
+    pub fn with_base_urls(mut self, accounts_base: &str, api_base: &str) -> Self {
+        self.accounts_base = accounts_base.trim_end_matches('/').to_string();
+        self.api_base = api_base.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Request counters for this service and its clones
+    pub fn stats(&self) -> Arc<SpotifyStats> {
+        self.stats.clone()
+    }
+
+    /// Wait for the rate limiter, then send a request, counting it and any 429 answer
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        self.rate_limiter.until_ready().await;
+        self.stats.requests.fetch_add(1, Ordering::Relaxed);
+
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            self.stats.rate_limit_hits.fetch_add(1, Ordering::Relaxed);
         }
+        Ok(response)
     }
 
     /// Generate authorization URL with PKCE
@@ -171,11 +470,12 @@ impl SpotifyService {
             "user-library-read",
             "playlist-read-private",
             "playlist-read-collaborative",
+            TOP_READ_SCOPE,
         ];
 
         let url = format!(
-            "{}?client_id={}&response_type=code&redirect_uri={}&code_challenge_method=S256&code_challenge={}&scope={}&state={}",
-            SPOTIFY_AUTH_URL,
+            "{}/authorize?client_id={}&response_type=code&redirect_uri={}&code_challenge_method=S256&code_challenge={}&scope={}&state={}",
+            self.accounts_base,
             urlencoding::encode(&self.client_id),
             urlencoding::encode(&self.redirect_uri),
             code_challenge,
@@ -196,8 +496,6 @@ impl SpotifyService {
         code: &str,
         code_verifier: &str,
     ) -> Result<TokenResponse> {
-        self.rate_limiter.until_ready().await;
-
         let params = [
             ("grant_type", "authorization_code"),
             ("code", code),
@@ -206,12 +504,11 @@ impl SpotifyService {
             ("code_verifier", code_verifier),
         ];
 
-        let response = self
+        let request = self
             .client
-            .post(SPOTIFY_TOKEN_URL)
-            .form(&params)
-            .send()
-            .await?;
+            .post(self.token_url())
+            .form(&params);
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             let error_text = response.text().await?;
@@ -226,20 +523,17 @@ impl SpotifyService {
 
     /// Refresh access token
     pub async fn refresh_token(&self, refresh_token: &str) -> Result<TokenResponse> {
-        self.rate_limiter.until_ready().await;
-
         let params = [
             ("grant_type", "refresh_token"),
             ("refresh_token", refresh_token),
             ("client_id", &self.client_id),
         ];
 
-        let response = self
+        let request = self
             .client
-            .post(SPOTIFY_TOKEN_URL)
-            .form(&params)
-            .send()
-            .await?;
+            .post(self.token_url())
+            .form(&params);
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             let error_text = response.text().await?;
@@ -255,51 +549,69 @@ impl SpotifyService {
     /// Fetch all saved albums from user's library
     pub async fn fetch_saved_albums(&self, access_token: &str) -> Result<Vec<SpotifyAlbum>> {
         let mut albums = Vec::new();
-        let mut next_url = Some(format!("{}/me/albums?limit=50", SPOTIFY_API_BASE));
+        let mut next_url = Some(self.saved_albums_url());
 
         while let Some(url) = next_url {
-            self.rate_limiter.until_ready().await;
+            let mut page = self.fetch_saved_albums_page(access_token, &url).await?;
+            albums.append(&mut page.albums);
+            next_url = page.next;
 
-            let response = self
-                .client
-                .get(&url)
-                .header("Authorization", format!("Bearer {}", access_token))
-                .send()
-                .await?;
+            tracing::debug!("Fetched {} albums so far", albums.len());
+        }
 
-            if !response.status().is_success() {
-                let status = response.status();
-                let error_text = response.text().await?;
-                return Err(AppError::ExternalApi(format!(
-                    "Spotify API error ({}): {}",
-                    status, error_text
-                )));
-            }
+        Ok(albums)
+    }
 
-            let mut data: SavedAlbumsResponse = response.json().await?;
-            albums.append(&mut data.items.into_iter().map(|item| item.album).collect());
-            next_url = data.next;
+    /// URL of the first page of the user's saved albums
+    pub fn saved_albums_url(&self) -> String {
+        format!("{}/me/albums?limit=50", self.api_base)
+    }
 
-            tracing::debug!("Fetched {} albums so far", albums.len());
+    /// Whether a pagination URL points at the saved albums endpoint (safe to resume from)
+    pub fn is_saved_albums_url(&self, url: &str) -> bool {
+        url.starts_with(&format!("{}/me/albums", self.api_base))
+    }
+
+    /// Fetch a single page of saved albums, returning the URL of the next page if any
+    pub async fn fetch_saved_albums_page(
+        &self,
+        access_token: &str,
+        url: &str,
+    ) -> Result<SavedAlbumsPage> {
+        let request = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Bearer {}", access_token));
+        let response = self.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(AppError::ExternalApi(format!(
+                "Spotify API error ({}): {}",
+                status, error_text
+            )));
         }
 
-        Ok(albums)
+        let data: SavedAlbumsResponse = response.json().await?;
+        Ok(SavedAlbumsPage {
+            albums: data.items.into_iter().map(|item| item.album).collect(),
+            next: data.next,
+            total: data.total,
+        })
     }
 
     /// Fetch all user's playlists (owned and followed)
     pub async fn fetch_user_playlists(&self, access_token: &str) -> Result<Vec<SpotifyPlaylist>> {
         let mut playlists = Vec::new();
-        let mut next_url = Some(format!("{}/me/playlists?limit=50", SPOTIFY_API_BASE));
+        let mut next_url = Some(format!("{}/me/playlists?limit=50", self.api_base));
 
         while let Some(url) = next_url {
-            self.rate_limiter.until_ready().await;
-
-            let response = self
+            let request = self
                 .client
                 .get(&url)
-                .header("Authorization", format!("Bearer {}", access_token))
-                .send()
-                .await?;
+                .header("Authorization", format!("Bearer {}", access_token));
+            let response = self.send(request).await?;
 
             if !response.status().is_success() {
                 let status = response.status();
@@ -340,7 +652,37 @@ impl SpotifyService {
         Ok(playlists)
     }
 
+    /// Fetch one playlist's details; any public playlist works, followed or not
+    pub async fn fetch_playlist(&self, access_token: &str, playlist_id: &str) -> Result<SpotifyPlaylist> {
+        let request = self
+            .client
+            .get(format!(
+                "{}/playlists/{}?fields=id,name,description,owner,collaborative,tracks.total,images,snapshot_id",
+                self.api_base, playlist_id
+            ))
+            .header("Authorization", format!("Bearer {}", access_token));
+        let response = self.send(request).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::NotFound(format!("Spotify playlist {} not found", playlist_id)));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(AppError::ExternalApi(format!(
+                "Spotify API error ({}): {}",
+                status, error_text
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+
     /// Fetch all tracks in a specific playlist
+    ///
+    /// A playlist that is gone answers `NotFound`, and one the user can no
+    /// longer read answers `Authentication`; other failures are `ExternalApi`.
     pub async fn fetch_playlist_tracks(
         &self,
         access_token: &str,
@@ -348,19 +690,32 @@ impl SpotifyService {
     ) -> Result<Vec<SpotifyPlaylistTrack>> {
         let mut tracks = Vec::new();
         let mut next_url = Some(format!(
-            "{}/playlists/{}/tracks?limit=100",
-            SPOTIFY_API_BASE, playlist_id
+            "{}/playlists/{}/tracks?limit=100&market=from_token",
+            self.api_base, playlist_id
         ));
 
         while let Some(url) = next_url {
-            self.rate_limiter.until_ready().await;
-
-            let response = self
+            let request = self
                 .client
                 .get(&url)
-                .header("Authorization", format!("Bearer {}", access_token))
-                .send()
-                .await?;
+                .header("Authorization", format!("Bearer {}", access_token));
+            let response = self.send(request).await?;
+
+            match response.status() {
+                reqwest::StatusCode::NOT_FOUND => {
+                    return Err(AppError::NotFound(format!(
+                        "Spotify playlist {} not found (404)",
+                        playlist_id
+                    )));
+                }
+                reqwest::StatusCode::FORBIDDEN => {
+                    return Err(AppError::Authentication(format!(
+                        "No access to Spotify playlist {} (403)",
+                        playlist_id
+                    )));
+                }
+                _ => {}
+            }
 
             if !response.status().is_success() {
                 let status = response.status();
@@ -388,17 +743,14 @@ impl SpotifyService {
     /// Fetch all saved tracks from user's library (Liked Songs)
     pub async fn fetch_saved_tracks(&self, access_token: &str) -> Result<Vec<SpotifyPlaylistTrack>> {
         let mut tracks = Vec::new();
-        let mut next_url = Some(format!("{}/me/tracks?limit=50", SPOTIFY_API_BASE));
+        let mut next_url = Some(format!("{}/me/tracks?limit=50&market=from_token", self.api_base));
 
         while let Some(url) = next_url {
-            self.rate_limiter.until_ready().await;
-
-            let response = self
+            let request = self
                 .client
                 .get(&url)
-                .header("Authorization", format!("Bearer {}", access_token))
-                .send()
-                .await?;
+                .header("Authorization", format!("Bearer {}", access_token));
+            let response = self.send(request).await?;
 
             if !response.status().is_success() {
                 let status = response.status();
@@ -422,14 +774,11 @@ impl SpotifyService {
 
     /// Get total count of saved tracks (for quick metadata updates)
     pub async fn get_saved_tracks_total(&self, access_token: &str) -> Result<i32> {
-        self.rate_limiter.until_ready().await;
-
-        let response = self
+        let request = self
             .client
-            .get(&format!("{}/me/tracks?limit=1", SPOTIFY_API_BASE))
-            .header("Authorization", format!("Bearer {}", access_token))
-            .send()
-            .await?;
+            .get(&format!("{}/me/tracks?limit=1", self.api_base))
+            .header("Authorization", format!("Bearer {}", access_token));
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -444,6 +793,109 @@ impl SpotifyService {
         Ok(data.total)
     }
 
+    /// Fetch the user's most played artists over the last ~6 months
+    pub async fn fetch_top_artists(&self, access_token: &str) -> Result<Vec<SpotifyTopArtist>> {
+        self.fetch_top_items(access_token, "artists").await
+    }
+
+    /// Fetch the user's most played tracks over the last ~6 months
+    pub async fn fetch_top_tracks(&self, access_token: &str) -> Result<Vec<SpotifyTrack>> {
+        self.fetch_top_items(access_token, "tracks").await
+    }
+
+    /// Fetch full artist objects, at most [`ARTISTS_BATCH_SIZE`] per call
+    ///
+    /// IDs Spotify doesn't know are left out of the result.
+    pub async fn fetch_artists(&self, access_token: &str, ids: &[String]) -> Result<Vec<SpotifyTopArtist>> {
+        if ids.len() > ARTISTS_BATCH_SIZE {
+            return Err(AppError::Validation(format!(
+                "At most {} artists can be fetched at once, got {}",
+                ARTISTS_BATCH_SIZE,
+                ids.len()
+            )));
+        }
+
+        let request = self
+            .client
+            .get(format!("{}/artists", self.api_base))
+            .query(&[("ids", ids.join(","))])
+            .header("Authorization", format!("Bearer {}", access_token));
+        let response = self.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(AppError::ExternalApi(format!(
+                "Spotify API error ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let data: ArtistsResponse = response.json().await?;
+        Ok(data.artists.into_iter().flatten().collect())
+    }
+
+    /// GET `/artists/{id}/related-artists`: up to 20 artists similar to one
+    pub async fn fetch_related_artists(&self, access_token: &str, artist_id: &str) -> Result<Vec<SpotifyTopArtist>> {
+        let request = self
+            .client
+            .get(format!("{}/artists/{}/related-artists", self.api_base, artist_id))
+            .header("Authorization", format!("Bearer {}", access_token));
+        let response = self.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(AppError::ExternalApi(format!(
+                "Spotify API error ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let data: ArtistsResponse = response.json().await?;
+        Ok(data.artists.into_iter().flatten().collect())
+    }
+
+    /// GET `/me/top/{item_type}`
+    ///
+    /// Tokens granted before the `user-top-read` scope was requested get a 403,
+    /// reported as [`AppError::InsufficientScope`] so callers can prompt a reconnect.
+    async fn fetch_top_items<T: serde::de::DeserializeOwned>(
+        &self,
+        access_token: &str,
+        item_type: &str,
+    ) -> Result<Vec<T>> {
+        let request = self
+            .client
+            .get(format!(
+                "{}/me/top/{}?limit=50&time_range=medium_term",
+                self.api_base, item_type
+            ))
+            .header("Authorization", format!("Bearer {}", access_token));
+        let response = self.send(request).await?;
+
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(AppError::InsufficientScope(TOP_READ_SCOPE.to_string()));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(AppError::ExternalApi(format!(
+                "Spotify API error ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let data: TopItemsResponse<T> = response.json().await?;
+        Ok(data.items)
+    }
+
+    /// Token endpoint used for code exchange and refresh
+    fn token_url(&self) -> String {
+        format!("{}/api/token", self.accounts_base)
+    }
+
     /// Generate a random code verifier
     fn generate_code_verifier(&self) -> String {
         let mut rng = rand::thread_rng();
@@ -463,6 +915,40 @@ impl SpotifyService {
     pub fn is_token_expired(&self, expires_at: DateTime<Utc>) -> bool {
         Utc::now() + Duration::minutes(5) >= expires_at
     }
+
+    /// The profile's access token, refreshed and saved first if it has expired
+    pub async fn profile_access_token(&self, db: &DatabaseConnection, profile: profiles::Model) -> Result<String> {
+        let access_token = profile
+            .spotify_access_token
+            .clone()
+            .ok_or_else(|| AppError::Authentication("Spotify not connected".to_string()))?;
+
+        let expired = profile
+            .spotify_token_expires_at
+            .map(|exp| self.is_token_expired(exp.to_utc()))
+            .unwrap_or(true);
+        if !expired {
+            return Ok(access_token);
+        }
+
+        let refresh_token = profile
+            .spotify_refresh_token
+            .clone()
+            .ok_or_else(|| AppError::Authentication("Spotify token expired".to_string()))?;
+        let token_response = self.refresh_token(&refresh_token).await?;
+
+        let mut active: profiles::ActiveModel = profile.into();
+        active.spotify_access_token = Set(Some(token_response.access_token.clone()));
+        if let Some(new_refresh) = token_response.refresh_token {
+            active.spotify_refresh_token = Set(Some(new_refresh));
+        }
+        active.spotify_token_expires_at =
+            Set(Some((Utc::now() + Duration::seconds(token_response.expires_in)).into()));
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+
+        Ok(token_response.access_token)
+    }
 }
 
 #[cfg(test)]
@@ -489,4 +975,197 @@ mod tests {
         let challenge = service.generate_code_challenge(verifier);
         assert!(!challenge.is_empty());
     }
+
+    #[test]
+    fn test_stats_rate_and_eta() {
+        let stats = SpotifyStats::default();
+        assert_eq!(stats.rate_over(std::time::Duration::from_secs(10)), 0.0);
+        assert_eq!(stats.eta(100), None);
+
+        stats.requests.store(40, Ordering::Relaxed);
+        assert_eq!(stats.rate_over(std::time::Duration::from_secs(20)), 2.0);
+        assert_eq!(stats.rate_over(std::time::Duration::ZERO), 0.0);
+
+        assert_eq!(eta_at_rate(2.0, 840), Some(std::time::Duration::from_secs(420)));
+        assert_eq!(eta_at_rate(2.0, 0), Some(std::time::Duration::ZERO));
+        assert_eq!(eta_at_rate(0.0, 10), None);
+    }
+
+    #[test]
+    fn test_with_base_urls() {
+        let service = SpotifyService::new(
+            "test_client_id".to_string(),
+            "http://localhost:3000/callback".to_string(),
+        )
+        .with_base_urls("http://127.0.0.1:9000/", "http://127.0.0.1:9000/v1/");
+
+        assert_eq!(service.saved_albums_url(), "http://127.0.0.1:9000/v1/me/albums?limit=50");
+        assert!(service.is_saved_albums_url("http://127.0.0.1:9000/v1/me/albums?offset=50"));
+        assert!(!service.is_saved_albums_url("https://api.spotify.com/v1/me/albums?offset=50"));
+
+        let auth_url = service.generate_authorization_url().unwrap();
+        assert!(auth_url.url.starts_with("http://127.0.0.1:9000/authorize?"));
+        assert!(auth_url.url.contains(TOP_READ_SCOPE));
+    }
+
+    #[test]
+    fn test_album_barcode_from_external_ids() {
+        let album: SpotifyAlbum = serde_json::from_str(
+            r#"{
+                "id": "2noRn2Aes5aoNVsU6iWThc",
+                "name": "Discovery",
+                "artists": [{"id": "4tZwfgrHOc3mvqYlEYSvVi", "name": "Daft Punk"}],
+                "release_date": "2001-03-12",
+                "total_tracks": 14,
+                "images": [],
+                "genres": [],
+                "external_ids": {"upc": "724384960650"}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(album.barcode(), Some("724384960650"));
+    }
+
+    #[test]
+    fn test_album_without_external_ids_has_no_barcode() {
+        let album: SpotifyAlbum = serde_json::from_str(
+            r#"{
+                "id": "2noRn2Aes5aoNVsU6iWThc",
+                "name": "Discovery",
+                "artists": [{"id": "4tZwfgrHOc3mvqYlEYSvVi", "name": "Daft Punk"}],
+                "release_date": "2001-03-12",
+                "total_tracks": 14,
+                "images": []
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(album.barcode(), None);
+    }
+
+    #[test]
+    fn test_playlist_track_availability() {
+        let track = |id: serde_json::Value, is_playable: serde_json::Value| {
+            serde_json::json!({
+                "id": id,
+                "name": "Mr. Blue Sky",
+                "track_number": 7,
+                "disc_number": 1,
+                "duration_ms": 303573,
+                "album": {
+                    "id": "1gMxiQQSg5zeu4htBosASY",
+                    "name": "Out of the Blue",
+                    "artists": [],
+                    "release_date": "1977-10-03",
+                    "total_tracks": 17,
+                    "images": []
+                },
+                "artists": [],
+                "is_playable": is_playable
+            })
+        };
+        let item = |value: serde_json::Value| -> SpotifyPlaylistTrack {
+            serde_json::from_value(value).unwrap()
+        };
+
+        let playable = item(serde_json::json!({ "track": track("abc".into(), true.into()) }));
+        let no_market = item(serde_json::json!({ "track": track("abc".into(), serde_json::Value::Null) }));
+        let restricted = item(serde_json::json!({ "track": track("abc".into(), false.into()) }));
+        let missing_id = item(serde_json::json!({ "track": track(serde_json::Value::Null, serde_json::Value::Null) }));
+        let removed = item(serde_json::json!({ "track": null }));
+        let local = item(serde_json::json!({
+            "is_local": true,
+            "track": track(serde_json::Value::Null, serde_json::Value::Null)
+        }));
+
+        assert!(!playable.is_unavailable());
+        assert!(!no_market.is_unavailable());
+        assert!(restricted.is_unavailable());
+        assert!(missing_id.is_unavailable());
+        assert!(removed.is_unavailable());
+        assert!(!local.is_unavailable());
+    }
+
+    #[test]
+    fn test_podcast_entries_parse_and_are_not_music() {
+        let page: PlaylistTracksResponse = serde_json::from_str(include_str!(
+            "../../tests/fixtures/spotify/playlist_tracks_podcasts.json"
+        ))
+        .unwrap();
+        assert_eq!(page.items.len(), 4);
+
+        // Typed episodes are flagged whether they come in track form or as
+        // episode objects with a show and no album
+        let episodes: Vec<bool> = page.items.iter().map(|item| item.is_episode).collect();
+        assert_eq!(episodes, vec![false, true, false, true]);
+        assert!(page.items.iter().all(|item| !item.is_unavailable()));
+
+        let tracks: Vec<&SpotifyTrack> = page.items.iter().filter_map(|item| item.track.as_ref()).collect();
+        let music: Vec<bool> = tracks.iter().map(|track| track.is_music()).collect();
+        assert_eq!(music, vec![true, false]);
+
+        // The untyped episode has no artists or track numbers at all
+        assert!(tracks[1].artists.is_empty());
+        assert!(tracks[1].album.artists.is_empty());
+        assert_eq!(tracks[1].track_number, 0);
+
+        // The flag survives a round trip through the crate's own serialization
+        let episode: SpotifyPlaylistTrack =
+            serde_json::from_value(serde_json::to_value(&page.items[3]).unwrap()).unwrap();
+        assert!(episode.is_episode);
+        assert!(episode.track.is_none());
+    }
+
+    #[test]
+    fn test_saved_show_is_not_music() {
+        let show: SpotifyAlbum = serde_json::from_str(
+            r#"{
+                "id": "4rOoJ6Egrf8K2IrywzwOMk",
+                "name": "The Joe Rogan Experience",
+                "type": "show",
+                "artists": null,
+                "release_date": "2009-12-24",
+                "total_tracks": 2100,
+                "images": []
+            }"#,
+        )
+        .unwrap();
+
+        assert!(show.artists.is_empty());
+        assert!(!show.is_music());
+    }
+
+    #[test]
+    fn test_parse_playlist_id() {
+        let id = "37i9dQZF1DX0XUsuxWHRQd";
+        for input in [
+            "37i9dQZF1DX0XUsuxWHRQd",
+            "  37i9dQZF1DX0XUsuxWHRQd\n",
+            "spotify:playlist:37i9dQZF1DX0XUsuxWHRQd",
+            "spotify:user:spotify:playlist:37i9dQZF1DX0XUsuxWHRQd",
+            "https://open.spotify.com/playlist/37i9dQZF1DX0XUsuxWHRQd",
+            "https://open.spotify.com/playlist/37i9dQZF1DX0XUsuxWHRQd?si=a1b2c3d4e5f6",
+            "https://open.spotify.com/intl-de/playlist/37i9dQZF1DX0XUsuxWHRQd",
+            "https://open.spotify.com/embed/playlist/37i9dQZF1DX0XUsuxWHRQd#top",
+            "open.spotify.com/playlist/37i9dQZF1DX0XUsuxWHRQd/",
+        ] {
+            assert_eq!(parse_playlist_id(input), Some(id), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_playlist_id_rejects_other_links() {
+        for input in [
+            "",
+            "https://open.spotify.com/album/2noRn2Aes5aoNVsU6iWThc",
+            "spotify:album:2noRn2Aes5aoNVsU6iWThc",
+            "https://example.com/playlist/37i9dQZF1DX0XUsuxWHRQd",
+            "https://open.spotify.com/playlist/",
+            "__LIKED_SONGS__:1",
+            "37i9dQZF1DX0XUsuxWHRQd/../tracks",
+        ] {
+            assert_eq!(parse_playlist_id(input), None, "{}", input);
+        }
+    }
 }
@@ -0,0 +1,47 @@
+//! Redis cache of the library stats
+//!
+//! The stats are refreshed by HTMX on every visit to the stats page. They're
+//! cached per profile for a minute under the album grid's generation, so any
+//! change to albums (`album_grid_cache::invalidate`) drops them at once.
+//!
+//! As with the grid, Redis problems never fail a request.
+
+use redis::aio::ConnectionManager;
+
+use crate::{
+    handlers::albums::StatsResponse,
+    services::{album_grid_cache, CacheService},
+};
+
+const TTL_SECONDS: usize = 60;
+
+/// Cached stats for a profile, if any
+///
+/// `exclude_imports` is the profile's setting to leave playlist imports out;
+/// stats counted either way are kept apart.
+pub async fn get(redis: &ConnectionManager, profile_id: i32, exclude_imports: bool) -> Option<StatsResponse> {
+    let cache = CacheService::new(redis.clone());
+    let key = stats_key(&cache, profile_id, exclude_imports).await?;
+    match cache.get(&key).await {
+        Ok(stats) => stats,
+        Err(e) => {
+            tracing::warn!("Failed to read cached stats: {}", e);
+            None
+        }
+    }
+}
+
+pub async fn put(redis: &ConnectionManager, profile_id: i32, exclude_imports: bool, stats: &StatsResponse) {
+    let cache = CacheService::new(redis.clone());
+    let Some(key) = stats_key(&cache, profile_id, exclude_imports).await else {
+        return;
+    };
+    if let Err(e) = cache.set(&key, stats, Some(TTL_SECONDS)).await {
+        tracing::warn!("Failed to cache stats: {}", e);
+    }
+}
+
+async fn stats_key(cache: &CacheService, profile_id: i32, exclude_imports: bool) -> Option<String> {
+    let generation = album_grid_cache::generation(cache).await?;
+    Some(format!("stats:{}:{}:{}", generation, profile_id, exclude_imports))
+}
@@ -0,0 +1,225 @@
+//! "You might also want" suggestions for an owned album
+//!
+//! Spotify's related artists of the album's artist are matched against the
+//! library, and their albums already there but not owned (usually brought in
+//! by playlist imports) are suggested. Nothing is created: an album only shows
+//! up once it's in the library. Related artists are cached per artist for a
+//! week; the ownership side is always read fresh.
+
+use std::collections::HashMap;
+
+use redis::aio::ConnectionManager;
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, JoinType, QueryFilter,
+    QuerySelect, RelationTrait,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        entities::{albums, artists, playlist_tracks, playlists, tracks},
+        enums::OwnershipStatus,
+        profile, slug,
+    },
+    error::Result,
+    services::{spotify::SpotifyTopArtist, CacheService},
+};
+
+/// Most suggestions shown for an album
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// How long an artist's related artists are cached
+const RELATED_ARTISTS_TTL_SECONDS: usize = 7 * 24 * 60 * 60;
+
+/// A related artist as cached: just what ranking needs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedArtist {
+    pub spotify_id: String,
+    pub popularity: i32,
+}
+
+impl From<SpotifyTopArtist> for RelatedArtist {
+    fn from(artist: SpotifyTopArtist) -> Self {
+        Self {
+            spotify_id: artist.id,
+            popularity: artist.popularity.unwrap_or(0),
+        }
+    }
+}
+
+/// An unowned library album by a related artist
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlbumSuggestion {
+    pub album_id: i32,
+    /// Path segment for the album's detail link
+    pub slug: String,
+    pub title: String,
+    pub artist_name: String,
+    pub cover_art_url: Option<String>,
+    pub ownership_status: String,
+    /// Tracks of the album in the profile's playlists
+    pub playlist_appearances: i64,
+    /// Spotify popularity of the album's artist
+    pub artist_popularity: i32,
+}
+
+/// Best suggestions first: most playlist appearances, then the more popular
+/// artist, then by title; at most `limit` of them
+pub fn rank(mut suggestions: Vec<AlbumSuggestion>, limit: usize) -> Vec<AlbumSuggestion> {
+    suggestions.sort_by(|a, b| {
+        b.playlist_appearances
+            .cmp(&a.playlist_appearances)
+            .then(b.artist_popularity.cmp(&a.artist_popularity))
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// Cached related artists of a Spotify artist; Redis problems are a miss
+pub async fn cached_related_artists(redis: &ConnectionManager, spotify_id: &str) -> Option<Vec<RelatedArtist>> {
+    let key = CacheService::spotify_related_artists_key(spotify_id);
+    match CacheService::new(redis.clone()).get(&key).await {
+        Ok(related) => related,
+        Err(e) => {
+            tracing::warn!("Failed to read cached related artists: {}", e);
+            None
+        }
+    }
+}
+
+pub async fn cache_related_artists(redis: &ConnectionManager, spotify_id: &str, related: &[RelatedArtist]) {
+    let key = CacheService::spotify_related_artists_key(spotify_id);
+    let cache = CacheService::new(redis.clone());
+    if let Err(e) = cache.set(&key, &related, Some(RELATED_ARTISTS_TTL_SECONDS)).await {
+        tracing::warn!("Failed to cache related artists: {}", e);
+    }
+}
+
+/// Unowned albums in a profile's library by the `related` artists, ranked
+pub async fn library_suggestions(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    related: &[RelatedArtist],
+) -> Result<Vec<AlbumSuggestion>> {
+    let popularity: HashMap<&str, i32> = related
+        .iter()
+        .map(|a| (a.spotify_id.as_str(), a.popularity))
+        .collect();
+    let related_artists = artists::Entity::find()
+        .filter(artists::Column::SpotifyId.is_in(popularity.keys().copied()))
+        .all(db)
+        .await?;
+    if related_artists.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let candidates = albums::Entity::find()
+        .filter(albums::Column::ArtistId.is_in(related_artists.iter().map(|a| a.id)))
+        .filter(albums::Column::OwnershipStatus.ne(OwnershipStatus::Owned.as_str()))
+        .filter(albums::Column::ArchivedAt.is_null())
+        .filter(profile::albums_in_profile(profile_id))
+        .all(db)
+        .await?;
+    let appearances = playlist_appearances(db, profile_id, candidates.iter().map(|a| a.id).collect()).await?;
+
+    let artists_by_id: HashMap<i32, &artists::Model> = related_artists.iter().map(|a| (a.id, a)).collect();
+    let suggestions = candidates
+        .into_iter()
+        .filter_map(|album| {
+            let artist = artists_by_id.get(&album.artist_id)?;
+            let artist_popularity = artist
+                .spotify_id
+                .as_deref()
+                .and_then(|id| popularity.get(id))
+                .copied()
+                .unwrap_or(0);
+            Some(AlbumSuggestion {
+                album_id: album.id,
+                slug: slug::link_key(album.slug.as_deref(), album.id),
+                playlist_appearances: appearances.get(&album.id).copied().unwrap_or(0),
+                title: album.title,
+                artist_name: artist.name.clone(),
+                cover_art_url: album.cover_art_url,
+                ownership_status: album.ownership_status,
+                artist_popularity,
+            })
+        })
+        .collect();
+    Ok(rank(suggestions, MAX_SUGGESTIONS))
+}
+
+/// Playlist tracks from each album across the profile's live playlists
+async fn playlist_appearances(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    album_ids: Vec<i32>,
+) -> Result<HashMap<i32, i64>> {
+    #[derive(FromQueryResult)]
+    struct AlbumAppearances {
+        album_id: i32,
+        appearances: i64,
+    }
+
+    if album_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows = playlist_tracks::Entity::find()
+        .select_only()
+        .column(tracks::Column::AlbumId)
+        .column_as(Expr::col((playlist_tracks::Entity, playlist_tracks::Column::Id)).count(), "appearances")
+        .join(JoinType::InnerJoin, playlist_tracks::Relation::Tracks.def())
+        .join(JoinType::InnerJoin, playlist_tracks::Relation::Playlists.def())
+        .filter(tracks::Column::AlbumId.is_in(album_ids))
+        .filter(playlists::Column::ArchivedAt.is_null())
+        .filter(profile::playlists_in_profile(profile_id))
+        .group_by(tracks::Column::AlbumId)
+        .into_model::<AlbumAppearances>()
+        .all(db)
+        .await?;
+    Ok(rows.into_iter().map(|r| (r.album_id, r.appearances)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(title: &str, playlist_appearances: i64, artist_popularity: i32) -> AlbumSuggestion {
+        AlbumSuggestion {
+            album_id: 1,
+            slug: title.to_lowercase(),
+            title: title.to_string(),
+            artist_name: "Artist".to_string(),
+            cover_art_url: None,
+            ownership_status: OwnershipStatus::NotOwned.as_str().to_string(),
+            playlist_appearances,
+            artist_popularity,
+        }
+    }
+
+    fn titles(suggestions: &[AlbumSuggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rank_prefers_playlist_appearances_then_popularity() {
+        let ranked = rank(
+            vec![
+                suggestion("Cross", 1, 80),
+                suggestion("Moon Safari", 4, 60),
+                suggestion("Talkie Walkie", 1, 90),
+                suggestion("Homework", 0, 95),
+            ],
+            MAX_SUGGESTIONS,
+        );
+        assert_eq!(titles(&ranked), vec!["Moon Safari", "Talkie Walkie", "Cross", "Homework"]);
+    }
+
+    #[test]
+    fn test_rank_breaks_ties_by_title_and_caps_results() {
+        let many: Vec<_> = ["f", "E", "d", "c", "B", "a"].iter().map(|t| suggestion(t, 2, 50)).collect();
+        let ranked = rank(many, MAX_SUGGESTIONS);
+        assert_eq!(titles(&ranked), vec!["a", "B", "c", "d", "E"]);
+        assert!(rank(Vec::new(), MAX_SUGGESTIONS).is_empty());
+    }
+}
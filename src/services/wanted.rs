@@ -0,0 +1,370 @@
+//! How long albums have been wanted
+//!
+//! `albums.wanted_since` is set when an album becomes not owned: by the sync
+//! that creates it, or by `ownership::set_status` when it moves back. Moving
+//! on to any other status clears it. The want-list board shows the age, and
+//! the wanted export can leave out recent wants.
+//!
+//! Priorities are per profile, on its `profile_albums` link. When a profile's
+//! demotion rule is on, its wants older than the threshold drop one priority
+//! at most once per threshold; those already at the lowest priority have the
+//! want cleared (`want_cleared_at`), which takes them off that profile's
+//! want-list board and wanted export. Must-haves are never touched. Every
+//! demotion is logged in `want_demotions` and can be restored.
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use sea_orm::{
+    sea_query::Query,
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    Set, TransactionTrait,
+};
+
+use crate::{
+    db::{
+        entities::{albums, profile_albums, profiles, want_demotions},
+        enums::OwnershipStatus,
+        profile,
+        settings as db_settings,
+    },
+    error::{AppError, Result},
+};
+
+/// Priority the demotion rule never lowers
+pub const MUST_HAVE_PRIORITY: i32 = 5;
+
+/// Priority of albums nobody set one for
+pub const DEFAULT_WANT_PRIORITY: i32 = 3;
+
+/// Priority below which the demotion rule clears the want instead
+pub const LOWEST_WANT_PRIORITY: i32 = 1;
+
+/// Days an album is wanted before the demotion rule lowers it
+pub const DEFAULT_DEMOTION_DAYS: i32 = 365;
+
+/// Time an album has been wanted, in the largest whole unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WantedAge {
+    Days(i64),
+    Months(i64),
+    Years(i64),
+}
+
+impl WantedAge {
+    pub fn since(wanted_since: DateTime<FixedOffset>, now: DateTime<Utc>) -> Self {
+        let days = (now - wanted_since.with_timezone(&Utc)).num_days().max(0);
+        match days {
+            0..=30 => Self::Days(days),
+            31..=364 => Self::Months(days / 30),
+            _ => Self::Years(days / 365),
+        }
+    }
+}
+
+/// Wanted albums that became wanted at least `days` before `now`
+pub fn wanted_for_at_least(days: u32, now: DateTime<Utc>) -> Condition {
+    let cutoff = now - Duration::days(i64::from(days));
+    Condition::all()
+        .add(albums::Column::OwnershipStatus.eq(OwnershipStatus::NotOwned.as_str()))
+        .add(albums::Column::WantedSince.lte(cutoff))
+}
+
+/// Albums that are either not wanted at all or still wanted by `profile_id`
+///
+/// Leaves out the wants the demotion rule cleared for that profile.
+pub fn not_cleared(profile_id: i32) -> Condition {
+    Condition::any()
+        .add(albums::Column::OwnershipStatus.ne(OwnershipStatus::NotOwned.as_str()))
+        .add(profile::wants_cleared_in_profile(profile_id).not())
+}
+
+/// Albums `profile_id`'s demotion rule lowers when run at `now` with a
+/// threshold of `days`
+///
+/// In the profile's library, wanted for at least `days`, not archived, want
+/// not cleared, not a must-have, and not demoted or restored for the profile
+/// within the last `days`, so each album drops at most one priority per
+/// threshold.
+pub fn demotion_candidates(profile_id: i32, days: u32, now: DateTime<Utc>) -> Condition {
+    let cutoff = now - Duration::days(i64::from(days));
+    let demotable = Query::select()
+        .column(profile_albums::Column::AlbumId)
+        .from(profile_albums::Entity)
+        .and_where(profile_albums::Column::ProfileId.eq(profile_id))
+        .and_where(profile_albums::Column::WantClearedAt.is_null())
+        .and_where(profile_albums::Column::WantPriority.lt(MUST_HAVE_PRIORITY))
+        .to_owned();
+    let recently_touched = Query::select()
+        .column(want_demotions::Column::AlbumId)
+        .from(want_demotions::Entity)
+        .and_where(want_demotions::Column::ProfileId.eq(profile_id))
+        .cond_where(
+            Condition::any()
+                .add(want_demotions::Column::CreatedAt.gt(cutoff))
+                .add(want_demotions::Column::RestoredAt.gt(cutoff)),
+        )
+        .to_owned();
+
+    wanted_for_at_least(days, now)
+        .add(albums::Column::ArchivedAt.is_null())
+        .add(albums::Column::Id.in_subquery(demotable))
+        .add(albums::Column::Id.not_in_subquery(recently_touched))
+}
+
+/// Demotion threshold in days for `profile`, or `None` while the rule is off
+pub async fn demotion_days(db: &DatabaseConnection, profile: &profiles::Model) -> Result<Option<u32>> {
+    let Some(settings) = db_settings::for_profile(db, profile).await? else {
+        return Ok(None);
+    };
+    if !settings.wanted_demotion_enabled.unwrap_or(false) {
+        return Ok(None);
+    }
+    let days = settings.wanted_demotion_days.unwrap_or(DEFAULT_DEMOTION_DAYS);
+    Ok(Some(days.max(1) as u32))
+}
+
+/// Lower the priority of `profile_id`'s stale wants by one, clearing those
+/// already at the lowest priority
+pub async fn demote_stale_wants(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    days: u32,
+    now: DateTime<Utc>,
+) -> Result<Vec<want_demotions::Model>> {
+    let candidates = profile_albums::Entity::find()
+        .filter(profile_albums::Column::ProfileId.eq(profile_id))
+        .filter(
+            profile_albums::Column::AlbumId.in_subquery(
+                Query::select()
+                    .column(albums::Column::Id)
+                    .from(albums::Entity)
+                    .cond_where(demotion_candidates(profile_id, days, now))
+                    .to_owned(),
+            ),
+        )
+        .order_by_asc(profile_albums::Column::AlbumId)
+        .all(db)
+        .await?;
+
+    let mut demotions = Vec::with_capacity(candidates.len());
+    for link in candidates {
+        let from_priority = link.want_priority;
+        let clear = from_priority <= LOWEST_WANT_PRIORITY;
+
+        let txn = db.begin().await?;
+        let mut active: profile_albums::ActiveModel = link.into();
+        if clear {
+            active.want_cleared_at = Set(Some(now.into()));
+        } else {
+            active.want_priority = Set(from_priority - 1);
+        }
+        let link = active.update(&txn).await?;
+
+        let demotion = want_demotions::ActiveModel {
+            album_id: Set(link.album_id),
+            profile_id: Set(profile_id),
+            from_priority: Set(from_priority),
+            to_priority: Set(link.want_priority),
+            cleared: Set(clear),
+            created_at: Set(now.into()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        txn.commit().await?;
+
+        demotions.push(demotion);
+    }
+
+    if !demotions.is_empty() {
+        tracing::info!("Demoted {} stale wants in profile {}", demotions.len(), profile_id);
+    }
+    Ok(demotions)
+}
+
+/// Undo a demotion: the album gets its priority back and is wanted again if
+/// the demotion cleared the want
+///
+/// Restoring an already restored demotion changes nothing, and demotions of
+/// other profiles are not found.
+pub async fn restore_demotion(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    demotion_id: i32,
+) -> Result<want_demotions::Model> {
+    let demotion = want_demotions::Entity::find_by_id(demotion_id)
+        .filter(want_demotions::Column::ProfileId.eq(profile_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Demotion {} not found", demotion_id)))?;
+    if demotion.restored_at.is_some() {
+        return Ok(demotion);
+    }
+
+    let link = profile::album_link(db, profile_id, demotion.album_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Album {} not in this library", demotion.album_id))
+        })?;
+
+    let now = Utc::now();
+    let txn = db.begin().await?;
+    let mut active: profile_albums::ActiveModel = link.into();
+    active.want_priority = Set(demotion.from_priority);
+    if demotion.cleared {
+        active.want_cleared_at = Set(None);
+    }
+    active.update(&txn).await?;
+
+    let mut restored: want_demotions::ActiveModel = demotion.into();
+    restored.restored_at = Set(Some(now.into()));
+    let restored = restored.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(restored)
+}
+
+/// Demotions of one album in `profile_id`, newest first
+pub async fn album_demotions(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    album_id: i32,
+) -> Result<Vec<want_demotions::Model>> {
+    Ok(want_demotions::Entity::find()
+        .filter(want_demotions::Column::AlbumId.eq(album_id))
+        .filter(want_demotions::Column::ProfileId.eq(profile_id))
+        .order_by_desc(want_demotions::Column::CreatedAt)
+        .all(db)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use sea_orm::PaginatorTrait;
+
+    fn age_after(days: i64) -> WantedAge {
+        let now = Utc::now();
+        WantedAge::since((now - Duration::days(days)).fixed_offset(), now)
+    }
+
+    #[test]
+    fn test_wanted_age_uses_largest_whole_unit() {
+        assert_eq!(age_after(0), WantedAge::Days(0));
+        assert_eq!(age_after(30), WantedAge::Days(30));
+        assert_eq!(age_after(31), WantedAge::Months(1));
+        assert_eq!(age_after(364), WantedAge::Months(12));
+        assert_eq!(age_after(365), WantedAge::Years(1));
+        assert_eq!(age_after(800), WantedAge::Years(2));
+        // Clock skew never gives a negative age
+        assert_eq!(age_after(-3), WantedAge::Days(0));
+    }
+
+    /// Album in the default profile's library, wanted for `days` at `priority`
+    async fn wanted_album(db: &DatabaseConnection, title: &str, days: i64, priority: i32) -> albums::Model {
+        let artist = create_test_artist(db, &format!("{} Artist", title), None).await;
+        let album = create_test_album(db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.wanted_since = Set(Some((Utc::now() - Duration::days(days)).into()));
+        let album = active.update(db).await.unwrap();
+
+        let default_profile = profile::default_profile(db).await.unwrap();
+        let mut link: profile_albums::ActiveModel = profile::album_link(db, default_profile.id, album.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .into();
+        link.want_priority = Set(priority);
+        link.update(db).await.unwrap();
+        album
+    }
+
+    async fn link(db: &DatabaseConnection, profile_id: i32, album: &albums::Model) -> profile_albums::Model {
+        profile::album_link(db, profile_id, album.id).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_demotion_candidates_skip_recent_archived_and_must_haves() {
+        let db = setup_test_db().await;
+        let profile = profile::default_profile(&db).await.unwrap();
+        let stale = wanted_album(&db, "Stale", 400, DEFAULT_WANT_PRIORITY).await;
+        wanted_album(&db, "Recent", 10, DEFAULT_WANT_PRIORITY).await;
+        wanted_album(&db, "Must Have", 400, MUST_HAVE_PRIORITY).await;
+        let archived = wanted_album(&db, "Archived", 400, DEFAULT_WANT_PRIORITY).await;
+        let mut active: albums::ActiveModel = archived.into();
+        active.archived_at = Set(Some(Utc::now().into()));
+        active.update(&db).await.unwrap();
+
+        let candidates = albums::Entity::find()
+            .filter(demotion_candidates(profile.id, 365, Utc::now()))
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(candidates.iter().map(|a| a.id).collect::<Vec<_>>(), vec![stale.id]);
+    }
+
+    #[tokio::test]
+    async fn test_demotion_lowers_once_per_threshold_and_never_touches_must_haves() {
+        let db = setup_test_db().await;
+        let profile = profile::default_profile(&db).await.unwrap();
+        let stale = wanted_album(&db, "Stale", 400, DEFAULT_WANT_PRIORITY).await;
+        let must_have = wanted_album(&db, "Must Have", 4000, MUST_HAVE_PRIORITY).await;
+
+        let now = Utc::now();
+        let demotions = demote_stale_wants(&db, profile.id, 365, now).await.unwrap();
+        assert_eq!(demotions.len(), 1);
+        assert_eq!((demotions[0].from_priority, demotions[0].to_priority), (3, 2));
+        assert!(!demotions[0].cleared);
+
+        // The next daily run leaves it alone until another threshold passes
+        let tomorrow = now + Duration::days(1);
+        assert!(demote_stale_wants(&db, profile.id, 365, tomorrow).await.unwrap().is_empty());
+
+        assert_eq!(link(&db, profile.id, &stale).await.want_priority, 2);
+        // Still wanted since the same day; only the priority moved
+        let stale = albums::Entity::find_by_id(stale.id).one(&db).await.unwrap().unwrap();
+        let wanted_since = stale.wanted_since.unwrap().with_timezone(&Utc);
+        assert_eq!(wanted_since.date_naive(), (now - Duration::days(400)).date_naive());
+        assert_eq!(link(&db, profile.id, &must_have).await.want_priority, MUST_HAVE_PRIORITY);
+    }
+
+    #[tokio::test]
+    async fn test_lowest_priority_is_cleared_per_profile_and_restorable() {
+        let db = setup_test_db().await;
+        let profile = profile::default_profile(&db).await.unwrap();
+        let other = profile::create_profile(&db, "Other").await.unwrap();
+        let album = wanted_album(&db, "Forgotten", 400, LOWEST_WANT_PRIORITY).await;
+        profile::link_album(&db, other.id, album.id).await.unwrap();
+
+        let demotions = demote_stale_wants(&db, profile.id, 365, Utc::now()).await.unwrap();
+        assert!(demotions[0].cleared);
+        assert!(link(&db, profile.id, &album).await.want_cleared_at.is_some());
+
+        // The other profile still wants it, at its own priority
+        let theirs = link(&db, other.id, &album).await;
+        assert_eq!(theirs.want_cleared_at, None);
+        assert_eq!(theirs.want_priority, DEFAULT_WANT_PRIORITY);
+        let still_wanted = |profile_id| {
+            albums::Entity::find()
+                .filter(albums::Column::Id.eq(album.id))
+                .filter(not_cleared(profile_id))
+                .count(&db)
+        };
+        assert_eq!(still_wanted(profile.id).await.unwrap(), 0);
+        assert_eq!(still_wanted(other.id).await.unwrap(), 1);
+
+        let err = restore_demotion(&db, other.id, demotions[0].id).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+
+        let restored = restore_demotion(&db, profile.id, demotions[0].id).await.unwrap();
+        assert!(restored.restored_at.is_some());
+        let mine = link(&db, profile.id, &album).await;
+        assert_eq!(mine.want_cleared_at, None);
+        assert_eq!(mine.want_priority, LOWEST_WANT_PRIORITY);
+
+        // Restoring again changes nothing, and the restore restarts the clock
+        let again = restore_demotion(&db, profile.id, demotions[0].id).await.unwrap();
+        assert_eq!(again.restored_at, restored.restored_at);
+        assert!(demote_stale_wants(&db, profile.id, 365, Utc::now()).await.unwrap().is_empty());
+    }
+}
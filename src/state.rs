@@ -3,7 +3,10 @@ use sea_orm::DatabaseConnection;
 use std::sync::Arc;
 
 use crate::config::Config;
+use crate::events::{EventBus, EVENT_CAPACITY};
 use crate::jobs::JobQueue;
+use crate::logging::LogFilter;
+use crate::tasks::cover_art::CoverArtDownloader;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -11,6 +14,9 @@ pub struct AppState {
     pub redis: ConnectionManager,
     pub config: Arc<Config>,
     pub job_queue: JobQueue,
+    pub log_filter: LogFilter,
+    pub cover_art: CoverArtDownloader,
+    pub events: EventBus,
 }
 
 impl AppState {
@@ -19,12 +25,18 @@ impl AppState {
         redis: ConnectionManager,
         config: Config,
         job_queue: JobQueue,
+        log_filter: LogFilter,
     ) -> Self {
+        let cover_art = CoverArtDownloader::new(&config);
+
         Self {
             db,
             redis,
             config: Arc::new(config),
             job_queue,
+            log_filter,
+            cover_art,
+            events: EventBus::new(EVENT_CAPACITY),
         }
     }
 }
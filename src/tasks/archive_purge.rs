@@ -0,0 +1,38 @@
+//! Daily purge of old archived rows
+//!
+//! Each profile with `archive_retention_days` set loses the albums, playlists
+//! and Liked Songs tracks archived longer ago than that. See
+//! `services::archive::purge_archived` for what a purge removes.
+
+use chrono::{Duration, Utc};
+use sea_orm::EntityTrait;
+
+use crate::{
+    db::entities::profiles,
+    error::Result,
+    services::{album_grid_cache, archive},
+    state::AppState,
+};
+
+/// Purge the archive of every profile that has a retention set
+///
+/// Returns how many rows were removed across profiles.
+pub async fn purge_archives(state: &AppState) -> Result<u64> {
+    let now = Utc::now();
+    let mut albums = 0;
+    let mut purged = 0;
+    for profile in profiles::Entity::find().all(&state.db).await? {
+        let Some(days) = archive::retention_days(&state.db, &profile).await? else {
+            continue;
+        };
+        let cutoff = now - Duration::days(days.into());
+        let removed = archive::purge_archived(&state.db, profile.id, cutoff).await?;
+        albums += removed.albums;
+        purged += removed.total();
+    }
+
+    if albums > 0 {
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+    Ok(purged)
+}
@@ -0,0 +1,241 @@
+//! Spotify images for the artists in the library
+//!
+//! Syncs only see simplified artist objects, which carry no images, so images
+//! are filled in by a backfill job that asks `GET /artists` for 50 artists at
+//! a time through the rate-limited Spotify client. After every batch the job
+//! stores the last artist it handled in its `sync_cursor`; a backfill started
+//! after a failed one continues from there. Libraries synced before artists
+//! had images get one backfill queued on their own at startup.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Select, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        entities::{artists, jobs, user_settings},
+        enums::{JobStatus, JobType},
+        profile, settings as db_settings,
+    },
+    handlers::jobs::enqueue_job,
+    jobs::record_result,
+    services::{spotify::ARTISTS_BATCH_SIZE, SpotifyService},
+    state::AppState,
+    tasks::spotify_sync::{report_progress, SyncProgress},
+};
+
+/// Where a backfill stands, stored as JSON in its job's `sync_cursor`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackfillCursor {
+    /// Fetch images again for artists that already have one
+    pub refresh: bool,
+    /// Highest artist ID handled so far
+    pub last_artist_id: Option<i32>,
+}
+
+/// Result payload stored on a finished backfill
+#[derive(Debug, Default, Serialize)]
+pub struct BackfillSummary {
+    /// Artists looked up on Spotify by this run
+    pub processed: usize,
+    /// Artists whose image was set or changed
+    pub updated: usize,
+    /// Artists Spotify has no image for, or doesn't know
+    pub without_image: usize,
+    /// Artist ID a failed backfill had reached, when this run continued from it
+    pub resumed_after: Option<i32>,
+}
+
+/// Create a backfill job and queue it
+///
+/// The access token of `profile_id` (the default profile if `None`) is used.
+pub async fn queue_backfill(
+    state: &AppState,
+    profile_id: Option<i32>,
+    refresh: bool,
+) -> crate::error::Result<jobs::Model> {
+    let cursor = BackfillCursor { refresh, last_artist_id: None };
+    let now = Utc::now().into();
+    let job = jobs::ActiveModel {
+        job_type: Set(JobType::ArtistImageBackfill.as_str().to_string()),
+        status: Set(JobStatus::Pending.as_str().to_string()),
+        entity_id: Set(profile_id),
+        sync_cursor: Set(Some(serde_json::to_string(&cursor)?)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
+
+    enqueue_job(state, job.clone(), JobType::ArtistImageBackfill).await?;
+    Ok(job)
+}
+
+/// Queue the backfill once for libraries synced before artists had images
+///
+/// Waits until settings exist and the default profile is connected to
+/// Spotify; `artist_image_backfill_queued` then keeps it from queuing again.
+pub async fn queue_initial_backfill(state: &AppState) -> Result<()> {
+    let Some(settings) = db_settings::shared(&state.db).await? else {
+        return Ok(());
+    };
+    if settings.artist_image_backfill_queued == Some(true) {
+        return Ok(());
+    }
+    let default_profile = profile::default_profile(&state.db).await?;
+    if default_profile.spotify_access_token.is_none() {
+        tracing::debug!("Not backfilling artist images until Spotify is connected");
+        return Ok(());
+    }
+
+    let missing = pending_artists(BackfillCursor::default()).count(&state.db).await?;
+    if missing > 0 {
+        let job = queue_backfill(state, None, false).await?;
+        tracing::info!("Queued artist image backfill job {} for {} artists", job.id, missing);
+    }
+
+    let mut active: user_settings::ActiveModel = settings.into();
+    active.artist_image_backfill_queued = Set(Some(true));
+    active.updated_at = Set(Utc::now().into());
+    active.update(&state.db).await?;
+    Ok(())
+}
+
+/// Main entry point for the artist image backfill job
+pub async fn run_artist_image_backfill(state: AppState, job_id: i32, profile_id: Option<i32>) -> Result<()> {
+    let db = &state.db;
+    let profile = profile::resolve_profile(db, profile_id).await?;
+    let spotify_service = SpotifyService::from_config(&state.config);
+    let access_token = spotify_service.profile_access_token(&state.db, profile).await?;
+
+    let mut cursor = start_cursor(db, job_id).await?;
+    let mut summary = BackfillSummary {
+        resumed_after: cursor.last_artist_id,
+        ..Default::default()
+    };
+    if let Some(last_artist_id) = cursor.last_artist_id {
+        tracing::info!("Resuming artist image backfill after artist {}", last_artist_id);
+    }
+
+    let total = pending_artists(cursor).count(db).await? as usize;
+    save_checkpoint(db, job_id, cursor, 0, total).await?;
+
+    loop {
+        let batch = pending_artists(cursor)
+            .order_by_asc(artists::Column::Id)
+            .limit(ARTISTS_BATCH_SIZE as u64)
+            .all(db)
+            .await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        cursor.last_artist_id = Some(last.id);
+
+        let ids: Vec<String> = batch.iter().filter_map(|a| a.spotify_id.clone()).collect();
+        let images: HashMap<String, String> = spotify_service
+            .fetch_artists(&access_token, &ids)
+            .await?
+            .into_iter()
+            .filter_map(|a| a.images.first().map(|image| (a.id, image.url.clone())))
+            .collect();
+
+        for artist in batch {
+            summary.processed += 1;
+            let image = artist.spotify_id.as_ref().and_then(|id| images.get(id));
+            let Some(image) = image else {
+                summary.without_image += 1;
+                continue;
+            };
+            if artist.image_url.as_ref() != Some(image) {
+                let mut active: artists::ActiveModel = artist.into();
+                active.image_url = Set(Some(image.clone()));
+                active.updated_at = Set(Utc::now().into());
+                active.update(db).await?;
+                summary.updated += 1;
+            }
+        }
+
+        save_checkpoint(db, job_id, cursor, summary.processed, total).await?;
+        let remaining_batches = total.saturating_sub(summary.processed).div_ceil(ARTISTS_BATCH_SIZE);
+        let progress = SyncProgress::new(&spotify_service.stats(), remaining_batches as u64);
+        report_progress(db, job_id, &progress).await?;
+    }
+
+    tracing::info!(
+        "Artist image backfill completed: {} artists looked up, {} images set",
+        summary.processed,
+        summary.updated
+    );
+    record_result(db, job_id, Some(summary.processed), &summary).await?;
+    Ok(())
+}
+
+/// Artists a backfill at `cursor` still has to look up
+fn pending_artists(cursor: BackfillCursor) -> Select<artists::Entity> {
+    let mut select = artists::Entity::find().filter(artists::Column::SpotifyId.is_not_null());
+    if !cursor.refresh {
+        select = select.filter(artists::Column::ImageUrl.is_null());
+    }
+    if let Some(last_artist_id) = cursor.last_artist_id {
+        select = select.filter(artists::Column::Id.gt(last_artist_id));
+    }
+    select
+}
+
+fn parse_cursor(job: &jobs::Model) -> Option<BackfillCursor> {
+    job.sync_cursor.as_deref().and_then(|c| serde_json::from_str(c).ok())
+}
+
+/// Find the cursor to start a backfill from
+///
+/// Uses the job's own checkpoint if it has one. Otherwise it inherits the
+/// checkpoint of the previous backfill when that one failed partway through
+/// in the same mode; a refresh never resumes a plain backfill or the reverse.
+async fn start_cursor(db: &DatabaseConnection, job_id: i32) -> Result<BackfillCursor> {
+    let own = jobs::Entity::find_by_id(job_id)
+        .one(db)
+        .await?
+        .and_then(|job| parse_cursor(&job))
+        .unwrap_or_default();
+    if own.last_artist_id.is_some() {
+        return Ok(own);
+    }
+
+    let previous = jobs::Entity::find()
+        .filter(jobs::Column::JobType.eq(JobType::ArtistImageBackfill.as_str()))
+        .filter(jobs::Column::Id.ne(job_id))
+        .order_by_desc(jobs::Column::Id)
+        .one(db)
+        .await?
+        .filter(|job| job.status == JobStatus::Failed.as_str())
+        .and_then(|job| parse_cursor(&job))
+        .filter(|previous| previous.refresh == own.refresh);
+    Ok(previous.unwrap_or(own))
+}
+
+/// Store a backfill's checkpoint and counts on its job
+async fn save_checkpoint(
+    db: &DatabaseConnection,
+    job_id: i32,
+    cursor: BackfillCursor,
+    processed: usize,
+    total: usize,
+) -> Result<()> {
+    if let Some(job) = jobs::Entity::find_by_id(job_id).one(db).await? {
+        let mut active: jobs::ActiveModel = job.into();
+        active.sync_cursor = Set(Some(serde_json::to_string(&cursor)?));
+        active.processed_items = Set(Some(processed as i32));
+        active.total_items = Set(Some(total as i32));
+        active.progress = Set(Some(if total == 0 { 100 } else { (processed * 100 / total) as i32 }));
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+    }
+    Ok(())
+}
@@ -1,14 +1,96 @@
 use anyhow::Result;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, Set,
+};
 use std::path::{Path, PathBuf};
-use tokio::fs;
+use std::sync::Arc;
+use tokio::{fs, sync::Semaphore, task::JoinSet};
 
 use crate::{
+    config::Config,
     db::entities::albums,
-    services::MusicBrainzService,
+    error::AppError,
+    services::{
+        album_grid_cache,
+        side_effects::{self, SideEffectKind},
+        MusicBrainzService,
+    },
     state::AppState,
 };
 
+/// Cover art fetcher shared by every task that downloads covers
+///
+/// All downloads go through one HTTP client, and at most
+/// `cover_art_concurrency` of them run at the same time, so a large library
+/// doesn't flood the Cover Art Archive or the local disk. Requests still start
+/// at least 100ms apart, as they did when downloads ran one by one.
+#[derive(Clone)]
+pub struct CoverArtDownloader {
+    mb_service: MusicBrainzService,
+    permits: Arc<Semaphore>,
+}
+
+impl CoverArtDownloader {
+    pub fn new(config: &Config) -> Self {
+        let mb_service = MusicBrainzService::new(format!(
+            "BeatCollector/0.1.0 ({})",
+            config.spotify_client_id
+        ));
+
+        Self {
+            mb_service,
+            permits: Arc::new(Semaphore::new(config.cover_art_concurrency.max(1))),
+        }
+    }
+
+    /// Downloads that can start right now without waiting
+    pub fn available_permits(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Download and store cover art for an album, waiting for a free slot first
+    pub async fn download(
+        &self,
+        album_id: i32,
+        mb_release_group_id: &str,
+        covers_dir: &Path,
+    ) -> Result<String> {
+        let _permit = self.permits.acquire().await?;
+
+        // Ensure covers directory exists
+        fs::create_dir_all(covers_dir).await?;
+
+        // Download cover art (500px size for good quality)
+        tracing::debug!(
+            "Downloading cover art for album {} from MusicBrainz {}",
+            album_id,
+            mb_release_group_id
+        );
+
+        // Parse MusicBrainz ID from string
+        let mb_uuid = uuid::Uuid::parse_str(mb_release_group_id)?;
+
+        let cover_data = self
+            .mb_service
+            .fetch_cover_art(
+                mb_uuid,
+                crate::services::musicbrainz::CoverArtSize::Medium,
+            )
+            .await?;
+
+        // Save to disk
+        let file_name = format!("{}.jpg", album_id);
+        let file_path = covers_dir.join(&file_name);
+
+        fs::write(&file_path, &cover_data).await?;
+
+        tracing::info!("Cover art saved to: {:?}", file_path);
+
+        // Return the URL path (relative to static serving)
+        Ok(format!("/static/covers/{}", file_name))
+    }
+}
+
 /// Download and store cover art for an album
 pub async fn download_cover_art(
     state: &AppState,
@@ -16,90 +98,146 @@ pub async fn download_cover_art(
     mb_release_group_id: &str,
     covers_dir: &Path,
 ) -> Result<String> {
-    // Ensure covers directory exists
-    fs::create_dir_all(covers_dir).await?;
+    state
+        .cover_art
+        .download(album_id, mb_release_group_id, covers_dir)
+        .await
+}
 
-    let mb_service = MusicBrainzService::new(format!(
-        "BeatCollector/0.1.0 ({})",
-        state.config.spotify_client_id
-    ));
+/// Download an album's cover and point the album at the local copy
+///
+/// Does nothing if the album was deleted while the download ran.
+pub async fn fetch_and_store_cover(
+    state: &AppState,
+    album_id: i32,
+    mb_release_group_id: &str,
+) -> Result<()> {
+    let covers_dir = PathBuf::from("static/covers");
+    let cover_url = download_cover_art(state, album_id, mb_release_group_id, &covers_dir).await?;
+
+    if let Some(album) = albums::Entity::find_by_id(album_id).one(&state.db).await? {
+        let mut active: albums::ActiveModel = album.into();
+        active.cover_art_url = Set(Some(cover_url));
+        active.updated_at = Set(chrono::Utc::now().into());
+        active.update(&state.db).await?;
+        tracing::debug!("Updated album {} with local cover art URL", album_id);
+    }
+    Ok(())
+}
+
+/// Download an album's cover, queueing a retry if the download fails
+pub async fn fetch_and_store_cover_or_queue(state: &AppState, album_id: i32, mb_release_group_id: &str) {
+    let Err(e) = fetch_and_store_cover(state, album_id, mb_release_group_id).await else {
+        return;
+    };
+    tracing::warn!("Failed to download cover art for album {}: {}", album_id, e);
+    if !is_transient(&e) {
+        return;
+    }
 
-    // Download cover art (500px size for good quality)
-    tracing::debug!(
-        "Downloading cover art for album {} from MusicBrainz {}",
+    let payload = side_effects::CoverArtDownload {
         album_id,
-        mb_release_group_id
-    );
+        release_group_id: mb_release_group_id.to_string(),
+    };
+    if let Err(queue_err) =
+        side_effects::enqueue(&state.db, SideEffectKind::CoverArtDownload, &payload, &e.to_string()).await
+    {
+        tracing::error!("Failed to queue cover art retry for album {}: {}", album_id, queue_err);
+    }
+}
 
-    // Parse MusicBrainz ID from string
-    let mb_uuid = uuid::Uuid::parse_str(mb_release_group_id)?;
+/// Whether a failed download is worth retrying
+///
+/// A release group without cover art or with a malformed ID fails the same
+/// way every time.
+fn is_transient(error: &anyhow::Error) -> bool {
+    !matches!(error.downcast_ref::<AppError>(), Some(AppError::NotFound(_)))
+        && error.downcast_ref::<uuid::Error>().is_none()
+}
 
-    let cover_data = mb_service
-        .fetch_cover_art(
-            mb_uuid,
-            crate::services::musicbrainz::CoverArtSize::Medium,
+/// Albums with a MusicBrainz ID but no local cover art
+fn missing_local_cover() -> Condition {
+    Condition::all()
+        .add(albums::Column::MusicbrainzReleaseGroupId.is_not_null())
+        .add(
+            albums::Column::CoverArtUrl
+                .not_like("/static/covers/%")
+                .or(albums::Column::CoverArtUrl.is_null()),
         )
-        .await?;
-
-    // Save to disk
-    let file_name = format!("{}.jpg", album_id);
-    let file_path = covers_dir.join(&file_name);
-
-    fs::write(&file_path, &cover_data).await?;
-
-    tracing::info!("Cover art saved to: {:?}", file_path);
+}
 
-    // Return the URL path (relative to static serving)
-    Ok(format!("/static/covers/{}", file_name))
+/// Number of albums a bulk cover art download would fetch
+pub async fn count_missing_covers(db: &DatabaseConnection) -> Result<u64> {
+    Ok(albums::Entity::find()
+        .filter(missing_local_cover())
+        .count(db)
+        .await?)
 }
 
 /// Download cover art for all matched albums that don't have local covers
 pub async fn download_all_missing_covers(state: AppState) -> Result<()> {
     tracing::info!("Starting bulk cover art download");
 
-    // Get static covers directory path
-    let covers_dir = PathBuf::from("static/covers");
-
-    // Find all albums with MusicBrainz IDs but no local cover art
     let albums = albums::Entity::find()
-        .filter(albums::Column::MusicbrainzReleaseGroupId.is_not_null())
-        .filter(
-            albums::Column::CoverArtUrl
-                .not_like("/static/covers/%")
-                .or(albums::Column::CoverArtUrl.is_null()),
-        )
+        .filter(missing_local_cover())
         .all(&state.db)
         .await?;
 
-    tracing::info!("Found {} albums needing cover art", albums.len());
+    tracing::info!(
+        "Found {} albums needing cover art (up to {} downloads at once)",
+        albums.len(),
+        state.cover_art.available_permits()
+    );
+
+    // Downloads run concurrently; the downloader's semaphore keeps them bounded
+    let mut downloads = JoinSet::new();
 
     for album_model in albums {
-        if let Some(ref mb_id) = album_model.musicbrainz_release_group_id {
-            match download_cover_art(&state, album_model.id, mb_id, &covers_dir).await {
-                Ok(cover_url) => {
-                    // Update database with local cover art URL
-                    let mut active: albums::ActiveModel = album_model.into();
-                    active.cover_art_url = Set(Some(cover_url));
-                    active.updated_at = Set(chrono::Utc::now().into());
-                    active.update(&state.db).await?;
-
-                    tracing::debug!("Updated album with local cover art URL");
-
-                    // Small delay to be respectful to Cover Art Archive
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                }
-                Err(e) => {
-                    // Log but continue - some albums may not have cover art
-                    tracing::warn!(
-                        "Failed to download cover art for album {}: {}",
-                        album_model.id,
-                        e
-                    );
-                }
-            }
-        }
+        let Some(mb_id) = album_model.musicbrainz_release_group_id.clone() else {
+            continue;
+        };
+        let state = state.clone();
+
+        // Failures are queued for a later retry; some albums may have no cover art at all
+        downloads.spawn(async move {
+            fetch_and_store_cover_or_queue(&state, album_model.id, &mb_id).await;
+        });
+    }
+
+    while let Some(result) = downloads.join_next().await {
+        result?;
     }
+    album_grid_cache::invalidate(&state.redis).await;
 
     tracing::info!("Bulk cover art download completed");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_config;
+
+    #[test]
+    fn test_downloader_uses_configured_concurrency() {
+        let config = Config {
+            cover_art_concurrency: 2,
+            ..test_config()
+        };
+        assert_eq!(CoverArtDownloader::new(&config).available_permits(), 2);
+
+        // Clones share the same limit
+        let downloader = CoverArtDownloader::new(&test_config());
+        let clone = downloader.clone();
+        let _permit = downloader.permits.try_acquire().unwrap();
+        assert_eq!(clone.available_permits(), test_config().cover_art_concurrency - 1);
+    }
+
+    #[test]
+    fn test_missing_cover_is_not_retried() {
+        assert!(!is_transient(&AppError::NotFound("Cover art not found".to_string()).into()));
+        assert!(!is_transient(&uuid::Uuid::parse_str("not-a-uuid").unwrap_err().into()));
+        assert!(is_transient(&AppError::ExternalApi("Failed to fetch cover art: 503".to_string()).into()));
+        assert!(is_transient(&anyhow::anyhow!("connection reset")));
+    }
+}
@@ -0,0 +1,143 @@
+//! Automatic retry of failed Lidarr downloads
+//!
+//! A periodic task looks for downloads that failed recently and asks Lidarr
+//! to search for the album again. Each download counts its retries, so an
+//! album that keeps failing is left alone once it reaches the configured
+//! maximum. Off unless `auto_retry_failed_downloads` is enabled in settings.
+
+use std::collections::HashSet;
+
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait};
+
+use crate::{
+    db::{
+        entities::{albums, lidarr_downloads},
+        enums::{DownloadStatus, OwnershipStatus},
+        settings as db_settings,
+    },
+    error::Result,
+    handlers::settings::{lidarr_connection, lidarr_service, DEFAULT_MAX_DOWNLOAD_RETRIES},
+    services::{album_grid_cache, ownership},
+    state::AppState,
+};
+
+/// Failures older than this are left for a manual search
+const RETRY_WINDOW_HOURS: i64 = 48;
+
+/// Outcome of one retry pass
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RetrySummary {
+    /// Searches Lidarr accepted
+    pub retried: usize,
+    /// Searches Lidarr refused; these are tried again next pass
+    pub failed: usize,
+}
+
+/// Ask Lidarr to search again for recently failed downloads
+///
+/// Only the latest download of each album is considered, and albums that
+/// have become owned or are downloading again are skipped.
+pub async fn retry_failed_downloads(state: &AppState) -> Result<RetrySummary> {
+    let Some(settings) = db_settings::shared(&state.db).await? else {
+        return Ok(RetrySummary::default());
+    };
+    if !settings.auto_retry_failed_downloads.unwrap_or(false) {
+        return Ok(RetrySummary::default());
+    }
+    let max_retries = settings.max_download_retries.unwrap_or(DEFAULT_MAX_DOWNLOAD_RETRIES);
+
+    let since = Utc::now() - Duration::hours(RETRY_WINDOW_HOURS);
+    let failed = lidarr_downloads::Entity::find()
+        .filter(lidarr_downloads::Column::Status.eq(DownloadStatus::Failed.as_str()))
+        .filter(lidarr_downloads::Column::UpdatedAt.gte(since))
+        .filter(lidarr_downloads::Column::RetryCount.lt(max_retries))
+        .filter(lidarr_downloads::Column::LidarrAlbumId.is_not_null())
+        .order_by_desc(lidarr_downloads::Column::Id)
+        .find_also_related(albums::Entity)
+        .all(&state.db)
+        .await?;
+    if failed.is_empty() {
+        return Ok(RetrySummary::default());
+    }
+
+    let (lidarr_url, lidarr_api_key) = lidarr_connection(state, None).await?;
+    let lidarr_service = lidarr_service(state, None).await?;
+    let mut summary = RetrySummary::default();
+    let mut seen_albums = HashSet::new();
+
+    for (download, album) in failed {
+        let Some(album) = album else { continue };
+        if !seen_albums.insert(album.id) || has_newer_download(state, &download).await? {
+            continue;
+        }
+
+        let from = ownership::current_status(&album);
+        if ownership::check_transition(from, OwnershipStatus::Downloading, ownership::REASON_LIDARR_RETRY, false)
+            .is_err()
+        {
+            continue;
+        }
+
+        let Some(lidarr_album_id) = download.lidarr_album_id else { continue };
+        if let Err(e) = lidarr_service
+            .search_album(&lidarr_url, &lidarr_api_key, lidarr_album_id)
+            .await
+        {
+            tracing::warn!("Retrying download of album {} failed: {}", album.id, e);
+            summary.failed += 1;
+            continue;
+        }
+
+        let retry_count = download.retry_count + 1;
+        let mut active_download: lidarr_downloads::ActiveModel = download.into();
+        active_download.status = Set(DownloadStatus::Searching.as_str().to_string());
+        active_download.retry_count = Set(retry_count);
+        active_download.updated_at = Set(Utc::now().into());
+        active_download.update(&state.db).await?;
+
+        let album_id = album.id;
+        let txn = state.db.begin().await?;
+        let mut active: albums::ActiveModel = album.into();
+        ownership::set_status(&mut active, from, OwnershipStatus::Downloading);
+        active.updated_at = Set(Utc::now().into());
+        active.update(&txn).await?;
+        ownership::record_transition(
+            &txn,
+            album_id,
+            from,
+            OwnershipStatus::Downloading,
+            ownership::REASON_LIDARR_RETRY,
+            false,
+        )
+        .await?;
+        txn.commit().await?;
+        ownership::publish_transition(
+            &state.events,
+            album_id,
+            from,
+            OwnershipStatus::Downloading,
+            ownership::REASON_LIDARR_RETRY,
+            false,
+        );
+
+        tracing::info!("Retrying download of album {} (attempt {} of {})", album_id, retry_count, max_retries);
+        summary.retried += 1;
+    }
+
+    if summary.retried > 0 {
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+    Ok(summary)
+}
+
+/// Whether the album has a download newer than `download`, e.g. a manual
+/// search started after the failure
+async fn has_newer_download(state: &AppState, download: &lidarr_downloads::Model) -> Result<bool> {
+    Ok(lidarr_downloads::Entity::find()
+        .filter(lidarr_downloads::Column::AlbumId.eq(download.album_id))
+        .filter(lidarr_downloads::Column::Id.gt(download.id))
+        .one(&state.db)
+        .await?
+        .is_some())
+}
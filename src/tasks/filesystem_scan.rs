@@ -1,25 +1,229 @@
 use anyhow::Result;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
-use std::collections::HashMap;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 use std::fs;
 
 use crate::{
     db::{
-        entities::{albums, artists},
+        entities::{albums, artists, jobs, tracks},
         enums::{AcquisitionSource, OwnershipStatus},
+        settings as db_settings,
+    },
+    error::AppError,
+    jobs::record_result,
+    services::{
+        audio_files::{self, DEFAULT_DURATION_TOLERANCE_SECS},
+        music_paths, ownership,
     },
     state::AppState,
 };
 
-pub async fn run_filesystem_scan(state: AppState, music_path: &Path) -> Result<()> {
+/// Names must be at least this similar for a folder to match an artist or album
+const MATCH_SIMILARITY: f64 = 0.8;
+
+/// Preview matches below this confidence start out unchecked in the review
+pub const PREVIEW_CONFIDENCE_THRESHOLD: f64 = 0.9;
+
+/// A folder the scan would mark an album owned from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanProposal {
+    pub directory: String,
+    pub album_id: i32,
+    pub album_title: String,
+    pub artist_name: String,
+    /// Similarity of the weaker of the artist and album names, 0.0-1.0
+    pub confidence: f64,
+}
+
+/// Result of a dry-run scan, stored on its job for review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanPreview {
+    pub proposals: Vec<ScanProposal>,
+    /// Album folders found that matched nothing in the library
+    pub unmatched: usize,
+}
+
+/// Outcome of applying accepted preview matches
+#[derive(Debug, Default, Serialize)]
+pub struct ScanApplyReport {
+    pub applied: usize,
+    /// Accepted albums whose ownership can no longer move to owned
+    pub skipped: usize,
+    /// Album IDs that weren't among the job's proposals
+    pub unknown: usize,
+    /// Tracks of the applied albums whose files don't match Spotify's length
+    pub mismatches: Vec<DurationMismatch>,
+}
+
+/// A track whose local file is longer or shorter than Spotify says
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationMismatch {
+    pub album_id: i32,
+    pub album_title: String,
+    pub track_id: i32,
+    pub track_title: String,
+    pub disc_number: i32,
+    pub track_number: i32,
+    pub spotify_duration_ms: i32,
+    pub local_duration_ms: i32,
+    pub path: String,
+}
+
+/// Result of a filesystem scan, stored on its job
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanReport {
+    /// Albums matched to a folder and marked owned
+    pub albums_owned: usize,
+    pub mismatches: Vec<DurationMismatch>,
+}
+
+/// Scan the music folder as a job, recording the mismatch report on it
+pub async fn run_filesystem_scan_job(
+    state: AppState,
+    job_id: i32,
+    profile_id: Option<i32>,
+    music_path: &Path,
+) -> Result<()> {
+    let report = run_filesystem_scan(state.clone(), profile_id, music_path).await?;
+    record_result(&state.db, job_id, Some(report.albums_owned), &report).await?;
+    Ok(())
+}
+
+/// Scan the music folder, with the settings of `profile_id` (the default
+/// profile if `None`)
+pub async fn run_filesystem_scan(state: AppState, profile_id: Option<i32>, music_path: &Path) -> Result<ScanReport> {
     tracing::info!("Starting filesystem scan: {:?}", music_path);
 
+    let found_albums = find_album_folders(music_path)?;
+    tracing::info!("Found {} potential albums in filesystem", found_albums.len());
+
+    let tolerance_secs = duration_tolerance_secs(&state.db, profile_id).await?;
+    let mut report = ScanReport::default();
+
+    // Match found albums to database and update ownership
+    for ((artist_name, album_title), local_path) in found_albums {
+        match propose_match(&state.db, &artist_name, &album_title, &local_path).await? {
+            Some(proposal) => {
+                let album = albums::Entity::find_by_id(proposal.album_id)
+                    .one(&state.db)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Album {} disappeared", proposal.album_id))?;
+                mark_owned_from_scan(&state, &album, &local_path).await?;
+                tracing::info!(
+                    "Updated album '{}' by '{}' to owned status",
+                    album_title,
+                    artist_name
+                );
+                report.albums_owned += 1;
+                match check_track_durations(&state.db, &album, &local_path, tolerance_secs).await {
+                    Ok(mismatches) => report.mismatches.extend(mismatches),
+                    Err(e) => tracing::warn!("Failed to check track lengths of '{}': {}", album.title, e),
+                }
+            }
+            None => tracing::debug!(
+                "No matching album found in database for: {} by {}",
+                album_title,
+                artist_name
+            ),
+        }
+    }
+
+    if let Err(e) = check_lidarr_paths(&state, profile_id).await {
+        tracing::warn!("Failed to check Lidarr paths against the music folder: {}", e);
+    }
+
+    tracing::info!(
+        "Filesystem scan completed: {} albums owned, {} tracks with mismatched lengths",
+        report.albums_owned,
+        report.mismatches.len()
+    );
+    Ok(report)
+}
+
+/// Match the music folder like a scan, but only record the proposed matches on the job
+pub async fn run_filesystem_scan_preview(state: AppState, job_id: i32, music_path: &Path) -> Result<()> {
+    tracing::info!("Starting filesystem scan preview: {:?}", music_path);
+
+    let found_albums = find_album_folders(music_path)?;
+    let mut proposals = Vec::new();
+    let mut unmatched = 0;
+    for ((artist_name, album_title), local_path) in found_albums {
+        match propose_match(&state.db, &artist_name, &album_title, &local_path).await? {
+            Some(proposal) => proposals.push(proposal),
+            None => unmatched += 1,
+        }
+    }
+
+    tracing::info!(
+        "Filesystem scan preview proposed {} matches ({} folders unmatched)",
+        proposals.len(),
+        unmatched
+    );
+    let processed = proposals.len() + unmatched;
+    record_result(&state.db, job_id, Some(processed), &ScanPreview { proposals, unmatched }).await?;
+    Ok(())
+}
+
+/// Mark the accepted albums of a finished scan preview owned, as the scan itself would
+///
+/// Only albums the preview proposed are applied, with the folder it found for them.
+pub async fn apply_scan_preview(
+    state: &AppState,
+    job: &jobs::Model,
+    album_ids: &[i32],
+) -> crate::error::Result<ScanApplyReport> {
+    let db = &state.db;
+    let preview: ScanPreview = job
+        .result
+        .as_deref()
+        .filter(|_| job.job_type == crate::db::enums::JobType::FilesystemScanPreview.as_str())
+        .and_then(|result| serde_json::from_str(result).ok())
+        .ok_or_else(|| {
+            AppError::Validation(format!("Job {} is not a finished scan preview", job.id))
+        })?;
+
+    let tolerance_secs = duration_tolerance_secs(db, job.entity_id).await?;
+    let mut report = ScanApplyReport::default();
+    let mut seen = HashSet::new();
+    for album_id in album_ids.iter().filter(|id| seen.insert(**id)) {
+        let Some(proposal) = preview.proposals.iter().find(|p| p.album_id == *album_id) else {
+            report.unknown += 1;
+            continue;
+        };
+        let Some(album) = albums::Entity::find_by_id(*album_id).one(db).await? else {
+            report.unknown += 1;
+            continue;
+        };
+
+        match mark_owned_from_scan(state, &album, &proposal.directory).await {
+            Ok(()) => {
+                report.applied += 1;
+                match check_track_durations(db, &album, &proposal.directory, tolerance_secs).await {
+                    Ok(mismatches) => report.mismatches.extend(mismatches),
+                    Err(e) => tracing::warn!("Failed to check track lengths of '{}': {}", album.title, e),
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Not applying scan match for album {}: {}", album_id, e);
+                report.skipped += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Album folders (`<Artist>/<Album>` with at least 3 audio files) under the music folder
+fn find_album_folders(music_path: &Path) -> Result<BTreeMap<(String, String), String>> {
     if !music_path.exists() {
         return Err(anyhow::anyhow!("Music path does not exist: {:?}", music_path));
     }
 
-    let mut found_albums = HashMap::new();
+    let mut found_albums = BTreeMap::new();
 
     // Walk the directory looking for <Artist>/<Album> structure
     for artist_entry in fs::read_dir(music_path)? {
@@ -71,108 +275,195 @@ pub async fn run_filesystem_scan(state: AppState, music_path: &Path) -> Result<(
         }
     }
 
-    tracing::info!("Found {} potential albums in filesystem", found_albums.len());
+    Ok(found_albums)
+}
 
-    // Match found albums to database and update ownership
-    for ((artist_name, album_title), local_path) in found_albums {
-        match_and_update_album(&state, &artist_name, &album_title, &local_path).await?;
-    }
+/// Re-check the folders of albums Lidarr imported against the music folder
+///
+/// Current path mappings are applied first, so adding a mapping clears the
+/// warning at the next scan.
+async fn check_lidarr_paths(state: &AppState, profile_id: Option<i32>) -> Result<()> {
+    let mappings = music_paths::parse_mappings(
+        db_settings::for_profile_id(&state.db, profile_id)
+            .await?
+            .and_then(|s| s.path_mappings)
+            .as_deref(),
+    );
+    let paths: Vec<String> = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::LocalPath)
+        .filter(albums::Column::AcquisitionSource.eq(AcquisitionSource::Lidarr.as_str()))
+        .filter(albums::Column::LocalPath.is_not_null())
+        .into_tuple::<Option<String>>()
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|path| music_paths::apply_mappings(&path, &mappings))
+        .collect();
 
-    tracing::info!("Filesystem scan completed");
+    music_paths::record_path_check(&state.db, profile_id, &paths).await?;
     Ok(())
 }
 
 /// Count audio files in a directory
 fn count_audio_files(path: &Path) -> Result<usize> {
     let mut count = 0;
-    let audio_extensions = ["mp3", "flac", "m4a", "ogg", "opus", "wav", "aac"];
-
     for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if let Some(ext_str) = ext.to_str() {
-                    if audio_extensions.contains(&ext_str.to_lowercase().as_str()) {
-                        count += 1;
-                    }
-                }
-            }
+        if audio_files::is_audio_file(&entry?.path()) {
+            count += 1;
         }
     }
-
     Ok(count)
 }
 
-/// Match found album to database and update ownership status
-async fn match_and_update_album(
-    state: &AppState,
+/// Find the library album a folder most likely holds, by fuzzy matching artist and title
+async fn propose_match(
+    db: &DatabaseConnection,
     artist_name: &str,
     album_title: &str,
     local_path: &str,
-) -> Result<()> {
-    // Try to find matching album in database by fuzzy matching artist and title
+) -> Result<Option<ScanProposal>> {
     // First, try to find artist
-    let artist_matches = artists::Entity::find()
-        .all(&state.db)
-        .await?;
+    let artist_matches = artists::Entity::find().all(db).await?;
 
-    let matching_artist = artist_matches.iter().find(|a| {
-        similarity::normalized_levenshtein(&a.name.to_lowercase(), &artist_name.to_lowercase())
-            > 0.8
+    let matching_artist = artist_matches.iter().find_map(|a| {
+        let score = similarity::normalized_levenshtein(&a.name.to_lowercase(), &artist_name.to_lowercase());
+        (score > MATCH_SIMILARITY).then_some((a, score))
     });
 
-    if let Some(artist) = matching_artist {
-        // Find albums by this artist
-        let albums = albums::Entity::find()
-            .filter(albums::Column::ArtistId.eq(artist.id))
-            .all(&state.db)
-            .await?;
-
-        let matching_album = albums.iter().find(|alb| {
-            similarity::normalized_levenshtein(
-                &alb.title.to_lowercase(),
-                &album_title.to_lowercase(),
-            ) > 0.8
-        });
-
-        if let Some(album_model) = matching_album {
-            // Update album ownership
-            let mut active: albums::ActiveModel = album_model.clone().into();
-            active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
-            active.local_path = Set(Some(local_path.to_string()));
-
-            // If acquisition source is not set, default to Unknown
-            if album_model.acquisition_source.is_none() {
-                active.acquisition_source = Set(Some(AcquisitionSource::Unknown.as_str().to_string()));
-            }
+    let Some((artist, artist_score)) = matching_artist else {
+        tracing::debug!("No matching artist found in database for: {}", artist_name);
+        return Ok(None);
+    };
 
-            active.updated_at = Set(chrono::Utc::now().into());
-            active.update(&state.db).await?;
+    // Find albums by this artist
+    let albums = albums::Entity::find()
+        .filter(albums::Column::ArtistId.eq(artist.id))
+        .all(db)
+        .await?;
 
-            tracing::info!(
-                "Updated album '{}' by '{}' to owned status",
-                album_title,
-                artist_name
-            );
-        } else {
-            tracing::debug!(
-                "No matching album found in database for: {} by {}",
-                album_title,
-                artist_name
-            );
-        }
-    } else {
-        tracing::debug!(
-            "No matching artist found in database for: {}",
-            artist_name
-        );
+    Ok(albums.into_iter().find_map(|album| {
+        let score = similarity::normalized_levenshtein(&album.title.to_lowercase(), &album_title.to_lowercase());
+        (score > MATCH_SIMILARITY).then(|| ScanProposal {
+            directory: local_path.to_string(),
+            album_id: album.id,
+            album_title: album.title,
+            artist_name: artist.name.clone(),
+            confidence: artist_score.min(score),
+        })
+    }))
+}
+
+/// Mark an album owned from the folder a scan found it in
+///
+/// The scan and the apply step of a scan preview both go through here.
+async fn mark_owned_from_scan(state: &AppState, album: &albums::Model, local_path: &str) -> Result<()> {
+    let from = ownership::current_status(album);
+    ownership::check_transition(from, OwnershipStatus::Owned, ownership::REASON_FILESYSTEM_SCAN, false)?;
+
+    let mut active: albums::ActiveModel = album.clone().into();
+    ownership::set_status(&mut active, from, OwnershipStatus::Owned);
+    active.local_path = Set(Some(local_path.to_string()));
+
+    // If acquisition source is not set, default to Unknown
+    if album.acquisition_source.is_none() {
+        active.acquisition_source = Set(Some(AcquisitionSource::Unknown.as_str().to_string()));
     }
 
+    active.updated_at = Set(chrono::Utc::now().into());
+    let txn = state.db.begin().await?;
+    active.update(&txn).await?;
+    ownership::record_transition(
+        &txn,
+        album.id,
+        from,
+        OwnershipStatus::Owned,
+        ownership::REASON_FILESYSTEM_SCAN,
+        false,
+    )
+    .await?;
+    txn.commit().await?;
+    ownership::publish_transition(
+        &state.events,
+        album.id,
+        from,
+        OwnershipStatus::Owned,
+        ownership::REASON_FILESYSTEM_SCAN,
+        false,
+    );
     Ok(())
 }
 
+/// Seconds a local file may differ from Spotify's track length
+async fn duration_tolerance_secs(db: &DatabaseConnection, profile_id: Option<i32>) -> crate::error::Result<i32> {
+    Ok(db_settings::for_profile_id(db, profile_id)
+        .await?
+        .and_then(|s| s.duration_tolerance_secs)
+        .unwrap_or(DEFAULT_DURATION_TOLERANCE_SECS))
+}
+
+/// Compare the files in an album's folder with its tracks, by disc and track
+/// number, and record each file's length on its track
+///
+/// Tracks without a file, or without a length on either side, are never
+/// flagged. Returns the tracks that are off by more than the tolerance.
+async fn check_track_durations(
+    db: &DatabaseConnection,
+    album: &albums::Model,
+    local_path: &str,
+    tolerance_secs: i32,
+) -> Result<Vec<DurationMismatch>> {
+    let local_tracks = audio_files::read_album_folder(Path::new(local_path))?;
+    let album_tracks = tracks::Entity::find()
+        .filter(tracks::Column::AlbumId.eq(album.id))
+        .all(db)
+        .await?;
+
+    let mut mismatches = Vec::new();
+    for track in album_tracks {
+        let Some(track_number) = track.track_number else {
+            continue;
+        };
+        let disc_number = track.disc_number.unwrap_or(1);
+        let local = local_tracks
+            .iter()
+            .find(|l| l.disc_number == disc_number && l.track_number == Some(track_number));
+        let local_duration_ms = local.and_then(|l| l.duration_ms);
+        let mismatch = match (track.duration_ms, local_duration_ms) {
+            (Some(spotify_ms), Some(local_ms)) => {
+                audio_files::exceeds_tolerance(spotify_ms, local_ms, tolerance_secs)
+            }
+            _ => false,
+        };
+
+        if let (true, Some(local), Some(spotify_ms), Some(local_ms)) =
+            (mismatch, local, track.duration_ms, local_duration_ms)
+        {
+            mismatches.push(DurationMismatch {
+                album_id: album.id,
+                album_title: album.title.clone(),
+                track_id: track.id,
+                track_title: track.title.clone(),
+                disc_number,
+                track_number,
+                spotify_duration_ms: spotify_ms,
+                local_duration_ms: local_ms,
+                path: local.path.clone(),
+            });
+        }
+
+        if track.local_duration_ms != local_duration_ms || track.duration_mismatch != mismatch {
+            let mut active: tracks::ActiveModel = track.into();
+            active.local_duration_ms = Set(local_duration_ms);
+            active.duration_mismatch = Set(mismatch);
+            active.updated_at = Set(chrono::Utc::now().into());
+            active.update(db).await?;
+        }
+    }
+    Ok(mismatches)
+}
+
 // Simple string similarity for fuzzy matching
 mod similarity {
     pub fn normalized_levenshtein(s1: &str, s2: &str) -> f64 {
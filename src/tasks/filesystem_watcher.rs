@@ -1,11 +1,10 @@
 use anyhow::Result;
 use notify_debouncer_full::{new_debouncer, notify::*, DebounceEventResult};
-use sea_orm::EntityTrait;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::sync::mpsc;
 
-use crate::state::AppState;
+use crate::{services::album_grid_cache, state::AppState};
 use super::filesystem_scan::run_filesystem_scan;
 
 /// Start the filesystem watcher for monitoring music directory changes
@@ -58,9 +57,10 @@ pub async fn start_watcher(state: AppState, music_path: PathBuf) -> Result<()> {
 
                 tokio::spawn(async move {
                     tracing::info!("Filesystem changes detected, triggering rescan");
-                    if let Err(e) = run_filesystem_scan(state_clone, &music_path_clone).await {
+                    if let Err(e) = run_filesystem_scan(state_clone.clone(), None, &music_path_clone).await {
                         tracing::error!("Filesystem scan failed: {}", e);
                     }
+                    album_grid_cache::invalidate(&state_clone.redis).await;
                 });
             }
         }
@@ -72,9 +72,7 @@ pub async fn start_watcher(state: AppState, music_path: PathBuf) -> Result<()> {
 /// Initialize the filesystem watcher if music folder is configured
 pub async fn init_watcher_if_configured(state: AppState) -> Result<()> {
     // Check if music folder is configured
-    if let Some(settings) = crate::db::entities::user_settings::Entity::find()
-        .one(&state.db)
-        .await?
+    if let Some(settings) = crate::db::settings::shared(&state.db).await?
     {
         if let Some(music_path) = settings.music_folder_path {
             let path = PathBuf::from(music_path);
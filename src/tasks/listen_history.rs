@@ -0,0 +1,243 @@
+//! Album listening history from ListenBrainz ("first/last listened")
+//!
+//! A daily task pulls the listens scrobbled since the last run and matches
+//! each one to an album by normalized artist name and album title. Albums
+//! keep when they were first and last played and how often, which helps pick
+//! what to drop from the want list. Listens that don't match any album are
+//! logged at debug level and counted.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+
+use crate::{
+    db::{
+        artist_names::{self, normalize_name, without_article},
+        entities::{albums, user_settings},
+        enums::OwnershipStatus,
+        settings as db_settings,
+    },
+    error::Result,
+    services::{album_grid_cache, ListenBrainzService, Listen},
+    state::AppState,
+};
+
+/// Outcome of one listening history sync
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListenSyncSummary {
+    /// Listens fetched from ListenBrainz
+    pub fetched: usize,
+    /// Listens credited to an album
+    pub matched: usize,
+    /// Listens without an album in the library
+    pub unmatched: usize,
+}
+
+/// Pull new listens for the configured ListenBrainz user and credit albums
+///
+/// Does nothing until a ListenBrainz username is saved in settings.
+pub async fn sync_listen_history(state: &AppState) -> Result<ListenSyncSummary> {
+    let Some(settings) = db_settings::shared(&state.db).await? else {
+        return Ok(ListenSyncSummary::default());
+    };
+    let Some(username) = settings
+        .listenbrainz_username
+        .clone()
+        .filter(|u| !u.trim().is_empty())
+    else {
+        return Ok(ListenSyncSummary::default());
+    };
+
+    let service = ListenBrainzService::from_config(&state.config);
+    let since = settings.listens_synced_until.map(|t| t.to_utc());
+    let listens = service.fetch_listens_since(username.trim(), since).await?;
+
+    let summary = apply_listens(&state.db, &listens).await?;
+    if summary.matched > 0 {
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+
+    if let Some(newest) = listens.last() {
+        let mut active: user_settings::ActiveModel = settings.into();
+        active.listens_synced_until = Set(Some(newest.listened_at.into()));
+        active.updated_at = Set(Utc::now().into());
+        active.update(&state.db).await?;
+    }
+
+    tracing::info!(
+        "Listening history sync: {} listens, {} matched, {} without an album",
+        summary.fetched,
+        summary.matched,
+        summary.unmatched
+    );
+    Ok(summary)
+}
+
+/// Credit listens to the albums they were played from
+pub async fn apply_listens(db: &DatabaseConnection, listens: &[Listen]) -> Result<ListenSyncSummary> {
+    let mut summary = ListenSyncSummary {
+        fetched: listens.len(),
+        ..Default::default()
+    };
+
+    // Group by album first, so a heavily played album costs one lookup
+    let mut groups: HashMap<(String, String), Vec<&Listen>> = HashMap::new();
+    for listen in listens {
+        let Some(release) = &listen.release_name else {
+            tracing::debug!("Listen of {} has no album; skipping", listen.artist_name);
+            summary.unmatched += 1;
+            continue;
+        };
+        groups
+            .entry((normalize_name(&listen.artist_name), normalize_name(release)))
+            .or_default()
+            .push(listen);
+    }
+
+    for ((artist, title), group) in groups {
+        let Some(album) = find_listened_album(db, &artist, &title).await? else {
+            tracing::debug!(
+                "No album for {} listens of {} - {}",
+                group.len(),
+                group[0].artist_name,
+                group[0].release_name.as_deref().unwrap_or_default()
+            );
+            summary.unmatched += group.len();
+            continue;
+        };
+
+        summary.matched += group.len();
+        let times: Vec<DateTime<Utc>> = group.iter().map(|l| l.listened_at).collect();
+        credit_listens(db, album, &times).await?;
+    }
+
+    Ok(summary)
+}
+
+/// Album matching a normalized artist name and title
+///
+/// The artist's name may differ by a leading article ("The Beatles").
+///
+/// When several albums match (the same album under two Spotify IDs, say),
+/// one already in the library wins, then the oldest.
+async fn find_listened_album(
+    db: &DatabaseConnection,
+    artist: &str,
+    title: &str,
+) -> Result<Option<albums::Model>> {
+    if artist.is_empty() || title.is_empty() {
+        return Ok(None);
+    }
+
+    let artist_ids: Vec<i32> = artist_names::candidates(db, artist)
+        .await?
+        .into_iter()
+        .filter(|a| a.normalized_name.as_deref().map(without_article) == Some(without_article(artist)))
+        .map(|a| a.id)
+        .collect();
+    if artist_ids.is_empty() {
+        return Ok(None);
+    }
+
+    let owned = OwnershipStatus::Owned.as_str();
+    Ok(albums::Entity::find()
+        .filter(albums::Column::ArtistId.is_in(artist_ids))
+        .order_by_asc(albums::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|album| normalize_name(&album.title) == title)
+        .min_by_key(|album| album.ownership_status != owned))
+}
+
+async fn credit_listens(db: &DatabaseConnection, album: albums::Model, times: &[DateTime<Utc>]) -> Result<()> {
+    let (Some(&earliest), Some(&latest)) = (times.iter().min(), times.iter().max()) else {
+        return Ok(());
+    };
+
+    let first = album
+        .first_listened_at
+        .map(|t| t.to_utc().min(earliest))
+        .unwrap_or(earliest);
+    let last = album
+        .last_listened_at
+        .map(|t| t.to_utc().max(latest))
+        .unwrap_or(latest);
+    let count = album.listen_count + times.len() as i32;
+
+    let mut active: albums::ActiveModel = album.into();
+    active.first_listened_at = Set(Some(first.into()));
+    active.last_listened_at = Set(Some(last.into()));
+    active.listen_count = Set(count);
+    active.update(db).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::test_utils::*;
+
+    fn listen(ts: i64, artist: &str, release: Option<&str>) -> Listen {
+        Listen {
+            listened_at: Utc.timestamp_opt(ts, 0).unwrap(),
+            artist_name: artist.to_string(),
+            release_name: release.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_listens_credits_albums() {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Sigur Rós", None).await;
+        let album = create_test_album(&db, artist.id, "Ágætis byrjun", None).await;
+
+        let listens = [
+            listen(1_000, "Sigur Ros", Some("Agaetis Byrjun")),
+            listen(3_000, "sigur rós", Some("Ágætis byrjun")),
+            listen(2_000, "Sigur Rós", Some("Ágætis byrjun")),
+            listen(4_000, "Sigur Rós", None),
+            listen(5_000, "Unknown Artist", Some("Unknown Album")),
+        ];
+        let summary = apply_listens(&db, &listens).await.unwrap();
+        assert_eq!(summary, ListenSyncSummary { fetched: 5, matched: 3, unmatched: 2 });
+
+        let album = albums::Entity::find_by_id(album.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(album.listen_count, 3);
+        assert_eq!(album.first_listened_at.unwrap().timestamp(), 1_000);
+        assert_eq!(album.last_listened_at.unwrap().timestamp(), 3_000);
+
+        // A later sync extends the range and the count
+        apply_listens(&db, &[listen(9_000, "Sigur Rós", Some("Ágætis byrjun"))])
+            .await
+            .unwrap();
+        let album = albums::Entity::find_by_id(album.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(album.listen_count, 4);
+        assert_eq!(album.first_listened_at.unwrap().timestamp(), 1_000);
+        assert_eq!(album.last_listened_at.unwrap().timestamp(), 9_000);
+    }
+
+    #[tokio::test]
+    async fn test_ambiguous_listens_prefer_owned_album() {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Radiohead", None).await;
+        let wanted = create_test_album(&db, artist.id, "OK Computer", None).await;
+        let owned = create_test_album(&db, artist.id, "OK Computer", None).await;
+
+        let mut active: albums::ActiveModel = owned.clone().into();
+        active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+        active.update(&db).await.unwrap();
+
+        apply_listens(&db, &[listen(1_000, "Radiohead", Some("OK Computer"))])
+            .await
+            .unwrap();
+
+        let wanted = albums::Entity::find_by_id(wanted.id).one(&db).await.unwrap().unwrap();
+        let owned = albums::Entity::find_by_id(owned.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(wanted.listen_count, 0);
+        assert_eq!(owned.listen_count, 1);
+    }
+}
@@ -0,0 +1,190 @@
+//! Re-bucket scored album matches against the current thresholds
+//!
+//! Works purely from stored `match_score`s, so it's quick and makes no
+//! MusicBrainz calls. Manually applied matches are left alone.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        entities::{albums, jobs},
+        enums::{MatchSource, MatchStatus},
+    },
+    jobs::record_result,
+    tasks::musicbrainz_match::classify_score,
+};
+
+/// Albums re-classified between progress updates
+const BATCH_SIZE: usize = 100;
+
+/// Result payload stored on the job
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReevaluationSummary {
+    /// Scored, automatically matched albums that were considered
+    pub examined: usize,
+    pub moved: usize,
+    /// Albums moved, keyed by `"<from>-><to>"`
+    pub transitions: BTreeMap<String, usize>,
+}
+
+pub async fn run_match_reevaluation(db: &DatabaseConnection, job_id: i32) -> Result<ReevaluationSummary> {
+    tracing::info!("Starting match re-evaluation");
+
+    let buckets = [MatchStatus::Matched, MatchStatus::ManualReview, MatchStatus::NoMatch];
+    let candidates: Vec<(i32, Option<String>, Option<i32>)> = albums::Entity::find()
+        .select_only()
+        .column(albums::Column::Id)
+        .column(albums::Column::MatchStatus)
+        .column(albums::Column::MatchScore)
+        .filter(albums::Column::MatchScore.is_not_null())
+        .filter(albums::Column::MatchStatus.is_in(buckets.iter().map(|s| s.as_str())))
+        .filter(
+            Condition::any()
+                .add(albums::Column::MatchSource.is_null())
+                .add(albums::Column::MatchSource.ne(MatchSource::Manual.as_str())),
+        )
+        .order_by_asc(albums::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let total = candidates.len();
+    record_progress(db, job_id, 0, total).await?;
+
+    let mut summary = ReevaluationSummary::default();
+    for (batch_index, batch) in candidates.chunks(BATCH_SIZE).enumerate() {
+        let mut moves: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+
+        for (id, status, score) in batch {
+            let (Some(status), Some(score)) = (status, score) else { continue };
+            let target = classify_score(*score).as_str().to_string();
+            if *status != target {
+                *summary.transitions.entry(format!("{}->{}", status, target)).or_default() += 1;
+                moves.entry(target).or_default().push(*id);
+                summary.moved += 1;
+            }
+        }
+
+        for (target, ids) in moves {
+            albums::Entity::update_many()
+                .col_expr(albums::Column::MatchStatus, Expr::value(target))
+                .col_expr(albums::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+                .filter(albums::Column::Id.is_in(ids))
+                .exec(db)
+                .await?;
+        }
+
+        let processed = (batch_index * BATCH_SIZE + batch.len()).min(total);
+        record_progress(db, job_id, processed, total).await?;
+    }
+    summary.examined = total;
+
+    record_result(db, job_id, None, &summary).await?;
+
+    tracing::info!(
+        "Match re-evaluation completed: {} of {} albums moved",
+        summary.moved,
+        summary.examined
+    );
+    Ok(summary)
+}
+
+async fn record_progress(db: &DatabaseConnection, job_id: i32, processed: usize, total: usize) -> Result<()> {
+    if let Some(job) = jobs::Entity::find_by_id(job_id).one(db).await? {
+        let mut active: jobs::ActiveModel = job.into();
+        active.total_items = Set(Some(total as i32));
+        active.processed_items = Set(Some(processed as i32));
+        active.progress = Set(Some(if total == 0 { 100 } else { (processed * 100 / total) as i32 }));
+        active.updated_at = Set(chrono::Utc::now().into());
+        active.update(db).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::enums::{JobStatus, JobType};
+    use crate::test_utils::*;
+
+    async fn scored_album(
+        db: &DatabaseConnection,
+        title: &str,
+        status: MatchStatus,
+        score: Option<i32>,
+        source: Option<MatchSource>,
+    ) -> albums::Model {
+        let artist = create_test_artist(db, &format!("{} Artist", title), None).await;
+        let album = create_test_album(db, artist.id, title, None).await;
+
+        let mut active: albums::ActiveModel = album.into();
+        active.match_status = Set(Some(status.as_str().to_string()));
+        active.match_score = Set(score);
+        active.match_source = Set(source.map(|s| s.as_str().to_string()));
+        active.update(db).await.unwrap()
+    }
+
+    async fn status_of(db: &DatabaseConnection, album: &albums::Model) -> String {
+        albums::Entity::find_by_id(album.id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
+            .match_status
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reevaluation_moves_albums_between_buckets() {
+        let db = setup_test_db().await;
+        let job = create_test_job(&db, JobType::MatchReevaluation, JobStatus::Running).await;
+
+        // Stored under older thresholds
+        let promoted = scored_album(&db, "Promoted", MatchStatus::ManualReview, Some(92), Some(MatchSource::Auto)).await;
+        let demoted = scored_album(&db, "Demoted", MatchStatus::Matched, Some(70), None).await;
+        let steady = scored_album(&db, "Steady", MatchStatus::Matched, Some(95), Some(MatchSource::Auto)).await;
+        let manual = scored_album(&db, "Manual", MatchStatus::Matched, Some(40), Some(MatchSource::Manual)).await;
+        let unscored = scored_album(&db, "Unscored", MatchStatus::NoMatch, None, Some(MatchSource::Auto)).await;
+
+        let summary = run_match_reevaluation(&db, job.id).await.unwrap();
+
+        assert_eq!(summary.examined, 3);
+        assert_eq!(summary.moved, 2);
+        assert_eq!(summary.transitions.get("manual_review->matched"), Some(&1));
+        assert_eq!(summary.transitions.get("matched->no_match"), Some(&1));
+
+        assert_eq!(status_of(&db, &promoted).await, "matched");
+        assert_eq!(status_of(&db, &demoted).await, "no_match");
+        assert_eq!(status_of(&db, &steady).await, "matched");
+        assert_eq!(status_of(&db, &manual).await, "matched");
+        assert_eq!(status_of(&db, &unscored).await, "no_match");
+
+        let job = jobs::Entity::find_by_id(job.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(job.progress, Some(100));
+        assert_eq!(job.processed_items, Some(3));
+        assert_eq!(job.total_items, Some(3));
+        let stored: ReevaluationSummary = serde_json::from_str(job.result.as_deref().unwrap()).unwrap();
+        assert_eq!(stored.moved, 2);
+    }
+
+    #[tokio::test]
+    async fn test_reevaluation_skips_pending_albums() {
+        let db = setup_test_db().await;
+        let job = create_test_job(&db, JobType::MatchReevaluation, JobStatus::Running).await;
+        let pending = scored_album(&db, "Pending", MatchStatus::Pending, Some(95), None).await;
+
+        let summary = run_match_reevaluation(&db, job.id).await.unwrap();
+
+        assert_eq!(summary.examined, 0);
+        assert_eq!(status_of(&db, &pending).await, "pending");
+        let job = jobs::Entity::find_by_id(job.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(job.progress, Some(100));
+    }
+}
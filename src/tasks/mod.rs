@@ -1,13 +1,23 @@
 use anyhow::Result;
+use chrono::{Local, Timelike};
 use tokio_cron_scheduler::{Job, JobScheduler};
 
 use crate::state::AppState;
 
 pub mod spotify_sync;
 pub mod musicbrainz_match;
+pub mod match_reevaluation;
 pub mod filesystem_scan;
 pub mod filesystem_watcher;
 pub mod cover_art;
+pub mod top_items;
+pub mod artist_images;
+pub mod listen_history;
+pub mod download_retry;
+pub mod side_effects;
+pub mod wanted_demotion;
+pub mod archive_purge;
+pub mod webhook_events;
 
 pub async fn start_scheduler(state: AppState) -> Result<JobScheduler> {
     let scheduler = JobScheduler::new().await?;
@@ -17,11 +27,119 @@ pub async fn start_scheduler(state: AppState) -> Result<JobScheduler> {
     // let spotify_sync_job = Job::new_async("0 0 */12 * * *", move |_uuid, _lock| {
     //     let state = state.clone();
     //     Box::pin(async move {
+    //         if skip_for_quiet_hours(&state, "Spotify sync") {
+    //             return;
+    //         }
     //         spotify_sync::run_spotify_sync(state).await.ok();
     //     })
     // })?;
     // scheduler.add(spotify_sync_job).await?;
 
+    // Refresh Spotify top artists/tracks weekly (Mondays at 04:00)
+    let top_items_state = state.clone();
+    let top_items_job = Job::new_async("0 0 4 * * Mon", move |_uuid, _lock| {
+        let state = top_items_state.clone();
+        Box::pin(async move {
+            if skip_for_quiet_hours(&state, "top items refresh") {
+                return;
+            }
+            if let Err(e) = top_items::refresh_all_top_items(state).await {
+                tracing::error!("Top items refresh failed: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(top_items_job).await?;
+
+    // Pull listening history from ListenBrainz daily at 05:00
+    let listen_history_state = state.clone();
+    let listen_history_job = Job::new_async("0 0 5 * * *", move |_uuid, _lock| {
+        let state = listen_history_state.clone();
+        Box::pin(async move {
+            if skip_for_quiet_hours(&state, "listening history sync") {
+                return;
+            }
+            if let Err(e) = listen_history::sync_listen_history(&state).await {
+                tracing::error!("Listening history sync failed: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(listen_history_job).await?;
+
+    // Retry recently failed Lidarr downloads every 30 minutes (if enabled)
+    let download_retry_state = state.clone();
+    let download_retry_job = Job::new_async("0 */30 * * * *", move |_uuid, _lock| {
+        let state = download_retry_state.clone();
+        Box::pin(async move {
+            if skip_for_quiet_hours(&state, "download retry") {
+                return;
+            }
+            if let Err(e) = download_retry::retry_failed_downloads(&state).await {
+                tracing::error!("Download retry failed: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(download_retry_job).await?;
+
+    // Retry failed external side effects every 5 minutes
+    let side_effects_state = state.clone();
+    let side_effects_job = Job::new_async("0 */5 * * * *", move |_uuid, _lock| {
+        let state = side_effects_state.clone();
+        Box::pin(async move {
+            if skip_for_quiet_hours(&state, "side effect retry") {
+                return;
+            }
+            if let Err(e) = side_effects::drain_pending_side_effects(&state).await {
+                tracing::error!("Side effect retry failed: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(side_effects_job).await?;
+
+    // Lower the priority of stale wants daily at 03:00 (if enabled)
+    let wanted_demotion_state = state.clone();
+    let wanted_demotion_job = Job::new_async("0 0 3 * * *", move |_uuid, _lock| {
+        let state = wanted_demotion_state.clone();
+        Box::pin(async move {
+            if skip_for_quiet_hours(&state, "wanted demotion") {
+                return;
+            }
+            if let Err(e) = wanted_demotion::demote_stale_wants(&state).await {
+                tracing::error!("Wanted demotion failed: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(wanted_demotion_job).await?;
+
+    // Purge old archived rows daily at 03:30 (if a retention is set)
+    let archive_purge_state = state.clone();
+    let archive_purge_job = Job::new_async("0 30 3 * * *", move |_uuid, _lock| {
+        let state = archive_purge_state.clone();
+        Box::pin(async move {
+            if skip_for_quiet_hours(&state, "archive purge") {
+                return;
+            }
+            if let Err(e) = archive_purge::purge_archives(&state).await {
+                tracing::error!("Archive purge failed: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(archive_purge_job).await?;
+
+    // Forget Lidarr webhook payloads past the replay window daily at 03:45
+    let webhook_events_state = state.clone();
+    let webhook_events_job = Job::new_async("0 45 3 * * *", move |_uuid, _lock| {
+        let state = webhook_events_state.clone();
+        Box::pin(async move {
+            if skip_for_quiet_hours(&state, "webhook event cleanup") {
+                return;
+            }
+            if let Err(e) = webhook_events::prune_webhook_events(&state).await {
+                tracing::error!("Webhook event cleanup failed: {}", e);
+            }
+        })
+    })?;
+    scheduler.add(webhook_events_job).await?;
+
     // Initialize filesystem watcher if configured
     filesystem_watcher::init_watcher_if_configured(state.clone()).await?;
 
@@ -29,3 +147,16 @@ pub async fn start_scheduler(state: AppState) -> Result<JobScheduler> {
 
     Ok(scheduler)
 }
+
+/// Whether a scheduled run should be skipped because it falls in `SYNC_QUIET_HOURS`
+///
+/// Only scheduled jobs check this; syncs triggered from the UI or API always run.
+fn skip_for_quiet_hours(state: &AppState, job_name: &str) -> bool {
+    match state.config.sync_quiet_hours {
+        Some(window) if window.contains(Local::now().hour()) => {
+            tracing::info!("Skipping scheduled {} during quiet hours ({})", job_name, window);
+            true
+        }
+        _ => false,
+    }
+}
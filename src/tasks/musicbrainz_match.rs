@@ -1,16 +1,39 @@
 use anyhow::Result;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use uuid::Uuid;
 
 use crate::{
     db::{
         entities::{albums, artists},
-        enums::MatchStatus,
+        enums::{GenreSource, MatchSource, MatchStatus},
+        settings as db_settings,
     },
-    services::MusicBrainzService,
+    services::{genres, musicbrainz::MusicBrainzMatch, MusicBrainzService},
     state::AppState,
 };
 
-pub async fn run_musicbrainz_match(state: AppState) -> Result<()> {
+/// Scores at or above this are accepted as matched without review
+pub const AUTO_MATCH_SCORE: i32 = 90;
+
+/// Scores at or above this (but below `AUTO_MATCH_SCORE`) need a human look
+pub const REVIEW_MATCH_SCORE: i32 = 80;
+
+/// Bucket a match score into the status the matcher would give it
+pub fn classify_score(score: i32) -> MatchStatus {
+    if score >= AUTO_MATCH_SCORE {
+        MatchStatus::Matched
+    } else if score >= REVIEW_MATCH_SCORE {
+        MatchStatus::ManualReview
+    } else {
+        MatchStatus::NoMatch
+    }
+}
+
+/// Match pending albums against MusicBrainz, or just `album_id` when given
+///
+/// A single album is matched whatever its current match status, so a user
+/// can ask for another try.
+pub async fn run_musicbrainz_match(state: AppState, album_id: Option<i32>) -> Result<()> {
     tracing::info!("Starting MusicBrainz matching job");
 
     // Initialize MusicBrainz service
@@ -19,9 +42,18 @@ pub async fn run_musicbrainz_match(state: AppState) -> Result<()> {
         state.config.spotify_client_id
     ));
 
+    // Barcode matching is on unless explicitly disabled in settings
+    let match_by_barcode = db_settings::shared(&state.db)
+        .await?
+        .and_then(|s| s.match_by_barcode)
+        .unwrap_or(true);
+
     // Get all albums with pending match status
     let pending_albums = albums::Entity::find()
-        .filter(albums::Column::MatchStatus.eq("pending"))
+        .filter(match album_id {
+            Some(id) => albums::Column::Id.eq(id),
+            None => albums::Column::MatchStatus.eq("pending"),
+        })
         .find_also_related(artists::Entity)
         .all(&state.db)
         .await?;
@@ -33,60 +65,42 @@ pub async fn run_musicbrainz_match(state: AppState) -> Result<()> {
             tracing::debug!("Matching album: {} by {}", album_model.title, artist.name);
 
             // Search MusicBrainz
-            match mb_service
-                .search_release_group(&artist.name, &album_model.title)
-                .await
-            {
-                Ok(matches) => {
-                    if let Some(best_match) = matches.first() {
+            match find_best_match(&mb_service, &album_model, &artist.name, match_by_barcode).await {
+                Ok(best_match) => {
+                    if let Some(best_match) = best_match {
                         let album_id = album_model.id;
                         let mb_id = best_match.id;
+                        let status = classify_score(best_match.score);
 
                         let mut active: albums::ActiveModel = album_model.into();
                         active.musicbrainz_release_group_id = Set(Some(mb_id.to_string()));
                         active.match_score = Set(Some(best_match.score));
-                        active.match_status = Set(Some(if best_match.score >= 90 {
-                            MatchStatus::Matched.as_str().to_string()
-                        } else if best_match.score >= 80 {
-                            MatchStatus::ManualReview.as_str().to_string()
-                        } else {
-                            MatchStatus::NoMatch.as_str().to_string()
-                        }));
+                        active.match_status = Set(Some(status.as_str().to_string()));
+                        active.match_source = Set(Some(MatchSource::Auto.as_str().to_string()));
                         active.updated_at = Set(chrono::Utc::now().into());
 
-                        active.update(&state.db).await?;
+                        let album = active.update(&state.db).await?;
                         tracing::debug!(
                             "Matched with score {}: {}",
                             best_match.score,
                             best_match.title
                         );
 
-                        // Download cover art after successful match
-                        let covers_dir = std::path::PathBuf::from("static/covers");
-                        match super::cover_art::download_cover_art(&state, album_id, &mb_id.to_string(), &covers_dir).await {
-                            Ok(cover_url) => {
-                                // Update album with local cover art URL
-                                let album_for_cover = albums::Entity::find_by_id(album_id)
-                                    .one(&state.db)
-                                    .await?;
-
-                                if let Some(alb) = album_for_cover {
-                                    let mut active_cover: albums::ActiveModel = alb.into();
-                                    active_cover.cover_art_url = Set(Some(cover_url));
-                                    active_cover.updated_at = Set(chrono::Utc::now().into());
-                                    active_cover.update(&state.db).await?;
-                                    tracing::debug!("Cover art downloaded and saved");
-                                }
-                            }
-                            Err(e) => {
-                                tracing::warn!("Failed to download cover art: {}", e);
-                                // Continue even if cover art fails
+                        // Tags of a match that still needs review may belong to another album
+                        if status == MatchStatus::Matched {
+                            if let Err(e) = enrich_genres(&state.db, &mb_service, album, mb_id).await {
+                                tracing::warn!("Failed to fetch genres for album {}: {}", album_id, e);
                             }
                         }
+
+                        // Download cover art after successful match; a failure is retried later
+                        super::cover_art::fetch_and_store_cover_or_queue(&state, album_id, &mb_id.to_string())
+                            .await;
                     } else {
                         // No match found
                         let mut active: albums::ActiveModel = album_model.into();
                         active.match_status = Set(Some(MatchStatus::NoMatch.as_str().to_string()));
+                        active.match_source = Set(Some(MatchSource::Auto.as_str().to_string()));
                         active.updated_at = Set(chrono::Utc::now().into());
                         active.update(&state.db).await?;
                         tracing::debug!("No match found");
@@ -103,3 +117,223 @@ pub async fn run_musicbrainz_match(state: AppState) -> Result<()> {
     tracing::info!("MusicBrainz matching completed");
     Ok(())
 }
+
+/// Find the best MusicBrainz release group for an album
+///
+/// When enabled and the album has a barcode, an exact barcode lookup is tried
+/// first; otherwise (or if it finds nothing) falls back to artist/title search.
+pub async fn find_best_match(
+    mb_service: &MusicBrainzService,
+    album: &albums::Model,
+    artist_name: &str,
+    match_by_barcode: bool,
+) -> crate::error::Result<Option<MusicBrainzMatch>> {
+    if let Some(barcode) = album.barcode.as_deref().filter(|_| match_by_barcode) {
+        match mb_service.search_by_barcode(barcode).await {
+            Ok(Some(barcode_match)) => {
+                tracing::debug!("Barcode {} matched {}", barcode, barcode_match.title);
+                return Ok(Some(barcode_match));
+            }
+            Ok(None) => tracing::debug!("No MusicBrainz release for barcode {}", barcode),
+            Err(e) => tracing::warn!("Barcode lookup failed, falling back to search: {}", e),
+        }
+    }
+
+    Ok(mb_service
+        .search_release_group(artist_name, &album.title)
+        .await?
+        .into_iter()
+        .next())
+}
+
+/// Fill an album's empty genres from the top tags of its release group
+///
+/// Returns whether any genres were stored; hand-edited and non-empty genres
+/// are left alone.
+pub async fn enrich_genres(
+    db: &DatabaseConnection,
+    mb_service: &MusicBrainzService,
+    album: albums::Model,
+    release_group_id: Uuid,
+) -> crate::error::Result<bool> {
+    if !genres::may_replace(&album, GenreSource::Musicbrainz) {
+        return Ok(false);
+    }
+
+    let tags = mb_service.fetch_release_group_tags(release_group_id).await?;
+    let top: Vec<String> = genres::normalize(tags.into_iter().map(|tag| tag.name))
+        .into_iter()
+        .take(genres::MUSICBRAINZ_GENRE_LIMIT)
+        .collect();
+    if top.is_empty() {
+        return Ok(false);
+    }
+
+    let mut active: albums::ActiveModel = album.into();
+    active.genres = Set(genres::to_column(&top));
+    active.genre_source = Set(Some(GenreSource::Musicbrainz.into()));
+    active.updated_at = Set(chrono::Utc::now().into());
+    active.update(db).await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use axum::{extract::State, routing::get, Json, Router};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    const BARCODE_RELEASE_GROUP: &str = "48117b82-8b61-3b57-a9e1-0d9bd29e8ba6";
+    const SEARCH_RELEASE_GROUP: &str = "0da580f2-6768-498f-af9d-2becaddf15e0";
+
+    type Hits = Arc<Mutex<Vec<&'static str>>>;
+
+    /// Serve canned MusicBrainz responses, recording which endpoints were hit
+    async fn mock_musicbrainz() -> (String, Hits) {
+        async fn release(State(hits): State<Hits>) -> Json<Value> {
+            hits.lock().unwrap().push("release");
+            Json(json!({
+                "releases": [{
+                    "score": 100,
+                    "title": "Discovery",
+                    "artist-credit": [],
+                    "release-group": { "id": BARCODE_RELEASE_GROUP, "title": "Discovery" }
+                }]
+            }))
+        }
+
+        async fn release_group(State(hits): State<Hits>) -> Json<Value> {
+            hits.lock().unwrap().push("release-group");
+            Json(json!({
+                "release-groups": [{
+                    "id": SEARCH_RELEASE_GROUP,
+                    "title": "Discovery",
+                    "score": 95,
+                    "artist-credit": []
+                }]
+            }))
+        }
+
+        async fn tags(State(hits): State<Hits>) -> Json<Value> {
+            hits.lock().unwrap().push("tags");
+            Json(json!({
+                "id": SEARCH_RELEASE_GROUP,
+                "tags": [
+                    { "name": "house", "count": 4 },
+                    { "name": "electronic", "count": 9 },
+                    { "name": "french house", "count": 7 },
+                    { "name": "disco", "count": 2 },
+                    { "name": "Electronic", "count": 1 },
+                    { "name": "dance", "count": 3 },
+                    { "name": "synth-pop", "count": 1 }
+                ]
+            }))
+        }
+
+        let hits: Hits = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route("/release", get(release))
+            .route("/release-group", get(release_group))
+            .route("/release-group/:id", get(tags))
+            .with_state(hits.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    async fn album_with_barcode(barcode: Option<&str>) -> albums::Model {
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Daft Punk", None).await;
+        let album = create_test_album(&db, artist.id, "Discovery", None).await;
+
+        let mut active: albums::ActiveModel = album.into();
+        active.barcode = Set(barcode.map(|b| b.to_string()));
+        active.update(&db).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_barcode_lookup_runs_before_text_search() {
+        let (base_url, hits) = mock_musicbrainz().await;
+        let service = MusicBrainzService::with_base_url("Test/1.0".to_string(), base_url);
+        let album = album_with_barcode(Some("724384960650")).await;
+
+        let best = find_best_match(&service, &album, "Daft Punk", true)
+            .await
+            .unwrap()
+            .expect("Should match by barcode");
+
+        assert_eq!(best.id.to_string(), BARCODE_RELEASE_GROUP);
+        assert_eq!(*hits.lock().unwrap(), vec!["release"]);
+    }
+
+    #[tokio::test]
+    async fn test_text_search_when_barcode_matching_disabled() {
+        let (base_url, hits) = mock_musicbrainz().await;
+        let service = MusicBrainzService::with_base_url("Test/1.0".to_string(), base_url);
+        let album = album_with_barcode(Some("724384960650")).await;
+
+        let best = find_best_match(&service, &album, "Daft Punk", false)
+            .await
+            .unwrap()
+            .expect("Should match by search");
+
+        assert_eq!(best.id.to_string(), SEARCH_RELEASE_GROUP);
+        assert_eq!(*hits.lock().unwrap(), vec!["release-group"]);
+    }
+
+    #[tokio::test]
+    async fn test_enrich_genres_fills_empty_genres_from_top_tags() {
+        let (base_url, hits) = mock_musicbrainz().await;
+        let service = MusicBrainzService::with_base_url("Test/1.0".to_string(), base_url);
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Daft Punk", None).await;
+        let album = create_test_album(&db, artist.id, "Discovery", None).await;
+        let mbid = Uuid::parse_str(SEARCH_RELEASE_GROUP).unwrap();
+
+        assert!(enrich_genres(&db, &service, album.clone(), mbid).await.unwrap());
+
+        let album = albums::Entity::find_by_id(album.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(
+            genres::album_genres(&album),
+            vec!["electronic", "french house", "house", "dance", "disco"]
+        );
+        assert_eq!(album.genre_source.as_deref(), Some("musicbrainz"));
+
+        // Once filled, the next match leaves them alone without asking MusicBrainz
+        assert!(!enrich_genres(&db, &service, album, mbid).await.unwrap());
+        assert_eq!(*hits.lock().unwrap(), vec!["tags"]);
+    }
+
+    #[tokio::test]
+    async fn test_enrich_genres_keeps_manual_genres() {
+        let (base_url, hits) = mock_musicbrainz().await;
+        let service = MusicBrainzService::with_base_url("Test/1.0".to_string(), base_url);
+        let db = setup_test_db().await;
+        let artist = create_test_artist(&db, "Daft Punk", None).await;
+        let album = create_test_album(&db, artist.id, "Discovery", None).await;
+
+        let mut active: albums::ActiveModel = album.into();
+        active.genres = Set(Some("[]".to_string()));
+        active.genre_source = Set(Some(GenreSource::Manual.into()));
+        let album = active.update(&db).await.unwrap();
+
+        let mbid = Uuid::parse_str(SEARCH_RELEASE_GROUP).unwrap();
+        assert!(!enrich_genres(&db, &service, album, mbid).await.unwrap());
+        assert!(hits.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_classify_score_boundaries() {
+        assert_eq!(classify_score(AUTO_MATCH_SCORE), MatchStatus::Matched);
+        assert_eq!(classify_score(AUTO_MATCH_SCORE - 1), MatchStatus::ManualReview);
+        assert_eq!(classify_score(REVIEW_MATCH_SCORE), MatchStatus::ManualReview);
+        assert_eq!(classify_score(REVIEW_MATCH_SCORE - 1), MatchStatus::NoMatch);
+    }
+}
@@ -0,0 +1,88 @@
+//! Scheduled retries of failed external side effects
+//!
+//! Runs every entry of the `pending_side_effects` queue whose backoff has
+//! passed. Entries that succeed are removed; entries that fail again are
+//! rescheduled, until they reach the attempt limit and become dead letters
+//! for the admin API to retry or discard.
+
+use crate::{
+    db::entities::pending_side_effects,
+    error::{AppError, Result},
+    services::{
+        album_grid_cache,
+        side_effects::{self, CoverArtDownload, SideEffectKind},
+    },
+    state::AppState,
+};
+
+/// Outcome of one drain pass
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DrainSummary {
+    /// Entries that ran and were removed
+    pub succeeded: usize,
+    /// Entries that failed again and were rescheduled
+    pub failed: usize,
+    /// Entries that failed for the last time and are now dead letters
+    pub dead_lettered: usize,
+}
+
+/// Retry every due side effect once
+pub async fn drain_pending_side_effects(state: &AppState) -> Result<DrainSummary> {
+    let mut summary = DrainSummary::default();
+
+    for entry in side_effects::due(&state.db).await? {
+        match run_entry(state, entry).await? {
+            None => summary.succeeded += 1,
+            Some(entry) if side_effects::is_dead(&entry) => summary.dead_lettered += 1,
+            Some(_) => summary.failed += 1,
+        }
+    }
+
+    if summary.succeeded > 0 {
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+    if summary != DrainSummary::default() {
+        tracing::info!(
+            "Side effect retries: {} succeeded, {} failed, {} gave up",
+            summary.succeeded,
+            summary.failed,
+            summary.dead_lettered
+        );
+    }
+    Ok(summary)
+}
+
+/// Run one entry now, whatever its schedule
+///
+/// Returns `None` when it succeeded and was removed, or the entry with the
+/// failure recorded.
+pub async fn run_entry(
+    state: &AppState,
+    entry: pending_side_effects::Model,
+) -> Result<Option<pending_side_effects::Model>> {
+    match perform(state, &entry).await {
+        Ok(()) => {
+            side_effects::complete(&state.db, entry.id).await?;
+            tracing::debug!("{} side effect {} succeeded", entry.kind, entry.id);
+            Ok(None)
+        }
+        Err(e) => {
+            let entry = side_effects::record_failure(&state.db, entry, &e.to_string()).await?;
+            Ok(Some(entry))
+        }
+    }
+}
+
+async fn perform(state: &AppState, entry: &pending_side_effects::Model) -> Result<()> {
+    let kind = SideEffectKind::parse(&entry.kind)
+        .ok_or_else(|| AppError::Internal(format!("Unknown side effect kind '{}'", entry.kind)))?;
+
+    match kind {
+        SideEffectKind::CoverArtDownload => {
+            let payload: CoverArtDownload = serde_json::from_str(&entry.payload)?;
+            super::cover_art::fetch_and_store_cover(state, payload.album_id, &payload.release_group_id)
+                .await?;
+        }
+    }
+    Ok(())
+}
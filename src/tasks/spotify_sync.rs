@@ -1,84 +1,378 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use crate::{
     db::{
-        entities::{albums, artists, playlist_tracks, playlists, tracks, user_settings},
-        enums::{AlbumSource, MatchStatus, OwnershipStatus},
+        entities::{albums, artists, jobs, playlist_tracks, playlists, tracks},
+        enums::{AlbumSource, GenreSource, JobStatus, JobType, MatchStatus, OwnershipStatus},
+        artist_names, profile, settings as db_settings, slug,
     },
-    services::{SpotifyAlbum, SpotifyArtist, SpotifyPlaylist, SpotifyPlaylistTrack, SpotifyService, SpotifyTrack},
+    events::{DomainEvent, EventBus},
+    handlers::jobs::enqueue_job,
+    services::{archive, genres, playlist_cleanup, spotify::{SavedAlbumsPage, SpotifyStats}, SpotifyAlbum, SpotifyArtist, SpotifyPlaylist, SpotifyPlaylistTrack, SpotifyService, SpotifyTrack},
+    jobs::record_result,
     state::AppState,
+    tasks::cover_art,
 };
 
-/// Synthetic Spotify ID for Liked Songs playlist
+/// Synthetic Spotify ID prefix for Liked Songs playlists (suffixed with the profile ID)
 pub const LIKED_SONGS_SPOTIFY_ID: &str = "__LIKED_SONGS__";
 pub const LIKED_SONGS_NAME: &str = "Liked Songs";
 
-/// Main entry point for Spotify sync job
-pub async fn run_spotify_sync(state: AppState) -> Result<()> {
-    tracing::info!("Starting Spotify sync job");
+/// Page sizes the sync requests from Spotify, for estimating the requests left
+const SAVED_PAGE_SIZE: u64 = 50;
+const PLAYLIST_TRACKS_PAGE_SIZE: u64 = 100;
 
-    // Get user settings with Spotify tokens
-    let settings = user_settings::Entity::find()
-        .one(&state.db)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("No user settings found"))?;
+/// Synthetic Spotify ID of a profile's Liked Songs playlist
+pub fn liked_songs_spotify_id(profile_id: i32) -> String {
+    format!("{}:{}", LIKED_SONGS_SPOTIFY_ID, profile_id)
+}
+
+/// Main entry point for Spotify sync job
+///
+/// Syncs into the library of the given profile (the default profile if `None`).
+pub async fn run_spotify_sync(state: AppState, job_id: i32, profile_id: Option<i32>) -> Result<()> {
+    let profile = profile::resolve_profile(&state.db, profile_id).await?;
+    tracing::info!("Starting Spotify sync job for profile {}", profile.name);
 
-    let access_token = settings
+    let access_token = profile
         .spotify_access_token
         .ok_or_else(|| anyhow::anyhow!("Spotify not connected"))?;
 
     // Initialize Spotify service
-    let spotify_service = SpotifyService::new(
-        state.config.spotify_client_id.clone(),
-        state.config.spotify_redirect_uri.clone(),
-    );
+    let spotify_service = SpotifyService::from_config(&state.config);
 
     // Phase 1: Sync saved albums
-    sync_saved_albums(&state.db, &spotify_service, &access_token).await?;
+    let (saved_albums, skipped_albums) =
+        sync_saved_albums(&state.db, &state.events, &spotify_service, &access_token, job_id, profile.id).await?;
 
     // Phase 2: Sync playlists
-    sync_playlists(&state.db, &spotify_service, &access_token).await?;
+    let (playlists, skipped_tracks) =
+        sync_playlists(&state.db, &state.events, &spotify_service, &access_token, job_id, profile.id).await?;
+    report_progress(&state.db, job_id, &SyncProgress::new(&spotify_service.stats(), 0)).await?;
+
+    let skipped = skipped_albums + skipped_tracks;
+    if skipped > 0 {
+        tracing::warn!("Skipped {} non-music items (podcasts or items without artists)", skipped);
+    }
+
+    let summary = SyncSummary { saved_albums, playlists, skipped };
+    record_result(&state.db, job_id, Some(saved_albums + playlists), &summary).await?;
+
+    queue_cover_art_fetch(&state, profile.id).await?;
 
     tracing::info!("Spotify sync completed successfully");
     Ok(())
 }
 
+/// Queue a cover art job for albums without local covers, when the synced
+/// profile has `auto_fetch_cover_art` on
+///
+/// A full queue is logged rather than failing the sync that already finished.
+async fn queue_cover_art_fetch(state: &AppState, profile_id: i32) -> Result<()> {
+    let enabled = db_settings::for_profile_id(&state.db, Some(profile_id))
+        .await?
+        .and_then(|s| s.auto_fetch_cover_art)
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(());
+    }
+
+    let missing = cover_art::count_missing_covers(&state.db).await?;
+    if missing == 0 {
+        tracing::debug!("No albums missing local cover art after sync");
+        return Ok(());
+    }
+
+    let now = Utc::now().into();
+    let job = jobs::ActiveModel {
+        job_type: Set(JobType::CoverArtFetch.as_str().to_string()),
+        status: Set(JobStatus::Pending.as_str().to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
+
+    match enqueue_job(state, job, JobType::CoverArtFetch).await {
+        Ok(()) => tracing::info!("Queued cover art fetch for {} albums", missing),
+        Err(e) => tracing::warn!("Could not queue cover art fetch for {} albums: {}", missing, e),
+    }
+    Ok(())
+}
+
+/// Result payload stored on a finished Spotify sync job
+#[derive(Debug, Serialize)]
+pub struct SyncSummary {
+    /// Saved albums processed by this run (after a resume, only the remaining pages)
+    pub saved_albums: usize,
+    /// Playlists fetched and upserted, including Liked Songs
+    pub playlists: usize,
+    /// Podcast shows and episodes, and other items without artists, left out of the library
+    pub skipped: usize,
+}
+
 /// Sync saved albums from user's Spotify library
+///
+/// Albums are fetched and upserted one page at a time. After each page the
+/// `next` URL is stored on the job as a cursor, so a failed sync resumes from
+/// the last completed page instead of re-fetching the whole library. The cursor
+/// is cleared once the final page has been processed.
+///
+/// A sync that saw the whole library (not a resumed one) archives the saved
+/// albums it didn't see, since they were un-saved on Spotify.
+///
+/// Returns the number of albums synced and the number skipped as non-music.
 async fn sync_saved_albums(
     db: &DatabaseConnection,
+    events: &EventBus,
     spotify_service: &SpotifyService,
     access_token: &str,
-) -> Result<()> {
-    let albums = spotify_service.fetch_saved_albums(access_token).await?;
-    tracing::info!("Fetched {} saved albums from Spotify", albums.len());
+    job_id: i32,
+    profile_id: i32,
+) -> Result<(usize, usize)> {
+    let sync_started_at = Utc::now();
+    let resume_from = resume_cursor(db, spotify_service, job_id, profile_id).await?;
+    let resumed = resume_from.is_some();
+    let mut next_url = match resume_from {
+        Some(cursor) => {
+            tracing::info!("Resuming saved albums sync from {}", cursor);
+            Some(cursor)
+        }
+        None => Some(spotify_service.saved_albums_url()),
+    };
+    let mut synced = 0;
+    let mut skipped = 0;
+
+    while let Some(url) = next_url {
+        let page = spotify_service.fetch_saved_albums_page(access_token, &url).await?;
+        let mut saved_ids = Vec::with_capacity(page.albums.len());
+
+        for spotify_album in &page.albums {
+            if !spotify_album.is_music() {
+                tracing::warn!("Skipping non-music saved item: {} ({})", spotify_album.name, spotify_album.id);
+                skipped += 1;
+                continue;
+            }
+
+            let artist = upsert_artist(db, &spotify_album.artists[0]).await?;
+            let album = upsert_album(db, events, spotify_album, artist.id, AlbumSource::SavedAlbum).await?;
+            profile::link_album(db, profile_id, album.id).await?;
+            saved_ids.push(album.id);
+            synced += 1;
+        }
+
+        archive::mark_albums_saved(db, saved_ids).await?;
+        save_sync_cursor(db, job_id, page.next.clone()).await?;
+        let progress = SyncProgress::new(&spotify_service.stats(), remaining_saved_pages(&page));
+        report_progress(db, job_id, &progress).await?;
+        next_url = page.next;
+    }
+
+    tracing::info!("Synced {} saved albums from Spotify", synced);
+    if !resumed {
+        archive::archive_unsaved_albums(db, profile_id, sync_started_at.into()).await?;
+    }
+    Ok((synced, skipped))
+}
+
+/// Find the cursor to resume a saved albums sync from
+///
+/// Uses the job's own cursor if it has one, otherwise inherits the cursor of the
+/// profile's previous Spotify sync job when that job failed partway through.
+async fn resume_cursor(
+    db: &DatabaseConnection,
+    spotify_service: &SpotifyService,
+    job_id: i32,
+    profile_id: i32,
+) -> Result<Option<String>> {
+    let own_cursor = jobs::Entity::find_by_id(job_id)
+        .one(db)
+        .await?
+        .and_then(|job| job.sync_cursor);
+
+    let cursor = match own_cursor {
+        Some(cursor) => Some(cursor),
+        None => jobs::Entity::find()
+            .filter(jobs::Column::JobType.eq(JobType::SpotifySync.as_str()))
+            .filter(jobs::Column::EntityId.eq(profile_id))
+            .filter(jobs::Column::Id.ne(job_id))
+            .order_by_desc(jobs::Column::Id)
+            .one(db)
+            .await?
+            .filter(|job| job.status == JobStatus::Failed.as_str())
+            .and_then(|job| job.sync_cursor),
+    };
+
+    // Only ever follow cursors that point back at the saved albums endpoint
+    Ok(cursor.filter(|url| spotify_service.is_saved_albums_url(url)))
+}
+
+/// Store (or clear) the saved albums resume cursor on a job
+async fn save_sync_cursor(db: &DatabaseConnection, job_id: i32, cursor: Option<String>) -> Result<()> {
+    if let Some(job) = jobs::Entity::find_by_id(job_id).one(db).await? {
+        let mut active: jobs::ActiveModel = job.into();
+        active.sync_cursor = Set(cursor);
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+    }
+    Ok(())
+}
 
-    for spotify_album in albums {
-        let artist = upsert_artist(db, &spotify_album.artists[0]).await?;
-        upsert_album(db, &spotify_album, artist.id, AlbumSource::SavedAlbum).await?;
+/// API usage of a running sync and its estimated time left
+///
+/// Stored as JSON in the job's `progress_detail` while the sync runs; the last
+/// report stays on the job once it finishes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncProgress {
+    pub api_calls: u64,
+    pub rate_limit_hits: u64,
+    pub requests_per_second: f64,
+    /// Spotify requests the sync knows it still has to make
+    pub remaining_requests: u64,
+    /// Seconds those requests take at the current rate; absent before the first request
+    pub eta_seconds: Option<u64>,
+}
+
+impl SyncProgress {
+    pub fn new(stats: &SpotifyStats, remaining_requests: u64) -> Self {
+        Self {
+            api_calls: stats.requests(),
+            rate_limit_hits: stats.rate_limit_hits(),
+            requests_per_second: stats.request_rate(),
+            remaining_requests,
+            eta_seconds: stats.eta(remaining_requests).map(|eta| eta.as_secs()),
+        }
+    }
+
+    /// One line for the jobs page, e.g. "~7 minutes remaining, 412 API calls made, 0 rate-limit hits"
+    ///
+    /// The time left only means something while the sync is still running.
+    pub fn summary(&self, running: bool) -> String {
+        let plural = |n: u64| if n == 1 { "" } else { "s" };
+        let usage = format!(
+            "{} API call{} made, {} rate-limit hit{}",
+            self.api_calls,
+            plural(self.api_calls),
+            self.rate_limit_hits,
+            plural(self.rate_limit_hits)
+        );
+        match self.eta_seconds.filter(|_| running) {
+            Some(secs) if secs < 60 => format!("under a minute remaining, {}", usage),
+            Some(secs) => {
+                let minutes = secs.div_ceil(60);
+                format!("~{} minute{} remaining, {}", minutes, plural(minutes), usage)
+            }
+            None => usage,
+        }
     }
+}
 
+/// Store a sync's progress on its job
+pub(crate) async fn report_progress(db: &DatabaseConnection, job_id: i32, progress: &SyncProgress) -> Result<()> {
+    if let Some(job) = jobs::Entity::find_by_id(job_id).one(db).await? {
+        let mut active: jobs::ActiveModel = job.into();
+        active.progress_detail = Set(Some(serde_json::to_string(progress)?));
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+    }
     Ok(())
 }
 
+/// Pages needed to fetch `items` entries
+fn pages(items: i32, page_size: u64) -> u64 {
+    (items.max(0) as u64).div_ceil(page_size)
+}
+
+/// Saved album pages left after this one, from the offset in the next page's URL
+fn remaining_saved_pages(page: &SavedAlbumsPage) -> u64 {
+    let Some(next) = page.next.as_deref() else {
+        return 0;
+    };
+    let offset = reqwest::Url::parse(next).ok().and_then(|url| {
+        url.query_pairs()
+            .find(|(key, _)| key == "offset")
+            .and_then(|(_, value)| value.parse::<i32>().ok())
+    });
+    match offset {
+        Some(offset) => pages(page.total - offset, SAVED_PAGE_SIZE).max(1),
+        None => 1,
+    }
+}
+
 /// Sync playlists and their tracks from Spotify
+///
+/// Returns the number of playlists synced, Liked Songs included, and the
+/// number of playlist entries skipped as non-music.
 async fn sync_playlists(
     db: &DatabaseConnection,
+    events: &EventBus,
     spotify_service: &SpotifyService,
     access_token: &str,
-) -> Result<()> {
+    job_id: i32,
+    profile_id: i32,
+) -> Result<(usize, usize)> {
     // Sync Liked Songs as a synthetic playlist first
-    sync_liked_songs(db, spotify_service, access_token).await?;
+    let mut skipped = sync_liked_songs(db, events, spotify_service, access_token, job_id, profile_id).await?;
 
     // Then sync regular playlists
     let spotify_playlists = spotify_service.fetch_user_playlists(access_token).await?;
     tracing::info!("Fetched {} playlists from Spotify", spotify_playlists.len());
 
-    for spotify_playlist in spotify_playlists {
+    let sync_collaborative = playlist_cleanup::sync_collaborative_enabled(db, Some(profile_id)).await?;
+    let failure_threshold = playlist_cleanup::sync_failure_threshold(db, Some(profile_id)).await?;
+    let mut synced = 1;
+    let followed: Vec<String> = spotify_playlists.iter().map(|p| p.id.clone()).collect();
+
+    // Track pages each playlist will need, to estimate the requests left
+    let known: HashMap<String, playlists::Model> = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.is_in(followed.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.spotify_id.clone(), p))
+        .collect();
+    let planned_pages: Vec<u64> = spotify_playlists
+        .iter()
+        .map(|spotify_playlist| {
+            let will_sync = (!spotify_playlist.collaborative || sync_collaborative)
+                && known.get(&spotify_playlist.id).is_some_and(|p| {
+                    p.is_enabled
+                        && (p.snapshot_id.as_deref() != Some(&spotify_playlist.snapshot_id)
+                            || p.last_synced_at.is_none())
+                });
+            if will_sync {
+                pages(spotify_playlist.tracks.total, PLAYLIST_TRACKS_PAGE_SIZE).max(1)
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    for (index, spotify_playlist) in spotify_playlists.into_iter().enumerate() {
+        if planned_pages[index] > 0 {
+            let remaining = planned_pages[index..].iter().sum();
+            report_progress(db, job_id, &SyncProgress::new(&spotify_service.stats(), remaining)).await?;
+        }
+
+        if spotify_playlist.collaborative && !sync_collaborative {
+            tracing::debug!("Skipping collaborative playlist: {}", spotify_playlist.name);
+            continue;
+        }
+        synced += 1;
+
         // Upsert the playlist record
         let playlist = upsert_playlist(db, &spotify_playlist).await?;
+        profile::link_playlist(db, profile_id, playlist.id).await?;
 
         // Only sync tracks for enabled playlists
         if !playlist.is_enabled {
@@ -95,10 +389,22 @@ async fn sync_playlists(
             continue;
         }
 
-        // Fetch and sync tracks for this playlist
-        let spotify_tracks = spotify_service
+        // Fetch and sync tracks for this playlist; one playlist that can't
+        // be read (gone private, access revoked) shouldn't fail the sync
+        let spotify_tracks = match spotify_service
             .fetch_playlist_tracks(access_token, &spotify_playlist.id)
-            .await?;
+            .await
+        {
+            Ok(tracks) => tracks,
+            Err(e) => {
+                tracing::warn!("Failed to fetch tracks for playlist {}: {}", playlist.name, e);
+                if playlist_cleanup::counts_as_sync_failure(&e) {
+                    playlist_cleanup::record_sync_failure(db, Some(profile_id), playlist, &e.to_string(), failure_threshold)
+                        .await?;
+                }
+                continue;
+            }
+        };
 
         tracing::info!(
             "Syncing {} tracks for playlist: {}",
@@ -106,29 +412,66 @@ async fn sync_playlists(
             playlist.name
         );
 
-        sync_playlist_tracks(db, playlist.id, &spotify_tracks).await?;
+        let counts = sync_playlist_tracks(db, events, playlist.id, &spotify_tracks, profile_id).await?;
+        skipped += counts.skipped;
 
         // Update playlist snapshot_id and last_synced_at
         let mut active: playlists::ActiveModel = playlist.into();
+        active.unavailable_tracks = Set(Some(counts.unavailable));
+        active.episode_count = Set(Some(counts.episodes));
         active.snapshot_id = Set(Some(spotify_playlist.snapshot_id.clone()));
         active.last_synced_at = Set(Some(Utc::now().into()));
+        active.sync_failure_count = Set(0);
         active.updated_at = Set(Utc::now().into());
         active.update(db).await?;
     }
 
-    Ok(())
+    archive::archive_unfollowed_playlists(db, profile_id, &followed).await?;
+
+    Ok((synced, skipped))
+}
+
+/// Entry counts from syncing one playlist's tracks
+struct TrackSyncCounts {
+    /// Stored entries Spotify can't play in the user's market, plus those
+    /// restricted so far that they came back without a track ID
+    unavailable: i32,
+    /// Podcast episodes and other entries without artists
+    skipped: usize,
+    /// Of the skipped entries, those that are podcast episodes
+    episodes: i32,
 }
 
 /// Sync tracks for a specific playlist
+///
+/// Unplayable tracks that still have an ID are stored like any other, with
+/// their `is_playable` flag; entries without one can't be and are skipped, as
+/// are podcast episodes. Relinked tracks are stored under the ID the playlist
+/// holds, not the market copy Spotify played instead.
 async fn sync_playlist_tracks(
     db: &DatabaseConnection,
+    events: &EventBus,
     playlist_id: i32,
     spotify_tracks: &[SpotifyPlaylistTrack],
-) -> Result<()> {
+    profile_id: i32,
+) -> Result<TrackSyncCounts> {
     // Collect track IDs that should be in this playlist
     let mut valid_track_ids: Vec<i32> = Vec::new();
+    let mut skipped = 0;
+    let mut episodes = 0;
+    let unstorable = spotify_tracks
+        .iter()
+        .filter(|t| t.is_unavailable() && t.track.as_ref().and_then(|track| track.spotify_id()).is_none())
+        .count() as i32;
 
     for (position, playlist_track) in spotify_tracks.iter().enumerate() {
+        // Episode objects have no track shape and were dropped while parsing
+        if playlist_track.is_episode {
+            episodes += 1;
+            skipped += 1;
+            continue;
+        }
+
         // Skip tracks without data (local files, deleted tracks)
         let spotify_track = match &playlist_track.track {
             Some(t) => t,
@@ -136,49 +479,233 @@ async fn sync_playlist_tracks(
         };
 
         // Skip tracks without Spotify ID (local files)
-        let track_spotify_id = match &spotify_track.id {
+        let track_spotify_id = match spotify_track.spotify_id() {
             Some(id) => id,
             None => continue,
         };
 
-        // Upsert artist (use first artist)
-        let artist = upsert_artist(db, &spotify_track.artists[0]).await?;
-
-        // Upsert album (mark as playlist import if new)
-        let album = upsert_album(db, &spotify_track.album, artist.id, AlbumSource::PlaylistImport).await?;
+        // Skip podcast episodes, which can also show up in playlists in track form
+        if !spotify_track.is_music() {
+            tracing::debug!("Skipping non-music playlist entry: {} ({})", spotify_track.name, track_spotify_id);
+            episodes += 1;
+            skipped += 1;
+            continue;
+        }
 
-        // Upsert track
-        let track = upsert_track(db, spotify_track, album.id, track_spotify_id).await?;
+        let track = upsert_playlist_entry(db, events, spotify_track, track_spotify_id, profile_id).await?;
 
         valid_track_ids.push(track.id);
 
         // Upsert playlist_tracks junction record
-        upsert_playlist_track(db, playlist_id, track.id, position as i32, &playlist_track.added_at).await?;
+        upsert_playlist_track(db, playlist_id, track.id, position as i32, playlist_track).await?;
+    }
+
+    if episodes > 0 {
+        tracing::info!("Skipped {} podcast episodes in playlist {}", episodes, playlist_id);
     }
 
     // Remove tracks no longer in the playlist
     cleanup_removed_tracks(db, playlist_id, &valid_track_ids).await?;
 
-    Ok(())
+    let unplayable = playlist_tracks::Entity::find()
+        .filter(playlist_tracks::Column::PlaylistId.eq(playlist_id))
+        .filter(playlist_tracks::Column::IsPlayable.eq(false))
+        .count(db)
+        .await? as i32;
+    let unavailable = unplayable + unstorable;
+    if unavailable > 0 {
+        tracing::info!("{} tracks in playlist {} are unavailable in this market", unavailable, playlist_id);
+    }
+
+    Ok(TrackSyncCounts { unavailable, skipped, episodes })
+}
+
+/// Upsert a playlist entry's artist, album and track, adding the album to the profile's library
+async fn upsert_playlist_entry(
+    db: &DatabaseConnection,
+    events: &EventBus,
+    spotify_track: &SpotifyTrack,
+    track_spotify_id: &str,
+    profile_id: i32,
+) -> Result<tracks::Model> {
+    // Upsert artist (use first artist)
+    let artist = upsert_artist(db, &spotify_track.artists[0]).await?;
+
+    // Upsert album (mark as playlist import if new)
+    let album = upsert_album(db, events, &spotify_track.album, artist.id, AlbumSource::PlaylistImport).await?;
+    profile::link_album(db, profile_id, album.id).await?;
+
+    upsert_track(db, spotify_track, album.id, track_spotify_id).await
+}
+
+/// Result of importing a playlist by link
+#[derive(Debug, Serialize)]
+pub struct PlaylistImportSummary {
+    pub spotify_id: String,
+    pub name: String,
+    /// Local playlist record, if one was kept
+    pub playlist_id: Option<i32>,
+    /// Albums new to the database
+    pub albums_created: usize,
+    /// Albums that were already known, now also in the profile's library
+    pub albums_existing: usize,
+    pub tracks_created: usize,
+    pub tracks_existing: usize,
+    /// Podcast episodes and other entries without artists
+    pub skipped: usize,
+}
+
+/// Import the albums of a playlist the user doesn't follow
+///
+/// Runs the same upserts as a playlist sync, so new albums land in the
+/// profile's library as not-owned playlist imports. With `keep_playlist` the
+/// playlist itself is stored too, flagged external and enabled, with its
+/// track memberships. Syncs never touch it since it isn't followed.
+pub async fn import_playlist(
+    db: &DatabaseConnection,
+    events: &EventBus,
+    spotify_playlist: &SpotifyPlaylist,
+    spotify_tracks: &[SpotifyPlaylistTrack],
+    profile_id: i32,
+    keep_playlist: bool,
+) -> Result<PlaylistImportSummary> {
+    // Note what's already stored so the summary can tell new from known
+    let music: Vec<&SpotifyTrack> = spotify_tracks
+        .iter()
+        .filter_map(|entry| entry.track.as_ref())
+        .filter(|track| track.spotify_id().is_some() && track.is_music())
+        .collect();
+    let mut album_ids: Vec<&str> = music.iter().map(|track| track.album.id.as_str()).collect();
+    album_ids.sort_unstable();
+    album_ids.dedup();
+    let mut track_ids: Vec<&str> = music.iter().filter_map(|track| track.spotify_id()).collect();
+    track_ids.sort_unstable();
+    track_ids.dedup();
+
+    let albums_existing = albums::Entity::find()
+        .filter(albums::Column::SpotifyId.is_in(album_ids.iter().copied()))
+        .count(db)
+        .await? as usize;
+    let tracks_existing = tracks::Entity::find()
+        .filter(tracks::Column::SpotifyId.is_in(track_ids.iter().copied()))
+        .count(db)
+        .await? as usize;
+
+    let (playlist_id, skipped) = if keep_playlist {
+        let playlist = upsert_external_playlist(db, spotify_playlist).await?;
+        profile::link_playlist(db, profile_id, playlist.id).await?;
+
+        let counts = sync_playlist_tracks(db, events, playlist.id, spotify_tracks, profile_id).await?;
+
+        let playlist_id = playlist.id;
+        let mut active: playlists::ActiveModel = playlist.into();
+        active.is_enabled = Set(true);
+        active.unavailable_tracks = Set(Some(counts.unavailable));
+        active.episode_count = Set(Some(counts.episodes));
+        active.snapshot_id = Set(Some(spotify_playlist.snapshot_id.clone()));
+        active.last_synced_at = Set(Some(Utc::now().into()));
+        active.updated_at = Set(Utc::now().into());
+        active.update(db).await?;
+
+        (Some(playlist_id), counts.skipped)
+    } else {
+        let mut skipped = 0;
+        for spotify_track in spotify_tracks.iter().filter_map(|entry| entry.track.as_ref()) {
+            let Some(track_spotify_id) = spotify_track.spotify_id() else {
+                continue;
+            };
+            if !spotify_track.is_music() {
+                tracing::warn!("Skipping non-music playlist entry: {} ({})", spotify_track.name, track_spotify_id);
+                skipped += 1;
+                continue;
+            }
+            upsert_playlist_entry(db, events, spotify_track, track_spotify_id, profile_id).await?;
+        }
+        (None, skipped)
+    };
+
+    tracing::info!(
+        "Imported playlist {} ({} albums, {} tracks)",
+        spotify_playlist.name,
+        album_ids.len(),
+        track_ids.len()
+    );
+
+    Ok(PlaylistImportSummary {
+        spotify_id: spotify_playlist.id.clone(),
+        name: spotify_playlist.name.clone(),
+        playlist_id,
+        albums_created: album_ids.len() - albums_existing,
+        albums_existing,
+        tracks_created: track_ids.len() - tracks_existing,
+        tracks_existing,
+        skipped,
+    })
+}
+
+/// Store a playlist imported by link, unless it's already known
+///
+/// A followed playlist keeps its record as-is; only new ones are flagged external.
+async fn upsert_external_playlist(
+    db: &DatabaseConnection,
+    spotify_playlist: &SpotifyPlaylist,
+) -> Result<playlists::Model> {
+    if let Some(existing) = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq(&spotify_playlist.id))
+        .one(db)
+        .await?
+    {
+        return Ok(existing);
+    }
+
+    let new_playlist = playlists::ActiveModel {
+        name: Set(spotify_playlist.name.clone()),
+        spotify_id: Set(spotify_playlist.id.clone()),
+        description: Set(spotify_playlist.description.clone()),
+        owner_name: Set(spotify_playlist.owner.display_name.clone()),
+        is_collaborative: Set(spotify_playlist.collaborative),
+        total_tracks: Set(Some(spotify_playlist.tracks.total)),
+        cover_image_url: Set(spotify_playlist.images.first().map(|i| i.url.clone())),
+        is_enabled: Set(true),
+        is_synthetic: Set(false),
+        is_external: Set(true),
+        created_at: Set(Utc::now().into()),
+        updated_at: Set(Utc::now().into()),
+        ..Default::default()
+    };
+
+    let playlist = new_playlist.insert(db).await?;
+    tracing::debug!("Created external playlist: {}", spotify_playlist.name);
+    Ok(playlist)
 }
 
 /// Sync Liked Songs as a synthetic playlist
+///
+/// Returns the number of entries skipped as non-music.
 async fn sync_liked_songs(
     db: &DatabaseConnection,
+    events: &EventBus,
     spotify_service: &SpotifyService,
     access_token: &str,
-) -> Result<()> {
+    job_id: i32,
+    profile_id: i32,
+) -> Result<usize> {
     tracing::info!("Syncing Liked Songs");
 
     // Upsert the Liked Songs playlist record
-    let playlist = upsert_liked_songs_playlist(db, spotify_service, access_token).await?;
+    let playlist = upsert_liked_songs_playlist(db, spotify_service, access_token, profile_id).await?;
+    profile::link_playlist(db, profile_id, playlist.id).await?;
 
     // Only sync tracks if enabled
     if !playlist.is_enabled {
         tracing::debug!("Liked Songs is disabled, skipping track sync");
-        return Ok(());
+        return Ok(0);
     }
 
+    // The saved tracks, then at least one page of followed playlists
+    let remaining = pages(playlist.total_tracks.unwrap_or(0), SAVED_PAGE_SIZE) + 1;
+    report_progress(db, job_id, &SyncProgress::new(&spotify_service.stats(), remaining)).await?;
+
     // Fetch all saved tracks
     let spotify_tracks = spotify_service.fetch_saved_tracks(access_token).await?;
     tracing::info!("Fetched {} Liked Songs tracks", spotify_tracks.len());
@@ -192,21 +719,25 @@ async fn sync_liked_songs(
 
     if !should_sync {
         tracing::debug!("Liked Songs unchanged (hash match), skipping track sync");
-        return Ok(());
+        return Ok(0);
     }
 
-    // Sync tracks using existing function
-    sync_playlist_tracks(db, playlist.id, &spotify_tracks).await?;
+    // Sync tracks using existing function, archiving tracks that were un-liked
+    let liked_before = archive::playlist_track_ids(db, playlist.id).await?;
+    let counts = sync_playlist_tracks(db, events, playlist.id, &spotify_tracks, profile_id).await?;
+    archive::archive_unliked_tracks(db, playlist.id, &liked_before).await?;
 
     // Update snapshot and last_synced_at
     let mut active: playlists::ActiveModel = playlist.into();
+    active.unavailable_tracks = Set(Some(counts.unavailable));
+    active.episode_count = Set(Some(counts.episodes));
     active.snapshot_id = Set(Some(new_snapshot));
     active.last_synced_at = Set(Some(Utc::now().into()));
     active.updated_at = Set(Utc::now().into());
     active.update(db).await?;
 
     tracing::info!("Liked Songs sync completed");
-    Ok(())
+    Ok(counts.skipped)
 }
 
 /// Upsert the Liked Songs synthetic playlist
@@ -214,12 +745,14 @@ async fn upsert_liked_songs_playlist(
     db: &DatabaseConnection,
     spotify_service: &SpotifyService,
     access_token: &str,
+    profile_id: i32,
 ) -> Result<playlists::Model> {
     // Get current track count for metadata
     let total_tracks = spotify_service.get_saved_tracks_total(access_token).await?;
+    let spotify_id = liked_songs_spotify_id(profile_id);
 
     match playlists::Entity::find()
-        .filter(playlists::Column::SpotifyId.eq(LIKED_SONGS_SPOTIFY_ID))
+        .filter(playlists::Column::SpotifyId.eq(&spotify_id))
         .one(db)
         .await?
     {
@@ -234,7 +767,7 @@ async fn upsert_liked_songs_playlist(
             // Create new Liked Songs playlist
             let new_playlist = playlists::ActiveModel {
                 name: Set(LIKED_SONGS_NAME.to_string()),
-                spotify_id: Set(LIKED_SONGS_SPOTIFY_ID.to_string()),
+                spotify_id: Set(spotify_id),
                 description: Set(Some("Your liked songs from Spotify".to_string())),
                 owner_name: Set(None), // Liked Songs has no "owner"
                 is_collaborative: Set(false),
@@ -261,7 +794,7 @@ fn compute_tracks_hash(tracks: &[SpotifyPlaylistTrack]) -> String {
     let mut track_ids: Vec<&str> = tracks
         .iter()
         .filter_map(|t| t.track.as_ref())
-        .filter_map(|t| t.id.as_deref())
+        .filter_map(|t| t.spotify_id())
         .collect();
 
     // Sort for consistent hashing regardless of pagination order
@@ -277,17 +810,40 @@ fn compute_tracks_hash(tracks: &[SpotifyPlaylistTrack]) -> String {
 }
 
 /// Upsert an artist by Spotify ID
+///
+/// Existing artists follow a Spotify rename only while their name is still the
+/// one Spotify gave last time, so names the user renamed (which locks them) or
+/// that were changed any other way are kept. Artists synced before Spotify's
+/// name was recorded just have it recorded.
 async fn upsert_artist(db: &DatabaseConnection, spotify_artist: &SpotifyArtist) -> Result<artists::Model> {
     match artists::Entity::find()
         .filter(artists::Column::SpotifyId.eq(&spotify_artist.id))
         .one(db)
         .await?
     {
+        Some(existing) if existing.spotify_name.as_deref() != Some(spotify_artist.name.as_str()) => {
+            let follows_spotify =
+                !existing.name_locked && existing.spotify_name.as_deref() == Some(existing.name.as_str());
+            if follows_spotify {
+                tracing::debug!("Renaming artist {} to {}", existing.name, spotify_artist.name);
+            }
+            let mut active: artists::ActiveModel = existing.into();
+            if follows_spotify {
+                active.name = Set(spotify_artist.name.clone());
+                active.normalized_name = Set(Some(artist_names::normalize_name(&spotify_artist.name)));
+            }
+            active.spotify_name = Set(Some(spotify_artist.name.clone()));
+            active.updated_at = Set(Utc::now().into());
+            Ok(active.update(db).await?)
+        }
         Some(existing) => Ok(existing),
         None => {
             let new_artist = artists::ActiveModel {
                 name: Set(spotify_artist.name.clone()),
                 spotify_id: Set(Some(spotify_artist.id.clone())),
+                spotify_name: Set(Some(spotify_artist.name.clone())),
+                slug: Set(Some(slug::new_artist_slug(db, &spotify_artist.name).await?)),
+                normalized_name: Set(Some(artist_names::normalize_name(&spotify_artist.name))),
                 created_at: Set(Utc::now().into()),
                 updated_at: Set(Utc::now().into()),
                 ..Default::default()
@@ -300,6 +856,7 @@ async fn upsert_artist(db: &DatabaseConnection, spotify_artist: &SpotifyArtist)
 /// Upsert an album by Spotify ID
 async fn upsert_album(
     db: &DatabaseConnection,
+    events: &EventBus,
     spotify_album: &SpotifyAlbum,
     artist_id: i32,
     source: AlbumSource,
@@ -309,29 +866,67 @@ async fn upsert_album(
         .one(db)
         .await?
     {
-        Some(existing) => Ok(existing),
+        Some(existing) => {
+            // Track album stubs carry no barcode, so fill it in once a full album object shows up
+            let barcode = spotify_album.barcode().filter(|_| existing.barcode.is_none());
+            // Refresh genres Spotify set itself, never enriched or hand-edited ones
+            let new_genres = Some(genres::normalize(spotify_album.genres.clone().unwrap_or_default()))
+                .filter(|g| !g.is_empty() && *g != genres::album_genres(&existing))
+                .filter(|_| genres::may_replace(&existing, GenreSource::Spotify));
+            if barcode.is_none() && new_genres.is_none() {
+                return Ok(existing);
+            }
+
+            let mut active: albums::ActiveModel = existing.into();
+            if let Some(barcode) = barcode {
+                active.barcode = Set(Some(barcode.to_string()));
+            }
+            if let Some(new_genres) = new_genres {
+                active.genres = Set(genres::to_column(&new_genres));
+                active.genre_source = Set(Some(GenreSource::Spotify.into()));
+            }
+            active.updated_at = Set(Utc::now().into());
+            Ok(active.update(db).await?)
+        }
         None => {
             let cover_url = spotify_album.images.first().map(|img| img.url.clone());
+            let artist_name = spotify_album.artists.first().map(|a| a.name.as_str()).unwrap_or_default();
+            let album_slug = slug::new_album_slug(db, artist_name, &spotify_album.name).await?;
+            let release_date = parse_release_date(&spotify_album.release_date);
+            let spotify_genres = genres::normalize(spotify_album.genres.clone().unwrap_or_default());
+            if release_date.is_none() && !spotify_album.release_date.trim().is_empty() {
+                tracing::warn!(
+                    "Unparseable release date {:?} for album {} ({}); leaving it unset",
+                    spotify_album.release_date,
+                    spotify_album.name,
+                    spotify_album.id
+                );
+            }
 
             let new_album = albums::ActiveModel {
                 title: Set(spotify_album.name.clone()),
                 artist_id: Set(artist_id),
                 spotify_id: Set(Some(spotify_album.id.clone())),
-                release_date: Set(parse_release_date(&spotify_album.release_date)),
+                slug: Set(Some(album_slug)),
+                release_date: Set(release_date),
                 total_tracks: Set(Some(spotify_album.total_tracks)),
                 cover_art_url: Set(cover_url),
-                genres: Set(spotify_album.genres.as_ref().and_then(|g| serde_json::to_string(g).ok())),
+                genres: Set(genres::to_column(&spotify_genres)),
+                genre_source: Set((!spotify_genres.is_empty()).then(|| GenreSource::Spotify.into())),
                 ownership_status: Set(OwnershipStatus::NotOwned.as_str().to_string()),
                 match_status: Set(Some(MatchStatus::Pending.as_str().to_string())),
                 source: Set(source.as_str().to_string()),
+                barcode: Set(spotify_album.barcode().map(|b| b.to_string())),
                 created_at: Set(Utc::now().into()),
                 updated_at: Set(Utc::now().into()),
                 last_synced_at: Set(Some(Utc::now().into())),
+                wanted_since: Set(Some(Utc::now().into())),
                 ..Default::default()
             };
 
             let album = new_album.insert(db).await?;
             tracing::debug!("Created album: {} (source: {:?})", spotify_album.name, source);
+            events.publish(DomainEvent::AlbumImported { album_id: album.id, source });
             Ok(album)
         }
     }
@@ -386,6 +981,9 @@ async fn upsert_playlist(db: &DatabaseConnection, spotify_playlist: &SpotifyPlay
             active.is_collaborative = Set(spotify_playlist.collaborative);
             active.total_tracks = Set(Some(spotify_playlist.tracks.total));
             active.cover_image_url = Set(spotify_playlist.images.first().map(|i| i.url.clone()));
+            // Following a playlist that was imported by link makes it a regular one
+            active.is_external = Set(false);
+            active.archived_at = Set(None);
             active.updated_at = Set(Utc::now().into());
             Ok(active.update(db).await?)
         }
@@ -420,8 +1018,9 @@ async fn upsert_playlist_track(
     playlist_id: i32,
     track_id: i32,
     position: i32,
-    added_at: &Option<String>,
+    entry: &SpotifyPlaylistTrack,
 ) -> Result<playlist_tracks::Model> {
+    let is_playable = entry.track.as_ref().and_then(|track| track.is_playable);
     match playlist_tracks::Entity::find()
         .filter(playlist_tracks::Column::PlaylistId.eq(playlist_id))
         .filter(playlist_tracks::Column::TrackId.eq(track_id))
@@ -429,14 +1028,16 @@ async fn upsert_playlist_track(
         .await?
     {
         Some(existing) => {
-            // Update position if changed
+            // Update position and playability if changed
             let mut active: playlist_tracks::ActiveModel = existing.into();
             active.position = Set(position);
+            active.is_playable = Set(is_playable);
             active.updated_at = Set(Utc::now().into());
             Ok(active.update(db).await?)
         }
         None => {
-            let added_at_parsed = added_at
+            let added_at_parsed = entry
+                .added_at
                 .as_ref()
                 .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
                 .map(|dt| dt.with_timezone(&Utc).into());
@@ -446,6 +1047,7 @@ async fn upsert_playlist_track(
                 track_id: Set(track_id),
                 position: Set(position),
                 added_at: Set(added_at_parsed),
+                is_playable: Set(is_playable),
                 created_at: Set(Utc::now().into()),
                 updated_at: Set(Utc::now().into()),
                 ..Default::default()
@@ -485,9 +1087,13 @@ async fn cleanup_removed_tracks(
 }
 
 /// Parse release date in various formats (YYYY, YYYY-MM, YYYY-MM-DD)
+///
+/// `/` and `.` are accepted in place of `-`, so `2001/03/12` and `2001.03.12`
+/// parse too.
 fn parse_release_date(date_str: &str) -> Option<chrono::NaiveDate> {
+    let date_str = date_str.trim().replace(['/', '.'], "-");
     // Try full date first
-    if let Ok(date) = chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
         return Some(date);
     }
     // Try year-month
@@ -500,3 +1106,137 @@ fn parse_release_date(date_str: &str) -> Option<chrono::NaiveDate> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const CURSOR: &str = "https://api.spotify.com/v1/me/albums?offset=100&limit=50";
+
+    const PROFILE_ID: i32 = 1;
+
+    fn spotify() -> SpotifyService {
+        SpotifyService::from_config(&test_config())
+    }
+
+    async fn create_sync_job(db: &DatabaseConnection, status: JobStatus, cursor: Option<&str>) -> jobs::Model {
+        let job = create_test_job(db, JobType::SpotifySync, status).await;
+        save_sync_cursor(db, job.id, cursor.map(|c| c.to_string())).await.unwrap();
+
+        let mut active: jobs::ActiveModel = job.into();
+        active.entity_id = Set(Some(PROFILE_ID));
+        active.update(db).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resume_cursor_uses_own_cursor() {
+        let db = setup_test_db().await;
+        let job = create_sync_job(&db, JobStatus::Running, Some(CURSOR)).await;
+
+        assert_eq!(resume_cursor(&db, &spotify(), job.id, PROFILE_ID).await.unwrap().as_deref(), Some(CURSOR));
+    }
+
+    #[tokio::test]
+    async fn test_resume_cursor_inherits_from_failed_sync() {
+        let db = setup_test_db().await;
+        create_sync_job(&db, JobStatus::Failed, Some(CURSOR)).await;
+        let job = create_sync_job(&db, JobStatus::Running, None).await;
+
+        assert_eq!(resume_cursor(&db, &spotify(), job.id, PROFILE_ID).await.unwrap().as_deref(), Some(CURSOR));
+    }
+
+    #[tokio::test]
+    async fn test_resume_cursor_starts_fresh_after_completed_sync() {
+        let db = setup_test_db().await;
+        create_sync_job(&db, JobStatus::Failed, Some(CURSOR)).await;
+        create_sync_job(&db, JobStatus::Completed, None).await;
+        let job = create_sync_job(&db, JobStatus::Running, None).await;
+
+        assert_eq!(resume_cursor(&db, &spotify(), job.id, PROFILE_ID).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_resume_cursor_ignores_other_profiles() {
+        let db = setup_test_db().await;
+        create_sync_job(&db, JobStatus::Failed, Some(CURSOR)).await;
+        let job = create_sync_job(&db, JobStatus::Running, None).await;
+
+        assert_eq!(resume_cursor(&db, &spotify(), job.id, PROFILE_ID + 1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_resume_cursor_ignores_foreign_urls() {
+        let db = setup_test_db().await;
+        let job = create_sync_job(&db, JobStatus::Running, Some("https://example.com/me/albums")).await;
+
+        assert_eq!(resume_cursor(&db, &spotify(), job.id, PROFILE_ID).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_save_sync_cursor_clears_cursor() {
+        let db = setup_test_db().await;
+        let job = create_sync_job(&db, JobStatus::Running, Some(CURSOR)).await;
+
+        save_sync_cursor(&db, job.id, None).await.unwrap();
+
+        let job = jobs::Entity::find_by_id(job.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(job.sync_cursor, None);
+    }
+
+    fn saved_page(next: Option<&str>, total: i32) -> SavedAlbumsPage {
+        SavedAlbumsPage { albums: Vec::new(), next: next.map(|n| n.to_string()), total }
+    }
+
+    #[test]
+    fn test_remaining_saved_pages() {
+        assert_eq!(remaining_saved_pages(&saved_page(Some(CURSOR), 420)), 7);
+        assert_eq!(remaining_saved_pages(&saved_page(Some(CURSOR), 101)), 1);
+        assert_eq!(remaining_saved_pages(&saved_page(None, 420)), 0);
+        // A total that shrank mid-sync still leaves the next page to fetch
+        assert_eq!(remaining_saved_pages(&saved_page(Some(CURSOR), 80)), 1);
+    }
+
+    #[test]
+    fn test_sync_progress_summary() {
+        let progress = SyncProgress {
+            api_calls: 412,
+            rate_limit_hits: 0,
+            requests_per_second: 1.5,
+            remaining_requests: 630,
+            eta_seconds: Some(420),
+        };
+        assert_eq!(progress.summary(true), "~7 minutes remaining, 412 API calls made, 0 rate-limit hits");
+        assert_eq!(progress.summary(false), "412 API calls made, 0 rate-limit hits");
+
+        let progress = SyncProgress { api_calls: 1, rate_limit_hits: 1, eta_seconds: Some(20), ..progress };
+        assert_eq!(progress.summary(true), "under a minute remaining, 1 API call made, 1 rate-limit hit");
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> Option<chrono::NaiveDate> {
+        chrono::NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    #[test]
+    fn test_parse_release_date_spotify_precisions() {
+        assert_eq!(parse_release_date("2001-03-12"), date(2001, 3, 12));
+        assert_eq!(parse_release_date("2001-03"), date(2001, 3, 1));
+        assert_eq!(parse_release_date("2001"), date(2001, 1, 1));
+    }
+
+    #[test]
+    fn test_parse_release_date_other_separators() {
+        assert_eq!(parse_release_date("2001/03/12"), date(2001, 3, 12));
+        assert_eq!(parse_release_date("2001.03.12"), date(2001, 3, 12));
+        assert_eq!(parse_release_date("2001/03"), date(2001, 3, 1));
+        assert_eq!(parse_release_date(" 2001.03 "), date(2001, 3, 1));
+    }
+
+    #[test]
+    fn test_parse_release_date_rejects_garbage() {
+        assert_eq!(parse_release_date(""), None);
+        assert_eq!(parse_release_date("0000-00-00"), None);
+        assert_eq!(parse_release_date("2001-13-01"), None);
+        assert_eq!(parse_release_date("March 2001"), None);
+    }
+}
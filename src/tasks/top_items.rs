@@ -0,0 +1,136 @@
+//! Cache of each profile's Spotify top artists and tracks ("Heavy rotation")
+//!
+//! Spotify's personalization endpoints change slowly, so results are cached in
+//! `top_items` and refreshed weekly by the scheduler, or on demand when the
+//! stats page finds the active profile's last fetch missing or stale.
+
+use chrono::{Duration, Utc};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, sea_query::Expr, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+
+use crate::{
+    db::{
+        entities::{profiles, top_items},
+        enums::TopItemKind,
+    },
+    error::{AppError, Result},
+    services::SpotifyService,
+    state::AppState,
+};
+
+/// How long cached top items are considered fresh
+pub const TOP_ITEMS_MAX_AGE: Duration = Duration::days(7);
+
+/// Cached top items for a profile, artists first, each in rank order
+pub async fn cached_top_items(db: &DatabaseConnection, profile_id: i32) -> Result<Vec<top_items::Model>> {
+    Ok(top_items::Entity::find()
+        .filter(top_items::Column::ProfileId.eq(profile_id))
+        .order_by_asc(top_items::Column::Kind)
+        .order_by_asc(top_items::Column::Rank)
+        .all(db)
+        .await?)
+}
+
+/// Whether the profile's top items were never fetched or were fetched more
+/// than [`TOP_ITEMS_MAX_AGE`] ago
+///
+/// Goes by the profile's last fetch rather than the cached rows, so a profile
+/// Spotify has no top items for isn't refetched on every visit.
+pub fn is_stale(profile: &profiles::Model) -> bool {
+    profile
+        .top_items_fetched_at
+        .map(|fetched_at| Utc::now() - fetched_at.to_utc() > TOP_ITEMS_MAX_AGE)
+        .unwrap_or(true)
+}
+
+/// Fetch a profile's top artists and tracks from Spotify and replace its cache
+///
+/// Returns the number of items stored. Fails with [`AppError::InsufficientScope`]
+/// when the profile's token was granted without `user-top-read`.
+pub async fn refresh_top_items(state: &AppState, profile: profiles::Model) -> Result<usize> {
+    let spotify_service = SpotifyService::from_config(&state.config);
+    let profile_id = profile.id;
+    let access_token = spotify_service.profile_access_token(&state.db, profile).await?;
+
+    let artists = spotify_service.fetch_top_artists(&access_token).await?;
+    let tracks = spotify_service.fetch_top_tracks(&access_token).await?;
+    let fetched_at = Utc::now();
+
+    let mut rows = Vec::with_capacity(artists.len() + tracks.len());
+    for (rank, artist) in artists.into_iter().enumerate() {
+        rows.push(top_items::ActiveModel {
+            profile_id: Set(profile_id),
+            kind: Set(TopItemKind::Artist.as_str().to_string()),
+            rank: Set(rank as i32 + 1),
+            spotify_id: Set(artist.id),
+            name: Set(artist.name),
+            image_url: Set(artist.images.first().map(|img| img.url.clone())),
+            fetched_at: Set(fetched_at.into()),
+            ..Default::default()
+        });
+    }
+    for (rank, track) in tracks.into_iter().enumerate() {
+        // Local files have no Spotify ID and can't be matched against the library
+        let Some(track_id) = track.id else {
+            continue;
+        };
+        rows.push(top_items::ActiveModel {
+            profile_id: Set(profile_id),
+            kind: Set(TopItemKind::Track.as_str().to_string()),
+            rank: Set(rank as i32 + 1),
+            spotify_id: Set(track_id),
+            name: Set(track.name),
+            artist_name: Set(track.artists.first().map(|a| a.name.clone())),
+            album_spotify_id: Set(Some(track.album.id.clone())),
+            album_name: Set(Some(track.album.name.clone())),
+            image_url: Set(track.album.images.first().map(|img| img.url.clone())),
+            fetched_at: Set(fetched_at.into()),
+            ..Default::default()
+        });
+    }
+
+    let stored = rows.len();
+    let txn = state.db.begin().await?;
+    top_items::Entity::delete_many()
+        .filter(top_items::Column::ProfileId.eq(profile_id))
+        .exec(&txn)
+        .await?;
+    if !rows.is_empty() {
+        top_items::Entity::insert_many(rows).exec(&txn).await?;
+    }
+    profiles::Entity::update_many()
+        .col_expr(profiles::Column::TopItemsFetchedAt, Expr::value(DateTimeWithTimeZone::from(fetched_at)))
+        .filter(profiles::Column::Id.eq(profile_id))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+
+    tracing::info!("Cached {} top items for profile {}", stored, profile_id);
+    Ok(stored)
+}
+
+/// Scheduled entry point: refresh the top items of every connected profile
+///
+/// Profiles whose token predates the `user-top-read` scope are skipped with a
+/// warning; the stats page prompts them to reconnect.
+pub async fn refresh_all_top_items(state: AppState) -> anyhow::Result<()> {
+    let connected = profiles::Entity::find()
+        .filter(profiles::Column::SpotifyAccessToken.is_not_null())
+        .all(&state.db)
+        .await?;
+
+    for profile in connected {
+        let name = profile.name.clone();
+        match refresh_top_items(&state, profile).await {
+            Ok(_) => {}
+            Err(AppError::InsufficientScope(_)) => {
+                tracing::warn!("Profile {} needs to reconnect Spotify to load top items", name);
+            }
+            Err(e) => tracing::warn!("Failed to refresh top items for profile {}: {}", name, e),
+        }
+    }
+
+    Ok(())
+}
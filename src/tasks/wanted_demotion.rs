@@ -0,0 +1,34 @@
+//! Daily demotion of stale wants
+//!
+//! Each profile with the demotion rule switched on gets its wants older than
+//! its threshold lowered one priority, or the want cleared at the lowest
+//! one. See `services::wanted` for which albums qualify.
+
+use chrono::Utc;
+use sea_orm::EntityTrait;
+
+use crate::{
+    db::entities::profiles,
+    error::Result,
+    services::{album_grid_cache, wanted},
+    state::AppState,
+};
+
+/// Run the demotion rule for every profile that has it on
+///
+/// Returns how many albums were demoted or had their want cleared.
+pub async fn demote_stale_wants(state: &AppState) -> Result<usize> {
+    let now = Utc::now();
+    let mut demoted = 0;
+    for profile in profiles::Entity::find().all(&state.db).await? {
+        let Some(days) = wanted::demotion_days(&state.db, &profile).await? else {
+            continue;
+        };
+        demoted += wanted::demote_stale_wants(&state.db, profile.id, days, now).await?.len();
+    }
+
+    if demoted > 0 {
+        album_grid_cache::invalidate(&state.redis).await;
+    }
+    Ok(demoted)
+}
@@ -0,0 +1,29 @@
+//! Daily cleanup of recorded Lidarr webhook payloads
+//!
+//! The webhook records each payload's hash in `lidarr_webhook_events` to
+//! ignore Lidarr's retries. Retries stop within hours, so rows older than
+//! [`WEBHOOK_EVENT_RETENTION`] only grow the table.
+
+use chrono::{Duration, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use crate::{db::entities::lidarr_webhook_events, error::Result, state::AppState};
+
+/// How long a payload is remembered for replay detection
+pub const WEBHOOK_EVENT_RETENTION: Duration = Duration::days(30);
+
+/// Delete payloads received more than [`WEBHOOK_EVENT_RETENTION`] ago
+///
+/// Returns how many rows were removed.
+pub async fn prune_webhook_events(state: &AppState) -> Result<u64> {
+    let cutoff = Utc::now() - WEBHOOK_EVENT_RETENTION;
+    let deleted = lidarr_webhook_events::Entity::delete_many()
+        .filter(lidarr_webhook_events::Column::ReceivedAt.lt(cutoff))
+        .exec(&state.db)
+        .await?;
+
+    if deleted.rows_affected > 0 {
+        tracing::info!("Pruned {} old Lidarr webhook events", deleted.rows_affected);
+    }
+    Ok(deleted.rows_affected)
+}
@@ -1,11 +1,71 @@
+use chrono_tz::Tz;
 use maud::{html, Markup};
 
-use crate::db::enums::OwnershipStatus;
+use super::time::timestamp;
+use super::placeholder::cover_or_placeholder;
+use crate::db::entities::{albums, artists, playlists};
+use crate::db::enums::{AlbumView, OwnershipStatus};
+use crate::db::slug;
+use crate::services::suggestions::AlbumSuggestion;
+use crate::services::wanted::WantedAge;
+use crate::t;
+
+/// Share of `total` that is owned, as a percentage (0 when there's nothing to own)
+pub fn ownership_percentage(owned: i64, total: i64) -> f64 {
+    if total > 0 {
+        (owned as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Color tier for an ownership percentage: ≥80% green, ≥50% yellow, else gray
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnershipColor {
+    Green,
+    Yellow,
+    Gray,
+}
+
+pub fn ownership_color(pct: f64) -> OwnershipColor {
+    if pct >= 80.0 {
+        OwnershipColor::Green
+    } else if pct >= 50.0 {
+        OwnershipColor::Yellow
+    } else {
+        OwnershipColor::Gray
+    }
+}
+
+/// Tailwind classes for each color tier, in green/yellow/gray order
+pub type OwnershipPalette = [&'static str; 3];
+
+/// Percentage badge on playlist cards
+pub const BADGE_PALETTE: OwnershipPalette = ["bg-green-500", "bg-yellow-500", "bg-gray-500"];
+/// Fill of ownership progress bars
+pub const PROGRESS_PALETTE: OwnershipPalette = ["bg-green-500", "bg-yellow-500", "bg-gray-400"];
+/// "N% complete" labels on artist cards and pages
+pub const LABEL_PALETTE: OwnershipPalette = ["text-green-600", "text-yellow-600", "text-gray-500"];
+/// Ownership figure in the playlist stats bar
+pub const STAT_PALETTE: OwnershipPalette = ["text-green-600", "text-yellow-600", "text-gray-600"];
+
+impl OwnershipColor {
+    pub fn class(self, palette: &OwnershipPalette) -> &'static str {
+        match self {
+            OwnershipColor::Green => palette[0],
+            OwnershipColor::Yellow => palette[1],
+            OwnershipColor::Gray => palette[2],
+        }
+    }
+}
 
 pub struct AlbumCardData {
     pub id: i32,
+    /// Path segment for the album's detail link (slug, or ID if it has none)
+    pub slug: String,
     pub title: String,
     pub artist_id: i32,
+    pub artist_slug: String,
     pub artist_name: String,
     pub cover_art_url: Option<String>,
     pub release_date: Option<String>,
@@ -13,35 +73,65 @@ pub struct AlbumCardData {
     pub match_score: Option<i32>,
 }
 
+impl AlbumCardData {
+    /// Card data for an album row and its artist
+    pub fn from_models(album: albums::Model, artist: &artists::Model) -> Self {
+        Self {
+            id: album.id,
+            slug: slug::link_key(album.slug.as_deref(), album.id),
+            title: album.title,
+            artist_id: artist.id,
+            artist_slug: slug::link_key(artist.slug.as_deref(), artist.id),
+            artist_name: artist.name.clone(),
+            cover_art_url: album.cover_art_url,
+            release_date: album.release_date.map(|d| d.to_string()),
+            ownership_status: OwnershipStatus::from_str(&album.ownership_status)
+                .unwrap_or(OwnershipStatus::NotOwned),
+            match_score: album.match_score,
+        }
+    }
+}
+
 pub fn album_card(album: &AlbumCardData) -> Markup {
+    album_card_inner(album, false)
+}
+
+/// Album card with the out-of-band swap attribute, sent back by quick actions
+pub fn album_card_oob(album: &AlbumCardData) -> Markup {
+    album_card_inner(album, true)
+}
+
+fn album_card_inner(album: &AlbumCardData, oob: bool) -> Markup {
     let status_class = match album.ownership_status {
         OwnershipStatus::Owned => "owned",
         OwnershipStatus::NotOwned => "not-owned",
         OwnershipStatus::Downloading => "downloading",
+        OwnershipStatus::Ordered => "ordered",
     };
 
-    let cover_url = album
-        .cover_art_url
-        .as_deref()
-        .unwrap_or("https://via.placeholder.com/300x300/1a1a1a/ffffff?text=No+Cover");
+    let cover_url = cover_or_placeholder(album.cover_art_url.as_deref(), &album.title);
 
     html! {
         div
-            class=(format!("album-card {} bg-white rounded-lg shadow-md overflow-hidden cursor-pointer", status_class))
-            hx-get={(format!("/albums/{}", album.id))}
+            id={(format!("album-card-{}", album.id))}
+            class=(format!("album-card {} group bg-white rounded-lg shadow-md overflow-hidden cursor-pointer", status_class))
+            hx-get={(format!("/albums/{}", album.slug))}
             hx-target="#album-detail-modal"
-            hx-swap="innerHTML" {
+            hx-swap="innerHTML"
+            hx-swap-oob=[if oob { Some("true") } else { None }] {
 
             // Album cover
             div class="relative aspect-square" {
                 img
                     src=(cover_url)
-                    alt={(format!("{} by {}", album.title, album.artist_name))}
+                    alt=(t!("card.cover_alt", title = album.title, artist = album.artist_name))
                     class="w-full h-full object-cover"
                     loading="lazy";
 
                 // Status badge
                 (status_badge(&album.ownership_status))
+
+                (album_quick_actions(album))
             }
 
             // Album info
@@ -50,7 +140,7 @@ pub fn album_card(album: &AlbumCardData) -> Markup {
                     (album.title)
                 }
                 a
-                    href={(format!("/artists/{}", album.artist_id))}
+                    href={(format!("/artists/{}", album.artist_slug))}
                     class="text-sm text-gray-600 truncate block hover:text-primary hover:underline"
                     title=(album.artist_name)
                     onclick="event.stopPropagation()" {
@@ -74,11 +164,130 @@ pub fn album_card(album: &AlbumCardData) -> Markup {
     }
 }
 
+/// Buttons over an album cover for triage without opening the modal
+///
+/// Shown on hover, whenever a button has keyboard focus, and always on touch
+/// screens, which have no hover. Each one answers with the updated card out of
+/// band, so the badge changes in place. Only moves the ownership rules allow
+/// are offered.
+fn album_quick_actions(album: &AlbumCardData) -> Markup {
+    let status = album.ownership_status;
+    let can_mark_owned = status != OwnershipStatus::Owned
+        && status.can_transition_to(OwnershipStatus::Owned, false);
+    let can_search = status != OwnershipStatus::Downloading
+        && status.can_transition_to(OwnershipStatus::Downloading, false);
+    let button_class = "w-8 h-8 flex items-center justify-center rounded-full bg-white/90 text-gray-800 shadow hover:bg-white focus:outline-none focus:ring-2 focus:ring-primary";
+
+    html! {
+        @if can_mark_owned || can_search {
+            div class="album-quick-actions absolute bottom-2 right-2 flex gap-1 opacity-0 transition-opacity group-hover:opacity-100 focus-within:opacity-100 [@media(hover:none)]:opacity-100" {
+                @if can_mark_owned {
+                    button
+                        type="button"
+                        class=(button_class)
+                        title=(t!("card.mark_owned"))
+                        aria-label=(t!("card.mark_owned_label", title = album.title))
+                        hx-patch={(format!("/api/albums/{}?card=true", album.id))}
+                        hx-vals=r#"{"ownership_status": "owned"}"#
+                        hx-swap="none"
+                        onclick="event.stopPropagation()" {
+                        "✓"
+                    }
+                }
+                @if can_search {
+                    button
+                        type="button"
+                        class=(button_class)
+                        title=(t!("card.search_lidarr"))
+                        aria-label=(t!("card.search_lidarr_label", title = album.title))
+                        hx-post={(format!("/api/albums/{}/search-lidarr?card=true", album.id))}
+                        hx-target="#notification-area"
+                        hx-swap="innerHTML"
+                        onclick="event.stopPropagation()" {
+                        "⤓"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Compact list row for an album (thumbnail, title, artist, status dot)
+pub fn album_list_row(album: &AlbumCardData) -> Markup {
+    html! {
+        div
+            class="album-row flex items-center gap-3 px-3 py-2 cursor-pointer hover:bg-gray-50"
+            hx-get={(format!("/albums/{}", album.slug))}
+            hx-target="#album-detail-modal"
+            hx-swap="innerHTML" {
+            @if let Some(url) = &album.cover_art_url {
+                img src=(url) alt="" class="w-10 h-10 rounded object-cover flex-shrink-0" loading="lazy";
+            } @else {
+                div class="w-10 h-10 rounded bg-gray-200 flex-shrink-0" {}
+            }
+            div class="min-w-0 flex-grow" {
+                p class="text-sm font-medium text-gray-900 truncate" { (album.title) }
+                p class="text-xs text-gray-500 truncate" { (album.artist_name) }
+            }
+            (status_dot(&album.ownership_status))
+        }
+    }
+}
+
+/// "You might also want" strip for the bottom of an owned album's modal; empty without suggestions
+pub fn album_suggestions_strip(suggestions: &[AlbumSuggestion]) -> Markup {
+    html! {
+        @if !suggestions.is_empty() {
+            div class="mt-6 pt-6 border-t" {
+                h3 class="text-sm font-semibold text-gray-700 mb-3" { (t!("album.suggestions")) }
+                div class="grid grid-cols-2 sm:grid-cols-5 gap-3" {
+                    @for suggestion in suggestions {
+                        div
+                            class="cursor-pointer min-w-0"
+                            hx-get={(format!("/albums/{}", suggestion.slug))}
+                            hx-target="#album-detail-modal"
+                            hx-swap="innerHTML" {
+                            img
+                                src=(cover_or_placeholder(suggestion.cover_art_url.as_deref(), &suggestion.title))
+                                alt=""
+                                class="w-full aspect-square rounded object-cover"
+                                loading="lazy";
+                            p class="mt-1 text-xs font-medium text-gray-900 truncate" title=(suggestion.title) {
+                                (suggestion.title)
+                            }
+                            p class="text-xs text-gray-500 truncate" { (suggestion.artist_name) }
+                            @if suggestion.playlist_appearances > 0 {
+                                p class="text-xs text-gray-400" {
+                                    (t!("album.suggestion_playlist_tracks", count = suggestion.playlist_appearances))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn status_dot(status: &OwnershipStatus) -> Markup {
+    let (title, color) = match status {
+        OwnershipStatus::Owned => (t!("status.owned"), "bg-green-500"),
+        OwnershipStatus::NotOwned => (t!("status.not_owned"), "bg-gray-400"),
+        OwnershipStatus::Downloading => (t!("status.downloading"), "bg-blue-500"),
+        OwnershipStatus::Ordered => (t!("status.ordered"), "bg-purple-500"),
+    };
+
+    html! {
+        span class={(format!("w-3 h-3 rounded-full flex-shrink-0 {}", color))} title=(title) {}
+    }
+}
+
 fn status_badge(status: &OwnershipStatus) -> Markup {
     let (text, color) = match status {
-        OwnershipStatus::Owned => ("Owned", "bg-green-500"),
-        OwnershipStatus::NotOwned => ("Not Owned", "bg-gray-500"),
-        OwnershipStatus::Downloading => ("Downloading", "bg-blue-500"),
+        OwnershipStatus::Owned => (t!("status.owned"), "bg-green-500"),
+        OwnershipStatus::NotOwned => (t!("status.not_owned"), "bg-gray-500"),
+        OwnershipStatus::Downloading => (t!("status.downloading"), "bg-blue-500"),
+        OwnershipStatus::Ordered => (t!("status.ordered"), "bg-purple-500"),
     };
 
     html! {
@@ -92,11 +301,11 @@ fn status_badge(status: &OwnershipStatus) -> Markup {
 
 fn match_score_indicator(score: i32) -> Markup {
     let (color, text) = if score >= 90 {
-        ("text-green-600", "Excellent match")
+        ("text-green-600", t!("card.match_excellent"))
     } else if score >= 80 {
-        ("text-yellow-600", "Good match")
+        ("text-yellow-600", t!("card.match_good"))
     } else {
-        ("text-red-600", "Poor match")
+        ("text-red-600", t!("card.match_poor"))
     };
 
     html! {
@@ -111,30 +320,67 @@ fn match_score_indicator(score: i32) -> Markup {
     }
 }
 
-pub fn filter_bar() -> Markup {
+/// Albums per page when the request doesn't say
+pub const DEFAULT_ALBUM_PAGE_SIZE: u64 = 50;
+
+/// Page sizes offered by the album filter bar
+pub const ALBUM_PAGE_SIZES: [u64; 3] = [20, DEFAULT_ALBUM_PAGE_SIZE, 100];
+
+/// Points a wanted-albums export link at the grid's current search and search fields
+const WANTED_EXPORT_ONCLICK: &str = "var params = new URLSearchParams({ format: this.dataset.format }); \
+     document.querySelectorAll('[name=search], [name=search_in]').forEach(function (input) { \
+     if (input.value) params.append(input.name, input.value); }); \
+     this.href = '/api/albums/export-wanted?' + params;";
+
+/// Puts the ticked "search ... too" boxes into `search_in` and reloads the grid
+const SEARCH_FIELDS_ONCHANGE: &str = "var fields = ['title']; \
+     document.querySelectorAll('[data-search-field]:checked').forEach(function (box) { \
+     fields.push(box.dataset.searchField); }); \
+     var input = document.querySelector('[name=search_in]'); \
+     input.value = fields.join(','); htmx.trigger(input, 'change');";
+
+pub fn filter_bar(view: AlbumView) -> Markup {
     html! {
         div class="bg-white rounded-lg shadow-sm p-4 mb-6" {
-            div class="grid grid-cols-1 md:grid-cols-6 gap-4" {
+            div class="grid grid-cols-1 md:grid-cols-8 gap-4" {
                 // Search
                 div {
                     label class="block text-sm font-medium text-gray-700 mb-2" {
-                        "Search Albums"
+                        (t!("filter.search"))
                     }
                     input
                         type="text"
                         name="search"
-                        placeholder="Search by title..."
+                        placeholder=(t!("filter.search_placeholder"))
                         class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-primary"
                         hx-get="/albums"
                         hx-trigger="keyup changed delay:500ms"
                         hx-target="#album-grid"
-                        hx-include="[name='ownership_status'], [name='match_status'], [name='sort_by'], [name='sort_order']";
+                        hx-include="[name='search_in'], [name='ownership_status'], [name='match_status'], [name='sort_by'], [name='sort_order'], [name='view'], [name='page_size']";
+                    input
+                        type="hidden"
+                        name="search_in"
+                        value="title"
+                        hx-get="/albums"
+                        hx-trigger="change"
+                        hx-target="#album-grid"
+                        hx-include="[name='search'], [name='ownership_status'], [name='match_status'], [name='sort_by'], [name='sort_order'], [name='view'], [name='page_size']";
+                    @for (field, label) in [("artist", "filter.search_artists"), ("notes", "filter.search_notes")] {
+                        label class="flex items-center gap-2 mt-2 text-sm text-gray-600" {
+                            input
+                                type="checkbox"
+                                data-search-field=(field)
+                                class="rounded border-gray-300 text-primary focus:ring-primary"
+                                onchange=(SEARCH_FIELDS_ONCHANGE);
+                            (t!(label))
+                        }
+                    }
                 }
 
                 // Ownership filter
                 div {
                     label class="block text-sm font-medium text-gray-700 mb-2" {
-                        "Ownership Status"
+                        (t!("filter.ownership"))
                     }
                     select
                         name="ownership_status"
@@ -142,18 +388,19 @@ pub fn filter_bar() -> Markup {
                         hx-get="/albums"
                         hx-trigger="change"
                         hx-target="#album-grid"
-                        hx-include="[name='search'], [name='match_status'], [name='sort_by'], [name='sort_order']" {
-                        option value="" { "All" }
-                        option value="owned" { "Owned" }
-                        option value="not_owned" { "Not Owned" }
-                        option value="downloading" { "Downloading" }
+                        hx-include="[name='search'], [name='search_in'], [name='match_status'], [name='sort_by'], [name='sort_order'], [name='view'], [name='page_size']" {
+                        option value="" { (t!("common.all")) }
+                        option value="owned" { (t!("status.owned")) }
+                        option value="not_owned" { (t!("status.not_owned")) }
+                        option value="downloading" { (t!("status.downloading")) }
+                        option value="ordered" { (t!("status.ordered")) }
                     }
                 }
 
                 // Match status filter
                 div {
                     label class="block text-sm font-medium text-gray-700 mb-2" {
-                        "Match Status"
+                        (t!("filter.match"))
                     }
                     select
                         name="match_status"
@@ -161,19 +408,19 @@ pub fn filter_bar() -> Markup {
                         hx-get="/albums"
                         hx-trigger="change"
                         hx-target="#album-grid"
-                        hx-include="[name='search'], [name='ownership_status'], [name='sort_by'], [name='sort_order']" {
-                        option value="" { "All" }
-                        option value="matched" { "Matched" }
-                        option value="pending" { "Pending" }
-                        option value="manual_review" { "Needs Review" }
-                        option value="no_match" { "No Match" }
+                        hx-include="[name='search'], [name='search_in'], [name='ownership_status'], [name='sort_by'], [name='sort_order'], [name='view'], [name='page_size']" {
+                        option value="" { (t!("common.all")) }
+                        option value="matched" { (t!("match.matched")) }
+                        option value="pending" { (t!("match.pending")) }
+                        option value="manual_review" { (t!("match.manual_review")) }
+                        option value="no_match" { (t!("match.no_match")) }
                     }
                 }
 
                 // Sort by
                 div {
                     label class="block text-sm font-medium text-gray-700 mb-2" {
-                        "Sort By"
+                        (t!("filter.sort_by"))
                     }
                     select
                         name="sort_by"
@@ -181,18 +428,20 @@ pub fn filter_bar() -> Markup {
                         hx-get="/albums"
                         hx-trigger="change"
                         hx-target="#album-grid"
-                        hx-include="[name='search'], [name='ownership_status'], [name='match_status'], [name='sort_order']" {
-                        option value="created_at" { "Date Added" }
-                        option value="title" { "Title" }
-                        option value="artist" { "Artist" }
-                        option value="release_date" { "Release Date" }
+                        hx-include="[name='search'], [name='search_in'], [name='ownership_status'], [name='match_status'], [name='sort_order'], [name='view'], [name='page_size']" {
+                        option value="created_at" { (t!("filter.sort.created_at")) }
+                        option value="title" { (t!("filter.sort.title")) }
+                        option value="artist" { (t!("filter.sort.artist")) }
+                        option value="release_date" { (t!("filter.sort.release_date")) }
+                        option value="last_listened" { (t!("filter.sort.last_listened")) }
+                        option value="listen_count" { (t!("filter.sort.listen_count")) }
                     }
                 }
 
                 // Sort order
                 div {
                     label class="block text-sm font-medium text-gray-700 mb-2" {
-                        "Order"
+                        (t!("filter.order"))
                     }
                     select
                         name="sort_order"
@@ -200,20 +449,68 @@ pub fn filter_bar() -> Markup {
                         hx-get="/albums"
                         hx-trigger="change"
                         hx-target="#album-grid"
-                        hx-include="[name='search'], [name='ownership_status'], [name='match_status'], [name='sort_by']" {
-                        option value="desc" { "Descending" }
-                        option value="asc" { "Ascending" }
+                        hx-include="[name='search'], [name='search_in'], [name='ownership_status'], [name='match_status'], [name='sort_by'], [name='view'], [name='page_size']" {
+                        option value="desc" { (t!("filter.order.desc")) }
+                        option value="asc" { (t!("filter.order.asc")) }
+                    }
+                }
+
+                // View toggle
+                div {
+                    label class="block text-sm font-medium text-gray-700 mb-2" {
+                        (t!("filter.view"))
+                    }
+                    select
+                        name="view"
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-primary"
+                        hx-get="/albums"
+                        hx-trigger="change"
+                        hx-target="#album-grid"
+                        hx-include="[name='search'], [name='search_in'], [name='ownership_status'], [name='match_status'], [name='sort_by'], [name='sort_order'], [name='page_size']" {
+                        option value="grid" selected[view == AlbumView::Grid] { (t!("filter.view.grid")) }
+                        option value="list" selected[view == AlbumView::List] { (t!("filter.view.list")) }
+                    }
+                }
+
+                // Page size
+                div {
+                    label class="block text-sm font-medium text-gray-700 mb-2" {
+                        (t!("filter.page_size"))
+                    }
+                    select
+                        name="page_size"
+                        class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-primary"
+                        hx-get="/albums"
+                        hx-trigger="change"
+                        hx-target="#album-grid"
+                        hx-include="[name='search'], [name='search_in'], [name='ownership_status'], [name='match_status'], [name='sort_by'], [name='sort_order'], [name='view']" {
+                        @for size in ALBUM_PAGE_SIZES {
+                            option value=(size) selected[size == DEFAULT_ALBUM_PAGE_SIZE] { (size) }
+                        }
                     }
                 }
 
                 // Actions
-                div class="flex items-end" {
+                div class="flex flex-col justify-end gap-1" {
                     button
                         class="w-full bg-primary hover:bg-green-600 text-white font-semibold py-2 px-4 rounded-md transition"
                         hx-post="/api/jobs/spotify-sync"
                         hx-target="#notification-area"
                         hx-swap="innerHTML" {
-                        "Sync Spotify"
+                        (t!("filter.sync_spotify"))
+                    }
+                    // Plain downloads; the current search and search fields are carried over when clicked
+                    div class="flex justify-center gap-2 text-xs text-gray-600" {
+                        span { (t!("filter.export_wanted")) }
+                        @for (format, label) in [("mbid", "MBIDs"), ("json", "JSON")] {
+                            a
+                                href={ "/api/albums/export-wanted?format=" (format) }
+                                data-format=(format)
+                                class="text-primary hover:underline"
+                                onclick=(WANTED_EXPORT_ONCLICK) {
+                                (label)
+                            }
+                        }
                     }
                 }
             }
@@ -221,65 +518,142 @@ pub fn filter_bar() -> Markup {
     }
 }
 
-pub fn pagination(page: u64, total_pages: u64, base_url: &str) -> Markup {
-    // Common hx-include for all filter/sort params
-    let hx_include = "[name='search'], [name='ownership_status'], [name='match_status'], [name='sort_by'], [name='sort_order']";
+/// Position of a paginated grid within its full result set
+#[derive(Debug, Clone, Copy)]
+pub struct PageInfo {
+    pub page: u64,
+    pub page_size: u64,
+    pub total_items: u64,
+}
 
-    html! {
-        div class="flex justify-center items-center space-x-2 mt-8" {
-            // Previous button
-            @if page > 1 {
-                button
-                    class="px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
-                    hx-get={(format!("{}?page={}", base_url, page - 1))}
-                    hx-target="#album-grid"
-                    hx-swap="innerHTML"
-                    hx-include=(hx_include) {
-                    "Previous"
-                }
-            } @else {
-                button class="px-4 py-2 bg-gray-100 border border-gray-300 rounded-md text-gray-400 cursor-not-allowed" disabled {
-                    "Previous"
-                }
+impl PageInfo {
+    pub fn total_pages(&self) -> u64 {
+        self.total_items.div_ceil(self.page_size.max(1))
+    }
+
+    /// "Showing 51–100 of 432 albums"
+    pub fn summary(&self, noun: &str) -> String {
+        if self.total_items == 0 {
+            return format!("No {}", noun);
+        }
+        let first = ((self.page - 1) * self.page_size + 1).min(self.total_items);
+        let last = (self.page * self.page_size).min(self.total_items);
+        format!("Showing {}–{} of {} {}", first, last, self.total_items, noun)
+    }
+}
+
+/// Where a grid's pagination controls load pages from and swap them into
+pub struct PaginationTarget<'a> {
+    pub base_url: &'a str,
+    /// Selector of the element the page is swapped into
+    pub target: &'a str,
+    /// Extra form fields (filters, sort) to send along with the page
+    pub hx_include: Option<&'a str>,
+    /// Plural noun for the results summary
+    pub noun: &'a str,
+    /// Put the page size in page links, for grids where it can be chosen
+    pub page_size_in_url: bool,
+}
+
+/// Album grid pagination; carries the filter bar's fields along
+pub const ALBUM_PAGINATION: PaginationTarget<'static> = PaginationTarget {
+    base_url: "/albums",
+    target: "#album-grid",
+    hx_include: Some("[name='search'], [name='search_in'], [name='ownership_status'], [name='match_status'], [name='sort_by'], [name='sort_order'], [name='view']"),
+    noun: "albums",
+    page_size_in_url: true,
+};
+
+pub const ARTIST_PAGINATION: PaginationTarget<'static> = PaginationTarget {
+    base_url: "/artists-grid",
+    target: "#artist-grid",
+    hx_include: None,
+    noun: "artists",
+    page_size_in_url: false,
+};
+
+pub const PLAYLIST_PAGINATION: PaginationTarget<'static> = PaginationTarget {
+    base_url: "/playlists-grid",
+    target: "#playlist-grid",
+    hx_include: None,
+    noun: "playlists",
+    page_size_in_url: false,
+};
+
+/// Pagination controls shared by the album, artist and playlist grids
+///
+/// Links carry rel=prev/next and spoken labels; the current page is marked
+/// with aria-current, and unavailable Previous/Next render as non-focusable
+/// spans. A visually hidden summary announces the range being shown.
+pub fn pagination(info: &PageInfo, target: &PaginationTarget) -> Markup {
+    let page = info.page;
+    let total_pages = info.total_pages();
+    let link_class = "px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-primary";
+    let disabled_class = "px-4 py-2 bg-gray-100 border border-gray-300 rounded-md text-gray-400 cursor-not-allowed";
+
+    let page_link = |p: u64, rel: Option<&str>, label: String, text: Markup| {
+        let url = if target.page_size_in_url {
+            format!("{}?page={}&page_size={}", target.base_url, p, info.page_size)
+        } else {
+            format!("{}?page={}", target.base_url, p)
+        };
+        html! {
+            a
+                class=(link_class)
+                href=(url)
+                rel=[rel]
+                aria-label=(label)
+                hx-get=(url)
+                hx-target=(target.target)
+                hx-swap="innerHTML"
+                hx-include=[target.hx_include] {
+                (text)
             }
+        }
+    };
 
-            // Page numbers
-            @for p in page_range(page, total_pages) {
-                @if p == page {
-                    span class="px-4 py-2 bg-primary text-white rounded-md font-semibold" {
-                        (p)
+    html! {
+        nav class="mt-8" aria-label={(format!("{} pagination", capitalize(target.noun)))} {
+            p class="sr-only" aria-live="polite" { (info.summary(target.noun)) }
+
+            @if total_pages > 1 {
+                div class="flex justify-center items-center space-x-2" {
+                    @if page > 1 {
+                        (page_link(page - 1, Some("prev"), format!("Go to previous page, page {}", page - 1), html! { "Previous" }))
+                    } @else {
+                        span class=(disabled_class) aria-disabled="true" { "Previous" }
                     }
-                } @else {
-                    button
-                        class="px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
-                        hx-get={(format!("{}?page={}", base_url, p))}
-                        hx-target="#album-grid"
-                        hx-swap="innerHTML"
-                        hx-include=(hx_include) {
-                        (p)
+
+                    @for p in page_range(page, total_pages) {
+                        @if p == page {
+                            span class="px-4 py-2 bg-primary text-white rounded-md font-semibold" aria-current="page" {
+                                span class="sr-only" { "Page " }
+                                (p)
+                            }
+                        } @else {
+                            (page_link(p, None, format!("Go to page {}", p), html! { (p) }))
+                        }
                     }
-                }
-            }
 
-            // Next button
-            @if page < total_pages {
-                button
-                    class="px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
-                    hx-get={(format!("{}?page={}", base_url, page + 1))}
-                    hx-target="#album-grid"
-                    hx-swap="innerHTML"
-                    hx-include=(hx_include) {
-                    "Next"
-                }
-            } @else {
-                button class="px-4 py-2 bg-gray-100 border border-gray-300 rounded-md text-gray-400 cursor-not-allowed" disabled {
-                    "Next"
+                    @if page < total_pages {
+                        (page_link(page + 1, Some("next"), format!("Go to next page, page {}", page + 1), html! { "Next" }))
+                    } @else {
+                        span class=(disabled_class) aria-disabled="true" { "Next" }
+                    }
                 }
             }
         }
     }
 }
 
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 fn page_range(current: u64, total: u64) -> Vec<u64> {
     let mut pages = Vec::new();
     let range = 2; // Show 2 pages before and after current
@@ -320,25 +694,272 @@ pub fn notification(message: &str, notification_type: &str) -> Markup {
     }
 }
 
+/// Notification for a Lidarr search that was queued
+///
+/// Links back to the album's downloads so the grab can be followed once
+/// Lidarr reports it.
+pub fn lidarr_search_started_notification(album_slug: &str, command_id: i32) -> Markup {
+    html! {
+        div class="p-4 rounded-md bg-green-50 text-green-800" role="status" {
+            div class="flex items-center flex-wrap gap-2" {
+                span class="font-bold" { "✓" }
+                span { "Lidarr search started (command #" (command_id) ")" }
+                a
+                    href="#album-downloads"
+                    class="underline font-semibold hover:text-green-900"
+                    hx-get={(format!("/albums/{}", album_slug))}
+                    hx-target="#album-detail-modal"
+                    hx-swap="innerHTML show:#album-downloads:top" {
+                    "View downloads"
+                }
+            }
+        }
+    }
+}
+
+/// Notification for an album Lidarr doesn't have yet
+///
+/// The add button stays disabled until albums can be added to Lidarr from here.
+pub fn lidarr_album_missing_notification() -> Markup {
+    html! {
+        div class="p-4 rounded-md bg-blue-50 text-blue-800" role="status" {
+            div class="flex items-center flex-wrap gap-2" {
+                span class="font-bold" { "ℹ" }
+                span { "Album not found in Lidarr." }
+                button
+                    type="button"
+                    class="px-3 py-1 bg-gray-300 text-gray-600 font-semibold rounded-md cursor-not-allowed"
+                    disabled
+                    title="Adding albums to Lidarr isn't supported yet; add it in Lidarr first" {
+                    "Add to Lidarr and search"
+                }
+            }
+        }
+    }
+}
+
+/// Listening history shown in the album detail modal
+pub struct AlbumListensData {
+    pub count: i32,
+    pub first_listened: Markup,
+    pub last_listened: Markup,
+}
+
+/// Track count and length check findings shown in the album detail modal
+#[derive(Default)]
+pub struct AlbumTracksData {
+    pub total: Option<i32>,
+    /// Tracks whose local file's length doesn't match Spotify's
+    pub mismatches: Vec<TrackMismatchData>,
+}
+
+/// Track the filesystem scan flagged as longer or shorter than on Spotify
+pub struct TrackMismatchData {
+    pub disc_number: i32,
+    pub track_number: i32,
+    pub title: String,
+    pub spotify_duration_ms: i32,
+    pub local_duration_ms: i32,
+}
+
+/// Lidarr download row in the album detail modal
+pub struct AlbumDownloadData {
+    pub status: String,
+    pub download_id: Option<String>,
+    pub created_at: Markup,
+    pub error_message: Option<String>,
+    /// Automatic retries made so far
+    pub retry_count: i32,
+}
+
+/// Artist offered when moving an album to another artist
+pub struct ArtistOptionData {
+    pub id: i32,
+    pub name: String,
+    pub album_count: i64,
+}
+
+/// Choices for the album modal's "Change artist" select
+///
+/// Album counts tell same-named artists apart. A non-empty search that
+/// matches no artist exactly is also offered as a new artist.
+pub fn artist_options(artists: &[ArtistOptionData], search: &str) -> Markup {
+    let search = search.trim();
+    let offer_new = !search.is_empty() && !artists.iter().any(|a| a.name.eq_ignore_ascii_case(search));
+
+    html! {
+        @if artists.is_empty() && !offer_new {
+            option value="" { "Type to search artists" }
+        }
+        @for artist in artists {
+            option value=(artist.id) {
+                (artist.name) " (" (artist.album_count) " albums)"
+            }
+        }
+        @if offer_new {
+            option value="new" { "Create new artist \"" (search) "\"" }
+        }
+    }
+}
+
+/// What a Lidarr artist override resolved to
+pub enum LidarrArtistPreview {
+    /// Lidarr knows the artist under this name
+    Found(String),
+    /// Lidarr has no artist with that MusicBrainz ID
+    NotFound,
+    /// The ID was invalid or Lidarr couldn't be asked
+    Error(String),
+}
+
+/// Feedback under the album modal's Lidarr artist input
+pub fn lidarr_artist_preview(preview: &LidarrArtistPreview) -> Markup {
+    html! {
+        @match preview {
+            LidarrArtistPreview::Found(name) => {
+                span class="text-green-700" { "Lidarr artist: " (name) }
+            }
+            LidarrArtistPreview::NotFound => {
+                span class="text-yellow-700" { "Lidarr doesn't know this artist" }
+            }
+            LidarrArtistPreview::Error(message) => {
+                span class="text-red-600" { (message) }
+            }
+        }
+    }
+}
+
+/// Job shown on the jobs page, with timestamps already localized
+pub struct JobRowData {
+    pub id: i32,
+    pub job_type: String,
+    pub status: String,
+    pub progress: Option<i32>,
+    pub error_message: Option<String>,
+    /// Extra progress line, such as a Spotify sync's API usage and time left
+    pub detail: Option<String>,
+    pub created: Markup,
+    pub completed: Option<Markup>,
+}
+
+/// A folder a scan preview matched to a library album
+pub struct ScanProposalData {
+    pub album_id: i32,
+    pub directory: String,
+    pub artist_name: String,
+    pub album_title: String,
+    /// Match confidence, 0-100
+    pub confidence: u32,
+    /// Whether the match starts out accepted in the review
+    pub accepted: bool,
+}
+
 // Playlist-related types and components
 
 pub struct PlaylistCardData {
     pub id: i32,
     pub name: String,
     pub owner_name: Option<String>,
+    /// Track count Spotify reports
     pub track_count: i32,
+    /// Tracks actually synced; differs from `track_count` when a sync skipped some
+    pub synced_count: i32,
     pub owned_count: i32,
     pub cover_image_url: Option<String>,
     pub is_enabled: bool,
+    /// Why syncs disabled the playlist, if they did
+    pub disabled_reason: Option<String>,
     pub ownership_percentage: f64,
     pub is_synthetic: bool,
+    /// Entries not playable in the user's Spotify market
+    pub unavailable_count: i32,
+    /// Podcast episodes skipped by the last sync
+    pub episode_count: i32,
+    /// When the tracks were last pulled from Spotify, already localized
+    pub last_synced: Option<Markup>,
+}
+
+impl PlaylistCardData {
+    /// Card data for a playlist row; ownership is `owned` out of the `synced` tracks
+    pub fn from_model(playlist: playlists::Model, owned: i64, synced: i64, tz: Tz) -> Self {
+        Self {
+            id: playlist.id,
+            name: playlist.name,
+            owner_name: playlist.owner_name,
+            track_count: playlist.total_tracks.unwrap_or(0),
+            synced_count: synced as i32,
+            owned_count: owned as i32,
+            cover_image_url: playlist.cover_image_url,
+            is_enabled: playlist.is_enabled,
+            disabled_reason: playlist.disabled_reason,
+            ownership_percentage: ownership_percentage(owned, synced),
+            is_synthetic: playlist.is_synthetic,
+            unavailable_count: playlist.unavailable_tracks.unwrap_or(0),
+            episode_count: playlist.episode_count.unwrap_or(0),
+            last_synced: playlist.last_synced_at.map(|t| timestamp(&t, tz)),
+        }
+    }
+}
+
+/// Track count, ownership and last sync strip shown under the playlist modal header
+pub fn playlist_stats_bar(playlist: &PlaylistCardData) -> Markup {
+    playlist_stats_bar_inner(playlist, false)
+}
+
+/// Stats strip with an out-of-band swap attribute, for actions taken from the track list
+pub fn playlist_stats_bar_oob(playlist: &PlaylistCardData) -> Markup {
+    playlist_stats_bar_inner(playlist, true)
+}
+
+fn playlist_stats_bar_inner(playlist: &PlaylistCardData, oob: bool) -> Markup {
+    html! {
+        div
+            id="playlist-stats-bar"
+            class="px-6 py-3 bg-gray-50 border-b flex-shrink-0 text-sm"
+            hx-swap-oob=[if oob { Some("true") } else { None }] {
+            span class="text-gray-500" { "Tracks: " }
+            @if playlist.synced_count == playlist.track_count {
+                span class="font-semibold" { (playlist.track_count) }
+            } @else {
+                span class="font-semibold" { (playlist.synced_count) }
+                span class="text-gray-500" title="Local files and other entries a sync skips aren't counted" {
+                    " synced of " (playlist.track_count) " on Spotify"
+                }
+            }
+            @if playlist.unavailable_count > 0 {
+                span class="text-gray-300 mx-3" { "|" }
+                span class="text-gray-500" title="Not playable in your Spotify market" { "Unavailable: " }
+                span class="font-semibold text-amber-600" { (playlist.unavailable_count) }
+            }
+            @if playlist.episode_count > 0 {
+                span class="text-gray-300 mx-3" { "|" }
+                span class="text-gray-500" title="Podcast episodes aren't synced as tracks" { "Episodes skipped: " }
+                span class="font-semibold" { (playlist.episode_count) }
+            }
+            span class="text-gray-300 mx-3" { "|" }
+            span class="text-gray-500" { "Owned: " }
+            span class="font-semibold text-green-600" { (playlist.owned_count) }
+            span class="text-gray-300 mx-3" { "|" }
+            span class="text-gray-500" { "Ownership: " }
+            span class=(format!("font-semibold {}", ownership_color(playlist.ownership_percentage).class(&STAT_PALETTE))) {
+                (format!("{:.1}%", playlist.ownership_percentage))
+            }
+            @if let Some(synced) = &playlist.last_synced {
+                span class="text-gray-300 mx-3" { "|" }
+                span class="text-gray-500" { "Last synced: " }
+                span class="font-semibold" { (synced) }
+            }
+        }
+    }
 }
 
 pub struct PlaylistTrackData {
     pub position: i32,
     pub track_name: String,
+    pub artist_slug: String,
     pub artist_name: String,
     pub album_id: i32,
+    pub album_slug: String,
     pub album_name: String,
     pub duration_ms: Option<i32>,
     pub ownership_status: OwnershipStatus,
@@ -379,7 +1000,7 @@ fn playlist_card_inner(playlist: &PlaylistCardData, oob: bool) -> Markup {
                     }
                 } @else {
                     img
-                        src=(playlist.cover_image_url.as_deref().unwrap_or("https://via.placeholder.com/300x300/1a1a1a/ffffff?text=Playlist"))
+                        src=(cover_or_placeholder(playlist.cover_image_url.as_deref(), &playlist.name))
                         alt={(format!("{} playlist", playlist.name))}
                         class="w-full h-full object-cover"
                         loading="lazy";
@@ -387,17 +1008,19 @@ fn playlist_card_inner(playlist: &PlaylistCardData, oob: bool) -> Markup {
 
                 // Enabled/disabled badge
                 @if !playlist.is_enabled {
-                    div class="absolute inset-0 bg-black bg-opacity-50 flex items-center justify-center" {
-                        span class="text-white text-sm font-semibold" { "Disabled" }
+                    div
+                        class="absolute inset-0 bg-black bg-opacity-50 flex items-center justify-center"
+                        title=[playlist.disabled_reason.as_deref()] {
+                        span class="text-white text-sm font-semibold" {
+                            @if playlist.disabled_reason.is_some() { "Disabled: sync failing" } @else { "Disabled" }
+                        }
                     }
                 }
 
                 // Ownership percentage badge
                 div class="absolute top-2 right-2" {
                     span class=(format!("px-2 py-1 text-xs font-semibold text-white rounded-full {}",
-                        if playlist.ownership_percentage >= 80.0 { "bg-green-500" }
-                        else if playlist.ownership_percentage >= 50.0 { "bg-yellow-500" }
-                        else { "bg-gray-500" }
+                        ownership_color(playlist.ownership_percentage).class(&BADGE_PALETTE)
                     )) {
                         (format!("{:.0}%", playlist.ownership_percentage))
                     }
@@ -418,7 +1041,24 @@ fn playlist_card_inner(playlist: &PlaylistCardData, oob: bool) -> Markup {
 
                 div class="mt-2 flex justify-between items-center" {
                     p class="text-xs text-gray-500" {
-                        (playlist.track_count) " tracks"
+                        @if playlist.synced_count == playlist.track_count {
+                            (playlist.track_count) " tracks"
+                        } @else {
+                            span title=(format!("{} synced of {} on Spotify", playlist.synced_count, playlist.track_count)) {
+                                (playlist.synced_count) " of " (playlist.track_count) " tracks"
+                            }
+                        }
+                        @if playlist.unavailable_count > 0 {
+                            span class="text-amber-600" title="Not playable in your Spotify market" {
+                                " · " (playlist.unavailable_count) " unavailable"
+                            }
+                        }
+                        @if playlist.episode_count > 0 {
+                            span title="Podcast episodes aren't synced as tracks" {
+                                " · " (playlist.episode_count)
+                                @if playlist.episode_count == 1 { " episode" } @else { " episodes" }
+                            }
+                        }
                     }
                     p class="text-xs text-green-600" {
                         (playlist.owned_count) " owned"
@@ -429,13 +1069,7 @@ fn playlist_card_inner(playlist: &PlaylistCardData, oob: bool) -> Markup {
     }
 }
 
-pub fn playlist_track_row(track: &PlaylistTrackData) -> Markup {
-    let status_color = match track.ownership_status {
-        OwnershipStatus::Owned => "text-green-600",
-        OwnershipStatus::NotOwned => "text-gray-400",
-        OwnershipStatus::Downloading => "text-blue-600",
-    };
-
+pub fn playlist_track_row(track: &PlaylistTrackData, playlist_id: i32) -> Markup {
     let duration_str = track.duration_ms.map(format_duration).unwrap_or_default();
 
     html! {
@@ -448,14 +1082,18 @@ pub fn playlist_track_row(track: &PlaylistTrackData) -> Markup {
             // Track name
             td class="px-4 py-3" {
                 div class="text-sm font-medium text-gray-900" { (track.track_name) }
-                div class="text-sm text-gray-500" { (track.artist_name) }
+                a
+                    class="text-sm text-gray-500 hover:text-primary hover:underline"
+                    href={(format!("/artists/{}", track.artist_slug))} {
+                    (track.artist_name)
+                }
             }
 
             // Album (clickable)
             td class="px-4 py-3 text-sm text-gray-600" {
                 span
                     class="cursor-pointer hover:text-primary hover:underline"
-                    hx-get={(format!("/albums/{}", track.album_id))}
+                    hx-get={(format!("/albums/{}", track.album_slug))}
                     hx-target="#album-detail-modal"
                     hx-swap="innerHTML" {
                     (track.album_name)
@@ -467,21 +1105,51 @@ pub fn playlist_track_row(track: &PlaylistTrackData) -> Markup {
                 (duration_str)
             }
 
-            // Ownership status
-            td class="px-4 py-3 text-right" {
-                span class=(format!("text-lg {}", status_color)) {
-                    @match track.ownership_status {
-                        OwnershipStatus::Owned => "●",
-                        OwnershipStatus::NotOwned => "○",
-                        OwnershipStatus::Downloading => "◐",
-                    }
+            (playlist_track_status(track.ownership_status, playlist_id, track.album_id, false))
+        }
+    }
+}
+
+/// Ownership cell of a playlist track row, with a button marking a wanted album owned
+///
+/// Cells carry their album's class, so marking an album owned can update
+/// every row of that album out of band.
+pub fn playlist_track_status(status: OwnershipStatus, playlist_id: i32, album_id: i32, oob: bool) -> Markup {
+    let status_color = match status {
+        OwnershipStatus::Owned => "text-green-600",
+        OwnershipStatus::NotOwned => "text-gray-400",
+        OwnershipStatus::Downloading => "text-blue-600",
+        OwnershipStatus::Ordered => "text-purple-600",
+    };
+    let album_class = format!("playlist-album-{}", album_id);
+
+    html! {
+        td
+            class=(format!("px-4 py-3 text-right whitespace-nowrap {}", album_class))
+            hx-swap-oob=[oob.then(|| format!("outerHTML:.{}", album_class))] {
+            @if status == OwnershipStatus::NotOwned {
+                button
+                    class="mr-2 text-xs text-gray-400 hover:text-green-600"
+                    title="Mark album owned"
+                    aria-label="Mark album owned"
+                    hx-post={(format!("/playlists/{}/albums/{}/mark-owned", playlist_id, album_id))}
+                    hx-swap="none" {
+                    "✓"
+                }
+            }
+            span class=(format!("text-lg {}", status_color)) {
+                @match status {
+                    OwnershipStatus::Owned => "●",
+                    OwnershipStatus::NotOwned => "○",
+                    OwnershipStatus::Downloading => "◐",
+                    OwnershipStatus::Ordered => "◔",
                 }
             }
         }
     }
 }
 
-fn format_duration(ms: i32) -> String {
+pub(crate) fn format_duration(ms: i32) -> String {
     let total_seconds = ms / 1000;
     let minutes = total_seconds / 60;
     let seconds = total_seconds % 60;
@@ -497,7 +1165,7 @@ pub fn playlist_tracks_rows(
 ) -> Markup {
     html! {
         @for track in &tracks {
-            (playlist_track_row(track))
+            (playlist_track_row(track, playlist_id))
         }
 
         @if has_more {
@@ -522,31 +1190,115 @@ pub fn playlist_tracks_rows(
 
 pub struct ArtistCardData {
     pub id: i32,
+    pub slug: String,
     pub name: String,
+    /// Spotify image, once the image backfill has found one
+    pub image_url: Option<String>,
     pub album_count: i64,
     pub owned_count: i64,
     pub ownership_percentage: f64,
 }
 
-pub fn artist_card(artist: &ArtistCardData) -> Markup {
-    let progress_width = artist.ownership_percentage.min(100.0).max(0.0);
+/// A Spotify top artist, matched against the library where possible
+pub struct TopArtistData {
+    pub name: String,
+    /// Artist page path segment, when the artist has albums in the library
+    pub slug: Option<String>,
+    pub image_url: Option<String>,
+    pub album_count: i64,
+    pub owned_count: i64,
+}
+
+/// Album of a Spotify top track, matched against the library where possible
+pub struct TopAlbumData {
+    pub title: String,
+    pub artist_name: String,
+    pub image_url: Option<String>,
+    /// Album detail path segment, when the album is in the library
+    pub slug: Option<String>,
+    /// `None` when the album isn't in the library at all
+    pub ownership_status: Option<OwnershipStatus>,
+}
 
-    let progress_color = if artist.ownership_percentage >= 80.0 {
-        "bg-green-500"
-    } else if artist.ownership_percentage >= 50.0 {
-        "bg-yellow-500"
+pub fn top_artist_row(artist: &TopArtistData) -> Markup {
+    let (text, color) = if artist.album_count == 0 {
+        ("Not in library".to_string(), "bg-red-100 text-red-800")
+    } else if artist.owned_count == artist.album_count {
+        (format!("{}/{} owned", artist.owned_count, artist.album_count), "bg-green-100 text-green-800")
     } else {
-        "bg-gray-400"
+        (format!("{}/{} owned", artist.owned_count, artist.album_count), "bg-yellow-100 text-yellow-800")
+    };
+
+    html! {
+        li class="flex items-center gap-3 py-2" {
+            img
+                src=(cover_or_placeholder(artist.image_url.as_deref(), &artist.name))
+                alt=(artist.name)
+                class="w-10 h-10 rounded-full object-cover";
+            @if let Some(slug) = &artist.slug {
+                a href={(format!("/artists/{}", slug))}
+                  class="flex-grow truncate text-gray-900 hover:text-primary hover:underline" {
+                    (artist.name)
+                }
+            } @else {
+                span class="flex-grow truncate text-gray-900" { (artist.name) }
+            }
+            span class={(format!("px-2 py-1 text-xs font-semibold rounded-full {}", color))} { (text) }
+        }
+    }
+}
+
+pub fn top_album_row(album: &TopAlbumData) -> Markup {
+    let (text, color) = match album.ownership_status {
+        Some(OwnershipStatus::Owned) => ("Owned", "bg-green-100 text-green-800"),
+        Some(OwnershipStatus::Downloading) => ("Downloading", "bg-blue-100 text-blue-800"),
+        Some(OwnershipStatus::Ordered) => ("Ordered", "bg-purple-100 text-purple-800"),
+        Some(OwnershipStatus::NotOwned) => ("Not Owned", "bg-yellow-100 text-yellow-800"),
+        None => ("Not in library", "bg-red-100 text-red-800"),
     };
 
+    html! {
+        li class="flex items-center gap-3 py-2" {
+            img
+                src=(cover_or_placeholder(album.image_url.as_deref(), &album.title))
+                alt={(format!("{} cover", album.title))}
+                class="w-10 h-10 rounded object-cover";
+            div class="flex-grow min-w-0" {
+                @if let Some(slug) = &album.slug {
+                    a href="#"
+                      hx-get={(format!("/albums/{}", slug))}
+                      hx-target="#album-detail-modal"
+                      class="block truncate text-gray-900 hover:text-primary hover:underline" {
+                        (album.title)
+                    }
+                } @else {
+                    span class="block truncate text-gray-900" { (album.title) }
+                }
+                span class="block truncate text-sm text-gray-500" { (album.artist_name) }
+            }
+            span class={(format!("px-2 py-1 text-xs font-semibold rounded-full {}", color))} { (text) }
+        }
+    }
+}
+
+pub fn artist_card(artist: &ArtistCardData) -> Markup {
+    let progress_width = artist.ownership_percentage.min(100.0).max(0.0);
+    let color = ownership_color(artist.ownership_percentage);
+    let progress_color = color.class(&PROGRESS_PALETTE);
+
     html! {
         a
-            href={(format!("/artists/{}", artist.id))}
+            href={(format!("/artists/{}", artist.slug))}
             class="artist-card block bg-white rounded-lg shadow-md overflow-hidden cursor-pointer hover:shadow-lg transition-shadow p-4" {
 
-            // Artist name
-            h3 class="font-semibold text-gray-900 text-lg truncate mb-2" title=(artist.name) {
-                (artist.name)
+            // Artist image and name
+            div class="flex items-center gap-3 mb-2" {
+                @if let Some(url) = &artist.image_url {
+                    img src=(url) alt="" class="w-12 h-12 rounded-full object-cover flex-shrink-0" loading="lazy";
+                }
+                h3 class="font-semibold text-gray-900 text-lg truncate" title=(artist.name) {
+                    (artist.name)
+                }
             }
 
             // Album count badge
@@ -568,11 +1320,7 @@ pub fn artist_card(artist: &ArtistCardData) -> Markup {
 
             // Percentage
             div class="text-right" {
-                span class=(format!("text-sm font-medium {}",
-                    if artist.ownership_percentage >= 80.0 { "text-green-600" }
-                    else if artist.ownership_percentage >= 50.0 { "text-yellow-600" }
-                    else { "text-gray-500" }
-                )) {
+                span class=(format!("text-sm font-medium {}", color.class(&LABEL_PALETTE))) {
                     (format!("{:.0}%", artist.ownership_percentage)) " complete"
                 }
             }
@@ -639,43 +1387,470 @@ pub fn artist_filter_bar() -> Markup {
     }
 }
 
-pub fn artist_pagination(page: u64, total_pages: u64, base_url: &str) -> Markup {
+/// Profile switcher for the navigation bar (posts the selection and reloads the page)
+pub fn profile_selector(profiles: &[(i32, String)], active_id: i32) -> Markup {
     html! {
-        div class="flex justify-center items-center space-x-2 mt-8" {
-            // Previous button
-            @if page > 1 {
-                button
-                    class="px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
-                    hx-get={(format!("{}?page={}", base_url, page - 1))}
-                    hx-target="#artist-grid"
-                    hx-swap="innerHTML" {
-                    "Previous"
-                }
-            } @else {
-                button class="px-4 py-2 bg-gray-100 border border-gray-300 rounded-md text-gray-400 cursor-not-allowed" disabled {
-                    "Previous"
-                }
+        select
+            name="profile_id"
+            title="Profile"
+            class="px-3 py-2 border border-gray-300 rounded-md text-sm focus:outline-none focus:ring-2 focus:ring-primary"
+            hx-post="/api/profiles/select"
+            hx-trigger="change"
+            hx-swap="none" {
+            @for (id, name) in profiles {
+                option value=(id) selected[*id == active_id] { (name) }
             }
+        }
+    }
+}
+
+/// Want-list board columns, left to right
+pub const BOARD_COLUMNS: [OwnershipStatus; 4] = [
+    OwnershipStatus::NotOwned,
+    OwnershipStatus::Ordered,
+    OwnershipStatus::Downloading,
+    OwnershipStatus::Owned,
+];
+
+pub fn board_column_title(status: OwnershipStatus) -> &'static str {
+    match status {
+        OwnershipStatus::NotOwned => "Wanted",
+        OwnershipStatus::Ordered => "Ordered",
+        OwnershipStatus::Downloading => "Downloading",
+        OwnershipStatus::Owned => "Owned",
+    }
+}
+
+pub struct BoardColumnData {
+    pub status: OwnershipStatus,
+    /// Albums in this state, including any not shown
+    pub count: u64,
+    pub cards: Vec<BoardCardData>,
+}
+
+pub struct BoardCardData {
+    pub id: i32,
+    /// Path segment for the album's detail link (slug, or ID if it has none)
+    pub slug: String,
+    pub title: String,
+    pub artist_name: String,
+    pub cover_art_url: Option<String>,
+    pub ownership_status: OwnershipStatus,
+    /// How long a wanted album has been wanted
+    pub wanted_age: Option<WantedAge>,
+}
+
+/// "Wanted for …" line of a board card
+pub fn wanted_age_label(age: WantedAge) -> String {
+    let (count, unit) = match age {
+        WantedAge::Days(0) => return "Wanted since today".to_string(),
+        WantedAge::Days(n) => (n, "day"),
+        WantedAge::Months(n) => (n, "month"),
+        WantedAge::Years(n) => (n, "year"),
+    };
+    format!("Wanted for {} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
 
-            // Page indicator
-            span class="px-4 py-2 text-gray-600" {
-                "Page " (page) " of " (total_pages)
+/// Album card on the want-list board
+///
+/// Buttons move the card to a neighbouring column when the ownership rules
+/// allow it; the server answers with the card for the destination column.
+pub fn board_card(card: &BoardCardData) -> Markup {
+    let column = BOARD_COLUMNS.iter().position(|s| *s == card.ownership_status).unwrap_or(0);
+    let neighbour = |index: Option<usize>| {
+        index
+            .and_then(|i| BOARD_COLUMNS.get(i).copied())
+            .filter(|to| card.ownership_status.can_transition_to(*to, false))
+    };
+    let previous = neighbour(column.checked_sub(1));
+    let next = neighbour(Some(column + 1));
+
+    let move_button = |to: OwnershipStatus, label: String| {
+        html! {
+            button
+                class="px-2 py-1 text-xs text-gray-600 border border-gray-300 rounded hover:bg-gray-100"
+                hx-post={(format!("/board/albums/{}/move", card.id))}
+                hx-vals={(format!(r#"{{"to": "{}"}}"#, to.as_str()))}
+                hx-target={(format!("#board-card-{}", card.id))}
+                hx-swap="outerHTML" {
+                (label)
             }
+        }
+    };
 
-            // Next button
-            @if page < total_pages {
-                button
-                    class="px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
-                    hx-get={(format!("{}?page={}", base_url, page + 1))}
-                    hx-target="#artist-grid"
-                    hx-swap="innerHTML" {
-                    "Next"
+    html! {
+        div id={(format!("board-card-{}", card.id))} class="board-card bg-white rounded-md shadow-sm p-3" {
+            div
+                class="flex items-center gap-3 cursor-pointer"
+                hx-get={(format!("/albums/{}", card.slug))}
+                hx-target="#album-detail-modal"
+                hx-swap="innerHTML" {
+                @if let Some(url) = &card.cover_art_url {
+                    img src=(url) alt="" class="w-12 h-12 rounded object-cover flex-shrink-0" loading="lazy";
+                } @else {
+                    div class="w-12 h-12 rounded bg-gray-200 flex-shrink-0" {}
                 }
-            } @else {
-                button class="px-4 py-2 bg-gray-100 border border-gray-300 rounded-md text-gray-400 cursor-not-allowed" disabled {
-                    "Next"
+                div class="min-w-0" {
+                    p class="text-sm font-medium text-gray-900 truncate" title=(card.title) { (card.title) }
+                    p class="text-xs text-gray-500 truncate" { (card.artist_name) }
+                    @if let Some(age) = card.wanted_age {
+                        p class="text-xs text-gray-400" { (wanted_age_label(age)) }
+                    }
+                }
+            }
+            @if previous.is_some() || next.is_some() {
+                div class="flex justify-between mt-2" {
+                    @if let Some(to) = previous {
+                        (move_button(to, format!("← {}", board_column_title(to))))
+                    } @else {
+                        span {}
+                    }
+                    @if let Some(to) = next {
+                        (move_button(to, format!("{} →", board_column_title(to))))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Card moved into a board column, inserted at the top of its column
+pub fn board_card_oob(card: &BoardCardData) -> Markup {
+    html! {
+        div hx-swap-oob={(format!("afterbegin:#board-cards-{}", card.ownership_status.as_str()))} {
+            (board_card(card))
+        }
+    }
+}
+
+/// Album count in a board column header, optionally as an out-of-band swap
+pub fn board_column_count(status: OwnershipStatus, count: u64, oob: bool) -> Markup {
+    html! {
+        span
+            id={(format!("board-count-{}", status.as_str()))}
+            class="px-2 py-0.5 text-xs font-semibold text-gray-600 bg-gray-200 rounded-full"
+            hx-swap-oob=[if oob { Some("true") } else { None }] {
+            (count)
+        }
+    }
+}
+
+/// Row on the archive page: something that disappeared from Spotify
+pub struct ArchivedRowData {
+    pub id: i32,
+    pub title: String,
+    /// Artist, playlist owner or containing album
+    pub detail: Option<String>,
+    pub archived_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+/// Archive row with a restore button that removes the row once restored
+///
+/// `kind` is the archive type in the restore URL: `albums`, `playlists` or `tracks`.
+pub fn archived_row(kind: &str, row: &ArchivedRowData, tz: Tz) -> Markup {
+    html! {
+        li class="flex items-center justify-between gap-4 py-3" {
+            div class="min-w-0" {
+                p class="text-sm font-medium text-gray-900 truncate" title=(row.title) { (row.title) }
+                p class="text-xs text-gray-500 truncate" {
+                    @if let Some(detail) = &row.detail {
+                        (detail) " · "
+                    }
+                    "Removed " (timestamp(&row.archived_at, tz))
                 }
             }
+            button
+                class="px-3 py-1 text-sm text-gray-700 border border-gray-300 rounded hover:bg-gray-100 flex-shrink-0"
+                hx-post={(format!("/api/archive/{}/{}/restore", kind, row.id))}
+                hx-target="closest li"
+                hx-swap="delete" {
+                "Restore"
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::album_grid_partial;
+
+    #[test]
+    fn test_artist_options_offer_new_artist_unless_name_exists() {
+        let artists = vec![ArtistOptionData { id: 7, name: "Björk".to_string(), album_count: 3 }];
+
+        let html = artist_options(&artists, "björk").into_string();
+        assert!(html.contains(r#"<option value="7">Björk (3 albums)</option>"#));
+        assert!(!html.contains(r#"value="new""#));
+
+        let html = artist_options(&artists, "Bjork Tribute").into_string();
+        assert!(html.contains(r#"<option value="new">Create new artist &quot;Bjork Tribute&quot;</option>"#));
+
+        let html = artist_options(&[], "  ").into_string();
+        assert_eq!(html, r#"<option value="">Type to search artists</option>"#);
+    }
+
+    fn sample_albums() -> Vec<AlbumCardData> {
+        (1..=3)
+            .map(|i| AlbumCardData {
+                id: i,
+                slug: format!("test-artist-album-{}", i),
+                title: format!("Album {}", i),
+                artist_id: 1,
+                artist_slug: "test-artist".to_string(),
+                artist_name: "Test Artist".to_string(),
+                cover_art_url: Some(format!("/static/covers/{}.jpg", i)),
+                release_date: Some("2020-01-01".to_string()),
+                ownership_status: OwnershipStatus::Owned,
+                match_score: Some(95),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_grid_and_list_render_same_albums() {
+        let page_info = PageInfo { page: 1, page_size: 3, total_items: 6 };
+        let grid = album_grid_partial(sample_albums(), page_info, AlbumView::Grid).into_string();
+        let list = album_grid_partial(sample_albums(), page_info, AlbumView::List).into_string();
+
+        for album in sample_albums() {
+            assert!(grid.contains(&album.title));
+            assert!(list.contains(&album.title));
+            assert!(grid.contains(&format!("/albums/{}", album.slug)));
+            assert!(list.contains(&format!("/albums/{}", album.slug)));
+        }
+
+        assert_eq!(grid.matches("class=\"album-card ").count(), 3);
+        assert_eq!(list.matches("album-row").count(), 3);
+        assert!(!list.contains("album-card"));
+
+        // Both views share the same pagination controls
+        assert!(grid.contains("/albums?page=2"));
+        assert!(list.contains("/albums?page=2"));
+        assert!(list.contains("[name='view']"));
+
+        // Page links keep the page size the grid was rendered with
+        assert!(grid.contains(r#"href="/albums?page=2&amp;page_size=3""#));
+        assert!(list.contains(r#"hx-get="/albums?page=2&amp;page_size=3""#));
+    }
+
+    #[test]
+    fn test_list_row_is_smaller_than_card() {
+        for album in sample_albums() {
+            let card = album_card(&album).into_string();
+            let row = album_list_row(&album).into_string();
+            assert!(row.len() < card.len(), "row: {}, card: {}", row.len(), card.len());
+            assert!(!row.contains("aspect-square"));
+        }
+    }
+
+    #[test]
+    fn test_filter_bar_selects_current_view() {
+        let markup = filter_bar(AlbumView::List).into_string();
+        assert!(markup.contains(r#"<option value="list" selected>"#));
+        assert!(!markup.contains(r#"<option value="grid" selected>"#));
+    }
+
+    #[test]
+    fn test_filter_bar_page_size_selector() {
+        let markup = filter_bar(AlbumView::Grid).into_string();
+
+        assert!(markup.contains(r#"name="page_size""#));
+        for size in ALBUM_PAGE_SIZES {
+            assert!(markup.contains(&format!(r#"<option value="{}""#, size)));
+        }
+        assert!(markup.contains(r#"<option value="50" selected>"#));
+
+        // Every other control sends the chosen size along
+        assert_eq!(markup.matches("[name='page_size']").count(), 7);
+    }
+
+    #[test]
+    fn test_pagination_accessibility_attributes() {
+        let info = PageInfo { page: 2, page_size: 50, total_items: 432 };
+        let markup = pagination(&info, &ALBUM_PAGINATION).into_string();
+
+        assert!(markup.contains(r#"aria-label="Albums pagination""#));
+        assert!(markup.contains(r#"rel="prev""#));
+        assert!(markup.contains(r#"rel="next""#));
+        assert!(markup.contains(r#"aria-label="Go to page 4""#));
+        assert_eq!(markup.matches(r#"aria-current="page""#).count(), 1);
+        assert!(markup.contains("Showing 51–100 of 432 albums"));
+        assert!(markup.contains(r#"class="sr-only" aria-live="polite""#));
+        assert!(!markup.contains("aria-disabled"));
+    }
+
+    #[test]
+    fn test_pagination_disables_unavailable_directions() {
+        let first = pagination(&PageInfo { page: 1, page_size: 50, total_items: 120 }, &ARTIST_PAGINATION)
+            .into_string();
+        assert!(first.contains(r#"aria-disabled="true">Previous</span>"#));
+        assert!(!first.contains(r#"rel="prev""#));
+        assert!(first.contains(r#"href="/artists-grid?page=2" rel="next""#));
+
+        let last = pagination(&PageInfo { page: 3, page_size: 50, total_items: 120 }, &PLAYLIST_PAGINATION)
+            .into_string();
+        assert!(last.contains(r#"aria-disabled="true">Next</span>"#));
+        assert!(last.contains("Showing 101–120 of 120 playlists"));
+        assert!(last.contains("hx-target=\"#playlist-grid\""));
+        assert!(!last.contains("hx-include"));
+    }
+
+    #[test]
+    fn test_pagination_single_page_only_summarizes() {
+        let markup = pagination(&PageInfo { page: 1, page_size: 50, total_items: 7 }, &ARTIST_PAGINATION)
+            .into_string();
+        assert!(markup.contains("Showing 1–7 of 7 artists"));
+        assert!(!markup.contains("<a "));
+    }
+
+    #[test]
+    fn test_lidarr_notifications() {
+        let started = lidarr_search_started_notification("daft-punk-discovery", 17).into_string();
+        assert!(started.contains("command #17"));
+        assert!(started.contains(r#"hx-get="/albums/daft-punk-discovery""#));
+        assert!(started.contains("show:#album-downloads:top"));
+
+        let missing = lidarr_album_missing_notification().into_string();
+        assert!(missing.contains("Add to Lidarr and search"));
+        assert!(missing.contains("disabled"));
+    }
+
+    #[test]
+    fn test_board_card_only_offers_allowed_moves() {
+        let card = |status: OwnershipStatus| {
+            board_card(&BoardCardData {
+                id: 7,
+                slug: "daft-punk-discovery".to_string(),
+                title: "Discovery".to_string(),
+                artist_name: "Daft Punk".to_string(),
+                cover_art_url: None,
+                ownership_status: status,
+                wanted_age: None,
+            })
+            .into_string()
+        };
+
+        let wanted = card(OwnershipStatus::NotOwned);
+        assert!(wanted.contains("Ordered →"));
+        assert!(!wanted.contains("←"));
+
+        let ordered = card(OwnershipStatus::Ordered);
+        assert!(ordered.contains("← Wanted"));
+        assert!(ordered.contains("Downloading →"));
+
+        // Downloading can't go back to Ordered, and Owned is final
+        let downloading = card(OwnershipStatus::Downloading);
+        assert!(downloading.contains("Owned →"));
+        assert!(!downloading.contains("←"));
+        assert!(!card(OwnershipStatus::Owned).contains("/board/albums/7/move"));
+    }
+
+
+    fn fixture_playlist(owned_count: i64) -> PlaylistCardData {
+        let synced = chrono::DateTime::parse_from_rfc3339("2024-03-10T07:00:00Z").unwrap();
+        let model = playlists::Model {
+            id: 12,
+            name: "Road Trip".to_string(),
+            spotify_id: "road-trip".to_string(),
+            description: None,
+            owner_name: Some("dj".to_string()),
+            is_collaborative: false,
+            total_tracks: Some(40),
+            cover_image_url: Some("/static/covers/road-trip.jpg".to_string()),
+            snapshot_id: None,
+            is_enabled: true,
+            created_at: synced,
+            updated_at: synced,
+            last_synced_at: Some(synced),
+            is_synthetic: false,
+            owned_count: Some(owned_count as i32),
+            unavailable_tracks: Some(2),
+            is_external: false,
+            sync_failure_count: 0,
+            disabled_reason: None,
+            archived_at: None,
+            episode_count: None,
+        };
+        PlaylistCardData::from_model(model, owned_count, 40, chrono_tz::America::New_York)
+    }
+
+    fn render_snapshot(playlists: &[PlaylistCardData]) -> String {
+        let tracks = || vec![PlaylistTrackData {
+            position: 50,
+            track_name: "Midnight City".to_string(),
+            artist_slug: "m83".to_string(),
+            artist_name: "M83".to_string(),
+            album_id: 3,
+            album_slug: "m83-hurry-up-were-dreaming".to_string(),
+            album_name: "Hurry Up, We're Dreaming".to_string(),
+            duration_ms: Some(243_000),
+            ownership_status: OwnershipStatus::Owned,
+        }];
+        let artist = |pct: f64| ArtistCardData {
+            id: 1,
+            slug: "m83".to_string(),
+            name: "M83".to_string(),
+            image_url: None,
+            album_count: 10,
+            owned_count: (pct / 10.0) as i64,
+            ownership_percentage: pct,
+        };
+
+        let mut out = String::new();
+        for playlist in playlists {
+            out.push_str(&crate::templates::playlist_detail_partial(playlist, tracks(), 2, 3).into_string());
+            out.push('\n');
+            out.push_str(&playlist_card(playlist).into_string());
+            out.push('\n');
+            out.push_str(&playlist_card_oob(playlist).into_string());
+            out.push('\n');
+        }
+        for pct in [90.0, 60.0, 20.0] {
+            out.push_str(&artist_card(&artist(pct)).into_string());
+            out.push('\n');
+            out.push_str(&crate::templates::artist_detail_page(&artist(pct), vec![]).into_string());
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn test_ownership_color_thresholds() {
+        assert_eq!(ownership_color(100.0), OwnershipColor::Green);
+        assert_eq!(ownership_color(80.0), OwnershipColor::Green);
+        assert_eq!(ownership_color(79.9), OwnershipColor::Yellow);
+        assert_eq!(ownership_color(50.0), OwnershipColor::Yellow);
+        assert_eq!(ownership_color(49.9), OwnershipColor::Gray);
+        assert_eq!(ownership_color(0.0), OwnershipColor::Gray);
+
+        assert_eq!(OwnershipColor::Gray.class(&PROGRESS_PALETTE), "bg-gray-400");
+        assert_eq!(OwnershipColor::Yellow.class(&LABEL_PALETTE), "text-yellow-600");
+    }
+
+    #[test]
+    fn test_ownership_percentage() {
+        assert_eq!(ownership_percentage(0, 0), 0.0);
+        assert_eq!(ownership_percentage(33, 40), 82.5);
+        assert_eq!(ownership_percentage(5, 5), 100.0);
+    }
+
+    #[test]
+    fn test_playlist_card_data_from_model() {
+        let card = fixture_playlist(10);
+        assert_eq!(card.track_count, 40);
+        assert_eq!(card.owned_count, 10);
+        assert_eq!(card.ownership_percentage, 25.0);
+        assert_eq!(card.unavailable_count, 2);
+        // Playlists not synced since episode counting began show none
+        assert_eq!(card.episode_count, 0);
+        assert!(card.last_synced.unwrap().into_string().contains("2024-03-10 03:00 EDT"));
+    }
+
+    /// Rendered before the card/stats helpers were extracted; the markup must not drift
+    #[test]
+    fn test_playlist_markup_matches_snapshot() {
+        let rendered = render_snapshot(&[fixture_playlist(33), fixture_playlist(22), fixture_playlist(10)]);
+        assert_eq!(rendered, include_str!("../../tests/fixtures/snapshots/playlist_detail.html"));
+    }
+}
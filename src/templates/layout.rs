@@ -1,9 +1,11 @@
 use maud::{html, Markup, DOCTYPE};
 
+use crate::{i18n, t};
+
 pub fn base_layout(title: &str, content: Markup) -> Markup {
     html! {
         (DOCTYPE)
-        html lang="en" class="h-full" {
+        html lang=(i18n::current().as_str()) class="h-full" {
             head {
                 meta charset="utf-8";
                 meta name="viewport" content="width=device-width, initial-scale=1";
@@ -57,23 +59,32 @@ fn nav_bar() -> Markup {
                     // Navigation links
                     div class="flex space-x-4" {
                         a href="/" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
-                            "Library"
+                            (t!("nav.library"))
                         }
                         a href="/artists" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
-                            "Artists"
+                            (t!("nav.artists"))
+                        }
+                        a href="/board" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
+                            (t!("nav.board"))
                         }
                         a href="/playlists" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
-                            "Playlists"
+                            (t!("nav.playlists"))
                         }
                         a href="/settings" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
-                            "Settings"
+                            (t!("nav.settings"))
                         }
                         a href="/jobs" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
-                            "Jobs"
+                            (t!("nav.jobs"))
                         }
                         a href="/stats" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
-                            "Stats"
+                            (t!("nav.stats"))
                         }
+                        a href="/archive" class="text-gray-700 hover:text-primary px-3 py-2 rounded-md text-sm font-medium" {
+                            (t!("nav.archive"))
+                        }
+
+                        // Profile switcher - loads via HTMX
+                        div hx-get="/api/profiles/selector" hx-trigger="load" {}
                     }
                 }
             }
@@ -86,7 +97,7 @@ fn footer() -> Markup {
         footer class="bg-white border-t border-gray-200 mt-12" {
             div class="container mx-auto px-4 py-6" {
                 div class="text-center text-gray-600 text-sm" {
-                    "Beat Collector - Self-hosted music library management"
+                    (t!("footer.tagline"))
                     " · "
                     a href="https://github.com/yourusername/beat-collector"
                       class="text-primary hover:underline"
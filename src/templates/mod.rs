@@ -1,7 +1,10 @@
 pub mod layout;
 pub mod components;
 pub mod pages;
+pub mod placeholder;
+pub mod time;
 
 pub use layout::*;
 pub use components::*;
 pub use pages::*;
+pub use time::*;
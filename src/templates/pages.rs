@@ -1,13 +1,23 @@
+use chrono_tz::Tz;
 use maud::{html, Markup};
 
 use super::components::{
-    album_card, artist_card, artist_filter_bar, artist_pagination, filter_bar, pagination,
-    playlist_card, playlist_track_row, AlbumCardData, ArtistCardData, PlaylistCardData,
-    PlaylistTrackData,
+    album_card, album_list_row, archived_row, artist_card, artist_options, board_card, board_column_count, board_column_title,
+    format_duration, AlbumDownloadData, AlbumTracksData, BoardColumnData, AlbumListensData, JobRowData, artist_filter_bar, filter_bar, pagination, playlist_card,
+    ownership_color, playlist_stats_bar, playlist_track_row, top_album_row, top_artist_row, AlbumCardData, ArtistCardData, PageInfo,
+    ArchivedRowData, PlaylistCardData, PlaylistTrackData, ScanProposalData, TopAlbumData, TopArtistData, ALBUM_PAGINATION,
+    ARTIST_PAGINATION, LABEL_PALETTE, PLAYLIST_PAGINATION, PROGRESS_PALETTE,
 };
 use super::layout::base_layout;
+use super::placeholder::cover_or_placeholder;
+use crate::db::enums::{AlbumView, OwnershipStatus};
+use crate::services::artist_heatmap::HeatmapArtist;
+use crate::{
+    i18n::{self, Locale},
+    t,
+};
 
-pub fn home_page() -> Markup {
+pub fn home_page(view: AlbumView) -> Markup {
     base_layout(
         "Library",
         html! {
@@ -15,7 +25,7 @@ pub fn home_page() -> Markup {
             div id="notification-area" class="mb-4" {}
 
             // Filter bar
-            (filter_bar())
+            (filter_bar(view))
 
             // Album grid
             div id="album-grid" hx-get="/albums" hx-trigger="load" {
@@ -31,11 +41,7 @@ pub fn home_page() -> Markup {
     )
 }
 
-pub fn album_grid_partial(
-    albums: Vec<AlbumCardData>,
-    page: u64,
-    total_pages: u64,
-) -> Markup {
+pub fn album_grid_partial(albums: Vec<AlbumCardData>, page_info: PageInfo, view: AlbumView) -> Markup {
     html! {
         @if albums.is_empty() {
             div class="text-center py-12" {
@@ -45,14 +51,25 @@ pub fn album_grid_partial(
                 }
             }
         } @else {
-            div class="grid grid-cols-2 md:grid-cols-3 lg:grid-cols-4 xl:grid-cols-5 gap-6" {
-                @for album in albums {
-                    (album_card(&album))
+            @match view {
+                AlbumView::Grid => {
+                    div class="grid grid-cols-2 md:grid-cols-3 lg:grid-cols-4 xl:grid-cols-5 gap-6" {
+                        @for album in &albums {
+                            (album_card(album))
+                        }
+                    }
+                }
+                AlbumView::List => {
+                    div class="bg-white rounded-lg shadow-sm divide-y divide-gray-100" {
+                        @for album in &albums {
+                            (album_list_row(album))
+                        }
+                    }
                 }
             }
 
             // Pagination
-            (pagination(page, total_pages, "/albums"))
+            (pagination(&page_info, &ALBUM_PAGINATION))
         }
     }
 }
@@ -61,7 +78,10 @@ pub fn album_detail_modal(
     album: &AlbumCardData,
     artist_name: &str,
     genres: &Option<Vec<String>>,
-    total_tracks: Option<i32>,
+    tracks: &AlbumTracksData,
+    downloads: &[AlbumDownloadData],
+    listens: Option<&AlbumListensData>,
+    lidarr_artist_override: Option<&str>,
 ) -> Markup {
     html! {
         // Modal backdrop
@@ -88,8 +108,8 @@ pub fn album_detail_modal(
                         // Album cover
                         div class="flex-shrink-0" {
                             img
-                                src={(album.cover_art_url.as_deref().unwrap_or("https://via.placeholder.com/300"))}
-                                alt={(format!("{} cover", album.title))}
+                                src=(cover_or_placeholder(album.cover_art_url.as_deref(), &album.title))
+                                alt=(t!("album.cover_alt", title = album.title))
                                 class="w-full md:w-64 rounded-lg shadow-md";
                         }
 
@@ -97,26 +117,97 @@ pub fn album_detail_modal(
                         div class="flex-grow" {
                             dl class="space-y-4" {
                                 div {
-                                    dt class="text-sm font-medium text-gray-500" { "Artist" }
-                                    dd class="mt-1 text-lg text-gray-900" { (artist_name) }
+                                    dt class="text-sm font-medium text-gray-500" { (t!("album.artist")) }
+                                    dd class="mt-1 text-lg text-gray-900" {
+                                        a
+                                            href={(format!("/artists/{}", album.artist_slug))}
+                                            class="hover:text-primary hover:underline" {
+                                            (artist_name)
+                                        }
+                                    }
+                                    details class="mt-2" {
+                                        summary class="text-sm text-gray-500 cursor-pointer hover:text-gray-700" {
+                                            (t!("album.change_artist"))
+                                        }
+                                        form class="mt-2 space-y-2"
+                                             hx-post={(format!("/albums/{}/artist", album.id))}
+                                             hx-target="#album-detail-modal" {
+                                            input
+                                                type="search"
+                                                name="search"
+                                                placeholder=(t!("album.artist_search_placeholder"))
+                                                autocomplete="off"
+                                                hx-get={(format!("/albums/{}/artist-options", album.id))}
+                                                hx-trigger="input changed delay:300ms"
+                                                hx-target={(format!("#artist-options-{}", album.id))}
+                                                class="w-full px-3 py-2 border border-gray-300 rounded-md text-sm focus:outline-none focus:ring-2 focus:ring-primary";
+                                            select
+                                                id={(format!("artist-options-{}", album.id))}
+                                                name="artist"
+                                                required
+                                                class="w-full px-3 py-2 border border-gray-300 rounded-md text-sm" {
+                                                (artist_options(&[], ""))
+                                            }
+                                            button
+                                                type="submit"
+                                                class="px-3 py-1 bg-gray-700 hover:bg-gray-800 text-white text-sm font-semibold rounded-md" {
+                                                (t!("album.move"))
+                                            }
+                                        }
+                                    }
                                 }
 
                                 @if let Some(date) = &album.release_date {
                                     div {
-                                        dt class="text-sm font-medium text-gray-500" { "Release Date" }
+                                        dt class="text-sm font-medium text-gray-500" { (t!("album.release_date")) }
                                         dd class="mt-1 text-gray-900" { (date) }
                                     }
                                 }
 
-                                @if let Some(tracks) = total_tracks {
+                                @if let Some(total) = tracks.total {
                                     div {
-                                        dt class="text-sm font-medium text-gray-500" { "Tracks" }
-                                        dd class="mt-1 text-gray-900" { (tracks) }
+                                        dt class="text-sm font-medium text-gray-500" { (t!("album.tracks")) }
+                                        dd class="mt-1 text-gray-900" { (i18n::format_number(total.into())) }
+                                    }
+                                }
+
+                                @if !tracks.mismatches.is_empty() {
+                                    div id="album-track-issues" {
+                                        dt class="text-sm font-medium text-gray-500" { (t!("album.track_issues")) }
+                                        dd class="mt-1" {
+                                            span
+                                                class="inline-block px-2 py-1 bg-yellow-100 text-yellow-800 text-xs font-semibold rounded"
+                                                title=(t!("album.length_mismatch_help")) {
+                                                "⚠ " (t!("album.length_mismatch_count", count = tracks.mismatches.len()))
+                                            }
+                                        }
+                                        dd class="mt-2" {
+                                            ul class="space-y-1 text-sm text-gray-900" {
+                                                @for track in &tracks.mismatches {
+                                                    li {
+                                                        span class="text-gray-500" {
+                                                            @if track.disc_number > 1 {
+                                                                (track.disc_number) "-"
+                                                            }
+                                                            (track.track_number) "."
+                                                        }
+                                                        " " (track.title) " "
+                                                        span class="text-gray-500" {
+                                                            (t!(
+                                                                "album.length_mismatch_detail",
+                                                                spotify = format_duration(track.spotify_duration_ms),
+                                                                local = format_duration(track.local_duration_ms),
+                                                            ))
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
                                     }
                                 }
 
                                 div {
-                                    dt class="text-sm font-medium text-gray-500" { "Status" }
+                                    dt class="text-sm font-medium text-gray-500" { (t!("album.status")) }
                                     dd class="mt-1" {
                                         (status_badge_large(&album.ownership_status))
                                     }
@@ -124,24 +215,129 @@ pub fn album_detail_modal(
 
                                 @if let Some(score) = album.match_score {
                                     div {
-                                        dt class="text-sm font-medium text-gray-500" { "MusicBrainz Match" }
-                                        dd class="mt-1 text-gray-900" { (score) "% confidence" }
+                                        dt class="text-sm font-medium text-gray-500" { (t!("album.musicbrainz_match")) }
+                                        dd class="mt-1 text-gray-900" { (t!("album.match_confidence", score = score)) }
                                     }
                                 }
 
-                                @if let Some(genre_list) = genres {
-                                    @if !genre_list.is_empty() {
-                                        div {
-                                            dt class="text-sm font-medium text-gray-500" { "Genres" }
-                                            dd class="mt-1 flex flex-wrap gap-2" {
-                                                @for genre in genre_list {
-                                                    span class="px-2 py-1 bg-gray-100 text-gray-700 text-sm rounded" {
-                                                        (genre)
-                                                    }
+                                @if let Some(listens) = listens {
+                                    div {
+                                        dt class="text-sm font-medium text-gray-500" { (t!("album.listening")) }
+                                        dd class="mt-1 text-gray-900" {
+                                            (t!("album.plays_last", count = i18n::format_number(listens.count.into()))) " " (listens.last_listened)
+                                        }
+                                        dd class="text-sm text-gray-500" {
+                                            (t!("album.first_played")) " " (listens.first_listened)
+                                        }
+                                    }
+                                }
+
+                                @let genre_list = genres.as_deref().unwrap_or_default();
+                                div {
+                                    dt class="text-sm font-medium text-gray-500" { (t!("album.genres")) }
+                                    dd class="mt-1 flex flex-wrap gap-2" {
+                                        @if genre_list.is_empty() {
+                                            span class="text-sm text-gray-500" { (t!("album.genres_none")) }
+                                        }
+                                        @for (i, genre) in genre_list.iter().enumerate() {
+                                            span class="inline-flex items-center gap-1 px-2 py-1 bg-gray-100 text-gray-700 text-sm rounded" {
+                                                (genre)
+                                                @let rest: Vec<&str> = genre_list.iter().enumerate()
+                                                    .filter(|(j, _)| *j != i)
+                                                    .map(|(_, g)| g.as_str())
+                                                    .collect();
+                                                button
+                                                    type="button"
+                                                    title=(t!("album.genre_remove", genre = genre))
+                                                    class="text-gray-400 hover:text-gray-600"
+                                                    hx-post={(format!("/albums/{}/genres", album.id))}
+                                                    hx-vals=(serde_json::json!({ "genres": rest.join(", ") }).to_string())
+                                                    hx-target="#album-detail-modal" {
+                                                    "×"
                                                 }
                                             }
                                         }
                                     }
+                                    details class="mt-2" {
+                                        summary class="text-sm text-gray-500 cursor-pointer hover:text-gray-700" {
+                                            (t!("album.genres_edit"))
+                                        }
+                                        form class="mt-2 flex gap-2"
+                                             hx-post={(format!("/albums/{}/genres", album.id))}
+                                             hx-target="#album-detail-modal" {
+                                            input
+                                                type="text"
+                                                name="genres"
+                                                value=(genre_list.join(", "))
+                                                placeholder=(t!("album.genres_placeholder"))
+                                                autocomplete="off"
+                                                class="flex-grow px-3 py-2 border border-gray-300 rounded-md text-sm focus:outline-none focus:ring-2 focus:ring-primary";
+                                            button
+                                                type="submit"
+                                                class="px-3 py-1 bg-gray-700 hover:bg-gray-800 text-white text-sm font-semibold rounded-md" {
+                                                (t!("common.save"))
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Lidarr downloads
+                    div id="album-downloads" class="mt-6 pt-6 border-t" {
+                        h3 class="text-sm font-medium text-gray-500" { (t!("album.downloads")) }
+                        @if downloads.is_empty() {
+                            p class="mt-1 text-gray-500 text-sm" { (t!("album.downloads_none")) }
+                        } @else {
+                            ul class="mt-2 space-y-1 text-sm" {
+                                @for download in downloads {
+                                    li class="flex flex-wrap gap-2 text-gray-900" {
+                                        span class="font-semibold" { (download.status) }
+                                        @if let Some(id) = &download.download_id {
+                                            span class="text-gray-500" { (id) }
+                                        }
+                                        span class="text-gray-500" { (download.created_at) }
+                                        @if download.retry_count > 0 {
+                                            span class="text-gray-500" {
+                                                (t!("album.download_retried", count = download.retry_count))
+                                            }
+                                        }
+                                        @if let Some(error) = &download.error_message {
+                                            span class="text-red-600" { (error) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        // Lidarr artist override
+                        details class="mt-4" open[lidarr_artist_override.is_some()] {
+                            summary class="text-sm text-gray-500 cursor-pointer hover:text-gray-700" {
+                                @if let Some(mbid) = lidarr_artist_override {
+                                    (t!("album.lidarr_artist", mbid = mbid))
+                                } @else {
+                                    (t!("album.lidarr_artist_change"))
+                                }
+                            }
+                            form class="mt-2 space-y-2"
+                                 hx-post={(format!("/albums/{}/lidarr-artist", album.id))}
+                                 hx-target="#album-detail-modal" {
+                                input
+                                    type="text"
+                                    name="mbid"
+                                    value=[lidarr_artist_override]
+                                    placeholder=(t!("album.lidarr_artist_placeholder"))
+                                    autocomplete="off"
+                                    hx-get={(format!("/albums/{}/lidarr-artist-preview", album.id))}
+                                    hx-trigger="input changed delay:500ms"
+                                    hx-target={(format!("#lidarr-artist-preview-{}", album.id))}
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md text-sm font-mono focus:outline-none focus:ring-2 focus:ring-primary";
+                                div id={(format!("lidarr-artist-preview-{}", album.id))} class="text-sm" {}
+                                button
+                                    type="submit"
+                                    class="px-3 py-1 bg-gray-700 hover:bg-gray-800 text-white text-sm font-semibold rounded-md" {
+                                    (t!("common.save"))
                                 }
                             }
                         }
@@ -154,7 +350,7 @@ pub fn album_detail_modal(
                             hx-post={(format!("/api/albums/{}/search-lidarr", album.id))}
                             hx-target="#notification-area"
                             hx-swap="innerHTML" {
-                            "Search in Lidarr"
+                            (t!("album.search_lidarr"))
                         }
 
                         button
@@ -162,7 +358,7 @@ pub fn album_detail_modal(
                             hx-post={(format!("/api/albums/{}/match", album.id))}
                             hx-target="#notification-area"
                             hx-swap="innerHTML" {
-                            "Re-match MusicBrainz"
+                            (t!("album.rematch"))
                         }
 
                         @if let Some(source_artist) = artist_name.split(" feat.").next() {
@@ -172,19 +368,23 @@ pub fn album_detail_modal(
                                     urlencoding::encode(&album.title)))}
                                 target="_blank"
                                 class="px-4 py-2 bg-gray-700 hover:bg-gray-800 text-white font-semibold rounded-md" {
-                                "Search on Bandcamp"
+                                (t!("album.search_bandcamp"))
                             }
                         }
 
                         button
                             class="px-4 py-2 bg-green-500 hover:bg-green-600 text-white font-semibold rounded-md"
-                            hx-patch={(format!("/api/albums/{}", album.id))}
+                            hx-patch={(format!("/api/albums/{}?card=true", album.id))}
                             hx-vals=r#"{"ownership_status": "owned", "acquisition_source": "manual"}"#
-                            hx-target="#notification-area"
-                            hx-swap="innerHTML" {
-                            "Mark as Owned"
+                            hx-swap="none" {
+                            (t!("album.mark_owned"))
                         }
                     }
+
+                    // Suggestions from related artists, loaded separately as they may call Spotify
+                    @if album.ownership_status == OwnershipStatus::Owned {
+                        div hx-get={(format!("/albums/{}/suggestions", album.slug))} hx-trigger="load" {}
+                    }
                 }
             }
         }
@@ -195,9 +395,10 @@ fn status_badge_large(status: &crate::db::OwnershipStatus) -> Markup {
     use crate::db::OwnershipStatus;
 
     let (text, color) = match status {
-        OwnershipStatus::Owned => ("Owned", "bg-green-100 text-green-800"),
-        OwnershipStatus::NotOwned => ("Not Owned", "bg-gray-100 text-gray-800"),
-        OwnershipStatus::Downloading => ("Downloading", "bg-blue-100 text-blue-800"),
+        OwnershipStatus::Owned => (t!("status.owned"), "bg-green-100 text-green-800"),
+        OwnershipStatus::NotOwned => (t!("status.not_owned"), "bg-gray-100 text-gray-800"),
+        OwnershipStatus::Downloading => (t!("status.downloading"), "bg-blue-100 text-blue-800"),
+        OwnershipStatus::Ordered => (t!("status.ordered"), "bg-purple-100 text-purple-800"),
     };
 
     html! {
@@ -210,40 +411,46 @@ fn status_badge_large(status: &crate::db::OwnershipStatus) -> Markup {
 pub fn settings_page(
     lidarr_url: Option<String>,
     music_folder: Option<String>,
+    path_mismatches: &[String],
+    locale: Locale,
 ) -> Markup {
     base_layout(
-        "Settings",
+        &t!("settings.title"),
         html! {
             div id="notification-area" class="mb-4" {}
 
             div class="max-w-3xl mx-auto" {
-                h1 class="text-3xl font-bold text-gray-900 mb-8" { "Settings" }
+                h1 class="text-3xl font-bold text-gray-900 mb-8" { (t!("settings.title")) }
+
+                @if !path_mismatches.is_empty() {
+                    (path_mismatch_banner(path_mismatches, music_folder.as_deref()))
+                }
 
                 // Spotify connection
                 div class="bg-white rounded-lg shadow-sm p-6 mb-6" {
-                    h2 class="text-xl font-semibold mb-4" { "Spotify Connection" }
+                    h2 class="text-xl font-semibold mb-4" { (t!("settings.spotify")) }
 
                     p class="text-gray-600 mb-4" {
-                        "Connect your Spotify account to import your music library."
+                        (t!("settings.spotify_help"))
                     }
 
                     // Dynamic Spotify button - checks auth status and shows appropriate action
                     div hx-get="/api/auth/spotify/button" hx-trigger="load" {
                         button class="px-4 py-2 bg-gray-300 text-gray-600 font-semibold rounded-md" disabled {
-                            "Checking connection..."
+                            (t!("settings.spotify_checking"))
                         }
                     }
                 }
 
                 // Lidarr settings
                 div class="bg-white rounded-lg shadow-sm p-6 mb-6" {
-                    h2 class="text-xl font-semibold mb-4" { "Lidarr Integration" }
+                    h2 class="text-xl font-semibold mb-4" { (t!("settings.lidarr")) }
 
                     form hx-put="/api/settings" hx-target="#notification-area" {
                         div class="space-y-4" {
                             div {
                                 label class="block text-sm font-medium text-gray-700 mb-2" {
-                                    "Lidarr URL"
+                                    (t!("settings.lidarr_url"))
                                 }
                                 input
                                     type="url"
@@ -255,12 +462,12 @@ pub fn settings_page(
 
                             div {
                                 label class="block text-sm font-medium text-gray-700 mb-2" {
-                                    "Lidarr API Key"
+                                    (t!("settings.lidarr_api_key"))
                                 }
                                 input
                                     type="password"
                                     name="lidarr_api_key"
-                                    placeholder="Your API key from Lidarr settings"
+                                    placeholder=(t!("settings.lidarr_api_key_placeholder"))
                                     class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-primary";
                             }
 
@@ -268,7 +475,7 @@ pub fn settings_page(
                                 button
                                     type="submit"
                                     class="px-4 py-2 bg-primary hover:bg-green-600 text-white font-semibold rounded-md" {
-                                    "Save Settings"
+                                    (t!("settings.save"))
                                 }
 
                                 button
@@ -276,7 +483,7 @@ pub fn settings_page(
                                     class="px-4 py-2 bg-gray-200 hover:bg-gray-300 text-gray-700 font-semibold rounded-md"
                                     hx-post="/api/settings/test-lidarr"
                                     hx-target="#notification-area" {
-                                    "Test Connection"
+                                    (t!("settings.test_connection"))
                                 }
                             }
                         }
@@ -284,14 +491,14 @@ pub fn settings_page(
                 }
 
                 // Music folder settings
-                div class="bg-white rounded-lg shadow-sm p-6" {
-                    h2 class="text-xl font-semibold mb-4" { "Music Folder" }
+                div class="bg-white rounded-lg shadow-sm p-6 mb-6" {
+                    h2 class="text-xl font-semibold mb-4" { (t!("settings.music_folder")) }
 
                     form hx-put="/api/settings" hx-target="#notification-area" {
                         div class="space-y-4" {
                             div {
                                 label class="block text-sm font-medium text-gray-700 mb-2" {
-                                    "Local Music Directory"
+                                    (t!("settings.music_directory"))
                                 }
                                 input
                                     type="text"
@@ -300,14 +507,85 @@ pub fn settings_page(
                                     placeholder="/path/to/your/music"
                                     class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-primary";
                                 p class="mt-2 text-sm text-gray-500" {
-                                    "Path to your local music folder (e.g., /music or /home/user/Music)"
+                                    (t!("settings.music_folder_help"))
+                                }
+                            }
+
+                            button
+                                type="submit"
+                                class="px-4 py-2 bg-primary hover:bg-green-600 text-white font-semibold rounded-md" {
+                                (t!("settings.save_path"))
+                            }
+                        }
+                    }
+                }
+
+                // Language
+                div class="bg-white rounded-lg shadow-sm p-6 mb-6" {
+                    h2 class="text-xl font-semibold mb-4" { (t!("settings.language")) }
+
+                    form hx-post="/settings/locale" hx-target="#notification-area" {
+                        div class="space-y-4" {
+                            div {
+                                select
+                                    name="locale"
+                                    aria-label=(t!("settings.language"))
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-primary" {
+                                    @for option in Locale::ALL {
+                                        option value=(option.as_str()) selected[option == locale] { (option.native_name()) }
+                                    }
+                                }
+                                p class="mt-2 text-sm text-gray-500" {
+                                    (t!("settings.language_help"))
                                 }
                             }
 
                             button
                                 type="submit"
                                 class="px-4 py-2 bg-primary hover:bg-green-600 text-white font-semibold rounded-md" {
-                                "Save Path"
+                                (t!("common.save"))
+                            }
+                        }
+                    }
+                }
+
+                // Maintenance
+                div class="bg-white rounded-lg shadow-sm p-6" {
+                    h2 class="text-xl font-semibold mb-4" { (t!("settings.maintenance")) }
+
+                    p class="text-gray-600 mb-4" {
+                        (t!("settings.maintenance_help"))
+                    }
+
+                    a
+                        href="/api/playlists/settings-export"
+                        download="playlist-settings.json"
+                        class="inline-block px-4 py-2 bg-gray-200 hover:bg-gray-300 text-gray-700 font-semibold rounded-md" {
+                        (t!("settings.export_playlists"))
+                    }
+
+                    form class="mt-6" hx-post="/api/playlists/settings-import" hx-target="#notification-area" {
+                        div class="space-y-4" {
+                            div {
+                                label for="playlist-settings-import" class="block text-sm font-medium text-gray-700 mb-2" {
+                                    (t!("settings.import_label"))
+                                }
+                                textarea
+                                    id="playlist-settings-import"
+                                    name="settings"
+                                    rows="4"
+                                    required
+                                    placeholder=(t!("settings.import_placeholder"))
+                                    class="w-full px-3 py-2 border border-gray-300 rounded-md font-mono text-sm focus:outline-none focus:ring-2 focus:ring-primary" {}
+                                p class="mt-2 text-sm text-gray-500" {
+                                    (t!("settings.import_help"))
+                                }
+                            }
+
+                            button
+                                type="submit"
+                                class="px-4 py-2 bg-primary hover:bg-green-600 text-white font-semibold rounded-md" {
+                                (t!("settings.import_playlists"))
                             }
                         }
                     }
@@ -317,14 +595,50 @@ pub fn settings_page(
     )
 }
 
+/// Warning that Lidarr reports albums outside the music folder
+fn path_mismatch_banner(prefixes: &[String], music_folder: Option<&str>) -> Markup {
+    html! {
+        div class="bg-amber-50 border border-amber-300 text-amber-900 rounded-lg p-4 mb-6" role="alert" {
+            p class="font-semibold" { (t!("settings.path_mismatch")) }
+            p class="text-sm mt-1" {
+                (t!("settings.path_mismatch_reported")) " "
+                @for (i, prefix) in prefixes.iter().enumerate() {
+                    @if i > 0 { ", " }
+                    code { (prefix) }
+                }
+                @if let Some(folder) = music_folder {
+                    ", " (t!("settings.path_mismatch_folder")) " " code { (folder) }
+                }
+                "."
+            }
+            p class="text-sm mt-1" {
+                (t!("settings.path_mapping_hint")) " "
+                code { (format!("{{\"{}\": \"{}\"}}", prefixes[0], music_folder.unwrap_or("/music"))) }
+                " " (t!("settings.path_mapping_setting_before")) " "
+                code { "path_mappings" }
+                " " (t!("settings.path_mapping_setting_after"))
+            }
+        }
+    }
+}
+
 pub fn jobs_page() -> Markup {
     base_layout(
         "Jobs",
         html! {
             div class="max-w-5xl mx-auto" {
-                h1 class="text-3xl font-bold text-gray-900 mb-8" { "Background Jobs" }
+                div class="flex items-center justify-between mb-8" {
+                    h1 class="text-3xl font-bold text-gray-900" { "Background Jobs" }
+                    button
+                        hx-post="/api/jobs/filesystem-scan?dry_run=true"
+                        hx-swap="none"
+                        title="Match the music folder against the library without changing anything"
+                        class="px-4 py-2 bg-white border border-gray-300 hover:bg-gray-50 text-gray-700 font-semibold rounded-md" {
+                        "Preview Folder Scan"
+                    }
+                }
 
-                div id="jobs-list" hx-get="/api/jobs" hx-trigger="load, every 5s" {
+                div id="jobs-list" hx-get="/jobs-list" hx-trigger="load, every 5s" {
                     div class="flex justify-center py-12" {
                         div class="animate-spin rounded-full h-12 w-12 border-b-2 border-primary" {}
                     }
@@ -334,6 +648,151 @@ pub fn jobs_page() -> Markup {
     )
 }
 
+/// Rows of the jobs table, newest first
+pub fn jobs_list_partial(jobs: &[JobRowData]) -> Markup {
+    html! {
+        @if jobs.is_empty() {
+            p class="text-center text-gray-500 py-12" { "No jobs yet." }
+        } @else {
+            div class="bg-white rounded-lg shadow-sm overflow-hidden" {
+                table class="min-w-full divide-y divide-gray-200 text-sm" {
+                    thead class="bg-gray-50" {
+                        tr {
+                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Job" }
+                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Status" }
+                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Progress" }
+                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Created" }
+                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Completed" }
+                        }
+                    }
+                    tbody class="divide-y divide-gray-200" {
+                        @for job in jobs {
+                            tr {
+                                td class="px-4 py-2 text-gray-900" {
+                                    a href={(format!("/jobs/{}", job.id))} class="hover:text-primary hover:underline" {
+                                        (job.job_type) " #" (job.id)
+                                    }
+                                }
+                                td class="px-4 py-2" {
+                                    (job.status)
+                                    @if let Some(error) = &job.error_message {
+                                        p class="text-red-600" { (error) }
+                                    }
+                                }
+                                td class="px-4 py-2 text-gray-700" {
+                                    @if let Some(progress) = job.progress { (progress) "%" } @else { "—" }
+                                    @if let Some(detail) = &job.detail {
+                                        p class="text-xs text-gray-500" { (detail) }
+                                    }
+                                }
+                                td class="px-4 py-2 text-gray-700" { (job.created) }
+                                td class="px-4 py-2 text-gray-700" {
+                                    @if let Some(completed) = &job.completed { (completed) } @else { "—" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One job's details; a scan preview also lists its proposed matches for review
+pub fn job_detail_page(job: &JobRowData, proposals: Option<&[ScanProposalData]>, threshold: u32) -> Markup {
+    base_layout(
+        &format!("Job #{}", job.id),
+        html! {
+            div class="max-w-5xl mx-auto" {
+                div id="notification-area" class="mb-4" {}
+
+                a href="/jobs" class="text-sm text-gray-500 hover:text-gray-700" { "← All jobs" }
+                h1 class="text-3xl font-bold text-gray-900 mt-2 mb-6" { (job.job_type) " #" (job.id) }
+
+                dl class="bg-white rounded-lg shadow-sm p-6 grid grid-cols-2 gap-4 text-sm" {
+                    div {
+                        dt class="font-medium text-gray-500" { "Status" }
+                        dd class="mt-1 text-gray-900" {
+                            (job.status)
+                            @if let Some(error) = &job.error_message {
+                                p class="text-red-600" { (error) }
+                            }
+                        }
+                    }
+                    div {
+                        dt class="font-medium text-gray-500" { "Progress" }
+                        dd class="mt-1 text-gray-900" {
+                            @if let Some(progress) = job.progress { (progress) "%" } @else { "—" }
+                            @if let Some(detail) = &job.detail {
+                                p class="text-xs text-gray-500" { (detail) }
+                            }
+                        }
+                    }
+                    div {
+                        dt class="font-medium text-gray-500" { "Created" }
+                        dd class="mt-1 text-gray-900" { (job.created) }
+                    }
+                    div {
+                        dt class="font-medium text-gray-500" { "Completed" }
+                        dd class="mt-1 text-gray-900" {
+                            @if let Some(completed) = &job.completed { (completed) } @else { "—" }
+                        }
+                    }
+                }
+
+                @if let Some(proposals) = proposals {
+                    h2 class="text-2xl font-bold text-gray-900 mt-8 mb-4" { "Proposed Matches" }
+                    @if proposals.is_empty() {
+                        p class="text-gray-500" { "The scan found no folders matching albums in your library." }
+                    } @else {
+                        p class="text-sm text-gray-500 mb-4" {
+                            "This scan changed nothing. Accepted matches are marked owned with their folder; "
+                            "matches below " (threshold) "% confidence start out unchecked."
+                        }
+                        form hx-post={(format!("/jobs/{}/apply-scan", job.id))} hx-target="#notification-area" {
+                            div class="bg-white rounded-lg shadow-sm overflow-hidden" {
+                                table class="min-w-full divide-y divide-gray-200 text-sm" {
+                                    thead class="bg-gray-50" {
+                                        tr {
+                                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Accept" }
+                                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Folder" }
+                                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Album" }
+                                            th class="px-4 py-2 text-left font-medium text-gray-500" { "Confidence" }
+                                        }
+                                    }
+                                    tbody class="divide-y divide-gray-200" {
+                                        @for proposal in proposals {
+                                            tr {
+                                                td class="px-4 py-2" {
+                                                    input
+                                                        type="checkbox"
+                                                        name={"accept_" (proposal.album_id)}
+                                                        checked[proposal.accepted]
+                                                        class="rounded border-gray-300";
+                                                }
+                                                td class="px-4 py-2 font-mono text-xs text-gray-700" { (proposal.directory) }
+                                                td class="px-4 py-2 text-gray-900" {
+                                                    (proposal.artist_name) " – " (proposal.album_title)
+                                                }
+                                                td class="px-4 py-2 text-gray-700" { (proposal.confidence) "%" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            button
+                                type="submit"
+                                class="mt-4 px-4 py-2 bg-primary hover:bg-green-600 text-white font-semibold rounded-md" {
+                                "Apply Accepted Matches"
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
 pub fn stats_page() -> Markup {
     base_layout(
         "Statistics",
@@ -346,6 +805,218 @@ pub fn stats_page() -> Markup {
                         div class="animate-spin rounded-full h-12 w-12 border-b-2 border-primary" {}
                     }
                 }
+
+                // Spotify top artists/albums, loaded separately since it may call Spotify
+                h2 class="text-2xl font-bold text-gray-900 mt-12 mb-4" { "Heavy Rotation" }
+                div id="heavy-rotation" hx-get="/stats/heavy-rotation" hx-trigger="load" {
+                    div class="flex justify-center py-12" {
+                        div class="animate-spin rounded-full h-12 w-12 border-b-2 border-primary" {}
+                    }
+                }
+
+                // Album ownership of the artists with the most albums
+                h2 class="text-2xl font-bold text-gray-900 mt-12 mb-4" { "Collection Heatmap" }
+                div id="artist-heatmap" hx-get="/stats/artist-heatmap" hx-trigger="load" {
+                    div class="flex justify-center py-12" {
+                        div class="animate-spin rounded-full h-12 w-12 border-b-2 border-primary" {}
+                    }
+                }
+
+                // Album detail modal (for clicking on top albums and heatmap cells)
+                div id="album-detail-modal" {}
+            }
+        },
+    )
+}
+
+/// Top artists and albums from Spotify, annotated with ownership
+pub fn heavy_rotation_panel(artists: &[TopArtistData], albums: &[TopAlbumData]) -> Markup {
+    html! {
+        @if artists.is_empty() && albums.is_empty() {
+            (heavy_rotation_message("Spotify doesn't have enough listening history yet."))
+        } @else {
+            div class="grid grid-cols-1 md:grid-cols-2 gap-6" {
+                div class="bg-white rounded-lg shadow-md p-6" {
+                    h3 class="text-lg font-semibold text-gray-900 mb-2" { "Top Artists" }
+                    ul class="divide-y divide-gray-100" {
+                        @for artist in artists {
+                            (top_artist_row(artist))
+                        }
+                    }
+                }
+                div class="bg-white rounded-lg shadow-md p-6" {
+                    h3 class="text-lg font-semibold text-gray-900 mb-2" { "Top Albums" }
+                    ul class="divide-y divide-gray-100" {
+                        @for album in albums {
+                            (top_album_row(album))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn heavy_rotation_message(message: &str) -> Markup {
+    html! {
+        div class="bg-white rounded-lg shadow-md p-6 text-center text-gray-600" { (message) }
+    }
+}
+
+/// Cell color of an album in the artist heatmap
+fn heatmap_cell_class(status: OwnershipStatus) -> &'static str {
+    match status {
+        OwnershipStatus::Owned => "bg-green-500",
+        OwnershipStatus::NotOwned => "bg-gray-300",
+        OwnershipStatus::Downloading => "bg-blue-500",
+        OwnershipStatus::Ordered => "bg-purple-500",
+    }
+}
+
+/// One row of album cells per artist, most albums first
+pub fn artist_heatmap_panel(artists: &[HeatmapArtist]) -> Markup {
+    let legend = [
+        (OwnershipStatus::Owned, "Owned"),
+        (OwnershipStatus::Downloading, "Downloading"),
+        (OwnershipStatus::Ordered, "Ordered"),
+        (OwnershipStatus::NotOwned, "Not owned"),
+    ];
+
+    html! {
+        @if artists.is_empty() {
+            div class="bg-white rounded-lg shadow-md p-6 text-center text-gray-600" {
+                "Sync your Spotify library to see how complete each artist is."
+            }
+        } @else {
+            div class="bg-white rounded-lg shadow-md p-6" {
+                div class="flex flex-wrap gap-4 mb-4 text-xs text-gray-600" {
+                    @for (status, label) in legend {
+                        span class="flex items-center gap-1" {
+                            span class=(format!("inline-block w-3 h-3 rounded-sm {}", heatmap_cell_class(status))) {}
+                            (label)
+                        }
+                    }
+                }
+                div class="space-y-2" {
+                    @for artist in artists {
+                        div class="flex items-start gap-3" {
+                            a href={(format!("/artists/{}", artist.slug))}
+                              class="w-40 flex-shrink-0 truncate text-sm text-gray-900 hover:text-primary hover:underline"
+                              title=(artist.name) {
+                                (artist.name)
+                            }
+                            div class="flex flex-wrap gap-1" {
+                                @for album in &artist.albums {
+                                    @let label = match &album.release_date {
+                                        Some(date) => format!("{} ({})", album.title, &date[..date.len().min(4)]),
+                                        None => album.title.clone(),
+                                    };
+                                    button
+                                        class=(format!("w-4 h-4 rounded-sm hover:ring-2 hover:ring-primary {}", heatmap_cell_class(album.status())))
+                                        title=(label)
+                                        aria-label=(label)
+                                        hx-get={(format!("/albums/{}", album.slug))}
+                                        hx-target="#album-detail-modal"
+                                        hx-swap="innerHTML" {}
+                                }
+                                @if artist.hidden_albums > 0 {
+                                    span class="text-xs text-gray-500" { "+" (artist.hidden_albums) " more" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Shown when the Spotify token was granted before top items were requested
+pub fn heavy_rotation_reauth() -> Markup {
+    html! {
+        div class="bg-white rounded-lg shadow-md p-6 text-center" {
+            p class="text-gray-600 mb-4" {
+                "Reconnect Spotify to allow Beat Collector to read your top artists and tracks."
+            }
+            button
+                class="px-4 py-2 bg-green-500 hover:bg-green-600 text-white font-semibold rounded-md"
+                hx-get="/api/auth/spotify/authorize"
+                hx-swap="none" {
+                "Reconnect Spotify"
+            }
+        }
+    }
+}
+
+/// Want-list board: one column per ownership state
+pub fn board_page(columns: &[BoardColumnData]) -> Markup {
+    base_layout(
+        "Board",
+        html! {
+            h1 class="text-3xl font-bold text-gray-900 mb-8" { "Want List" }
+
+            div class="grid grid-cols-1 md:grid-cols-4 gap-4" {
+                @for column in columns {
+                    section class="bg-gray-100 rounded-lg p-3" {
+                        div class="flex justify-between items-center mb-3" {
+                            h2 class="font-semibold text-gray-800" { (board_column_title(column.status)) }
+                            (board_column_count(column.status, column.count, false))
+                        }
+                        div id={(format!("board-cards-{}", column.status.as_str()))} class="space-y-2" {
+                            @for card in &column.cards {
+                                (board_card(card))
+                            }
+                        }
+                        @let hidden = column.count.saturating_sub(column.cards.len() as u64);
+                        @if hidden > 0 {
+                            p class="text-xs text-gray-500 mt-3 text-center" { "and " (hidden) " more" }
+                        }
+                    }
+                }
+            }
+
+            // Album detail modal (populated by HTMX)
+            div id="album-detail-modal" {}
+        },
+    )
+}
+
+/// Albums, playlists and Liked Songs tracks that disappeared from Spotify
+pub fn archive_page(
+    albums: &[ArchivedRowData],
+    playlists: &[ArchivedRowData],
+    tracks: &[ArchivedRowData],
+    tz: Tz,
+) -> Markup {
+    let section = |title: &str, kind: &str, empty: &str, rows: &[ArchivedRowData]| {
+        html! {
+            section class="bg-white rounded-lg shadow-sm p-6 mb-6" {
+                h2 class="text-xl font-semibold text-gray-900 mb-2" { (title) }
+                @if rows.is_empty() {
+                    p class="text-sm text-gray-500" { (empty) }
+                } @else {
+                    ul class="divide-y divide-gray-100" {
+                        @for row in rows {
+                            (archived_row(kind, row, tz))
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    base_layout(
+        "Archive",
+        html! {
+            div class="max-w-4xl mx-auto" {
+                h1 class="text-3xl font-bold text-gray-900 mb-2" { "Archive" }
+                p class="text-gray-600 mb-8" {
+                    "Items that disappeared from Spotify. Restoring one keeps it in the library."
+                }
+
+                (section("Albums", "albums", "No un-saved albums.", albums))
+                (section("Playlists", "playlists", "No unfollowed playlists.", playlists))
+                (section("Liked Songs", "tracks", "No tracks removed from Liked Songs.", tracks))
             }
         },
     )
@@ -388,11 +1059,7 @@ pub fn playlists_page() -> Markup {
     )
 }
 
-pub fn playlist_grid_partial(
-    playlists: Vec<PlaylistCardData>,
-    page: u64,
-    total_pages: u64,
-) -> Markup {
+pub fn playlist_grid_partial(playlists: Vec<PlaylistCardData>, page_info: PageInfo) -> Markup {
     html! {
         @if playlists.is_empty() {
             div class="text-center py-12" {
@@ -409,7 +1076,7 @@ pub fn playlist_grid_partial(
             }
 
             // Pagination
-            (playlist_pagination(page, total_pages, "/playlists-grid"))
+            (pagination(&page_info, &PLAYLIST_PAGINATION))
         }
     }
 }
@@ -451,6 +1118,9 @@ pub fn playlist_detail_partial(
                             @if let Some(owner) = &playlist.owner_name {
                                 p class="text-sm text-gray-600" { "by " (owner) }
                             }
+                            @if let Some(reason) = playlist.disabled_reason.as_deref().filter(|_| !playlist.is_enabled) {
+                                p class="text-sm text-red-600" { (reason) ". Enable it to try again." }
+                            }
                         }
                     }
 
@@ -475,23 +1145,7 @@ pub fn playlist_detail_partial(
                     }
                 }
 
-                // Stats bar
-                div class="px-6 py-3 bg-gray-50 border-b flex-shrink-0 text-sm" {
-                    span class="text-gray-500" { "Tracks: " }
-                    span class="font-semibold" { (playlist.track_count) }
-                    span class="text-gray-300 mx-3" { "|" }
-                    span class="text-gray-500" { "Owned: " }
-                    span class="font-semibold text-green-600" { (playlist.owned_count) }
-                    span class="text-gray-300 mx-3" { "|" }
-                    span class="text-gray-500" { "Ownership: " }
-                    span class=(format!("font-semibold {}",
-                        if playlist.ownership_percentage >= 80.0 { "text-green-600" }
-                        else if playlist.ownership_percentage >= 50.0 { "text-yellow-600" }
-                        else { "text-gray-600" }
-                    )) {
-                        (format!("{:.1}%", playlist.ownership_percentage))
-                    }
-                }
+                (playlist_stats_bar(playlist))
 
                 // Track list
                 div class="overflow-y-auto flex-grow min-h-0" {
@@ -512,7 +1166,7 @@ pub fn playlist_detail_partial(
                             }
                             tbody id="playlist-tracks-body" class="divide-y divide-gray-200" {
                                 @for track in &tracks {
-                                    (playlist_track_row(track))
+                                    (playlist_track_row(track, playlist.id))
                                 }
                             }
                         }
@@ -561,47 +1215,6 @@ pub fn playlist_detail_partial(
     }
 }
 
-fn playlist_pagination(page: u64, total_pages: u64, base_url: &str) -> Markup {
-    html! {
-        div class="flex justify-center items-center space-x-2 mt-8" {
-            // Previous button
-            @if page > 1 {
-                button
-                    class="px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
-                    hx-get={(format!("{}?page={}", base_url, page - 1))}
-                    hx-target="#playlist-grid"
-                    hx-swap="innerHTML" {
-                    "Previous"
-                }
-            } @else {
-                button class="px-4 py-2 bg-gray-100 border border-gray-300 rounded-md text-gray-400 cursor-not-allowed" disabled {
-                    "Previous"
-                }
-            }
-
-            // Page indicator
-            span class="px-4 py-2 text-gray-600" {
-                "Page " (page) " of " (total_pages)
-            }
-
-            // Next button
-            @if page < total_pages {
-                button
-                    class="px-4 py-2 bg-white border border-gray-300 rounded-md hover:bg-gray-50"
-                    hx-get={(format!("{}?page={}", base_url, page + 1))}
-                    hx-target="#playlist-grid"
-                    hx-swap="innerHTML" {
-                    "Next"
-                }
-            } @else {
-                button class="px-4 py-2 bg-gray-100 border border-gray-300 rounded-md text-gray-400 cursor-not-allowed" disabled {
-                    "Next"
-                }
-            }
-        }
-    }
-}
-
 // Artist pages
 
 pub fn artists_page() -> Markup {
@@ -634,11 +1247,7 @@ pub fn artists_page() -> Markup {
     )
 }
 
-pub fn artist_grid_partial(
-    artists: Vec<ArtistCardData>,
-    page: u64,
-    total_pages: u64,
-) -> Markup {
+pub fn artist_grid_partial(artists: Vec<ArtistCardData>, page_info: PageInfo) -> Markup {
     html! {
         @if artists.is_empty() {
             div class="text-center py-12" {
@@ -655,9 +1264,7 @@ pub fn artist_grid_partial(
             }
 
             // Pagination
-            @if total_pages > 1 {
-                (artist_pagination(page, total_pages, "/artists-grid"))
-            }
+            (pagination(&page_info, &ARTIST_PAGINATION))
         }
     }
 }
@@ -667,13 +1274,8 @@ pub fn artist_detail_page(
     albums: Vec<AlbumCardData>,
 ) -> Markup {
     let progress_width = artist.ownership_percentage.min(100.0).max(0.0);
-    let progress_color = if artist.ownership_percentage >= 80.0 {
-        "bg-green-500"
-    } else if artist.ownership_percentage >= 50.0 {
-        "bg-yellow-500"
-    } else {
-        "bg-gray-400"
-    };
+    let color = ownership_color(artist.ownership_percentage);
+    let progress_color = color.class(&PROGRESS_PALETTE);
 
     base_layout(
         &artist.name,
@@ -703,11 +1305,7 @@ pub fn artist_detail_page(
                         span class="text-2xl font-semibold text-green-600" { (artist.owned_count) }
                         " owned"
                     }
-                    div class=(format!("text-2xl font-semibold {}",
-                        if artist.ownership_percentage >= 80.0 { "text-green-600" }
-                        else if artist.ownership_percentage >= 50.0 { "text-yellow-600" }
-                        else { "text-gray-500" }
-                    )) {
+                    div class=(format!("text-2xl font-semibold {}", color.class(&LABEL_PALETTE))) {
                         (format!("{:.0}%", artist.ownership_percentage)) " complete"
                     }
                 }
@@ -0,0 +1,86 @@
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+use maud::{html, Markup};
+
+use crate::i18n;
+
+/// Resolve an IANA zone name, falling back to UTC when it's missing or unknown
+pub fn resolve_timezone(name: Option<&str>) -> Tz {
+    name.and_then(|n| n.parse::<Tz>().ok()).unwrap_or(Tz::UTC)
+}
+
+/// Wall-clock time of `instant` in `tz`, in the current locale's date order,
+/// e.g. "2024-03-10 03:00 EDT" or "10.03.2024 03:00 EDT"
+pub fn format_local<T: TimeZone>(instant: &DateTime<T>, tz: Tz) -> String {
+    instant
+        .with_timezone(&tz)
+        .format(i18n::current().datetime_format())
+        .to_string()
+}
+
+/// `<time>` element showing the local time, with the exact UTC instant on hover
+pub fn timestamp<T: TimeZone>(instant: &DateTime<T>, tz: Tz) -> Markup {
+    let utc = instant.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    html! {
+        time datetime=(utc) title=(utc) { (format_local(instant, tz)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_spring_forward_boundary() {
+        let tz = resolve_timezone(Some("America/New_York"));
+
+        // Clocks jump from 02:00 EST to 03:00 EDT on 2024-03-10
+        assert_eq!(format_local(&utc("2024-03-10T06:59:00Z"), tz), "2024-03-10 01:59 EST");
+        assert_eq!(format_local(&utc("2024-03-10T07:00:00Z"), tz), "2024-03-10 03:00 EDT");
+    }
+
+    #[test]
+    fn test_fall_back_boundary() {
+        let tz = resolve_timezone(Some("Europe/London"));
+
+        // 01:30 happens twice on 2024-10-27; the zone abbreviation tells them apart
+        assert_eq!(format_local(&utc("2024-10-27T00:30:00Z"), tz), "2024-10-27 01:30 BST");
+        assert_eq!(format_local(&utc("2024-10-27T01:30:00Z"), tz), "2024-10-27 01:30 GMT");
+    }
+
+    #[test]
+    fn test_invalid_zone_falls_back_to_utc() {
+        assert_eq!(resolve_timezone(Some("Mars/Olympus_Mons")), Tz::UTC);
+        assert_eq!(resolve_timezone(None), Tz::UTC);
+        assert_eq!(format_local(&utc("2024-06-01T12:00:00Z"), Tz::UTC), "2024-06-01 12:00 UTC");
+    }
+
+    #[test]
+    fn test_date_order_follows_locale() {
+        let tz = resolve_timezone(Some("Europe/Berlin"));
+        let instant = utc("2024-06-01T12:00:00Z");
+
+        assert_eq!(format_local(&instant, tz), "2024-06-01 14:00 CEST");
+        assert_eq!(
+            i18n::with_locale(i18n::Locale::De, || format_local(&instant, tz)),
+            "01.06.2024 14:00 CEST"
+        );
+    }
+
+    #[test]
+    fn test_timestamp_keeps_utc_instant_in_title() {
+        let tz = resolve_timezone(Some("Asia/Tokyo"));
+        let instant = DateTime::parse_from_rfc3339("2024-06-01T12:00:00+00:00").unwrap();
+
+        let markup = timestamp(&instant, tz).into_string();
+        assert_eq!(
+            markup,
+            r#"<time datetime="2024-06-01T12:00:00Z" title="2024-06-01T12:00:00Z">2024-06-01 21:00 JST</time>"#
+        );
+    }
+}
@@ -11,15 +11,22 @@ use std::sync::atomic::{AtomicU8, Ordering};
 use chrono::Utc;
 use migration::MigratorTrait;
 use redis::aio::ConnectionManager;
-use sea_orm::{ActiveModelTrait, Database, DatabaseConnection, Set};
+use sea_orm::{ActiveModelTrait, ConnectOptions, ConnectionTrait, Database, DatabaseConnection, EntityTrait, Set};
 
 use crate::{
-    config::Config,
+    config::{Config, LogFormat, DEFAULT_COVER_ART_CONCURRENCY, DEFAULT_JOB_QUEUE_CAPACITY},
     db::{
         entities::{albums, artists, jobs},
         enums::{JobStatus, JobType, MatchStatus, OwnershipStatus},
+        artist_names, profile, slug,
     },
+    events,
     jobs::JobQueue,
+    logging::{LogFilter, DEFAULT_LOG_DIRECTIVES},
+    services::{
+        listenbrainz::LISTENBRAINZ_API_BASE,
+        spotify::{SPOTIFY_ACCOUNTS_BASE, SPOTIFY_API_BASE},
+    },
     state::AppState,
 };
 
@@ -48,6 +55,46 @@ pub async fn setup_test_db() -> DatabaseConnection {
     db
 }
 
+/// Serializes the database-wide setup of Postgres test schemas
+static POSTGRES_SETUP: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Setup a Postgres database for tests that depend on Postgres behavior
+///
+/// Uses the scratch database in `TEST_POSTGRES_URL` and returns `None` when
+/// it isn't set, so those tests skip. Each call migrates a fresh schema of
+/// its own, so tests can run in parallel against the same database.
+pub async fn setup_postgres_test_db() -> Option<DatabaseConnection> {
+    let url = std::env::var("TEST_POSTGRES_URL").ok()?;
+    let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+
+    {
+        let _setup = POSTGRES_SETUP.lock().await;
+        let admin = Database::connect(&url)
+            .await
+            .expect("Failed to connect to the Postgres test database");
+        // Installed once in public, which every test schema can see
+        admin
+            .execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm")
+            .await
+            .expect("Failed to install pg_trgm");
+        admin
+            .execute_unprepared(&format!("CREATE SCHEMA {}", schema))
+            .await
+            .expect("Failed to create a test schema");
+    }
+
+    let mut options = ConnectOptions::new(url);
+    options.set_schema_search_path(format!("{},public", schema));
+    let db = Database::connect(options)
+        .await
+        .expect("Failed to connect to the Postgres test schema");
+    migration::Migrator::up(&db, None)
+        .await
+        .expect("Failed to run migrations");
+
+    Some(db)
+}
+
 /// Setup a test Redis connection using a unique database number
 ///
 /// Redis supports 16 databases (0-15), so we use test_id % 16 to isolate tests
@@ -89,34 +136,62 @@ pub fn test_config() -> Config {
         server_port: 3000,
         spotify_client_id: "test_client_id".to_string(),
         spotify_redirect_uri: "http://localhost:3000/api/auth/spotify/callback".to_string(),
+        spotify_accounts_base: SPOTIFY_ACCOUNTS_BASE.to_string(),
+        spotify_api_base: SPOTIFY_API_BASE.to_string(),
+        listenbrainz_api_base: LISTENBRAINZ_API_BASE.to_string(),
         music_folder_path: None,
         lidarr_url: None,
         lidarr_api_key: None,
+        allow_schema_ahead: false,
+        log_format: LogFormat::default(),
+        cover_art_concurrency: DEFAULT_COVER_ART_CONCURRENCY,
+        job_queue_capacity: DEFAULT_JOB_QUEUE_CAPACITY,
+        sync_quiet_hours: None,
     }
 }
 
+/// Log filter that isn't installed as the global subscriber, so tests can
+/// change it freely without affecting each other
+pub fn test_log_filter() -> LogFilter {
+    LogFilter::detached(DEFAULT_LOG_DIRECTIVES)
+}
+
 /// Create a complete test AppState with isolated database and Redis
 pub async fn setup_test_app_state() -> AppState {
     let db = setup_test_db().await;
     let redis = setup_test_redis().await;
     let config = test_config();
-    let (job_queue, _receiver) = JobQueue::new();
+    let (job_queue, _receiver) = JobQueue::new(config.job_queue_capacity);
 
-    AppState::new(db, redis, config, job_queue)
+    let state = AppState::new(db, redis, config, job_queue, test_log_filter());
+    events::start_subscribers(&state);
+    state
 }
 
 /// Create a test AppState with job queue that keeps the receiver alive
 /// Returns (AppState, receiver) tuple - keep receiver in scope to prevent queue from closing
 pub async fn setup_test_app_state_with_queue() -> (
     AppState,
-    tokio::sync::mpsc::UnboundedReceiver<crate::jobs::queue::JobMessage>,
+    tokio::sync::mpsc::Receiver<crate::jobs::queue::JobMessage>,
+) {
+    setup_test_app_state_with_queue_capacity(test_config().job_queue_capacity).await
+}
+
+/// Like `setup_test_app_state_with_queue`, but with a queue that fills after `capacity` jobs
+pub async fn setup_test_app_state_with_queue_capacity(
+    capacity: usize,
+) -> (
+    AppState,
+    tokio::sync::mpsc::Receiver<crate::jobs::queue::JobMessage>,
 ) {
     let db = setup_test_db().await;
     let redis = setup_test_redis().await;
-    let config = test_config();
-    let (job_queue, receiver) = JobQueue::new();
+    let config = Config { job_queue_capacity: capacity, ..test_config() };
+    let (job_queue, receiver) = JobQueue::new(config.job_queue_capacity);
 
-    (AppState::new(db, redis, config, job_queue), receiver)
+    let state = AppState::new(db, redis, config, job_queue, test_log_filter());
+    events::start_subscribers(&state);
+    (state, receiver)
 }
 
 // ============================================================================
@@ -134,6 +209,12 @@ pub async fn create_test_artist(
         name: Set(name.to_string()),
         spotify_id: Set(spotify_id.map(|s| s.to_string())),
         musicbrainz_id: Set(None),
+        slug: Set(Some(
+            slug::new_artist_slug(db, name)
+                .await
+                .expect("Failed to generate artist slug"),
+        )),
+        normalized_name: Set(Some(artist_names::normalize_name(name))),
         created_at: Set(now),
         updated_at: Set(now),
         ..Default::default()
@@ -142,17 +223,28 @@ pub async fn create_test_artist(
     artist.insert(db).await.expect("Failed to insert test artist")
 }
 
-/// Create a test album in the database
+/// Create a test album in the database, linked to the default profile
 pub async fn create_test_album(
     db: &DatabaseConnection,
     artist_id: i32,
     title: &str,
     spotify_id: Option<&str>,
 ) -> albums::Model {
+    let artist = artists::Entity::find_by_id(artist_id)
+        .one(db)
+        .await
+        .expect("Failed to load test artist")
+        .expect("Test artist not found");
+
     let now = Utc::now().into();
     let album = albums::ActiveModel {
         artist_id: Set(artist_id),
         title: Set(title.to_string()),
+        slug: Set(Some(
+            slug::new_album_slug(db, &artist.name, title)
+                .await
+                .expect("Failed to generate album slug"),
+        )),
         spotify_id: Set(spotify_id.map(|s| s.to_string())),
         musicbrainz_release_group_id: Set(None),
         release_date: Set(None),
@@ -161,10 +253,20 @@ pub async fn create_test_album(
         match_status: Set(Some(MatchStatus::Pending.as_str().to_string())),
         created_at: Set(now),
         updated_at: Set(now),
+        wanted_since: Set(Some(now)),
         ..Default::default()
     };
 
-    album.insert(db).await.expect("Failed to insert test album")
+    let album = album.insert(db).await.expect("Failed to insert test album");
+
+    let default_profile = profile::default_profile(db)
+        .await
+        .expect("Failed to load default profile");
+    profile::link_album(db, default_profile.id, album.id)
+        .await
+        .expect("Failed to link test album to default profile");
+
+    album
 }
 
 /// Create a test job in the database
@@ -191,6 +293,44 @@ pub async fn create_test_job(
     job.insert(db).await.expect("Failed to insert test job")
 }
 
+/// A minimal FLAC file for scan tests: stream info of the given length at 44.1 kHz, and
+/// Vorbis comments with the track and disc numbers
+pub fn test_flac(duration_ms: u64, track_number: Option<i32>, disc_number: Option<i32>) -> Vec<u8> {
+    let sample_rate: u64 = 44_100;
+    let total_samples = duration_ms * sample_rate / 1000;
+
+    let mut streaminfo = vec![0u8; 34];
+    streaminfo[10] = (sample_rate >> 12) as u8;
+    streaminfo[11] = (sample_rate >> 4) as u8;
+    // 2 channels and 16 bits per sample share these bytes with the rate and total
+    streaminfo[12] = ((sample_rate & 0x0f) as u8) << 4 | (1 << 1);
+    streaminfo[13] = (15 << 4) | ((total_samples >> 32) & 0x0f) as u8;
+    streaminfo[14..18].copy_from_slice(&(total_samples as u32).to_be_bytes());
+
+    let comments: Vec<String> = [("TRACKNUMBER", track_number), ("DISCNUMBER", disc_number)]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| format!("{}={}", name, v)))
+        .collect();
+    let vendor = b"beat-collector";
+    let mut vorbis = Vec::new();
+    vorbis.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    vorbis.extend_from_slice(vendor);
+    vorbis.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in &comments {
+        vorbis.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        vorbis.extend_from_slice(comment.as_bytes());
+    }
+
+    let mut bytes = b"fLaC".to_vec();
+    for (block_type, block, is_last) in [(0u8, &streaminfo, false), (4u8, &vorbis, true)] {
+        let length = (block.len() as u32).to_be_bytes();
+        bytes.push(block_type | if is_last { 0x80 } else { 0 });
+        bytes.extend_from_slice(&length[1..]);
+        bytes.extend_from_slice(block);
+    }
+    bytes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,221 @@
+//! Integration tests for admin handler routes
+//!
+//! Tests the runtime log level endpoints:
+//! - Get the current log filter
+//! - Set and persist an override
+//! - Reject malformed directives
+//! - Reset to the default
+//!
+//! And the pending side effect endpoints:
+//! - List, retry and discard queued entries
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use sea_orm::EntityTrait;
+use serde_json::json;
+use tower::util::ServiceExt;
+
+use beat_collector::db::entities::user_settings;
+use beat_collector::handlers;
+use beat_collector::services::side_effects::{self, CoverArtDownload, SideEffectKind};
+use beat_collector::logging::DEFAULT_LOG_DIRECTIVES;
+use beat_collector::state::AppState;
+use beat_collector::test_utils::*;
+
+/// Helper to create a test router with admin routes
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+/// Helper to parse JSON response body
+async fn parse_json_response<T: serde::de::DeserializeOwned>(
+    response: axum::response::Response,
+) -> T {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn put_log_level(directives: &str) -> Request<Body> {
+    Request::builder()
+        .method("PUT")
+        .uri("/api/admin/log-level")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({ "directives": directives }).to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_get_log_level_defaults() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/admin/log-level")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["default"], DEFAULT_LOG_DIRECTIVES);
+    assert!(body["saved_override"].is_null());
+}
+
+#[tokio::test]
+async fn test_set_log_level_applies_and_persists() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(put_log_level("beat_collector=trace"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["current"], "beat_collector=trace");
+    assert_eq!(body["saved_override"], "beat_collector=trace");
+    assert_eq!(state.log_filter.current(), "beat_collector=trace");
+
+    let settings = user_settings::Entity::find()
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(settings.log_level, Some("beat_collector=trace".to_string()));
+}
+
+#[tokio::test]
+async fn test_set_log_level_rejects_malformed_directives() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    let before = state.log_filter.current();
+
+    let response = app
+        .oneshot(put_log_level("beat_collector=loud"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(state.log_filter.current(), before);
+
+    // Nothing is saved for a rejected override
+    let settings = user_settings::Entity::find().one(&state.db).await.unwrap();
+    assert!(settings.is_none());
+}
+
+#[tokio::test]
+async fn test_reset_log_level() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(put_log_level("beat_collector=trace"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/api/admin/log-level")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert!(body["saved_override"].is_null());
+    assert_eq!(body["current"], body["default"]);
+
+    let settings = user_settings::Entity::find()
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(settings.log_level, None);
+
+    // The saved override is gone, so a restart keeps the default
+    state.log_filter.restore_saved(&state.db).await.unwrap();
+    assert_eq!(state.log_filter.current(), body["default"].as_str().unwrap());
+}
+
+#[tokio::test]
+async fn test_side_effects_list_retry_and_discard() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    // A malformed release group ID fails before any request is made
+    let payload = CoverArtDownload {
+        album_id: 42,
+        release_group_id: "not-a-uuid".to_string(),
+    };
+    let entry = side_effects::enqueue(&state.db, SideEffectKind::CoverArtDownload, &payload, "timed out")
+        .await
+        .unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/admin/side-effects")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["kind"], "cover_art_download");
+    assert_eq!(body[0]["payload"]["album_id"], 42);
+    assert_eq!(body[0]["attempts"], 1);
+    assert_eq!(body[0]["last_error"], "timed out");
+    assert_eq!(body[0]["dead"], false);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/admin/side-effects/{}/retry", entry.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["succeeded"], false);
+    assert_eq!(body["entry"]["attempts"], 2);
+    assert_ne!(body["entry"]["last_error"], "timed out");
+
+    let discard = || {
+        Request::builder()
+            .method("DELETE")
+            .uri(format!("/api/admin/side-effects/{}", entry.id))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = app.clone().oneshot(discard()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(side_effects::list(&state.db).await.unwrap().is_empty());
+
+    let response = app.oneshot(discard()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
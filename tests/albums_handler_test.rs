@@ -1,25 +1,50 @@
 //! Integration tests for album handler routes
 //!
 //! Tests all album-related API endpoints including:
-//! - List albums with various filters and pagination
+//! - List albums with various filters and pagination, optionally hiding singles
+//! - Sort albums by listening history
 //! - Get single album
-//! - Update album
+//! - Update album (including manual matches, genres, ownership transitions
+//!   and moving it to another artist)
+//! - Queue a match for one album and list an album's activity history
+//! - Export wanted, matched albums as an MBID or JSON download, optionally only
+//!   albums wanted for a while
 //! - Search Lidarr
 //! - Get stats
+//! - Collection value report
+//! - Deduplicated track ownership across playlists
+//! - Genre distribution with an "other" bucket
+//! - Track title search across the library
+//! - Archived albums left out of lists, reports and exports, and listed in
+//!   the archive
+//! - Reset matches to pending
+//! - Cached default album grid page
+//! - Want-list board columns and moves between them, and how long albums
+//!   have been wanted
+//! - Want priorities, the stale wants demotion rule and undoing a demotion
+
+use std::sync::{Arc, Mutex};
 
 use axum::{
     body::Body,
     http::{Request, StatusCode},
     Router,
 };
-use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 use serde_json::json;
 use tower::util::ServiceExt;
 
 // Import from the main crate
 use beat_collector::db::{
-    entities::{albums, artists, user_settings},
-    enums::{AcquisitionSource, MatchStatus, OwnershipStatus},
+    entities::{
+        albums, artists, jobs, lidarr_downloads, ownership_events, playlist_tracks, playlists, tracks,
+        user_settings,
+    },
+    enums::{
+        AcquisitionSource, AlbumSource, DownloadStatus, JobStatus, JobType, MatchSource, MatchStatus,
+        OwnershipStatus,
+    },
+    profile,
 };
 use beat_collector::handlers;
 use beat_collector::state::AppState;
@@ -313,6 +338,147 @@ async fn test_list_albums_search() {
     assert_eq!(body["albums"][0]["title"], "The Wall");
 }
 
+#[tokio::test]
+async fn test_list_albums_search_in_artist() {
+    let state = setup_test_app_state().await;
+
+    let moon = create_test_artist(&state.db, "Moonlight Trio", None).await;
+    let other = create_test_artist(&state.db, "Other Band", None).await;
+    create_test_album(&state.db, moon.id, "Nocturnes", None).await;
+    create_test_album(&state.db, other.id, "Moon Songs", None).await;
+    create_test_album(&state.db, other.id, "Daylight", None).await;
+
+    let app = create_test_router(&state);
+    let search = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            (status, parse_json_response::<serde_json::Value>(response).await)
+        }
+    };
+
+    // Titles only by default
+    let (status, body) = search("/api/albums?search=moon").await;
+    assert_eq!(status, StatusCode::OK);
+    let albums = body["albums"].as_array().unwrap();
+    assert_eq!(albums.len(), 1);
+    assert_eq!(albums[0]["title"], "Moon Songs");
+    assert_eq!(albums[0]["matched_on"], serde_json::json!(["title"]));
+
+    // An artist match brings in albums whose title doesn't have it
+    let (status, body) = search("/api/albums?search=moon&search_in=title,artist&sort_by=title&sort_order=asc").await;
+    assert_eq!(status, StatusCode::OK);
+    let albums = body["albums"].as_array().unwrap();
+    assert_eq!(albums.len(), 2);
+    assert_eq!(albums[0]["title"], "Moon Songs");
+    assert_eq!(albums[0]["matched_on"], serde_json::json!(["title"]));
+    assert_eq!(albums[1]["title"], "Nocturnes");
+    assert_eq!(albums[1]["matched_on"], serde_json::json!(["artist"]));
+
+    // Not searching leaves matched_on out
+    let (_, body) = search("/api/albums").await;
+    assert!(body["albums"][0].get("matched_on").is_none());
+
+    // Fields we can't search are rejected
+    let (status, body) = search("/api/albums?search=moon&search_in=title,label").await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["error"]["message"].as_str().unwrap().contains("label"));
+}
+
+#[tokio::test]
+async fn test_list_albums_search_in_notes() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Stereolab", None).await;
+    let dots = create_test_album(&state.db, artist.id, "Dots and Loops", None).await;
+    let vinyl = create_test_album(&state.db, artist.id, "Vinyl Sessions", None).await;
+    create_test_album(&state.db, artist.id, "Emperor Tomato Ketchup", None).await;
+
+    let response = patch_album(&state, dots.id, serde_json::json!({ "notes": "  Vinyl preorder, gift idea " })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    patch_album(&state, vinyl.id, serde_json::json!({ "notes": "vinyl" })).await;
+    let response = patch_album(&state, vinyl.id, serde_json::json!({ "notes": "" })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let app = create_test_router(&state);
+    let search = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            parse_json_response::<serde_json::Value>(response).await
+        }
+    };
+
+    // Notes are left out unless asked for
+    let body = search("/api/albums?search=preorder").await;
+    assert!(body["albums"].as_array().unwrap().is_empty());
+    let body = search("/api/albums?search=preorder&search_in=title,artist").await;
+    assert!(body["albums"].as_array().unwrap().is_empty());
+
+    // A notes-only match says so
+    let body = search("/api/albums?search=preorder&search_in=title,artist,notes").await;
+    let albums = body["albums"].as_array().unwrap();
+    assert_eq!(albums.len(), 1);
+    assert_eq!(albums[0]["id"], dots.id);
+    assert_eq!(albums[0]["notes"], "Vinyl preorder, gift idea");
+    assert_eq!(albums[0]["matched_on"], serde_json::json!(["notes"]));
+
+    // Blank notes were cleared, so only the title matches there
+    let body = search("/api/albums?search=vinyl&search_in=title,notes&sort_by=title&sort_order=asc").await;
+    let albums = body["albums"].as_array().unwrap();
+    assert_eq!(albums.len(), 2);
+    assert_eq!(albums[0]["matched_on"], serde_json::json!(["notes"]));
+    assert_eq!(albums[1]["title"], "Vinyl Sessions");
+    assert!(albums[1]["notes"].is_null());
+    assert_eq!(albums[1]["matched_on"], serde_json::json!(["title"]));
+}
+
+#[tokio::test]
+async fn test_list_albums_search_ignores_case_and_wildcards() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Café Tacvba", None).await;
+    create_test_album(&state.db, artist.id, "100% Pure", None).await;
+    create_test_album(&state.db, artist.id, "1000 Hertz", None).await;
+
+    let app = create_test_router(&state);
+    let titles = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body: serde_json::Value = parse_json_response(response).await;
+            let mut titles: Vec<String> = body["albums"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|album| album["title"].as_str().unwrap().to_string())
+                .collect();
+            titles.sort();
+            titles
+        }
+    };
+
+    // Case beyond ASCII, which a plain LIKE only folds on SQLite
+    assert_eq!(
+        titles("/api/albums?search=CAF%C3%89&search_in=artist").await,
+        vec!["100% Pure", "1000 Hertz"]
+    );
+    // A `%` in the search is a literal, not a wildcard
+    assert_eq!(titles("/api/albums?search=100%25").await, vec!["100% Pure"]);
+}
+
 #[tokio::test]
 async fn test_list_albums_combined_filters() {
     let state = setup_test_app_state().await;
@@ -347,6 +513,111 @@ async fn test_list_albums_combined_filters() {
     assert_eq!(body["albums"][0]["title"], "Owned Album");
 }
 
+/// A one-track single and a ten-track album by the same artist
+async fn create_single_and_album(state: &AppState) {
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    for (title, total_tracks) in [("The Single", 1), ("The Album", 10)] {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.total_tracks = Set(Some(total_tracks));
+        active.update(&state.db).await.unwrap();
+    }
+}
+
+async fn list_album_titles(state: &AppState, uri: &str) -> Vec<String> {
+    let response = create_test_router(state)
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = parse_json_response(response).await;
+    body["albums"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_albums_exclude_singles() {
+    let state = setup_test_app_state().await;
+    create_single_and_album(&state).await;
+
+    // Everything is shown by default
+    assert_eq!(list_album_titles(&state, "/api/albums").await.len(), 2);
+
+    let titles = list_album_titles(&state, "/api/albums?exclude_singles=true").await;
+    assert_eq!(titles, vec!["The Album".to_string()]);
+}
+
+#[tokio::test]
+async fn test_list_albums_exclude_singles_setting() {
+    let state = setup_test_app_state().await;
+    create_single_and_album(&state).await;
+
+    let now = chrono::Utc::now().into();
+    user_settings::ActiveModel {
+        exclude_singles: Set(Some(true)),
+        single_track_threshold: Set(Some(1)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let titles = list_album_titles(&state, "/api/albums").await;
+    assert_eq!(titles, vec!["The Album".to_string()]);
+
+    // The query parameter overrides the setting
+    let titles = list_album_titles(&state, "/api/albums?exclude_singles=false").await;
+    assert_eq!(titles.len(), 2);
+}
+
+#[tokio::test]
+async fn test_list_albums_least_recently_listened() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Radiohead", None).await;
+    let day = |d: i64| (chrono::Utc::now() - chrono::Duration::days(d)).into();
+
+    for (title, last_listened, count) in [
+        ("In Rainbows", Some(day(3)), 12),
+        ("Kid A", None, 0),
+        ("Amnesiac", Some(day(800)), 2),
+    ] {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.first_listened_at = Set(last_listened);
+        active.last_listened_at = Set(last_listened);
+        active.listen_count = Set(count);
+        active.update(&state.db).await.unwrap();
+    }
+
+    // Never played first, then the longest unplayed
+    let titles = list_album_titles(&state, "/api/albums?sort_by=last_listened&sort_order=asc").await;
+    assert_eq!(titles, ["Kid A", "Amnesiac", "In Rainbows"]);
+
+    let titles = list_album_titles(&state, "/api/albums?sort_by=listen_count&sort_order=desc").await;
+    assert_eq!(titles, ["In Rainbows", "Amnesiac", "Kid A"]);
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums?sort_by=listen_count")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["albums"][0]["listen_count"], 12);
+    assert!(body["albums"][0]["last_listened_at"].is_string());
+    assert!(body["albums"][2]["last_listened_at"].is_null());
+}
+
 #[tokio::test]
 async fn test_get_album_success() {
     let state = setup_test_app_state().await;
@@ -381,6 +652,54 @@ async fn test_get_album_success() {
     assert_eq!(body["artist"]["id"], artist.id);
 }
 
+#[tokio::test]
+async fn test_get_album_by_slug() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Sigur Rós", None).await;
+    let album = create_test_album(&state.db, artist.id, "Ágætis byrjun", None).await;
+    assert_eq!(album.slug.as_deref(), Some("sigur-ros-agaetis-byrjun"));
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums/sigur-ros-agaetis-byrjun")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["id"], album.id);
+
+    // The HTML detail modal resolves the same slug
+    let html_app = Router::new()
+        .merge(handlers::html_routes())
+        .with_state(state.clone());
+    let response = html_app
+        .oneshot(
+            Request::builder()
+                .uri("/albums/sigur-ros-agaetis-byrjun")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Ágætis byrjun"));
+    assert!(html.contains("/artists/sigur-ros"));
+}
+
 #[tokio::test]
 async fn test_get_album_not_found() {
     let state = setup_test_app_state().await;
@@ -431,6 +750,50 @@ async fn test_update_album_ownership_status() {
     assert_eq!(body["ownership_status"].as_str().unwrap(), "\"owned\"");
 }
 
+#[tokio::test]
+async fn test_update_album_from_card_quick_action() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+
+    let app = create_test_router(&state);
+    let mark_owned = |query: &str| {
+        Request::builder()
+            .method("PATCH")
+            .uri(format!("/api/albums/{}{}", album.id, query))
+            .header("HX-Request", "true")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from("ownership_status=owned"))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(mark_owned("?card=true")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = String::from_utf8(
+        axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec(),
+    )
+    .unwrap();
+    assert!(body.contains(&format!(r#"id="album-card-{}""#, album.id)));
+    assert!(body.contains(r#"hx-swap-oob="true""#));
+    assert!(body.contains("album-card owned"));
+    // Owned albums offer neither quick action
+    assert!(!body.contains("album-quick-actions"));
+
+    let updated = albums::Entity::find_by_id(album.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.ownership_status, "owned");
+
+    // Without the hint a form body still gets the JSON album
+    let response = app.oneshot(mark_owned("")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["id"], album.id);
+}
+
 #[tokio::test]
 async fn test_update_album_acquisition_source() {
     let state = setup_test_app_state().await;
@@ -512,6 +875,107 @@ async fn test_update_album_local_path() {
     assert_eq!(updated_album.local_path, Some("/music/artist/album".to_string()));
 }
 
+#[tokio::test]
+async fn test_update_album_estimated_value() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(&format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "estimated_value": 34.5 }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["estimated_value"], 34.5);
+
+    // Negative values are rejected
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(&format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "estimated_value": -1.0 }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let updated_album = albums::Entity::find_by_id(album.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated_album.estimated_value, Some(34.5));
+}
+
+#[tokio::test]
+async fn test_update_album_genres() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(&format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({ "genres": [" French House", "disco", "french house"] }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["genres"], json!(["French House", "disco"]));
+    assert_eq!(body["genre_source"], "manual");
+
+    // Forms send them comma separated
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(&format!("/api/albums/{}", album.id))
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from("genres=house%2C+electronic"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let updated_album = albums::Entity::find_by_id(album.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated_album.genres.as_deref(), Some(r#"["house","electronic"]"#));
+    assert_eq!(updated_album.genre_source.as_deref(), Some("manual"));
+}
+
 #[tokio::test]
 async fn test_update_album_not_found() {
     let state = setup_test_app_state().await;
@@ -563,142 +1027,1876 @@ async fn test_update_album_invalid_ownership_status() {
         .await
         .unwrap();
 
-    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 }
 
 #[tokio::test]
-async fn test_get_stats_empty() {
+async fn test_update_album_manual_match() {
     let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+
     let app = create_test_router(&state);
 
     let response = app
         .oneshot(
             Request::builder()
-                .uri("/api/stats")
-                .body(Body::empty())
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "musicbrainz_release_group_id": "48117b82-8d4b-3b1a-8ff5-0b1f6b1d8c2a"
+                    })
+                    .to_string(),
+                ))
                 .unwrap(),
         )
         .await
         .unwrap();
 
     assert_eq!(response.status(), StatusCode::OK);
-    let body: serde_json::Value = parse_json_response(response).await;
 
-    assert_eq!(body["total_albums"], 0);
-    assert_eq!(body["owned_albums"], 0);
-    assert_eq!(body["not_owned_albums"], 0);
-    assert_eq!(body["downloading_albums"], 0);
-    assert_eq!(body["matched_albums"], 0);
-    assert_eq!(body["unmatched_albums"], 0);
-    assert_eq!(body["total_artists"], 0);
+    let updated_album = albums::Entity::find_by_id(album.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        updated_album.musicbrainz_release_group_id.as_deref(),
+        Some("48117b82-8d4b-3b1a-8ff5-0b1f6b1d8c2a")
+    );
+    assert_eq!(updated_album.match_status.as_deref(), Some(MatchStatus::Matched.as_str()));
+    assert_eq!(updated_album.match_source.as_deref(), Some(MatchSource::Manual.as_str()));
+    assert_eq!(updated_album.match_score, None);
 }
 
 #[tokio::test]
-async fn test_get_stats_with_data() {
+async fn test_update_album_invalid_release_group_id() {
     let state = setup_test_app_state().await;
 
-    let artist1 = create_test_artist(&state.db, "Artist 1", None).await;
-    let artist2 = create_test_artist(&state.db, "Artist 2", None).await;
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
 
-    // Create albums with different statuses
-    let album1 = create_test_album(&state.db, artist1.id, "Owned Album", None).await;
-    let album2 = create_test_album(&state.db, artist1.id, "Not Owned Album", None).await;
-    let album3 = create_test_album(&state.db, artist2.id, "Downloading Album", None).await;
-    let album4 = create_test_album(&state.db, artist2.id, "Matched Album", None).await;
+    let app = create_test_router(&state);
 
-    // Update ownership statuses
-    let mut album1_active: albums::ActiveModel = album1.into();
-    album1_active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
-    album1_active.update(&state.db).await.unwrap();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "musicbrainz_release_group_id": "not-a-uuid"
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
 
-    let mut album3_active: albums::ActiveModel = album3.into();
-    album3_active.ownership_status = Set(OwnershipStatus::Downloading.as_str().to_string());
-    album3_active.update(&state.db).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 
-    // Update match status
-    let mut album4_active: albums::ActiveModel = album4.into();
-    album4_active.match_status = Set(Some(MatchStatus::Matched.as_str().to_string()));
-    album4_active.update(&state.db).await.unwrap();
+    let updated_album = albums::Entity::find_by_id(album.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated_album.musicbrainz_release_group_id, None);
+}
+
+#[tokio::test]
+async fn test_update_album_lidarr_artist_override() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+
+    let app = create_test_router(&state);
+    let patch = |override_mbid: &str| {
+        Request::builder()
+            .method("PATCH")
+            .uri(format!("/api/albums/{}", album.id))
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "lidarr_artist_mbid_override": override_mbid }).to_string()))
+            .unwrap()
+    };
+    let saved_override = || async {
+        albums::Entity::find_by_id(album.id)
+            .one(&state.db)
+            .await
+            .unwrap()
+            .unwrap()
+            .lidarr_artist_mbid_override
+    };
+
+    let response = app.clone().oneshot(patch("F82BCF78-5B69-4622-A5EF-73800768D9AC")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(saved_override().await.as_deref(), Some("f82bcf78-5b69-4622-a5ef-73800768d9ac"));
+
+    let response = app.clone().oneshot(patch("jay-z")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(saved_override().await.as_deref(), Some("f82bcf78-5b69-4622-a5ef-73800768d9ac"));
+
+    let response = app.oneshot(patch("")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(saved_override().await, None);
+}
+
+/// Helper to mark an album owned directly in the database
+async fn mark_owned(state: &AppState, album: albums::Model) {
+    let mut active: albums::ActiveModel = album.into();
+    active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_update_album_owned_to_not_owned_conflict() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+    mark_owned(&state, album.clone()).await;
 
     let app = create_test_router(&state);
 
     let response = app
         .oneshot(
             Request::builder()
-                .uri("/api/stats")
-                .body(Body::empty())
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "ownership_status": "not_owned"
+                    })
+                    .to_string(),
+                ))
                 .unwrap(),
         )
         .await
         .unwrap();
 
-    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.status(), StatusCode::CONFLICT);
     let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["error"]["code"], "conflict");
+    assert_eq!(body["error"]["details"]["allowed"], json!([]));
 
-    assert_eq!(body["total_albums"], 4);
-    assert_eq!(body["owned_albums"], 1);
-    assert_eq!(body["not_owned_albums"], 2);
-    assert_eq!(body["downloading_albums"], 1);
-    assert_eq!(body["matched_albums"], 1);
-    assert_eq!(body["unmatched_albums"], 3); // pending is counted as unmatched
-    assert_eq!(body["total_artists"], 2);
+    let unchanged = albums::Entity::find_by_id(album.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(unchanged.ownership_status, OwnershipStatus::Owned.as_str());
 }
 
 #[tokio::test]
-async fn test_search_lidarr_no_settings() {
+async fn test_update_album_files_missing_records_event() {
     let state = setup_test_app_state().await;
 
     let artist = create_test_artist(&state.db, "Test Artist", None).await;
     let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+    mark_owned(&state, album.clone()).await;
 
     let app = create_test_router(&state);
 
     let response = app
         .oneshot(
             Request::builder()
-                .method("POST")
-                .uri(&format!("/api/albums/{}/search-lidarr", album.id))
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "ownership_status": "not_owned",
+                        "reason": "files_missing"
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    state.events.settled().await;
+    let events = ownership_events::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].album_id, album.id);
+    assert_eq!(events[0].from_status, OwnershipStatus::Owned.as_str());
+    assert_eq!(events[0].to_status, OwnershipStatus::NotOwned.as_str());
+    assert_eq!(events[0].reason, "files_missing");
+    assert!(!events[0].forced);
+}
+
+/// Helper to PATCH an album with a JSON body
+async fn patch_album(state: &AppState, album_id: i32, body: serde_json::Value) -> axum::response::Response {
+    create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", album_id))
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Helper to fetch an artist's album stats from the API
+async fn artist_stats(state: &AppState, artist_id: i32) -> serde_json::Value {
+    let response = create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/artists/{}", artist_id))
                 .body(Body::empty())
                 .unwrap(),
         )
         .await
         .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    body["artist"].clone()
+}
 
-    // Should fail because user settings don't exist
-    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+#[tokio::test]
+async fn test_update_album_moves_to_existing_artist() {
+    let state = setup_test_app_state().await;
+
+    let various = create_test_artist(&state.db, "Various Artists", Some("various")).await;
+    let real = create_test_artist(&state.db, "Real Artist", None).await;
+    let album = create_test_album(&state.db, various.id, "Misattributed", None).await;
+    mark_owned(&state, album.clone()).await;
+    create_test_album(&state.db, real.id, "Earlier Album", None).await;
+
+    let response = patch_album(&state, album.id, json!({ "artist_id": real.id })).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["artist"]["id"], real.id);
+
+    let stats = artist_stats(&state, real.id).await;
+    assert_eq!(stats["album_count"], 2);
+    assert_eq!(stats["owned_count"], 1);
+
+    // The album was the old artist's last, so the artist is cleaned up
+    let removed = artists::Entity::find_by_id(various.id).one(&state.db).await.unwrap();
+    assert!(removed.is_none());
 }
 
 #[tokio::test]
-async fn test_search_lidarr_no_musicbrainz_id() {
+async fn test_update_album_moves_to_new_artist() {
     let state = setup_test_app_state().await;
 
-    // Create user settings
-    let now = chrono::Utc::now().into();
-    let settings = user_settings::ActiveModel {
-        lidarr_url: Set(Some("http://localhost:8686".to_string())),
-        lidarr_api_key: Set(Some("test-api-key".to_string())),
-        created_at: Set(now),
-        updated_at: Set(now),
-        ..Default::default()
-    };
-    settings.insert(&state.db).await.unwrap();
+    let artist = create_test_artist(&state.db, "Same Name", None).await;
+    let moved = create_test_album(&state.db, artist.id, "Not Theirs", None).await;
+    mark_owned(&state, moved.clone()).await;
+    create_test_album(&state.db, artist.id, "Theirs", None).await;
+
+    let response = patch_album(&state, moved.id, json!({ "new_artist_name": "  Same Name  " })).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    let new_id = body["artist"]["id"].as_i64().unwrap() as i32;
+    assert_ne!(new_id, artist.id);
+
+    let new_artist = artists::Entity::find_by_id(new_id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(new_artist.name, "Same Name");
+    assert_ne!(new_artist.slug, artist.slug);
+
+    // The old artist still has an album, so it stays, and both counts add up
+    let old_stats = artist_stats(&state, artist.id).await;
+    assert_eq!(old_stats["album_count"], 1);
+    assert_eq!(old_stats["owned_count"], 0);
+    let new_stats = artist_stats(&state, new_id).await;
+    assert_eq!(new_stats["album_count"], 1);
+    assert_eq!(new_stats["owned_count"], 1);
+}
+
+#[tokio::test]
+async fn test_update_album_keeps_empty_artist_with_locked_name() {
+    let state = setup_test_app_state().await;
+
+    let corrected = create_test_artist(&state.db, "Corrected", None).await;
+    let mut active: artists::ActiveModel = corrected.clone().into();
+    active.name_locked = Set(true);
+    active.update(&state.db).await.unwrap();
+    let other = create_test_artist(&state.db, "Other", None).await;
+    let album = create_test_album(&state.db, corrected.id, "Only Album", None).await;
+
+    let response = patch_album(&state, album.id, json!({ "artist_id": other.id })).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let kept = artists::Entity::find_by_id(corrected.id).one(&state.db).await.unwrap();
+    assert!(kept.is_some());
+}
+
+#[tokio::test]
+async fn test_update_album_rejects_bad_artist_choice() {
+    let state = setup_test_app_state().await;
 
     let artist = create_test_artist(&state.db, "Test Artist", None).await;
     let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
 
-    let app = create_test_router(&state);
+    for body in [
+        json!({ "artist_id": 99999 }),
+        json!({ "new_artist_name": "   " }),
+        json!({ "artist_id": artist.id, "new_artist_name": "Both" }),
+    ] {
+        let response = patch_album(&state, album.id, body).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
 
-    let response = app
+    let unchanged = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(unchanged.artist_id, artist.id);
+    assert_eq!(artists::Entity::find().all(&state.db).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_trigger_match_queues_job_for_album() {
+    let (state, mut receiver) = setup_test_app_state_with_queue().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+
+    let response = create_test_router(&state)
         .oneshot(
             Request::builder()
                 .method("POST")
-                .uri(&format!("/api/albums/{}/search-lidarr", album.id))
+                .uri(format!("/api/albums/{}/match", album.id))
                 .body(Body::empty())
                 .unwrap(),
         )
         .await
         .unwrap();
 
-    // Should fail because album doesn't have MusicBrainz ID
-    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    let job = jobs::Entity::find_by_id(body["job_id"].as_i64().unwrap() as i32)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.job_type, JobType::MusicbrainzMatch.as_str());
+    assert_eq!(job.entity_id, Some(album.id));
+
+    let message = receiver.try_recv().expect("Job should be queued");
+    assert_eq!(message.entity_id, Some(album.id));
+}
+
+#[tokio::test]
+async fn test_job_status_reports_album_of_match_job() {
+    let (state, _receiver) = setup_test_app_state_with_queue().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+    let router = create_test_router(&state);
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/albums/{}/match", album.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: serde_json::Value = parse_json_response(response).await;
+    let job_id = body["job_id"].as_i64().unwrap();
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/jobs/{}/status", job_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["entity_id"], album.id);
+}
+
+/// Helper to set up a library for the wanted-albums export
+///
+/// Only "Abbey Road" and "Let It Be" should be exported; "Help!" is matched
+/// without an MBID and counts as skipped.
+async fn seed_wanted_export(state: &AppState) {
+    let artist = create_test_artist(&state.db, "The Beatles", None).await;
+    let albums_to_seed = [
+        ("Let It Be", OwnershipStatus::NotOwned, MatchStatus::Matched, Some("rg-let-it-be")),
+        ("Abbey Road", OwnershipStatus::NotOwned, MatchStatus::Matched, Some("rg-abbey-road")),
+        ("Help!", OwnershipStatus::NotOwned, MatchStatus::Matched, None),
+        ("Revolver", OwnershipStatus::Owned, MatchStatus::Matched, Some("rg-revolver")),
+        ("Rubber Soul", OwnershipStatus::NotOwned, MatchStatus::Pending, Some("rg-rubber-soul")),
+    ];
+    for (title, ownership, match_status, mbid) in albums_to_seed {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.ownership_status = Set(ownership.as_str().to_string());
+        active.match_status = Set(Some(match_status.as_str().to_string()));
+        active.musicbrainz_release_group_id = Set(mbid.map(|m| m.to_string()));
+        active.update(&state.db).await.unwrap();
+    }
+}
+
+async fn export_wanted(state: &AppState, query: &str) -> axum::response::Response {
+    create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/albums/export-wanted?{}", query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_export_wanted_lists_mbids_and_counts_skipped() {
+    let state = setup_test_app_state().await;
+    seed_wanted_export(&state).await;
+
+    let response = export_wanted(&state, "format=mbid").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-disposition"],
+        "attachment; filename=\"wanted-albums.txt\""
+    );
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(
+        String::from_utf8(body.to_vec()).unwrap(),
+        "rg-abbey-road\nrg-let-it-be\n# Skipped 1 matched album(s) without a MusicBrainz ID\n"
+    );
+}
+
+#[tokio::test]
+async fn test_export_wanted_json_honors_search() {
+    let state = setup_test_app_state().await;
+    seed_wanted_export(&state).await;
+
+    let response = export_wanted(&state, "format=json&search=Abbey").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let mut body: serde_json::Value = parse_json_response(response).await;
+    let wanted_since = body["albums"][0].as_object_mut().unwrap().remove("wanted_since");
+    assert!(wanted_since.is_some_and(|since| since.is_string()));
+    assert_eq!(
+        body,
+        json!({
+            "albums": [{ "artist": "The Beatles", "title": "Abbey Road", "mbid": "rg-abbey-road" }],
+            "skipped_without_mbid": 0
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_export_wanted_searches_the_same_fields_as_the_grid() {
+    let state = setup_test_app_state().await;
+    seed_wanted_export(&state).await;
+
+    // The artist's name is only found when the search looks at artists too
+    let response = export_wanted(&state, "format=mbid&search=Beatles").await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8(body.to_vec()).unwrap().contains("rg-"));
+
+    let response = export_wanted(&state, "format=mbid&search=Beatles&search_in=title,artist").await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8(body.to_vec()).unwrap().starts_with("rg-abbey-road\nrg-let-it-be\n"));
+
+    let response = export_wanted(&state, "format=mbid&search=Beatles&search_in=label").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_export_wanted_leaves_out_recent_wants() {
+    let state = setup_test_app_state().await;
+    seed_wanted_export(&state).await;
+
+    // Wanted for over a year, just past the cutoff, and one day short of it
+    let backdate = [("Let It Be", 400), ("Abbey Road", 365), ("Help!", 364)];
+    for (title, days) in backdate {
+        let album = albums::Entity::find()
+            .filter(albums::Column::Title.eq(title))
+            .one(&state.db)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut active: albums::ActiveModel = album.into();
+        active.wanted_since = Set(Some((chrono::Utc::now() - chrono::Duration::days(days)).into()));
+        active.update(&state.db).await.unwrap();
+    }
+
+    let response = export_wanted(&state, "format=json&min_wanted_days=365").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    let titles: Vec<&str> = body["albums"].as_array().unwrap().iter().map(|a| a["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, vec!["Abbey Road", "Let It Be"]);
+    assert!(body["albums"][0]["wanted_since"].is_string());
+    assert_eq!(body["skipped_without_mbid"], 0);
+}
+
+#[tokio::test]
+async fn test_export_wanted_rejects_unknown_format() {
+    let state = setup_test_app_state().await;
+
+    let response = export_wanted(&state, "format=csv").await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_album_activity_merges_history_newest_first() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+    let at = |minutes: i64| (chrono::Utc::now() - chrono::Duration::minutes(minutes)).into();
+
+    let job = create_test_job(&state.db, JobType::MusicbrainzMatch, JobStatus::Completed).await;
+    let mut active: jobs::ActiveModel = job.clone().into();
+    active.entity_id = Set(Some(album.id));
+    active.created_at = Set(at(30));
+    active.update(&state.db).await.unwrap();
+
+    // A sync job's entity_id is a profile ID that happens to equal the album's
+    let sync = create_test_job(&state.db, JobType::SpotifySync, JobStatus::Completed).await;
+    let mut active: jobs::ActiveModel = sync.into();
+    active.entity_id = Set(Some(album.id));
+    active.update(&state.db).await.unwrap();
+
+    ownership_events::ActiveModel {
+        album_id: Set(album.id),
+        from_status: Set(OwnershipStatus::NotOwned.as_str().to_string()),
+        to_status: Set(OwnershipStatus::Downloading.as_str().to_string()),
+        reason: Set("lidarr_search".to_string()),
+        forced: Set(false),
+        created_at: Set(at(20)),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    lidarr_downloads::ActiveModel {
+        album_id: Set(album.id),
+        download_id: Set(Some("SABnzbd_nzo_1".to_string())),
+        status: Set(DownloadStatus::Downloading.as_str().to_string()),
+        created_at: Set(at(10)),
+        updated_at: Set(at(10)),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/albums/{}/activity", album.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    let activity = body["activity"].as_array().unwrap();
+    let kinds: Vec<&str> = activity.iter().map(|a| a["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, vec!["lidarr_download", "ownership_change", "job"]);
+    assert_eq!(activity[0]["download_id"], "SABnzbd_nzo_1");
+    assert_eq!(activity[1]["reason"], "lidarr_search");
+    assert_eq!(activity[2]["id"], job.id);
+    assert_eq!(activity[2]["job_type"], "musicbrainz_match");
+}
+
+#[tokio::test]
+async fn test_get_stats_empty() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/stats")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+
+    assert_eq!(body["total_albums"], 0);
+    assert_eq!(body["owned_albums"], 0);
+    assert_eq!(body["not_owned_albums"], 0);
+    assert_eq!(body["downloading_albums"], 0);
+    assert_eq!(body["ordered_albums"], 0);
+    assert_eq!(body["matched_albums"], 0);
+    assert_eq!(body["unmatched_albums"], 0);
+    assert_eq!(body["total_artists"], 0);
+}
+
+#[tokio::test]
+async fn test_get_stats_with_data() {
+    let state = setup_test_app_state().await;
+
+    let artist1 = create_test_artist(&state.db, "Artist 1", None).await;
+    let artist2 = create_test_artist(&state.db, "Artist 2", None).await;
+
+    // Create albums with different statuses
+    let album1 = create_test_album(&state.db, artist1.id, "Owned Album", None).await;
+    let album2 = create_test_album(&state.db, artist1.id, "Not Owned Album", None).await;
+    let album3 = create_test_album(&state.db, artist2.id, "Downloading Album", None).await;
+    let album4 = create_test_album(&state.db, artist2.id, "Matched Album", None).await;
+    let album5 = create_test_album(&state.db, artist2.id, "Ordered Album", None).await;
+
+    // Update ownership statuses
+    let mut album1_active: albums::ActiveModel = album1.into();
+    album1_active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    album1_active.update(&state.db).await.unwrap();
+
+    let mut album3_active: albums::ActiveModel = album3.into();
+    album3_active.ownership_status = Set(OwnershipStatus::Downloading.as_str().to_string());
+    album3_active.update(&state.db).await.unwrap();
+
+    let mut album5_active: albums::ActiveModel = album5.into();
+    album5_active.ownership_status = Set(OwnershipStatus::Ordered.as_str().to_string());
+    album5_active.update(&state.db).await.unwrap();
+
+    // Update match status
+    let mut album4_active: albums::ActiveModel = album4.into();
+    album4_active.match_status = Set(Some(MatchStatus::Matched.as_str().to_string()));
+    album4_active.update(&state.db).await.unwrap();
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/stats")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+
+    assert_eq!(body["total_albums"], 5);
+    assert_eq!(body["owned_albums"], 1);
+    assert_eq!(body["not_owned_albums"], 2);
+    assert_eq!(body["downloading_albums"], 1);
+    assert_eq!(body["ordered_albums"], 1);
+    assert_eq!(body["matched_albums"], 1);
+    assert_eq!(body["unmatched_albums"], 4); // pending is counted as unmatched
+    assert_eq!(body["total_artists"], 2);
+    assert_eq!(body["by_source"], json!({ "saved_album": 5 }));
+    assert_eq!(body["ignored"], json!({ "archived": 0, "playlist_imports": 0 }));
+}
+
+#[tokio::test]
+async fn test_get_stats_exclude_playlist_imports_setting() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Artist", None).await;
+    let saved = create_test_album(&state.db, artist.id, "Saved Album", None).await;
+    let imported = create_test_album(&state.db, artist.id, "Imported Album", None).await;
+
+    let mut saved_active: albums::ActiveModel = saved.into();
+    saved_active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    saved_active.update(&state.db).await.unwrap();
+
+    let mut imported_active: albums::ActiveModel = imported.into();
+    imported_active.source = Set(AlbumSource::PlaylistImport.as_str().to_string());
+    imported_active.update(&state.db).await.unwrap();
+
+    let stats = |state: AppState| async move {
+        let response = create_test_router(&state)
+            .oneshot(Request::builder().uri("/api/stats").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        parse_json_response(response).await
+    };
+
+    // Everything counts by default
+    let body: serde_json::Value = stats(state.clone()).await;
+    assert_eq!(body["total_albums"], 2);
+    assert_eq!(body["not_owned_albums"], 1);
+    assert_eq!(body["by_source"], json!({ "playlist_import": 1, "saved_album": 1 }));
+
+    let now = chrono::Utc::now().into();
+    user_settings::ActiveModel {
+        stats_exclude_playlist_imports: Set(Some(true)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let body: serde_json::Value = stats(state.clone()).await;
+    assert_eq!(body["total_albums"], 1);
+    assert_eq!(body["owned_albums"], 1);
+    assert_eq!(body["not_owned_albums"], 0);
+    assert_eq!(body["by_source"], json!({ "saved_album": 1 }));
+    assert_eq!(body["ignored"]["playlist_imports"], 1);
+}
+
+#[tokio::test]
+async fn test_get_stats_query_count_and_cache() {
+    let mut state = setup_test_app_state().await;
+    let statements = Arc::new(Mutex::new(Vec::new()));
+    let recorder = statements.clone();
+    state.db.set_metric_callback(move |info| {
+        recorder.lock().unwrap().push(info.statement.sql.clone());
+    });
+
+    let artist = create_test_artist(&state.db, "Boards of Canada", None).await;
+    let album = create_test_album(&state.db, artist.id, "Geogaddi", None).await;
+    let archived = create_test_album(&state.db, artist.id, "Twoism", None).await;
+    let mut active: albums::ActiveModel = archived.into();
+    active.archived_at = Set(Some(chrono::Utc::now().into()));
+    active.update(&state.db).await.unwrap();
+
+    let stats = || async {
+        statements.lock().unwrap().clear();
+        let response = create_test_router(&state)
+            .oneshot(Request::builder().uri("/api/stats").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body: serde_json::Value = parse_json_response(response).await;
+        let ran = statements.lock().unwrap().clone();
+        (body, ran)
+    };
+    let counting = |sql: &[String]| {
+        sql.iter()
+            .filter(|s| s.contains(r#"FROM "albums""#) || s.contains(r#"FROM "artists""#))
+            .count()
+    };
+
+    // One grouped album query and an artist count, then nothing until albums change
+    let (first, ran) = stats().await;
+    assert_eq!(counting(&ran), 2, "{:#?}", ran);
+    assert_eq!(first["total_albums"], 1);
+    assert_eq!(first["ignored"]["archived"], 1);
+
+    let (cached, ran) = stats().await;
+    assert_eq!(cached, first);
+    assert_eq!(counting(&ran), 0, "{:#?}", ran);
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "ownership_status": "owned" }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (refreshed, ran) = stats().await;
+    assert_eq!(counting(&ran), 2, "{:#?}", ran);
+    assert_eq!(refreshed["owned_albums"], 1);
+}
+
+#[tokio::test]
+async fn test_collection_value_report() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Artist", None).await;
+    let albums_to_create = [
+        ("Vinyl", OwnershipStatus::Owned, Some(AcquisitionSource::Physical), Some(40.0)),
+        ("CD", OwnershipStatus::Owned, Some(AcquisitionSource::Physical), Some(12.5)),
+        ("Download", OwnershipStatus::Owned, Some(AcquisitionSource::Bandcamp), Some(9.0)),
+        ("Unpriced", OwnershipStatus::Owned, None, None),
+        ("Wishlist", OwnershipStatus::NotOwned, Some(AcquisitionSource::Physical), Some(100.0)),
+    ];
+
+    for (title, status, source, value) in albums_to_create {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.ownership_status = Set(status.as_str().to_string());
+        active.acquisition_source = Set(source.map(|s| s.as_str().to_string()));
+        active.estimated_value = Set(value);
+        active.update(&state.db).await.unwrap();
+    }
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/reports/value")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+
+    // Albums that aren't owned don't count
+    assert_eq!(body["total_value"], 61.5);
+    assert_eq!(body["owned_albums"], 4);
+    assert_eq!(body["valued_albums"], 3);
+
+    let by_source = body["by_source"].as_array().unwrap();
+    assert_eq!(by_source.len(), 3);
+    let physical = by_source
+        .iter()
+        .find(|s| s["acquisition_source"] == "physical")
+        .unwrap();
+    assert_eq!(physical["total_value"], 52.5);
+    assert_eq!(physical["owned_albums"], 2);
+    let unknown = by_source
+        .iter()
+        .find(|s| s["acquisition_source"] == "unknown")
+        .unwrap();
+    assert_eq!(unknown["total_value"], 0.0);
+    assert_eq!(unknown["valued_albums"], 0);
+}
+
+#[tokio::test]
+async fn test_search_lidarr_no_settings() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(&format!("/api/albums/{}/search-lidarr", album.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Should fail because user settings don't exist
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_search_lidarr_no_musicbrainz_id() {
+    let state = setup_test_app_state().await;
+
+    // Create user settings
+    let now = chrono::Utc::now().into();
+    let settings = user_settings::ActiveModel {
+        lidarr_url: Set(Some("http://localhost:8686".to_string())),
+        lidarr_api_key: Set(Some("test-api-key".to_string())),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+    settings.insert(&state.db).await.unwrap();
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Test Album", None).await;
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(&format!("/api/albums/{}/search-lidarr", album.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Should fail because album doesn't have MusicBrainz ID
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Helper to set an album's match score
+async fn set_match_score(state: &AppState, album: albums::Model, score: Option<i32>) {
+    let mut active: albums::ActiveModel = album.into();
+    active.match_status = Set(Some(MatchStatus::Matched.as_str().to_string()));
+    active.match_score = Set(score);
+    active.update(&state.db).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_list_albums_by_match_score() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let solid = create_test_album(&state.db, artist.id, "Solid Match", None).await;
+    let borderline = create_test_album(&state.db, artist.id, "Borderline", None).await;
+    let shaky = create_test_album(&state.db, artist.id, "Shaky", None).await;
+    let unscored = create_test_album(&state.db, artist.id, "Unscored", None).await;
+    let rejected = create_test_album(&state.db, artist.id, "Rejected", None).await;
+
+    set_match_score(&state, solid, Some(95)).await;
+    set_match_score(&state, borderline, Some(85)).await;
+    set_match_score(&state, shaky, Some(70)).await;
+    set_match_score(&state, unscored, None).await;
+    // Scored by a candidate that was turned down, so not a match to review
+    set_match(&state, rejected, MatchStatus::NoMatch, Some(75), MatchSource::Auto).await;
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums/by-match-score?min=70&max=89")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    let albums = body["albums"].as_array().unwrap();
+    assert_eq!(albums.len(), 2);
+    assert_eq!(albums[0]["title"], "Shaky");
+    assert_eq!(albums[0]["match_score"], 70);
+    assert_eq!(albums[1]["title"], "Borderline");
+    assert_eq!(body["pagination"]["total_items"], 2);
+}
+
+#[tokio::test]
+async fn test_list_albums_by_match_score_pagination() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    for score in [81, 82, 83] {
+        let album = create_test_album(&state.db, artist.id, &format!("Album {}", score), None).await;
+        set_match_score(&state, album, Some(score)).await;
+    }
+
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums/by-match-score?min=80&max=89&page=2&page_size=2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["albums"].as_array().unwrap().len(), 1);
+    assert_eq!(body["albums"][0]["match_score"], 83);
+    assert_eq!(body["pagination"]["total_pages"], 2);
+}
+
+#[tokio::test]
+async fn test_list_albums_by_match_score_invalid_range() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums/by-match-score?min=90&max=70")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Helper to give an album a match result
+async fn set_match(
+    state: &AppState,
+    album: albums::Model,
+    status: MatchStatus,
+    score: Option<i32>,
+    source: MatchSource,
+) -> albums::Model {
+    let mut active: albums::ActiveModel = album.into();
+    active.match_status = Set(Some(status.as_str().to_string()));
+    active.match_score = Set(score);
+    active.match_source = Set(Some(source.as_str().to_string()));
+    active.musicbrainz_release_group_id = Set(Some(format!("rg-{:?}", score)));
+    active.update(&state.db).await.unwrap()
+}
+
+fn reset_matches(scope: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/api/albums/reset-matches?scope={}", scope))
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn match_status_of(state: &AppState, album: &albums::Model) -> Option<String> {
+    albums::Entity::find_by_id(album.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .match_status
+}
+
+#[tokio::test]
+async fn test_reset_matches_low_score() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let solid = create_test_album(&state.db, artist.id, "Solid", None).await;
+    let review = create_test_album(&state.db, artist.id, "Review", None).await;
+    let weak = create_test_album(&state.db, artist.id, "Weak", None).await;
+    let manual = create_test_album(&state.db, artist.id, "Manual", None).await;
+
+    let solid = set_match(&state, solid, MatchStatus::Matched, Some(95), MatchSource::Auto).await;
+    let review = set_match(&state, review, MatchStatus::ManualReview, Some(85), MatchSource::Auto).await;
+    let weak = set_match(&state, weak, MatchStatus::NoMatch, Some(60), MatchSource::Auto).await;
+    let manual = set_match(&state, manual, MatchStatus::Matched, Some(50), MatchSource::Manual).await;
+
+    let app = create_test_router(&state);
+    let response = app.oneshot(reset_matches("low_score")).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["scope"], "low_score");
+    assert_eq!(body["reset_count"], 2);
+
+    let review = albums::Entity::find_by_id(review.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(review.match_status.as_deref(), Some(MatchStatus::Pending.as_str()));
+    assert_eq!(review.match_score, None);
+    assert_eq!(review.musicbrainz_release_group_id, None);
+    assert_eq!(match_status_of(&state, &weak).await.as_deref(), Some("pending"));
+
+    assert_eq!(match_status_of(&state, &solid).await.as_deref(), Some("matched"));
+    assert_eq!(match_status_of(&state, &manual).await.as_deref(), Some("matched"));
+}
+
+#[tokio::test]
+async fn test_reset_matches_no_match() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let missing = create_test_album(&state.db, artist.id, "Missing", None).await;
+    let review = create_test_album(&state.db, artist.id, "Review", None).await;
+
+    let missing = set_match(&state, missing, MatchStatus::NoMatch, None, MatchSource::Auto).await;
+    let review = set_match(&state, review, MatchStatus::ManualReview, Some(85), MatchSource::Auto).await;
+
+    let app = create_test_router(&state);
+    let response = app.oneshot(reset_matches("no_match")).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["reset_count"], 1);
+    assert_eq!(match_status_of(&state, &missing).await.as_deref(), Some("pending"));
+    assert_eq!(match_status_of(&state, &review).await.as_deref(), Some("manual_review"));
+}
+
+#[tokio::test]
+async fn test_reset_matches_all_includes_manual() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let manual = create_test_album(&state.db, artist.id, "Manual", None).await;
+    let solid = create_test_album(&state.db, artist.id, "Solid", None).await;
+    // Still pending: not counted as reset
+    create_test_album(&state.db, artist.id, "Pending", None).await;
+
+    let manual = set_match(&state, manual, MatchStatus::Matched, None, MatchSource::Manual).await;
+    let solid = set_match(&state, solid, MatchStatus::Matched, Some(95), MatchSource::Auto).await;
+
+    let app = create_test_router(&state);
+    let response = app.oneshot(reset_matches("all")).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["reset_count"], 2);
+    assert_eq!(match_status_of(&state, &manual).await.as_deref(), Some("pending"));
+    assert_eq!(match_status_of(&state, &solid).await.as_deref(), Some("pending"));
+}
+
+#[tokio::test]
+async fn test_reset_matches_invalid_scope() {
+    let state = setup_test_app_state().await;
+
+    let artist = create_test_artist(&state.db, "Test Artist", None).await;
+    let album = create_test_album(&state.db, artist.id, "Solid", None).await;
+    let album = set_match(&state, album, MatchStatus::Matched, Some(95), MatchSource::Auto).await;
+
+    for uri in ["/api/albums/reset-matches?scope=everything", "/api/albums/reset-matches"] {
+        let response = create_test_router(&state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    assert_eq!(match_status_of(&state, &album).await.as_deref(), Some("matched"));
+}
+
+/// Render the HTML album grid, returning its body and the SQL statements it ran
+async fn get_album_grid(state: &AppState, statements: &Arc<Mutex<Vec<String>>>, uri: &str) -> (String, Vec<String>) {
+    statements.lock().unwrap().clear();
+    let response = Router::new()
+        .merge(handlers::html_routes())
+        .with_state(state.clone())
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let ran = statements.lock().unwrap().clone();
+    (String::from_utf8(body.to_vec()).unwrap(), ran)
+}
+
+#[tokio::test]
+async fn test_default_album_grid_is_cached() {
+    let mut state = setup_test_app_state().await;
+    let statements = Arc::new(Mutex::new(Vec::new()));
+    let recorder = statements.clone();
+    state.db.set_metric_callback(move |info| {
+        recorder.lock().unwrap().push(info.statement.sql.clone());
+    });
+    let touches_albums = |sql: &[String]| sql.iter().any(|s| s.contains(r#""albums""#));
+
+    let artist = create_test_artist(&state.db, "Radiohead", None).await;
+    let album = create_test_album(&state.db, artist.id, "OK Computer", None).await;
+
+    let (first, ran) = get_album_grid(&state, &statements, "/albums").await;
+    assert!(first.contains("OK Computer"));
+    assert!(touches_albums(&ran));
+
+    // An identical request is served from Redis without querying albums
+    let (second, ran) = get_album_grid(&state, &statements, "/albums").await;
+    assert_eq!(second, first);
+    assert!(!touches_albums(&ran), "cached grid queried albums: {:?}", ran);
+
+    // Filtered pages are never cached
+    let (_, ran) = get_album_grid(&state, &statements, "/albums?search=OK").await;
+    assert!(touches_albums(&ran));
+
+    // Updating an album invalidates the cached page
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", album.id))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "ownership_status": "owned" }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (third, ran) = get_album_grid(&state, &statements, "/albums").await;
+    assert!(touches_albums(&ran));
+    assert_ne!(third, first);
+}
+
+/// Playlist in the default profile holding the given tracks
+async fn create_playlist_with_tracks(state: &AppState, name: &str, enabled: bool, track_ids: &[i32]) {
+    let now = chrono::Utc::now().into();
+    let playlist = playlists::ActiveModel {
+        name: Set(name.to_string()),
+        spotify_id: Set(name.to_lowercase()),
+        is_collaborative: Set(false),
+        total_tracks: Set(Some(track_ids.len() as i32)),
+        is_enabled: Set(enabled),
+        is_synthetic: Set(false),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    profile::link_playlist(&state.db, default_profile.id, playlist.id).await.unwrap();
+
+    for (position, track_id) in track_ids.iter().enumerate() {
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist.id),
+            track_id: Set(*track_id),
+            position: Set(position as i32),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+    }
+}
+
+async fn create_track(state: &AppState, album_id: i32, title: &str) -> i32 {
+    let now = chrono::Utc::now().into();
+    tracks::ActiveModel {
+        album_id: Set(album_id),
+        title: Set(title.to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap()
+    .id
+}
+
+#[tokio::test]
+async fn test_track_ownership_dedupes_shared_tracks() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Radiohead", None).await;
+    let ok_computer = create_test_album(&state.db, artist.id, "OK Computer", None).await;
+    let kid_a = create_test_album(&state.db, artist.id, "Kid A", None).await;
+    let in_rainbows = create_test_album(&state.db, artist.id, "In Rainbows", None).await;
+    mark_owned(&state, ok_computer.clone()).await;
+    mark_owned(&state, in_rainbows.clone()).await;
+
+    let paranoid_android = create_track(&state, ok_computer.id, "Paranoid Android").await;
+    let idioteque = create_track(&state, kid_a.id, "Idioteque").await;
+    let reckoner = create_track(&state, in_rainbows.id, "Reckoner").await;
+
+    create_playlist_with_tracks(&state, "Favourites", true, &[paranoid_android, idioteque]).await;
+    create_playlist_with_tracks(&state, "Late Night", true, &[paranoid_android, reckoner]).await;
+    create_playlist_with_tracks(&state, "Archive", false, &[idioteque, reckoner]).await;
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/stats/track-ownership")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["enabled_playlists"], 2);
+
+    // Paranoid Android is in both enabled playlists but is one track
+    assert_eq!(body["distinct"]["tracks"], 3);
+    assert_eq!(body["distinct"]["owned_tracks"], 2);
+    let distinct = body["distinct"]["ownership_percentage"].as_f64().unwrap();
+    assert!((distinct - 200.0 / 3.0).abs() < 0.01);
+
+    assert_eq!(body["naive_sum"]["tracks"], 4);
+    assert_eq!(body["naive_sum"]["owned_tracks"], 3);
+    assert_eq!(body["naive_sum"]["ownership_percentage"], 75.0);
+}
+
+#[tokio::test]
+async fn test_track_ownership_without_playlists() {
+    let state = setup_test_app_state().await;
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/stats/track-ownership")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["enabled_playlists"], 0);
+    assert_eq!(body["distinct"]["tracks"], 0);
+    assert_eq!(body["naive_sum"]["owned_tracks"], 0);
+    assert_eq!(body["naive_sum"]["ownership_percentage"], 0.0);
+}
+
+async fn get_genre_distribution(state: &AppState, query: &str) -> serde_json::Value {
+    let response = create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/stats/genres{}", query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    parse_json_response(response).await
+}
+
+#[tokio::test]
+async fn test_genre_distribution_folds_small_genres_into_other() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Various", None).await;
+
+    let albums_to_seed = [
+        ("A", Some(r#"["electronic", "house"]"#), true),
+        ("B", Some(r#"["electronic"]"#), false),
+        ("C", Some(r#"["rock"]"#), true),
+        ("D", None, false),
+        ("E", Some("[]"), false),
+        ("F", Some(r#"["jazz", "house"]"#), false),
+    ];
+    for (title, genres, owned) in albums_to_seed {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.genres = Set(genres.map(|g| g.to_string()));
+        if owned {
+            active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+        }
+        active.update(&state.db).await.unwrap();
+    }
+
+    let body = get_genre_distribution(&state, "?top=2").await;
+
+    // Ties are broken by name; albums without genres count as Unknown
+    assert_eq!(body["total_albums"], 6);
+    assert_eq!(
+        body["genres"],
+        json!([
+            { "genre": "Unknown", "album_count": 2, "owned_count": 0 },
+            { "genre": "electronic", "album_count": 2, "owned_count": 1 },
+        ])
+    );
+    // house, jazz and rock; A, C and F each count once
+    assert_eq!(body["other"], json!({ "genre_count": 3, "album_count": 3, "owned_count": 2 }));
+
+    let body = get_genre_distribution(&state, "").await;
+    assert_eq!(body["genres"].as_array().unwrap().len(), 5);
+    assert_eq!(body["other"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_genre_distribution_of_empty_library() {
+    let state = setup_test_app_state().await;
+
+    let body = get_genre_distribution(&state, "").await;
+
+    assert_eq!(body, json!({ "total_albums": 0, "genres": [], "other": null }));
+}
+
+#[tokio::test]
+async fn test_artist_heatmap_orders_and_limits() {
+    let mut state = setup_test_app_state().await;
+    let statements = Arc::new(Mutex::new(Vec::new()));
+    let recorder = statements.clone();
+    state.db.set_metric_callback(move |info| {
+        recorder.lock().unwrap().push(info.statement.sql.clone());
+    });
+
+    let air = create_test_artist(&state.db, "Air", None).await;
+    let bjork = create_test_artist(&state.db, "Björk", None).await;
+    let cocteau = create_test_artist(&state.db, "Cocteau Twins", None).await;
+    let dntel = create_test_artist(&state.db, "Dntel", None).await;
+    let seed = [
+        (bjork.id, "Vespertine", Some("2001-08-27"), OwnershipStatus::Owned),
+        (bjork.id, "Post", Some("1995-06-13"), OwnershipStatus::Downloading),
+        (bjork.id, "Unreleased", None, OwnershipStatus::NotOwned),
+        (air.id, "Talkie Walkie", Some("2004-01-26"), OwnershipStatus::Owned),
+        (air.id, "Moon Safari", Some("1998-01-16"), OwnershipStatus::NotOwned),
+        (cocteau.id, "Heaven or Las Vegas", Some("1990-09-17"), OwnershipStatus::Ordered),
+        (cocteau.id, "Treasure", Some("1984-11-01"), OwnershipStatus::Owned),
+        (dntel.id, "Life Is Full of Possibilities", Some("2001-04-10"), OwnershipStatus::NotOwned),
+    ];
+    for (artist_id, title, released, status) in seed {
+        let album = create_test_album(&state.db, artist_id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.release_date = Set(released.map(|d| d.parse().unwrap()));
+        active.ownership_status = Set(status.as_str().to_string());
+        active.update(&state.db).await.unwrap();
+    }
+    // Archived albums don't count towards an artist or get a cell
+    let archived = create_test_album(&state.db, dntel.id, "Dumb Luck", None).await;
+    let mut active: albums::ActiveModel = archived.into();
+    active.archived_at = Set(Some(chrono::Utc::now().into()));
+    active.update(&state.db).await.unwrap();
+
+    let heatmap = |query: &'static str| {
+        let state = state.clone();
+        let statements = statements.clone();
+        async move {
+            statements.lock().unwrap().clear();
+            let response = create_test_router(&state)
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/api/stats/artist-heatmap{}", query))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body: serde_json::Value = parse_json_response(response).await;
+            let ran = statements.lock().unwrap().clone();
+            (body, ran)
+        }
+    };
+    let cells = |artist: &serde_json::Value| -> Vec<(String, String)> {
+        artist["albums"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| (a["title"].as_str().unwrap().to_string(), a["ownership_status"].as_str().unwrap().to_string()))
+            .collect()
+    };
+
+    // Most albums first, ties by name; albums oldest first, undated last
+    let (body, ran) = heatmap("?limit=3").await;
+    let artists = body["artists"].as_array().unwrap();
+    let names: Vec<&str> = artists.iter().map(|a| a["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Björk", "Air", "Cocteau Twins"]);
+    assert_eq!(artists[0]["album_count"], 3);
+    assert_eq!(
+        cells(&artists[0]),
+        vec![
+            ("Post".to_string(), "downloading".to_string()),
+            ("Vespertine".to_string(), "owned".to_string()),
+            ("Unreleased".to_string(), "not_owned".to_string()),
+        ]
+    );
+    assert_eq!(cells(&artists[2])[0], ("Treasure".to_string(), "owned".to_string()));
+    assert_eq!(artists[0]["hidden_albums"], 0);
+    let heatmap_queries = ran
+        .iter()
+        .filter(|s| s.contains(r#"FROM "albums""#) || s.contains(r#"FROM "artists""#))
+        .count();
+    assert_eq!(heatmap_queries, 2, "{:#?}", ran);
+
+    let (body, _) = heatmap("?limit=1").await;
+    assert_eq!(body["artists"].as_array().unwrap().len(), 1);
+
+    // The default covers every artist here, and the archived album is left out
+    let (body, _) = heatmap("").await;
+    let artists = body["artists"].as_array().unwrap();
+    assert_eq!(artists.len(), 4);
+    assert_eq!(artists[3]["name"], "Dntel");
+    assert_eq!(artists[3]["album_count"], 1);
+}
+
+async fn search_tracks(state: &AppState, query: &str) -> (StatusCode, serde_json::Value) {
+    let response = create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/search/tracks?{}", query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    (status, parse_json_response(response).await)
+}
+
+#[tokio::test]
+async fn test_search_tracks_matches_title_across_albums() {
+    let state = setup_test_app_state().await;
+    let radiohead = create_test_artist(&state.db, "Radiohead", None).await;
+    let portishead = create_test_artist(&state.db, "Portishead", None).await;
+    let ok_computer = create_test_album(&state.db, radiohead.id, "OK Computer", None).await;
+    let dummy = create_test_album(&state.db, portishead.id, "Dummy", None).await;
+    mark_owned(&state, ok_computer.clone()).await;
+
+    let airbag = create_track(&state, ok_computer.id, "Airbag").await;
+    create_track(&state, ok_computer.id, "Lucky").await;
+    let roads = create_track(&state, dummy.id, "Roads").await;
+    create_track(&state, dummy.id, "Sour Times").await;
+
+    // Case-insensitive substring match, ordered by title
+    let (status, body) = search_tracks(&state, "q=A").await;
+    assert_eq!(status, StatusCode::OK);
+    let hits = body["tracks"].as_array().unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0]["track_id"], airbag);
+    assert_eq!(hits[0]["album_id"], ok_computer.id);
+    assert_eq!(hits[0]["album_title"], "OK Computer");
+    assert_eq!(hits[0]["artist_name"], "Radiohead");
+    assert_eq!(hits[0]["ownership_status"], "owned");
+    assert_eq!(hits[1]["track_id"], roads);
+    assert_eq!(hits[1]["artist_id"], portishead.id);
+    assert_eq!(hits[1]["ownership_status"], "not_owned");
+    assert_eq!(body["pagination"]["total_items"], 2);
+
+    let (_, body) = search_tracks(&state, "q=a&page=2&page_size=1").await;
+    assert_eq!(body["tracks"][0]["track_id"], roads);
+    assert_eq!(body["pagination"]["total_pages"], 2);
+}
+
+#[tokio::test]
+async fn test_tracks_search_alias_finds_tracks_by_name() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+    let track = create_track(&state, album.id, "One More Time").await;
+    create_track(&state, album.id, "Aerodynamic").await;
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/tracks/search?q=more%20time")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["tracks"].as_array().unwrap().len(), 1);
+    assert_eq!(body["tracks"][0]["track_id"], track);
+    assert_eq!(body["tracks"][0]["album_id"], album.id);
+    assert_eq!(body["tracks"][0]["artist_name"], "Daft Punk");
+}
+
+#[tokio::test]
+async fn test_search_tracks_treats_wildcards_literally() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Various", None).await;
+    let album = create_test_album(&state.db, artist.id, "Mix", None).await;
+    let pure = create_track(&state, album.id, "100% Pure").await;
+    create_track(&state, album.id, "1000 Pure").await;
+    let snake = create_track(&state, album.id, "snake_case").await;
+    create_track(&state, album.id, "snakes case").await;
+
+    let (_, body) = search_tracks(&state, "q=100%25").await;
+    let hits = body["tracks"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["track_id"], pure);
+
+    let (_, body) = search_tracks(&state, "q=e_c").await;
+    let hits = body["tracks"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["track_id"], snake);
+}
+
+#[tokio::test]
+async fn test_search_tracks_matches_unicode_title() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Sigur Rós", None).await;
+    let album = create_test_album(&state.db, artist.id, "Ágætis byrjun", None).await;
+    let track = create_track(&state, album.id, "Starálfur").await;
+    create_track(&state, album.id, "Staralfur").await;
+
+    // "stará"
+    let (status, body) = search_tracks(&state, "q=star%C3%A1").await;
+    assert_eq!(status, StatusCode::OK);
+    let hits = body["tracks"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["track_id"], track);
+    assert_eq!(hits[0]["title"], "Starálfur");
+    assert_eq!(hits[0]["artist_name"], "Sigur Rós");
+}
+
+#[tokio::test]
+async fn test_archived_albums_leave_default_lists() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Boards of Canada", None).await;
+    let kept = create_test_album(&state.db, artist.id, "Music Has the Right to Children", None).await;
+    let archived = create_test_album(&state.db, artist.id, "Geogaddi", None).await;
+    let mut active: albums::ActiveModel = archived.clone().into();
+    active.archived_at = Set(Some(chrono::Utc::now().into()));
+    active.update(&state.db).await.unwrap();
+
+    let response = create_test_router(&state)
+        .oneshot(Request::builder().uri("/api/albums").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body: serde_json::Value = parse_json_response(response).await;
+    let albums = body["albums"].as_array().unwrap();
+    assert_eq!(albums.len(), 1);
+    assert_eq!(albums[0]["id"], kept.id);
+
+    let response = create_test_router(&state)
+        .oneshot(Request::builder().uri("/api/archive?type=albums").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["albums"][0]["id"], archived.id);
+    assert_eq!(body["playlists"], json!([]));
+
+    let response = create_test_router(&state)
+        .oneshot(Request::builder().uri("/api/archive?type=artists").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_archived_albums_leave_reports_and_exports() {
+    let state = setup_test_app_state().await;
+    seed_wanted_export(&state).await;
+    let artist = create_test_artist(&state.db, "Boards of Canada", None).await;
+    for (title, ownership, mbid) in [
+        ("Geogaddi", OwnershipStatus::Owned, None),
+        ("Tomorrow's Harvest", OwnershipStatus::NotOwned, Some("rg-tomorrows-harvest")),
+    ] {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.ownership_status = Set(ownership.as_str().to_string());
+        active.match_status = Set(Some(MatchStatus::Matched.as_str().to_string()));
+        active.musicbrainz_release_group_id = Set(mbid.map(|m| m.to_string()));
+        active.estimated_value = Set(Some(30.0));
+        active.archived_at = Set(Some(chrono::Utc::now().into()));
+        active.update(&state.db).await.unwrap();
+    }
+
+    let response = create_test_router(&state)
+        .oneshot(Request::builder().uri("/api/reports/value").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["owned_albums"], 1);
+    assert_eq!(body["valued_albums"], 0);
+
+    let response = export_wanted(&state, "format=mbid").await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(!String::from_utf8(body.to_vec()).unwrap().contains("rg-tomorrows-harvest"));
+}
+
+#[tokio::test]
+async fn test_search_tracks_requires_query() {
+    let state = setup_test_app_state().await;
+
+    let (status, _) = search_tracks(&state, "q=%20%20").await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Send a request to the HTML routes and return the status and body
+async fn html_request(state: &AppState, request: Request<Body>) -> (StatusCode, String) {
+    let response = Router::new()
+        .merge(handlers::html_routes())
+        .with_state(state.clone())
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+fn board_move(album_id: i32, to: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/board/albums/{}/move", album_id))
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(format!("to={}", to)))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_board_renders_columns() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    create_test_album(&state.db, artist.id, "Homework", None).await;
+    let discovery = create_test_album(&state.db, artist.id, "Discovery", None).await;
+
+    let mut active: albums::ActiveModel = discovery.into();
+    active.ownership_status = Set(OwnershipStatus::Ordered.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+
+    let (status, html) =
+        html_request(&state, Request::builder().uri("/board").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Columns in pipeline order, each with its albums
+    let positions: Vec<usize> = ["Wanted", "Ordered", "Downloading", "Owned"]
+        .iter()
+        .map(|title| html.find(&format!(">{}</h2>", title)).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+    let wanted = html.find(r#"id="board-cards-not_owned""#).unwrap();
+    let ordered = html.find(r#"id="board-cards-ordered""#).unwrap();
+    let homework = html.find("Homework").unwrap();
+    let discovery = html.find("Discovery").unwrap();
+    assert!(wanted < homework && homework < ordered && ordered < discovery);
+}
+
+#[tokio::test]
+async fn test_board_move_updates_album_and_counts() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+
+    let (status, html) = html_request(&state, board_move(album.id, "ordered")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The card lands at the top of the Ordered column, and every count is refreshed
+    assert!(html.contains(r#"hx-swap-oob="afterbegin:#board-cards-ordered""#));
+    assert!(html.contains(&format!(r#"id="board-card-{}""#, album.id)));
+    for (column, count) in [("not_owned", 0), ("ordered", 1), ("downloading", 0), ("owned", 0)] {
+        let id = format!(r#"id="board-count-{}""#, column);
+        let start = html.find(&id).unwrap_or_else(|| panic!("no count for {}", column));
+        let rendered = &html[start..];
+        assert!(rendered.contains(r#"hx-swap-oob="true""#));
+        assert!(rendered.contains(&format!(">{}</span>", count)), "{}", column);
+    }
+
+    let stored = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(stored.ownership_status, "ordered");
+
+    state.events.settled().await;
+    let events = ownership_events::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].from_status, "not_owned");
+    assert_eq!(events[0].to_status, "ordered");
+
+    // Owned albums stay put, and unknown states are rejected
+    let (status, _) = html_request(&state, board_move(album.id, "owned")).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = html_request(&state, board_move(album.id, "ordered")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = html_request(&state, board_move(album.id, "shipped")).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_wanted_since_follows_ownership_moves() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+    let mut active: albums::ActiveModel = album.clone().into();
+    active.wanted_since = Set(Some((chrono::Utc::now() - chrono::Duration::days(800)).into()));
+    active.update(&state.db).await.unwrap();
+
+    let (_, html) = html_request(&state, Request::builder().uri("/board").body(Body::empty()).unwrap()).await;
+    assert!(html.contains("Wanted for 2 years"));
+
+    // Ordering it ends the want; cancelling the order starts a new one. Both
+    // are written with the move itself, without waiting for subscribers
+    html_request(&state, board_move(album.id, "ordered")).await;
+    let stored = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(stored.wanted_since, None);
+
+    html_request(&state, board_move(album.id, "not_owned")).await;
+    let stored = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    let since = stored.wanted_since.unwrap();
+    assert!(chrono::Utc::now() - since.with_timezone(&chrono::Utc) < chrono::Duration::minutes(1));
+}
+
+async fn json_request(
+    state: &AppState,
+    method: &str,
+    uri: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let response = create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+#[tokio::test]
+async fn test_stale_wants_are_demoted_and_restorable() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Homework", None).await;
+    let mut active: albums::ActiveModel = album.clone().into();
+    active.wanted_since = Set(Some((chrono::Utc::now() - chrono::Duration::days(400)).into()));
+    active.match_status = Set(Some(MatchStatus::Matched.as_str().to_string()));
+    active.musicbrainz_release_group_id = Set(Some("rg-homework".to_string()));
+    active.update(&state.db).await.unwrap();
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    let want_cleared_at = || async {
+        profile::album_link(&state.db, default_profile.id, album.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .want_cleared_at
+    };
+    let exported = || async {
+        let response = export_wanted(&state, "format=mbid").await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    };
+
+    let uri = format!("/api/albums/{}", album.id);
+    let (status, _) = json_request(&state, "PATCH", &uri, json!({ "want_priority": 6 })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, body) = json_request(&state, "PATCH", &uri, json!({ "want_priority": 1 })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["want_priority"], 1);
+
+    // Off by default
+    assert_eq!(beat_collector::tasks::wanted_demotion::demote_stale_wants(&state).await.unwrap(), 0);
+
+    let (status, _) = json_request(&state, "PUT", "/api/settings", json!({ "wanted_demotion_days": 0 })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, body) = json_request(
+        &state,
+        "PUT",
+        "/api/settings",
+        json!({ "wanted_demotion_enabled": true, "wanted_demotion_days": 365 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["wanted_demotion_enabled"], true);
+
+    // Already at the lowest priority, so the want is cleared: off the export, not archived
+    assert_eq!(beat_collector::tasks::wanted_demotion::demote_stale_wants(&state).await.unwrap(), 1);
+    assert!(want_cleared_at().await.is_some());
+    let stored = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(stored.archived_at, None);
+    assert_eq!(exported().await, "");
+
+    let (_, body) = json_request(&state, "GET", &format!("{}/activity", uri), serde_json::Value::Null).await;
+    let demotion = &body["activity"][0];
+    assert_eq!(demotion["kind"], "want_demotion");
+    assert_eq!(demotion["cleared"], true);
+    let restore_url = demotion["restore_url"].as_str().unwrap().to_string();
+
+    let (status, body) = json_request(&state, "POST", &restore_url, serde_json::Value::Null).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["want_priority"], 1);
+    assert_eq!(want_cleared_at().await, None);
+    assert_eq!(exported().await, "rg-homework\n");
+
+    let (_, body) = json_request(&state, "GET", &format!("{}/activity", uri), serde_json::Value::Null).await;
+    assert!(body["activity"][0]["restore_url"].is_null());
 }
@@ -0,0 +1,388 @@
+//! Integration tests for artist handler routes
+//!
+//! Tests the bulk ownership endpoint:
+//! - Mark all of an artist's albums owned
+//! - Playlist owned counts follow
+//! - Every change lands in the audit trail, however many albums move
+//! - Reject unknown acquisition sources
+//!
+//! And the artist completion report, as JSON and CSV, without archived albums.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, EntityTrait, PaginatorTrait, Set};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+use beat_collector::db::{
+    entities::{albums, artists, ownership_events, playlist_tracks, playlists, tracks},
+    enums::{AcquisitionSource, OwnershipStatus},
+};
+use beat_collector::events::EVENT_CAPACITY;
+use beat_collector::handlers;
+use beat_collector::state::AppState;
+use beat_collector::test_utils::*;
+
+/// Helper to create a test router with artist routes
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+/// Helper to parse JSON response body
+async fn parse_json_response<T: serde::de::DeserializeOwned>(
+    response: axum::response::Response,
+) -> T {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn mark_owned(key: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/api/artists/{}/mark-owned", key))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Playlist with one track from each of the given albums
+async fn create_playlist_with_albums(state: &AppState, album_ids: &[i32]) -> playlists::Model {
+    let now = Utc::now().into();
+    let playlist = playlists::ActiveModel {
+        name: Set("Mixtape".to_string()),
+        spotify_id: Set("mixtape".to_string()),
+        is_collaborative: Set(false),
+        total_tracks: Set(Some(album_ids.len() as i32)),
+        is_enabled: Set(true),
+        is_synthetic: Set(false),
+        owned_count: Set(Some(0)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    for (position, album_id) in album_ids.iter().enumerate() {
+        let track = tracks::ActiveModel {
+            album_id: Set(*album_id),
+            title: Set(format!("Track {}", position + 1)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist.id),
+            track_id: Set(track.id),
+            position: Set(position as i32),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+    }
+
+    playlist
+}
+
+#[tokio::test]
+async fn test_mark_artist_owned() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let artist = create_test_artist(&state.db, "Talk Talk", None).await;
+    let colour = create_test_album(&state.db, artist.id, "The Colour of Spring", None).await;
+    let eden = create_test_album(&state.db, artist.id, "Spirit of Eden", None).await;
+    let laughing = create_test_album(&state.db, artist.id, "Laughing Stock", None).await;
+    let other_artist = create_test_artist(&state.db, "Bark Psychosis", None).await;
+    let hex = create_test_album(&state.db, other_artist.id, "Hex", None).await;
+
+    // Already owned on vinyl: keeps its source
+    let mut active: albums::ActiveModel = laughing.clone().into();
+    active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    active.acquisition_source = Set(Some(AcquisitionSource::Physical.as_str().to_string()));
+    active.update(&state.db).await.unwrap();
+
+    let playlist = create_playlist_with_albums(&state, &[colour.id, eden.id, hex.id]).await;
+
+    let response = app
+        .oneshot(mark_owned(
+            artist.slug.as_deref().unwrap(),
+            json!({ "acquisition_source": "bandcamp" }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["artist_id"], artist.id);
+    assert_eq!(body["updated_count"], 2);
+
+    for album in [&colour, &eden] {
+        let album = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+        assert_eq!(album.ownership_status, OwnershipStatus::Owned.as_str());
+        assert_eq!(album.acquisition_source.as_deref(), Some(AcquisitionSource::Bandcamp.as_str()));
+    }
+
+    let laughing = albums::Entity::find_by_id(laughing.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(laughing.acquisition_source.as_deref(), Some(AcquisitionSource::Physical.as_str()));
+
+    let hex = albums::Entity::find_by_id(hex.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(hex.ownership_status, OwnershipStatus::NotOwned.as_str());
+
+    state.events.settled().await;
+    let playlist = playlists::Entity::find_by_id(playlist.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(playlist.owned_count, Some(2));
+}
+
+#[tokio::test]
+async fn test_mark_artist_owned_audits_more_albums_than_events_buffer() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "The Fall", None).await;
+    let album_count = EVENT_CAPACITY + 10;
+    for n in 0..album_count {
+        create_test_album(&state.db, artist.id, &format!("Album {}", n), None).await;
+    }
+
+    let response = create_test_router(&state)
+        .oneshot(mark_owned(&artist.id.to_string(), json!({})))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Written with the albums, not by a subscriber that may skip events
+    let logged = ownership_events::Entity::find().count(&state.db).await.unwrap();
+    assert_eq!(logged, album_count as u64);
+}
+
+#[tokio::test]
+async fn test_mark_artist_owned_without_body() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let artist = create_test_artist(&state.db, "Talk Talk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Spirit of Eden", None).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/artists/{}/mark-owned", artist.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["updated_count"], 1);
+
+    let album = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(album.ownership_status, OwnershipStatus::Owned.as_str());
+    assert_eq!(album.acquisition_source, None);
+}
+
+#[tokio::test]
+async fn test_mark_artist_owned_invalid_source() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let artist = create_test_artist(&state.db, "Talk Talk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Spirit of Eden", None).await;
+
+    let response = app
+        .oneshot(mark_owned(&artist.id.to_string(), json!({ "acquisition_source": "stolen" })))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let album = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(album.ownership_status, OwnershipStatus::NotOwned.as_str());
+}
+
+#[tokio::test]
+async fn test_mark_artist_owned_not_found() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app.oneshot(mark_owned("nobody", json!({}))).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+fn rename(key: &str, name: &str) -> Request<Body> {
+    Request::builder()
+        .method("PATCH")
+        .uri(format!("/api/artists/{}", key))
+        .header("content-type", "application/json")
+        .body(Body::from(json!({ "name": name }).to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_rename_artist_locks_name_and_keeps_slug() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    let artist = create_test_artist(&state.db, "sigur ros", None).await;
+
+    let response = app.oneshot(rename("sigur-ros", "  Sigur Rós ")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["name"], "Sigur Rós");
+    assert_eq!(body["slug"], "sigur-ros");
+    assert_eq!(body["name_locked"], true);
+
+    let stored = artists::Entity::find_by_id(artist.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(stored.name, "Sigur Rós");
+    assert!(stored.name_locked);
+    assert!(stored.updated_at >= artist.updated_at);
+}
+
+#[tokio::test]
+async fn test_rename_artist_rejects_empty_name() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Low", None).await;
+
+    let response = create_test_router(&state)
+        .oneshot(rename(&artist.id.to_string(), "   "))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = create_test_router(&state).oneshot(rename("nobody", "Somebody")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let stored = artists::Entity::find_by_id(artist.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(stored.name, "Low");
+    assert!(!stored.name_locked);
+}
+
+/// Artists with 2 of 2, 1 of 2 and 0 of 1 albums owned
+async fn seed_completion(state: &AppState) {
+    for (name, titles, owned) in [
+        ("Complete", &["A", "B"][..], 2),
+        ("Half, Done", &["C", "D"][..], 1),
+        ("One-off", &["E"][..], 0),
+    ] {
+        let artist = create_test_artist(&state.db, name, None).await;
+        for (i, title) in titles.iter().enumerate() {
+            let album = create_test_album(&state.db, artist.id, title, None).await;
+            if i < owned {
+                let mut active: albums::ActiveModel = album.into();
+                active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+                active.update(&state.db).await.unwrap();
+            }
+        }
+    }
+}
+
+async fn get_completion(state: &AppState, query: &str) -> axum::response::Response {
+    create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/reports/artist-completion{}", query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_artist_completion_least_complete_first() {
+    let state = setup_test_app_state().await;
+    seed_completion(&state).await;
+
+    let response = get_completion(&state, "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    let rows: Vec<(&str, i64, i64, f64)> = body["artists"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| {
+            (
+                a["name"].as_str().unwrap(),
+                a["album_count"].as_i64().unwrap(),
+                a["owned_count"].as_i64().unwrap(),
+                a["ownership_percentage"].as_f64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        vec![("One-off", 1, 0, 0.0), ("Half, Done", 2, 1, 50.0), ("Complete", 2, 2, 100.0)]
+    );
+
+    let body: serde_json::Value = parse_json_response(get_completion(&state, "?min_albums=2").await).await;
+    let names: Vec<&str> = body["artists"].as_array().unwrap().iter().map(|a| a["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Half, Done", "Complete"]);
+}
+
+#[tokio::test]
+async fn test_artist_completion_csv() {
+    let state = setup_test_app_state().await;
+    seed_completion(&state).await;
+
+    let response = get_completion(&state, "?format=csv&min_albums=2").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/csv; charset=utf-8");
+    assert!(response.headers()["content-disposition"]
+        .to_str()
+        .unwrap()
+        .contains("artist-completion.csv"));
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let lines: Vec<String> = String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        // Drop the artist IDs, which depend on insertion order
+        .map(|line| line.split_once(',').unwrap().1.to_string())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "artist,album_count,owned_count,ownership_percentage",
+            "\"Half, Done\",2,1,50.0",
+            "Complete,2,2,100.0",
+        ]
+    );
+
+    let response = get_completion(&state, "?format=xml").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_artist_completion_leaves_out_archived_albums() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Boards of Canada", None).await;
+    create_test_album(&state.db, artist.id, "Geogaddi", None).await;
+    let archived = create_test_album(&state.db, artist.id, "Tomorrow's Harvest", None).await;
+    let mut active: albums::ActiveModel = archived.into();
+    active.archived_at = Set(Some(Utc::now().into()));
+    active.update(&state.db).await.unwrap();
+
+    let body: serde_json::Value = parse_json_response(get_completion(&state, "").await).await;
+    assert_eq!(body["artists"][0]["album_count"], 1);
+
+    let response = get_completion(&state, "?format=csv").await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8(body.to_vec()).unwrap().contains("Boards of Canada,1,0,0.0"));
+}
@@ -0,0 +1,1996 @@
+//! End-to-end tests against mock Spotify and Lidarr servers
+//!
+//! Spins up wiremock servers that serve recorded fixture JSON from
+//! `tests/fixtures`, points the app config at them and drives whole flows
+//! through the real HTTP services:
+//! - Trigger Spotify sync → job executor → library in the database
+//! - Collaborative playlists skipped when the setting is off
+//! - Playlists that keep answering 404 disabled, while outages don't count
+//! - Podcast episodes in playlists skipped and counted
+//! - Artists follow Spotify renames unless their name was changed here
+//! - Un-saved albums, unfollowed playlists and un-liked tracks archived, then restored
+//! - Cover art fetch queued after a sync when enabled
+//! - Spotify token refresh
+//! - Lidarr search → webhooks → album ownership
+//! - Lidarr search from HTMX → notification fragments
+//! - Lidarr artist override → preview and album lookup under that artist
+//! - Spotify top artists/tracks → heavy rotation panel
+//! - Playlist sync preview against the live Spotify playlist
+//! - Playlist import by link, without following it
+//! - ListenBrainz listens → album listening history
+//! - Artist image backfill, resumed from a failed run's checkpoint
+//! - Related artists → suggestions of unowned library albums, cached
+
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::json;
+use tower::util::ServiceExt;
+use wiremock::{
+    matchers::{body_partial_json, body_string_contains, header, method, path, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use beat_collector::config::Config;
+use beat_collector::db::{
+    entities::{
+        albums, artists, jobs, lidarr_downloads, playlist_tracks, playlists, profile_albums, profiles, top_items,
+        tracks, user_settings,
+    },
+    enums::{AcquisitionSource, JobStatus, JobType, OwnershipStatus, TopItemKind},
+    profile,
+};
+use beat_collector::events;
+use beat_collector::handlers;
+use beat_collector::jobs::{JobExecutor, JobQueue};
+use beat_collector::services::CacheService;
+use beat_collector::state::AppState;
+use beat_collector::tasks::{artist_images, download_retry, listen_history, spotify_sync};
+use beat_collector::test_utils::*;
+
+const ACCESS_TOKEN: &str = "test-access-token";
+const LIDARR_API_KEY: &str = "test-lidarr-key";
+const DISCOVERY_MBID: &str = "48117b90-a16e-34ca-a514-19c702df1158";
+
+/// Helper to create a test router with all API routes
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+/// Load a fixture, substituting the mock Spotify API base URL into pagination links
+fn fixture(name: &str, spotify_api: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path, e))
+        .replace("{{SPOTIFY_API}}", spotify_api)
+}
+
+fn json_response(body: String) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_raw(body, "application/json")
+}
+
+/// Test config pointing Spotify and Lidarr at the mock servers
+fn mock_config(spotify: &MockServer, lidarr: Option<&MockServer>) -> Config {
+    Config {
+        spotify_accounts_base: spotify.uri(),
+        spotify_api_base: format!("{}/v1", spotify.uri()),
+        lidarr_url: lidarr.map(|server| server.uri()),
+        lidarr_api_key: lidarr.map(|_| LIDARR_API_KEY.to_string()),
+        ..test_config()
+    }
+}
+
+/// AppState with a running job executor behind its queue
+async fn setup_state_with_executor(config: Config) -> AppState {
+    let db = setup_test_db().await;
+    let redis = setup_test_redis().await;
+    let (job_queue, receiver) = JobQueue::new(config.job_queue_capacity);
+    let state = AppState::new(db, redis, config, job_queue, test_log_filter());
+    events::start_subscribers(&state);
+
+    tokio::spawn(JobExecutor::new(state.clone(), receiver).start());
+    state
+}
+
+/// Store Spotify tokens on the default profile
+async fn connect_spotify(state: &AppState, expires_in: chrono::Duration) {
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    let mut active: profiles::ActiveModel = default_profile.into();
+    active.spotify_access_token = Set(Some(ACCESS_TOKEN.to_string()));
+    active.spotify_refresh_token = Set(Some("test-refresh-token".to_string()));
+    active.spotify_token_expires_at = Set(Some((Utc::now() + expires_in).into()));
+    active.update(&state.db).await.unwrap();
+}
+
+/// Mount the Spotify Web API endpoints hit by a full sync
+async fn mount_spotify_library(server: &MockServer) {
+    let api = format!("{}/v1", server.uri());
+    let auth = format!("Bearer {}", ACCESS_TOKEN);
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/albums"))
+        .and(query_param_is_missing("offset"))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/saved_albums_page1.json", &api)))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/albums"))
+        .and(query_param("offset", "1"))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/saved_albums_page2.json", &api)))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/tracks"))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/saved_tracks_empty.json", &api)))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/playlists"))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/playlists.json", &api)))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/playlists/37i9dQZF1DX0XUsuxWHRQd/tracks"))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/playlist_tracks.json", &api)))
+        .mount(server)
+        .await;
+}
+
+/// Trigger a Spotify sync through the API and wait for the executor to finish it
+async fn run_sync(app: &Router, state: &AppState) -> jobs::Model {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/jobs/spotify-sync")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    wait_for_job(state, created["job_id"].as_i64().unwrap() as i32).await
+}
+
+/// Wait for the executor to finish a job
+async fn wait_for_job(state: &AppState, job_id: i32) -> jobs::Model {
+    for _ in 0..100 {
+        let job = jobs::Entity::find_by_id(job_id)
+            .one(&state.db)
+            .await
+            .unwrap()
+            .unwrap();
+        if job.status == JobStatus::Completed.as_str() || job.status == JobStatus::Failed.as_str() {
+            return job;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    panic!("Job {} did not finish", job_id);
+}
+
+#[tokio::test]
+async fn test_spotify_sync_end_to_end() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+
+    // First sync: saved albums across both pages, playlists created disabled
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+    assert_eq!(job.sync_cursor, None);
+
+    // Two saved albums; Liked Songs plus both playlists
+    let summary: serde_json::Value = serde_json::from_str(job.result.as_deref().unwrap()).unwrap();
+    assert_eq!(summary, json!({ "saved_albums": 2, "playlists": 3, "skipped": 0 }));
+    assert_eq!(job.processed_items, Some(5));
+
+    // The last progress report stays behind with the sync's API usage
+    let progress: serde_json::Value = serde_json::from_str(job.progress_detail.as_deref().unwrap()).unwrap();
+    assert!(progress["api_calls"].as_u64().unwrap() >= 4, "{}", progress);
+    assert_eq!(progress["rate_limit_hits"], 0);
+    assert_eq!(progress["remaining_requests"], 0);
+
+    let saved = albums::Entity::find().all(&state.db).await.unwrap();
+    let titles: Vec<&str> = saved.iter().map(|a| a.title.as_str()).collect();
+    assert_eq!(saved.len(), 2);
+    assert!(titles.contains(&"Discovery"));
+    assert!(titles.contains(&"OK Computer"));
+
+    let discovery = saved.iter().find(|a| a.title == "Discovery").unwrap();
+    assert_eq!(discovery.barcode.as_deref(), Some("724384960650"));
+    assert_eq!(discovery.ownership_status, OwnershipStatus::NotOwned.as_str());
+
+    let road_trip = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq("37i9dQZF1DX0XUsuxWHRQd"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .expect("Playlist should be synced");
+    assert!(!road_trip.is_enabled);
+    assert_eq!(tracks::Entity::find().all(&state.db).await.unwrap().len(), 0);
+
+    // Enable the playlist and sync again: its tracks and their albums are imported
+    let mut active: playlists::ActiveModel = road_trip.into();
+    active.is_enabled = Set(true);
+    active.update(&state.db).await.unwrap();
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    assert_eq!(tracks::Entity::find().all(&state.db).await.unwrap().len(), 2);
+    assert_eq!(albums::Entity::find().all(&state.db).await.unwrap().len(), 3);
+
+    // The market-restricted track is stored; the removed one can't be, but both are counted
+    let road_trip = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq("37i9dQZF1DX0XUsuxWHRQd"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(road_trip.unavailable_tracks, Some(2));
+    assert!(artists::Entity::find()
+        .filter(artists::Column::Name.eq("Electric Light Orchestra"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_spotify_sync_stores_relinked_tracks_under_their_playlist_id() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    // Spotify swaps in a copy of "One More Time" that plays in the user's market
+    let album = json!({
+        "id": "2noRn2Aes5aoNVsU6iWThc",
+        "name": "Discovery",
+        "artists": [{ "id": "4tZwfgrHOc3mvqYlEYSvVi", "name": "Daft Punk" }],
+        "release_date": "2001-03-12",
+        "total_tracks": 14,
+        "images": []
+    });
+    let artists = json!([{ "id": "4tZwfgrHOc3mvqYlEYSvVi", "name": "Daft Punk" }]);
+    let page = json!({
+        "total": 2,
+        "next": null,
+        "items": [
+            {
+                "added_at": "2024-03-02T08:30:00Z",
+                "track": {
+                    "id": "5W3cjX2J3tjhG8zb6u0qHn",
+                    "linked_from": { "id": "0DiWol3AO6WpXZgp0goxAV" },
+                    "is_playable": true,
+                    "name": "One More Time",
+                    "track_number": 1,
+                    "disc_number": 1,
+                    "duration_ms": 320357,
+                    "album": album,
+                    "artists": artists
+                }
+            },
+            {
+                "added_at": "2024-03-02T08:31:00Z",
+                "track": {
+                    "id": "2VEZx7NWsZ1D0eJ4uv5Fym",
+                    "is_playable": false,
+                    "name": "Digital Love",
+                    "track_number": 3,
+                    "disc_number": 1,
+                    "duration_ms": 301373,
+                    "album": album,
+                    "artists": artists
+                }
+            }
+        ]
+    });
+    Mock::given(method("GET"))
+        .and(path("/v1/playlists/37i9dQZF1DX0XUsuxWHRQd/tracks"))
+        .and(query_param("market", "from_token"))
+        .respond_with(json_response(page.to_string()))
+        .with_priority(1)
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+    run_sync(&app, &state).await;
+
+    let road_trip = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq("37i9dQZF1DX0XUsuxWHRQd"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: playlists::ActiveModel = road_trip.clone().into();
+    active.is_enabled = Set(true);
+    active.update(&state.db).await.unwrap();
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    // The relinked track keeps the ID the playlist holds
+    let one_more_time = tracks::Entity::find()
+        .filter(tracks::Column::Title.eq("One More Time"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(one_more_time.spotify_id.as_deref(), Some("0DiWol3AO6WpXZgp0goxAV"));
+    assert!(tracks::Entity::find()
+        .filter(tracks::Column::SpotifyId.eq("5W3cjX2J3tjhG8zb6u0qHn"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Playability is kept per entry, and the unavailable count follows it
+    let entries = playlist_tracks::Entity::find()
+        .filter(playlist_tracks::Column::PlaylistId.eq(road_trip.id))
+        .all(&state.db)
+        .await
+        .unwrap();
+    let playable = |track_id: i32| entries.iter().find(|e| e.track_id == track_id).unwrap().is_playable;
+    assert_eq!(playable(one_more_time.id), Some(true));
+    assert_eq!(entries.iter().filter(|e| e.is_playable == Some(false)).count(), 1);
+    let road_trip = playlists::Entity::find_by_id(road_trip.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(road_trip.unavailable_tracks, Some(1));
+}
+
+#[tokio::test]
+async fn test_spotify_sync_skips_collaborative_playlists_when_disabled() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+
+    let now = Utc::now().into();
+    user_settings::ActiveModel {
+        album_view: Set("grid".to_string()),
+        sync_collaborative_playlists: Set(Some(false)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    let synced: Vec<String> = playlists::Entity::find()
+        .filter(playlists::Column::IsSynthetic.eq(false))
+        .all(&state.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    assert_eq!(synced, vec!["Road Trip".to_string()]);
+}
+
+#[tokio::test]
+async fn test_spotify_sync_skips_podcast_episodes() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+    Mock::given(method("GET"))
+        .and(path("/v1/playlists/4fKqS2pXyA1bZ0cQ9mRt7w/tracks"))
+        .respond_with(json_response(fixture(
+            "spotify/playlist_tracks_podcasts.json",
+            &format!("{}/v1", spotify.uri()),
+        )))
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    let office_jams = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq("4fKqS2pXyA1bZ0cQ9mRt7w"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: playlists::ActiveModel = office_jams.into();
+    active.is_enabled = Set(true);
+    active.update(&state.db).await.unwrap();
+
+    // All three episodes are left out: in track form typed or without
+    // artists, and as an episode object without a track's shape
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+    let summary: serde_json::Value = serde_json::from_str(job.result.as_deref().unwrap()).unwrap();
+    assert_eq!(summary["skipped"], 3);
+
+    let office_jams = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq("4fKqS2pXyA1bZ0cQ9mRt7w"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(office_jams.episode_count, Some(3));
+    assert_eq!(office_jams.unavailable_tracks, Some(0));
+
+    let synced: Vec<String> = tracks::Entity::find()
+        .all(&state.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| t.title)
+        .collect();
+    assert_eq!(synced, vec!["Knights of Cydonia".to_string()]);
+    for show in ["The Joe Rogan Experience", "The Daily", "Song Exploder"] {
+        assert!(albums::Entity::find()
+            .filter(albums::Column::Title.eq(show))
+            .one(&state.db)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_spotify_sync_disables_playlist_that_keeps_failing() {
+    // Office Jams' tracks aren't mounted, so fetching them answers 404
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+
+    let now = Utc::now().into();
+    user_settings::ActiveModel {
+        album_view: Set("grid".to_string()),
+        playlist_failure_threshold: Set(Some(2)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    run_sync(&app, &state).await;
+    let office_jams = || async {
+        playlists::Entity::find()
+            .filter(playlists::Column::SpotifyId.eq("4fKqS2pXyA1bZ0cQ9mRt7w"))
+            .one(&state.db)
+            .await
+            .unwrap()
+            .unwrap()
+    };
+    let mut active: playlists::ActiveModel = office_jams().await.into();
+    active.is_enabled = Set(true);
+    active.update(&state.db).await.unwrap();
+
+    // The failing playlist doesn't fail the sync, and is only counted at first
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+    let playlist = office_jams().await;
+    assert_eq!(playlist.sync_failure_count, 1);
+    assert!(playlist.is_enabled);
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+    let playlist = office_jams().await;
+    assert_eq!(playlist.sync_failure_count, 2);
+    assert!(!playlist.is_enabled);
+    assert!(playlist.disabled_reason.unwrap().contains("404"));
+
+    // Enabling it again starts the count over
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/playlists/{}/toggle", playlist.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let playlist = office_jams().await;
+    assert!(playlist.is_enabled);
+    assert_eq!(playlist.sync_failure_count, 0);
+    assert_eq!(playlist.disabled_reason, None);
+}
+
+#[tokio::test]
+async fn test_spotify_sync_outages_do_not_count_as_playlist_failures() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+    // A server error, then a rate limit, for the playlist's tracks
+    Mock::given(method("GET"))
+        .and(path("/v1/playlists/4fKqS2pXyA1bZ0cQ9mRt7w/tracks"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&spotify)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/playlists/4fKqS2pXyA1bZ0cQ9mRt7w/tracks"))
+        .respond_with(ResponseTemplate::new(429))
+        .expect(1..)
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+
+    let now = Utc::now().into();
+    user_settings::ActiveModel {
+        album_view: Set("grid".to_string()),
+        playlist_failure_threshold: Set(Some(1)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    run_sync(&app, &state).await;
+    let office_jams = || async {
+        playlists::Entity::find()
+            .filter(playlists::Column::SpotifyId.eq("4fKqS2pXyA1bZ0cQ9mRt7w"))
+            .one(&state.db)
+            .await
+            .unwrap()
+            .unwrap()
+    };
+    let mut active: playlists::ActiveModel = office_jams().await.into();
+    active.is_enabled = Set(true);
+    active.update(&state.db).await.unwrap();
+
+    // With a threshold of one, a single counted failure would disable it
+    for _ in 0..2 {
+        let job = run_sync(&app, &state).await;
+        assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+        let playlist = office_jams().await;
+        assert_eq!(playlist.sync_failure_count, 0);
+        assert!(playlist.is_enabled);
+        assert_eq!(playlist.disabled_reason, None);
+    }
+}
+
+#[tokio::test]
+async fn test_spotify_sync_archives_items_removed_from_spotify() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    let an_hour_ago = (Utc::now() - chrono::Duration::hours(1)).into();
+
+    // An album an earlier sync saw saved, and a playlist it saw followed
+    let artist = create_test_artist(&state.db, "Boards of Canada", None).await;
+    let unsaved = create_test_album(&state.db, artist.id, "Geogaddi", Some("spotify:album:geogaddi")).await;
+    let mut active: albums::ActiveModel = unsaved.clone().into();
+    active.last_synced_at = Set(Some(an_hour_ago));
+    active.update(&state.db).await.unwrap();
+
+    let unfollowed = playlists::ActiveModel {
+        name: Set("Old Mix".to_string()),
+        spotify_id: Set("old-mix".to_string()),
+        created_at: Set(an_hour_ago),
+        updated_at: Set(an_hour_ago),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    profile::link_playlist(&state.db, default_profile.id, unfollowed.id).await.unwrap();
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    // A track that was liked before Liked Songs came back empty
+    let liked_songs = playlists::Entity::find()
+        .filter(playlists::Column::IsSynthetic.eq(true))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let unliked = tracks::ActiveModel {
+        album_id: Set(unsaved.id),
+        title: Set("Music Is Math".to_string()),
+        created_at: Set(an_hour_ago),
+        updated_at: Set(an_hour_ago),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    playlist_tracks::ActiveModel {
+        playlist_id: Set(liked_songs.id),
+        track_id: Set(unliked.id),
+        position: Set(0),
+        created_at: Set(an_hour_ago),
+        updated_at: Set(an_hour_ago),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    let mut active: playlists::ActiveModel = liked_songs.into();
+    active.is_enabled = Set(true);
+    active.snapshot_id = Set(None);
+    active.update(&state.db).await.unwrap();
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    let get = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+
+    let archive = get("/api/archive").await;
+    assert_eq!(archive["albums"].as_array().unwrap().len(), 1);
+    assert_eq!(archive["albums"][0]["title"], "Geogaddi");
+    assert_eq!(archive["playlists"].as_array().unwrap().len(), 1);
+    assert_eq!(archive["playlists"][0]["name"], "Old Mix");
+    assert_eq!(archive["tracks"].as_array().unwrap().len(), 1);
+    assert_eq!(archive["tracks"][0]["title"], "Music Is Math");
+    assert_eq!(archive["tracks"][0]["album_title"], "Geogaddi");
+
+    // Still-saved albums stay out of the archive; archived ones leave the stats
+    let stats = get("/api/stats").await;
+    assert_eq!(stats["total_albums"], 2);
+
+    // Restored items are kept and not archived again by the next sync
+    for uri in [
+        format!("/api/archive/albums/{}/restore", unsaved.id),
+        format!("/api/archive/playlists/{}/restore", unfollowed.id),
+        format!("/api/archive/tracks/{}/restore", unliked.id),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().method("POST").uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    let archive = get("/api/archive").await;
+    assert_eq!(archive, json!({ "albums": [], "playlists": [], "tracks": [] }));
+    let playlist = playlists::Entity::find_by_id(unfollowed.id).one(&state.db).await.unwrap().unwrap();
+    assert!(playlist.is_external);
+    assert_eq!(get("/api/stats").await["total_albums"], 3);
+}
+
+#[tokio::test]
+async fn test_spotify_sync_queues_cover_art_fetch_when_enabled() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    // No executor: the sync runs inline and the queued messages stay put
+    let config = mock_config(&spotify, None);
+    let (job_queue, mut receiver) = JobQueue::new(config.job_queue_capacity);
+    let state = AppState::new(setup_test_db().await, setup_test_redis().await, config, job_queue, test_log_filter());
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+    let mut active: albums::ActiveModel = album.into();
+    active.musicbrainz_release_group_id = Set(Some(DISCOVERY_MBID.to_string()));
+    active.update(&state.db).await.unwrap();
+
+    let now = Utc::now().into();
+    let settings = user_settings::ActiveModel {
+        album_view: Set("grid".to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let sync = |state: AppState| async move {
+        let job = jobs::ActiveModel {
+            job_type: Set(JobType::SpotifySync.as_str().to_string()),
+            status: Set(JobStatus::Running.as_str().to_string()),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+        spotify_sync::run_spotify_sync(state, job.id, None).await.unwrap();
+    };
+
+    // Off by default
+    sync(state.clone()).await;
+    assert!(receiver.try_recv().is_err());
+
+    let mut active: user_settings::ActiveModel = settings.into();
+    active.auto_fetch_cover_art = Set(Some(true));
+    active.update(&state.db).await.unwrap();
+
+    sync(state.clone()).await;
+    let message = receiver.try_recv().expect("Sync should queue a cover art fetch");
+    assert_eq!(message.job_type, JobType::CoverArtFetch);
+    let job = jobs::Entity::find_by_id(message.job_id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(job.job_type, JobType::CoverArtFetch.as_str());
+    assert_eq!(job.status, JobStatus::Pending.as_str());
+}
+
+#[tokio::test]
+async fn test_spotify_sync_keeps_renamed_artist_names() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+    run_sync(&app, &state).await;
+
+    let artist = |spotify_id: &'static str| {
+        let db = state.db.clone();
+        async move {
+            artists::Entity::find()
+                .filter(artists::Column::SpotifyId.eq(spotify_id))
+                .one(&db)
+                .await
+                .unwrap()
+                .unwrap()
+        }
+    };
+    let daft_punk = artist("4tZwfgrHOc3mvqYlEYSvVi").await;
+    let radiohead = artist("4Z8W4fKeB5YxbusRsdQVPb").await;
+
+    // A user rename is locked
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/artists/{}", daft_punk.id))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "name": "Daft Punk (FR)" }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // A name changed any other way no longer matches Spotify's last name either
+    let mut active: artists::ActiveModel = radiohead.into();
+    active.name = Set("RADIOHEAD".to_string());
+    active.update(&state.db).await.unwrap();
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    assert_eq!(artist("4tZwfgrHOc3mvqYlEYSvVi").await.name, "Daft Punk (FR)");
+    assert_eq!(artist("4Z8W4fKeB5YxbusRsdQVPb").await.name, "RADIOHEAD");
+
+    // Spotify called it "RADIOHEAD" last time and "Radiohead" now: follow the rename
+    let mut active: artists::ActiveModel = artist("4Z8W4fKeB5YxbusRsdQVPb").await.into();
+    active.spotify_name = Set(Some("RADIOHEAD".to_string()));
+    active.update(&state.db).await.unwrap();
+
+    run_sync(&app, &state).await;
+    let radiohead = artist("4Z8W4fKeB5YxbusRsdQVPb").await;
+    assert_eq!(radiohead.name, "Radiohead");
+    assert_eq!(radiohead.spotify_name.as_deref(), Some("Radiohead"));
+}
+
+#[tokio::test]
+async fn test_spotify_sync_keeps_edited_genres() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+    run_sync(&app, &state).await;
+
+    let album = |title: &'static str| {
+        let db = state.db.clone();
+        async move {
+            albums::Entity::find()
+                .filter(albums::Column::Title.eq(title))
+                .one(&db)
+                .await
+                .unwrap()
+                .unwrap()
+        }
+    };
+    let ok_computer = album("OK Computer").await;
+    assert_eq!(ok_computer.genres.as_deref(), Some(r#"["alternative rock"]"#));
+    assert_eq!(ok_computer.genre_source.as_deref(), Some("spotify"));
+
+    // Edited genres and ones filled from MusicBrainz both outrank Spotify's
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/albums/{}", ok_computer.id))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "genres": ["britpop"] }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut active: albums::ActiveModel = album("Discovery").await.into();
+    active.genres = Set(Some(r#"["french house"]"#.to_string()));
+    active.genre_source = Set(Some("musicbrainz".to_string()));
+    active.update(&state.db).await.unwrap();
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    assert_eq!(album("OK Computer").await.genres.as_deref(), Some(r#"["britpop"]"#));
+    assert_eq!(album("Discovery").await.genres.as_deref(), Some(r#"["french house"]"#));
+}
+
+#[tokio::test]
+async fn test_spotify_sync_keeps_cleared_wants_cleared() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+    run_sync(&app, &state).await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri("/api/settings")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({ "wanted_demotion_enabled": true, "wanted_demotion_days": 365 }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // A long-wanted album already at the lowest priority has its want cleared
+    let discovery = albums::Entity::find()
+        .filter(albums::Column::Title.eq("Discovery"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: albums::ActiveModel = discovery.clone().into();
+    active.wanted_since = Set(Some((Utc::now() - chrono::Duration::days(400)).into()));
+    active.update(&state.db).await.unwrap();
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    let link = profile::album_link(&state.db, default_profile.id, discovery.id).await.unwrap().unwrap();
+    let mut link: profile_albums::ActiveModel = link.into();
+    link.want_priority = Set(1);
+    link.update(&state.db).await.unwrap();
+    assert_eq!(beat_collector::tasks::wanted_demotion::demote_stale_wants(&state).await.unwrap(), 1);
+
+    // Spotify still has the album saved; the sync must not bring the want back
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    let link = profile::album_link(&state.db, default_profile.id, discovery.id).await.unwrap().unwrap();
+    assert!(link.want_cleared_at.is_some());
+    assert_eq!(link.want_priority, 1);
+    let discovery = albums::Entity::find_by_id(discovery.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(discovery.archived_at, None);
+}
+
+#[tokio::test]
+async fn test_spotify_sync_failure_is_recorded() {
+    let spotify = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/albums"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "error": { "status": 401, "message": "The access token expired" }
+        })))
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Failed.as_str());
+    assert!(job.error_message.unwrap().contains("401"));
+    assert_eq!(albums::Entity::find().all(&state.db).await.unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_spotify_token_refresh() {
+    let spotify = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/token"))
+        .and(body_string_contains("grant_type=refresh_token"))
+        .and(body_string_contains("refresh_token=test-refresh-token"))
+        .respond_with(json_response(fixture("spotify/token_refresh.json", "")))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(-1)).await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/auth/spotify/status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status["connected"], true);
+    assert_eq!(status["needs_reauth"], false);
+
+    let refreshed = profile::default_profile(&state.db).await.unwrap();
+    assert_eq!(refreshed.spotify_access_token.as_deref(), Some("refreshed-access-token"));
+    assert_eq!(refreshed.spotify_refresh_token.as_deref(), Some("rotated-refresh-token"));
+    assert!(refreshed.spotify_token_expires_at.unwrap().to_utc() > Utc::now());
+}
+
+#[tokio::test]
+async fn test_lidarr_search_and_webhook_ownership_flow() {
+    let spotify = MockServer::start().await;
+    let lidarr = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/album/lookup"))
+        .and(query_param("term", format!("lidarr:{}", DISCOVERY_MBID).as_str()))
+        .and(header("X-Api-Key", LIDARR_API_KEY))
+        .respond_with(json_response(fixture("lidarr/album_lookup.json", "")))
+        .expect(1)
+        .mount(&lidarr)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/command"))
+        .and(header("X-Api-Key", LIDARR_API_KEY))
+        .and(body_partial_json(json!({ "name": "AlbumSearch", "albumIds": [42] })))
+        .respond_with(json_response(fixture("lidarr/command_album_search.json", "")))
+        .expect(1)
+        .mount(&lidarr)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, Some(&lidarr))).await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+
+    let mut active: albums::ActiveModel = album.clone().into();
+    active.musicbrainz_release_group_id = Set(Some(DISCOVERY_MBID.to_string()));
+    active.update(&state.db).await.unwrap();
+
+    let app = create_test_router(&state);
+
+    // Search: Lidarr config comes from the app config (no user settings saved)
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/albums/{}/search-lidarr", album.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let album_state = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(album_state.ownership_status, OwnershipStatus::Downloading.as_str());
+
+    // Grab webhook: download record created
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/webhooks/lidarr")
+                .header("content-type", "application/json")
+                .body(Body::from(fixture("lidarr/webhook_grab.json", "")))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let download = lidarr_downloads::Entity::find()
+        .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .expect("Grab should create a download record");
+    assert_eq!(download.status, "grabbing");
+    assert_eq!(download.lidarr_album_id, Some(42));
+
+    // Download webhook: album becomes owned with its local path
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/webhooks/lidarr")
+                .header("content-type", "application/json")
+                .body(Body::from(fixture("lidarr/webhook_download.json", "")))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let owned = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(owned.ownership_status, OwnershipStatus::Owned.as_str());
+    assert_eq!(owned.acquisition_source.as_deref(), Some(AcquisitionSource::Lidarr.as_str()));
+    assert_eq!(owned.local_path.as_deref(), Some("/music/Daft Punk/Discovery (2001)"));
+
+    let download = lidarr_downloads::Entity::find_by_id(download.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(download.status, "completed");
+    assert!(download.completed_at.is_some());
+}
+
+#[tokio::test]
+async fn test_lidarr_search_htmx_notifications() {
+    let spotify = MockServer::start().await;
+    let lidarr = MockServer::start().await;
+
+    // Only Discovery is known to Lidarr; other lookups fall through to a 404
+    Mock::given(method("GET"))
+        .and(path("/api/v1/album/lookup"))
+        .and(query_param("term", format!("lidarr:{}", DISCOVERY_MBID).as_str()))
+        .respond_with(json_response(fixture("lidarr/album_lookup.json", "")))
+        .mount(&lidarr)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/command"))
+        .respond_with(json_response(fixture("lidarr/command_album_search.json", "")))
+        .mount(&lidarr)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, Some(&lidarr))).await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let discovery = create_test_album(&state.db, artist.id, "Discovery", None).await;
+    let homework = create_test_album(&state.db, artist.id, "Homework", None).await;
+
+    for (album, mbid) in [(&discovery, DISCOVERY_MBID), (&homework, "00000000-0000-0000-0000-000000000001")] {
+        let mut active: albums::ActiveModel = album.clone().into();
+        active.musicbrainz_release_group_id = Set(Some(mbid.to_string()));
+        active.update(&state.db).await.unwrap();
+    }
+
+    let app = create_test_router(&state);
+    let search = |album_id: i32, query: &str| {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/albums/{}/search-lidarr{}", album_id, query))
+            .header("HX-Request", "true")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(search(discovery.id, "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = String::from_utf8(
+        axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec(),
+    )
+    .unwrap();
+    assert!(body.contains("command #1001"));
+    assert!(body.contains(&format!(r#"hx-get="/albums/{}""#, discovery.slug.as_deref().unwrap())));
+    assert!(!body.contains("album-card"));
+
+    // A card quick action also gets the card back with its new badge
+    let response = app.clone().oneshot(search(discovery.id, "?card=true")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = String::from_utf8(
+        axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec(),
+    )
+    .unwrap();
+    assert!(body.contains("command #1001"));
+    assert!(body.contains(&format!(r#"id="album-card-{}""#, discovery.id)));
+    assert!(body.contains(r#"hx-swap-oob="true""#));
+    assert!(body.contains("Downloading"));
+    assert!(!body.contains("Search Lidarr for Discovery"));
+
+    let response = app.oneshot(search(homework.id, "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = String::from_utf8(
+        axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec(),
+    )
+    .unwrap();
+    assert!(body.contains("Album not found in Lidarr."));
+    assert!(body.contains("Add to Lidarr and search"));
+}
+
+#[tokio::test]
+async fn test_lidarr_artist_override_picks_album_and_previews_artist() {
+    const COLLAB_MBID: &str = "0a5b9b4c-51c8-4c6f-8f5e-6f2a1c7b3d10";
+    let spotify = MockServer::start().await;
+    let lidarr = MockServer::start().await;
+
+    // Lidarr lists the release group under both credited artists
+    Mock::given(method("GET"))
+        .and(path("/api/v1/album/lookup"))
+        .and(query_param("term", format!("lidarr:{}", DISCOVERY_MBID).as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {
+                "id": 42,
+                "title": "Discovery",
+                "artist": { "id": 7, "artist_name": "Daft Punk", "foreign_artist_id": "056e4f3e-d505-4dad-8ec1-d04f521cbb56" },
+                "release_date": null,
+                "monitored": true
+            },
+            {
+                "id": 43,
+                "title": "Discovery",
+                "artist": { "id": 8, "artist_name": "Daft Punk & Friends", "foreign_artist_id": COLLAB_MBID },
+                "release_date": null,
+                "monitored": true
+            }
+        ])))
+        .mount(&lidarr)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/artist/lookup"))
+        .and(query_param("term", format!("lidarr:{}", COLLAB_MBID).as_str()))
+        .and(header("X-Api-Key", LIDARR_API_KEY))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "artist_name": "Daft Punk & Friends", "foreign_artist_id": COLLAB_MBID }
+        ])))
+        .mount(&lidarr)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/artist/lookup"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&lidarr)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/command"))
+        .and(body_partial_json(json!({ "name": "AlbumSearch", "albumIds": [43] })))
+        .respond_with(json_response(fixture("lidarr/command_album_search.json", "")))
+        .expect(1)
+        .mount(&lidarr)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, Some(&lidarr))).await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+    let mut active: albums::ActiveModel = album.clone().into();
+    active.musicbrainz_release_group_id = Set(Some(DISCOVERY_MBID.to_string()));
+    active.update(&state.db).await.unwrap();
+
+    let app = Router::new()
+        .merge(handlers::html_routes())
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone());
+    let body_of = |response: axum::response::Response| async move {
+        String::from_utf8(axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec())
+            .unwrap()
+    };
+    let preview = |mbid: &str| {
+        Request::builder()
+            .uri(format!("/albums/{}/lidarr-artist-preview?mbid={}", album.id, mbid))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(preview(COLLAB_MBID)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(body_of(response).await.contains("Lidarr artist: Daft Punk &amp; Friends"));
+
+    let response = app.clone().oneshot(preview("00000000-0000-0000-0000-000000000000")).await.unwrap();
+    assert!(body_of(response).await.contains("Lidarr doesn't know this artist"));
+
+    let response = app.clone().oneshot(preview("daft-punk")).await.unwrap();
+    assert!(body_of(response).await.contains("Invalid MusicBrainz artist ID"));
+
+    // Saving re-renders the modal with the override filled in
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/albums/{}/lidarr-artist", album.id))
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(format!("mbid={}", COLLAB_MBID.to_uppercase())))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(body_of(response).await.contains(&format!(r#"value="{}""#, COLLAB_MBID)));
+
+    let saved = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(saved.lidarr_artist_mbid_override.as_deref(), Some(COLLAB_MBID));
+
+    // The search now triggers the copy of the album filed under the override artist
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/albums/{}/search-lidarr", album.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Render the heavy rotation panel for the default profile
+async fn get_heavy_rotation(state: &AppState) -> String {
+    let app = Router::new()
+        .merge(handlers::html_routes())
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/stats/heavy-rotation")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_heavy_rotation_caches_and_annotates_top_items() {
+    let spotify = MockServer::start().await;
+    let api = format!("{}/v1", spotify.uri());
+    let auth = format!("Bearer {}", ACCESS_TOKEN);
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/top/artists"))
+        .and(query_param("time_range", "medium_term"))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/top_artists.json", &api)))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/top/tracks"))
+        .and(query_param("time_range", "medium_term"))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/top_tracks.json", &api)))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+
+    // Daft Punk is in the library with Discovery owned; David Bowie isn't
+    let daft_punk = create_test_artist(&state.db, "Daft Punk", Some("4tZwfgrHOc3mvqYlEYSvVi")).await;
+    let discovery = create_test_album(&state.db, daft_punk.id, "Discovery", Some("2noRn2Aes5aoNVsU6iWThc")).await;
+    let mut active: albums::ActiveModel = discovery.into();
+    active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+
+    let html = get_heavy_rotation(&state).await;
+    assert!(html.contains("Top Artists"));
+    assert!(html.contains("/artists/daft-punk"));
+    assert!(html.contains("1/1 owned"));
+    assert!(html.contains("David Bowie"));
+    assert!(html.contains("Not in library"));
+    assert!(html.contains("Owned"));
+    // Both Daft Punk tracks collapse into one album row
+    assert_eq!(html.matches("Discovery cover").count(), 1);
+
+    let cached = top_items::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(cached.iter().filter(|i| i.kind == TopItemKind::Artist.as_str()).count(), 2);
+    assert_eq!(cached.iter().filter(|i| i.kind == TopItemKind::Track.as_str()).count(), 3);
+
+    // Fresh cache is served without calling Spotify again (mocks expect one call each)
+    let html = get_heavy_rotation(&state).await;
+    assert!(html.contains("David Bowie"));
+}
+
+#[tokio::test]
+async fn test_heavy_rotation_keeps_an_empty_result_until_stale() {
+    let spotify = MockServer::start().await;
+    let empty_page = json!({ "items": [], "limit": 50, "offset": 0, "total": 0, "next": null, "previous": null });
+
+    for endpoint in ["/v1/me/top/artists", "/v1/me/top/tracks"] {
+        Mock::given(method("GET"))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&empty_page))
+            .expect(1)
+            .mount(&spotify)
+            .await;
+    }
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+
+    let html = get_heavy_rotation(&state).await;
+    assert!(html.contains("enough listening history"));
+    assert!(top_items::Entity::find().all(&state.db).await.unwrap().is_empty());
+
+    // No items came back, but the fetch is recent so Spotify isn't asked again
+    let html = get_heavy_rotation(&state).await;
+    assert!(html.contains("enough listening history"));
+
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    assert!(default_profile.top_items_fetched_at.is_some());
+}
+
+#[tokio::test]
+async fn test_heavy_rotation_prompts_reconnect_without_scope() {
+    let spotify = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/me/top/artists"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+            "error": { "status": 403, "message": "Insufficient client scope" }
+        })))
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+
+    let html = get_heavy_rotation(&state).await;
+    assert!(html.contains("Reconnect Spotify"));
+    assert!(html.contains("/api/auth/spotify/authorize"));
+    assert!(top_items::Entity::find().all(&state.db).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_playlist_sync_preview_does_not_write() {
+    let spotify = MockServer::start().await;
+    mount_spotify_library(&spotify).await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    let app = create_test_router(&state);
+
+    // Sync once to create the playlist, enable it and sync again to store its tracks
+    run_sync(&app, &state).await;
+    let road_trip = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq("37i9dQZF1DX0XUsuxWHRQd"))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: playlists::ActiveModel = road_trip.clone().into();
+    active.is_enabled = Set(true);
+    active.update(&state.db).await.unwrap();
+    let job = run_sync(&app, &state).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    // Live playlist now: "One More Time" removed, "Mr. Blue Sky" moved up, a new track at the end
+    let api = format!("{}/v1", spotify.uri());
+    let mut live: serde_json::Value =
+        serde_json::from_str(&fixture("spotify/playlist_tracks.json", &api)).unwrap();
+    let items = live["items"].as_array_mut().unwrap();
+    let mut added = items.remove(0);
+    added["track"]["id"] = json!("5W3cjX2J3tjhG8zb6u0qHn");
+    added["track"]["name"] = json!("Harder, Better, Faster, Stronger");
+    items.push(added);
+
+    spotify.reset().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/playlists/37i9dQZF1DX0XUsuxWHRQd/tracks"))
+        .respond_with(json_response(live.to_string()))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/playlists/{}/sync-preview", road_trip.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let preview: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(preview["has_changes"], true);
+    assert_eq!(preview["unchanged"], 0);
+    assert_eq!(preview["removed"][0]["track_name"], "One More Time");
+    assert_eq!(preview["added"][0]["track_name"], "Harder, Better, Faster, Stronger");
+    assert_eq!(preview["added"][0]["position"], 2);
+    assert_eq!(preview["reordered"][0]["track_name"], "Mr. Blue Sky");
+    assert_eq!(preview["reordered"][0]["from_position"], 1);
+    assert_eq!(preview["reordered"][0]["to_position"], 0);
+
+    // Nothing was written
+    let stored = playlist_tracks::Entity::find()
+        .filter(playlist_tracks::Column::PlaylistId.eq(road_trip.id))
+        .all(&state.db)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 2);
+}
+
+#[tokio::test]
+async fn test_import_playlist_by_link_without_following() {
+    let spotify = MockServer::start().await;
+    let api = format!("{}/v1", spotify.uri());
+    let auth = format!("Bearer {}", ACCESS_TOKEN);
+    let friend_playlist = "1Fr13ndM1xT4p3Pl4yL1st";
+
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/playlists/{}", friend_playlist)))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/playlist.json", &api)))
+        .mount(&spotify)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/playlists/{}/tracks", friend_playlist)))
+        .and(header("Authorization", auth.as_str()))
+        .respond_with(json_response(fixture("spotify/playlist_tracks.json", &api)))
+        .mount(&spotify)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/playlists/0000000000000000000000"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&spotify)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+
+    let import = |playlist: &str, keep_playlist: bool| {
+        let app = create_test_router(&state);
+        let body = json!({ "playlist": playlist, "keep_playlist": keep_playlist }).to_string();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/api/import/spotify-playlist")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+        }
+    };
+
+    // Albums only: nothing playlist-shaped is stored
+    let (status, summary) = import(&format!("https://open.spotify.com/playlist/{}?si=abc123", friend_playlist), false).await;
+    assert_eq!(status, StatusCode::OK, "{}", summary);
+    assert_eq!(summary["name"], "Friday Mix");
+    assert_eq!(summary["playlist_id"], serde_json::Value::Null);
+    assert_eq!(summary["albums_created"], 2);
+    assert_eq!(summary["tracks_created"], 2);
+    assert!(playlists::Entity::find().all(&state.db).await.unwrap().is_empty());
+
+    let imported = albums::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(imported.len(), 2);
+    assert!(imported.iter().all(|a| a.source == "playlist_import"
+        && a.ownership_status == OwnershipStatus::NotOwned.as_str()));
+
+    // Keeping the playlist reuses the albums and stores an external playlist
+    let (status, summary) = import(&format!("spotify:playlist:{}", friend_playlist), true).await;
+    assert_eq!(status, StatusCode::OK, "{}", summary);
+    assert_eq!(summary["albums_created"], 0);
+    assert_eq!(summary["albums_existing"], 2);
+    assert_eq!(summary["tracks_existing"], 2);
+
+    let playlist = playlists::Entity::find()
+        .filter(playlists::Column::SpotifyId.eq(friend_playlist))
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(summary["playlist_id"], playlist.id);
+    assert!(playlist.is_external);
+    assert!(playlist.is_enabled);
+    assert_eq!(playlist.owner_name.as_deref(), Some("A Friend"));
+    let memberships = playlist_tracks::Entity::find()
+        .filter(playlist_tracks::Column::PlaylistId.eq(playlist.id))
+        .all(&state.db)
+        .await
+        .unwrap();
+    assert_eq!(memberships.len(), 2);
+
+    let (status, _) = import("https://open.spotify.com/album/2noRn2Aes5aoNVsU6iWThc", false).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = import("0000000000000000000000", false).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_listen_history_sync() {
+    let listenbrainz = MockServer::start().await;
+    let listens = std::fs::read_to_string(format!(
+        "{}/tests/fixtures/listenbrainz/listens.json",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap();
+    Mock::given(method("GET"))
+        .and(path("/1/user/alice/listens"))
+        .and(query_param("min_ts", "0"))
+        .respond_with(json_response(listens))
+        .expect(1)
+        .mount(&listenbrainz)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/1/user/alice/listens"))
+        .and(query_param("min_ts", "1700003600"))
+        .respond_with(json_response(json!({ "payload": { "count": 0, "listens": [] } }).to_string()))
+        .expect(1)
+        .mount(&listenbrainz)
+        .await;
+
+    let config = Config {
+        listenbrainz_api_base: listenbrainz.uri(),
+        ..test_config()
+    };
+    let state = setup_state_with_executor(config).await;
+    let now = Utc::now().into();
+    user_settings::ActiveModel {
+        listenbrainz_username: Set(Some("alice".to_string())),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let discovery = create_test_album(&state.db, artist.id, "Discovery", None).await;
+
+    let summary = listen_history::sync_listen_history(&state).await.unwrap();
+    assert_eq!((summary.fetched, summary.matched, summary.unmatched), (4, 2, 2));
+
+    let discovery = albums::Entity::find_by_id(discovery.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(discovery.listen_count, 2);
+    assert_eq!(discovery.first_listened_at.unwrap().timestamp(), 1_700_000_000);
+    assert_eq!(discovery.last_listened_at.unwrap().timestamp(), 1_700_003_600);
+
+    // The next run only asks for newer listens, so nothing is counted twice
+    let summary = listen_history::sync_listen_history(&state).await.unwrap();
+    assert_eq!(summary.fetched, 0);
+    let discovery = albums::Entity::find_by_id(discovery.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(discovery.listen_count, 2);
+}
+
+async fn insert_failed_download(state: &AppState, album_id: i32, failed_at: chrono::DateTime<Utc>) -> lidarr_downloads::Model {
+    lidarr_downloads::ActiveModel {
+        album_id: Set(album_id),
+        lidarr_album_id: Set(Some(42)),
+        download_id: Set(Some("SABnzbd_nzo_failed".to_string())),
+        status: Set("failed".to_string()),
+        error_message: Set(Some("No files found".to_string())),
+        created_at: Set(failed_at.into()),
+        updated_at: Set(failed_at.into()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_failed_downloads_are_retried_up_to_the_limit() {
+    let spotify = MockServer::start().await;
+    let lidarr = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/api/v1/command"))
+        .and(header("X-Api-Key", LIDARR_API_KEY))
+        .and(body_partial_json(json!({ "name": "AlbumSearch", "albumIds": [42] })))
+        .respond_with(json_response(fixture("lidarr/command_album_search.json", "")))
+        .expect(1)
+        .mount(&lidarr)
+        .await;
+
+    let state = setup_state_with_executor(mock_config(&spotify, Some(&lidarr))).await;
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+    let stale_album = create_test_album(&state.db, artist.id, "Homework", None).await;
+    let download = insert_failed_download(&state, album.id, Utc::now()).await;
+    insert_failed_download(&state, stale_album.id, Utc::now() - chrono::Duration::days(7)).await;
+
+    // Off by default
+    let summary = download_retry::retry_failed_downloads(&state).await.unwrap();
+    assert_eq!(summary, download_retry::RetrySummary::default());
+
+    let now = Utc::now().into();
+    user_settings::ActiveModel {
+        auto_retry_failed_downloads: Set(Some(true)),
+        max_download_retries: Set(Some(1)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    // Only the recent failure is retried
+    let summary = download_retry::retry_failed_downloads(&state).await.unwrap();
+    assert_eq!((summary.retried, summary.failed), (1, 0));
+
+    let retried = lidarr_downloads::Entity::find_by_id(download.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(retried.status, "searching");
+    assert_eq!(retried.retry_count, 1);
+    let album_state = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(album_state.ownership_status, OwnershipStatus::Downloading.as_str());
+
+    // The grab answering the retry takes over the retried record
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/webhooks/lidarr")
+                .header("content-type", "application/json")
+                .body(Body::from(fixture("lidarr/webhook_grab.json", "")))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let downloads = lidarr_downloads::Entity::find()
+        .filter(lidarr_downloads::Column::AlbumId.eq(album.id))
+        .all(&state.db)
+        .await
+        .unwrap();
+    assert_eq!(downloads.len(), 1);
+    assert_eq!(downloads[0].status, "grabbing");
+    assert_eq!(downloads[0].download_id.as_deref(), Some("SABnzbd_nzo_abc123"));
+    assert_eq!(downloads[0].retry_count, 1);
+
+    // Failing again once the limit is reached leaves it failed
+    let mut active: lidarr_downloads::ActiveModel = downloads[0].clone().into();
+    active.status = Set("failed".to_string());
+    active.updated_at = Set(Utc::now().into());
+    active.update(&state.db).await.unwrap();
+    let mut active: albums::ActiveModel = album_state.into();
+    active.ownership_status = Set(OwnershipStatus::NotOwned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+
+    let summary = download_retry::retry_failed_downloads(&state).await.unwrap();
+    assert_eq!(summary.retried, 0);
+}
+
+/// Spotify `GET /artists` body giving each artist one image
+fn artists_response(ids: &[&str]) -> ResponseTemplate {
+    let artists: Vec<_> = ids
+        .iter()
+        .map(|id| {
+            json!({
+                "id": id,
+                "name": id,
+                "images": [{ "url": format!("https://i.scdn.co/image/{}", id), "height": 640, "width": 640 }]
+            })
+        })
+        .collect();
+    ResponseTemplate::new(200).set_body_json(json!({ "artists": artists }))
+}
+
+async fn trigger_artist_images(state: &AppState, refresh: bool) -> jobs::Model {
+    let uri = if refresh { "/api/jobs/artist-images?refresh=true" } else { "/api/jobs/artist-images" };
+    let response = create_test_router(state)
+        .oneshot(Request::builder().method("POST").uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    wait_for_job(state, created["job_id"].as_i64().unwrap() as i32).await
+}
+
+async fn artist_image(state: &AppState, id: i32) -> Option<String> {
+    artists::Entity::find_by_id(id).one(&state.db).await.unwrap().unwrap().image_url
+}
+
+#[tokio::test]
+async fn test_artist_image_backfill_resumes_from_failed_checkpoint() {
+    let spotify = MockServer::start().await;
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+
+    let daft_punk = create_test_artist(&state.db, "Daft Punk", Some("daft")).await;
+    let justice = create_test_artist(&state.db, "Justice", Some("justice")).await;
+    let air = create_test_artist(&state.db, "Air", Some("air")).await;
+    create_test_artist(&state.db, "Local Band", None).await;
+
+    // A previous backfill failed after handling Daft Punk
+    let failed = create_test_job(&state.db, JobType::ArtistImageBackfill, JobStatus::Failed).await;
+    let mut active: jobs::ActiveModel = failed.into();
+    active.sync_cursor = Set(Some(json!({ "refresh": false, "last_artist_id": daft_punk.id }).to_string()));
+    active.update(&state.db).await.unwrap();
+
+    // Only the artists after the checkpoint are asked for
+    Mock::given(method("GET"))
+        .and(path("/v1/artists"))
+        .and(query_param("ids", "justice,air"))
+        .and(header("Authorization", format!("Bearer {}", ACCESS_TOKEN).as_str()))
+        .respond_with(artists_response(&["justice", "air"]))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+
+    let job = trigger_artist_images(&state, false).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+    assert_eq!((job.processed_items, job.total_items, job.progress), (Some(2), Some(2), Some(100)));
+    let result: serde_json::Value = serde_json::from_str(job.result.as_deref().unwrap()).unwrap();
+    assert_eq!(result["resumed_after"], daft_punk.id);
+    assert_eq!(result["updated"], 2);
+    let progress: serde_json::Value = serde_json::from_str(job.progress_detail.as_deref().unwrap()).unwrap();
+    assert_eq!(progress["api_calls"], 1);
+
+    assert_eq!(artist_image(&state, daft_punk.id).await, None);
+    assert_eq!(artist_image(&state, justice.id).await.as_deref(), Some("https://i.scdn.co/image/justice"));
+    assert_eq!(artist_image(&state, air.id).await.as_deref(), Some("https://i.scdn.co/image/air"));
+}
+
+#[tokio::test]
+async fn test_artist_image_backfill_refresh_flag() {
+    let spotify = MockServer::start().await;
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+
+    let daft_punk = create_test_artist(&state.db, "Daft Punk", Some("daft")).await;
+    let mut active: artists::ActiveModel = daft_punk.clone().into();
+    active.image_url = Set(Some("https://i.scdn.co/image/old".to_string()));
+    active.update(&state.db).await.unwrap();
+    let justice = create_test_artist(&state.db, "Justice", Some("justice")).await;
+
+    // Artists that already have an image are skipped
+    Mock::given(method("GET"))
+        .and(path("/v1/artists"))
+        .and(query_param("ids", "justice"))
+        .respond_with(artists_response(&["justice"]))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+    let job = trigger_artist_images(&state, false).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+    assert_eq!(artist_image(&state, daft_punk.id).await.as_deref(), Some("https://i.scdn.co/image/old"));
+    assert_eq!(artist_image(&state, justice.id).await.as_deref(), Some("https://i.scdn.co/image/justice"));
+
+    // A refresh fetches every artist again, and doesn't resume the earlier run
+    Mock::given(method("GET"))
+        .and(path("/v1/artists"))
+        .and(query_param("ids", "daft,justice"))
+        .respond_with(artists_response(&["daft", "justice"]))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+    let job = trigger_artist_images(&state, true).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+    let result: serde_json::Value = serde_json::from_str(job.result.as_deref().unwrap()).unwrap();
+    assert_eq!((result["processed"].as_i64(), result["updated"].as_i64()), (Some(2), Some(1)));
+    assert_eq!(artist_image(&state, daft_punk.id).await.as_deref(), Some("https://i.scdn.co/image/daft"));
+}
+
+#[tokio::test]
+async fn test_initial_artist_image_backfill_is_queued_once() {
+    let spotify = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/artists"))
+        .respond_with(artists_response(&["daft"]))
+        .mount(&spotify)
+        .await;
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    create_test_artist(&state.db, "Daft Punk", Some("daft")).await;
+    let now = Utc::now().into();
+    user_settings::ActiveModel {
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let backfills = || {
+        jobs::Entity::find()
+            .filter(jobs::Column::JobType.eq(JobType::ArtistImageBackfill.as_str()))
+            .all(&state.db)
+    };
+
+    artist_images::queue_initial_backfill(&state).await.unwrap();
+    let queued = backfills().await.unwrap();
+    assert_eq!(queued.len(), 1);
+    let job = wait_for_job(&state, queued[0].id).await;
+    assert_eq!(job.status, JobStatus::Completed.as_str(), "{:?}", job.error_message);
+
+    // Later startups leave it alone, even with artists still missing images
+    create_test_artist(&state.db, "Justice", Some("justice")).await;
+    artist_images::queue_initial_backfill(&state).await.unwrap();
+    assert_eq!(backfills().await.unwrap().len(), 1);
+}
+
+async fn get_body(app: Router, uri: &str) -> String {
+    let response = app
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_album_suggestions_from_related_artists() {
+    let spotify = MockServer::start().await;
+    let state = setup_state_with_executor(mock_config(&spotify, None)).await;
+    connect_spotify(&state, chrono::Duration::hours(1)).await;
+    CacheService::new(state.redis.clone())
+        .delete(&CacheService::spotify_related_artists_key("suggest-daft"))
+        .await
+        .unwrap();
+
+    let daft_punk = create_test_artist(&state.db, "Daft Punk", Some("suggest-daft")).await;
+    let justice = create_test_artist(&state.db, "Justice", Some("suggest-justice")).await;
+    let air = create_test_artist(&state.db, "Air", Some("suggest-air")).await;
+    let set_owned = |album: albums::Model| {
+        let mut active: albums::ActiveModel = album.into();
+        active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+        active.update(&state.db)
+    };
+    let discovery = set_owned(create_test_album(&state.db, daft_punk.id, "Discovery", None).await).await.unwrap();
+    let cross = create_test_album(&state.db, justice.id, "Cross", None).await;
+    create_test_album(&state.db, air.id, "Moon Safari", None).await;
+    set_owned(create_test_album(&state.db, air.id, "Talkie Walkie", None).await).await.unwrap();
+
+    // Cross is in a playlist twice, so it beats the more popular Air
+    let now = Utc::now().into();
+    let playlist = playlists::ActiveModel {
+        name: Set("Electro".to_string()),
+        spotify_id: Set("suggest-electro".to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    profile::link_playlist(&state.db, default_profile.id, playlist.id).await.unwrap();
+    for (position, title) in ["Genesis", "D.A.N.C.E."].iter().enumerate() {
+        let track = tracks::ActiveModel {
+            album_id: Set(cross.id),
+            title: Set(title.to_string()),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist.id),
+            track_id: Set(track.id),
+            position: Set(position as i32),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+    }
+
+    Mock::given(method("GET"))
+        .and(path("/v1/artists/suggest-daft/related-artists"))
+        .and(header("Authorization", format!("Bearer {}", ACCESS_TOKEN).as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "artists": [
+                { "id": "suggest-air", "name": "Air", "images": [], "popularity": 80 },
+                { "id": "suggest-justice", "name": "Justice", "images": [], "popularity": 70 },
+                { "id": "suggest-unknown", "name": "Not In Library", "images": [], "popularity": 90 }
+            ]
+        })))
+        .expect(1)
+        .mount(&spotify)
+        .await;
+
+    let uri = format!("/api/albums/{}/suggestions", discovery.id);
+    let body = get_body(create_test_router(&state), &uri).await;
+    let suggestions: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+    let titles: Vec<&str> = suggestions.iter().map(|s| s["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, vec!["Cross", "Moon Safari"]);
+    assert_eq!(suggestions[0]["playlist_appearances"], 2);
+    assert_eq!(suggestions[1]["artist_popularity"], 80);
+
+    // The modal strip is served from the cached related artists
+    let app = Router::new().merge(handlers::html_routes()).with_state(state.clone());
+    let strip = get_body(app, &format!("/albums/{}/suggestions", discovery.id)).await;
+    assert!(strip.contains("You might also want"));
+    assert!(strip.contains("Moon Safari"));
+
+    // Unowned albums get none, without asking Spotify
+    let body = get_body(create_test_router(&state), &format!("/api/albums/{}/suggestions", cross.id)).await;
+    assert_eq!(body, "[]");
+}
@@ -0,0 +1,168 @@
+//! Integration tests for the JSON error body of the API
+//!
+//! Every failure answers `{"error": {"code", "message", "details"?}}`:
+//! - Errors returned by handlers, with invalid input never reported as internal
+//! - Request bodies axum rejects before a handler runs
+//! - Unknown API paths and unsupported methods, keeping headers like `Allow`
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use beat_collector::error::{ErrorBody, ErrorCode};
+use beat_collector::handlers;
+use beat_collector::state::AppState;
+use beat_collector::test_utils::*;
+
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+/// Send a request and return its status and parsed error body
+async fn error_response(state: &AppState, request: Request<Body>) -> (StatusCode, ErrorBody) {
+    let response = create_test_router(state).oneshot(request).await.unwrap();
+    let status = response.status();
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: ErrorBody = serde_json::from_slice(&body)
+        .unwrap_or_else(|e| panic!("Not an error body ({}): {}", e, String::from_utf8_lossy(&body)));
+    (status, error)
+}
+
+fn json_request(method: &str, uri: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_not_found_errors() {
+    let state = setup_test_app_state().await;
+
+    let (status, body) = error_response(&state, get("/api/albums/999999")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body.error.code, ErrorCode::NotFound);
+
+    let (status, body) = error_response(&state, get("/api/no-such-endpoint")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body.error.code, ErrorCode::NotFound);
+    assert_eq!(body.error.message, "No API endpoint at /api/no-such-endpoint");
+}
+
+#[tokio::test]
+async fn test_validation_errors() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Justice", None).await;
+
+    // Rejected by the handler
+    let goal = json!({ "goal_type": "artist", "target_id": artist.id, "target_percentage": 120.0 });
+    let (status, body) = error_response(&state, json_request("POST", "/api/goals", &goal.to_string())).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body.error.code, ErrorCode::Validation);
+    assert!(body.error.details.is_none());
+
+    // Rejected by axum before the handler, keeping axum's status
+    let (status, body) = error_response(&state, json_request("POST", "/api/goals", "{not json")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body.error.code, ErrorCode::Validation);
+    assert!(body.error.message.contains("JSON"), "{}", body.error.message);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/goals")
+        .body(Body::from(goal.to_string()))
+        .unwrap();
+    let (status, body) = error_response(&state, request).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(body.error.code, ErrorCode::Validation);
+
+    let (status, body) = error_response(&state, json_request("DELETE", "/api/stats", "")).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(body.error.code, ErrorCode::Validation);
+    assert_eq!(body.error.message, "Method Not Allowed");
+
+    // The rewritten body keeps the original headers
+    let response = create_test_router(&state)
+        .oneshot(json_request("DELETE", "/api/stats", ""))
+        .await
+        .unwrap();
+    assert_eq!(response.headers()[header::ALLOW], "GET,HEAD");
+}
+
+#[tokio::test]
+async fn test_invalid_input_is_not_an_internal_error() {
+    let state = setup_test_app_state().await;
+
+    let view = json!({ "album_view": "carousel" }).to_string();
+    let (status, body) = error_response(&state, json_request("PUT", "/api/settings", &view)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body.error.code, ErrorCode::Validation);
+    assert_eq!(body.error.message, "Invalid album view: carousel");
+
+    let (status, body) = error_response(&state, json_request("POST", "/api/profiles", r#"{"name": "  "}"#)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body.error.code, ErrorCode::Validation);
+
+    create_test_router(&state)
+        .oneshot(json_request("POST", "/api/profiles", r#"{"name": "Partner"}"#))
+        .await
+        .unwrap();
+    let (status, body) = error_response(&state, json_request("POST", "/api/profiles", r#"{"name": "Partner"}"#)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body.error.code, ErrorCode::Conflict);
+    assert_eq!(body.error.message, "Profile 'Partner' already exists");
+}
+
+#[tokio::test]
+async fn test_authentication_error() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Daft Punk", Some("4tZwfgrHOc3mvqYlEYSvVi")).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+    let request = json_request(
+        "PATCH",
+        &format!("/api/albums/{}", album.id),
+        &json!({ "ownership_status": "owned" }).to_string(),
+    );
+    create_test_router(&state).oneshot(request).await.unwrap();
+
+    // Suggestions need Spotify, which isn't connected
+    let (status, body) = error_response(&state, get(&format!("/api/albums/{}/suggestions", album.id))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body.error.code, ErrorCode::Authentication);
+    assert_eq!(body.error.message, "Spotify not connected");
+}
+
+#[tokio::test]
+async fn test_conflict_error() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Air", None).await;
+    let album = create_test_album(&state.db, artist.id, "Moon Safari", None).await;
+    let patch = |status: &str| {
+        json_request(
+            "PATCH",
+            &format!("/api/albums/{}", album.id),
+            &json!({ "ownership_status": status }).to_string(),
+        )
+    };
+    create_test_router(&state).oneshot(patch("owned")).await.unwrap();
+
+    let (status, body) = error_response(&state, patch("not_owned")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body.error.code, ErrorCode::Conflict);
+    let details: Value = body.error.details.unwrap();
+    assert_eq!(details["from"], "owned");
+    assert_eq!(details["to"], "not_owned");
+}
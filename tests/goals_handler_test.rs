@@ -0,0 +1,313 @@
+//! Integration tests for collection goal routes
+//!
+//! Tests goal definitions and the progress computed on read:
+//! - Playlist and artist goals report ownership against their target
+//! - Update and delete goals
+//! - Reject invalid targets and percentages
+//! - Goals are scoped to the active profile
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, Set};
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use beat_collector::db::entities::{albums, playlist_tracks, playlists, tracks};
+use beat_collector::db::enums::OwnershipStatus;
+use beat_collector::db::profile;
+use beat_collector::handlers;
+use beat_collector::state::AppState;
+use beat_collector::test_utils::*;
+
+/// Helper to create a test router with goal routes
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+/// Helper to parse JSON response body
+async fn parse_json_response<T: serde::de::DeserializeOwned>(
+    response: axum::response::Response,
+) -> T {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn json_request(method: &str, uri: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+async fn mark_owned(state: &AppState, album: albums::Model) {
+    let mut active: albums::ActiveModel = album.into();
+    active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+}
+
+/// Playlist in the default profile with one track from each of the given albums
+async fn create_playlist_with_albums(state: &AppState, album_ids: &[i32]) -> playlists::Model {
+    let now = Utc::now().into();
+    let playlist = playlists::ActiveModel {
+        name: Set("Liked Songs".to_string()),
+        spotify_id: Set("liked".to_string()),
+        is_collaborative: Set(false),
+        total_tracks: Set(Some(album_ids.len() as i32)),
+        is_enabled: Set(true),
+        is_synthetic: Set(false),
+        owned_count: Set(Some(0)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    profile::link_playlist(&state.db, default_profile.id, playlist.id)
+        .await
+        .unwrap();
+
+    for (position, album_id) in album_ids.iter().enumerate() {
+        let track = tracks::ActiveModel {
+            album_id: Set(*album_id),
+            title: Set(format!("Track {}", position + 1)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist.id),
+            track_id: Set(track.id),
+            position: Set(position as i32),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+    }
+
+    playlist
+}
+
+#[tokio::test]
+async fn test_goals_report_progress() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Radiohead", None).await;
+    let owned = create_test_album(&state.db, artist.id, "OK Computer", None).await;
+    let kid_a = create_test_album(&state.db, artist.id, "Kid A", None).await;
+    let amnesiac = create_test_album(&state.db, artist.id, "Amnesiac", None).await;
+    let in_rainbows = create_test_album(&state.db, artist.id, "In Rainbows", None).await;
+    mark_owned(&state, owned.clone()).await;
+
+    let playlist =
+        create_playlist_with_albums(&state, &[owned.id, kid_a.id, amnesiac.id, in_rainbows.id]).await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "POST",
+            "/api/goals",
+            json!({ "goal_type": "playlist", "target_id": playlist.id, "target_percentage": 90.0 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let goal: Value = parse_json_response(response).await;
+    assert_eq!(goal["target_name"], "Liked Songs");
+    assert_eq!(goal["owned_count"], 1);
+    assert_eq!(goal["total_count"], 4);
+    assert_eq!(goal["current_percentage"], 25.0);
+    assert_eq!(goal["remaining"], 3);
+    assert_eq!(goal["achieved"], false);
+
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "POST",
+            "/api/goals",
+            json!({ "goal_type": "artist", "target_id": artist.id, "target_percentage": 25.0 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // Progress follows ownership changes without touching the goals
+    mark_owned(&state, kid_a).await;
+
+    let response = app.oneshot(get("/api/goals")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let goals: Vec<Value> = parse_json_response(response).await;
+    assert_eq!(goals.len(), 2);
+    assert_eq!(goals[0]["goal_type"], "playlist");
+    assert_eq!(goals[0]["owned_count"], 2);
+    assert_eq!(goals[0]["remaining"], 2);
+    assert_eq!(goals[1]["goal_type"], "artist");
+    assert_eq!(goals[1]["target_name"], "Radiohead");
+    assert_eq!(goals[1]["owned_count"], 2);
+    assert_eq!(goals[1]["total_count"], 4);
+    assert_eq!(goals[1]["remaining"], 0);
+    assert_eq!(goals[1]["achieved"], true);
+}
+
+#[tokio::test]
+async fn test_update_and_delete_goal() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Björk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Homogenic", None).await;
+    create_test_album(&state.db, artist.id, "Post", None).await;
+    mark_owned(&state, album).await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "POST",
+            "/api/goals",
+            json!({ "goal_type": "artist", "target_id": artist.id, "target_percentage": 100.0 }),
+        ))
+        .await
+        .unwrap();
+    let goal: Value = parse_json_response(response).await;
+    let id = goal["id"].as_i64().unwrap();
+    assert_eq!(goal["achieved"], false);
+
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "PATCH",
+            &format!("/api/goals/{}", id),
+            json!({ "target_percentage": 50.0 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let goal: Value = parse_json_response(response).await;
+    assert_eq!(goal["target_percentage"], 50.0);
+    assert_eq!(goal["achieved"], true);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/api/goals/{}", id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .oneshot(get(&format!("/api/goals/{}", id)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_create_goal_validation() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Portishead", None).await;
+    create_test_album(&state.db, artist.id, "Dummy", None).await;
+    let app = create_test_router(&state);
+
+    let cases = [
+        (json!({ "goal_type": "label", "target_id": artist.id, "target_percentage": 50.0 }), StatusCode::UNPROCESSABLE_ENTITY),
+        (json!({ "goal_type": "artist", "target_id": artist.id, "target_percentage": 0.0 }), StatusCode::UNPROCESSABLE_ENTITY),
+        (json!({ "goal_type": "artist", "target_id": artist.id, "target_percentage": 120.0 }), StatusCode::UNPROCESSABLE_ENTITY),
+        (json!({ "goal_type": "artist", "target_id": 999, "target_percentage": 50.0 }), StatusCode::NOT_FOUND),
+        (json!({ "goal_type": "playlist", "target_id": 999, "target_percentage": 50.0 }), StatusCode::NOT_FOUND),
+    ];
+    for (body, status) in cases {
+        let response = app
+            .clone()
+            .oneshot(json_request("POST", "/api/goals", body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status, "{}", body);
+    }
+
+    // One goal per target
+    let body = json!({ "goal_type": "artist", "target_id": artist.id, "target_percentage": 50.0 });
+    let response = app
+        .clone()
+        .oneshot(json_request("POST", "/api/goals", body.clone()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = app
+        .oneshot(json_request("POST", "/api/goals", body))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_goals_are_scoped_to_profile() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Massive Attack", None).await;
+    create_test_album(&state.db, artist.id, "Mezzanine", None).await;
+    let partner = profile::create_profile(&state.db, "Partner").await.unwrap();
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "POST",
+            "/api/goals",
+            json!({ "goal_type": "artist", "target_id": artist.id, "target_percentage": 50.0 }),
+        ))
+        .await
+        .unwrap();
+    let goal: Value = parse_json_response(response).await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/goals")
+                .header(header::COOKIE, format!("beat_profile={}", partner.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let goals: Vec<Value> = parse_json_response(response).await;
+    assert!(goals.is_empty());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/goals/{}", goal["id"]))
+                .header(header::COOKIE, format!("beat_profile={}", partner.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -5,22 +5,27 @@
 //! - Get job status
 //! - Trigger Spotify sync
 //! - Trigger MusicBrainz match
+//! - Sync history report
+//! - Filesystem scan preview and applying its matches
+//! - Track length checks of the filesystem scan
 
 use axum::{
     body::Body,
     http::{Request, StatusCode},
     Router,
 };
-use sea_orm::EntityTrait;
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
 use serde_json::json;
 use tower::util::ServiceExt;
 
 use beat_collector::db::{
-    entities::jobs,
-    enums::{JobStatus, JobType},
+    entities::{albums, jobs, tracks},
+    enums::{JobStatus, JobType, OwnershipStatus},
 };
 use beat_collector::handlers;
 use beat_collector::state::AppState;
+use beat_collector::tasks::filesystem_scan;
 use beat_collector::test_utils::*;
 
 /// Helper to create a test router with job routes
@@ -355,3 +360,265 @@ async fn test_multiple_job_types() {
     assert!(job_types.contains(&"\"musicbrainz_match\"".to_string()));
     assert!(job_types.contains(&"\"spotify_sync\"".to_string()));
 }
+
+/// Mark a job finished `secs` seconds after it started
+async fn finish_job(
+    state: &AppState,
+    job: jobs::Model,
+    status: JobStatus,
+    secs: i64,
+    processed: Option<i32>,
+) -> jobs::Model {
+    let started = Utc::now() - Duration::minutes(10);
+    let mut active: jobs::ActiveModel = job.into();
+    active.status = Set(status.as_str().to_string());
+    active.started_at = Set(Some(started.into()));
+    active.completed_at = Set(Some((started + Duration::seconds(secs)).into()));
+    active.processed_items = Set(processed);
+    active.result = Set(processed.map(|n| json!({ "saved_albums": n, "playlists": 0 }).to_string()));
+    active.update(&state.db).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_sync_history() {
+    let state = setup_test_app_state().await;
+
+    let slow = create_test_job(&state.db, JobType::SpotifySync, JobStatus::Pending).await;
+    finish_job(&state, slow, JobStatus::Completed, 90, Some(120)).await;
+    let failed = create_test_job(&state.db, JobType::SpotifySync, JobStatus::Pending).await;
+    let mut failed: jobs::ActiveModel = finish_job(&state, failed, JobStatus::Failed, 5, None).await.into();
+    failed.error_message = Set(Some("Spotify not connected".to_string()));
+    failed.update(&state.db).await.unwrap();
+    create_test_job(&state.db, JobType::MusicbrainzMatch, JobStatus::Completed).await;
+    create_test_job(&state.db, JobType::SpotifySync, JobStatus::Running).await;
+
+    let app = create_test_router(&state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/reports/sync-history?job_type=spotify_sync")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    let jobs = body["jobs"].as_array().unwrap();
+    assert_eq!(jobs.len(), 3);
+    assert_eq!(body["pagination"]["total_items"], 3);
+
+    // Newest first: the running job has no outcome or duration yet
+    assert_eq!(jobs[0]["status"], "running");
+    assert!(jobs[0]["succeeded"].is_null());
+    assert!(jobs[0]["duration_ms"].is_null());
+
+    assert_eq!(jobs[1]["succeeded"], false);
+    assert_eq!(jobs[1]["duration_ms"], 5_000);
+    assert_eq!(jobs[1]["error_message"], "Spotify not connected");
+
+    assert_eq!(jobs[2]["succeeded"], true);
+    assert_eq!(jobs[2]["duration_ms"], 90_000);
+    assert_eq!(jobs[2]["items_processed"], 120);
+    assert_eq!(jobs[2]["result"]["saved_albums"], 120);
+}
+
+#[tokio::test]
+async fn test_sync_history_pagination() {
+    let state = setup_test_app_state().await;
+    for _ in 0..5 {
+        create_test_job(&state.db, JobType::SpotifySync, JobStatus::Completed).await;
+    }
+
+    let app = create_test_router(&state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/reports/sync-history?page=2&page_size=2")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(body["jobs"].as_array().unwrap().len(), 2);
+    assert_eq!(body["pagination"]["total_pages"], 3);
+}
+
+#[tokio::test]
+async fn test_sync_history_invalid_job_type() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/reports/sync-history?job_type=nap")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Music folder with `<Artist>/<Album>` folders of three tracks each
+fn music_folder(name: &str, albums: &[(&str, &str)]) -> std::path::PathBuf {
+    let root = std::env::temp_dir().join(format!("beat-collector-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for (artist, album) in albums {
+        let dir = root.join(artist).join(album);
+        std::fs::create_dir_all(&dir).unwrap();
+        for track in 1..=3 {
+            std::fs::write(dir.join(format!("{:02}.flac", track)), b"").unwrap();
+        }
+    }
+    root
+}
+
+#[tokio::test]
+async fn test_scan_preview_then_apply() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Radiohead", None).await;
+    let ok_computer = create_test_album(&state.db, artist.id, "OK Computer", None).await;
+    let kid_a = create_test_album(&state.db, artist.id, "Kid A", None).await;
+    let root = music_folder(
+        "scan-preview",
+        &[("Radiohead", "OK Computer"), ("Radiohead", "Kid A"), ("Portishead", "Dummy")],
+    );
+
+    let job = create_test_job(&state.db, JobType::FilesystemScanPreview, JobStatus::Running).await;
+    filesystem_scan::run_filesystem_scan_preview(state.clone(), job.id, &root)
+        .await
+        .unwrap();
+
+    // The preview proposes matches without touching the library
+    let job = jobs::Entity::find_by_id(job.id).one(&state.db).await.unwrap().unwrap();
+    let preview: serde_json::Value = serde_json::from_str(job.result.as_deref().unwrap()).unwrap();
+    assert_eq!(preview["proposals"].as_array().unwrap().len(), 2);
+    assert_eq!(preview["unmatched"], 1);
+    assert_eq!(preview["proposals"][0]["album_id"], kid_a.id);
+    assert_eq!(preview["proposals"][0]["confidence"], 1.0);
+    for album in [&ok_computer, &kid_a] {
+        let album = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+        assert_eq!(album.ownership_status, OwnershipStatus::NotOwned.as_str());
+        assert!(album.local_path.is_none());
+    }
+
+    // Only the accepted match is applied; IDs the preview didn't propose are ignored
+    let app = create_test_router(&state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/jobs/{}/apply-scan", job.id))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "album_ids": [ok_computer.id, 999] }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let report: serde_json::Value = parse_json_response(response).await;
+    assert_eq!(report["applied"], 1);
+    assert_eq!(report["unknown"], 1);
+
+    let owned = albums::Entity::find_by_id(ok_computer.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(owned.ownership_status, OwnershipStatus::Owned.as_str());
+    assert_eq!(
+        owned.local_path.as_deref(),
+        Some(root.join("Radiohead").join("OK Computer").to_string_lossy().as_ref())
+    );
+    let rejected = albums::Entity::find_by_id(kid_a.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(rejected.ownership_status, OwnershipStatus::NotOwned.as_str());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn test_scan_flags_tracks_with_mismatched_lengths() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Radiohead", None).await;
+    let ok_computer = create_test_album(&state.db, artist.id, "OK Computer", None).await;
+    create_test_album(&state.db, artist.id, "Kid A", None).await;
+    let now = Utc::now();
+    let mut album_tracks = Vec::new();
+    let listing = [
+        ("Airbag", 284_000),
+        ("Paranoid Android", 383_000),
+        ("Subterranean Homesick Alien", 267_000),
+    ];
+    for (number, (title, duration_ms)) in listing.into_iter().enumerate() {
+        let track = tracks::ActiveModel {
+            album_id: Set(ok_computer.id),
+            title: Set(title.to_string()),
+            track_number: Set(Some(number as i32 + 1)),
+            disc_number: Set(Some(1)),
+            duration_ms: Set(Some(duration_ms)),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+        album_tracks.push(track);
+    }
+
+    // One file within the default 3s tolerance, one cut short, one without tags
+    let root = music_folder("duration-check", &[("Radiohead", "OK Computer"), ("Radiohead", "Kid A")]);
+    let dir = root.join("Radiohead").join("OK Computer");
+    std::fs::write(dir.join("01.flac"), test_flac(285_500, Some(1), Some(1))).unwrap();
+    std::fs::write(dir.join("02.flac"), test_flac(121_000, Some(2), Some(1))).unwrap();
+
+    let job = create_test_job(&state.db, JobType::FilesystemScan, JobStatus::Running).await;
+    filesystem_scan::run_filesystem_scan_job(state.clone(), job.id, None, &root)
+        .await
+        .unwrap();
+
+    let job = jobs::Entity::find_by_id(job.id).one(&state.db).await.unwrap().unwrap();
+    let report: serde_json::Value = serde_json::from_str(job.result.as_deref().unwrap()).unwrap();
+    assert_eq!(report["albums_owned"], 2);
+    assert_eq!(report["mismatches"].as_array().unwrap().len(), 1);
+    assert_eq!(report["mismatches"][0]["track_id"], album_tracks[1].id);
+    assert_eq!(report["mismatches"][0]["spotify_duration_ms"], 383_000);
+    assert_eq!(report["mismatches"][0]["local_duration_ms"], 121_000);
+
+    let stored: Vec<(Option<i32>, bool)> = tracks::Entity::find()
+        .all(&state.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| (t.local_duration_ms, t.duration_mismatch))
+        .collect();
+    assert_eq!(stored, vec![(Some(285_500), false), (Some(121_000), true), (None, false)]);
+
+    // Only the album with a flagged track has issues
+    let app = create_test_router(&state);
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/api/albums?has_issues=true").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let page: serde_json::Value = parse_json_response(response).await;
+    let ids: Vec<i64> = page["albums"].as_array().unwrap().iter().map(|a| a["id"].as_i64().unwrap()).collect();
+    assert_eq!(ids, vec![ok_computer.id as i64]);
+
+    // A fixed rip clears the flag at the next scan
+    std::fs::write(dir.join("02.flac"), test_flac(383_200, Some(2), Some(1))).unwrap();
+    let report = filesystem_scan::run_filesystem_scan(state.clone(), None, &root).await.unwrap();
+    assert!(report.mismatches.is_empty());
+    let response = app
+        .oneshot(Request::builder().uri("/api/albums?has_issues=true").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let page: serde_json::Value = parse_json_response(response).await;
+    assert!(page["albums"].as_array().unwrap().is_empty());
+
+    std::fs::remove_dir_all(root).unwrap();
+}
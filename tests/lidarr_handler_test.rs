@@ -0,0 +1,464 @@
+//! Integration tests for the Lidarr webhook
+//!
+//! Lidarr retries deliveries, so these post the same payload more than once:
+//! - Replayed Grab creates a single download record
+//! - Replayed Download doesn't re-apply ownership or playlist stats
+//! - An import publishes one ownership event, handled once by each subscriber
+//! - A new Grab for the same download updates the existing record
+//! - A Grab for an owned album doesn't move it back to Downloading
+//! - Malformed payloads are rejected without being recorded
+//! - Recorded payloads are pruned once past the replay window
+//! - Download paths are rewritten by path mappings and checked against the
+//!   music folder
+//!
+//! It also covers the batch search preview, which lists qualifying and
+//! skipped albums (including unconfirmed matches) without contacting Lidarr.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, EntityTrait, PaginatorTrait, QueryOrder, Set};
+use tower::util::ServiceExt;
+
+use beat_collector::db::{
+    entities::{
+        activity_log, albums, lidarr_downloads, lidarr_webhook_events, ownership_events, playlist_tracks,
+        playlists, tracks, user_settings,
+    },
+    enums::{AlbumSource, MatchStatus, OwnershipStatus},
+};
+use beat_collector::events::{DomainEvent, EVENT_CAPACITY};
+use beat_collector::handlers;
+use beat_collector::state::AppState;
+use beat_collector::tasks::webhook_events::{self, WEBHOOK_EVENT_RETENTION};
+use beat_collector::test_utils::*;
+
+/// Helper to create a test router with the webhook route
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/lidarr/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path, e))
+}
+
+async fn post_webhook(app: &Router, body: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/webhooks/lidarr")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+/// Daft Punk's Discovery, with one track in an enabled playlist
+async fn setup_discovery(state: &AppState) -> (albums::Model, playlists::Model) {
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    let album = create_test_album(&state.db, artist.id, "Discovery", None).await;
+
+    let now = Utc::now().into();
+    let playlist = playlists::ActiveModel {
+        name: Set("Daft Punk Essentials".to_string()),
+        spotify_id: Set("daft-punk-essentials".to_string()),
+        is_collaborative: Set(false),
+        total_tracks: Set(Some(1)),
+        is_enabled: Set(true),
+        is_synthetic: Set(false),
+        owned_count: Set(Some(0)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let track = tracks::ActiveModel {
+        album_id: Set(album.id),
+        title: Set("One More Time".to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    playlist_tracks::ActiveModel {
+        playlist_id: Set(playlist.id),
+        track_id: Set(track.id),
+        position: Set(0),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    (album, playlist)
+}
+
+#[tokio::test]
+async fn test_replayed_grab_creates_single_download() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    let (album, _) = setup_discovery(&state).await;
+
+    let grab = fixture("webhook_grab.json");
+    assert_eq!(post_webhook(&app, &grab).await, StatusCode::OK);
+    assert_eq!(post_webhook(&app, &grab).await, StatusCode::OK);
+
+    let downloads = lidarr_downloads::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(downloads.len(), 1);
+    assert_eq!(downloads[0].album_id, album.id);
+    assert_eq!(downloads[0].download_id.as_deref(), Some("SABnzbd_nzo_abc123"));
+
+    let events = lidarr_webhook_events::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "Grab");
+}
+
+#[tokio::test]
+async fn test_replayed_download_is_not_reapplied() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    let (album, playlist) = setup_discovery(&state).await;
+
+    let download = fixture("webhook_download.json");
+    assert_eq!(post_webhook(&app, &download).await, StatusCode::OK);
+
+    let owned = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(owned.ownership_status, OwnershipStatus::Owned.as_str());
+    state.events.settled().await;
+    let playlist = playlists::Entity::find_by_id(playlist.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(playlist.owned_count, Some(1));
+
+    // The user un-owns the album; a late retry of the same delivery must not undo that
+    let mut active: albums::ActiveModel = owned.into();
+    active.ownership_status = Set(OwnershipStatus::NotOwned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+
+    // Key order and whitespace don't make it a different event
+    let reformatted: serde_json::Value = serde_json::from_str(&download).unwrap();
+    assert_eq!(post_webhook(&app, &reformatted.to_string()).await, StatusCode::OK);
+
+    let album = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(album.ownership_status, OwnershipStatus::NotOwned.as_str());
+    let replayed = playlists::Entity::find_by_id(playlist.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(replayed.updated_at, playlist.updated_at);
+    assert_eq!(lidarr_webhook_events::Entity::find().count(&state.db).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_old_webhook_events_are_pruned() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    setup_discovery(&state).await;
+
+    let grab = fixture("webhook_grab.json");
+    let download = fixture("webhook_download.json");
+    assert_eq!(post_webhook(&app, &grab).await, StatusCode::OK);
+    assert_eq!(post_webhook(&app, &download).await, StatusCode::OK);
+
+    // The Grab was received just past the retention window
+    let grab_event = lidarr_webhook_events::Entity::find()
+        .order_by_asc(lidarr_webhook_events::Column::Id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: lidarr_webhook_events::ActiveModel = grab_event.into();
+    active.received_at = Set((Utc::now() - WEBHOOK_EVENT_RETENTION - chrono::Duration::hours(1)).into());
+    active.update(&state.db).await.unwrap();
+
+    assert_eq!(webhook_events::prune_webhook_events(&state).await.unwrap(), 1);
+    let remaining = lidarr_webhook_events::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].event_type, "Download");
+}
+
+#[tokio::test]
+async fn test_webhook_ownership_change_publishes_one_event() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    let (album, playlist) = setup_discovery(&state).await;
+    let mut events = state.events.receiver();
+
+    let download = fixture("webhook_download.json");
+    assert_eq!(post_webhook(&app, &download).await, StatusCode::OK);
+    assert_eq!(post_webhook(&app, &download).await, StatusCode::OK);
+    state.events.settled().await;
+
+    let mut published = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        published.push(event);
+    }
+    assert_eq!(
+        published,
+        vec![DomainEvent::OwnershipChanged {
+            album_id: album.id,
+            from: OwnershipStatus::NotOwned,
+            to: OwnershipStatus::Owned,
+            reason: "lidarr_import".to_string(),
+            forced: false,
+        }]
+    );
+
+    // One audit row, and the activity feed and playlist stats each picked it up once
+    let logged = ownership_events::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0].album_id, album.id);
+    assert_eq!(logged[0].reason, "lidarr_import");
+    let feed = activity_log::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(feed.len(), 1);
+    assert_eq!((feed[0].kind.as_str(), feed[0].album_id), ("ownership_changed", Some(album.id)));
+    let playlist = playlists::Entity::find_by_id(playlist.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(playlist.owned_count, Some(1));
+
+    let response = app
+        .oneshot(Request::builder().uri("/api/activity").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["activity"][0]["kind"], "ownership_changed");
+    assert_eq!(body["activity"][0]["details"]["reason"], "lidarr_import");
+}
+
+#[tokio::test]
+async fn test_subscribers_that_fall_behind_catch_up() {
+    let state = setup_test_app_state().await;
+    let (album, playlist) = setup_discovery(&state).await;
+
+    // The album becomes owned, but more events than a subscriber can queue
+    // follow before the subscribers get to run, so the change is skipped
+    let mut active: albums::ActiveModel = album.clone().into();
+    active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+    state.events.publish(DomainEvent::OwnershipChanged {
+        album_id: album.id,
+        from: OwnershipStatus::NotOwned,
+        to: OwnershipStatus::Owned,
+        reason: "manual".to_string(),
+        forced: false,
+    });
+    for _ in 0..EVENT_CAPACITY + 10 {
+        state.events.publish(DomainEvent::AlbumImported { album_id: album.id, source: AlbumSource::SavedAlbum });
+    }
+    state.events.settled().await;
+
+    // Playlist stats recount every playlist instead
+    let playlist = playlists::Entity::find_by_id(playlist.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(playlist.owned_count, Some(1));
+
+    // The activity feed notes the gap ahead of the events it did get
+    let feed = activity_log::Entity::find().order_by_asc(activity_log::Column::Id).all(&state.db).await.unwrap();
+    assert_eq!(feed[0].kind, "skipped");
+    assert_eq!(feed[0].details, r#"{"skipped":11}"#);
+    assert_eq!(feed.len(), EVENT_CAPACITY + 1);
+    assert!(feed[1..].iter().all(|entry| entry.kind == "album_imported"));
+}
+
+#[tokio::test]
+async fn test_regrab_updates_existing_download() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    setup_discovery(&state).await;
+
+    let grab = fixture("webhook_grab.json");
+    assert_eq!(post_webhook(&app, &grab).await, StatusCode::OK);
+
+    // Same download reported again with a different Lidarr album ID
+    let mut regrab: serde_json::Value = serde_json::from_str(&grab).unwrap();
+    regrab["albums"][0]["id"] = 43.into();
+    assert_eq!(post_webhook(&app, &regrab.to_string()).await, StatusCode::OK);
+
+    let downloads = lidarr_downloads::Entity::find().all(&state.db).await.unwrap();
+    assert_eq!(downloads.len(), 1);
+    assert_eq!(downloads[0].lidarr_album_id, Some(43));
+    assert_eq!(lidarr_webhook_events::Entity::find().count(&state.db).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_grab_keeps_owned_album_owned() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    let (album, _) = setup_discovery(&state).await;
+
+    let mut active: albums::ActiveModel = album.clone().into();
+    active.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+
+    // An upgrade grab is still tracked, but can't take the album out of Owned
+    let grab = fixture("webhook_grab.json");
+    assert_eq!(post_webhook(&app, &grab).await, StatusCode::OK);
+
+    let album = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(album.ownership_status, OwnershipStatus::Owned.as_str());
+    assert_eq!(lidarr_downloads::Entity::find().count(&state.db).await.unwrap(), 1);
+    assert_eq!(ownership_events::Entity::find().count(&state.db).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_download_paths_are_mapped_and_checked() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+    let (album, _) = setup_discovery(&state).await;
+
+    // Lidarr sees the library at /music, this instance at /srv/music
+    let now = Utc::now().into();
+    let settings = user_settings::ActiveModel {
+        album_view: Set("grid".to_string()),
+        music_folder_path: Set(Some("/srv/music".to_string())),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let download = fixture("webhook_download.json");
+    assert_eq!(post_webhook(&app, &download).await, StatusCode::OK);
+
+    let owned = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(owned.local_path.as_deref(), Some("/music/Daft Punk/Discovery (2001)"));
+    let warned = user_settings::Entity::find_by_id(settings.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(warned.path_mismatch_prefixes.as_deref(), Some(r#"["/music"]"#));
+
+    // With a mapping, the next import lands in the music folder and clears the warning
+    let mut active: user_settings::ActiveModel = warned.into();
+    active.path_mappings = Set(Some(r#"{"/music": "/srv/music"}"#.to_string()));
+    active.update(&state.db).await.unwrap();
+    let mut active: albums::ActiveModel = owned.into();
+    active.ownership_status = Set(OwnershipStatus::NotOwned.as_str().to_string());
+    active.update(&state.db).await.unwrap();
+
+    let upgrade = download.replace(r#""isUpgrade": false"#, r#""isUpgrade": true"#);
+    assert_eq!(post_webhook(&app, &upgrade).await, StatusCode::OK);
+
+    let owned = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(owned.local_path.as_deref(), Some("/srv/music/Daft Punk/Discovery (2001)"));
+    let cleared = user_settings::Entity::find_by_id(settings.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(cleared.path_mismatch_prefixes, None);
+}
+
+#[tokio::test]
+async fn test_malformed_webhook_is_not_recorded() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let status = post_webhook(&app, r#"{"eventType": "Grab", "albums": []}"#).await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(lidarr_webhook_events::Entity::find().count(&state.db).await.unwrap(), 0);
+}
+
+async fn get_search_preview(app: &Router, query: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/lidarr/search-preview{}", query))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_search_preview_requires_lidarr() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let (status, _) = get_search_preview(&app, "").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_search_preview_lists_qualifying_and_skipped() {
+    let state = setup_test_app_state().await;
+    let now = Utc::now().into();
+    user_settings::ActiveModel {
+        lidarr_url: Set(Some("http://localhost:8686".to_string())),
+        lidarr_api_key: Set(Some("test-api-key".to_string())),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+
+    let artist = create_test_artist(&state.db, "Daft Punk", None).await;
+    for (title, status, match_status, mbid) in [
+        ("Homework", OwnershipStatus::NotOwned, MatchStatus::Matched, Some("rg-homework")),
+        ("Discovery", OwnershipStatus::NotOwned, MatchStatus::Matched, Some("rg-discovery")),
+        ("Human After All", OwnershipStatus::NotOwned, MatchStatus::Matched, None),
+        ("Random Access Memories", OwnershipStatus::Owned, MatchStatus::Matched, Some("rg-ram")),
+        ("Alive 2007", OwnershipStatus::Downloading, MatchStatus::Matched, Some("rg-alive")),
+        // A release group picked but never confirmed isn't searched for
+        ("Daft Club", OwnershipStatus::NotOwned, MatchStatus::Pending, Some("rg-daft-club")),
+    ] {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        let mut active: albums::ActiveModel = album.into();
+        active.ownership_status = Set(status.as_str().to_string());
+        active.match_status = Set(Some(match_status.as_str().to_string()));
+        active.musicbrainz_release_group_id = Set(mbid.map(str::to_string));
+        active.update(&state.db).await.unwrap();
+    }
+    let app = create_test_router(&state);
+
+    let (status, body) = get_search_preview(&app, "?page_size=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["totals"]["qualifying"], 2);
+    assert_eq!(body["totals"]["already_owned"], 1);
+    assert_eq!(body["totals"]["already_downloading"], 1);
+    assert_eq!(body["totals"]["not_matched"], 1);
+    assert_eq!(body["totals"]["no_mbid"], 1);
+    assert_eq!(body["pagination"]["total_items"], 2);
+    assert_eq!(body["pagination"]["total_pages"], 2);
+    assert_eq!(body["albums"][0]["title"], "Homework");
+    assert!(body["albums"][0]["skip_reason"].is_null());
+
+    let (_, body) = get_search_preview(&app, "?list=skipped").await;
+    assert_eq!(body["pagination"]["total_items"], 4);
+    let reasons: Vec<(&str, &str)> = body["albums"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| (a["title"].as_str().unwrap(), a["skip_reason"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            ("Human After All", "no_mbid"),
+            ("Random Access Memories", "already_owned"),
+            ("Alive 2007", "already_downloading"),
+            ("Daft Club", "not_matched"),
+        ]
+    );
+
+    // A preview never changes anything
+    assert_eq!(ownership_events::Entity::find().count(&state.db).await.unwrap(), 0);
+}
@@ -0,0 +1,611 @@
+//! Integration tests for playlist handler routes
+//!
+//! Tests addressing playlists by their Spotify ID:
+//! - Get a playlist by Spotify ID
+//! - Toggle a playlist by Spotify ID
+//! - 404 for unknown Spotify IDs
+//!
+//! And ownership against synced tracks when a sync skipped some
+//!
+//! And marking a track's album owned from the playlist modal
+//!
+//! And streaming the track lists of the JSON playlist endpoints, including
+//! how a failed stream shows up
+//!
+//! And playlist settings export/import:
+//! - Enablement survives a wipe and resync
+//! - Unknown Spotify IDs are reported
+//! - The settings page form gets a notification
+
+use axum::{
+    body::{Body, HttpBody},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use futures_util::StreamExt;
+use sea_orm::{ActiveModelTrait, ConnectionTrait, EntityTrait, ModelTrait, Set};
+use serde_json::Value;
+use tower::util::ServiceExt;
+
+use beat_collector::db::entities::{albums, playlist_tracks, playlists, tracks};
+use beat_collector::db::enums::OwnershipStatus;
+use beat_collector::db::profile;
+use beat_collector::handlers;
+use beat_collector::state::AppState;
+use beat_collector::test_utils::*;
+
+/// Helper to create a test router with playlist routes
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+/// Helper to parse JSON response body
+async fn parse_json_response<T: serde::de::DeserializeOwned>(
+    response: axum::response::Response,
+) -> T {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn create_playlist(state: &AppState, name: &str, spotify_id: &str) -> playlists::Model {
+    let now = Utc::now().into();
+    playlists::ActiveModel {
+        name: Set(name.to_string()),
+        spotify_id: Set(spotify_id.to_string()),
+        is_collaborative: Set(false),
+        total_tracks: Set(Some(0)),
+        is_enabled: Set(true),
+        is_synthetic: Set(false),
+        owned_count: Set(Some(0)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_get_playlist_by_spotify_id() {
+    let state = setup_test_app_state().await;
+    create_playlist(&state, "Mixtape", "37i9dQZF1DXcBWIGoYBM5M").await;
+    let road_trip = create_playlist(&state, "Road Trip", "5ABHKGoOzxkaa28ttQV9sE").await;
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/playlists/by-spotify-id/5ABHKGoOzxkaa28ttQV9sE")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["playlist"]["id"], road_trip.id);
+    assert_eq!(body["playlist"]["name"], "Road Trip");
+}
+
+#[tokio::test]
+async fn test_toggle_playlist_by_spotify_id() {
+    let state = setup_test_app_state().await;
+    let playlist = create_playlist(&state, "Mixtape", "37i9dQZF1DXcBWIGoYBM5M").await;
+
+    for expected in [false, true] {
+        let response = create_test_router(&state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/playlists/by-spotify-id/37i9dQZF1DXcBWIGoYBM5M/toggle")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = parse_json_response(response).await;
+        assert_eq!(body["id"], playlist.id);
+        assert_eq!(body["is_enabled"], expected);
+
+        let stored = playlists::Entity::find_by_id(playlist.id)
+            .one(&state.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.is_enabled, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_spotify_id_returns_404() {
+    let state = setup_test_app_state().await;
+    create_playlist(&state, "Mixtape", "37i9dQZF1DXcBWIGoYBM5M").await;
+
+    for (method, uri) in [
+        ("GET", "/api/playlists/by-spotify-id/unknown"),
+        ("POST", "/api/playlists/by-spotify-id/unknown/toggle"),
+    ] {
+        let response = create_test_router(&state)
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{} {}", method, uri);
+    }
+}
+
+/// Playlist Spotify reports with 3 tracks, of which only 2 were synced (the
+/// third was a local file), both from an owned album
+async fn create_playlist_with_skipped_track(state: &AppState) -> playlists::Model {
+    let now = Utc::now().into();
+    let mut playlist: playlists::ActiveModel =
+        create_playlist(state, "Mixtape", "37i9dQZF1DXcBWIGoYBM5M").await.into();
+    playlist.total_tracks = Set(Some(3));
+    playlist.owned_count = Set(Some(2));
+    let playlist = playlist.update(&state.db).await.unwrap();
+
+    let artist = create_test_artist(&state.db, "Stereolab", None).await;
+    let album = create_test_album(&state.db, artist.id, "Dots and Loops", None).await;
+    let mut album: albums::ActiveModel = album.into();
+    album.ownership_status = Set(OwnershipStatus::Owned.as_str().to_string());
+    let album = album.update(&state.db).await.unwrap();
+
+    for position in 0..2 {
+        let track = tracks::ActiveModel {
+            album_id: Set(album.id),
+            title: Set(format!("Track {}", position + 1)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist.id),
+            track_id: Set(track.id),
+            position: Set(position),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+    }
+
+    playlist
+}
+
+async fn body_string(response: axum::response::Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_ownership_counts_synced_tracks_not_spotify_total() {
+    let state = setup_test_app_state().await;
+    let playlist = create_playlist_with_skipped_track(&state).await;
+
+    // JSON detail and toggle
+    for (method, uri) in [
+        ("GET", "/api/playlists/by-spotify-id/37i9dQZF1DXcBWIGoYBM5M".to_string()),
+        ("POST", format!("/api/playlists/{}/toggle", playlist.id)),
+    ] {
+        let response = create_test_router(&state)
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(&uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{} {}", method, uri);
+
+        let body: Value = parse_json_response(response).await;
+        let body = if method == "GET" { &body["playlist"] } else { &body };
+        assert_eq!(body["total_tracks"], 3, "{}", uri);
+        assert_eq!(body["synced_tracks"], 2, "{}", uri);
+        assert_eq!(body["owned_count"], 2, "{}", uri);
+        assert_eq!(body["ownership_percentage"], 100.0, "{}", uri);
+    }
+
+    // HTML modal and the toggle that re-renders it with the grid card
+    let html_app = Router::new()
+        .merge(handlers::html_routes())
+        .with_state(state.clone());
+    for (method, uri) in [
+        ("GET", format!("/playlists/{}", playlist.id)),
+        ("POST", format!("/playlists/{}/toggle", playlist.id)),
+    ] {
+        let response = html_app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(&uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{} {}", method, uri);
+
+        let html = body_string(response).await;
+        assert!(html.contains(" synced of 3 on Spotify"), "{}", uri);
+        assert!(html.contains("100.0%"), "{}", uri);
+        assert!(!html.contains("66.7%"), "{}", uri);
+    }
+}
+
+/// The modal's stats strip out of a rendered fragment
+fn stats_bar(html: &str) -> &str {
+    let start = html.find(r#"id="playlist-stats-bar""#).expect("no stats bar");
+    let end = start + html[start..].find("</div>").unwrap();
+    &html[start..end]
+}
+
+#[tokio::test]
+async fn test_mark_album_owned_from_playlist_modal() {
+    let state = setup_test_app_state().await;
+    let playlist = create_playlist_with_skipped_track(&state).await;
+
+    // A third track, from an album not owned yet
+    let now = Utc::now().into();
+    let artist = create_test_artist(&state.db, "Broadcast", None).await;
+    let album = create_test_album(&state.db, artist.id, "Tender Buttons", None).await;
+    let track = tracks::ActiveModel {
+        album_id: Set(album.id),
+        title: Set("America's Boy".to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    // ...and a fourth from the same album
+    let second = tracks::ActiveModel {
+        album_id: Set(album.id),
+        title: Set("Black Cat".to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    for (position, track_id) in [(2, track.id), (3, second.id)] {
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist.id),
+            track_id: Set(track_id),
+            position: Set(position),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+    }
+
+    let html_app = Router::new()
+        .merge(handlers::html_routes())
+        .with_state(state.clone());
+    let send = |method: &str, uri: String| {
+        let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+        html_app.clone().oneshot(request)
+    };
+
+    // Rows link to their artist, and only the wanted album offers the action
+    let modal = body_string(send("GET", format!("/playlists/{}", playlist.id)).await.unwrap()).await;
+    assert!(modal.contains(&format!(r#"href="/artists/{}""#, artist.slug.as_deref().unwrap())));
+    let mark_owned = format!("/playlists/{}/albums/{}/mark-owned", playlist.id, album.id);
+    assert_eq!(modal.matches(&mark_owned).count(), 2);
+    assert_eq!(modal.matches("/mark-owned").count(), 2);
+    assert!(stats_bar(&modal).contains("50.0%"));
+
+    // An album with no track in the playlist can't be marked from it
+    let elsewhere = create_test_album(&state.db, artist.id, "Work and Non Work", None).await;
+    let response = send("POST", format!("/playlists/{}/albums/{}/mark-owned", playlist.id, elsewhere.id))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // One cell swaps out every row of the album
+    let response = send("POST", mark_owned.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = body_string(response).await;
+    assert!(html.starts_with("<td"));
+    assert!(html.contains(&format!(r#"hx-swap-oob="outerHTML:.playlist-album-{}""#, album.id)));
+    assert!(!html.contains("/mark-owned"));
+    assert!(html.contains(r#"id="playlist-stats-bar" class="px-6 py-3 bg-gray-50 border-b flex-shrink-0 text-sm" hx-swap-oob="true""#));
+    assert!(html.contains(&format!(r#"id="playlist-card-{}""#, playlist.id)));
+
+    let album = albums::Entity::find_by_id(album.id).one(&state.db).await.unwrap().unwrap();
+    assert_eq!(album.ownership_status, OwnershipStatus::Owned.as_str());
+
+    // The strip matches what the next full load shows
+    let modal = body_string(send("GET", format!("/playlists/{}", playlist.id)).await.unwrap()).await;
+    assert!(stats_bar(&modal).contains("100.0%"));
+    assert_eq!(
+        stats_bar(&modal).replace(r#" hx-swap-oob="true""#, ""),
+        stats_bar(&html).replace(r#" hx-swap-oob="true""#, ""),
+    );
+
+    // A second click, e.g. from a stale row of the same album, changes nothing
+    let response = send("POST", mark_owned).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(stats_bar(&body_string(response).await).contains("100.0%"));
+}
+
+/// Playlist in the default profile's library, as a sync would store it
+async fn create_library_playlist(state: &AppState, spotify_id: &str, enabled: bool) -> playlists::Model {
+    let mut playlist: playlists::ActiveModel =
+        create_playlist(state, &format!("Playlist {}", spotify_id), spotify_id).await.into();
+    playlist.is_enabled = Set(enabled);
+    let playlist = playlist.update(&state.db).await.unwrap();
+
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    profile::link_playlist(&state.db, default_profile.id, playlist.id)
+        .await
+        .unwrap();
+    playlist
+}
+
+async fn export_settings(state: &AppState) -> Value {
+    let response = create_test_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/playlists/settings-export")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-disposition").unwrap(),
+        "attachment; filename=\"playlist-settings.json\""
+    );
+    parse_json_response(response).await
+}
+
+#[tokio::test]
+async fn test_playlist_settings_round_trip_after_wipe() {
+    let state = setup_test_app_state().await;
+    let road_trip = create_library_playlist(&state, "road-trip", true).await;
+    let focus = create_library_playlist(&state, "focus", false).await;
+    let gym = create_library_playlist(&state, "gym", true).await;
+
+    let exported = export_settings(&state).await;
+    assert_eq!(
+        exported,
+        serde_json::json!({
+            "focus": { "is_enabled": false },
+            "gym": { "is_enabled": true },
+            "road-trip": { "is_enabled": true },
+        })
+    );
+
+    // Wipe and resync: playlists come back disabled, and one hasn't synced yet
+    for playlist in [road_trip, focus, gym] {
+        playlist.delete(&state.db).await.unwrap();
+    }
+    create_library_playlist(&state, "road-trip", false).await;
+    create_library_playlist(&state, "focus", false).await;
+    create_library_playlist(&state, "new-releases", false).await;
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/playlists/settings-import")
+                .header("content-type", "application/json")
+                .body(Body::from(exported.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let report: Value = parse_json_response(response).await;
+    assert_eq!(report["updated"], 1);
+    assert_eq!(report["unchanged"], 1);
+    assert_eq!(report["unknown"], serde_json::json!(["gym"]));
+
+    // A body that isn't an export is rejected
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/playlists/settings-import")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"road-trip": true}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let restored = export_settings(&state).await;
+    assert_eq!(restored["road-trip"], exported["road-trip"]);
+    assert_eq!(restored["focus"], exported["focus"]);
+    assert_eq!(restored["new-releases"]["is_enabled"], false);
+}
+
+#[tokio::test]
+async fn test_playlist_settings_import_from_settings_page() {
+    let state = setup_test_app_state().await;
+    let playlist = create_library_playlist(&state, "road-trip", false).await;
+
+    let import = |settings: &str| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/playlists/settings-import")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .header("HX-Request", "true")
+            .body(Body::from(format!("settings={}", urlencoding::encode(settings))))
+            .unwrap()
+    };
+
+    let response = create_test_router(&state)
+        .oneshot(import(r#"{"road-trip": {"is_enabled": true}, "gym": {"is_enabled": true}}"#))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = body_string(response).await;
+    assert!(html.contains("1 updated, 0 unchanged, 1 not in the library yet"), "{}", html);
+
+    let stored = playlists::Entity::find_by_id(playlist.id)
+        .one(&state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stored.is_enabled);
+
+    // Pasting something else reports it without touching any playlist
+    let response = create_test_router(&state)
+        .oneshot(import("not json"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = body_string(response).await;
+    assert!(html.contains("Not a playlist settings export"), "{}", html);
+}
+
+/// Add `count` tracks of one album to a playlist, in order
+async fn add_playlist_tracks(state: &AppState, playlist: &playlists::Model, count: i32) {
+    let now = Utc::now().into();
+    let artist = create_test_artist(&state.db, "Can", None).await;
+    let album = create_test_album(&state.db, artist.id, "Tago Mago", None).await;
+
+    for position in 0..count {
+        let track = tracks::ActiveModel {
+            album_id: Set(album.id),
+            title: Set(format!("Track {}", position + 1)),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+        playlist_tracks::ActiveModel {
+            playlist_id: Set(playlist.id),
+            track_id: Set(track.id),
+            position: Set(position),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&state.db)
+        .await
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_playlist_tracks_are_streamed() {
+    let state = setup_test_app_state().await;
+    let playlist = create_playlist(&state, "Long Mix", "3cEYpjA9oz9GiPac4AsH4n").await;
+    // More tracks than fit in one batch, so the array spans several chunks
+    add_playlist_tracks(&state, &playlist, 120).await;
+
+    for uri in [
+        format!("/api/playlists/{}", playlist.id),
+        format!("/api/playlists/{}/tracks?offset=10&limit=70", playlist.id),
+    ] {
+        let response = create_test_router(&state)
+            .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+        // No length up front, so the server falls back to chunked transfer
+        assert!(response.headers().get(header::CONTENT_LENGTH).is_none(), "{}", uri);
+        assert_eq!(response.body().size_hint().exact(), None, "{}", uri);
+    }
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/playlists/{}", playlist.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["playlist"]["name"], "Long Mix");
+    let tracks = body["tracks"].as_array().unwrap();
+    assert_eq!(tracks.len(), 100);
+    assert_eq!(tracks[99]["track_name"], "Track 100");
+
+    let response = create_test_router(&state)
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/playlists/{}/tracks?offset=10&limit=70", playlist.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    let tracks = body["tracks"].as_array().unwrap();
+    assert_eq!(tracks.len(), 70);
+    assert_eq!(tracks[0]["position"], 10);
+    assert_eq!(tracks[69]["position"], 79);
+    assert_eq!(body["has_more"], true);
+    assert_eq!(body["total"], 120);
+    assert_eq!(body["next_offset"], 80);
+}
+
+#[tokio::test]
+async fn test_playlist_track_stream_failures_are_detectable() {
+    let state = setup_test_app_state().await;
+    let playlist = create_playlist(&state, "Long Mix", "3cEYpjA9oz9GiPac4AsH4n").await;
+    add_playlist_tracks(&state, &playlist, 120).await;
+    let get_playlist = || {
+        Request::builder()
+            .uri(format!("/api/playlists/{}", playlist.id))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // The first batch is out with the status; losing the table afterwards
+    // ends the body with an error and no closing "]}"
+    let response = create_test_router(&state).oneshot(get_playlist()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut body = response.into_body().into_data_stream();
+    let first = body.next().await.unwrap().unwrap();
+    assert!(String::from_utf8_lossy(&first).contains("Track 50"));
+
+    state.db.execute_unprepared("DROP TABLE playlist_tracks").await.unwrap();
+    let rest: Vec<_> = body.collect().await;
+    assert!(rest.last().unwrap().is_err());
+    assert!(rest.iter().flatten().all(|chunk| !chunk.ends_with(b"]}")));
+
+    // Failing before anything is sent is still a plain 500
+    let response = create_test_router(&state).oneshot(get_playlist()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -0,0 +1,409 @@
+//! Integration tests for profile handler routes
+//!
+//! Tests profile management and per-profile library isolation:
+//! - List and create profiles
+//! - Select a profile via cookie
+//! - Album lists scoped to the active profile
+//! - Album lists following the active profile's own settings
+//! - Restoring from the archive only within the active profile
+//! - Purging old archived rows by each profile's retention
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+use beat_collector::db::{
+    entities::{albums, playlists, tracks},
+    profile,
+};
+use beat_collector::handlers;
+use beat_collector::tasks::archive_purge;
+use beat_collector::state::AppState;
+use beat_collector::test_utils::*;
+
+/// Helper to create a test router with profile routes
+fn create_test_router(state: &AppState) -> Router {
+    Router::new()
+        .nest("/api", handlers::api_routes())
+        .with_state(state.clone())
+}
+
+/// Helper to parse JSON response body
+async fn parse_json_response<T: serde::de::DeserializeOwned>(
+    response: axum::response::Response,
+) -> T {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_list_profiles_includes_default() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/profiles")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let profiles: Vec<Value> = parse_json_response(response).await;
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0]["name"], profile::DEFAULT_PROFILE_NAME);
+    assert_eq!(profiles[0]["active"], true);
+    assert_eq!(profiles[0]["spotify_connected"], false);
+}
+
+#[tokio::test]
+async fn test_create_profile() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/profiles")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "name": "Partner" }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let created: Value = parse_json_response(response).await;
+    assert_eq!(created["name"], "Partner");
+    assert_eq!(created["active"], false);
+
+    // Duplicate names are rejected
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/profiles")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "name": "Partner" }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_select_profile_sets_cookie() {
+    let state = setup_test_app_state().await;
+    let partner = profile::create_profile(&state.db, "Partner").await.unwrap();
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/profiles/select")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(format!("profile_id={}", partner.id)))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response
+        .headers()
+        .get(header::SET_COOKIE)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(cookie.starts_with(&format!("beat_profile={};", partner.id)));
+    assert_eq!(response.headers().get("HX-Refresh").unwrap(), "true");
+}
+
+#[tokio::test]
+async fn test_select_unknown_profile_not_found() {
+    let state = setup_test_app_state().await;
+    let app = create_test_router(&state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/profiles/select")
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from("profile_id=999"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_album_lists_are_scoped_to_profile() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Shared Artist", None).await;
+
+    // Test albums are linked to the default profile
+    create_test_album(&state.db, artist.id, "Default Album", None).await;
+    let partner_album = create_test_album(&state.db, artist.id, "Partner Album", None).await;
+
+    let partner = profile::create_profile(&state.db, "Partner").await.unwrap();
+    profile::link_album(&state.db, partner.id, partner_album.id)
+        .await
+        .unwrap();
+
+    let app = create_test_router(&state);
+
+    // Without a cookie the default profile is used
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["pagination"]["total_items"], 2);
+
+    // The partner profile only sees its own album
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums")
+                .header(header::COOKIE, format!("beat_profile={}", partner.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["pagination"]["total_items"], 1);
+    assert_eq!(body["albums"][0]["title"], "Partner Album");
+}
+
+#[tokio::test]
+async fn test_album_lists_follow_profile_settings() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Shared Artist", None).await;
+    profile::default_profile(&state.db).await.unwrap();
+    let partner = profile::create_profile(&state.db, "Partner").await.unwrap();
+    for (title, total_tracks) in [("The Single", 1), ("The Album", 10)] {
+        let album = create_test_album(&state.db, artist.id, title, None).await;
+        profile::link_album(&state.db, partner.id, album.id).await.unwrap();
+        let mut active: albums::ActiveModel = album.into();
+        active.total_tracks = Set(Some(total_tracks));
+        active.update(&state.db).await.unwrap();
+    }
+    let app = create_test_router(&state);
+    let partner_cookie = format!("beat_profile={}", partner.id);
+
+    // Only the partner hides singles
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri("/api/settings")
+                .header("content-type", "application/json")
+                .header(header::COOKIE, &partner_cookie)
+                .body(Body::from(json!({ "exclude_singles": true }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/albums")
+                .header(header::COOKIE, &partner_cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["pagination"]["total_items"], 1);
+    assert_eq!(body["albums"][0]["title"], "The Album");
+
+    let response = app
+        .oneshot(Request::builder().uri("/api/albums").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["pagination"]["total_items"], 2);
+}
+
+#[tokio::test]
+async fn test_restore_is_scoped_to_profile() {
+    let state = setup_test_app_state().await;
+    let artist = create_test_artist(&state.db, "Shared Artist", None).await;
+    profile::default_profile(&state.db).await.unwrap();
+    let partner = profile::create_profile(&state.db, "Partner").await.unwrap();
+    let album = create_test_album(&state.db, artist.id, "Default Album", None).await;
+    let mut active: albums::ActiveModel = album.clone().into();
+    active.archived_at = Set(Some(chrono::Utc::now().into()));
+    active.update(&state.db).await.unwrap();
+    let app = create_test_router(&state);
+    let restore = |cookie: Option<String>| {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/archive/albums/{}/restore", album.id));
+        if let Some(cookie) = cookie {
+            request = request.header(header::COOKIE, cookie);
+        }
+        request.body(Body::empty()).unwrap()
+    };
+
+    // Another profile can't see the album, let alone restore it
+    let response = app
+        .clone()
+        .oneshot(restore(Some(format!("beat_profile={}", partner.id))))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app.oneshot(restore(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_archive_purge_follows_each_profiles_retention() {
+    let state = setup_test_app_state().await;
+    let default_profile = profile::default_profile(&state.db).await.unwrap();
+    let partner = profile::create_profile(&state.db, "Partner").await.unwrap();
+    let artist = create_test_artist(&state.db, "Archived Artist", None).await;
+    let now = chrono::Utc::now();
+    let days_ago = |days: i64| Some((now - chrono::Duration::days(days)).into());
+    let archive_album = |title: &'static str, archived_days_ago: i64| {
+        let state = state.clone();
+        let artist_id = artist.id;
+        async move {
+            let album = create_test_album(&state.db, artist_id, title, None).await;
+            let mut active: albums::ActiveModel = album.into();
+            active.archived_at = Set(days_ago(archived_days_ago));
+            active.update(&state.db).await.unwrap()
+        }
+    };
+
+    let old = archive_album("Old", 40).await;
+    let shared = archive_album("Shared", 40).await;
+    profile::link_album(&state.db, partner.id, shared.id).await.unwrap();
+    let recent = archive_album("Recent", 10).await;
+    let kept = create_test_album(&state.db, artist.id, "Kept", None).await;
+    let unliked = tracks::ActiveModel {
+        album_id: Set(kept.id),
+        title: Set("Unliked".to_string()),
+        archived_at: Set(days_ago(40)),
+        created_at: Set(now.into()),
+        updated_at: Set(now.into()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    let unfollowed = playlists::ActiveModel {
+        name: Set("Unfollowed".to_string()),
+        spotify_id: Set("unfollowed".to_string()),
+        is_collaborative: Set(false),
+        is_enabled: Set(true),
+        is_synthetic: Set(false),
+        archived_at: Set(days_ago(40)),
+        created_at: Set(now.into()),
+        updated_at: Set(now.into()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .unwrap();
+    profile::link_playlist(&state.db, default_profile.id, unfollowed.id).await.unwrap();
+
+    // Nothing is purged until a retention is set
+    assert_eq!(archive_purge::purge_archives(&state).await.unwrap(), 0);
+
+    // The partner keeps its archive for good
+    let app = create_test_router(&state);
+    let save_retention = |days: i32, cookie: Option<String>| {
+        let mut request = Request::builder()
+            .method("PUT")
+            .uri("/api/settings")
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(cookie) = cookie {
+            request = request.header(header::COOKIE, cookie);
+        }
+        app.clone()
+            .oneshot(request.body(Body::from(json!({ "archive_retention_days": days }).to_string())).unwrap())
+    };
+    let response = save_retention(30, None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["archive_retention_days"], 30);
+    let response = save_retention(0, Some(format!("beat_profile={}", partner.id))).await.unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert!(body["archive_retention_days"].is_null());
+
+    // Album, track and playlist, with the shared album only unlinked
+    assert_eq!(archive_purge::purge_archives(&state).await.unwrap(), 4);
+    assert!(albums::Entity::find_by_id(old.id).one(&state.db).await.unwrap().is_none());
+    assert!(tracks::Entity::find_by_id(unliked.id).one(&state.db).await.unwrap().is_none());
+    assert!(playlists::Entity::find_by_id(unfollowed.id).one(&state.db).await.unwrap().is_none());
+    assert!(albums::Entity::find_by_id(kept.id).one(&state.db).await.unwrap().is_some());
+    assert!(profile::album_link(&state.db, default_profile.id, shared.id).await.unwrap().is_none());
+    assert!(profile::album_link(&state.db, partner.id, shared.id).await.unwrap().is_some());
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/api/archive").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["albums"].as_array().unwrap().len(), 1);
+    assert_eq!(body["albums"][0]["id"], recent.id);
+    assert_eq!(body["playlists"], json!([]));
+    assert_eq!(body["tracks"], json!([]));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/archive")
+                .header(header::COOKIE, format!("beat_profile={}", partner.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body: Value = parse_json_response(response).await;
+    assert_eq!(body["albums"][0]["id"], shared.id);
+
+    // 0 keeps archived rows again; past ten years is too long
+    assert_eq!(save_retention(0, None).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(
+        save_retention(3651, None).await.unwrap().status(),
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let settings = beat_collector::db::settings::for_profile(&state.db, &default_profile).await.unwrap().unwrap();
+    assert_eq!(settings.archive_retention_days, None);
+}
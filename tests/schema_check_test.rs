@@ -0,0 +1,133 @@
+//! Integration tests for the database schema compatibility check
+//!
+//! Simulates a database that was migrated by a newer build by recording a
+//! migration this binary doesn't know about.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+use serde_json::Value;
+use tower::util::ServiceExt;
+
+use beat_collector::db::schema::{check_schema_compatibility, schema_status};
+use beat_collector::error::AppError;
+use beat_collector::handlers;
+use beat_collector::test_utils::*;
+
+const FUTURE_MIGRATION: &str = "m20990101_000001_from_the_future";
+
+async fn record_future_migration(db: &DatabaseConnection) {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        format!(
+            "INSERT INTO seaql_migrations (version, applied_at) VALUES ('{}', 0)",
+            FUTURE_MIGRATION
+        ),
+    ))
+    .await
+    .expect("Failed to record future migration");
+}
+
+#[tokio::test]
+async fn test_schema_in_sync() {
+    let db = setup_test_db().await;
+
+    let status = check_schema_compatibility(&db, false)
+        .await
+        .expect("Migrated test database should be compatible");
+
+    assert!(!status.is_ahead());
+    assert!(status.unknown.is_empty());
+    assert!(status.applied.len() >= 12);
+}
+
+#[tokio::test]
+async fn test_schema_ahead_is_refused() {
+    let db = setup_test_db().await;
+    record_future_migration(&db).await;
+
+    let err = check_schema_compatibility(&db, false)
+        .await
+        .expect_err("Should refuse to start against unknown migrations");
+
+    match err {
+        AppError::Configuration(msg) => {
+            assert!(msg.contains(FUTURE_MIGRATION));
+            assert!(msg.contains("ALLOW_SCHEMA_AHEAD"));
+        }
+        other => panic!("Expected configuration error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_schema_ahead_allowed_with_escape_hatch() {
+    let db = setup_test_db().await;
+    record_future_migration(&db).await;
+
+    let status = check_schema_compatibility(&db, true)
+        .await
+        .expect("ALLOW_SCHEMA_AHEAD should let startup continue");
+
+    assert!(status.is_ahead());
+    assert_eq!(status.unknown, vec![FUTURE_MIGRATION.to_string()]);
+}
+
+#[tokio::test]
+async fn test_verbose_health_lists_migrations() {
+    let state = setup_test_app_state().await;
+    record_future_migration(&state.db).await;
+
+    let app = Router::new()
+        .route("/health", get(handlers::health::health_check))
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/health?verbose=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    let applied = json["migrations"]["applied"].as_array().unwrap();
+    let status = schema_status(&state.db).await.unwrap();
+    assert_eq!(applied.len(), status.applied.len());
+    assert_eq!(json["migrations"]["unknown"][0], FUTURE_MIGRATION);
+    assert_eq!(json["job_queue"]["depth"], 0);
+    assert_eq!(json["job_queue"]["capacity"], state.config.job_queue_capacity);
+}
+
+#[tokio::test]
+async fn test_plain_health_omits_migrations() {
+    let state = setup_test_app_state().await;
+
+    let app = Router::new()
+        .route("/health", get(handlers::health::health_check))
+        .with_state(state);
+
+    let response = app
+        .oneshot(Request::builder().uri("/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["status"], "healthy");
+    assert!(json.get("migrations").is_none());
+}
@@ -0,0 +1,914 @@
+//! Snapshot tests for the Maud templates
+//!
+//! Every public template in `templates::pages` and `templates::components` is
+//! rendered with fixture data and compared with its snapshot in
+//! `tests/snapshots/`. Fixtures cover empty and populated variants, and each
+//! ownership status where the markup depends on it. After an intended markup
+//! change, review the new snapshots with `cargo insta review`.
+//!
+//! `assert_hx` checks the HTMX wiring of interactive elements on its own, so
+//! a lost hx-target or hx-include fails with a readable message rather than
+//! only a snapshot diff.
+//!
+//! Templates translate through `t!`, which panics on a message missing from
+//! the English catalog, so rendering them here also checks every key.
+
+use maud::{html, Markup};
+
+use beat_collector::db::enums::{AlbumView, OwnershipStatus};
+use beat_collector::services::artist_heatmap::{HeatmapAlbum, HeatmapArtist};
+use beat_collector::services::wanted::WantedAge;
+use beat_collector::i18n::{self, Locale};
+use beat_collector::templates::*;
+
+/// Rendered markup with one tag per line, so snapshot diffs point at the change
+fn pretty(markup: Markup) -> String {
+    markup.into_string().replace("><", ">\n<")
+}
+
+macro_rules! assert_markup_snapshot {
+    ($name:expr, $markup:expr) => {
+        insta::assert_snapshot!($name, pretty($markup))
+    };
+}
+
+/// Assert the element carrying `anchor` (say `hx-get="/jobs-list"`) also has each of `attrs`
+#[track_caller]
+fn assert_hx(markup: &Markup, anchor: &str, attrs: &[&str]) {
+    let html = markup.clone().into_string();
+    let at = html
+        .find(anchor)
+        .unwrap_or_else(|| panic!("no element with {}", anchor));
+    let start = html[..at].rfind('<').expect("attribute outside a tag");
+    let end = at + html[at..].find('>').expect("unterminated tag");
+    let tag = &html[start..=end];
+    for attr in attrs {
+        assert!(tag.contains(attr), "{} is missing {}", tag, attr);
+    }
+}
+
+/// Timestamps are rendered by the handlers; a fixed stand-in keeps snapshots stable
+fn at(text: &str) -> Markup {
+    html! { time datetime="2024-05-01T12:00:00Z" { (text) } }
+}
+
+fn album(id: i32, status: OwnershipStatus) -> AlbumCardData {
+    AlbumCardData {
+        id,
+        slug: format!("artist-album-{}", id),
+        title: format!("Album {}", id),
+        artist_id: 1,
+        artist_slug: "artist".to_string(),
+        artist_name: "Artist".to_string(),
+        cover_art_url: (id % 2 == 0).then(|| format!("https://i.scdn.co/image/{}", id)),
+        release_date: Some("2001-03-12".to_string()),
+        ownership_status: status,
+        match_score: match id % 3 {
+            0 => None,
+            1 => Some(95),
+            _ => Some(62),
+        },
+    }
+}
+
+/// One album in each ownership status
+fn albums() -> Vec<AlbumCardData> {
+    BOARD_COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, status)| album(i as i32 + 1, *status))
+        .collect()
+}
+
+fn page_info(total_items: u64) -> PageInfo {
+    PageInfo {
+        page: 2,
+        page_size: 2,
+        total_items,
+    }
+}
+
+fn playlist(enabled: bool, synthetic: bool) -> PlaylistCardData {
+    PlaylistCardData {
+        id: 7,
+        name: "Road Trip".to_string(),
+        owner_name: (!synthetic).then(|| "someone".to_string()),
+        track_count: 20,
+        synced_count: 20,
+        owned_count: 13,
+        cover_image_url: None,
+        is_enabled: enabled,
+        disabled_reason: None,
+        ownership_percentage: 65.0,
+        is_synthetic: synthetic,
+        unavailable_count: 2,
+        episode_count: if synthetic { 0 } else { 1 },
+        last_synced: Some(at("May 1, 12:00")),
+    }
+}
+
+/// A playlist syncs disabled after it kept failing
+fn sync_failing_playlist() -> PlaylistCardData {
+    PlaylistCardData {
+        disabled_reason: Some("Disabled after 3 failed syncs in a row: 404 Not Found".to_string()),
+        ..playlist(false, false)
+    }
+}
+
+fn tracks() -> Vec<PlaylistTrackData> {
+    BOARD_COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, status)| PlaylistTrackData {
+            position: i as i32,
+            track_name: format!("Track {}", i + 1),
+            artist_slug: "artist".to_string(),
+            artist_name: "Artist".to_string(),
+            album_id: i as i32 + 1,
+            album_slug: format!("artist-album-{}", i + 1),
+            album_name: format!("Album {}", i + 1),
+            duration_ms: (i != 2).then_some(215_000 + i as i32 * 1_000),
+            ownership_status: *status,
+        })
+        .collect()
+}
+
+fn artist(percentage: f64) -> ArtistCardData {
+    ArtistCardData {
+        id: 1,
+        slug: "artist".to_string(),
+        name: "Artist".to_string(),
+        image_url: None,
+        album_count: 10,
+        owned_count: (percentage / 10.0) as i64,
+        ownership_percentage: percentage,
+    }
+}
+
+fn board_card_for(status: OwnershipStatus) -> BoardCardData {
+    BoardCardData {
+        id: 3,
+        slug: "artist-album-3".to_string(),
+        title: "Album 3".to_string(),
+        artist_name: "Artist".to_string(),
+        cover_art_url: None,
+        ownership_status: status,
+        wanted_age: (status == OwnershipStatus::NotOwned).then_some(WantedAge::Months(8)),
+    }
+}
+
+fn top_artists() -> Vec<TopArtistData> {
+    vec![
+        TopArtistData {
+            name: "Owned Artist".to_string(),
+            slug: Some("owned-artist".to_string()),
+            image_url: Some("https://i.scdn.co/image/artist".to_string()),
+            album_count: 2,
+            owned_count: 2,
+        },
+        TopArtistData {
+            name: "Partial Artist".to_string(),
+            slug: Some("partial-artist".to_string()),
+            image_url: None,
+            album_count: 3,
+            owned_count: 1,
+        },
+        TopArtistData {
+            name: "Stranger".to_string(),
+            slug: None,
+            image_url: None,
+            album_count: 0,
+            owned_count: 0,
+        },
+    ]
+}
+
+fn top_albums() -> Vec<TopAlbumData> {
+    BOARD_COLUMNS
+        .iter()
+        .map(|status| Some(*status))
+        .chain([None])
+        .enumerate()
+        .map(|(i, status)| TopAlbumData {
+            title: format!("Top Album {}", i + 1),
+            artist_name: "Artist".to_string(),
+            image_url: None,
+            slug: status.map(|_| format!("artist-top-album-{}", i + 1)),
+            ownership_status: status,
+        })
+        .collect()
+}
+
+fn jobs() -> Vec<JobRowData> {
+    vec![
+        JobRowData {
+            id: 2,
+            job_type: "musicbrainz_match".to_string(),
+            status: "running".to_string(),
+            progress: Some(40),
+            error_message: None,
+            detail: None,
+            created: at("12:00"),
+            completed: None,
+        },
+        JobRowData {
+            id: 1,
+            job_type: "spotify_sync".to_string(),
+            status: "failed".to_string(),
+            progress: None,
+            error_message: Some("Spotify returned 502".to_string()),
+            detail: Some("38 API calls made, 2 rate-limit hits".to_string()),
+            created: at("11:00"),
+            completed: Some(at("11:05")),
+        },
+    ]
+}
+
+// ============================================================================
+// Pages
+// ============================================================================
+
+#[test]
+fn test_home_page() {
+    assert_markup_snapshot!("home_page_grid", home_page(AlbumView::Grid));
+    assert_markup_snapshot!("home_page_list", home_page(AlbumView::List));
+}
+
+#[test]
+fn test_album_grid_partial() {
+    assert_markup_snapshot!(
+        "album_grid_empty",
+        album_grid_partial(vec![], page_info(0), AlbumView::Grid)
+    );
+    assert_markup_snapshot!(
+        "album_grid_grid",
+        album_grid_partial(albums(), page_info(9), AlbumView::Grid)
+    );
+    assert_markup_snapshot!(
+        "album_grid_list",
+        album_grid_partial(albums(), page_info(9), AlbumView::List)
+    );
+}
+
+#[test]
+fn test_album_detail_modal() {
+    for status in BOARD_COLUMNS {
+        let name = format!("album_detail_modal_bare_{}", status.as_str());
+        assert_markup_snapshot!(
+            name,
+            album_detail_modal(&album(3, status), "Artist", &None, &AlbumTracksData::default(), &[], None, None)
+        );
+    }
+
+    assert_markup_snapshot!("album_detail_modal_full", full_album_modal());
+}
+
+/// Album modal with every optional section filled in
+fn full_album_modal() -> Markup {
+    let downloads = [
+        AlbumDownloadData {
+            status: "completed".to_string(),
+            download_id: Some("SABnzbd_nzo_1".to_string()),
+            created_at: at("May 1"),
+            error_message: None,
+            retry_count: 0,
+        },
+        AlbumDownloadData {
+            status: "failed".to_string(),
+            download_id: None,
+            created_at: at("Apr 30"),
+            error_message: Some("No files found".to_string()),
+            retry_count: 2,
+        },
+    ];
+    let listens = AlbumListensData {
+        count: 1204,
+        first_listened: at("Jan 3"),
+        last_listened: at("May 1"),
+    };
+    let tracks = AlbumTracksData {
+        total: Some(14),
+        mismatches: vec![
+            TrackMismatchData {
+                disc_number: 1,
+                track_number: 3,
+                title: "Digital Love".to_string(),
+                spotify_duration_ms: 301_000,
+                local_duration_ms: 122_000,
+            },
+            TrackMismatchData {
+                disc_number: 2,
+                track_number: 1,
+                title: "Too Long".to_string(),
+                spotify_duration_ms: 600_000,
+                local_duration_ms: 594_500,
+            },
+        ],
+    };
+    album_detail_modal(
+        &album(4, OwnershipStatus::Owned),
+        "Artist feat. Guest",
+        &Some(vec!["electronic".to_string(), "house".to_string()]),
+        &tracks,
+        &downloads,
+        Some(&listens),
+        Some("f82bcf78-5b69-4622-a5ef-73800768d9ac"),
+    )
+}
+
+#[test]
+fn test_static_pages() {
+    assert_markup_snapshot!("settings_page_empty", settings_page(None, None, &[], Locale::En));
+    assert_markup_snapshot!(
+        "settings_page_configured",
+        settings_page(
+            Some("http://lidarr:8686".to_string()),
+            Some("/music".to_string()),
+            &[],
+            Locale::En
+        )
+    );
+    assert_markup_snapshot!(
+        "settings_page_path_mismatch",
+        settings_page(
+            Some("http://lidarr:8686".to_string()),
+            Some("/music".to_string()),
+            &["/data/music".to_string(), "/downloads".to_string()],
+            Locale::En
+        )
+    );
+    assert_markup_snapshot!("jobs_page", jobs_page());
+    assert_markup_snapshot!("stats_page", stats_page());
+    assert_markup_snapshot!("playlists_page", playlists_page());
+    assert_markup_snapshot!("artists_page", artists_page());
+}
+
+#[test]
+fn test_jobs_list_partial() {
+    assert_markup_snapshot!("jobs_list_empty", jobs_list_partial(&[]));
+    assert_markup_snapshot!("jobs_list", jobs_list_partial(&jobs()));
+}
+
+#[test]
+fn test_job_detail_page() {
+    let preview = JobRowData {
+        id: 3,
+        job_type: "filesystem_scan_preview".to_string(),
+        status: "completed".to_string(),
+        progress: Some(100),
+        error_message: None,
+        detail: None,
+        created: at("13:00"),
+        completed: Some(at("13:01")),
+    };
+    let proposals = [
+        ScanProposalData {
+            album_id: 4,
+            directory: "/music/Radiohead/OK Computer".to_string(),
+            artist_name: "Radiohead".to_string(),
+            album_title: "OK Computer".to_string(),
+            confidence: 100,
+            accepted: true,
+        },
+        ScanProposalData {
+            album_id: 7,
+            directory: "/music/Radiohed/Kid A (Deluxe)".to_string(),
+            artist_name: "Radiohead".to_string(),
+            album_title: "Kid A".to_string(),
+            confidence: 82,
+            accepted: false,
+        },
+    ];
+    assert_markup_snapshot!("job_detail_scan_preview", job_detail_page(&preview, Some(&proposals), 90));
+    assert_markup_snapshot!("job_detail", job_detail_page(&jobs()[1], None, 90));
+}
+
+/// Pages converted to messages render in the configured locale. Every test
+/// here renders through the English catalog, where a missing message panics.
+#[test]
+fn test_pages_in_german() {
+    i18n::with_locale(Locale::De, || {
+        assert_markup_snapshot!("home_page_grid_de", home_page(AlbumView::Grid));
+        assert_markup_snapshot!("album_detail_modal_full_de", full_album_modal());
+        assert_markup_snapshot!(
+            "settings_page_path_mismatch_de",
+            settings_page(
+                Some("http://lidarr:8686".to_string()),
+                Some("/music".to_string()),
+                &["/data/music".to_string()],
+                Locale::De
+            )
+        );
+        // Sent back by the card quick actions through the API
+        assert_markup_snapshot!("album_card_oob_de", album_card_oob(&album(4, OwnershipStatus::NotOwned)));
+    });
+}
+
+#[test]
+fn test_heavy_rotation() {
+    assert_markup_snapshot!("heavy_rotation_empty", heavy_rotation_panel(&[], &[]));
+    assert_markup_snapshot!(
+        "heavy_rotation_panel",
+        heavy_rotation_panel(&top_artists(), &top_albums())
+    );
+    assert_markup_snapshot!(
+        "heavy_rotation_message",
+        heavy_rotation_message("Spotify is unavailable.")
+    );
+    assert_markup_snapshot!("heavy_rotation_reauth", heavy_rotation_reauth());
+}
+
+#[test]
+fn test_artist_heatmap() {
+    let heatmap = vec![HeatmapArtist {
+        id: 1,
+        name: "Artist".to_string(),
+        slug: "artist".to_string(),
+        album_count: 6,
+        albums: BOARD_COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(i, status)| HeatmapAlbum {
+                id: i as i32 + 1,
+                slug: format!("artist-album-{}", i + 1),
+                title: format!("Album {}", i + 1),
+                release_date: (i != 3).then(|| format!("200{}-05-01", i)),
+                ownership_status: status.as_str().to_string(),
+            })
+            .collect(),
+        hidden_albums: 2,
+    }];
+
+    assert_markup_snapshot!("artist_heatmap_empty", artist_heatmap_panel(&[]));
+    let panel = artist_heatmap_panel(&heatmap);
+    assert_hx(
+        &panel,
+        r#"hx-get="/albums/artist-album-1""#,
+        &[r##"hx-target="#album-detail-modal""##, r#"hx-swap="innerHTML""#],
+    );
+    assert_markup_snapshot!("artist_heatmap_panel", panel);
+}
+
+#[test]
+fn test_board_page() {
+    let columns: Vec<BoardColumnData> = BOARD_COLUMNS
+        .iter()
+        .map(|status| BoardColumnData {
+            status: *status,
+            count: if *status == OwnershipStatus::Owned {
+                0
+            } else {
+                1
+            },
+            cards: if *status == OwnershipStatus::Owned {
+                vec![]
+            } else {
+                vec![board_card_for(*status)]
+            },
+        })
+        .collect();
+    assert_markup_snapshot!("board_page", board_page(&columns));
+}
+
+#[test]
+fn test_archive_page() {
+    let removed_at = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap();
+    let albums = vec![ArchivedRowData {
+        id: 3,
+        title: "Geogaddi".to_string(),
+        detail: Some("Boards of Canada".to_string()),
+        archived_at: removed_at,
+    }];
+    let tracks = vec![ArchivedRowData {
+        id: 9,
+        title: "Music Is Math".to_string(),
+        detail: Some("Boards of Canada · Geogaddi".to_string()),
+        archived_at: removed_at,
+    }];
+    let utc = resolve_timezone(None);
+
+    assert_markup_snapshot!("archive_page_empty", archive_page(&[], &[], &[], utc));
+    let page = archive_page(&albums, &[], &tracks, utc);
+    assert_hx(
+        &page,
+        r#"hx-post="/api/archive/tracks/9/restore""#,
+        &[r#"hx-target="closest li""#, r#"hx-swap="delete""#],
+    );
+    assert_markup_snapshot!("archive_page", page);
+}
+
+#[test]
+fn test_playlist_partials() {
+    assert_markup_snapshot!(
+        "playlist_grid_empty",
+        playlist_grid_partial(vec![], page_info(0))
+    );
+    assert_markup_snapshot!(
+        "playlist_grid",
+        playlist_grid_partial(
+            vec![playlist(true, false), playlist(false, true)],
+            page_info(9)
+        )
+    );
+    assert_markup_snapshot!(
+        "playlist_detail_empty",
+        playlist_detail_partial(&playlist(false, false), vec![], 1, 1)
+    );
+    assert_markup_snapshot!(
+        "playlist_detail",
+        playlist_detail_partial(&playlist(true, false), tracks(), 2, 3)
+    );
+    assert_markup_snapshot!(
+        "playlist_detail_sync_failing",
+        playlist_detail_partial(&sync_failing_playlist(), vec![], 1, 1)
+    );
+}
+
+#[test]
+fn test_artist_partials() {
+    assert_markup_snapshot!(
+        "artist_grid_empty",
+        artist_grid_partial(vec![], page_info(0))
+    );
+    assert_markup_snapshot!(
+        "artist_grid",
+        artist_grid_partial(vec![artist(100.0), artist(60.0), artist(0.0)], page_info(9))
+    );
+    assert_markup_snapshot!(
+        "artist_detail_empty",
+        artist_detail_page(&artist(0.0), vec![])
+    );
+    assert_markup_snapshot!("artist_detail", artist_detail_page(&artist(50.0), albums()));
+}
+
+// ============================================================================
+// Components
+// ============================================================================
+
+#[test]
+fn test_album_card_and_row_per_status() {
+    for status in BOARD_COLUMNS {
+        let album = album(4, status);
+        assert_markup_snapshot!(
+            format!("album_card_{}", status.as_str()),
+            album_card(&album)
+        );
+        assert_markup_snapshot!(
+            format!("album_list_row_{}", status.as_str()),
+            album_list_row(&album)
+        );
+    }
+}
+
+#[test]
+fn test_filter_bars() {
+    assert_markup_snapshot!("filter_bar_grid", filter_bar(AlbumView::Grid));
+    assert_markup_snapshot!("filter_bar_list", filter_bar(AlbumView::List));
+    assert_markup_snapshot!("artist_filter_bar", artist_filter_bar());
+}
+
+#[test]
+fn test_pagination() {
+    assert_markup_snapshot!(
+        "pagination_single_page",
+        pagination(&page_info(1), &ALBUM_PAGINATION)
+    );
+    assert_markup_snapshot!(
+        "pagination_albums",
+        pagination(
+            &PageInfo {
+                page: 5,
+                page_size: 10,
+                total_items: 200
+            },
+            &ALBUM_PAGINATION
+        )
+    );
+    assert_markup_snapshot!(
+        "pagination_artists",
+        pagination(&page_info(9), &ARTIST_PAGINATION)
+    );
+    assert_markup_snapshot!(
+        "pagination_playlists",
+        pagination(&page_info(9), &PLAYLIST_PAGINATION)
+    );
+}
+
+#[test]
+fn test_notifications() {
+    assert_markup_snapshot!("loading_spinner", loading_spinner());
+    for kind in ["success", "error", "info", "other"] {
+        assert_markup_snapshot!(
+            format!("notification_{}", kind),
+            notification("Saved", kind)
+        );
+    }
+    assert_markup_snapshot!(
+        "lidarr_search_started_notification",
+        lidarr_search_started_notification("artist-album-3", 42)
+    );
+    assert_markup_snapshot!(
+        "lidarr_album_missing_notification",
+        lidarr_album_missing_notification()
+    );
+}
+
+#[test]
+fn test_artist_options() {
+    let options = [
+        ArtistOptionData {
+            id: 1,
+            name: "Same Name".to_string(),
+            album_count: 4,
+        },
+        ArtistOptionData {
+            id: 2,
+            name: "Same Name".to_string(),
+            album_count: 1,
+        },
+    ];
+    assert_markup_snapshot!("artist_options_empty", artist_options(&[], ""));
+    assert_markup_snapshot!("artist_options", artist_options(&options, "same name"));
+    assert_markup_snapshot!("artist_options_new", artist_options(&[], "New Artist"));
+}
+
+#[test]
+fn test_playlist_components() {
+    for (name, enabled, synthetic) in [
+        ("enabled", true, false),
+        ("disabled", false, false),
+        ("synthetic", true, true),
+    ] {
+        let playlist = playlist(enabled, synthetic);
+        assert_markup_snapshot!(format!("playlist_card_{}", name), playlist_card(&playlist));
+        assert_markup_snapshot!(
+            format!("playlist_card_oob_{}", name),
+            playlist_card_oob(&playlist)
+        );
+    }
+    assert_markup_snapshot!("playlist_card_sync_failing", playlist_card(&sync_failing_playlist()));
+    assert_markup_snapshot!(
+        "playlist_stats_bar",
+        playlist_stats_bar(&playlist(true, false))
+    );
+    // A sync skipped some of the tracks Spotify counts
+    let skipped_tracks = PlaylistCardData {
+        synced_count: 18,
+        ownership_percentage: 72.2,
+        ..playlist(true, false)
+    };
+    assert_markup_snapshot!("playlist_stats_bar_skipped_tracks", playlist_stats_bar(&skipped_tracks));
+    assert_markup_snapshot!("playlist_card_skipped_tracks", playlist_card(&skipped_tracks));
+    assert_markup_snapshot!(
+        "playlist_stats_bar_never_synced",
+        playlist_stats_bar(&PlaylistCardData {
+            last_synced: None,
+            unavailable_count: 0,
+            ..playlist(true, false)
+        })
+    );
+
+    for track in tracks() {
+        let name = format!("playlist_track_row_{}", track.ownership_status.as_str());
+        assert_markup_snapshot!(name, playlist_track_row(&track, 7));
+    }
+    assert_markup_snapshot!(
+        "playlist_tracks_rows_more",
+        playlist_tracks_rows(tracks(), true, 7, 4)
+    );
+    assert_markup_snapshot!(
+        "playlist_tracks_rows_last",
+        playlist_tracks_rows(tracks(), false, 7, 4)
+    );
+}
+
+#[test]
+fn test_top_item_rows() {
+    for (i, artist) in top_artists().iter().enumerate() {
+        assert_markup_snapshot!(format!("top_artist_row_{}", i), top_artist_row(artist));
+    }
+    for (i, album) in top_albums().iter().enumerate() {
+        assert_markup_snapshot!(format!("top_album_row_{}", i), top_album_row(album));
+    }
+}
+
+#[test]
+fn test_artist_card() {
+    for (name, percentage) in [("green", 90.0), ("yellow", 50.0), ("gray", 10.0)] {
+        assert_markup_snapshot!(
+            format!("artist_card_{}", name),
+            artist_card(&artist(percentage))
+        );
+    }
+}
+
+#[test]
+fn test_profile_selector() {
+    assert_markup_snapshot!(
+        "profile_selector",
+        profile_selector(&[(1, "Default".to_string()), (2, "Shared".to_string())], 2)
+    );
+}
+
+#[test]
+fn test_board_components() {
+    for status in BOARD_COLUMNS {
+        let card = board_card_for(status);
+        assert_markup_snapshot!(format!("board_card_{}", status.as_str()), board_card(&card));
+        assert_markup_snapshot!(
+            format!("board_card_oob_{}", status.as_str()),
+            board_card_oob(&card)
+        );
+        assert_markup_snapshot!(
+            format!("board_column_count_{}", status.as_str()),
+            html! { (board_column_count(status, 3, false)) (board_column_count(status, 3, true)) }
+        );
+    }
+}
+
+// ============================================================================
+// HTMX wiring
+// ============================================================================
+
+/// Names of the album filter controls, which each send the others along
+const ALBUM_FILTERS: [&str; 8] = [
+    "search",
+    "search_in",
+    "ownership_status",
+    "match_status",
+    "sort_by",
+    "sort_order",
+    "view",
+    "page_size",
+];
+
+#[test]
+fn test_album_filters_include_each_other() {
+    let bar = filter_bar(AlbumView::Grid);
+    for name in ALBUM_FILTERS {
+        let others: Vec<String> = ALBUM_FILTERS
+            .iter()
+            .filter(|other| **other != name)
+            .map(|other| format!("[name='{}']", other))
+            .collect();
+        let include = format!(r#"hx-include="{}""#, others.join(", "));
+        assert_hx(
+            &bar,
+            &format!(r#"name="{}""#, name),
+            &[
+                r#"hx-get="/albums""#,
+                r##"hx-target="#album-grid""##,
+                &include,
+            ],
+        );
+    }
+}
+
+#[test]
+fn test_pagination_keeps_filters() {
+    let links = pagination(
+        &PageInfo {
+            page: 5,
+            page_size: 10,
+            total_items: 200,
+        },
+        &ALBUM_PAGINATION,
+    );
+    assert_hx(
+        &links,
+        r#"rel="prev""#,
+        &[
+            r##"hx-target="#album-grid""##,
+            "[name='search']",
+            "[name='view']",
+        ],
+    );
+
+    let links = pagination(&page_info(9), &ARTIST_PAGINATION);
+    assert_hx(
+        &links,
+        r#"rel="prev""#,
+        &[
+            r#"hx-get="/artists-grid?page=1""#,
+            r##"hx-target="#artist-grid""##,
+        ],
+    );
+}
+
+#[test]
+fn test_pages_load_their_partials() {
+    assert_hx(
+        &jobs_page(),
+        r#"id="jobs-list""#,
+        &[r#"hx-get="/jobs-list""#, r#"hx-trigger="load, every 5s""#],
+    );
+    assert_hx(
+        &artist_filter_bar(),
+        r#"name="search""#,
+        &[r#"hx-get="/artists-grid""#, r##"hx-target="#artist-grid""##],
+    );
+    assert_hx(
+        &playlists_page(),
+        r#"hx-get="/playlists-grid""#,
+        &[r#"hx-trigger="load""#],
+    );
+}
+
+#[test]
+fn test_album_modal_actions_target_the_right_places() {
+    let modal = album_detail_modal(
+        &album(4, OwnershipStatus::NotOwned),
+        "Artist",
+        &None,
+        &AlbumTracksData::default(),
+        &[],
+        None,
+        None,
+    );
+    assert_hx(
+        &modal,
+        r#"hx-post="/api/albums/4/search-lidarr""#,
+        &[r##"hx-target="#notification-area""##],
+    );
+    assert_hx(
+        &modal,
+        r#"hx-post="/api/albums/4/match""#,
+        &[r##"hx-target="#notification-area""##],
+    );
+    assert_hx(&modal, r#"hx-patch="/api/albums/4?card=true""#, &[r#"hx-swap="none""#]);
+    assert_hx(
+        &modal,
+        r#"hx-post="/albums/4/artist""#,
+        &[r##"hx-target="#album-detail-modal""##],
+    );
+    assert_hx(
+        &modal,
+        r#"hx-get="/albums/4/artist-options""#,
+        &[r##"hx-target="#artist-options-4""##],
+    );
+    assert_hx(
+        &modal,
+        r#"hx-post="/albums/4/lidarr-artist""#,
+        &[r##"hx-target="#album-detail-modal""##],
+    );
+    assert_hx(
+        &modal,
+        r#"hx-get="/albums/4/lidarr-artist-preview""#,
+        &[r##"hx-target="#lidarr-artist-preview-4""##],
+    );
+    assert_hx(
+        &modal,
+        r#"hx-post="/albums/4/genres""#,
+        &[r##"hx-target="#album-detail-modal""##],
+    );
+}
+
+#[test]
+fn test_album_card_quick_actions() {
+    let card = album_card(&album(4, OwnershipStatus::NotOwned));
+    assert_hx(
+        &card,
+        r#"hx-patch="/api/albums/4?card=true""#,
+        &[r#"hx-swap="none""#, r#"onclick="event.stopPropagation()""#],
+    );
+    assert_hx(
+        &card,
+        r#"hx-post="/api/albums/4/search-lidarr?card=true""#,
+        &[r##"hx-target="#notification-area""##, r#"onclick="event.stopPropagation()""#],
+    );
+
+    // Only moves the ownership rules allow are offered
+    let downloading = album_card(&album(4, OwnershipStatus::Downloading)).into_string();
+    assert!(downloading.contains("Mark Album 4 owned"));
+    assert!(!downloading.contains("search-lidarr"));
+    let owned = album_card(&album(4, OwnershipStatus::Owned)).into_string();
+    assert!(!owned.contains("album-quick-actions"));
+
+    assert_markup_snapshot!("album_card_oob", album_card_oob(&album(4, OwnershipStatus::Ordered)));
+}
+
+#[test]
+fn test_cards_open_their_modals() {
+    for status in BOARD_COLUMNS {
+        assert_hx(
+            &album_card(&album(4, status)),
+            r#"hx-get="/albums/artist-album-4""#,
+            &[r##"hx-target="#album-detail-modal""##],
+        );
+        assert_hx(
+            &board_card(&board_card_for(status)),
+            r#"hx-get="/albums/artist-album-3""#,
+            &[r##"hx-target="#album-detail-modal""##],
+        );
+    }
+    assert_hx(
+        &playlist_card(&playlist(true, false)),
+        r#"hx-get="/playlists/7""#,
+        &[r##"hx-target="#playlist-detail-modal""##],
+    );
+    assert_hx(
+        &playlist_tracks_rows(tracks(), true, 7, 4),
+        r#"id="load-more-trigger""#,
+        &[r#"hx-trigger="revealed""#, r#"hx-swap="outerHTML""#],
+    );
+}